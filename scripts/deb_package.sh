@@ -2,7 +2,7 @@
 
 NAME="wikijs-rs"
 DESTDIR=/usr/bin
-# DOCDIR=/usr/share/man/
+DOCDIR=/usr/share/man/
 
 TAG=$(git describe --tags --abbrev=0)
 VERSION=${TAG:1}
@@ -29,8 +29,8 @@ for ARCH in "${!TARGETS[@]}"; do
     echo " -> creating directory structure"
     mkdir -p "${DEB_TMP_DIR}"
     mkdir -p "${DEB_TMP_DIR}${DESTDIR}"
-    # mkdir -p "${DEB_TMP_DIR}${DOCDIR}"
-    # mkdir -p "${DEB_TMP_DIR}${DOCDIR}/man1"
+    mkdir -p "${DEB_TMP_DIR}${DOCDIR}"
+    mkdir -p "${DEB_TMP_DIR}${DOCDIR}/man1"
     # mkdir -p "${DEB_TMP_DIR}${DOCDIR}/man5"
     mkdir -p "${DEB_TMP_DIR}/DEBIAN"
     mkdir -p "${DEB_TMP_DIR}/usr/share/doc/${NAME}"
@@ -43,9 +43,12 @@ for ARCH in "${!TARGETS[@]}"; do
     cp "target/${TARGET}/release/wikijs" "${DEB_TMP_DIR}${DESTDIR}/wikijs"
     chmod 755 "${DEB_TMP_DIR}${DESTDIR}/wikijs"
 
-    # echo " -> compress man pages"
-    # gzip -cn9 target/man/wikijs.1 > "${DEB_TMP_DIR}${DOCDIR}man1/wikijs.1.gz"
-    # chmod 644 "${DEB_TMP_DIR}${DOCDIR}"/**/*.gz
+    echo " -> compress man pages"
+    "target/${TARGET}/release/wikijs" man --output-dir target/man > /dev/null
+    for PAGE in target/man/*.1; do
+        gzip -cn9 "${PAGE}" > "${DEB_TMP_DIR}${DOCDIR}man1/$(basename "${PAGE}").gz"
+    done
+    chmod 644 "${DEB_TMP_DIR}${DOCDIR}"man1/*.gz
 
     # echo " -> copy completions"
     # cp completions/bash/wikijs "${DEB_TMP_DIR}/usr/share/bash-completion/completions/"
@@ -1,26 +1,78 @@
-use crate::common::Execute;
-use clap::Subcommand;
+use crate::common::{
+    is_dry_run, parse_config_option, print_dry_run, settings_diff, Execute,
+};
+use crate::output::{print_serialized, Table};
+use clap::{ArgAction, Subcommand};
+use colored::Colorize;
 use std::error::Error;
-use tabled::{builder::Builder, settings::Style};
+use wikijs::analytics::AnalyticsProviderInput;
 
 #[derive(Subcommand, Debug)]
 pub(crate) enum AnalyticsProviderCommand {
     #[clap(about = "List analytics providers")]
     List {},
+
+    #[clap(about = "Enable an analytics provider")]
+    Enable {
+        #[clap(help = "Provider key, like google")]
+        key: String,
+    },
+
+    #[clap(about = "Disable an analytics provider")]
+    Disable {
+        #[clap(help = "Provider key, like google")]
+        key: String,
+    },
+
+    #[clap(about = "Change the config of an analytics provider")]
+    Configure {
+        #[clap(help = "Provider key, like google")]
+        key: String,
+
+        #[clap(
+            short,
+            long = "config",
+            help = "Config option as key=value, the value may be JSON",
+            value_parser = parse_config_option,
+            action = ArgAction::Append,
+            required = true
+        )]
+        config: Vec<(String, serde_json::Value)>,
+    },
 }
 
 impl Execute for AnalyticsProviderCommand {
     fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
         match self {
             AnalyticsProviderCommand::List {} => analytics_provider_list(api),
+            AnalyticsProviderCommand::Enable { key } => {
+                analytics_provider_edit(api, key, |input| {
+                    input.is_enabled = true
+                })
+            }
+            AnalyticsProviderCommand::Disable { key } => {
+                analytics_provider_edit(api, key, |input| {
+                    input.is_enabled = false
+                })
+            }
+            AnalyticsProviderCommand::Configure { key, config } => {
+                analytics_provider_edit(api, key, |input| {
+                    for (option, value) in config {
+                        input.set_config(option, value.clone());
+                    }
+                })
+            }
         }
     }
 }
 
 fn analytics_provider_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
     let providers = api.analytics_provider_list()?;
-    let mut builder = Builder::new();
-    builder.push_record([
+    if print_serialized(&providers)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record([
         "is_enabled",
         "key",
         // "props",
@@ -32,7 +84,7 @@ fn analytics_provider_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
         // "config",
     ]);
     for provider in providers {
-        builder.push_record([
+        table.push_record([
             provider.is_enabled.to_string().as_str(),
             provider.key.as_str(),
             // provider.props.as_str(),
@@ -44,6 +96,32 @@ fn analytics_provider_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
             // provider.config.as_str(),
         ]);
     }
-    println!("{}", builder.build().with(Style::rounded()));
+    table.print()?;
+    Ok(())
+}
+
+// change a single provider, keeping the settings of all others
+fn analytics_provider_edit(
+    api: wikijs::Api,
+    key: &str,
+    edit: impl FnOnce(&mut AnalyticsProviderInput),
+) -> Result<(), Box<dyn Error>> {
+    let mut input = api
+        .analytics_provider_list()?
+        .into_iter()
+        .find(|provider| provider.key == key)
+        .ok_or(format!("unknown analytics provider {}", key))?
+        .to_input();
+    let old = input.clone();
+    edit(&mut input);
+    if is_dry_run() {
+        return print_dry_run(format!(
+            "update analytics provider {}: {}",
+            key,
+            settings_diff(&old, &input)
+        ));
+    }
+    api.analytics_provider_update(vec![input])?;
+    info!("{}: Analytics provider updated", "success".bold().green());
     Ok(())
 }
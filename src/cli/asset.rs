@@ -1,70 +1,122 @@
-use crate::common::Execute;
+use crate::common::{confirm, is_dry_run, print_dry_run, Execute, PagingArgs};
+use crate::output::{print_serialized, Table};
 use clap::Subcommand;
-use colored::Colorize;
+use colored::{ColoredString, Colorize};
+use std::collections::HashMap;
 use std::error::Error;
-use std::fs::File;
-use std::io::Write;
-use tabled::{builder::Builder, settings::Style};
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use wikijs::asset::AssetKind;
 
 #[derive(Subcommand, Debug)]
 pub(crate) enum AssetCommand {
     #[clap(about = "List assets")]
     List {
-        #[clap(help = "Parent folder ID")]
-        folder_id: i64,
+        #[clap(help = "Folder ID or path")]
+        folder: String,
+
+        #[clap(flatten)]
+        paging: PagingArgs,
     },
 
-    #[clap(about = "Download an asset")]
+    #[clap(about = "Download an asset or a folder with its subfolders")]
     Download {
-        #[clap(help = "Source path in wiki")]
+        #[clap(help = "Source asset or folder path in wiki")]
         source: String,
 
-        #[clap(help = "Destination path on disk")]
-        destination: Option<String>,
+        #[clap(help = "Destination file or directory on disk")]
+        destination: Option<PathBuf>,
     },
 
-    #[clap(about = "Upload an asset")]
+    #[clap(about = "Upload a file or a directory with its subdirectories")]
     Upload {
-        #[clap(help = "Source path on disk")]
-        source: String,
+        #[clap(help = "Source file or directory on disk")]
+        source: PathBuf,
+
+        #[clap(
+            short,
+            long,
+            help = "Destination folder ID or path, created if missing, the \
+                    root by default",
+            default_value = "",
+            hide_default_value = true
+        )]
+        folder: String,
+
+        #[clap(short, long, help = "Destination name in wiki, for a file")]
+        name: Option<String>,
+    },
 
-        #[clap(help = "Destination folder ID")]
-        folder: i64,
+    #[clap(
+        about = "Upload the files of a directory missing or changed in a folder"
+    )]
+    Sync {
+        #[clap(help = "Source directory on disk")]
+        dir: PathBuf,
+
+        #[clap(help = "Destination folder ID or path, created if missing")]
+        folder: String,
+    },
+
+    #[clap(about = "Move an asset to another folder")]
+    Move {
+        #[clap(help = "Asset ID")]
+        id: i64,
+
+        #[clap(help = "Destination folder ID or path")]
+        folder: String,
+    },
 
-        #[clap(help = "Destination name in wiki")]
-        name: String,
+    #[clap(about = "Delete an asset")]
+    Delete {
+        #[clap(help = "Asset ID")]
+        id: i64,
     },
+
+    #[clap(about = "List assets not referenced by any page")]
+    Orphans {},
 }
 
 #[derive(Subcommand, Debug)]
 pub(crate) enum AssetFolderCommand {
     #[clap(about = "List asset folders")]
     List {
-        #[clap(help = "Parent folder ID")]
-        parent_folder_id: i64,
+        #[clap(help = "Parent folder ID or path")]
+        parent_folder: String,
     },
 }
 
 impl Execute for AssetCommand {
     fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
         match self {
-            AssetCommand::List { folder_id } => {
-                asset_list(api, folder_id.to_owned())
+            AssetCommand::List { folder, paging } => {
+                let folder_id = resolve_folder(&api, folder)?;
+                asset_list(api, folder_id, paging)
             }
             AssetCommand::Download {
                 source,
                 destination,
-            } => asset_download(api, source.to_owned(), destination.to_owned()),
+            } => asset_download(api, source, destination.as_deref()),
             AssetCommand::Upload {
                 source,
                 folder,
                 name,
-            } => asset_upload(
-                api,
-                source.to_owned(),
-                folder.to_owned(),
-                name.to_owned(),
-            ),
+            } => asset_upload(api, source, folder, name.as_deref(), false),
+            AssetCommand::Sync { dir, folder } => {
+                if !dir.is_dir() {
+                    return Err(
+                        format!("not a directory: {}", dir.display()).into()
+                    );
+                }
+                asset_upload(api, dir, folder, None, true)
+            }
+            AssetCommand::Move { id, folder } => {
+                let folder_id = resolve_folder(&api, folder)?;
+                asset_move(api, *id, folder_id)
+            }
+            AssetCommand::Delete { id } => asset_delete(api, *id),
+            AssetCommand::Orphans {} => asset_orphans(api),
         }
     }
 }
@@ -72,17 +124,39 @@ impl Execute for AssetCommand {
 impl Execute for AssetFolderCommand {
     fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
         match self {
-            AssetFolderCommand::List { parent_folder_id } => {
-                asset_folder_list(api, *parent_folder_id)
+            AssetFolderCommand::List { parent_folder } => {
+                let parent_folder_id = resolve_folder(&api, parent_folder)?;
+                asset_folder_list(api, parent_folder_id)
             }
         }
     }
 }
 
-fn asset_list(api: wikijs::Api, folder_id: i64) -> Result<(), Box<dyn Error>> {
-    let assets = api.asset_list(folder_id, wikijs::asset::AssetKind::ALL)?;
-    let mut builder = Builder::new();
-    builder.push_record([
+// folders can be given by id or by their slug path like images/screenshots
+fn resolve_folder(
+    api: &wikijs::Api,
+    folder: &str,
+) -> Result<i64, Box<dyn Error>> {
+    if let Ok(id) = folder.parse::<i64>() {
+        return Ok(id);
+    }
+    api.asset_folder_tree()?
+        .resolve(folder)
+        .ok_or_else(|| format!("asset folder not found: {}", folder).into())
+}
+
+fn asset_list(
+    api: wikijs::Api,
+    folder_id: i64,
+    paging: &PagingArgs,
+) -> Result<(), Box<dyn Error>> {
+    let assets = paging
+        .apply(api.asset_list(folder_id, wikijs::asset::AssetKind::ALL)?)?;
+    if print_serialized(&assets)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record([
         "id",
         "filename",
         "ext",
@@ -96,7 +170,7 @@ fn asset_list(api: wikijs::Api, folder_id: i64) -> Result<(), Box<dyn Error>> {
         // "author",
     ]);
     for asset in assets {
-        builder.push_record([
+        table.push_record([
             asset.id.to_string().as_str(),
             asset.filename.as_str(),
             asset.ext.as_str(),
@@ -111,7 +185,57 @@ fn asset_list(api: wikijs::Api, folder_id: i64) -> Result<(), Box<dyn Error>> {
             // asset.author.unwrap_or(0).to_string().as_str(),
         ]);
     }
-    println!("{}", builder.build().with(Style::rounded()));
+    table.print()?;
+    Ok(())
+}
+
+fn asset_move(
+    api: wikijs::Api,
+    id: i64,
+    folder_id: i64,
+) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        let tree = api.asset_folder_tree()?;
+        return print_dry_run(format!(
+            "move asset {} to folder {}",
+            id,
+            tree.path(folder_id)
+                .map(|path| format!("/{}", path))
+                .unwrap_or(folder_id.to_string())
+        ));
+    }
+    let id = api.asset_move(id, folder_id)?;
+    info!("{}: asset moved, new ID: {}", "success".bold().green(), id);
+    Ok(())
+}
+
+fn asset_delete(api: wikijs::Api, id: i64) -> Result<(), Box<dyn Error>> {
+    let action = format!("delete asset {}", id);
+    if is_dry_run() {
+        return print_dry_run(action);
+    }
+    confirm(action)?;
+    api.asset_delete(id)?;
+    info!("{}: asset deleted", "success".bold().green());
+    Ok(())
+}
+
+fn asset_orphans(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+    let report = api.asset_orphan_report(None)?;
+    if print_serialized(&report.orphans())? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["id", "path", "file_size", "updated_at"]);
+    for asset in report.orphans() {
+        table.push_record([
+            asset.asset.id.to_string().as_str(),
+            asset.path().as_str(),
+            asset.asset.file_size.to_string().as_str(),
+            asset.asset.updated_at.to_string().as_str(),
+        ]);
+    }
+    table.print()?;
     Ok(())
 }
 
@@ -120,39 +244,278 @@ fn asset_folder_list(
     parent_folder_id: i64,
 ) -> Result<(), Box<dyn Error>> {
     let asset_folders = api.asset_folder_list(parent_folder_id)?;
-    let mut builder = Builder::new();
-    builder.push_record(["id", "slug", "name"]);
+    if print_serialized(&asset_folders)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["id", "slug", "name"]);
     for asset_folder in asset_folders {
-        builder.push_record([
+        table.push_record([
             asset_folder.id.to_string().as_str(),
             asset_folder.slug.as_str(),
             asset_folder.name.unwrap_or("".to_string()).as_str(),
         ]);
     }
-    println!("{}", builder.build().with(Style::rounded()));
+    table.print()?;
     Ok(())
 }
 
+// progress of a transfer, drawn as a bar on a terminal and as one line per
+// file otherwise
+struct Progress {
+    total: usize,
+    done: usize,
+    terminal: bool,
+}
+
+impl Progress {
+    fn new(total: usize) -> Self {
+        Self {
+            total,
+            done: 0,
+            terminal: std::io::stderr().is_terminal(),
+        }
+    }
+
+    fn step(&mut self, name: &str, status: ColoredString) {
+        self.done += 1;
+        if !self.terminal {
+            info!("[{}/{}] {}: {}", self.done, self.total, name, status);
+            return;
+        }
+        let width = 30;
+        let filled = width * self.done / self.total.max(1);
+        progress!(
+            "\r\x1b[K[{}{}] {}/{} {}",
+            "#".repeat(filled),
+            "-".repeat(width - filled),
+            self.done,
+            self.total,
+            name
+        );
+        if self.done == self.total {
+            progress!("\n");
+        }
+    }
+
+    // the lines printed without a terminal already show the error
+    fn warn(&self, message: String) {
+        if self.terminal {
+            eprintln!("\r\x1b[K{}: {}", "warning".bold().yellow(), message);
+        }
+    }
+}
+
 fn asset_download(
     api: wikijs::Api,
-    source: String,
-    destination: Option<String>,
+    source: &str,
+    destination: Option<&Path>,
+) -> Result<(), Box<dyn Error>> {
+    let source = source.trim_matches('/');
+    let tree = api.asset_folder_tree()?;
+    let Some(folder_id) = tree.resolve(source).filter(|_| !source.is_empty())
+    else {
+        let destination = match destination {
+            Some(path) if path.is_dir() => {
+                path.join(source.rsplit('/').next().unwrap_or(source))
+            }
+            Some(path) => path.to_path_buf(),
+            None => PathBuf::from(source.rsplit('/').next().unwrap_or(source)),
+        };
+        fs::write(&destination, api.asset_download(source.to_string())?)?;
+        info!("{}: asset downloaded", "success".bold().green());
+        return Ok(());
+    };
+    let destination = destination.map(Path::to_path_buf).unwrap_or_else(|| {
+        PathBuf::from(source.rsplit('/').next().unwrap_or(source))
+    });
+    let assets = api.asset_list_recursive(folder_id, AssetKind::ALL)?;
+    let mut progress = Progress::new(assets.len());
+    let mut failed = 0;
+    for item in &assets {
+        let file = destination
+            .join(&item.folder_path)
+            .join(&item.asset.filename);
+        let result = fs::create_dir_all(file.parent().unwrap_or(&destination))
+            .map_err(|e| e.to_string())
+            .and_then(|_| {
+                api.asset_download(format!("{}/{}", source, item.path()))
+                    .map_err(|e| e.to_string())
+            })
+            .and_then(|data| fs::write(&file, data).map_err(|e| e.to_string()));
+        if let Err(e) = &result {
+            failed += 1;
+            progress.warn(format!("{}: {}", item.path(), e));
+        }
+        progress.step(&item.path(), status(&result, "downloaded"));
+    }
+    if failed > 0 {
+        return Err(format!("{} assets could not be downloaded", failed).into());
+    }
+    info!(
+        "{}: downloaded {} assets",
+        "success".bold().green(),
+        assets.len()
+    );
+    Ok(())
+}
+
+fn status(result: &Result<(), String>, done: &str) -> ColoredString {
+    match result {
+        Ok(()) => done.green(),
+        Err(e) => e.red(),
+    }
+}
+
+// files below the directory as slash separated relative folder path and
+// file, hidden ones are left out
+fn scan_dir(
+    root: &Path,
+    dir: &Path,
+    files: &mut Vec<(String, PathBuf)>,
 ) -> Result<(), Box<dyn Error>> {
-    let asset = api.asset_download(source.clone())?;
-    let mut file = File::create(destination.unwrap_or(source)).unwrap();
-    file.write_all(&asset).unwrap();
-    println!("{}: asset downloaded", "success".bold().green());
+    let mut entries = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            scan_dir(root, &path, files)?;
+        } else if entry.file_type()?.is_file() {
+            let relative = dir.strip_prefix(root).unwrap_or(dir);
+            let folder = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push((folder, path));
+        }
+    }
     Ok(())
 }
 
 fn asset_upload(
     api: wikijs::Api,
-    source: String,
-    folder: i64,
-    name: String,
+    source: &Path,
+    folder: &str,
+    name: Option<&str>,
+    skip_existing: bool,
 ) -> Result<(), Box<dyn Error>> {
-    let data = std::fs::read(source)?;
-    api.asset_upload(folder, name, data)?;
-    println!("{}: asset uploaded", "success".bold().green());
+    let mut tree = api.asset_folder_tree()?;
+    let root = match folder.parse::<i64>() {
+        Ok(id) => tree
+            .path(id)
+            .ok_or(format!("asset folder not found: {}", folder))?,
+        Err(_) => folder.trim_matches('/').to_string(),
+    };
+    let mut files = Vec::new();
+    if source.is_dir() {
+        if name.is_some() {
+            return Err("a name can only be given for a file".into());
+        }
+        scan_dir(source, source, &mut files)?;
+    } else {
+        files.push((String::new(), source.to_path_buf()));
+    }
+    // the assets already in the folders, by folder path and file name
+    let mut existing = HashMap::new();
+    if skip_existing {
+        if let Some(id) = tree.resolve(&root) {
+            for item in api.asset_list_recursive(id, AssetKind::ALL)? {
+                existing.insert(
+                    (item.folder_path, item.asset.filename),
+                    item.asset.file_size,
+                );
+            }
+        }
+    }
+    let mut progress = Progress::new(files.len());
+    let (mut uploaded, mut skipped, mut failed) = (0, 0, 0);
+    for (relative, file) in &files {
+        let filename = match name {
+            Some(name) => name.to_string(),
+            None => file
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        };
+        let display = match relative.is_empty() {
+            true => filename.clone(),
+            false => format!("{}/{}", relative, filename),
+        };
+        let size = fs::metadata(file).map(|m| m.len() as i64).ok();
+        let key = (relative.clone(), filename.clone());
+        let unchanged = existing.get(&key).is_some_and(|s| Some(*s) == size);
+        let path = [root.as_str(), relative.as_str()]
+            .iter()
+            .filter(|p| !p.is_empty())
+            .copied()
+            .collect::<Vec<_>>()
+            .join("/");
+        if is_dry_run() {
+            match unchanged {
+                true => skipped += 1,
+                false => {
+                    uploaded += 1;
+                    print_dry_run(format!(
+                        "upload {} to {}",
+                        file.display(),
+                        [path.as_str(), filename.as_str()]
+                            .iter()
+                            .filter(|p| !p.is_empty())
+                            .copied()
+                            .collect::<Vec<_>>()
+                            .join("/")
+                    ))?;
+                }
+            }
+            continue;
+        }
+        if unchanged {
+            skipped += 1;
+            progress.step(&display, "skipped".yellow());
+            continue;
+        }
+        let result = api
+            .asset_folder_create_all(&mut tree, &path)
+            .map_err(|e| e.to_string())
+            .and_then(|folder_id| {
+                let data = fs::read(file).map_err(|e| e.to_string())?;
+                api.asset_upload(folder_id, filename, data)
+                    .map_err(|e| e.to_string())
+            });
+        match &result {
+            Ok(()) => uploaded += 1,
+            Err(e) => {
+                failed += 1;
+                progress.warn(format!("{}: {}", display, e));
+            }
+        }
+        progress.step(&display, status(&result, "uploaded"));
+    }
+    if failed > 0 {
+        return Err(format!(
+            "{} assets could not be uploaded, {} uploaded, {} skipped",
+            failed, uploaded, skipped
+        )
+        .into());
+    }
+    if is_dry_run() {
+        info!(
+            "{}: would upload {} assets, skip {}",
+            "dry run".bold().cyan(),
+            uploaded,
+            skipped
+        );
+        return Ok(());
+    }
+    info!(
+        "{}: uploaded {} assets, skipped {}",
+        "success".bold().green(),
+        uploaded,
+        skipped
+    );
     Ok(())
 }
@@ -1,7 +1,10 @@
-use crate::common::Execute;
-use clap::Subcommand;
+use crate::common::{is_dry_run, parse_duration, print_dry_run, Execute};
+use crate::output::{print_serialized, Table};
+use clap::{ArgAction, Subcommand};
+use colored::Colorize;
 use std::error::Error;
-use tabled::{builder::Builder, settings::Style};
+use std::io::Write;
+use std::process::{Command, Stdio};
 
 #[derive(Subcommand, Debug)]
 pub(crate) enum AuthenticationStrategyCommand {
@@ -23,8 +26,11 @@ fn authentication_strategy_list(
     api: wikijs::Api,
 ) -> Result<(), Box<dyn Error>> {
     let providers = api.authentication_strategy_list()?;
-    let mut builder = Builder::new();
-    builder.push_record([
+    if print_serialized(&providers)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record([
         "key",
         // "props",
         "title",
@@ -38,7 +44,7 @@ fn authentication_strategy_list(
         // "icon",
     ]);
     for provider in providers {
-        builder.push_record([
+        table.push_record([
             provider.key.as_str(),
             // provider.props.as_str(),
             provider.title.as_str(),
@@ -56,6 +62,326 @@ fn authentication_strategy_list(
             // provider.icon.as_str(),
         ]);
     }
-    println!("{}", builder.build().with(Style::rounded()));
+    table.print()?;
+    Ok(())
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum AuthCommand {
+    #[clap(about = "Show or change whether the API is enabled")]
+    ApiState {
+        #[clap(subcommand)]
+        command: ApiStateCommand,
+    },
+
+    #[clap(about = "Reset the guest user to its default permissions")]
+    GuestReset {},
+
+    #[clap(about = "Regenerate the certificates used to sign sessions")]
+    CertRegenerate {},
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum ApiStateCommand {
+    #[clap(about = "Show whether the API is enabled")]
+    Get {},
+
+    #[clap(about = "Enable or disable the API")]
+    Set {
+        #[clap(help = "Whether the API is enabled", action = ArgAction::Set)]
+        enabled: bool,
+    },
+}
+
+impl Execute for AuthCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        match self {
+            AuthCommand::ApiState {
+                command: ApiStateCommand::Get {},
+            } => {
+                let enabled = api.api_state_get()?;
+                if print_serialized(&serde_json::json!({ "enabled": enabled }))?
+                {
+                    return Ok(());
+                }
+                println!("{}", if enabled { "enabled" } else { "disabled" });
+                Ok(())
+            }
+            AuthCommand::ApiState {
+                command: ApiStateCommand::Set { enabled },
+            } => {
+                if is_dry_run() {
+                    return print_dry_run(if *enabled {
+                        "enable the API"
+                    } else {
+                        "disable the API"
+                    });
+                }
+                api.api_state_set(*enabled)?;
+                info!(
+                    "{}: API {}",
+                    "success".bold().green(),
+                    if *enabled { "enabled" } else { "disabled" }
+                );
+                Ok(())
+            }
+            AuthCommand::GuestReset {} => {
+                if is_dry_run() {
+                    return print_dry_run("reset the guest user");
+                }
+                api.guest_user_reset()?;
+                info!("{}: Guest user reset", "success".bold().green());
+                Ok(())
+            }
+            AuthCommand::CertRegenerate {} => {
+                if is_dry_run() {
+                    return print_dry_run(
+                        "regenerate the certificates, logging out all users",
+                    );
+                }
+                api.authentication_certificate_regenerate()?;
+                info!(
+                    "{}: Certificates regenerated, all users have to log in \
+                     again",
+                    "success".bold().green()
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum ApiKeyCommand {
+    #[clap(about = "List API keys")]
+    List {},
+
+    #[clap(about = "Create an API key and print it once")]
+    Create {
+        #[clap(help = "Name of the key")]
+        name: String,
+
+        #[clap(
+            short,
+            long,
+            help = "Time until the key expires, like 30d or 1y",
+            default_value = "1y"
+        )]
+        expiration: String,
+
+        #[clap(
+            short,
+            long,
+            help = "Give the key full access",
+            conflicts_with = "group"
+        )]
+        full_access: bool,
+
+        #[clap(short, long, help = "Group whose permissions the key gets")]
+        group: Option<i64>,
+
+        #[clap(short, long, help = "Copy the key to the clipboard")]
+        clipboard: bool,
+    },
+
+    #[clap(about = "Revoke an API key")]
+    Revoke {
+        #[clap(help = "API key ID")]
+        id: i64,
+    },
+
+    #[clap(
+        about = "List revoked and expiring API keys, fails if a key that is \
+                 not revoked expires within the window"
+    )]
+    Audit {
+        #[clap(
+            short,
+            long,
+            help = "Window to check, like 30d or 12w",
+            value_parser = parse_duration,
+            default_value = "30d"
+        )]
+        within: chrono::Duration,
+    },
+}
+
+impl Execute for ApiKeyCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        match self {
+            ApiKeyCommand::List {} => api_key_list(api),
+            ApiKeyCommand::Create {
+                name,
+                expiration,
+                full_access,
+                group,
+                clipboard,
+            } => api_key_create(
+                api,
+                name.to_string(),
+                expiration.to_string(),
+                *full_access,
+                *group,
+                *clipboard,
+            ),
+            ApiKeyCommand::Audit { within } => api_key_audit(api, *within),
+            ApiKeyCommand::Revoke { id } => {
+                if is_dry_run() {
+                    let key = api
+                        .api_key_list()?
+                        .into_iter()
+                        .find(|key| key.id == *id)
+                        .ok_or(format!("API key {} not found", id))?;
+                    return print_dry_run(format!(
+                        "revoke API key {} ({})",
+                        id, key.name
+                    ));
+                }
+                api.api_key_revoke(*id)?;
+                info!("{}: API key revoked", "success".bold().green());
+                Ok(())
+            }
+        }
+    }
+}
+
+fn api_key_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+    let keys = api.api_key_list()?;
+    if print_serialized(&keys)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record([
+        "id",
+        "name",
+        "key_short",
+        "expiration",
+        "created_at",
+        "is_revoked",
+    ]);
+    for key in keys {
+        table.push_record([
+            key.id.to_string(),
+            key.name,
+            key.key_short,
+            key.expiration,
+            key.created_at,
+            key.is_revoked.to_string(),
+        ]);
+    }
+    table.print()?;
+    Ok(())
+}
+
+fn api_key_audit(
+    api: wikijs::Api,
+    within: chrono::Duration,
+) -> Result<(), Box<dyn Error>> {
+    let keys = api.api_key_expiring(within.to_std()?)?;
+    let now = chrono::Utc::now();
+    let serialized = print_serialized(&keys)?;
+    let mut table = Table::new();
+    table.push_record(["id", "name", "key_short", "expiration", "status"]);
+    for key in keys.iter().filter(|_| !serialized) {
+        let status = if key.is_revoked {
+            "revoked".normal()
+        } else if key.expires_before(now) {
+            "expired".red()
+        } else {
+            "expiring".yellow()
+        };
+        table.push_record([
+            key.id.to_string(),
+            key.name.clone(),
+            key.key_short.clone(),
+            key.expiration.clone(),
+            status.to_string(),
+        ]);
+    }
+    if !serialized {
+        table.print()?;
+    }
+    let expiring = keys.iter().filter(|key| !key.is_revoked).count();
+    if expiring > 0 {
+        return Err(format!(
+            "{} API keys expire within {} days",
+            expiring,
+            within.num_days()
+        )
+        .into());
+    }
+    if !serialized {
+        info!("{}: no API key expires soon", "success".bold().green());
+    }
+    Ok(())
+}
+
+// hand the text to the first clipboard tool that is installed
+fn copy_to_clipboard(text: &str) -> Result<(), Box<dyn Error>> {
+    let tools: [&[&str]; 5] = [
+        &["wl-copy"],
+        &["xclip", "-selection", "clipboard"],
+        &["xsel", "--clipboard", "--input"],
+        &["pbcopy"],
+        &["clip.exe"],
+    ];
+    for tool in tools {
+        let Ok(mut child) = Command::new(tool[0])
+            .args(&tool[1..])
+            .stdin(Stdio::piped())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+    Err("no clipboard tool found (wl-copy, xclip, xsel, pbcopy)".into())
+}
+
+fn api_key_create(
+    api: wikijs::Api,
+    name: String,
+    expiration: String,
+    full_access: bool,
+    group: Option<i64>,
+    clipboard: bool,
+) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        let mut action =
+            format!("create API key {} expiring in {}", name, expiration);
+        if full_access {
+            action.push_str(" with full access");
+        }
+        if let Some(group) = group {
+            action.push_str(&format!(" for group {}", group));
+        }
+        return print_dry_run(action);
+    }
+    let key = api.api_key_create(name, Some(expiration), full_access, group)?;
+    // the key is lost if it cannot be copied, so print it instead
+    match clipboard.then(|| copy_to_clipboard(&key)) {
+        Some(Ok(())) => info!(
+            "{}: API key copied to the clipboard",
+            "success".bold().green()
+        ),
+        Some(Err(e)) => {
+            eprintln!(
+                "{}: failed to copy the API key to the clipboard: {}",
+                "warning".bold().yellow(),
+                e
+            );
+            println!("{}", key);
+        }
+        None => println!("{}", key),
+    }
+    eprintln!(
+        "{}: the key cannot be shown again, store it now",
+        "warning".bold().yellow()
+    );
     Ok(())
 }
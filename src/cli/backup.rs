@@ -0,0 +1,105 @@
+use crate::common::{is_dry_run, Execute};
+use crate::output::{print_serialized, Table};
+use clap::Args;
+use colored::Colorize;
+use std::error::Error;
+use std::path::PathBuf;
+use wikijs::backup::{BackupOptions, RestoreOptions};
+
+#[derive(Args, Debug)]
+pub(crate) struct BackupCommand {
+    #[clap(help = "Archive to write, .zip or .tar.gz")]
+    file: PathBuf,
+
+    #[clap(
+        short,
+        long,
+        help = "Only back up changes since this date, e.g. the \
+                newest_updated_at of the previous backup"
+    )]
+    since: Option<String>,
+
+    #[clap(long, help = "Do not back up assets")]
+    skip_assets: bool,
+}
+
+impl Execute for BackupCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        let manifest = api.backup_create(
+            &self.file,
+            &BackupOptions {
+                since: self.since.to_owned(),
+                skip_assets: self.skip_assets,
+            },
+            None,
+        )?;
+        info!(
+            "{}: backed up {} pages and {} assets to {}",
+            "success".bold().green(),
+            manifest.pages,
+            manifest.assets,
+            self.file.display()
+        );
+        if let Some(newest) = manifest.newest_updated_at {
+            info!("next incremental backup: --since {}", newest);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct RestoreCommand {
+    #[clap(help = "Archive to restore, .zip or .tar.gz")]
+    file: PathBuf,
+
+    #[clap(long, help = "Do not restore assets")]
+    skip_assets: bool,
+
+    #[clap(short, long, help = "Only print what would be restored")]
+    dry_run: bool,
+}
+
+impl Execute for RestoreCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        let dry_run = self.dry_run || is_dry_run();
+        let (_, result) = api.backup_restore(
+            &self.file,
+            &RestoreOptions {
+                dry_run,
+                skip_assets: self.skip_assets,
+            },
+            None,
+        )?;
+        let rows: Vec<(String, String, String)> = result
+            .into_iter()
+            .map(|(item, outcome)| {
+                let status = match outcome {
+                    Ok(()) if dry_run => "planned".to_string(),
+                    Ok(()) => "ok".to_string(),
+                    Err(e) => e.to_string(),
+                };
+                (item.file, item.action.to_string(), status)
+            })
+            .collect();
+        let records: Vec<_> = rows
+            .iter()
+            .map(|(file, action, status)| {
+                serde_json::json!({
+                    "file": file,
+                    "action": action,
+                    "status": status,
+                })
+            })
+            .collect();
+        if print_serialized(&records)? {
+            return Ok(());
+        }
+        let mut table = Table::new();
+        table.push_record(["file", "action", "status"]);
+        for (file, action, status) in rows {
+            table.push_record([file, action, status]);
+        }
+        table.print()?;
+        Ok(())
+    }
+}
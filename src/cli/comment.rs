@@ -1,25 +1,141 @@
-use crate::common::Execute;
+use crate::common::{confirm, is_dry_run, print_dry_run, Execute, PagingArgs};
+use crate::output::{print_serialized, Table};
+use chrono::{DateTime, Utc};
 use clap::Subcommand;
+use colored::Colorize;
 use std::error::Error;
-use tabled::{builder::Builder, settings::Style};
+use wikijs::comment::CommentTree;
 
 #[derive(Subcommand, Debug)]
 pub(crate) enum CommentCommand {
     #[clap(about = "List comments")]
     List {
-        #[clap(short, long, help = "Page locale", default_value = "en")]
+        #[clap(
+            short,
+            long,
+            help = "Page locale",
+            default_value = "en",
+            env = "WIKI_JS_LOCALE"
+        )]
         locale: String,
 
-        #[clap(help = "Page path")]
-        path: String,
+        #[clap(short, long, help = "Page path")]
+        page: String,
+
+        #[clap(
+            short,
+            long,
+            help = "Only comments created since this RFC 3339 date, \
+                    e.g. 2024-05-01T08:00:00Z"
+        )]
+        since: Option<DateTime<Utc>>,
+
+        #[clap(
+            short,
+            long,
+            help = "Show replies threaded below their comment"
+        )]
+        tree: bool,
+
+        #[clap(flatten)]
+        paging: PagingArgs,
+    },
+
+    #[clap(about = "Delete all comments of a page")]
+    DeleteAll {
+        #[clap(
+            short,
+            long,
+            help = "Page locale",
+            default_value = "en",
+            env = "WIKI_JS_LOCALE"
+        )]
+        locale: String,
+
+        #[clap(short, long, help = "Page path")]
+        page: String,
+    },
+
+    #[clap(about = "Reply to a comment")]
+    Reply {
+        #[clap(help = "ID of the comment to reply to")]
+        id: i64,
+
+        #[clap(help = "Content of the reply")]
+        content: String,
+
+        #[clap(
+            short,
+            long,
+            help = "Page locale",
+            default_value = "en",
+            env = "WIKI_JS_LOCALE"
+        )]
+        locale: String,
+
+        #[clap(short, long, help = "Path of the page the comment belongs to")]
+        page: String,
     },
 }
 
 impl Execute for CommentCommand {
     fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
         match self {
-            CommentCommand::List { locale, path } => {
-                comment_list(api, locale.to_string(), path.to_string())
+            CommentCommand::List {
+                locale,
+                page,
+                since,
+                tree,
+                paging,
+            } => comment_list(
+                api,
+                locale.to_string(),
+                page.to_string(),
+                *since,
+                *tree,
+                paging,
+            ),
+            CommentCommand::DeleteAll { locale, page } => {
+                let comments =
+                    api.comment_list(locale.to_string(), page.to_string())?;
+                let action = format!(
+                    "delete {} comments of page {}/{}",
+                    comments.len(),
+                    locale,
+                    page
+                );
+                if is_dry_run() {
+                    return print_dry_run(action);
+                }
+                confirm(action)?;
+                let count = api.comment_delete_all_for_page(
+                    locale.to_string(),
+                    page.to_string(),
+                )?;
+                info!(
+                    "{}: {} comments deleted",
+                    "success".bold().green(),
+                    count
+                );
+                Ok(())
+            }
+            CommentCommand::Reply {
+                id,
+                content,
+                locale,
+                page,
+            } => {
+                let page =
+                    api.page_get_by_path(page.to_string(), locale.to_string())?;
+                if is_dry_run() {
+                    return print_dry_run(format!(
+                        "reply to comment {} on page {} ({}/{})",
+                        id, page.id, page.locale, page.path
+                    ));
+                }
+                api.comment_reply(page.id, *id, content.to_string())?;
+                info!("{}: Reply created", "success".bold().green());
+                Ok(())
             }
         }
     }
@@ -29,13 +145,52 @@ fn comment_list(
     api: wikijs::Api,
     locale: String,
     path: String,
+    since: Option<DateTime<Utc>>,
+    tree: bool,
+    paging: &PagingArgs,
 ) -> Result<(), Box<dyn Error>> {
-    let comments = api.comment_list(locale, path)?;
-    let mut builder = Builder::new();
-    builder.push_record([
+    let comments: Vec<_> = api
+        .comment_list(locale, path)?
+        .into_iter()
+        .filter(|comment| {
+            since.is_none_or(|since| {
+                DateTime::parse_from_rfc3339(&comment.created_at)
+                    .is_ok_and(|created_at| created_at >= since)
+            })
+        })
+        .collect();
+    let comments = paging.apply(comments)?;
+    if tree {
+        let threads = CommentTree::build(comments);
+        if print_serialized(&threads)? {
+            return Ok(());
+        }
+        for thread in threads {
+            for (depth, comment) in thread.flatten() {
+                let indent = "  ".repeat(depth);
+                println!(
+                    "{}{} {} ({})",
+                    indent,
+                    format!("#{}", comment.id).bold(),
+                    comment.author_name,
+                    comment.created_at
+                );
+                for line in comment.text().lines() {
+                    println!("{}  {}", indent, line);
+                }
+            }
+        }
+        return Ok(());
+    }
+    if print_serialized(&comments)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record([
         "id",
         // "content",
         // "render",
+        "reply_to",
         "author_id",
         "author_name",
         "author_email",
@@ -44,10 +199,15 @@ fn comment_list(
         "updated_at",
     ]);
     for comment in comments {
-        builder.push_record([
+        table.push_record([
             comment.id.to_string().as_str(),
             // comment.content.as_str(),
             // comment.render.as_str(),
+            comment
+                .reply_to()
+                .map(|id| id.to_string())
+                .unwrap_or_default()
+                .as_str(),
             comment.author_id.to_string().as_str(),
             comment.author_name.as_str(),
             comment.author_email.as_str(),
@@ -56,6 +216,6 @@ fn comment_list(
             comment.updated_at.to_string().as_str(),
         ]);
     }
-    println!("{}", builder.build().with(Style::rounded()));
+    table.print()?;
     Ok(())
 }
@@ -1,6 +1,530 @@
+use colored::Colorize;
+
 pub(crate) trait Execute {
     fn execute(
         &self,
         api: wikijs::Api,
     ) -> Result<(), Box<dyn std::error::Error>>;
 }
+
+// parse a module config option given as key=value, the value may be JSON
+pub(crate) fn parse_config_option(
+    s: &str,
+) -> Result<(String, serde_json::Value), String> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or(format!("expected key=value, got {}", s))?;
+    let value = serde_json::from_str(value)
+        .unwrap_or(serde_json::Value::String(value.to_string()));
+    Ok((key.to_string(), value))
+}
+
+// parse a duration like 90m, 12h, 180d, 4w or 1y
+pub(crate) fn parse_duration(s: &str) -> Result<chrono::Duration, String> {
+    let unit_start = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or(format!("missing unit in duration {}", s))?;
+    let (amount, unit) = s.split_at(unit_start);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| format!("invalid duration {}", s))?;
+    match unit {
+        "s" => Ok(chrono::Duration::seconds(amount)),
+        "m" => Ok(chrono::Duration::minutes(amount)),
+        "h" => Ok(chrono::Duration::hours(amount)),
+        "d" => Ok(chrono::Duration::days(amount)),
+        "w" => Ok(chrono::Duration::weeks(amount)),
+        "y" => Ok(chrono::Duration::days(amount * 365)),
+        _ => Err(format!("unknown unit {} in duration {}", unit, s)),
+    }
+}
+
+// limit, offset and sort order of the list commands
+#[derive(clap::Args, Clone, Debug, Default)]
+pub(crate) struct PagingArgs {
+    #[clap(long, help = "Print at most this many items")]
+    pub limit: Option<usize>,
+
+    #[clap(long, help = "Skip this many items first", default_value = "0")]
+    pub offset: usize,
+
+    #[clap(
+        long,
+        help = "Sort by this field of the JSON output, prefixed with - for \
+                descending order, e.g. -updatedAt",
+        allow_hyphen_values = true
+    )]
+    pub sort: Option<String>,
+}
+
+// order values of the same field, numbers by value and anything else by its
+// JSON text, missing values first
+fn compare_values(
+    a: &serde_json::Value,
+    b: &serde_json::Value,
+) -> std::cmp::Ordering {
+    use serde_json::Value;
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(std::cmp::Ordering::Equal),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Null, Value::Null) => std::cmp::Ordering::Equal,
+        (Value::Null, _) => std::cmp::Ordering::Less,
+        (_, Value::Null) => std::cmp::Ordering::Greater,
+        (a, b) => a.to_string().cmp(&b.to_string()),
+    }
+}
+
+impl PagingArgs {
+    // the sort field and whether it is descending
+    pub(crate) fn sort_field(&self) -> Option<(&str, bool)> {
+        let sort = self.sort.as_deref()?;
+        Some(match sort.strip_prefix('-') {
+            Some(field) => (field, true),
+            None => (sort, false),
+        })
+    }
+
+    // number of items to ask an API that limits itself for, enough to skip
+    // the offset
+    pub(crate) fn fetch_limit(&self) -> Option<usize> {
+        self.limit.map(|limit| self.offset + limit)
+    }
+
+    // sort and slice the items on the client
+    pub(crate) fn apply<T: serde::Serialize>(
+        &self,
+        items: Vec<T>,
+    ) -> Result<Vec<T>, Box<dyn std::error::Error>> {
+        self.apply_by(items, |item| item)
+    }
+
+    // sort and slice the items on the client, by the fields of what `data`
+    // gives for each item
+    pub(crate) fn apply_by<T, D: serde::Serialize>(
+        &self,
+        items: Vec<T>,
+        data: impl Fn(&T) -> &D,
+    ) -> Result<Vec<T>, Box<dyn std::error::Error>> {
+        let mut items = items;
+        if let Some((field, descending)) = self.sort_field() {
+            let mut keyed = items
+                .into_iter()
+                .map(|item| {
+                    let value = serde_json::to_value(data(&item))?;
+                    let key = match value.get(field) {
+                        Some(key) => key.clone(),
+                        None if value.is_object() => {
+                            return Err(format!(
+                                "unknown sort field {}, the items have {}",
+                                field,
+                                value
+                                    .as_object()
+                                    .map(|o| o
+                                        .keys()
+                                        .cloned()
+                                        .collect::<Vec<_>>()
+                                        .join(", "))
+                                    .unwrap_or_default()
+                            )
+                            .into());
+                        }
+                        None => serde_json::Value::Null,
+                    };
+                    Ok((key, item))
+                })
+                .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+            keyed.sort_by(|(a, _), (b, _)| match descending {
+                true => compare_values(b, a),
+                false => compare_values(a, b),
+            });
+            items = keyed.into_iter().map(|(_, item)| item).collect();
+        }
+        Ok(items
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+}
+
+static QUIET: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+
+pub(crate) fn set_quiet(quiet: bool) {
+    let _ = QUIET.set(quiet);
+}
+
+// whether success and progress messages are left out
+pub(crate) fn is_quiet() -> bool {
+    QUIET.get().copied().unwrap_or_default()
+}
+
+const EXIT_NOT_FOUND: i32 = 2;
+const EXIT_FORBIDDEN: i32 = 3;
+const EXIT_CONFLICT: i32 = 4;
+const EXIT_NETWORK: i32 = 5;
+
+// exit code of the errors that only tell failed requests apart
+macro_rules! request_exit_code {
+    ($error:expr, $($kind:ident),+) => {
+        $(
+            if let Some(e) = $error.downcast_ref::<$kind>() {
+                return match e {
+                    $kind::RequestFailed { .. } => EXIT_NETWORK,
+                    _ => 1,
+                };
+            }
+        )+
+    };
+}
+
+// exit code by the category of an error, so scripts can tell them apart:
+// 2 not found, 3 forbidden, 4 conflict, 5 network and 1 for anything else
+pub(crate) fn exit_code(error: &(dyn std::error::Error + 'static)) -> i32 {
+    use wikijs::analytics::AnalyticsError;
+    use wikijs::asset::AssetError;
+    use wikijs::audit::AuditError;
+    use wikijs::backup::BackupError;
+    use wikijs::comment::CommentError;
+    use wikijs::contribute::ContributeError;
+    use wikijs::export::ExportError;
+    use wikijs::group::GroupError;
+    use wikijs::import::ImportError;
+    use wikijs::localization::LocaleError;
+    use wikijs::logging::LoggingError;
+    use wikijs::mail::MailError;
+    use wikijs::mirror::MirrorError;
+    use wikijs::navigation::NavigationError;
+    use wikijs::page::PageError;
+    use wikijs::rendering::RenderingError;
+    use wikijs::report::ReportError;
+    use wikijs::search::SearchError;
+    use wikijs::site::SiteError;
+    use wikijs::storage::StorageError;
+    use wikijs::sync::SyncError;
+    use wikijs::system::SystemError;
+    use wikijs::template::TemplateError;
+    use wikijs::theming::ThemeError;
+    use wikijs::user::UserError;
+    use wikijs::watch::WatchError;
+
+    if let Some(e) = error.downcast_ref::<PageError>() {
+        return match e {
+            PageError::PageNotFound | PageError::PageTagNotFound => {
+                EXIT_NOT_FOUND
+            }
+            PageError::PageMoveForbidden
+            | PageError::PageCreateForbidden
+            | PageError::PageUpdateForbidden
+            | PageError::PageDeleteForbidden
+            | PageError::PageRestoreForbidden
+            | PageError::PageHistoryForbidden
+            | PageError::PageViewForbidden => EXIT_FORBIDDEN,
+            // Wiki.js reports missing permissions on some queries as a bare
+            // error without a code
+            PageError::UnknownErrorMessage { message }
+                if message == "Forbidden" =>
+            {
+                EXIT_FORBIDDEN
+            }
+            PageError::PageDuplicateCreate
+            | PageError::PagePathCollision
+            | PageError::PageUpdateConflict => EXIT_CONFLICT,
+            PageError::RequestFailed { .. } => EXIT_NETWORK,
+            _ => 1,
+        };
+    }
+    if let Some(e) = error.downcast_ref::<AssetError>() {
+        return match e {
+            AssetError::AssetDeleteForbidden
+            | AssetError::AssetRenameForbidden
+            | AssetError::AssetRenameTargetForbidden => EXIT_FORBIDDEN,
+            AssetError::AssetFolderExists
+            | AssetError::AssetRenameCollision => EXIT_CONFLICT,
+            AssetError::RequestFailed { .. } => EXIT_NETWORK,
+            _ => 1,
+        };
+    }
+    if let Some(e) = error.downcast_ref::<CommentError>() {
+        return match e {
+            CommentError::CommentNotFound => EXIT_NOT_FOUND,
+            CommentError::CommentPostForbidden
+            | CommentError::CommentManageForbidden
+            | CommentError::CommentViewForbidden => EXIT_FORBIDDEN,
+            CommentError::RequestFailed { .. } => EXIT_NETWORK,
+            _ => 1,
+        };
+    }
+    if let Some(e) = error.downcast_ref::<UserError>() {
+        return match e {
+            UserError::UserNotFound
+            | UserError::AuthenticationStrategyNotFound { .. } => {
+                EXIT_NOT_FOUND
+            }
+            UserError::AuthRequired
+            | UserError::AuthRegistrationDomainUnauthorized => EXIT_FORBIDDEN,
+            UserError::AuthAccountAlreadyExists => EXIT_CONFLICT,
+            UserError::RequestFailed { .. } => EXIT_NETWORK,
+            _ => 1,
+        };
+    }
+    if let Some(e) = error.downcast_ref::<GroupError>() {
+        return match e {
+            GroupError::UserNotFound { .. } => EXIT_NOT_FOUND,
+            GroupError::RequestFailed { .. } => EXIT_NETWORK,
+            _ => 1,
+        };
+    }
+    if let Some(e) = error.downcast_ref::<NavigationError>() {
+        return match e {
+            NavigationError::NavigationItemNotFound { .. } => EXIT_NOT_FOUND,
+            NavigationError::RequestFailed { .. } => EXIT_NETWORK,
+            _ => 1,
+        };
+    }
+    request_exit_code!(
+        error,
+        AnalyticsError,
+        ContributeError,
+        LocaleError,
+        LoggingError,
+        MailError,
+        RenderingError,
+        SearchError,
+        SiteError,
+        StorageError,
+        SystemError,
+        ThemeError
+    );
+    // errors of operations spanning several kinds of objects wrap theirs
+    if let Some(e) = error.downcast_ref::<SyncError>() {
+        return match e {
+            SyncError::Page(e) => exit_code(e),
+            SyncError::Conflict(_) => EXIT_CONFLICT,
+            _ => 1,
+        };
+    }
+    let inner: Option<&(dyn std::error::Error + 'static)> =
+        if let Some(e) = error.downcast_ref::<MirrorError>() {
+            match e {
+                MirrorError::Page(e) => Some(e),
+                MirrorError::Asset(e) => Some(e),
+            }
+        } else if let Some(e) = error.downcast_ref::<BackupError>() {
+            match e {
+                BackupError::Page(e) => Some(e),
+                BackupError::Asset(e) => Some(e),
+                _ => None,
+            }
+        } else if let Some(e) = error.downcast_ref::<ExportError>() {
+            match e {
+                ExportError::Page(e) => Some(e),
+                ExportError::Asset(e) => Some(e),
+                _ => None,
+            }
+        } else if let Some(e) = error.downcast_ref::<AuditError>() {
+            match e {
+                AuditError::Page(e) => Some(e),
+                AuditError::Asset(e) => Some(e),
+                _ => None,
+            }
+        } else if let Some(e) = error.downcast_ref::<ReportError>() {
+            match e {
+                ReportError::Page(e) => Some(e),
+                ReportError::Comment(e) => Some(e),
+                _ => None,
+            }
+        } else if let Some(e) = error.downcast_ref::<WatchError>() {
+            match e {
+                WatchError::Page(e) => Some(e),
+                WatchError::Comment(e) => Some(e),
+            }
+        } else if let Some(e) = error.downcast_ref::<ImportError>() {
+            match e {
+                ImportError::Page(e) => Some(e),
+                _ => None,
+            }
+        } else if let Some(e) = error.downcast_ref::<TemplateError>() {
+            match e {
+                TemplateError::Page(e) => Some(e),
+                _ => None,
+            }
+        } else {
+            None
+        };
+    if let Some(e) = inner {
+        return exit_code(e);
+    }
+    #[cfg(feature = "index")]
+    if let Some(wikijs::index::IndexError::Page(e)) = error.downcast_ref() {
+        return exit_code(e);
+    }
+    #[cfg(feature = "keyring")]
+    if let Some(wikijs::keyring::KeyringError::NotFound { .. }) =
+        error.downcast_ref()
+    {
+        return EXIT_NOT_FOUND;
+    }
+    // errors of the CLI itself, like downloads, may carry a request error
+    let mut source = Some(error);
+    while let Some(error) = source {
+        if error.downcast_ref::<reqwest::Error>().is_some() {
+            return EXIT_NETWORK;
+        }
+        source = error.source();
+    }
+    1
+}
+
+static DRY_RUN: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+
+pub(crate) fn set_dry_run(dry_run: bool) {
+    let _ = DRY_RUN.set(dry_run);
+}
+
+// whether mutating commands should only print what they would do
+pub(crate) fn is_dry_run() -> bool {
+    DRY_RUN.get().copied().unwrap_or_default()
+}
+
+// print what a mutating command would do instead of doing it
+pub(crate) fn print_dry_run(
+    action: impl std::fmt::Display,
+) -> Result<(), Box<dyn std::error::Error>> {
+    info!("{}: would {}", "dry run".bold().cyan(), action);
+    Ok(())
+}
+
+static ASSUME_YES: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+
+pub(crate) fn set_assume_yes(yes: bool) {
+    let _ = ASSUME_YES.set(yes);
+}
+
+// ask before a destructive action, unless --yes was given, without a
+// terminal to ask on the action is refused
+pub(crate) fn confirm(
+    action: impl std::fmt::Display,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{IsTerminal, Write};
+    if ASSUME_YES.get().copied().unwrap_or_default() {
+        return Ok(());
+    }
+    if !std::io::stdin().is_terminal() {
+        return Err(format!(
+            "not confirmed to {}, pass --yes to skip the confirmation",
+            action
+        )
+        .into());
+    }
+    eprint!("{}: {}? [y/N] ", "confirm".bold().yellow(), action);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => Ok(()),
+        _ => Err("aborted".into()),
+    }
+}
+
+// lists of key value pairs, like module configs, are compared by key
+fn key_value_pairs(
+    values: &[serde_json::Value],
+) -> Option<Vec<(&str, &serde_json::Value)>> {
+    values
+        .iter()
+        .map(|pair| Some((pair.get("key")?.as_str()?, pair.get("value")?)))
+        .collect()
+}
+
+// long values, like injected CSS, are only summarized
+fn setting_value(value: &serde_json::Value) -> String {
+    let text = value.to_string();
+    match text.chars().count() > 60 {
+        true => format!("({} characters)", text.chars().count()),
+        false => text,
+    }
+}
+
+fn diff_values(
+    name: &str,
+    old: &serde_json::Value,
+    new: &serde_json::Value,
+    changes: &mut Vec<String>,
+) {
+    use serde_json::Value;
+    let field = |key: &str| match name.is_empty() {
+        true => key.to_string(),
+        false => format!("{}.{}", name, key),
+    };
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, value) in new {
+                let previous = old.get(key).unwrap_or(&Value::Null);
+                diff_values(&field(key), previous, value, changes);
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            match (key_value_pairs(old), key_value_pairs(new)) {
+                (Some(old), Some(new)) => {
+                    for (key, value) in new {
+                        let previous = old
+                            .iter()
+                            .find(|(k, _)| *k == key)
+                            .map(|(_, v)| *v)
+                            .unwrap_or(&Value::Null);
+                        diff_values(&field(key), previous, value, changes);
+                    }
+                }
+                _ if old != new => changes.push(format!(
+                    "{}={}",
+                    name,
+                    setting_value(&Value::from(new.clone()))
+                )),
+                _ => {}
+            }
+        }
+        (old, new) if old != new => {
+            changes.push(format!("{}={}", name, setting_value(new)))
+        }
+        _ => {}
+    }
+}
+
+// the settings that differ between two versions of an input, like
+// isEnabled=true, to show what a dry run would change
+pub(crate) fn settings_diff<T: serde::Serialize>(old: &T, new: &T) -> String {
+    let mut changes = Vec::new();
+    diff_values(
+        "",
+        &serde_json::to_value(old).unwrap_or_default(),
+        &serde_json::to_value(new).unwrap_or_default(),
+        &mut changes,
+    );
+    match changes.is_empty() {
+        true => "no changes".to_string(),
+        false => changes.join(", "),
+    }
+}
+
+// open a URL in the default browser
+pub(crate) fn open_url(url: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let (program, args): (&str, &[&str]) = if cfg!(target_os = "macos") {
+        ("open", &[])
+    } else if cfg!(windows) {
+        // cmd would interpret characters like & in the URL
+        ("rundll32", &["url.dll,FileProtocolHandler"])
+    } else {
+        ("xdg-open", &[])
+    };
+    let status = std::process::Command::new(program)
+        .args(args)
+        .arg(url)
+        .status()?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", program, status).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use crate::credentials::CredentialArgs;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Command, Id};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::PathBuf;
+
+/// The CLI configuration file with named profiles, like
+///
+/// ```toml
+/// default_profile = "prod"
+///
+/// [profiles.prod]
+/// url = "https://wiki.example.com"
+/// key = "..."
+/// locale = "de"
+/// output = "json"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct Config {
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+}
+
+/// Defaults for the global options, each used only if neither the option
+/// nor its environment variable is given. The key is left out if a username
+/// or password is given, the username if a key is given.
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct Profile {
+    pub url: Option<String>,
+    pub key: Option<String>,
+    pub username: Option<String>,
+    pub provider: Option<String>,
+    pub locale: Option<String>,
+    pub output: Option<String>,
+}
+
+// the directory of the CLI configuration, following the XDG convention
+pub(crate) fn config_dir() -> Option<PathBuf> {
+    match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir).join("wikijs")),
+        _ => std::env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(".config").join("wikijs")),
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Config, Box<dyn Error>> {
+        let Some(path) = config_dir().map(|dir| dir.join("config.toml")) else {
+            return Ok(Config::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => toml_edit::de::from_str(&text).map_err(|e| {
+                format!("invalid config {}: {}", path.display(), e).into()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Ok(Config::default())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+// the profile given with --profile, looked up before clap parses the
+// arguments so the profile can provide their defaults
+fn profile_arg() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if let Some(name) = arg.strip_prefix("--profile=") {
+            return Some(name.to_string());
+        }
+        if arg == "--profile" {
+            return args.next();
+        }
+    }
+    std::env::var("WIKI_JS_PROFILE").ok()
+}
+
+/// Make the values of the selected profile the defaults of the options
+/// they stand for, in all subcommands. Unlike the built-in defaults they are
+/// not shown in the help.
+///
+/// As defaults they have the lowest precedence and never count as given, so
+/// they neither conflict with options that are given nor require others.
+pub(crate) fn apply_profile(
+    command: Command,
+) -> Result<Command, Box<dyn Error>> {
+    let config = Config::load()?;
+    let explicit = profile_arg();
+    let Some(name) = explicit.clone().or(config.default_profile) else {
+        return Ok(command);
+    };
+    let Some(profile) = config.profiles.get(&name) else {
+        return match explicit {
+            Some(_) => Err(format!("unknown profile {}", name).into()),
+            None => Err(format!("unknown default profile {}", name).into()),
+        };
+    };
+    let values = [
+        ("WIKI_JS_BASE_URL", &profile.url),
+        ("WIKI_JS_API_KEY", &profile.key),
+        ("WIKI_JS_USERNAME", &profile.username),
+        ("WIKI_JS_AUTH_PROVIDER", &profile.provider),
+        ("WIKI_JS_LOCALE", &profile.locale),
+        ("WIKI_JS_OUTPUT", &profile.output),
+    ];
+    Ok(values
+        .into_iter()
+        .fold(command, |command, (var, value)| match value {
+            Some(value) => default_by_env(command, var, value),
+            None => command,
+        }))
+}
+
+// set the default of the options read from the environment variable, in the
+// command and all its subcommands
+fn default_by_env(command: Command, var: &str, value: &str) -> Command {
+    let ids: Vec<Id> = command
+        .get_arguments()
+        .filter(|arg| arg.get_env() == Some(OsStr::new(var)))
+        .map(|arg| arg.get_id().clone())
+        .collect();
+    let command = ids.into_iter().fold(command, |command, id| {
+        command.mut_arg(id, |arg| {
+            arg.default_value(value.to_string())
+                .hide_default_value(true)
+        })
+    });
+    let names: Vec<String> = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .collect();
+    names.into_iter().fold(command, |command, name| {
+        command.mut_subcommand(name, |subcommand| {
+            default_by_env(subcommand, var, value)
+        })
+    })
+}
+
+/// Drop the key of the profile if a username or password is given, and
+/// the username of the profile if a key is given, so the credentials of
+/// the profile never mix with those given.
+pub(crate) fn drop_profile_credentials(
+    matches: &ArgMatches,
+    credentials: &mut CredentialArgs,
+) {
+    let given = |id: &str| {
+        matches
+            .value_source(id)
+            .is_some_and(|source| source != ValueSource::DefaultValue)
+    };
+    let from_profile =
+        |id: &str| matches.value_source(id) == Some(ValueSource::DefaultValue);
+    if from_profile("key") && (given("username") || given("password")) {
+        credentials.key = None;
+    }
+    if from_profile("username") && given("key") {
+        credentials.username = None;
+    }
+}
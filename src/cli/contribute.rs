@@ -1,7 +1,7 @@
 use crate::common::Execute;
+use crate::output::{print_serialized, Table};
 use clap::Subcommand;
 use std::error::Error;
-use tabled::{builder::Builder, settings::Style};
 
 #[derive(Subcommand, Debug)]
 pub(crate) enum ContributorCommand {
@@ -19,8 +19,11 @@ impl Execute for ContributorCommand {
 
 pub(crate) fn contributor_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
     let contributors = api.contributor_list()?;
-    let mut builder = Builder::new();
-    builder.push_record([
+    if print_serialized(&contributors)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record([
         "id", "source", "name",
         "joined",
         // "website",
@@ -28,7 +31,7 @@ pub(crate) fn contributor_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
         // "avatar",
     ]);
     for contributor in contributors {
-        builder.push_record([
+        table.push_record([
             contributor.id.to_string().as_str(),
             contributor.source.as_str(),
             contributor.name.as_str(),
@@ -39,6 +42,6 @@ pub(crate) fn contributor_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
             // contributor.avatar.unwrap_or("".to_string()).as_str(),
         ]);
     }
-    println!("{}", builder.build().with(Style::rounded()));
+    table.print()?;
     Ok(())
 }
@@ -0,0 +1,179 @@
+// Shared by the CLI and the FUSE filesystem, which includes this file as a
+// module of its own, so it must not depend on other modules of the CLI.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use clap::Args;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use std::error::Error;
+use std::io::{IsTerminal, Write};
+use std::time::SystemTime;
+
+#[derive(Args, Clone, Debug)]
+#[group(multiple = true)]
+pub(crate) struct CredentialArgs {
+    #[clap(short, long, help = "Wiki.js API key", env = "WIKI_JS_API_KEY")]
+    pub key: Option<String>,
+
+    #[clap(
+        short = 'U',
+        long,
+        help = "Wiki.js username",
+        env = "WIKI_JS_USERNAME",
+        conflicts_with = "key"
+    )]
+    pub username: Option<String>,
+
+    #[clap(
+        short = 'P',
+        long,
+        help = "Wiki.js password",
+        env = "WIKI_JS_PASSWORD",
+        requires = "username",
+        conflicts_with = "key"
+    )]
+    pub password: Option<String>,
+
+    #[clap(
+        short,
+        long,
+        help = "Wiki.js authentication provider ID",
+        env = "WIKI_JS_AUTH_PROVIDER",
+        default_value = "local"
+    )]
+    pub provider: Option<String>,
+
+    #[clap(
+        long,
+        help = "Base32 TFA secret to compute the security code from, \
+                instead of asking for it",
+        env = "WIKI_JS_TOTP_SECRET",
+        requires = "username"
+    )]
+    pub totp_secret: Option<String>,
+}
+
+// log in with username and password, asking for the password if not given
+// and for the TFA security code if the account requires one, and return the
+// JWT
+pub(crate) fn login(
+    url: &str,
+    credentials: &CredentialArgs,
+) -> Result<String, Box<dyn Error>> {
+    let credentials = with_password(credentials)?;
+    login_with(url, &credentials, || prompt_hidden("TFA security code: "))
+}
+
+// the credentials with the password asked for if a username but no password
+// is given
+pub(crate) fn with_password(
+    credentials: &CredentialArgs,
+) -> Result<CredentialArgs, Box<dyn Error>> {
+    let mut credentials = credentials.clone();
+    if credentials.username.is_some() && credentials.password.is_none() {
+        credentials.password = Some(prompt_hidden("Password: ")?);
+    }
+    Ok(credentials)
+}
+
+// log in with username and password, calling tfa_code for the TFA security
+// code if the account requires one and there is no secret to compute it
+pub(crate) fn login_with(
+    url: &str,
+    credentials: &CredentialArgs,
+    tfa_code: impl FnOnce() -> Result<String, Box<dyn Error>>,
+) -> Result<String, Box<dyn Error>> {
+    let client = anonymous_client()?;
+    let graphql_url = format!("{}/graphql", url);
+    let response = wikijs::authentication::login(
+        &client,
+        &graphql_url,
+        credentials.username.clone().unwrap_or_default(),
+        credentials.password.clone().unwrap_or_default(),
+        credentials.provider.clone().unwrap_or_default(),
+    )?;
+    if let Some(jwt) = response.jwt {
+        return Ok(jwt);
+    }
+    if response.must_setup_tfa == Some(true) {
+        return Err("TFA must be set up in the web interface first".into());
+    }
+    if response.must_change_pwd == Some(true) {
+        return Err("the password must be changed in the web interface".into());
+    }
+    let token = response
+        .continuation_token
+        .filter(|_| response.must_provide_tfa == Some(true))
+        .ok_or("login did not return a token")?;
+    let code = match &credentials.totp_secret {
+        Some(secret) => {
+            wikijs::authentication::totp_code(secret, SystemTime::now())
+                .ok_or("the TFA secret is not valid base32")?
+        }
+        None => tfa_code()?,
+    };
+    wikijs::authentication::login_tfa(&client, &graphql_url, token, code, None)?
+        .jwt
+        .ok_or_else(|| "login did not return a token".into())
+}
+
+pub(crate) fn anonymous_client(
+) -> Result<reqwest::blocking::Client, Box<dyn Error>> {
+    Ok(reqwest::blocking::Client::builder()
+        .user_agent("wikijs-rs/0.1.0")
+        .build()?)
+}
+
+// read a line from the terminal without echoing it
+pub(crate) fn prompt_hidden(prompt: &str) -> Result<String, Box<dyn Error>> {
+    eprint!("{}", prompt);
+    std::io::stderr().flush()?;
+    if !std::io::stdin().is_terminal() {
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        return Ok(line.trim().to_string());
+    }
+    // raw mode turns off the echo, so the keys are read one by one
+    terminal::enable_raw_mode()?;
+    let result = read_hidden_line();
+    terminal::disable_raw_mode()?;
+    eprintln!();
+    Ok(result?.trim().to_string())
+}
+
+// the keys typed until enter, in raw mode
+fn read_hidden_line() -> Result<String, Box<dyn Error>> {
+    let mut line = String::new();
+    loop {
+        let key = match event::read()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => key,
+            _ => continue,
+        };
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c' | 'd') if control => {
+                return Err("interrupted".into());
+            }
+            KeyCode::Char(_) if control => {}
+            KeyCode::Char(c) => line.push(c),
+            KeyCode::Backspace => {
+                line.pop();
+            }
+            KeyCode::Enter => return Ok(line),
+            _ => {}
+        }
+    }
+}
+
+// the exp claim of a JWT in seconds since the epoch, the signature is not
+// checked
+pub(crate) fn jwt_expiry(jwt: &str) -> Option<u64> {
+    let payload = URL_SAFE_NO_PAD
+        .decode(jwt.split('.').nth(1)?.trim_end_matches('='))
+        .ok()?;
+    serde_json::from_slice::<serde_json::Value>(&payload)
+        .ok()?
+        .get("exp")?
+        .as_u64()
+}
@@ -0,0 +1,59 @@
+use crate::common::Execute;
+use clap::Subcommand;
+use colored::Colorize;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use wikijs::export::{ExportSummary, VaultFormat};
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum ExportCommand {
+    #[clap(about = "Export published pages as a static HTML site")]
+    Html {
+        #[clap(help = "Directory to write the site to")]
+        dir: PathBuf,
+    },
+
+    #[clap(about = "Export published pages as a Hugo content dir")]
+    Hugo {
+        #[clap(help = "Directory of the Hugo site")]
+        dir: PathBuf,
+    },
+
+    #[clap(about = "Export published pages as an Obsidian vault")]
+    Obsidian {
+        #[clap(help = "Directory of the vault")]
+        dir: PathBuf,
+    },
+}
+
+impl Execute for ExportCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        match self {
+            ExportCommand::Html { dir } => {
+                print_summary(api.export_html(dir, None)?, dir)
+            }
+            ExportCommand::Hugo { dir } => print_summary(
+                api.export_vault(dir, VaultFormat::Hugo, None)?,
+                dir,
+            ),
+            ExportCommand::Obsidian { dir } => print_summary(
+                api.export_vault(dir, VaultFormat::Obsidian, None)?,
+                dir,
+            ),
+        }
+    }
+}
+
+fn print_summary(
+    summary: ExportSummary,
+    dir: &Path,
+) -> Result<(), Box<dyn Error>> {
+    info!(
+        "{}: exported {} pages and {} assets to {}",
+        "success".bold().green(),
+        summary.pages,
+        summary.assets,
+        dir.display()
+    );
+    Ok(())
+}
@@ -1,7 +1,9 @@
-use crate::common::Execute;
+use crate::common::{confirm, is_dry_run, print_dry_run, Execute};
+use crate::output::{print_serialized, Table};
 use clap::Subcommand;
+use colored::Colorize;
 use std::error::Error;
-use tabled::{builder::Builder, settings::Style};
+use wikijs::group::{PageRuleBuilder, PageRuleMatch, Permission};
 
 #[derive(Subcommand, Debug)]
 pub(crate) enum GroupCommand {
@@ -13,6 +15,73 @@ pub(crate) enum GroupCommand {
         #[clap(short, long, help = "Order groups by this")]
         order_by: Option<String>,
     },
+
+    #[clap(about = "Delete a group")]
+    Delete {
+        #[clap(help = "Group ID")]
+        id: i64,
+    },
+
+    #[clap(about = "Page rule commands")]
+    Rule {
+        #[clap(subcommand)]
+        command: GroupRuleCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum GroupRuleCommand {
+    #[clap(about = "List the page rules of a group")]
+    List {
+        #[clap(help = "Group ID")]
+        group: i64,
+    },
+
+    #[clap(about = "Add a page rule to a group")]
+    Add {
+        #[clap(help = "Group ID")]
+        group: i64,
+
+        #[clap(short, long, help = "Path the rule applies to")]
+        path: String,
+
+        #[clap(
+            short,
+            long = "match",
+            help = "How the path is matched (start, exact, end, regex, tag)",
+            default_value = "start"
+        )]
+        r#match: PageRuleMatch,
+
+        #[clap(short, long, help = "Deny the roles instead of allowing them")]
+        deny: bool,
+
+        #[clap(
+            short,
+            long,
+            help = "Comma separated locales, all locales if omitted",
+            value_delimiter = ','
+        )]
+        locales: Vec<String>,
+
+        #[clap(
+            short,
+            long,
+            help = "Comma separated roles, like read:pages,write:pages",
+            value_delimiter = ',',
+            default_value = "read:pages"
+        )]
+        roles: Vec<Permission>,
+    },
+
+    #[clap(about = "Remove a page rule from a group")]
+    Remove {
+        #[clap(help = "Group ID")]
+        group: i64,
+
+        #[clap(help = "Rule ID")]
+        id: String,
+    },
 }
 
 impl Execute for GroupCommand {
@@ -21,6 +90,77 @@ impl Execute for GroupCommand {
             GroupCommand::List { filter, order_by } => {
                 group_list(api, filter.to_owned(), order_by.to_owned())
             }
+            GroupCommand::Delete { id } => group_delete(api, *id),
+            GroupCommand::Rule { command } => command.execute(api),
+        }
+    }
+}
+
+impl Execute for GroupRuleCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        match self {
+            GroupRuleCommand::List { group } => group_rule_list(api, *group),
+            GroupRuleCommand::Add {
+                group,
+                path,
+                r#match,
+                deny,
+                locales,
+                roles,
+            } => {
+                let rule = locales
+                    .iter()
+                    .fold(PageRuleBuilder::new(path), |rule, locale| {
+                        rule.locale(locale)
+                    })
+                    .r#match(r#match.clone())
+                    .deny(*deny)
+                    .roles(roles.iter().copied())
+                    .build();
+                let id = rule.id.clone();
+                if is_dry_run() {
+                    let group = api.group_get(*group)?;
+                    return print_dry_run(format!(
+                        "add page rule {} ({} {} {}) to group {} ({})",
+                        id,
+                        if rule.deny { "deny" } else { "allow" },
+                        format!("{:?}", rule.r#match).to_lowercase(),
+                        rule.path,
+                        group.id,
+                        group.name
+                    ));
+                }
+                api.group_rules_edit(*group, |_, rules| rules.push(rule))?;
+                info!("{}: Page rule {} added", "success".bold().green(), id);
+                Ok(())
+            }
+            GroupRuleCommand::Remove { group, id } => {
+                if is_dry_run() {
+                    let group = api.group_get(*group)?;
+                    let rule = group
+                        .page_rules
+                        .iter()
+                        .flatten()
+                        .flatten()
+                        .find(|rule| &rule.id == id)
+                        .ok_or(format!("unknown page rule {}", id))?;
+                    return print_dry_run(format!(
+                        "remove page rule {} on {} from group {} ({})",
+                        id, rule.path, group.id, group.name
+                    ));
+                }
+                let mut found = false;
+                api.group_rules_edit(*group, |_, rules| {
+                    let count = rules.len();
+                    rules.retain(|rule| &rule.id != id);
+                    found = rules.len() < count;
+                })?;
+                if !found {
+                    return Err(format!("unknown page rule {}", id).into());
+                }
+                info!("{}: Page rule removed", "success".bold().green());
+                Ok(())
+            }
         }
     }
 }
@@ -31,8 +171,11 @@ fn group_list(
     order_by: Option<String>,
 ) -> Result<(), Box<dyn Error>> {
     let groups = api.group_list(filter, order_by)?;
-    let mut builder = Builder::new();
-    builder.push_record([
+    if print_serialized(&groups)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record([
         "id",
         "name",
         "is_system",
@@ -41,7 +184,7 @@ fn group_list(
         "updated_at",
     ]);
     for group in groups {
-        builder.push_record([
+        table.push_record([
             group.id.to_string().as_str(),
             group.name.as_str(),
             group.is_system.to_string().as_str(),
@@ -50,6 +193,39 @@ fn group_list(
             group.updated_at.to_string().as_str(),
         ]);
     }
-    println!("{}", builder.build().with(Style::rounded()));
+    table.print()?;
+    Ok(())
+}
+
+fn group_delete(api: wikijs::Api, id: i64) -> Result<(), Box<dyn Error>> {
+    let group = api.group_get(id)?;
+    let action = format!("delete group {} ({})", group.id, group.name);
+    if is_dry_run() {
+        return print_dry_run(action);
+    }
+    confirm(action)?;
+    api.group_delete(id)?;
+    info!("{}: Group deleted", "success".bold().green());
+    Ok(())
+}
+
+fn group_rule_list(api: wikijs::Api, group: i64) -> Result<(), Box<dyn Error>> {
+    let group = api.group_get(group)?;
+    if print_serialized(&group.page_rules)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["id", "deny", "match", "path", "locales", "roles"]);
+    for rule in group.page_rules.iter().flatten().flatten() {
+        table.push_record([
+            rule.id.as_str(),
+            rule.deny.to_string().as_str(),
+            format!("{:?}", rule.r#match).to_lowercase().as_str(),
+            rule.path.as_str(),
+            rule.locales.join(",").as_str(),
+            rule.roles.join(",").as_str(),
+        ]);
+    }
+    table.print()?;
     Ok(())
 }
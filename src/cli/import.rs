@@ -0,0 +1,162 @@
+use crate::common::{is_dry_run, Execute};
+use clap::{Args, Subcommand};
+use colored::Colorize;
+use std::error::Error;
+use std::path::PathBuf;
+use wikijs::import::mediawiki::MediaWikiOptions;
+use wikijs::import::{ImportAction, ImportCollision, ImportOptions};
+use wikijs::page::PageError;
+
+#[derive(Args, Debug)]
+#[command(
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+pub(crate) struct ImportCommand {
+    #[clap(subcommand)]
+    command: Option<ImportSubcommand>,
+
+    #[clap(help = "Directory of markdown files", required = true)]
+    dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    options: ImportArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum ImportSubcommand {
+    #[clap(about = "Create pages from a MediaWiki XML dump")]
+    Mediawiki {
+        #[clap(help = "MediaWiki XML dump file")]
+        file: PathBuf,
+
+        #[clap(flatten)]
+        options: ImportArgs,
+
+        #[clap(long, help = "Import talk pages as well")]
+        include_talk: bool,
+    },
+}
+
+#[derive(Args, Debug)]
+struct ImportArgs {
+    #[clap(
+        short,
+        long,
+        help = "Locale of pages without one in their front matter",
+        default_value = "en",
+        env = "WIKI_JS_LOCALE"
+    )]
+    locale: String,
+
+    #[clap(
+        short,
+        long,
+        help = "What to do with existing pages (skip, overwrite, update)",
+        default_value = "skip"
+    )]
+    collision: ImportCollision,
+}
+
+impl Execute for ImportCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        let outcomes = match (&self.command, &self.dir) {
+            (
+                Some(ImportSubcommand::Mediawiki {
+                    file,
+                    options,
+                    include_talk,
+                }),
+                _,
+            ) => {
+                let options = MediaWikiOptions {
+                    locale: options.locale.clone(),
+                    collision: options.collision,
+                    include_talk: *include_talk,
+                    dry_run: is_dry_run(),
+                };
+                api.import_mediawiki(
+                    file,
+                    &options,
+                    |i, n, o| {
+                        print_progress(i, n, &o.title, &o.path, &o.result);
+                        if !o.unconverted.is_empty() {
+                            let unconverted: Vec<&str> = o
+                                .unconverted
+                                .iter()
+                                .map(String::as_str)
+                                .collect();
+                            println!(
+                                "  {}: unconverted {}",
+                                "warning".bold().yellow(),
+                                unconverted.join(", ")
+                            );
+                        }
+                    },
+                    None,
+                )?
+                .into_iter()
+                .map(|o| o.result)
+                .collect::<Vec<_>>()
+            }
+            (None, Some(dir)) => {
+                let options = ImportOptions {
+                    locale: self.options.locale.clone(),
+                    collision: self.options.collision,
+                    dry_run: is_dry_run(),
+                };
+                api.import_dir(
+                    dir,
+                    &options,
+                    |i, n, o| {
+                        let path = format!("{}/{}", o.locale, o.path);
+                        print_progress(i, n, &o.file, &path, &o.result);
+                    },
+                    None,
+                )?
+                .into_iter()
+                .map(|o| o.result)
+                .collect::<Vec<_>>()
+            }
+            // clap requires either the directory or a subcommand
+            (None, None) => unreachable!(),
+        };
+        let failed = outcomes.iter().filter(|r| r.is_err()).count();
+        if failed > 0 {
+            return Err(
+                format!("{} pages could not be imported", failed).into()
+            );
+        }
+        if is_dry_run() {
+            info!(
+                "{}: would import {} pages",
+                "dry run".bold().cyan(),
+                outcomes.len()
+            );
+            return Ok(());
+        }
+        info!(
+            "{}: imported {} pages",
+            "success".bold().green(),
+            outcomes.len()
+        );
+        Ok(())
+    }
+}
+
+fn print_progress(
+    index: usize,
+    total: usize,
+    source: &str,
+    path: &str,
+    result: &Result<ImportAction, PageError>,
+) {
+    let status = match result {
+        Ok(action) if is_dry_run() => {
+            format!("would be {:?}", action).to_lowercase().cyan()
+        }
+        Ok(action) => format!("{:?}", action).to_lowercase().green(),
+        Err(e) => e.to_string().red(),
+    };
+    info!("[{}/{}] {} -> {}: {}", index, total, source, path, status);
+}
@@ -0,0 +1,134 @@
+use crate::common::{is_dry_run, print_dry_run};
+use crate::config::config_dir;
+use crate::output::{print_serialized, Table};
+use clap::{Args, Subcommand};
+use colored::Colorize;
+use std::error::Error;
+use std::path::PathBuf;
+use wikijs::index::SearchIndex;
+
+#[derive(Args, Debug)]
+pub(crate) struct IndexCommand {
+    #[clap(
+        long,
+        help = "Directory of the index, by default one per wiki in the \
+                config directory",
+        env = "WIKI_JS_INDEX_DIR"
+    )]
+    dir: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    command: IndexSubcommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum IndexSubcommand {
+    #[clap(about = "Build the index or bring it up to date")]
+    Build {},
+
+    #[clap(about = "Search the index without contacting the wiki")]
+    Search {
+        #[clap(help = "Words to search for")]
+        query: String,
+
+        #[clap(
+            short,
+            long,
+            help = "Maximum number of pages to list",
+            default_value = "10"
+        )]
+        limit: usize,
+    },
+}
+
+impl IndexCommand {
+    fn open(&self, url: &str) -> Result<SearchIndex, Box<dyn Error>> {
+        let dir = match &self.dir {
+            Some(dir) => dir.clone(),
+            None => {
+                let name: String = url
+                    .chars()
+                    .map(|c| if c.is_alphanumeric() { c } else { '_' })
+                    .collect();
+                config_dir()
+                    .ok_or("cannot find the config directory, HOME is not set")?
+                    .join("index")
+                    .join(name)
+            }
+        };
+        Ok(SearchIndex::open(dir)?)
+    }
+
+    // searching needs no credentials, so it runs before logging in
+    pub(crate) fn run_offline(
+        &self,
+        url: &str,
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        match &self.command {
+            IndexSubcommand::Search { query, limit } => Some(
+                self.open(url)
+                    .and_then(|index| index_search(&index, query, *limit)),
+            ),
+            IndexSubcommand::Build {} => None,
+        }
+    }
+
+    pub(crate) fn run(
+        &self,
+        api: wikijs::Api,
+        url: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        match &self.command {
+            IndexSubcommand::Build {} => {
+                if is_dry_run() {
+                    return print_dry_run("update the local search index");
+                }
+                let api = api.with_index(self.open(url)?);
+                let update = api.local_index_update()?;
+                for (id, e) in &update.failed {
+                    eprintln!(
+                        "{}: page {}: {}",
+                        "warning".bold().yellow(),
+                        id,
+                        e
+                    );
+                }
+                info!(
+                    "{}: {} pages added, {} updated, {} removed",
+                    "success".bold().green(),
+                    update.added.len(),
+                    update.updated.len(),
+                    update.removed.len()
+                );
+                Ok(())
+            }
+            IndexSubcommand::Search { query, limit } => {
+                index_search(&self.open(url)?, query, *limit)
+            }
+        }
+    }
+}
+
+fn index_search(
+    index: &SearchIndex,
+    query: &str,
+    limit: usize,
+) -> Result<(), Box<dyn Error>> {
+    let hits = index.search(query, limit)?;
+    if print_serialized(&hits)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["id", "score", "locale", "path", "title"]);
+    for hit in hits {
+        table.push_record([
+            hit.id.to_string(),
+            format!("{:.2}", hit.score),
+            hit.locale,
+            hit.path,
+            hit.title,
+        ]);
+    }
+    table.print()?;
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use crate::credentials::prompt_hidden;
+use clap::Subcommand;
+use colored::Colorize;
+use std::error::Error;
+use std::io::{IsTerminal, Read};
+use wikijs::keyring::KeyringCredentials;
+
+const SERVICE: &str = "wikijs";
+
+pub(crate) fn api_key_entry(url: &str) -> KeyringCredentials {
+    KeyringCredentials::new(SERVICE, url.trim_end_matches('/'))
+}
+
+pub(crate) fn session_entry(url: &str) -> KeyringCredentials {
+    KeyringCredentials::new(
+        SERVICE,
+        format!("session:{}", url.trim_end_matches('/')),
+    )
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum KeyringCommand {
+    #[clap(
+        about = "Store an API key for the wiki, read from stdin or asked for"
+    )]
+    Store {},
+
+    #[clap(about = "Delete the API key of the wiki")]
+    Delete {},
+}
+
+impl KeyringCommand {
+    pub(crate) fn run(&self, url: &str) -> Result<(), Box<dyn Error>> {
+        let entry = api_key_entry(url);
+        match self {
+            KeyringCommand::Store {} => {
+                let key = if std::io::stdin().is_terminal() {
+                    prompt_hidden("API key: ")?
+                } else {
+                    let mut key = String::new();
+                    std::io::stdin().read_to_string(&mut key)?;
+                    key.trim().to_string()
+                };
+                if key.is_empty() {
+                    return Err("the API key is empty".into());
+                }
+                entry.set(&key)?;
+                info!("{}: API key stored", "success".bold().green());
+            }
+            KeyringCommand::Delete {} => {
+                entry.delete()?;
+                info!("{}: API key deleted", "success".bold().green());
+            }
+        }
+        Ok(())
+    }
+}
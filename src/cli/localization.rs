@@ -1,26 +1,63 @@
-use crate::common::Execute;
+use crate::common::{is_dry_run, print_dry_run, Execute};
+use crate::output::{print_serialized, Table};
 use clap::Subcommand;
+use colored::Colorize;
 use std::error::Error;
-use tabled::{builder::Builder, settings::Style};
 
 #[derive(Subcommand, Debug)]
 pub(crate) enum LocaleCommand {
     #[clap(about = "List locales")]
     List,
+
+    #[clap(about = "List the translations of a locale namespace")]
+    Translations {
+        #[clap(help = "Locale code, like en")]
+        locale: String,
+
+        #[clap(help = "Namespace, like common")]
+        namespace: String,
+    },
+
+    #[clap(about = "Download and install a locale")]
+    Download {
+        #[clap(help = "Locale code, like de")]
+        locale: String,
+    },
+
+    #[clap(about = "Show the locale configuration")]
+    Config,
 }
 
 impl Execute for LocaleCommand {
     fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
         match self {
             LocaleCommand::List => locale_list(api),
+            LocaleCommand::Translations { locale, namespace } => {
+                translation_list(api, locale.to_string(), namespace.to_string())
+            }
+            LocaleCommand::Download { locale } => {
+                if is_dry_run() {
+                    return print_dry_run(format!(
+                        "download locale {}",
+                        locale
+                    ));
+                }
+                api.locale_download(locale.to_string())?;
+                info!("{}: Locale downloaded", "success".bold().green());
+                Ok(())
+            }
+            LocaleCommand::Config => locale_config(api),
         }
     }
 }
 
 fn locale_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
     let locales = api.locale_list()?;
-    let mut builder = Builder::new();
-    builder.push_record([
+    if print_serialized(&locales)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record([
         "availability",
         "code",
         "created_at",
@@ -32,7 +69,7 @@ fn locale_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
         "updated_at",
     ]);
     for locale in locales {
-        builder.push_record([
+        table.push_record([
             locale.availability.to_string().as_str(),
             locale.code.as_str(),
             locale.created_at.to_string().as_str(),
@@ -44,6 +81,41 @@ fn locale_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
             locale.updated_at.to_string().as_str(),
         ]);
     }
-    println!("{}", builder.build().with(Style::rounded()));
+    table.print()?;
+    Ok(())
+}
+
+fn translation_list(
+    api: wikijs::Api,
+    locale: String,
+    namespace: String,
+) -> Result<(), Box<dyn Error>> {
+    let translations = api.translation_list(locale, namespace)?;
+    if print_serialized(&translations)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["key", "value"]);
+    for translation in translations {
+        table.push_record([translation.key, translation.value]);
+    }
+    table.print()?;
+    Ok(())
+}
+
+fn locale_config(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+    let config = api.locale_config_get()?;
+    if print_serialized(&config)? {
+        return Ok(());
+    }
+    let namespaces: Vec<String> =
+        config.namespaces.into_iter().flatten().collect();
+    let mut table = Table::new();
+    table.push_record(["key", "value"]);
+    table.push_record(["locale", &config.locale]);
+    table.push_record(["auto_update", &config.auto_update.to_string()]);
+    table.push_record(["namespacing", &config.namespacing.to_string()]);
+    table.push_record(["namespaces", &namespaces.join(", ")]);
+    table.print()?;
     Ok(())
 }
@@ -1,7 +1,7 @@
 use crate::common::Execute;
+use crate::output::{print_serialized, Table};
 use clap::Subcommand;
 use std::error::Error;
-use tabled::{builder::Builder, settings::Style};
 
 #[derive(Subcommand, Debug)]
 pub(crate) enum LoggerCommand {
@@ -31,8 +31,11 @@ fn logger_list(
     order_by: Option<String>,
 ) -> Result<(), Box<dyn Error>> {
     let loggers = api.logger_list(filter, order_by)?;
-    let mut builder = Builder::new();
-    builder.push_record([
+    if print_serialized(&loggers)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record([
         "is_enabled",
         "key",
         "title",
@@ -43,7 +46,7 @@ fn logger_list(
         // "config",
     ]);
     for logger in loggers {
-        builder.push_record([
+        table.push_record([
             logger.is_enabled.to_string().as_str(),
             logger.key.as_str(),
             logger.title.as_str(),
@@ -54,6 +57,6 @@ fn logger_list(
             // logger.config.as_str(),
         ]);
     }
-    println!("{}", builder.build().with(Style::rounded()));
+    table.print()?;
     Ok(())
 }
@@ -0,0 +1,221 @@
+use crate::common::{is_dry_run, print_dry_run, Execute};
+use crate::output::{print_serialized, Table};
+use clap::{Args, Subcommand};
+use colored::Colorize;
+use std::error::Error;
+use std::io::BufRead;
+use std::path::PathBuf;
+
+const PASSWORD_ENV: &str = "WIKI_JS_SMTP_PASSWORD";
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum MailCommand {
+    #[clap(about = "Show the mail config with secrets masked")]
+    GetConfig {},
+
+    #[clap(about = "Change the mail config, keeping fields not given")]
+    UpdateConfig {
+        #[clap(flatten)]
+        args: MailConfigArgs,
+    },
+
+    #[clap(about = "Send a test mail")]
+    SendTest {
+        #[clap(help = "Recipient email address")]
+        recipient: String,
+    },
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct MailConfigArgs {
+    #[clap(long, help = "Sender name")]
+    sender_name: Option<String>,
+
+    #[clap(long, help = "Sender email address")]
+    sender_email: Option<String>,
+
+    #[clap(long, help = "SMTP host")]
+    host: Option<String>,
+
+    #[clap(long, help = "SMTP port")]
+    port: Option<i64>,
+
+    #[clap(long, help = "Client name sent to the SMTP server")]
+    name: Option<String>,
+
+    #[clap(long, help = "Use TLS")]
+    secure: Option<bool>,
+
+    #[clap(long, help = "Verify the SSL certificate")]
+    verify_ssl: Option<bool>,
+
+    #[clap(long, help = "SMTP username")]
+    user: Option<String>,
+
+    #[clap(
+        long,
+        help = "Read the SMTP password from the first line of stdin, \
+                otherwise it is taken from WIKI_JS_SMTP_PASSWORD if set"
+    )]
+    password_stdin: bool,
+
+    #[clap(long, help = "Sign mails with DKIM")]
+    use_dkim: Option<bool>,
+
+    #[clap(long, help = "DKIM domain name")]
+    dkim_domain_name: Option<String>,
+
+    #[clap(long, help = "DKIM key selector")]
+    dkim_key_selector: Option<String>,
+
+    #[clap(long, help = "File with the DKIM private key")]
+    dkim_private_key_file: Option<PathBuf>,
+}
+
+impl Execute for MailCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        match self {
+            MailCommand::GetConfig {} => mail_get_config(api),
+            MailCommand::UpdateConfig { args } => mail_update_config(api, args),
+            MailCommand::SendTest { recipient } => {
+                if is_dry_run() {
+                    return print_dry_run(format!(
+                        "send a test mail to {}",
+                        recipient
+                    ));
+                }
+                api.mail_send_test(recipient.to_string())?;
+                info!("{}: Test mail sent", "success".bold().green());
+                Ok(())
+            }
+        }
+    }
+}
+
+fn mask(secret: Option<String>) -> String {
+    match secret {
+        Some(secret) if !secret.is_empty() => "********".to_string(),
+        _ => String::new(),
+    }
+}
+
+fn mail_get_config(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+    let mut config = api.mail_config_get()?;
+    config.pass = Some(mask(config.pass));
+    config.dkim_private_key = Some(mask(config.dkim_private_key));
+    if print_serialized(&config)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["key", "value"]);
+    let to_string =
+        |value: Option<bool>| value.map(|v| v.to_string()).unwrap_or_default();
+    table.push_record(["sender_name", &config.sender_name.unwrap_or_default()]);
+    table.push_record([
+        "sender_email",
+        &config.sender_email.unwrap_or_default(),
+    ]);
+    table.push_record(["host", &config.host.unwrap_or_default()]);
+    table.push_record([
+        "port",
+        &config.port.map(|p| p.to_string()).unwrap_or_default(),
+    ]);
+    table.push_record(["name", &config.name.unwrap_or_default()]);
+    table.push_record(["secure", &to_string(config.secure)]);
+    table.push_record(["verify_ssl", &to_string(config.verify_ssl)]);
+    table.push_record(["user", &config.user.unwrap_or_default()]);
+    table.push_record(["pass", &config.pass.unwrap_or_default()]);
+    table.push_record(["use_dkim", &to_string(config.use_dkim)]);
+    table.push_record([
+        "dkim_domain_name",
+        &config.dkim_domain_name.unwrap_or_default(),
+    ]);
+    table.push_record([
+        "dkim_key_selector",
+        &config.dkim_key_selector.unwrap_or_default(),
+    ]);
+    table.push_record([
+        "dkim_private_key",
+        &config.dkim_private_key.unwrap_or_default(),
+    ]);
+    table.print()?;
+    Ok(())
+}
+
+fn read_password(
+    args: &MailConfigArgs,
+) -> Result<Option<String>, Box<dyn Error>> {
+    if args.password_stdin {
+        let mut line = String::new();
+        std::io::stdin().lock().read_line(&mut line)?;
+        return Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()));
+    }
+    Ok(std::env::var(PASSWORD_ENV).ok())
+}
+
+fn mail_update_config(
+    api: wikijs::Api,
+    args: &MailConfigArgs,
+) -> Result<(), Box<dyn Error>> {
+    let config = api.mail_config_get()?;
+    let pass = read_password(args)?;
+    let dkim_private_key = match &args.dkim_private_key_file {
+        Some(path) => Some(std::fs::read_to_string(path)?),
+        None => None,
+    };
+    if is_dry_run() {
+        let fields: Vec<&str> = [
+            ("sender_name", args.sender_name.is_some()),
+            ("sender_email", args.sender_email.is_some()),
+            ("host", args.host.is_some()),
+            ("port", args.port.is_some()),
+            ("name", args.name.is_some()),
+            ("secure", args.secure.is_some()),
+            ("verify_ssl", args.verify_ssl.is_some()),
+            ("user", args.user.is_some()),
+            ("pass", pass.is_some()),
+            ("use_dkim", args.use_dkim.is_some()),
+            ("dkim_domain_name", args.dkim_domain_name.is_some()),
+            ("dkim_key_selector", args.dkim_key_selector.is_some()),
+            ("dkim_private_key", dkim_private_key.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(field, given)| given.then_some(field))
+        .collect();
+        return print_dry_run(format!(
+            "update {} of the mail config",
+            fields.join(", ")
+        ));
+    }
+    api.mail_config_update(
+        args.sender_name
+            .clone()
+            .or(config.sender_name)
+            .unwrap_or_default(),
+        args.sender_email
+            .clone()
+            .or(config.sender_email)
+            .unwrap_or_default(),
+        args.host.clone().or(config.host).unwrap_or_default(),
+        args.port.or(config.port).unwrap_or(25),
+        args.name.clone().or(config.name).unwrap_or_default(),
+        args.secure.or(config.secure).unwrap_or(true),
+        args.verify_ssl.or(config.verify_ssl).unwrap_or(true),
+        args.user.clone().or(config.user).unwrap_or_default(),
+        pass.or(config.pass).unwrap_or_default(),
+        args.use_dkim.or(config.use_dkim).unwrap_or(false),
+        args.dkim_domain_name
+            .clone()
+            .or(config.dkim_domain_name)
+            .unwrap_or_default(),
+        args.dkim_key_selector
+            .clone()
+            .or(config.dkim_key_selector)
+            .unwrap_or_default(),
+        dkim_private_key
+            .or(config.dkim_private_key)
+            .unwrap_or_default(),
+    )?;
+    info!("{}: Mail config updated", "success".bold().green());
+    Ok(())
+}
@@ -1,71 +1,188 @@
-use clap::{Args, Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 use colored::Colorize;
+use std::error::Error;
+use std::io::Write;
+use std::path::PathBuf;
+use wikijs::cache::Cache;
 use wikijs::{Api, Credentials};
 
+// Success and progress messages, hidden by --quiet. Whatever a command was
+// asked to print, warnings and errors use the std macros and are always shown.
+macro_rules! info {
+    ($($arg:tt)*) => {{
+        if !$crate::common::is_quiet() {
+            println!($($arg)*)
+        }
+    }};
+}
+
+macro_rules! progress {
+    ($($arg:tt)*) => {{
+        if !$crate::common::is_quiet() {
+            eprint!($($arg)*)
+        }
+    }};
+}
+
 mod analytics;
 mod asset;
 mod authentication;
+mod backup;
 mod comment;
 mod common;
+mod config;
 mod contribute;
+mod credentials;
+mod export;
 mod group;
+mod import;
+#[cfg(feature = "index")]
+mod index;
+#[cfg(feature = "keyring")]
+mod keyring;
 mod localization;
 mod logger;
+mod mail;
+mod man;
+mod mirror;
+mod navigation;
+mod output;
 mod page;
+mod rendering;
+mod report;
+mod search;
+mod session;
+mod site;
+mod storage;
+mod sync;
 mod system;
 mod theming;
+#[cfg(feature = "tui")]
+mod tui;
 mod user;
+mod watch;
 
-use crate::common::Execute;
+use crate::common::{
+    exit_code, is_dry_run, print_dry_run, set_assume_yes, set_dry_run,
+    set_quiet, Execute,
+};
+use crate::credentials::{
+    anonymous_client, login, prompt_hidden, CredentialArgs,
+};
+use crate::output::{
+    set_fields, set_output_format, set_table_options, OutputFormat,
+    TableOptions,
+};
+use crate::session::Session;
 
-#[derive(Args, Debug)]
-#[group(required = true, multiple = true)]
-struct CredentialArgs {
-    #[clap(short, long, help = "Wiki.js API key", env = "WIKI_JS_API_KEY")]
-    key: Option<String>,
+#[derive(Parser, Debug)]
+#[command(name = "wikijs-cli")]
+#[command(author = "Sandro-Alessio Gierens <sandro@gierens.de>")]
+#[command(version = "0.2.1")]
+#[command(about = "Command line client for Wiki.js")]
+struct Cli {
+    #[clap(short, long, help = "Wiki.js base URL", env = "WIKI_JS_BASE_URL")]
+    url: Option<String>,
 
     #[clap(
-        short = 'U',
         long,
-        help = "Wiki.js username",
-        env = "WIKI_JS_USERNAME",
-        requires = "password",
-        conflicts_with = "key"
+        help = "Profile of the config file to take defaults from",
+        env = "WIKI_JS_PROFILE"
     )]
-    username: Option<String>,
+    profile: Option<String>,
 
     #[clap(
-        short = 'P',
         long,
-        help = "Wiki.js password",
-        env = "WIKI_JS_PASSWORD",
-        requires = "username",
-        conflicts_with = "key"
+        help = "Output format",
+        env = "WIKI_JS_OUTPUT",
+        value_enum,
+        default_value = "table"
     )]
-    password: Option<String>,
+    output: OutputFormat,
+
+    #[clap(long, help = "Do not color the output, like setting NO_COLOR")]
+    no_color: bool,
+
+    #[clap(long, help = "Leave out the header row of tables")]
+    no_header: bool,
 
     #[clap(
-        short,
         long,
-        help = "Wiki.js authentication provider ID",
-        env = "WIKI_JS_AUTH_PROVIDER",
-        default_value = "local"
+        help = "Only print these columns of tables, in this order",
+        value_delimiter = ',',
+        env = "WIKI_JS_COLUMNS"
     )]
-    provider: Option<String>,
-}
+    columns: Option<Vec<String>>,
 
-#[derive(Parser, Debug)]
-#[command(name = "wikijs-cli")]
-#[command(author = "Sandro-Alessio Gierens <sandro@gierens.de>")]
-#[command(version = "0.2.1")]
-#[command(about = "Command line client for Wiki.js")]
-struct Cli {
-    #[clap(short, long, help = "Wiki.js base URL", env = "WIKI_JS_BASE_URL")]
-    url: String,
+    #[clap(
+        long,
+        help = "Only print these fields of the data, by their name in the \
+                JSON output, in this order and in any output format",
+        value_delimiter = ',',
+        env = "WIKI_JS_FIELDS",
+        conflicts_with = "columns"
+    )]
+    fields: Option<Vec<String>>,
 
     #[clap(flatten)]
     credentials: CredentialArgs,
 
+    #[clap(
+        long,
+        help = "Fail on unexpected server responses",
+        env = "WIKI_JS_STRICT"
+    )]
+    strict: bool,
+
+    #[clap(
+        short,
+        long,
+        help = "API calls bulk commands make at once, like import, export, \
+                sync, backup and tagging [default: 4]",
+        env = "WIKI_JS_JOBS"
+    )]
+    jobs: Option<usize>,
+
+    #[clap(
+        long,
+        help = "Print what mutating commands would do without doing it",
+        env = "WIKI_JS_DRY_RUN"
+    )]
+    dry_run: bool,
+
+    #[clap(
+        short,
+        long,
+        help = "Leave out success and progress messages, the exit code tells \
+                the category of an error: 2 not found, 3 forbidden, 4 \
+                conflict, 5 network, 1 anything else",
+        env = "WIKI_JS_QUIET"
+    )]
+    quiet: bool,
+
+    #[clap(
+        short,
+        long,
+        help = "Delete without asking for confirmation",
+        env = "WIKI_JS_YES"
+    )]
+    yes: bool,
+
+    #[clap(
+        long,
+        help = "Directory to cache pages and assets in",
+        env = "WIKI_JS_CACHE_DIR"
+    )]
+    cache_dir: Option<PathBuf>,
+
+    #[clap(
+        long,
+        help = "Read pages from the cache without contacting the server",
+        env = "WIKI_JS_OFFLINE",
+        requires = "cache_dir"
+    )]
+    offline: bool,
+
     #[clap(subcommand)]
     command: Command,
 }
@@ -84,18 +201,104 @@ enum Command {
         command: asset::AssetFolderCommand,
     },
 
+    #[clap(about = "API key commands")]
+    ApiKey {
+        #[clap(subcommand)]
+        command: authentication::ApiKeyCommand,
+    },
+
     #[clap(about = "Authentication strategy commands")]
     AuthenticationStrategy {
         #[clap(subcommand)]
         command: authentication::AuthenticationStrategyCommand,
     },
 
+    #[clap(about = "Authentication administration commands")]
+    Auth {
+        #[clap(subcommand)]
+        command: authentication::AuthCommand,
+    },
+
     #[clap(about = "Page commands")]
     Page {
         #[clap(subcommand)]
         command: page::PageCommand,
     },
 
+    #[clap(about = "Log in with username and password and keep the session")]
+    Login {
+        #[cfg(feature = "keyring")]
+        #[clap(long, help = "Keep the session in the system keyring")]
+        keyring: bool,
+    },
+
+    #[clap(about = "Forget the session of the wiki")]
+    Logout {},
+
+    #[clap(about = "Register a new account, asking for the password")]
+    Register {
+        #[clap(help = "Email address of the account")]
+        email: String,
+
+        #[clap(help = "Display name of the account")]
+        name: String,
+    },
+
+    #[clap(about = "Send a mail to reset the password of an account")]
+    ForgotPassword {
+        #[clap(help = "Email address of the account")]
+        email: String,
+    },
+
+    #[cfg(feature = "keyring")]
+    #[clap(about = "Manage the API key in the system keyring")]
+    Keyring {
+        #[clap(subcommand)]
+        command: keyring::KeyringCommand,
+    },
+
+    #[clap(about = "Show the user you are logged in as")]
+    Whoami {},
+
+    #[cfg(feature = "tui")]
+    #[clap(about = "Browse and edit the pages in an interactive interface")]
+    Tui {
+        #[clap(
+            short,
+            long,
+            help = "Locale of the pages",
+            default_value = "en",
+            env = "WIKI_JS_LOCALE"
+        )]
+        locale: String,
+
+        #[clap(
+            short,
+            long,
+            help = "Editor to use for editing pages",
+            default_value = "vi",
+            env = "EDITOR"
+        )]
+        editor: String,
+    },
+
+    #[clap(about = "Print the completion script for a shell")]
+    Completion {
+        #[clap(help = "Shell to complete in", value_enum)]
+        shell: clap_complete::Shell,
+    },
+
+    #[clap(about = "Write man pages for all commands")]
+    Man {
+        #[clap(
+            short,
+            long,
+            help = "Directory to write the man pages to",
+            default_value = "."
+        )]
+        output_dir: PathBuf,
+    },
+
     #[clap(about = "Contributor commands")]
     Contributor {
         #[clap(subcommand)]
@@ -150,34 +353,253 @@ enum Command {
         command: logger::LoggerCommand,
     },
 
+    #[clap(about = "Mail commands")]
+    Mail {
+        #[clap(subcommand)]
+        command: mail::MailCommand,
+    },
+
+    #[clap(about = "System commands")]
+    System {
+        #[clap(subcommand)]
+        command: system::SystemCommand,
+    },
+
     #[clap(about = "System flag commands")]
     SystemFlag {
         #[clap(subcommand)]
         command: system::SystemFlagCommand,
     },
 
+    #[clap(about = "Navigation commands")]
+    Navigation {
+        #[clap(subcommand)]
+        command: navigation::NavigationCommand,
+    },
+
+    #[clap(about = "Renderer commands")]
+    Renderer {
+        #[clap(subcommand)]
+        command: rendering::RendererCommand,
+    },
+
+    #[clap(about = "Search engine commands")]
+    SearchEngine {
+        #[clap(subcommand)]
+        command: search::SearchEngineCommand,
+    },
+
+    #[clap(about = "Site config commands")]
+    Site {
+        #[clap(subcommand)]
+        command: site::SiteCommand,
+    },
+
+    #[clap(about = "Storage target commands")]
+    Storage {
+        #[clap(subcommand)]
+        command: storage::StorageCommand,
+    },
+
     #[clap(about = "Theme commands")]
     Theme {
         #[clap(subcommand)]
         command: theming::ThemeCommand,
     },
+
+    #[clap(about = "Back up all pages and assets into an archive")]
+    Backup {
+        #[clap(flatten)]
+        command: backup::BackupCommand,
+    },
+
+    #[clap(about = "Restore the pages and assets of a backup archive")]
+    Restore {
+        #[clap(flatten)]
+        command: backup::RestoreCommand,
+    },
+
+    #[clap(about = "Wiki statistics and activity report")]
+    Report {
+        #[clap(flatten)]
+        command: report::ReportCommand,
+    },
+
+    #[clap(about = "Compare the pages and assets of this wiki with another")]
+    DiffWikis {
+        #[clap(flatten)]
+        command: mirror::DiffWikisCommand,
+    },
+
+    #[clap(about = "Export the wiki to other formats")]
+    Export {
+        #[clap(subcommand)]
+        command: export::ExportCommand,
+    },
+
+    #[clap(about = "Create pages from a directory of markdown files")]
+    Import {
+        #[clap(flatten)]
+        command: import::ImportCommand,
+    },
+
+    #[clap(about = "Mirror the pages and assets of this wiki to another")]
+    Mirror {
+        #[clap(flatten)]
+        command: mirror::MirrorCommand,
+    },
+
+    #[clap(about = "Sync a local directory of page files with the wiki")]
+    Sync {
+        #[clap(subcommand)]
+        command: sync::SyncCommand,
+    },
+
+    #[clap(about = "Run a command or call a webhook when pages change")]
+    Watch {
+        #[clap(flatten)]
+        command: watch::WatchCommand,
+    },
+
+    #[cfg(feature = "index")]
+    #[clap(about = "Search the pages offline in a local full-text index")]
+    Index {
+        #[clap(flatten)]
+        command: index::IndexCommand,
+    },
+}
+
+// print the error and exit with the code of its category
+fn fail(error: &(dyn Error + 'static)) -> ! {
+    eprintln!("{}: {}", "error".bold().red(), error);
+    std::process::exit(exit_code(error));
 }
 
 fn main() {
-    let cli = Cli::parse();
-    let credentials = match cli.credentials.key {
-        Some(key) => Credentials::Key(key),
-        None => {
-            let username = cli.credentials.username.unwrap();
-            let password = cli.credentials.password.unwrap();
-            let provider = cli.credentials.provider.unwrap();
-            Credentials::UsernamePassword(username, password, provider)
+    let command = config::apply_profile(Cli::command())
+        .unwrap_or_else(|e| fail(e.as_ref()));
+    // usage errors exit with 1, as 2 means not found
+    let (mut cli, matches) = command
+        .try_get_matches()
+        .and_then(|matches| Ok((Cli::from_arg_matches(&matches)?, matches)))
+        .unwrap_or_else(|e| match e.use_stderr() {
+            true => {
+                let _ = e.print();
+                std::process::exit(1);
+            }
+            false => e.exit(),
+        });
+    config::drop_profile_credentials(&matches, &mut cli.credentials);
+    set_quiet(cli.quiet);
+    set_output_format(cli.output);
+    set_fields(cli.fields.clone());
+    set_table_options(TableOptions {
+        no_header: cli.no_header,
+        columns: cli.columns.clone(),
+    });
+    if cli.no_color {
+        colored::control::set_override(false);
+    }
+    set_dry_run(cli.dry_run);
+    set_assume_yes(cli.yes);
+    // commands that do not talk to a wiki
+    let result = match cli.command {
+        Command::Completion { shell } => {
+            clap_complete::generate(
+                shell,
+                &mut Cli::command(),
+                env!("CARGO_BIN_NAME"),
+                &mut std::io::stdout(),
+            );
+            Some(Ok(()))
         }
+        Command::Man { ref output_dir } => Some(
+            man::write_man_pages(
+                Cli::command().bin_name(env!("CARGO_BIN_NAME")),
+                output_dir,
+            )
+            .map(|count| {
+                info!("{}: Wrote {} man pages", "success".bold().green(), count)
+            }),
+        ),
+        _ => None,
     };
-    let api = Api::new(cli.url.clone(), credentials).unwrap_or_else(|e| {
-        eprintln!("{}: {}", "error".bold().red(), e);
+    if let Some(result) = result {
+        if let Err(e) = result {
+            fail(e.as_ref());
+        }
+        return;
+    }
+    let url = cli.url.clone().unwrap_or_else(|| {
+        eprintln!(
+            "{}: no wiki URL, pass --url or set WIKI_JS_BASE_URL",
+            "error".bold().red()
+        );
         std::process::exit(1);
     });
+    let cache = cli.cache_dir.as_ref().map(|dir| Cache::new(256).dir(dir));
+    if cli.offline {
+        let result = match (&cli.command, cache) {
+            (Command::Page { command }, Some(cache)) => {
+                command.execute_offline(&cache)
+            }
+            _ => Err("this command is not available offline".into()),
+        };
+        if let Err(e) = result {
+            fail(e.as_ref());
+        }
+        return;
+    }
+    let result = match cli.command {
+        #[cfg(feature = "keyring")]
+        Command::Login { keyring } => {
+            Some(session_login(&url, &cli.credentials, keyring))
+        }
+        #[cfg(not(feature = "keyring"))]
+        Command::Login {} => Some(session_login(&url, &cli.credentials, false)),
+        Command::Logout {} => Some(session_logout(&url)),
+        Command::Register {
+            ref email,
+            ref name,
+        } => Some(register(&url, email, name)),
+        Command::ForgotPassword { ref email } => {
+            Some(forgot_password(&url, email))
+        }
+        #[cfg(feature = "keyring")]
+        Command::Keyring { ref command } => Some(command.run(&url)),
+        #[cfg(feature = "index")]
+        Command::Index { ref command } => command.run_offline(&url),
+        _ => None,
+    };
+    if let Some(result) = result {
+        if let Err(e) = result {
+            fail(e.as_ref());
+        }
+        return;
+    }
+    let credentials = match (&cli.credentials.key, &cli.credentials.username) {
+        (Some(key), _) => Ok(Credentials::Key(key.clone())),
+        (None, Some(_)) => login(&url, &cli.credentials).map(Credentials::Key),
+        (None, None) => {
+            stored_key(&url).map(Credentials::Key).ok_or_else(|| {
+                "no credentials, pass an API key or username and password, \
+                 or log in first"
+                    .into()
+            })
+        }
+    }
+    .unwrap_or_else(|e: Box<dyn Error>| fail(e.as_ref()));
+    let api = Api::new(url.clone(), credentials)
+        .unwrap_or_else(|e| fail(&e))
+        .with_strict_mode(cli.strict);
+    let api = match cli.jobs {
+        Some(jobs) => api.with_concurrency(jobs),
+        None => api,
+    };
+    let api = match cache {
+        Some(cache) => api.with_cache(cache),
+        None => api,
+    };
 
     // TODO each command should be in its own module
     // TODO each subcommand should implement an Execute trait to call here
@@ -185,8 +607,16 @@ fn main() {
     match match cli.command {
         Command::Asset { ref command } => command.execute(api),
         Command::AssetFolder { ref command } => command.execute(api),
+        Command::ApiKey { command } => command.execute(api),
         Command::AuthenticationStrategy { ref command } => command.execute(api),
+        Command::Auth { ref command } => command.execute(api),
         Command::Page { ref command } => command.execute(api),
+        Command::Whoami {} => session::whoami(api),
+        #[cfg(feature = "tui")]
+        Command::Tui {
+            ref locale,
+            ref editor,
+        } => tui::run(api, &url, locale, editor),
         Command::Contributor { ref command } => command.execute(api),
         Command::AnalyticsProvider { command } => command.execute(api),
         Command::Comment { ref command } => command.execute(api),
@@ -196,13 +626,122 @@ fn main() {
         Command::Group { command } => command.execute(api),
         Command::Locale { command } => command.execute(api),
         Command::Logger { command } => command.execute(api),
+        Command::Mail { command } => command.execute(api),
+        Command::System { command } => command.execute(api),
         Command::SystemFlag { command } => command.execute(api),
+        Command::Navigation { command } => command.execute(api),
+        Command::Renderer { command } => command.execute(api),
+        Command::SearchEngine { command } => command.execute(api),
+        Command::Site { command } => command.execute(api),
+        Command::Storage { command } => command.execute(api),
         Command::Theme { command } => command.execute(api),
+        Command::Backup { command } => command.execute(api),
+        Command::Restore { command } => command.execute(api),
+        Command::Report { command } => command.execute(api),
+        Command::DiffWikis { command } => command.execute(api),
+        Command::Export { command } => command.execute(api),
+        Command::Import { command } => command.execute(api),
+        Command::Mirror { command } => command.execute(api),
+        Command::Sync { command } => command.execute(api),
+        Command::Watch { command } => command.execute(api),
+        #[cfg(feature = "index")]
+        Command::Index { ref command } => command.run(api, &url),
+        // handled before the credentials are needed
+        Command::Login { .. }
+        | Command::Logout {}
+        | Command::Register { .. }
+        | Command::ForgotPassword { .. }
+        | Command::Completion { .. }
+        | Command::Man { .. } => unreachable!(),
+        #[cfg(feature = "keyring")]
+        Command::Keyring { .. } => unreachable!(),
     } {
         Ok(_) => {}
-        Err(e) => {
-            eprintln!("{}: {}", "error".bold().red(), e);
-            std::process::exit(1);
-        }
+        Err(e) => fail(e.as_ref()),
+    }
+}
+
+// an API key from the keyring or the token of a previous login
+fn stored_key(url: &str) -> Option<String> {
+    #[cfg(feature = "keyring")]
+    if let Ok(key) = keyring::api_key_entry(url).get() {
+        return Some(key);
+    }
+    Session::load(url).map(|session| session.jwt)
+}
+
+// log in, asking for the username and password if not given, and store
+// the session
+fn session_login(
+    url: &str,
+    credentials: &CredentialArgs,
+    keyring: bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut credentials = credentials.clone();
+    if credentials.username.is_none() {
+        eprint!("Username: ");
+        std::io::stderr().flush()?;
+        let mut username = String::new();
+        std::io::stdin().read_line(&mut username)?;
+        credentials.username = Some(username.trim().to_string());
+    }
+    let session = Session::new(url, login(url, &credentials)?);
+    session.save(keyring)?;
+    match session
+        .expires_at
+        .and_then(|e| chrono::DateTime::from_timestamp(e as i64, 0))
+    {
+        Some(expires_at) => info!(
+            "{}: Logged in until {}",
+            "success".bold().green(),
+            expires_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+        ),
+        None => info!("{}: Logged in", "success".bold().green()),
+    }
+    Ok(())
+}
+
+fn session_logout(url: &str) -> Result<(), Box<dyn Error>> {
+    if !Session::remove(url)? {
+        return Err(format!("not logged in to {}", url).into());
+    }
+    info!("{}: Logged out", "success".bold().green());
+    Ok(())
+}
+
+fn register(url: &str, email: &str, name: &str) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        return print_dry_run(format!("register {} <{}>", name, email));
+    }
+    let password = prompt_hidden("Password: ")?;
+    if prompt_hidden("Repeat password: ")? != password {
+        return Err("the passwords do not match".into());
+    }
+    wikijs::authentication::register(
+        &anonymous_client()?,
+        &format!("{}/graphql", url),
+        email.to_string(),
+        password,
+        name.to_string(),
+    )?;
+    info!("{}: Account registered", "success".bold().green());
+    Ok(())
+}
+
+fn forgot_password(url: &str, email: &str) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        return print_dry_run(format!(
+            "send a password reset mail to {}",
+            email
+        ));
     }
+    wikijs::authentication::password_forgot(
+        &anonymous_client()?,
+        &format!("{}/graphql", url),
+        email.to_string(),
+    )?;
+    info!("{}: Password reset mail sent", "success".bold().green());
+    Ok(())
 }
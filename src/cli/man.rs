@@ -0,0 +1,180 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+// escape text for roff, so dashes, backslashes and leading dots are not
+// taken as markup
+fn escape(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.replace('\\', "\\e").replace('-', "\\-");
+            match line.starts_with(['.', '\'']) {
+                true => format!("\\&{}", line),
+                false => line,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// the help subcommands clap adds are left out, like the hidden ones
+fn documented(command: &clap::Command) -> bool {
+    !command.is_hide_set() && command.get_name() != "help"
+}
+
+fn page_name(command: &clap::Command) -> String {
+    command
+        .get_bin_name()
+        .unwrap_or(command.get_name())
+        .replace(' ', "-")
+}
+
+fn render_options(command: &clap::Command, page: &mut String) {
+    let args: Vec<_> = command
+        .get_arguments()
+        .filter(|arg| !arg.is_hide_set())
+        .collect();
+    if args.is_empty() {
+        return;
+    }
+    page.push_str(".SH OPTIONS\n");
+    for arg in args {
+        let mut names = Vec::new();
+        if let Some(short) = arg.get_short() {
+            names.push(format!("\\fB\\-{}\\fR", short));
+        }
+        if let Some(long) = arg.get_long() {
+            names.push(format!("\\fB\\-\\-{}\\fR", escape(long)));
+        }
+        let value = arg
+            .get_value_names()
+            .map(|names| names.join(" "))
+            .unwrap_or_else(|| arg.get_id().to_string().to_uppercase());
+        let takes_value = arg.get_action().takes_values();
+        let mut term = names.join(", ");
+        if arg.is_positional() {
+            term = format!("<{}>", escape(&value));
+        } else if takes_value {
+            term.push_str(&format!(" <{}>", escape(&value)));
+        }
+        page.push_str(&format!(".TP\n{}\n", term));
+        let mut help =
+            arg.get_help().map(|h| h.to_string()).unwrap_or_default();
+        let possible: Vec<_> = arg
+            .get_possible_values()
+            .iter()
+            .filter(|value| !value.is_hide_set())
+            .map(|value| value.get_name().to_string())
+            .collect();
+        if takes_value && !possible.is_empty() {
+            help.push_str(&format!(
+                " [possible values: {}]",
+                possible.join(", ")
+            ));
+        }
+        let defaults: Vec<_> = arg
+            .get_default_values()
+            .iter()
+            .map(|value| value.to_string_lossy())
+            .collect();
+        if !defaults.is_empty() {
+            help.push_str(&format!(" [default: {}]", defaults.join(", ")));
+        }
+        if let Some(env) = arg.get_env() {
+            help.push_str(&format!(" [env: {}]", env.to_string_lossy()));
+        }
+        page.push_str(&escape(help.trim()));
+        page.push('\n');
+    }
+}
+
+fn render(command: &clap::Command, version: &str) -> String {
+    let name = page_name(command);
+    let mut page = format!(
+        ".TH {} 1 \"\" \"{}\"\n",
+        escape(&name.to_uppercase()),
+        escape(version)
+    );
+    let about = command.get_about().map(|a| a.to_string());
+    page.push_str(".SH NAME\n");
+    match &about {
+        Some(about) => {
+            page.push_str(&format!("{} \\- {}\n", escape(&name), escape(about)))
+        }
+        None => page.push_str(&format!("{}\n", escape(&name))),
+    }
+    let usage = command.clone().render_usage().to_string();
+    page.push_str(".SH SYNOPSIS\n");
+    for line in usage.trim_start_matches("Usage:").lines() {
+        page.push_str(&format!("{}\n.br\n", escape(line.trim())));
+    }
+    if let Some(about) =
+        command.get_long_about().map(|a| a.to_string()).or(about)
+    {
+        page.push_str(&format!(".SH DESCRIPTION\n{}\n", escape(&about)));
+    }
+    render_options(command, &mut page);
+    let subcommands: Vec<_> = command
+        .get_subcommands()
+        .filter(|subcommand| documented(subcommand))
+        .collect();
+    if !subcommands.is_empty() {
+        page.push_str(".SH COMMANDS\n");
+        for subcommand in &subcommands {
+            page.push_str(&format!(
+                ".TP\n\\fB{}\\fR\n{}\nSee \\fB{}\\fR(1).\n",
+                escape(subcommand.get_name()),
+                escape(
+                    &subcommand
+                        .get_about()
+                        .map(|a| a.to_string())
+                        .unwrap_or_default()
+                ),
+                escape(&page_name(subcommand))
+            ));
+        }
+    }
+    if let Some(author) = command.get_author() {
+        page.push_str(&format!(".SH AUTHORS\n{}\n", escape(author)));
+    }
+    page
+}
+
+fn write_recursive(
+    command: &clap::Command,
+    version: &str,
+    dir: &Path,
+    written: &mut usize,
+) -> Result<(), Box<dyn Error>> {
+    fs::write(
+        dir.join(format!("{}.1", page_name(command))),
+        render(command, version),
+    )?;
+    *written += 1;
+    for subcommand in command
+        .get_subcommands()
+        .filter(|subcommand| documented(subcommand))
+    {
+        write_recursive(subcommand, version, dir, written)?;
+    }
+    Ok(())
+}
+
+/// Write a man page for the command and every subcommand into the
+/// directory, named like `wikijs-page-list.1`, and return how many were
+/// written.
+pub(crate) fn write_man_pages(
+    mut command: clap::Command,
+    dir: &Path,
+) -> Result<usize, Box<dyn Error>> {
+    command.build();
+    fs::create_dir_all(dir)?;
+    let version = format!(
+        "{} {}",
+        page_name(&command),
+        command.get_version().unwrap_or_default()
+    );
+    let mut written = 0;
+    write_recursive(&command, &version, dir, &mut written)?;
+    Ok(written)
+}
@@ -0,0 +1,147 @@
+use crate::common::{is_dry_run, Execute};
+use crate::output::{print_serialized, Table};
+use crate::page::PageFilterArgs;
+use clap::Args;
+use colored::Colorize;
+use std::error::Error;
+use wikijs::mirror::MirrorOptions;
+use wikijs::{Api, Credentials};
+
+#[derive(Args, Debug)]
+pub(crate) struct MirrorCommand {
+    #[clap(
+        long,
+        help = "Base URL of the destination wiki",
+        env = "WIKI_JS_DEST_BASE_URL"
+    )]
+    dest_url: String,
+
+    #[clap(
+        long,
+        help = "API key of the destination wiki",
+        env = "WIKI_JS_DEST_API_KEY"
+    )]
+    dest_key: String,
+
+    #[clap(flatten)]
+    filter: PageFilterArgs,
+
+    #[clap(long, help = "Delete pages and assets missing on the source")]
+    delete: bool,
+
+    #[clap(long, help = "Do not mirror assets")]
+    skip_assets: bool,
+
+    #[clap(short, long, help = "Only print the planned changes")]
+    dry_run: bool,
+}
+
+impl Execute for MirrorCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        let dest = Api::new(
+            self.dest_url.clone(),
+            Credentials::Key(self.dest_key.clone()),
+        )?
+        .with_strict_mode(api.strict_mode())
+        .with_concurrency(api.concurrency());
+        let plan = api.mirror_plan(
+            &dest,
+            &MirrorOptions {
+                filter: (&self.filter).into(),
+                delete: self.delete,
+                skip_assets: self.skip_assets,
+            },
+        )?;
+
+        let dry_run = self.dry_run || is_dry_run();
+        let rows: Vec<(String, Option<String>)> = if dry_run {
+            plan.actions
+                .iter()
+                .map(|action| (action.to_string(), None))
+                .collect()
+        } else {
+            api.mirror_apply(&dest, &plan, None)
+                .into_iter()
+                .map(|(action, result)| {
+                    let status = match result {
+                        Ok(()) => "ok".to_string(),
+                        Err(e) => e.to_string(),
+                    };
+                    (action.to_string(), Some(status))
+                })
+                .collect()
+        };
+        let records: Vec<_> = rows
+            .iter()
+            .map(|(action, status)| match status {
+                Some(status) => {
+                    serde_json::json!({"action": action, "status": status})
+                }
+                None => serde_json::json!({ "action": action }),
+            })
+            .collect();
+        if print_serialized(&records)? {
+            return Ok(());
+        }
+        let mut table = Table::new();
+        if dry_run {
+            table.push_record(["action"]);
+        } else {
+            table.push_record(["action", "status"]);
+        }
+        for (action, status) in rows {
+            table.push_record([Some(action), status].into_iter().flatten());
+        }
+        table.print()?;
+        Ok(())
+    }
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct DiffWikisCommand {
+    #[clap(
+        long,
+        help = "Base URL of the wiki to compare with",
+        env = "WIKI_JS_OTHER_BASE_URL"
+    )]
+    other_url: String,
+
+    #[clap(
+        long,
+        help = "API key of the wiki to compare with",
+        env = "WIKI_JS_OTHER_API_KEY"
+    )]
+    other_key: String,
+
+    #[clap(flatten)]
+    filter: PageFilterArgs,
+
+    #[clap(long, help = "Do not compare assets")]
+    skip_assets: bool,
+}
+
+impl Execute for DiffWikisCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        let other = Api::new(
+            self.other_url.clone(),
+            Credentials::Key(self.other_key.clone()),
+        )?
+        .with_strict_mode(api.strict_mode())
+        .with_concurrency(api.concurrency());
+        let diff =
+            api.wiki_diff(&other, &(&self.filter).into(), self.skip_assets)?;
+        for (names, kind, sign) in [
+            (&diff.pages_added, "page", "+".green()),
+            (&diff.pages_removed, "page", "-".red()),
+            (&diff.pages_modified, "page", "~".yellow()),
+            (&diff.assets_added, "asset", "+".green()),
+            (&diff.assets_removed, "asset", "-".red()),
+            (&diff.assets_modified, "asset", "~".yellow()),
+        ] {
+            for name in names {
+                println!("{} {} {}", sign, kind, name);
+            }
+        }
+        Ok(())
+    }
+}
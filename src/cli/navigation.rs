@@ -0,0 +1,259 @@
+use crate::common::{is_dry_run, print_dry_run, Execute};
+use crate::output::{print_serialized, Table};
+use clap::{Subcommand, ValueEnum};
+use colored::Colorize;
+use std::error::Error;
+use wikijs::navigation::{NavigationItemInput, NavigationMode};
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub(crate) enum NavigationItemKind {
+    Link,
+    Header,
+    Divider,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum NavigationCommand {
+    #[clap(about = "Show the navigation mode and tree")]
+    Get {
+        #[clap(
+            short,
+            long,
+            help = "Navigation locale",
+            default_value = "en",
+            env = "WIKI_JS_LOCALE"
+        )]
+        locale: String,
+    },
+
+    #[clap(about = "Set the navigation mode")]
+    SetMode {
+        #[clap(help = "Navigation mode (none, tree, mixed, static)")]
+        mode: NavigationMode,
+    },
+
+    #[clap(about = "Add an item to the navigation")]
+    AddItem {
+        #[clap(
+            short,
+            long,
+            help = "Navigation locale",
+            default_value = "en",
+            env = "WIKI_JS_LOCALE"
+        )]
+        locale: String,
+
+        #[clap(short, long, help = "Item kind", default_value = "link")]
+        kind: NavigationItemKind,
+
+        #[clap(short = 'L', long, help = "Item label")]
+        label: Option<String>,
+
+        #[clap(
+            short = 'T',
+            long,
+            help = "Link target type (page, external, externalblank, home, \
+                    search)",
+            default_value = "page"
+        )]
+        target_type: String,
+
+        #[clap(short, long, help = "Link target, a page path or URL")]
+        target: Option<String>,
+
+        #[clap(short, long, help = "Item icon, like mdi-home")]
+        icon: Option<String>,
+
+        #[clap(short, long, help = "Position to insert at, default is last")]
+        position: Option<usize>,
+    },
+
+    #[clap(about = "Remove an item from the navigation")]
+    RemoveItem {
+        #[clap(
+            short,
+            long,
+            help = "Navigation locale",
+            default_value = "en",
+            env = "WIKI_JS_LOCALE"
+        )]
+        locale: String,
+
+        #[clap(help = "Item ID")]
+        id: String,
+    },
+
+    #[clap(about = "Move an item of the navigation")]
+    MoveItem {
+        #[clap(
+            short,
+            long,
+            help = "Navigation locale",
+            default_value = "en",
+            env = "WIKI_JS_LOCALE"
+        )]
+        locale: String,
+
+        #[clap(help = "Item ID")]
+        id: String,
+
+        #[clap(help = "New position of the item")]
+        position: usize,
+    },
+}
+
+impl Execute for NavigationCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        match self {
+            NavigationCommand::Get { locale } => navigation_get(api, locale),
+            NavigationCommand::SetMode { mode } => {
+                if is_dry_run() {
+                    return print_dry_run(format!(
+                        "set the navigation mode to {:?}",
+                        mode
+                    ));
+                }
+                api.navigation_config_update(mode.clone())?;
+                info!("{}: Navigation mode set", "success".bold().green());
+                Ok(())
+            }
+            NavigationCommand::AddItem {
+                locale,
+                kind,
+                label,
+                target_type,
+                target,
+                icon,
+                position,
+            } => {
+                let label = label.as_deref().unwrap_or_default();
+                let mut item = match kind {
+                    NavigationItemKind::Link => NavigationItemInput::link(
+                        label,
+                        target_type,
+                        target.as_deref().unwrap_or_default(),
+                    ),
+                    NavigationItemKind::Header => {
+                        NavigationItemInput::header(label)
+                    }
+                    NavigationItemKind::Divider => {
+                        NavigationItemInput::divider()
+                    }
+                };
+                if let Some(icon) = icon {
+                    item = item.icon(icon);
+                }
+                let id = item.id.clone();
+                if is_dry_run() {
+                    let position = match position {
+                        Some(position) => format!("at position {}", position),
+                        None => "at the end".to_string(),
+                    };
+                    return print_dry_run(format!(
+                        "add {} {} {} of the {} navigation",
+                        item.kind, label, position, locale
+                    ));
+                }
+                api.navigation_tree_edit(locale, |tree| {
+                    tree.insert(*position, item)
+                })?;
+                info!(
+                    "{}: Navigation item {} added",
+                    "success".bold().green(),
+                    id
+                );
+                Ok(())
+            }
+            NavigationCommand::RemoveItem { locale, id } => {
+                if is_dry_run() {
+                    return print_dry_run(format!(
+                        "remove {} from the {} navigation",
+                        navigation_item_label(&api, locale, id)?,
+                        locale
+                    ));
+                }
+                api.navigation_tree_edit(locale, |tree| {
+                    tree.remove(id).map(|_| ())
+                })?;
+                info!("{}: Navigation item removed", "success".bold().green());
+                Ok(())
+            }
+            NavigationCommand::MoveItem {
+                locale,
+                id,
+                position,
+            } => {
+                if is_dry_run() {
+                    return print_dry_run(format!(
+                        "move {} to position {} of the {} navigation",
+                        navigation_item_label(&api, locale, id)?,
+                        position,
+                        locale
+                    ));
+                }
+                api.navigation_tree_edit(locale, |tree| {
+                    tree.move_item(id, *position)
+                })?;
+                info!("{}: Navigation item moved", "success".bold().green());
+                Ok(())
+            }
+        }
+    }
+}
+
+// an item id with its label, to show what a dry run would touch
+fn navigation_item_label(
+    api: &wikijs::Api,
+    locale: &str,
+    id: &str,
+) -> Result<String, Box<dyn Error>> {
+    let item = api
+        .navigation_tree_get()?
+        .into_iter()
+        .filter(|tree| tree.locale == locale)
+        .flat_map(|tree| tree.items.into_iter().flatten())
+        .find(|item| item.id == id)
+        .ok_or(format!("navigation item {} not found", id))?;
+    Ok(match item.label {
+        Some(label) => format!("{} {} ({})", item.kind, id, label),
+        None => format!("{} {}", item.kind, id),
+    })
+}
+
+fn navigation_get(
+    api: wikijs::Api,
+    locale: &str,
+) -> Result<(), Box<dyn Error>> {
+    let config = api.navigation_config_get()?;
+    let trees = api.navigation_tree_get()?;
+    let items: Vec<_> = trees
+        .iter()
+        .filter(|tree| tree.locale == locale)
+        .flat_map(|tree| tree.items.iter().flatten())
+        .collect();
+    if print_serialized(&items)? {
+        return Ok(());
+    }
+    println!("mode: {:?}", config.mode);
+    let mut table = Table::new();
+    table.push_record([
+        "position",
+        "id",
+        "kind",
+        "label",
+        "target_type",
+        "target",
+    ]);
+    for (position, item) in items.into_iter().enumerate() {
+        table.push_record([
+            position.to_string(),
+            item.id.clone(),
+            item.kind.clone(),
+            item.label.clone().unwrap_or_default(),
+            item.target_type.clone().unwrap_or_default(),
+            item.target.clone().unwrap_or_default(),
+        ]);
+    }
+    table.print()?;
+    Ok(())
+}
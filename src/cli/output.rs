@@ -0,0 +1,300 @@
+use std::error::Error;
+use tabled::{builder::Builder, settings::Style};
+
+/// Format of the data printed by the commands.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+    Yaml,
+    Csv,
+}
+
+static OUTPUT_FORMAT: std::sync::OnceLock<OutputFormat> =
+    std::sync::OnceLock::new();
+
+pub(crate) fn set_output_format(format: OutputFormat) {
+    let _ = OUTPUT_FORMAT.set(format);
+}
+
+pub(crate) fn output_format() -> OutputFormat {
+    OUTPUT_FORMAT.get().copied().unwrap_or_default()
+}
+
+static FIELDS: std::sync::OnceLock<Option<Vec<String>>> =
+    std::sync::OnceLock::new();
+
+/// Only print these fields of the data, in this order and in any format.
+pub(crate) fn set_fields(fields: Option<Vec<String>>) {
+    let _ = FIELDS.set(fields);
+}
+
+fn fields() -> Option<Vec<String>> {
+    FIELDS.get().cloned().flatten()
+}
+
+static TABLE_OPTIONS: std::sync::OnceLock<TableOptions> =
+    std::sync::OnceLock::new();
+
+/// How the tables of all commands are printed.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TableOptions {
+    /// Leave out the header row.
+    pub(crate) no_header: bool,
+    /// Only print these columns, in this order.
+    pub(crate) columns: Option<Vec<String>>,
+}
+
+pub(crate) fn set_table_options(options: TableOptions) {
+    let _ = TABLE_OPTIONS.set(options);
+}
+
+fn table_options() -> TableOptions {
+    TABLE_OPTIONS.get().cloned().unwrap_or_default()
+}
+
+/// A table whose first record is the header, printed the same way by all
+/// commands.
+#[derive(Debug, Default)]
+pub(crate) struct Table {
+    records: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn push_record<R>(&mut self, record: R)
+    where
+        R: IntoIterator,
+        R::Item: Into<String>,
+    {
+        self.records
+            .push(record.into_iter().map(Into::into).collect());
+    }
+
+    // pick the columns given with --columns by their header
+    fn select(
+        records: Vec<Vec<String>>,
+        columns: &[String],
+    ) -> Result<Vec<Vec<String>>, Box<dyn Error>> {
+        let header = records.first().cloned().unwrap_or_default();
+        let indices = columns
+            .iter()
+            .map(|column| {
+                header.iter().position(|h| h == column).ok_or_else(|| {
+                    format!(
+                        "unknown column {}, the table has {}",
+                        column,
+                        header.join(", ")
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(records
+            .into_iter()
+            .map(|record| {
+                indices
+                    .iter()
+                    .map(|i| record.get(*i).cloned().unwrap_or_default())
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Print the table with the options given on the command line.
+    pub(crate) fn print(self) -> Result<(), Box<dyn Error>> {
+        let options = table_options();
+        let mut records = match &options.columns {
+            Some(columns) => Self::select(self.records, columns)?,
+            None => self.records,
+        };
+        if options.no_header && !records.is_empty() {
+            records.remove(0);
+        }
+        if records.is_empty() {
+            return Ok(());
+        }
+        let mut builder = Builder::new();
+        for record in records {
+            builder.push_record(record);
+        }
+        println!("{}", builder.build().with(Style::rounded()));
+        Ok(())
+    }
+}
+
+// a value as text, nested values as JSON
+fn csv_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn csv_field(value: &serde_json::Value) -> String {
+    let text = csv_text(value);
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+// CSV with a column per field of the objects, nested values as JSON
+fn to_csv(value: serde_json::Value) -> String {
+    let rows = match value {
+        serde_json::Value::Array(rows) => rows,
+        value => vec![value],
+    };
+    let mut columns: Vec<String> = Vec::new();
+    for row in &rows {
+        if let serde_json::Value::Object(fields) = row {
+            for key in fields.keys() {
+                if !columns.contains(key) {
+                    columns.push(key.clone());
+                }
+            }
+        }
+    }
+    let mut csv = String::new();
+    if columns.is_empty() {
+        for row in &rows {
+            csv.push_str(&csv_field(row));
+            csv.push('\n');
+        }
+        return csv;
+    }
+    csv.push_str(&columns.join(","));
+    csv.push('\n');
+    for row in &rows {
+        let fields: Vec<String> = columns
+            .iter()
+            .map(|c| csv_field(row.get(c).unwrap_or(&serde_json::Value::Null)))
+            .collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+// the fields of an object in the order they were picked in
+struct Projection(Vec<(String, serde_json::Value)>);
+
+impl serde::Serialize for Projection {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in &self.0 {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+// pick the fields given with --fields by their serialized name from each
+// object of the data
+fn project(
+    value: serde_json::Value,
+    fields: &[String],
+) -> Result<Vec<Projection>, Box<dyn Error>> {
+    let rows = match value {
+        serde_json::Value::Array(rows) => rows,
+        value => vec![value],
+    };
+    let mut known: Vec<String> = Vec::new();
+    for row in &rows {
+        if let serde_json::Value::Object(object) = row {
+            for key in object.keys() {
+                if !known.contains(key) {
+                    known.push(key.clone());
+                }
+            }
+        }
+    }
+    if !rows.is_empty() {
+        if let Some(field) = fields.iter().find(|f| !known.contains(f)) {
+            return Err(format!(
+                "unknown field {}, the data has {}",
+                field,
+                known.join(", ")
+            )
+            .into());
+        }
+    }
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            Projection(
+                fields
+                    .iter()
+                    .map(|field| {
+                        let value = row
+                            .get(field)
+                            .cloned()
+                            .unwrap_or(serde_json::Value::Null);
+                        (field.clone(), value)
+                    })
+                    .collect(),
+            )
+        })
+        .collect())
+}
+
+// print the picked fields in the output format, tables included
+fn print_projection(
+    rows: Vec<Projection>,
+    fields: &[String],
+) -> Result<(), Box<dyn Error>> {
+    match output_format() {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.push_record(fields.iter().cloned());
+            for row in rows {
+                table.push_record(row.0.iter().map(|(_, v)| csv_text(v)));
+            }
+            table.print()?;
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&rows)?)
+        }
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&rows)?),
+        OutputFormat::Csv => {
+            println!("{}", fields.join(","));
+            for row in rows {
+                let values: Vec<String> =
+                    row.0.iter().map(|(_, v)| csv_field(v)).collect();
+                println!("{}", values.join(","));
+            }
+        }
+    }
+    Ok(())
+}
+
+// print the data in the machine readable output format, returns false for
+// the table format so the caller prints its table instead, unless fields
+// were picked with --fields, which are printed in any format
+pub(crate) fn print_serialized<T: serde::Serialize + ?Sized>(
+    data: &T,
+) -> Result<bool, Box<dyn Error>> {
+    if let Some(fields) = fields() {
+        let rows = project(serde_json::to_value(data)?, &fields)?;
+        print_projection(rows, &fields)?;
+        return Ok(true);
+    }
+    match output_format() {
+        OutputFormat::Table => return Ok(false),
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(data)?)
+        }
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(data)?),
+        OutputFormat::Csv => print!("{}", to_csv(serde_json::to_value(data)?)),
+    }
+    Ok(true)
+}
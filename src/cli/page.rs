@@ -1,11 +1,40 @@
-use crate::common::Execute;
-use clap::Subcommand;
+use crate::common::{
+    confirm, is_dry_run, open_url, parse_duration, print_dry_run, Execute,
+    PagingArgs,
+};
+use crate::output::{print_serialized, Table};
+use chrono::{DateTime, Utc};
+use clap::{ArgAction, Args, Subcommand, ValueEnum};
 use colored::Colorize;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::error::Error;
 use std::io::Error as IoError;
-use std::io::Write;
-use tabled::{builder::Builder, settings::Style};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use tempfile::Builder as TempFileBuilder;
+use wikijs::cache::{Cache, CachedPage};
+use wikijs::graph::GraphFormat;
+use wikijs::page::{
+    parse_frontmatter_markdown, PageDiff, PageEditor, PageError,
+    PageFrontMatter, PageMerge, PageOrderBy, PageOrderByDirection,
+    PageTreeItem,
+};
+use wikijs::replace::{PageFilter, ReplacePattern};
+use wikijs::template::TemplateOptions;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub(crate) enum PageFormat {
+    /// The raw content, whatever the editor
+    Markdown,
+    /// The rendered content
+    Html,
+    /// All fields of the page
+    Json,
+    /// The content with the metadata as YAML front matter
+    Frontmatter,
+}
 
 #[derive(Subcommand, Debug)]
 pub(crate) enum PageCommand {
@@ -13,19 +42,61 @@ pub(crate) enum PageCommand {
     Get {
         #[clap(help = "Page ID")]
         id: i64,
+
+        #[clap(
+            short,
+            long,
+            help = "Print the page in this format instead of a table",
+            value_enum
+        )]
+        format: Option<PageFormat>,
     },
 
     #[clap(about = "List pages")]
-    // TODO support all arguments of page_list
-    List {},
+    List {
+        #[clap(flatten)]
+        filter: PageListArgs,
+
+        #[clap(flatten)]
+        paging: PagingArgs,
+    },
+
+    #[clap(about = "Search pages")]
+    Search {
+        #[clap(help = "Search query")]
+        query: String,
+
+        #[clap(short, long, help = "Only pages below this path")]
+        path: Option<String>,
+
+        #[clap(short, long, help = "Only pages of this locale")]
+        locale: Option<String>,
+
+        #[clap(short, long, help = "Open the first result in the browser")]
+        open: bool,
+    },
 
     #[clap(about = "Show page tree")]
     Tree {
-        #[clap(help = "Parent tree item ID")]
-        parent_id: i64,
+        #[clap(
+            short,
+            long,
+            help = "Parent tree item ID, the root by default",
+            default_value = "0"
+        )]
+        parent: i64,
 
-        #[clap(short, long, help = "Page tree locale", default_value = "en")]
+        #[clap(
+            short,
+            long,
+            help = "Page tree locale",
+            default_value = "en",
+            env = "WIKI_JS_LOCALE"
+        )]
         locale: String,
+
+        #[clap(short, long, help = "Levels to show below the parent")]
+        depth: Option<usize>,
     },
 
     #[clap(about = "Delete a page")]
@@ -42,36 +113,39 @@ pub(crate) enum PageCommand {
 
     #[clap(about = "Create a page")]
     Create {
-        #[clap(short, long, help = "Page content", default_value = "...")]
-        content: String,
-
-        #[clap(short, long, help = "Page description", default_value = "")]
-        description: String,
-
-        #[clap(short, long, help = "Page editor", default_value = "markdown")]
-        editor: String,
+        #[clap(short, long, help = "Page content", conflicts_with = "file")]
+        content: Option<String>,
 
         #[clap(
-            short = 'p',
+            short,
             long,
-            help = "Page is private",
-            default_value = "false"
+            help = "File to read the content and front matter from, - for \
+                    stdin"
         )]
+        file: Option<PathBuf>,
+
+        #[clap(short, long, help = "Page description")]
+        description: Option<String>,
+
+        #[clap(short, long, help = "Page editor")]
+        editor: Option<PageEditor>,
+
+        #[clap(short = 'p', long, help = "Page is private")]
         is_private: bool,
 
         #[clap(
             short = 'P',
             long,
             help = "Page is published",
-            default_value = "true"
+            action = ArgAction::Set
         )]
-        is_published: bool,
+        is_published: Option<bool>,
 
-        #[clap(short, long, help = "Page locale", default_value = "en")]
-        locale: String,
+        #[clap(short, long, help = "Page locale", env = "WIKI_JS_LOCALE")]
+        locale: Option<String>,
 
-        #[clap(help = "Page path")]
-        path: String,
+        #[clap(help = "Page path, taken from the front matter if not given")]
+        path: Option<String>,
 
         // #[clap(help = "Page publish start date")]
         // publish_start_date: Option<String>,
@@ -91,6 +165,71 @@ pub(crate) enum PageCommand {
         title: Option<String>,
     },
 
+    #[clap(about = "Create a page from a template")]
+    New {
+        #[clap(help = "Page path")]
+        path: String,
+
+        #[clap(short, long, help = "Template path below the template prefix")]
+        template: String,
+
+        #[clap(
+            short = 'V',
+            long = "var",
+            help = "Template variable as name=value",
+            value_parser = parse_var
+        )]
+        vars: Vec<(String, String)>,
+
+        #[clap(
+            short,
+            long,
+            help = "Page locale",
+            default_value = "en",
+            env = "WIKI_JS_LOCALE"
+        )]
+        locale: String,
+
+        #[clap(
+            long,
+            help = "Path prefix of the templates",
+            default_value = "templates"
+        )]
+        template_prefix: String,
+    },
+
+    #[clap(about = "Publish a page right away")]
+    Publish {
+        #[clap(help = "Page ID")]
+        id: i64,
+    },
+
+    #[clap(about = "Unpublish a page")]
+    Unpublish {
+        #[clap(help = "Page ID")]
+        id: i64,
+    },
+
+    #[clap(about = "Publish a page within a time frame")]
+    Schedule {
+        #[clap(help = "Page ID")]
+        id: i64,
+
+        #[clap(
+            short,
+            long,
+            help = "Publish start as RFC 3339 date, e.g. 2024-05-01T08:00:00Z"
+        )]
+        start: Option<DateTime<Utc>>,
+
+        #[clap(
+            short,
+            long,
+            help = "Publish end as RFC 3339 date, e.g. 2024-06-01T08:00:00Z"
+        )]
+        end: Option<DateTime<Utc>>,
+    },
+
     #[clap(about = "Update a page")]
     Update {
         #[clap(help = "Page ID")]
@@ -103,7 +242,7 @@ pub(crate) enum PageCommand {
         description: Option<String>,
 
         #[clap(short, long, help = "Page editor")]
-        editor: Option<String>,
+        editor: Option<PageEditor>,
 
         #[clap(short = 'P', long, help = "Page is private")]
         is_private: Option<bool>,
@@ -155,8 +294,17 @@ pub(crate) enum PageCommand {
 
     #[clap(about = "Edit a page")]
     Edit {
-        #[clap(help = "Page ID")]
-        id: i64,
+        #[clap(help = "Page ID or path")]
+        page: String,
+
+        #[clap(
+            short,
+            long,
+            help = "Page locale, if the page is given by path",
+            default_value = "en",
+            env = "WIKI_JS_LOCALE"
+        )]
+        locale: String,
 
         #[clap(
             short,
@@ -167,20 +315,270 @@ pub(crate) enum PageCommand {
         )]
         editor: String,
     },
+
+    #[clap(about = "Open a page in the browser")]
+    Open {
+        #[clap(help = "Page ID or path")]
+        page: String,
+
+        #[clap(
+            short,
+            long,
+            help = "Page locale, if the page is given by path",
+            default_value = "en",
+            env = "WIKI_JS_LOCALE"
+        )]
+        locale: String,
+
+        #[clap(short, long, help = "Open the editor instead of the page")]
+        edit: bool,
+    },
+
+    #[clap(about = "Show the version history of a page")]
+    History {
+        #[clap(help = "Page ID")]
+        id: i64,
+
+        #[clap(short = 'p', long, help = "Page of the history to show")]
+        offset_page: Option<i64>,
+
+        #[clap(short = 's', long, help = "Number of entries per page")]
+        offset_size: Option<i64>,
+    },
+
+    #[clap(about = "Restore a version of a page")]
+    Restore {
+        #[clap(help = "Page ID")]
+        id: i64,
+
+        #[clap(help = "Version ID")]
+        version: i64,
+    },
+
+    #[clap(about = "Show the changes between versions of a page")]
+    Diff {
+        #[clap(help = "Page ID")]
+        id: i64,
+
+        #[clap(
+            short,
+            long,
+            help = "Version to diff from, the latest by default"
+        )]
+        from: Option<i64>,
+
+        #[clap(
+            short,
+            long,
+            help = "Version to diff to, or current",
+            default_value = "current",
+            value_parser = parse_version
+        )]
+        to: DiffTarget,
+
+        #[clap(short, long, help = "Only show the number of changed lines")]
+        stat: bool,
+    },
+
+    #[clap(about = "Flush the page cache of the wiki")]
+    FlushCache {},
+
+    #[clap(about = "Rebuild the page tree")]
+    RebuildTree {},
+
+    #[clap(about = "Delete page versions older than a period")]
+    PurgeHistory {
+        #[clap(
+            short,
+            long,
+            help = "Age of the versions to delete, like 30d, 26w or 1y",
+            value_parser = parse_duration
+        )]
+        older_than: chrono::Duration,
+    },
+
+    #[clap(about = "Move all pages from one locale to another")]
+    MigrateLocale {
+        #[clap(short, long, help = "Locale to move the pages from")]
+        from: String,
+
+        #[clap(short, long, help = "Locale to move the pages to")]
+        to: String,
+    },
+
+    #[clap(about = "Export the page link graph")]
+    Graph {
+        #[clap(
+            short,
+            long,
+            help = "Page locale",
+            default_value = "en",
+            env = "WIKI_JS_LOCALE"
+        )]
+        locale: String,
+
+        #[clap(
+            short,
+            long,
+            help = "Output format (dot, graphml)",
+            default_value = "dot"
+        )]
+        format: GraphFormat,
+    },
+
+    #[clap(about = "Search and replace text across pages")]
+    Replace {
+        #[clap(help = "Text or regular expression to search for")]
+        pattern: String,
+
+        #[clap(help = "Replacement text")]
+        replacement: String,
+
+        #[clap(short, long, help = "Treat the pattern as regular expression")]
+        regex: bool,
+
+        #[clap(short = 'n', long, help = "Only show the diffs")]
+        dry_run: bool,
+
+        #[clap(flatten)]
+        filter: PageFilterArgs,
+    },
+
+    #[clap(about = "Add or remove tags of many pages")]
+    Tag {
+        #[clap(subcommand)]
+        command: PageTagCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum PageTagCommand {
+    #[clap(about = "Add tags to all matching pages")]
+    Add {
+        #[clap(flatten)]
+        filter: PageFilterArgs,
+
+        #[clap(short, long = "tag", required = true, help = "Tag to add")]
+        tags: Vec<String>,
+    },
+
+    #[clap(about = "Remove tags from all matching pages")]
+    Remove {
+        #[clap(flatten)]
+        filter: PageFilterArgs,
+
+        #[clap(short, long = "tag", required = true, help = "Tag to remove")]
+        tags: Vec<String>,
+    },
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct PageFilterArgs {
+    #[clap(short, long, help = "Only pages of this locale")]
+    locale: Option<String>,
+
+    #[clap(short, long, help = "Only pages below this path")]
+    path_prefix: Option<String>,
+
+    #[clap(short = 'T', long, help = "Only pages with these tags")]
+    with_tags: Option<Vec<String>>,
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct PageListArgs {
+    #[clap(
+        short,
+        long,
+        help = "Only pages with this tag, give it again for pages with all \
+                of several tags"
+    )]
+    tag: Vec<String>,
+
+    #[clap(short, long, help = "Only pages of this locale")]
+    locale: Option<String>,
+
+    #[clap(long, help = "Only pages last edited by the user with this ID")]
+    author: Option<i64>,
+
+    #[clap(long, help = "Only pages created by the user with this ID")]
+    creator: Option<i64>,
+
+    #[clap(short, long, help = "Only pages below this path")]
+    path_prefix: Option<String>,
+
+    #[clap(
+        long,
+        help = "Only pages updated since this RFC 3339 date, e.g. \
+                2024-05-01T08:00:00Z"
+    )]
+    updated_since: Option<DateTime<Utc>>,
+}
+
+impl PageListArgs {
+    // the filters page_list has no argument for
+    fn matches(&self, path: &str, updated_at: &str) -> bool {
+        let prefix = self
+            .path_prefix
+            .as_deref()
+            .map(|prefix| prefix.trim_start_matches('/'));
+        prefix.is_none_or(|prefix| path.starts_with(prefix))
+            && self.updated_since.is_none_or(|since| {
+                DateTime::parse_from_rfc3339(updated_at)
+                    .is_ok_and(|updated_at| updated_at >= since)
+            })
+    }
+
+    // all filters, for pages from the cache
+    fn matches_page(&self, page: &wikijs::page::Page) -> bool {
+        let tags: Vec<&str> =
+            page.tags.iter().flatten().map(|t| t.tag.as_str()).collect();
+        self.matches(&page.path, &page.updated_at)
+            && self.tag.iter().all(|tag| tags.contains(&tag.as_str()))
+            && self.locale.as_ref().is_none_or(|l| *l == page.locale)
+            && self.author.is_none_or(|id| id == page.author_id)
+            && self.creator.is_none_or(|id| id == page.creator_id)
+    }
+}
+
+impl From<&PageFilterArgs> for PageFilter {
+    fn from(args: &PageFilterArgs) -> Self {
+        PageFilter {
+            locale: args.locale.to_owned(),
+            path_prefix: args.path_prefix.to_owned(),
+            tags: args.with_tags.to_owned(),
+        }
+    }
 }
 
 impl Execute for PageCommand {
     fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
         match self {
-            PageCommand::Get { id } => page_get(api, *id),
-            PageCommand::List {} => page_list(api),
-            PageCommand::Tree { parent_id, locale } => {
-                page_tree(api, *parent_id, locale.to_string())
+            PageCommand::Get { id, format } => page_get(api, *id, *format),
+            PageCommand::List { filter, paging } => {
+                page_list(api, filter, paging)
             }
+            PageCommand::Search {
+                query,
+                path,
+                locale,
+                open,
+            } => page_search(
+                api,
+                query.to_string(),
+                path.clone(),
+                locale.clone(),
+                *open,
+            ),
+            PageCommand::Tree {
+                parent,
+                locale,
+                depth,
+            } => page_tree(api, *parent, locale.to_string(), *depth),
             PageCommand::Delete { id } => page_delete(api, *id),
             PageCommand::Render { id } => page_render(api, *id),
             PageCommand::Create {
                 content,
+                file,
                 description,
                 editor,
                 is_private,
@@ -195,19 +593,19 @@ impl Execute for PageCommand {
                 title,
             } => page_create(
                 api,
-                content.to_string(),
-                description.to_string(),
-                editor.to_string(),
-                *is_private,
-                *is_published,
-                locale.to_string(),
-                path.to_string(),
-                // publish_start_date.to_string(),
-                // publish_end_date.to_string(),
-                // script_css.to_string(),
-                // script_js.to_string(),
-                tags.to_vec(),
-                title.clone(),
+                content.clone(),
+                file.as_deref(),
+                PageFrontMatter {
+                    path: path.clone(),
+                    locale: locale.clone(),
+                    title: title.clone(),
+                    description: description.clone(),
+                    editor: editor.clone(),
+                    tags: (!tags.is_empty()).then(|| tags.to_vec()),
+                    is_published: *is_published,
+                    is_private: is_private.then_some(true),
+                    ..Default::default()
+                },
             ),
             PageCommand::Update {
                 id,
@@ -246,60 +644,531 @@ impl Execute for PageCommand {
             PageCommand::UpdateContent { id, content } => {
                 page_update_content(api, *id, content.to_string())
             }
-            PageCommand::Edit { id, editor } => {
-                page_edit(api, *id, editor.to_string())
+            PageCommand::Edit {
+                page,
+                locale,
+                editor,
+            } => page_edit(&api, page, locale, editor),
+            PageCommand::Open { page, locale, edit } => {
+                let page = page_resolve(&api, page, locale)?;
+                let url = match edit {
+                    true => api.page_edit_url(&page.locale, &page.path),
+                    false => api.page_url(&page.locale, &page.path),
+                };
+                println!("{}", url);
+                open_url(&url)
+            }
+            PageCommand::New {
+                path,
+                template,
+                vars,
+                locale,
+                template_prefix,
+            } => page_new(
+                api,
+                path,
+                template,
+                vars,
+                &TemplateOptions {
+                    prefix: template_prefix.to_string(),
+                    locale: locale.to_string(),
+                },
+            ),
+            PageCommand::Publish { id } => {
+                page_publish_at(api, *id, None, None)
+            }
+            PageCommand::Unpublish { id } => page_unpublish(api, *id),
+            PageCommand::Schedule { id, start, end } => {
+                page_publish_at(api, *id, *start, *end)
+            }
+            PageCommand::History {
+                id,
+                offset_page,
+                offset_size,
+            } => page_history(api, *id, *offset_page, *offset_size),
+            PageCommand::Restore { id, version } => {
+                if is_dry_run() {
+                    return print_dry_run(format!(
+                        "restore page {} to version {}",
+                        page_label(&api, *id)?,
+                        version
+                    ));
+                }
+                api.page_restore(*id, *version)?;
+                info!("{}: Page restored", "success".bold().green());
+                Ok(())
+            }
+            PageCommand::Diff { id, from, to, stat } => {
+                page_diff(api, *id, *from, *to, *stat)
+            }
+            PageCommand::FlushCache {} => {
+                if is_dry_run() {
+                    return print_dry_run("flush the page cache");
+                }
+                api.page_cache_flush()?;
+                info!("{}: Page cache flushed", "success".bold().green());
+                Ok(())
+            }
+            PageCommand::RebuildTree {} => {
+                if is_dry_run() {
+                    return print_dry_run("rebuild the page tree");
+                }
+                api.page_tree_rebuild()?;
+                info!("{}: Page tree rebuilt", "success".bold().green());
+                Ok(())
+            }
+            PageCommand::PurgeHistory { older_than } => {
+                let action = format!(
+                    "purge the page history older than {} seconds",
+                    older_than.num_seconds()
+                );
+                if is_dry_run() {
+                    return print_dry_run(action);
+                }
+                confirm(action)?;
+                // the wiki expects an ISO 8601 duration
+                api.page_history_purge(format!(
+                    "PT{}S",
+                    older_than.num_seconds()
+                ))?;
+                info!("{}: Page history purged", "success".bold().green());
+                Ok(())
+            }
+            PageCommand::MigrateLocale { from, to } => {
+                if is_dry_run() {
+                    let pages = api.page_filter_list(&PageFilter {
+                        locale: Some(from.to_string()),
+                        ..Default::default()
+                    })?;
+                    return print_dry_run(format!(
+                        "migrate {} pages from {} to {}",
+                        pages.len(),
+                        from,
+                        to
+                    ));
+                }
+                api.page_migrate_to_locale(from.to_string(), to.to_string())?;
+                info!(
+                    "{}: Pages migrated from {} to {}",
+                    "success".bold().green(),
+                    from,
+                    to
+                );
+                Ok(())
+            }
+            PageCommand::Graph { locale, format } => {
+                page_graph(api, locale.to_string(), *format)
             }
+            PageCommand::Replace {
+                pattern,
+                replacement,
+                regex,
+                dry_run,
+                filter,
+            } => page_replace(
+                api,
+                pattern.to_string(),
+                replacement.to_string(),
+                *regex,
+                *dry_run,
+                filter.into(),
+            ),
+            PageCommand::Tag { command } => command.execute(api),
         }
     }
 }
 
-fn page_get(api: wikijs::Api, id: i64) -> Result<(), Box<dyn Error>> {
-    let page = api.page_get(id)?;
-    let mut builder = Builder::new();
-    builder.push_record(["key", "value"]);
-    builder.push_record(["id", page.id.to_string().as_str()]);
-    builder.push_record(["path", page.path.to_string().as_str()]);
-    builder.push_record(["hash", page.hash.to_string().as_str()]);
-    builder.push_record(["title", page.title.as_str()]);
+impl PageCommand {
+    // serve the read only commands from the cache
+    pub(crate) fn execute_offline(
+        &self,
+        cache: &Cache,
+    ) -> Result<(), Box<dyn Error>> {
+        match self {
+            PageCommand::Get { id, format } => {
+                page_get_offline(cache, *id, *format)
+            }
+            PageCommand::List { filter, paging } => {
+                let pages: Vec<_> = cache
+                    .pages()
+                    .into_iter()
+                    .filter(|cached| filter.matches_page(&cached.page))
+                    .collect();
+                // sorted by the fields of the pages, not the cache entries
+                print_cached_pages(
+                    paging.apply_by(pages, |cached| &cached.page)?,
+                )
+            }
+            PageCommand::Search { open: true, .. } => {
+                Err("--open is not available offline".into())
+            }
+            PageCommand::Search {
+                query,
+                path,
+                locale,
+                ..
+            } => page_search_offline(
+                cache,
+                query,
+                path.as_deref(),
+                locale.as_deref(),
+            ),
+            _ => Err("this command is not available offline".into()),
+        }
+    }
+}
+
+impl Execute for PageTagCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        if is_dry_run() {
+            let (filter, action) = match self {
+                PageTagCommand::Add { filter, tags } => {
+                    (filter, format!("add tags {} to", tags.join(", ")))
+                }
+                PageTagCommand::Remove { filter, tags } => {
+                    (filter, format!("remove tags {} from", tags.join(", ")))
+                }
+            };
+            for page in api.page_filter_list(&filter.into())? {
+                print_dry_run(format!(
+                    "{} page {} ({}/{})",
+                    action, page.id, page.locale, page.path
+                ))?;
+            }
+            return Ok(());
+        }
+        let results = match self {
+            PageTagCommand::Add { filter, tags } => {
+                api.page_tags_add(&filter.into(), tags, None)?
+            }
+            PageTagCommand::Remove { filter, tags } => {
+                api.page_tags_remove(&filter.into(), tags, None)?
+            }
+        };
+        print_bulk_result(results)
+    }
+}
+
+fn parse_var(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .ok_or(format!("expected name=value, got {}", s))
+}
+
+fn page_new(
+    api: wikijs::Api,
+    path: &str,
+    template: &str,
+    vars: &[(String, String)],
+    options: &TemplateOptions,
+) -> Result<(), Box<dyn Error>> {
+    let vars: HashMap<String, String> = vars.iter().cloned().collect();
+    if is_dry_run() {
+        return print_dry_run(format!(
+            "create page {}/{} from template {}",
+            options.locale,
+            path.trim_matches('/'),
+            template
+        ));
+    }
+    api.page_create_from_template(template, &vars, path, options)?;
+    info!("{}: Page created", "success".bold().green());
+    Ok(())
+}
+
+fn page_publish_at(
+    api: wikijs::Api,
+    id: i64,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        let mut action = format!("publish page {}", page_label(&api, id)?);
+        if let Some(start) = start {
+            action.push_str(&format!(" from {}", start));
+        }
+        if let Some(end) = end {
+            action.push_str(&format!(" until {}", end));
+        }
+        return print_dry_run(action);
+    }
+    api.page_publish_at(id, start, end)?;
+    info!("{}: Page published", "success".bold().green());
+    Ok(())
+}
+
+fn page_unpublish(api: wikijs::Api, id: i64) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        return print_dry_run(format!(
+            "unpublish page {}",
+            page_label(&api, id)?
+        ));
+    }
+    api.page_unpublish(id)?;
+    info!("{}: Page unpublished", "success".bold().green());
+    Ok(())
+}
+
+fn print_bulk_result(
+    results: wikijs::page::PageBulkResult,
+) -> Result<(), Box<dyn Error>> {
+    let rows: Vec<(i64, String)> = results
+        .into_iter()
+        .map(|(id, result)| match result {
+            Ok(()) => (id, "ok".to_string()),
+            Err(e) => (id, e.to_string()),
+        })
+        .collect();
+    let records: Vec<_> = rows
+        .iter()
+        .map(|(id, status)| serde_json::json!({"id": id, "status": status}))
+        .collect();
+    if print_serialized(&records)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["id", "status"]);
+    for (id, status) in rows {
+        table.push_record([id.to_string(), status]);
+    }
+    table.print()?;
+    Ok(())
+}
+
+fn page_get(
+    api: wikijs::Api,
+    id: i64,
+    format: Option<PageFormat>,
+) -> Result<(), Box<dyn Error>> {
+    print_page(api.page_get(id)?, format)
+}
+
+fn page_get_offline(
+    cache: &Cache,
+    id: i64,
+    format: Option<PageFormat>,
+) -> Result<(), Box<dyn Error>> {
+    let cached = cache.page(id).ok_or("page is not in the cache")?;
+    eprintln!(
+        "{}: offline, page cached {}",
+        "warning".bold().yellow(),
+        age(cached.fetched_at)
+    );
+    print_page(cached.page, format)
+}
+
+// how long ago something was fetched, like "3h ago"
+fn age(fetched_at: SystemTime) -> String {
+    let secs = fetched_at.elapsed().unwrap_or_default().as_secs();
+    match secs {
+        0..=59 => format!("{}s ago", secs),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+// the same filters as the server applies, on the cached pages
+fn page_search_offline(
+    cache: &Cache,
+    query: &str,
+    path: Option<&str>,
+    locale: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let pages = cache
+        .search(query)
+        .into_iter()
+        .filter(|cached| {
+            path.is_none_or(|path| {
+                cached.page.path.starts_with(path.trim_start_matches('/'))
+            }) && locale.is_none_or(|locale| cached.page.locale == locale)
+        })
+        .collect();
+    print_cached_pages(pages)
+}
+
+fn print_cached_pages(pages: Vec<CachedPage>) -> Result<(), Box<dyn Error>> {
+    eprintln!(
+        "{}: offline, only pages in the cache are shown",
+        "warning".bold().yellow()
+    );
+    let records: Vec<_> = pages.iter().map(|cached| &cached.page).collect();
+    if print_serialized(&records)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["id", "locale", "path", "title", "cached"]);
+    for cached in pages {
+        table.push_record([
+            cached.page.id.to_string(),
+            cached.page.locale,
+            cached.page.path,
+            cached.page.title,
+            age(cached.fetched_at),
+        ]);
+    }
+    table.print()?;
+    Ok(())
+}
+
+fn print_page(
+    page: wikijs::page::Page,
+    format: Option<PageFormat>,
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        Some(PageFormat::Markdown) => print!("{}", page.content),
+        Some(PageFormat::Html) => print!("{}", page.render.unwrap_or_default()),
+        Some(PageFormat::Json) => {
+            println!("{}", serde_json::to_string_pretty(&page)?)
+        }
+        Some(PageFormat::Frontmatter) => {
+            print!("{}", page.to_markdown_with_frontmatter())
+        }
+        None => return print_page_table(page),
+    }
+    Ok(())
+}
+
+fn print_page_table(page: wikijs::page::Page) -> Result<(), Box<dyn Error>> {
+    if print_serialized(&page)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["key", "value"]);
+    table.push_record(["id", page.id.to_string().as_str()]);
+    table.push_record(["path", page.path.to_string().as_str()]);
+    table.push_record(["hash", page.hash.to_string().as_str()]);
+    table.push_record(["title", page.title.as_str()]);
     // TODO description
-    builder.push_record(["is_private", page.is_private.to_string().as_str()]);
-    builder
-        .push_record(["is_published", page.is_published.to_string().as_str()]);
-    builder.push_record([
+    table.push_record(["is_private", page.is_private.to_string().as_str()]);
+    table.push_record(["is_published", page.is_published.to_string().as_str()]);
+    table.push_record([
         "private_ns",
         page.private_ns.unwrap_or("".to_string()).as_str(),
     ]);
-    builder.push_record([
+    table.push_record([
         "publish_start_date",
         &page.publish_start_date.to_string(),
     ]);
-    builder
-        .push_record(["publish_end_date", &page.publish_end_date.to_string()]);
+    table.push_record(["publish_end_date", &page.publish_end_date.to_string()]);
     // TODO tags
     // TODO content
     // TODO toc
     // TODO render
-    builder.push_record(["content_type", page.content_type.as_str()]);
-    builder.push_record(["created_at", &page.created_at.to_string()]);
-    builder.push_record(["updated_at", &page.updated_at.to_string()]);
-    builder.push_record(["editor", page.editor.as_str()]);
-    builder.push_record(["locale", page.locale.as_str()]);
+    table.push_record(["content_type", page.content_type.as_str()]);
+    table.push_record(["created_at", &page.created_at.to_string()]);
+    table.push_record(["updated_at", &page.updated_at.to_string()]);
+    table.push_record(["editor", page.editor.as_str()]);
+    table.push_record(["locale", page.locale.as_str()]);
     // TODO script_css
     // TODO script_js
-    builder.push_record(["author_id", page.author_id.to_string().as_str()]);
-    builder.push_record(["author_name", page.author_name.as_str()]);
-    builder.push_record(["author_email", page.author_email.as_str()]);
-    builder.push_record(["creator_id", page.creator_id.to_string().as_str()]);
-    builder.push_record(["creator_name", page.creator_name.as_str()]);
-    builder.push_record(["creator_email", page.creator_email.as_str()]);
-    println!("{}", builder.build().with(Style::rounded()));
+    table.push_record(["author_id", page.author_id.to_string().as_str()]);
+    table.push_record(["author_name", page.author_name.as_str()]);
+    table.push_record(["author_email", page.author_email.as_str()]);
+    table.push_record(["creator_id", page.creator_id.to_string().as_str()]);
+    table.push_record(["creator_name", page.creator_name.as_str()]);
+    table.push_record(["creator_email", page.creator_email.as_str()]);
+    table.print()?;
     Ok(())
 }
 
-fn page_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
-    let pages = api.page_list(None, None, None, None, None, None, None)?;
-    let mut builder = Builder::new();
-    builder.push_record([
+fn page_search(
+    api: wikijs::Api,
+    query: String,
+    path: Option<String>,
+    locale: Option<String>,
+    open: bool,
+) -> Result<(), Box<dyn Error>> {
+    let response = api.page_search(query, path, locale)?;
+    let results: Vec<_> = response.results.into_iter().flatten().collect();
+    if open {
+        let first = results.first().ok_or("no page found")?;
+        return open_url(&api.page_url(&first.locale, &first.path));
+    }
+    if print_serialized(&results)? {
+        return Ok(());
+    }
+    // the results come ordered by relevance, without a score
+    let mut table = Table::new();
+    table.push_record(["rank", "id", "locale", "path", "title"]);
+    for (rank, result) in results.into_iter().enumerate() {
+        table.push_record([
+            (rank + 1).to_string(),
+            result.id,
+            result.locale,
+            result.path,
+            result.title,
+        ]);
+    }
+    table.print()?;
+    println!("{} hits", response.total_hits);
+    let suggestions: Vec<_> =
+        response.suggestions.into_iter().flatten().collect();
+    if !suggestions.is_empty() {
+        println!("did you mean: {}", suggestions.join(", "));
+    }
+    Ok(())
+}
+
+fn page_list(
+    api: wikijs::Api,
+    filter: &PageListArgs,
+    paging: &PagingArgs,
+) -> Result<(), Box<dyn Error>> {
+    let tags = match filter.tag.is_empty() {
+        true => None,
+        false => Some(filter.tag.clone()),
+    };
+    // let the API sort and limit where it can
+    let order = paging.sort_field().and_then(|(field, descending)| {
+        let order_by = match field {
+            "id" => PageOrderBy::ID,
+            "path" => PageOrderBy::PATH,
+            "title" => PageOrderBy::TITLE,
+            "createdAt" => PageOrderBy::CREATED,
+            "updatedAt" => PageOrderBy::UPDATED,
+            _ => return None,
+        };
+        let direction = match descending {
+            true => PageOrderByDirection::DESC,
+            false => PageOrderByDirection::ASC,
+        };
+        Some((order_by, direction))
+    });
+    let client_side = filter.path_prefix.is_some()
+        || filter.updated_since.is_some()
+        || (paging.sort.is_some() && order.is_none());
+    let limit = match client_side {
+        true => None,
+        false => paging.fetch_limit().map(|limit| limit as i64),
+    };
+    let paging = match order {
+        Some(_) => PagingArgs {
+            sort: None,
+            ..paging.clone()
+        },
+        None => paging.clone(),
+    };
+    let (order_by, direction) = order.unzip();
+    let pages: Vec<_> = api
+        .page_list(
+            limit,
+            order_by,
+            direction,
+            tags,
+            filter.locale.clone(),
+            filter.creator,
+            filter.author,
+        )?
+        .into_iter()
+        .filter(|page| filter.matches(&page.path, &page.updated_at))
+        .collect();
+    let pages = paging.apply(pages)?;
+    if print_serialized(&pages)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record([
         "id",
         "locate",
         "path",
@@ -312,7 +1181,7 @@ fn page_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
         "updated_at",
     ]);
     for page in pages {
-        builder.push_record([
+        table.push_record([
             page.id.to_string().as_str(),
             page.path.as_str(),
             page.locale.as_str(),
@@ -327,97 +1196,191 @@ fn page_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
             // TODO tags
         ]);
     }
-    println!("{}", builder.build().with(Style::rounded()));
+    table.print()?;
     Ok(())
 }
 
+#[derive(Serialize)]
+struct PageTreeNode {
+    #[serde(flatten)]
+    item: PageTreeItem,
+    children: Vec<PageTreeNode>,
+}
+
+// fetch the children of a tree item and their children down to the depth
+fn page_tree_nodes(
+    api: &wikijs::Api,
+    parent: i64,
+    locale: &str,
+    depth: Option<usize>,
+) -> Result<Vec<PageTreeNode>, Box<dyn Error>> {
+    let items = api.page_tree_get(
+        parent,
+        wikijs::page::PageTreeMode::ALL,
+        false,
+        locale.to_string(),
+    )?;
+    items
+        .into_iter()
+        .map(|item| {
+            let children = match depth {
+                _ if !item.is_folder => Vec::new(),
+                Some(depth) if depth <= 1 => Vec::new(),
+                _ => page_tree_nodes(
+                    api,
+                    item.id,
+                    locale,
+                    depth.map(|depth| depth - 1),
+                )?,
+            };
+            Ok(PageTreeNode { item, children })
+        })
+        .collect()
+}
+
+fn print_page_tree(nodes: &[PageTreeNode], prefix: &str) {
+    for (index, node) in nodes.iter().enumerate() {
+        let last = index + 1 == nodes.len();
+        let title = match node.item.is_folder {
+            true => node.item.title.bold().blue(),
+            false => node.item.title.normal(),
+        };
+        println!(
+            "{}{} {} {}",
+            prefix,
+            if last { "└──" } else { "├──" },
+            title,
+            format!("({})", node.item.path).dimmed()
+        );
+        let prefix =
+            format!("{}{}", prefix, if last { "    " } else { "│   " });
+        print_page_tree(&node.children, &prefix);
+    }
+}
+
 fn page_tree(
     api: wikijs::Api,
-    parent_id: i64,
+    parent: i64,
     locale: String,
+    depth: Option<usize>,
 ) -> Result<(), Box<dyn Error>> {
-    let tree_items = api.page_tree_get(
-        parent_id,
-        wikijs::page::PageTreeMode::ALL,
-        true,
-        locale,
-    )?;
-    let mut builder = Builder::new();
-    builder.push_record([
-        "id",
-        "path",
-        "depth",
-        "title",
-        "is_private",
-        "is_folder",
-        "private_ns",
-        "parent",
-        "page_id",
-        "locale",
-    ]);
-    for tree_item in tree_items {
-        builder.push_record([
-            tree_item.id.to_string().as_str(),
-            tree_item.path.as_str(),
-            tree_item.depth.to_string().as_str(),
-            tree_item.title.as_str(),
-            tree_item.is_private.to_string().as_str(),
-            tree_item.is_folder.to_string().as_str(),
-            tree_item.private_ns.unwrap_or("".to_string()).as_str(),
-            tree_item.parent.unwrap_or(-1).to_string().as_str(),
-            tree_item.page_id.unwrap_or(-1).to_string().as_str(),
-            tree_item.locale.as_str(),
-        ]);
+    if depth == Some(0) {
+        return Ok(());
+    }
+    let nodes = page_tree_nodes(&api, parent, &locale, depth)?;
+    if print_serialized(&nodes)? {
+        return Ok(());
     }
-    println!("{}", builder.build().with(Style::rounded()));
+    println!("{}", if parent == 0 { "/" } else { "." });
+    print_page_tree(&nodes, "");
     Ok(())
 }
 
+// a page id with its locale and path, to show what a dry run
+// or confirmation is about
+fn page_label(api: &wikijs::Api, id: i64) -> Result<String, Box<dyn Error>> {
+    let page = api.page_get(id)?;
+    Ok(format!("{} ({}/{})", id, page.locale, page.path))
+}
+
 fn page_delete(api: wikijs::Api, id: i64) -> Result<(), Box<dyn Error>> {
+    let action = format!("delete page {}", page_label(&api, id)?);
+    if is_dry_run() {
+        return print_dry_run(action);
+    }
+    confirm(action)?;
     api.page_delete(id)?;
-    println!("{}: Page deleted", "success".bold().green());
+    info!("{}: Page deleted", "success".bold().green());
     Ok(())
 }
 
 fn page_render(api: wikijs::Api, id: i64) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        return print_dry_run(format!("render page {}", page_label(&api, id)?));
+    }
     api.page_render(id)?;
-    println!("{}: Page rendered", "success".bold().green());
+    info!("{}: Page rendered", "success".bold().green());
     Ok(())
 }
 
-#[allow(clippy::too_many_arguments)]
+// read a file, or stdin for -
+fn read_input(file: &Path) -> Result<String, Box<dyn Error>> {
+    if file == Path::new("-") {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        return Ok(input);
+    }
+    Ok(std::fs::read_to_string(file)?)
+}
+
+// create a page, the given metadata taking precedence over the front
+// matter of the file
 fn page_create(
     api: wikijs::Api,
-    content: String,
-    description: String,
-    editor: String,
-    is_private: bool,
-    is_published: bool,
-    locale: String,
-    path: String,
-    // publish_start_date: String,
-    // publish_end_date: String,
-    // script_css: String,
-    // script_js: String,
-    tags: Vec<String>,
-    title: Option<String>,
+    content: Option<String>,
+    file: Option<&Path>,
+    given: PageFrontMatter,
 ) -> Result<(), Box<dyn Error>> {
+    let (front_matter, content) = match file {
+        Some(file) => parse_frontmatter_markdown(&read_input(file)?)?,
+        None => (
+            PageFrontMatter::default(),
+            content.unwrap_or("...".to_string()),
+        ),
+    };
+    let path = given
+        .path
+        .or(front_matter.path)
+        .ok_or("no page path given or in the front matter")?;
+    let path = path.trim_matches('/').to_string();
+    let title = given
+        .title
+        .or(front_matter.title)
+        .unwrap_or(path.split('/').next_back().unwrap().to_string());
+    let locale = given
+        .locale
+        .or(front_matter.locale)
+        .unwrap_or("en".to_string());
+    if is_dry_run() {
+        return print_dry_run(format!(
+            "create page {}/{} titled {}",
+            locale, path, title
+        ));
+    }
     api.page_create(
         content,
-        description,
-        editor,
-        is_published,
-        is_private,
+        given
+            .description
+            .or(front_matter.description)
+            .unwrap_or_default(),
+        given
+            .editor
+            .or(front_matter.editor)
+            .unwrap_or(PageEditor::Markdown),
+        given
+            .is_published
+            .or(front_matter.is_published)
+            .unwrap_or(true),
+        given
+            .is_private
+            .or(front_matter.is_private)
+            .unwrap_or(false),
         locale,
-        path.clone(),
-        None,
-        None,
-        None,
-        None,
-        tags.iter().map(|s| Some(s.clone())).collect(),
-        title.unwrap_or(path.split('/').last().unwrap().to_string()),
+        path,
+        front_matter.publish_end_date,
+        front_matter.publish_start_date,
+        front_matter.script_css,
+        front_matter.script_js,
+        given
+            .tags
+            .or(front_matter.tags)
+            .unwrap_or_default()
+            .into_iter()
+            .map(Some)
+            .collect(),
+        title,
     )?;
-    println!("{}: Page created", "success".bold().green());
+    info!("{}: Page created", "success".bold().green());
     Ok(())
 }
 
@@ -427,7 +1390,7 @@ fn page_update(
     id: i64,
     content: Option<String>,
     description: Option<String>,
-    editor: Option<String>,
+    editor: Option<PageEditor>,
     is_private: Option<bool>,
     is_published: Option<bool>,
     locale: Option<String>,
@@ -440,6 +1403,27 @@ fn page_update(
     no_tags: bool,
     title: Option<String>,
 ) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        let fields: Vec<&str> = [
+            ("content", content.is_some()),
+            ("description", description.is_some()),
+            ("editor", editor.is_some()),
+            ("is_private", is_private.is_some()),
+            ("is_published", is_published.is_some()),
+            ("locale", locale.is_some()),
+            ("path", path.is_some()),
+            ("tags", tags.is_some() || no_tags),
+            ("title", title.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(field, given)| given.then_some(field))
+        .collect();
+        return print_dry_run(format!(
+            "update {} of page {}",
+            fields.join(", "),
+            page_label(&api, id)?
+        ));
+    }
     api.page_update(
         id,
         content,
@@ -464,7 +1448,7 @@ fn page_update(
         },
         title,
     )?;
-    println!("{}: Page updated", "success".bold().green());
+    info!("{}: Page updated", "success".bold().green());
     Ok(())
 }
 
@@ -473,35 +1457,311 @@ fn page_update_content(
     id: i64,
     content: String,
 ) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        return print_dry_run(format!(
+            "update the content of page {}",
+            page_label(&api, id)?
+        ));
+    }
     api.page_update_content(id, content)?;
-    println!("{}: Page content updated", "success".bold().green());
+    info!("{}: Page content updated", "success".bold().green());
     Ok(())
 }
 
-fn page_edit(
-    api: wikijs::Api,
-    id: i64,
-    editor: String,
-) -> Result<(), Box<dyn Error>> {
-    let page = api.page_get(id)?;
-    let file = match page.editor.as_str() {
-        "markdown" => TempFileBuilder::new().suffix(".md").tempfile(),
-        _ => TempFileBuilder::new().tempfile(),
-    }?;
-    file.reopen()?.write_all(page.content.as_bytes())?;
-    let mut child = std::process::Command::new(editor)
-        .arg(file.path())
-        .spawn()?;
-    let status = child.wait()?;
+// get a page by its ID or, if it is not a number, by its path
+fn page_resolve(
+    api: &wikijs::Api,
+    page: &str,
+    locale: &str,
+) -> Result<wikijs::page::Page, Box<dyn Error>> {
+    Ok(match page.parse::<i64>() {
+        Ok(id) => api.page_get(id)?,
+        Err(_) => api.page_get_by_path(
+            page.trim_matches('/').to_string(),
+            locale.to_string(),
+        )?,
+    })
+}
+
+fn print_diff(diff: &PageDiff) {
+    for line in diff.to_unified().lines() {
+        match line.chars().next() {
+            _ if line.starts_with("---") || line.starts_with("+++") => {
+                println!("{}", line.bold())
+            }
+            Some('@') => println!("{}", line.cyan()),
+            Some('+') => println!("{}", line.green()),
+            Some('-') => println!("{}", line.red()),
+            _ => println!("{}", line),
+        }
+    }
+}
+
+fn run_editor(editor: &str, path: &Path) -> Result<(), Box<dyn Error>> {
+    let status = std::process::Command::new(editor).arg(path).status()?;
     if !status.success() {
         return Err(Box::new(IoError::new(
             std::io::ErrorKind::Other,
             "Editor exited with non-zero status code",
         )));
     }
-    let content = std::fs::read_to_string(file.path())?;
-    api.page_update_content(id, content)?;
-    // TODO a generic success print function could be useful
-    println!("{}: Page content updated", "success".bold().green());
+    Ok(())
+}
+
+fn ask(question: &str) -> Result<String, Box<dyn Error>> {
+    eprint!("{} ", question);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().to_lowercase())
+}
+
+pub(crate) fn page_edit(
+    api: &wikijs::Api,
+    page: &str,
+    locale: &str,
+    editor: &str,
+) -> Result<(), Box<dyn Error>> {
+    let page = page_resolve(api, page, locale)?;
+    let file = match page.editor {
+        PageEditor::Markdown => TempFileBuilder::new().suffix(".md").tempfile(),
+        _ => TempFileBuilder::new().tempfile(),
+    }?;
+    file.reopen()?.write_all(page.content.as_bytes())?;
+    // the content our edit started from and when it was fetched
+    let mut base = page.content;
+    let mut checkout_date = page.updated_at;
+    loop {
+        run_editor(editor, file.path())?;
+        let content = std::fs::read_to_string(file.path())?;
+        if PageMerge::has_markers(&content) {
+            match ask("Conflict markers are left, [e]dit again or [a]bort?")?
+                .as_str()
+            {
+                "a" | "abort" => return abort_edit(file),
+                _ => continue,
+            }
+        }
+        if content == base {
+            info!("{}: Page content unchanged", "success".bold().green());
+            return Ok(());
+        }
+        if is_dry_run() {
+            return print_dry_run(format!(
+                "update the content of page {} ({}/{})",
+                page.id, page.locale, page.path
+            ));
+        }
+        match api.page_update_content_checked(
+            page.id,
+            content.clone(),
+            checkout_date.clone(),
+        ) {
+            Ok(()) => {
+                info!("{}: Page content updated", "success".bold().green());
+                return Ok(());
+            }
+            Err(PageError::PageUpdateConflict) => {}
+            Err(e) => return Err(e.into()),
+        }
+        let latest = api.page_conflict_latest(page.id)?;
+        eprintln!(
+            "{}: {} changed the page at {} since you started editing",
+            "warning".bold().yellow(),
+            latest.author_name,
+            latest.updated_at
+        );
+        print_diff(&PageDiff::new(
+            "remote".to_string(),
+            "local".to_string(),
+            &latest.content,
+            &content,
+        ));
+        match ask("[m]erge, [o]verwrite or [a]bort?")?.as_str() {
+            "m" | "merge" => {
+                let merge = PageMerge::new(
+                    "local",
+                    "remote",
+                    &base,
+                    &content,
+                    &latest.content,
+                );
+                if merge.conflicts > 0 {
+                    eprintln!(
+                        "{}: {} conflicts to resolve",
+                        "warning".bold().yellow(),
+                        merge.conflicts
+                    );
+                }
+                std::fs::write(file.path(), merge.content)?;
+                base = latest.content;
+                checkout_date = latest.updated_at;
+            }
+            "o" | "overwrite" => {
+                api.page_update_content(page.id, content)?;
+                info!("{}: Page content updated", "success".bold().green());
+                return Ok(());
+            }
+            _ => return abort_edit(file),
+        }
+    }
+}
+
+// keep the edited content around so it is not lost
+fn abort_edit(file: tempfile::NamedTempFile) -> Result<(), Box<dyn Error>> {
+    let (_, path) = file.keep()?;
+    Err(
+        format!("edit aborted, the content is kept in {}", path.display())
+            .into(),
+    )
+}
+
+fn page_history(
+    api: wikijs::Api,
+    id: i64,
+    offset_page: Option<i64>,
+    offset_size: Option<i64>,
+) -> Result<(), Box<dyn Error>> {
+    let history = api.page_history_get(id, offset_page, offset_size)?;
+    let trail: Vec<_> = history.trail.into_iter().flatten().flatten().collect();
+    if print_serialized(&trail)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["version_id", "version_date", "author", "action"]);
+    for entry in trail {
+        table.push_record([
+            entry.version_id.to_string(),
+            entry.version_date,
+            entry.author_name,
+            entry.action_type,
+        ]);
+    }
+    table.print()?;
+    println!("{} versions in total", history.total);
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum DiffTarget {
+    Version(i64),
+    Current,
+}
+
+fn parse_version(s: &str) -> Result<DiffTarget, String> {
+    match s {
+        "current" => Ok(DiffTarget::Current),
+        s => s.parse().map(DiffTarget::Version).map_err(|_| {
+            format!("invalid version {}, expected a number or current", s)
+        }),
+    }
+}
+
+fn page_diff(
+    api: wikijs::Api,
+    id: i64,
+    from: Option<i64>,
+    to: DiffTarget,
+    stat: bool,
+) -> Result<(), Box<dyn Error>> {
+    let from = match from {
+        Some(from) => from,
+        None => api
+            .page_history_get(id, None, None)?
+            .trail
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.version_id)
+            .next()
+            .ok_or("the page has no previous versions")?,
+    };
+    let diff = match to {
+        DiffTarget::Version(to) => api.page_version_diff(id, from, to)?,
+        DiffTarget::Current => api.page_version_diff_current(id, from)?,
+    };
+    if stat {
+        println!(
+            "{} -> {}: {} insertions({}), {} deletions({})",
+            diff.old_label,
+            diff.new_label,
+            diff.insertions(),
+            "+".green(),
+            diff.deletions(),
+            "-".red()
+        );
+    } else {
+        print_diff(&diff);
+    }
+    Ok(())
+}
+
+fn page_graph(
+    api: wikijs::Api,
+    locale: String,
+    format: GraphFormat,
+) -> Result<(), Box<dyn Error>> {
+    let graph = api.page_graph(locale)?;
+    print!("{}", graph.render(format));
+    Ok(())
+}
+
+fn page_replace(
+    api: wikijs::Api,
+    pattern: String,
+    replacement: String,
+    regex: bool,
+    dry_run: bool,
+    filter: PageFilter,
+) -> Result<(), Box<dyn Error>> {
+    let dry_run = dry_run || is_dry_run();
+    let pattern = if regex {
+        ReplacePattern::Regex(regex::Regex::new(&pattern)?)
+    } else {
+        ReplacePattern::Text(pattern)
+    };
+    let replacements = api.page_search_replace(
+        &pattern,
+        &replacement,
+        &filter,
+        dry_run,
+        None,
+    )?;
+    if dry_run {
+        for replacement in replacements {
+            print!("{}", replacement.diff.to_unified());
+        }
+        return Ok(());
+    }
+    let rows: Vec<_> = replacements
+        .into_iter()
+        .map(|replacement| {
+            let status = match replacement.result {
+                Some(Err(e)) => e.to_string(),
+                _ => "updated".to_string(),
+            };
+            (replacement.id, replacement.path, replacement.count, status)
+        })
+        .collect();
+    let records: Vec<_> = rows
+        .iter()
+        .map(|(id, path, count, status)| {
+            serde_json::json!({
+                "id": id,
+                "path": path,
+                "replacements": count,
+                "status": status,
+            })
+        })
+        .collect();
+    if print_serialized(&records)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["id", "path", "replacements", "status"]);
+    for (id, path, count, status) in rows {
+        table.push_record([id.to_string(), path, count.to_string(), status]);
+    }
+    table.print()?;
     Ok(())
 }
@@ -0,0 +1,145 @@
+use crate::common::{
+    is_dry_run, parse_config_option, print_dry_run, settings_diff, Execute,
+};
+use crate::output::{print_serialized, Table};
+use clap::{ArgAction, Subcommand};
+use colored::Colorize;
+use std::error::Error;
+use wikijs::rendering::Renderer;
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum RendererCommand {
+    #[clap(about = "List renderers")]
+    List {},
+
+    #[clap(about = "Enable a renderer")]
+    Enable {
+        #[clap(help = "Renderer key, like markdownKroki")]
+        key: String,
+    },
+
+    #[clap(about = "Disable a renderer")]
+    Disable {
+        #[clap(help = "Renderer key, like markdownKroki")]
+        key: String,
+    },
+
+    #[clap(about = "Show or change the config of a renderer")]
+    Config {
+        #[clap(help = "Renderer key, like markdownKroki")]
+        key: String,
+
+        #[clap(
+            short,
+            long = "config",
+            help = "Config option as key=value, the value may be JSON",
+            value_parser = parse_config_option,
+            action = ArgAction::Append
+        )]
+        config: Vec<(String, serde_json::Value)>,
+    },
+}
+
+impl Execute for RendererCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        match self {
+            RendererCommand::List {} => renderer_list(api),
+            RendererCommand::Enable { key } => {
+                renderer_set_enabled(api, key, true)
+            }
+            RendererCommand::Disable { key } => {
+                renderer_set_enabled(api, key, false)
+            }
+            RendererCommand::Config { key, config } => {
+                renderer_config(api, key, config)
+            }
+        }
+    }
+}
+
+fn renderer_get(
+    api: &wikijs::Api,
+    key: &str,
+) -> Result<Renderer, Box<dyn Error>> {
+    Ok(api
+        .renderer_list(None, None)?
+        .into_iter()
+        .find(|renderer| renderer.key == key)
+        .ok_or(format!("unknown renderer {}", key))?)
+}
+
+fn renderer_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+    let renderers = api.renderer_list(None, None)?;
+    if print_serialized(&renderers)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["key", "title", "is_enabled", "depends_on"]);
+    for renderer in renderers {
+        table.push_record([
+            renderer.key,
+            renderer.title,
+            renderer.is_enabled.to_string(),
+            renderer.depends_on.unwrap_or_default(),
+        ]);
+    }
+    table.print()?;
+    Ok(())
+}
+
+fn renderer_set_enabled(
+    api: wikijs::Api,
+    key: &str,
+    is_enabled: bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut input = renderer_get(&api, key)?.to_input();
+    let old = input.clone();
+    input.is_enabled = is_enabled;
+    if is_dry_run() {
+        return print_dry_run(format!(
+            "update renderer {}: {}",
+            key,
+            settings_diff(&old, &input)
+        ));
+    }
+    api.renderer_update(vec![input])?;
+    info!("{}: Renderer updated", "success".bold().green());
+    Ok(())
+}
+
+fn renderer_config(
+    api: wikijs::Api,
+    key: &str,
+    config: &[(String, serde_json::Value)],
+) -> Result<(), Box<dyn Error>> {
+    let renderer = renderer_get(&api, key)?;
+    if config.is_empty() {
+        let values: serde_json::Map<_, _> =
+            renderer.config_values().into_iter().collect();
+        if print_serialized(&values)? {
+            return Ok(());
+        }
+        let mut table = Table::new();
+        table.push_record(["key", "value"]);
+        for (option, value) in renderer.config_values() {
+            table.push_record([option, value.to_string()]);
+        }
+        table.print()?;
+        return Ok(());
+    }
+    let mut input = renderer.to_input();
+    let old = input.clone();
+    for (option, value) in config {
+        input.set_config(option, value.clone());
+    }
+    if is_dry_run() {
+        return print_dry_run(format!(
+            "update renderer {}: {}",
+            key,
+            settings_diff(&old, &input)
+        ));
+    }
+    api.renderer_update(vec![input])?;
+    info!("{}: Renderer updated", "success".bold().green());
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use crate::common::Execute;
+use crate::output::{print_serialized, Table};
+use clap::Args;
+use std::error::Error;
+use wikijs::report::ReportOptions;
+
+#[derive(Args, Debug)]
+pub(crate) struct ReportCommand {
+    #[clap(
+        short,
+        long,
+        help = "Only count activity since this date, e.g. 2024-01-01"
+    )]
+    since: Option<String>,
+
+    #[clap(
+        short,
+        long,
+        help = "Length of the ranked lists",
+        default_value = "10"
+    )]
+    limit: usize,
+
+    #[clap(short, long, help = "Print the report as JSON")]
+    json: bool,
+}
+
+impl Execute for ReportCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        let report = api.wiki_report(
+            &ReportOptions {
+                since: self.since.to_owned(),
+                limit: self.limit,
+            },
+            None,
+        )?;
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            return Ok(());
+        }
+        if print_serialized(&report)? {
+            return Ok(());
+        }
+
+        let mut table = Table::new();
+        table.push_record(["key", "value"]);
+        table.push_record([
+            "since",
+            report.since.as_deref().unwrap_or("beginning"),
+        ]);
+        table.push_record(["pages", report.total_pages.to_string().as_str()]);
+        table.push_record([
+            "comments",
+            report.total_comments.to_string().as_str(),
+        ]);
+        table.print()?;
+
+        let mut table = Table::new();
+        table.push_record(["locale", "pages"]);
+        for (locale, count) in &report.pages_per_locale {
+            table.push_record([locale.as_str(), count.to_string().as_str()]);
+        }
+        table.print()?;
+
+        let mut table = Table::new();
+        table.push_record(["tag", "pages"]);
+        for (tag, count) in &report.pages_per_tag {
+            table.push_record([tag.as_str(), count.to_string().as_str()]);
+        }
+        table.print()?;
+
+        let mut table = Table::new();
+        table.push_record(["id", "path", "locale", "updated_at"]);
+        for page in &report.recently_updated {
+            table.push_record([
+                page.id.to_string().as_str(),
+                page.path.as_str(),
+                page.locale.as_str(),
+                page.updated_at.as_str(),
+            ]);
+        }
+        table.print()?;
+
+        let mut table = Table::new();
+        table.push_record(["author", "edits"]);
+        for author in &report.most_active_authors {
+            table.push_record([
+                author.name.as_str(),
+                author.edits.to_string().as_str(),
+            ]);
+        }
+        table.print()?;
+
+        let mut table = Table::new();
+        table.push_record(["id", "path", "comments"]);
+        for count in &report.most_commented {
+            table.push_record([
+                count.page.id.to_string().as_str(),
+                count.page.path.as_str(),
+                count.comments.to_string().as_str(),
+            ]);
+        }
+        table.print()?;
+        Ok(())
+    }
+}
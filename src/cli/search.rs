@@ -0,0 +1,120 @@
+use crate::common::{
+    is_dry_run, parse_config_option, print_dry_run, settings_diff, Execute,
+};
+use crate::output::{print_serialized, Table};
+use clap::{ArgAction, Subcommand};
+use colored::Colorize;
+use std::error::Error;
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum SearchEngineCommand {
+    #[clap(about = "List search engines")]
+    List {},
+
+    #[clap(about = "Update a search engine")]
+    Update {
+        #[clap(help = "Search engine key, like elasticsearch")]
+        key: String,
+
+        #[clap(long, help = "Enable the engine and disable all others")]
+        enable: bool,
+
+        #[clap(
+            short,
+            long = "config",
+            help = "Config option as key=value, the value may be JSON",
+            value_parser = parse_config_option,
+            action = ArgAction::Append
+        )]
+        config: Vec<(String, serde_json::Value)>,
+    },
+
+    #[clap(about = "Rebuild the search index")]
+    RebuildIndex {},
+}
+
+impl Execute for SearchEngineCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        match self {
+            SearchEngineCommand::List {} => search_engine_list(api),
+            SearchEngineCommand::Update {
+                key,
+                enable,
+                config,
+            } => search_engine_update(api, key, *enable, config),
+            SearchEngineCommand::RebuildIndex {} => {
+                if is_dry_run() {
+                    return print_dry_run("rebuild the search index");
+                }
+                api.search_engine_index_rebuild()?;
+                info!("{}: Search index rebuilt", "success".bold().green());
+                Ok(())
+            }
+        }
+    }
+}
+
+fn search_engine_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+    let engines = api.search_engine_list(None, None)?;
+    if print_serialized(&engines)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["key", "title", "is_enabled", "is_available"]);
+    for engine in engines {
+        table.push_record([
+            engine.key,
+            engine.title,
+            engine.is_enabled.to_string(),
+            engine
+                .is_available
+                .map(|a| a.to_string())
+                .unwrap_or_default(),
+        ]);
+    }
+    table.print()?;
+    Ok(())
+}
+
+fn search_engine_update(
+    api: wikijs::Api,
+    key: &str,
+    enable: bool,
+    config: &[(String, serde_json::Value)],
+) -> Result<(), Box<dyn Error>> {
+    let engines = api.search_engine_list(None, None)?;
+    if !engines.iter().any(|engine| engine.key == key) {
+        return Err(format!("unknown search engine {}", key).into());
+    }
+    let inputs: Vec<_> = engines
+        .iter()
+        .map(|engine| {
+            let mut input = engine.to_input();
+            if engine.key == key {
+                input.is_enabled |= enable;
+                for (option, value) in config {
+                    input.set_config(option, value.clone());
+                }
+            } else if enable {
+                input.is_enabled = false;
+            }
+            input
+        })
+        .collect();
+    if is_dry_run() {
+        // enabling an engine disables the others
+        for (engine, input) in engines.iter().zip(&inputs) {
+            if engine.key == key || engine.is_enabled != input.is_enabled {
+                print_dry_run(format!(
+                    "update search engine {}: {}",
+                    engine.key,
+                    settings_diff(&engine.to_input(), input)
+                ))?;
+            }
+        }
+        return Ok(());
+    }
+    api.search_engine_update(inputs)?;
+    info!("{}: Search engine updated", "success".bold().green());
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use crate::config::config_dir;
+use crate::credentials::jwt_expiry;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A JWT from a previous login, stored so later commands need no
+/// credentials.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct Session {
+    pub url: String,
+    pub jwt: String,
+    /// Expiry of the JWT in seconds since the epoch, if it has one.
+    pub expires_at: Option<u64>,
+}
+
+fn sessions_path() -> Result<PathBuf, Box<dyn Error>> {
+    Ok(config_dir()
+        .ok_or("cannot find the config directory, HOME is not set")?
+        .join("sessions.json"))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl Session {
+    pub fn new(url: &str, jwt: String) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            expires_at: jwt_expiry(&jwt),
+            jwt,
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= now())
+    }
+
+    fn load_all() -> Result<Vec<Session>, Box<dyn Error>> {
+        match fs::read(sessions_path()?) {
+            Ok(json) => Ok(serde_json::from_slice(&json)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save_all(sessions: &[Session]) -> Result<(), Box<dyn Error>> {
+        let path = sessions_path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        // the file holds tokens, keep it private to the user from the start
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(0o600);
+            // the mode only applies to new files
+            if path.exists() {
+                fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+            }
+        }
+        options
+            .open(&path)?
+            .write_all(&serde_json::to_vec_pretty(sessions)?)?;
+        Ok(())
+    }
+
+    /// The stored session for a wiki, if it did not expire yet.
+    pub fn load(url: &str) -> Option<Session> {
+        let url = url.trim_end_matches('/');
+        #[cfg(feature = "keyring")]
+        if let Some(session) = crate::keyring::session_entry(url)
+            .get()
+            .ok()
+            .and_then(|json| serde_json::from_str::<Session>(&json).ok())
+        {
+            return Some(session).filter(|session| !session.is_expired());
+        }
+        Self::load_all()
+            .ok()?
+            .into_iter()
+            .find(|session| session.url == url)
+            .filter(|session| !session.is_expired())
+    }
+
+    /// Store the session, replacing the one of the same wiki, in the
+    /// system keyring or the sessions file.
+    pub fn save(&self, keyring: bool) -> Result<(), Box<dyn Error>> {
+        if keyring {
+            #[cfg(feature = "keyring")]
+            return Ok(crate::keyring::session_entry(&self.url)
+                .set(&serde_json::to_string(self)?)?);
+        }
+        let mut sessions = Self::load_all()?;
+        sessions.retain(|session| session.url != self.url);
+        sessions.push(self.clone());
+        Self::save_all(&sessions)
+    }
+
+    /// Remove the session of a wiki and return whether there was one.
+    pub fn remove(url: &str) -> Result<bool, Box<dyn Error>> {
+        let url = url.trim_end_matches('/');
+        #[cfg(feature = "keyring")]
+        let removed = crate::keyring::session_entry(url).delete().is_ok();
+        #[cfg(not(feature = "keyring"))]
+        let removed = false;
+        let mut sessions = Self::load_all()?;
+        let count = sessions.len();
+        sessions.retain(|session| session.url != url);
+        if sessions.len() == count {
+            return Ok(removed);
+        }
+        Self::save_all(&sessions)?;
+        Ok(true)
+    }
+}
+
+pub(crate) fn whoami(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+    let profile = api.user_profile_get()?;
+    println!("{} <{}>", profile.name, profile.email);
+    println!("id: {}", profile.id);
+    println!(
+        "provider: {}",
+        profile
+            .provider_name
+            .or(profile.provider_key)
+            .unwrap_or_default()
+    );
+    println!("groups: {}", profile.groups.join(", "));
+    Ok(())
+}
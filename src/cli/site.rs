@@ -0,0 +1,78 @@
+use crate::common::{is_dry_run, print_dry_run, Execute};
+use crate::output::{print_serialized, Table};
+use clap::Subcommand;
+use colored::Colorize;
+use std::error::Error;
+use wikijs::site::SiteConfigUpdateBuilder;
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum SiteCommand {
+    #[clap(about = "Show the site config")]
+    Get {
+        #[clap(help = "Only show this field")]
+        field: Option<String>,
+    },
+
+    #[clap(about = "Change a field of the site config")]
+    Set {
+        #[clap(help = "Field name, like title or featurePageComments")]
+        field: String,
+
+        #[clap(help = "New value, lists are comma separated")]
+        value: String,
+    },
+}
+
+impl Execute for SiteCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        match self {
+            SiteCommand::Get { field } => site_get(api, field.as_deref()),
+            SiteCommand::Set { field, value } => {
+                let changes =
+                    SiteConfigUpdateBuilder::new().set(field, value)?;
+                if is_dry_run() {
+                    return print_dry_run(format!(
+                        "set {} of the site config to {}",
+                        field, value
+                    ));
+                }
+                api.site_config_update_partial(&changes)?;
+                info!("{}: Site config updated", "success".bold().green());
+                Ok(())
+            }
+        }
+    }
+}
+
+fn site_get(
+    api: wikijs::Api,
+    field: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let config = serde_json::to_value(api.site_config_get()?)?;
+    let fields: serde_json::Map<_, _> = config
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(name, _)| {
+            field.is_none_or(|field| {
+                field.replace('_', "").to_lowercase() == name.to_lowercase()
+            })
+        })
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    if print_serialized(&fields)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["field", "value"]);
+    for (name, value) in &fields {
+        let value = match value {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Null => String::new(),
+            value => value.to_string(),
+        };
+        table.push_record([name.clone(), value]);
+    }
+    table.print()?;
+    Ok(())
+}
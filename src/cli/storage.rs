@@ -0,0 +1,188 @@
+use crate::common::{
+    is_dry_run, parse_config_option, print_dry_run, settings_diff, Execute,
+};
+use crate::output::{print_serialized, Table};
+use clap::{ArgAction, Subcommand};
+use colored::Colorize;
+use std::error::Error;
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum StorageCommand {
+    #[clap(about = "List storage targets with their sync status")]
+    List {},
+
+    #[clap(about = "Show the sync status of the enabled storage targets")]
+    Status {},
+
+    #[clap(about = "Update a storage target")]
+    Update {
+        #[clap(help = "Storage target key, like git")]
+        key: String,
+
+        #[clap(long, help = "Enable the target", conflicts_with = "disable")]
+        enable: bool,
+
+        #[clap(long, help = "Disable the target")]
+        disable: bool,
+
+        #[clap(short, long, help = "Sync mode (sync, push, pull)")]
+        mode: Option<String>,
+
+        #[clap(
+            short,
+            long,
+            help = "Sync interval as ISO 8601 duration, like PT5M"
+        )]
+        sync_interval: Option<String>,
+
+        #[clap(
+            short,
+            long = "config",
+            help = "Config option as key=value, the value may be JSON",
+            value_parser = parse_config_option,
+            action = ArgAction::Append
+        )]
+        config: Vec<(String, serde_json::Value)>,
+    },
+
+    #[clap(about = "Execute an action of a storage target")]
+    Action {
+        #[clap(help = "Storage target key, like git")]
+        key: String,
+
+        #[clap(help = "Action handler, like sync or importAll")]
+        handler: String,
+    },
+}
+
+impl Execute for StorageCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        match self {
+            StorageCommand::List {} => storage_list(api),
+            StorageCommand::Status {} => storage_status(api),
+            StorageCommand::Update {
+                key,
+                enable,
+                disable,
+                mode,
+                sync_interval,
+                config,
+            } => storage_update(
+                api,
+                key,
+                enable.then_some(true).or(disable.then_some(false)),
+                mode.to_owned(),
+                sync_interval.to_owned(),
+                config,
+            ),
+            StorageCommand::Action { key, handler } => {
+                if is_dry_run() {
+                    return print_dry_run(format!(
+                        "execute action {} of storage target {}",
+                        handler, key
+                    ));
+                }
+                api.storage_action_execute(key.to_owned(), handler.to_owned())?;
+                info!("{}: Action executed", "success".bold().green());
+                Ok(())
+            }
+        }
+    }
+}
+
+fn storage_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+    let targets = api.storage_target_list()?;
+    let status = api.storage_status_list()?;
+    if print_serialized(&status)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record([
+        "key",
+        "title",
+        "is_enabled",
+        "mode",
+        "sync_interval",
+        "status",
+        "last_attempt",
+        "last_error",
+    ]);
+    for target in targets {
+        let state = status.iter().find(|s| s.key == target.key);
+        let last_error = state
+            .filter(|s| s.status == "error")
+            .map(|s| s.message.as_str())
+            .unwrap_or("");
+        table.push_record([
+            target.key.as_str(),
+            target.title.as_str(),
+            target.is_enabled.to_string().as_str(),
+            target.mode.as_deref().unwrap_or(""),
+            target.sync_interval.as_deref().unwrap_or(""),
+            state.map(|s| s.status.as_str()).unwrap_or(""),
+            state.map(|s| s.last_attempt.as_str()).unwrap_or(""),
+            last_error,
+        ]);
+    }
+    table.print()?;
+    Ok(())
+}
+
+fn storage_status(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+    let status = api.storage_status_list()?;
+    if print_serialized(&status)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["key", "title", "status", "message", "last_attempt"]);
+    for state in status {
+        table.push_record([
+            state.key,
+            state.title,
+            state.status,
+            state.message,
+            state.last_attempt,
+        ]);
+    }
+    table.print()?;
+    Ok(())
+}
+
+fn storage_update(
+    api: wikijs::Api,
+    key: &str,
+    is_enabled: Option<bool>,
+    mode: Option<String>,
+    sync_interval: Option<String>,
+    config: &[(String, serde_json::Value)],
+) -> Result<(), Box<dyn Error>> {
+    let target = api
+        .storage_target_list()?
+        .into_iter()
+        .find(|target| target.key == key)
+        .ok_or(format!("unknown storage target {}", key))?;
+    let mut input = target.to_input();
+    let old = input.clone();
+    if let Some(is_enabled) = is_enabled {
+        input.is_enabled = is_enabled;
+    }
+    if let Some(mode) = mode {
+        input.mode = mode;
+    }
+    if sync_interval.is_some() {
+        input.sync_interval = sync_interval;
+    }
+    for (key, value) in config {
+        input.set_config(key, value.clone());
+    }
+    if is_dry_run() {
+        return print_dry_run(format!(
+            "update storage target {}: {}",
+            key,
+            settings_diff(&old, &input)
+        ));
+    }
+    api.storage_target_update(vec![input])?;
+    info!("{}: Storage target updated", "success".bold().green());
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use crate::common::{is_dry_run, print_dry_run, Execute};
+use crate::output::{print_serialized, Table};
+use clap::Subcommand;
+use colored::Colorize;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use wikijs::sync::{SyncItem, SyncResult, SyncStatus};
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum SyncCommand {
+    #[clap(about = "Show the differences between a directory and the wiki")]
+    Status {
+        #[clap(help = "Directory with one file per page")]
+        dir: PathBuf,
+    },
+
+    #[clap(about = "Upload local changes to the wiki")]
+    Push {
+        #[clap(help = "Directory with one file per page")]
+        dir: PathBuf,
+    },
+
+    #[clap(about = "Download changes from the wiki")]
+    Pull {
+        #[clap(help = "Directory with one file per page")]
+        dir: PathBuf,
+    },
+}
+
+impl Execute for SyncCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        match self {
+            SyncCommand::Status { dir } => sync_status(api, dir),
+            SyncCommand::Push { dir } | SyncCommand::Pull { dir }
+                if is_dry_run() =>
+            {
+                sync_plan(api, dir, matches!(self, SyncCommand::Push { .. }))
+            }
+            SyncCommand::Push { dir } => {
+                print_sync_result(api.sync_push(dir, None)?)
+            }
+            SyncCommand::Pull { dir } => {
+                print_sync_result(api.sync_pull(dir, None)?)
+            }
+        }
+    }
+}
+
+fn sync_status(api: wikijs::Api, dir: &Path) -> Result<(), Box<dyn Error>> {
+    let items: Vec<_> = api
+        .sync_status(dir)?
+        .into_iter()
+        .filter(|item| item.status != SyncStatus::Unchanged)
+        .collect();
+    if print_serialized(&items)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["status", "id", "locale", "path", "file"]);
+    for item in items {
+        table.push_record([
+            item.status.to_string(),
+            item.id.map(|id| id.to_string()).unwrap_or_default(),
+            item.locale,
+            item.path,
+            item.file.unwrap_or_default(),
+        ]);
+    }
+    table.print()?;
+    Ok(())
+}
+
+fn page_label(item: &SyncItem) -> String {
+    match item.id {
+        Some(id) => format!("{} ({}/{})", id, item.locale, item.path),
+        None => format!("{}/{}", item.locale, item.path),
+    }
+}
+
+// print what a push or pull would change, conflicts are left alone just
+// like when applying it
+fn sync_plan(
+    api: wikijs::Api,
+    dir: &Path,
+    push: bool,
+) -> Result<(), Box<dyn Error>> {
+    for item in api.sync_status(dir)? {
+        let file = item.file.clone().unwrap_or_default();
+        match (push, item.status) {
+            (_, SyncStatus::Conflict) => eprintln!(
+                "{}: {} has a conflict and is left alone",
+                "warning".bold().yellow(),
+                page_label(&item)
+            ),
+            (true, SyncStatus::LocalNew) => print_dry_run(format!(
+                "create page {} from {}",
+                page_label(&item),
+                file
+            ))?,
+            (true, SyncStatus::LocalModified) => print_dry_run(format!(
+                "update page {} from {}",
+                page_label(&item),
+                file
+            ))?,
+            (true, SyncStatus::LocalDeleted) => {
+                print_dry_run(format!("delete page {}", page_label(&item)))?
+            }
+            (false, SyncStatus::RemoteNew | SyncStatus::RemoteModified) => {
+                print_dry_run(format!("download page {}", page_label(&item)))?
+            }
+            (false, SyncStatus::RemoteDeleted) => {
+                print_dry_run(format!("remove {}", file))?
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn print_sync_result(results: SyncResult) -> Result<(), Box<dyn Error>> {
+    let rows: Vec<_> = results
+        .into_iter()
+        .map(|(item, result)| {
+            let status = match result {
+                Ok(()) => "ok".to_string(),
+                Err(e) => e.to_string(),
+            };
+            (item, status)
+        })
+        .collect();
+    let records: Vec<_> = rows
+        .iter()
+        .map(|(item, status)| {
+            serde_json::json!({
+                "change": item.status,
+                "locale": item.locale,
+                "path": item.path,
+                "status": status,
+            })
+        })
+        .collect();
+    if print_serialized(&records)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["change", "locale", "path", "status"]);
+    for (item, status) in rows {
+        table.push_record([
+            item.status.to_string(),
+            item.locale,
+            item.path,
+            status,
+        ]);
+    }
+    table.print()?;
+    Ok(())
+}
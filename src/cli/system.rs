@@ -1,30 +1,222 @@
-use crate::common::Execute;
-use clap::Subcommand;
+use crate::common::{is_dry_run, print_dry_run, Execute};
+use crate::output::{print_serialized, Table};
+use clap::{ArgAction, Subcommand};
+use colored::Colorize;
 use std::error::Error;
-use tabled::{builder::Builder, settings::Style};
+use std::time::Instant;
+use wikijs::system::SystemFlagInput;
 
 #[derive(Subcommand, Debug)]
 pub(crate) enum SystemFlagCommand {
     #[clap(about = "List system flags")]
     List {},
+
+    #[clap(about = "Set a system flag")]
+    Set {
+        #[clap(help = "Flag key")]
+        key: String,
+
+        #[clap(help = "Flag value", action = ArgAction::Set)]
+        value: bool,
+    },
 }
 
 impl Execute for SystemFlagCommand {
     fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
         match self {
             SystemFlagCommand::List {} => system_flag_list(api),
+            SystemFlagCommand::Set { key, value } => {
+                if is_dry_run() {
+                    return print_dry_run(format!(
+                        "set system flag {} to {}",
+                        key, value
+                    ));
+                }
+                api.system_flags_update(vec![SystemFlagInput {
+                    key: key.to_string(),
+                    value: *value,
+                }])?;
+                info!("{}: System flag set", "success".bold().green());
+                Ok(())
+            }
         }
     }
 }
 
 fn system_flag_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
     let flags = api.system_flag_list()?;
-    let mut builder = Builder::new();
-    builder.push_record(["key", "value"]);
+    if print_serialized(&flags)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["key", "value"]);
     for flag in flags {
-        builder
-            .push_record([flag.key.as_str(), flag.value.to_string().as_str()]);
+        table.push_record([flag.key.as_str(), flag.value.to_string().as_str()]);
+    }
+    table.print()?;
+    Ok(())
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum SystemCommand {
+    #[clap(about = "Show version, database and platform information")]
+    Info {},
+
+    #[clap(about = "List system extensions")]
+    Extensions {},
+
+    #[clap(about = "Show the status of the last export")]
+    ExportStatus {},
+
+    #[clap(about = "Upgrade Wiki.js to the latest version")]
+    Upgrade {},
+
+    #[clap(about = "Telemetry commands")]
+    Telemetry {
+        #[clap(subcommand)]
+        command: TelemetryCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum TelemetryCommand {
+    #[clap(about = "Enable telemetry")]
+    Enable {},
+
+    #[clap(about = "Disable telemetry")]
+    Disable {},
+
+    #[clap(about = "Reset the telemetry client ID")]
+    ResetClientId {},
+}
+
+impl Execute for SystemCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        match self {
+            SystemCommand::Info {} => system_info(api),
+            SystemCommand::Extensions {} => system_extensions(api),
+            SystemCommand::ExportStatus {} => system_export_status(api),
+            SystemCommand::Upgrade {} => {
+                if is_dry_run() {
+                    return print_dry_run(
+                        "upgrade Wiki.js to the latest version",
+                    );
+                }
+                api.system_upgrade_perform()?;
+                info!("{}: Upgrade started", "success".bold().green());
+                Ok(())
+            }
+            SystemCommand::Telemetry { command } => command.execute(api),
+        }
+    }
+}
+
+impl Execute for TelemetryCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        if is_dry_run() {
+            return print_dry_run(match self {
+                TelemetryCommand::Enable {} => "enable telemetry",
+                TelemetryCommand::Disable {} => "disable telemetry",
+                TelemetryCommand::ResetClientId {} => {
+                    "reset the telemetry client ID"
+                }
+            });
+        }
+        match self {
+            TelemetryCommand::Enable {} => api.telemetry_set(true)?,
+            TelemetryCommand::Disable {} => api.telemetry_set(false)?,
+            TelemetryCommand::ResetClientId {} => {
+                api.telemetry_client_id_reset()?
+            }
+        }
+        info!("{}: Telemetry updated", "success".bold().green());
+        Ok(())
+    }
+}
+
+fn system_info(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+    let start = Instant::now();
+    let info = api.system_info_get()?;
+    let latency = start.elapsed();
+    if print_serialized(&info)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["key", "value"]);
+    for (key, value) in [
+        ("current_version", info.current_version),
+        ("latest_version", info.latest_version),
+        ("db_type", info.db_type),
+        ("db_version", info.db_version),
+        ("db_host", info.db_host),
+        ("latency", Some(format!("{} ms", latency.as_millis()))),
+        ("platform", info.platform),
+        ("operating_system", info.operating_system),
+        ("hostname", info.hostname),
+        ("cpu_cores", info.cpu_cores.map(|c| c.to_string())),
+        ("ram_total", info.ram_total),
+        ("node_version", info.node_version),
+        ("working_directory", info.working_directory),
+        ("config_file", info.config_file),
+        ("pages_total", info.pages_total.map(|c| c.to_string())),
+        ("users_total", info.users_total.map(|c| c.to_string())),
+        ("groups_total", info.groups_total.map(|c| c.to_string())),
+        ("tags_total", info.tags_total.map(|c| c.to_string())),
+        ("telemetry", info.telemetry.map(|t| t.to_string())),
+        (
+            "upgrade_capable",
+            info.upgrade_capable.map(|u| u.to_string()),
+        ),
+    ] {
+        table.push_record([key.to_string(), value.unwrap_or_default()]);
+    }
+    table.print()?;
+    Ok(())
+}
+
+fn system_extensions(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+    let extensions = api.system_extension_list()?;
+    if print_serialized(&extensions)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record([
+        "key",
+        "title",
+        "description",
+        "is_installed",
+        "is_compatible",
+    ]);
+    for extension in extensions {
+        table.push_record([
+            extension.key,
+            extension.title,
+            extension.description,
+            extension.is_installed.to_string(),
+            extension.is_compatible.to_string(),
+        ]);
+    }
+    table.print()?;
+    Ok(())
+}
+
+fn system_export_status(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+    let status = api.system_export_status_get()?;
+    if print_serialized(&status)? {
+        return Ok(());
     }
-    println!("{}", builder.build().with(Style::rounded()));
+    let mut table = Table::new();
+    table.push_record(["key", "value"]);
+    table.push_record(["status", &status.status.unwrap_or_default()]);
+    table.push_record([
+        "progress",
+        &status
+            .progress
+            .map(|p| format!("{}%", p))
+            .unwrap_or_default(),
+    ]);
+    table.push_record(["message", &status.message.unwrap_or_default()]);
+    table.push_record(["started_at", &status.started_at.unwrap_or_default()]);
+    table.print()?;
     Ok(())
 }
@@ -1,33 +1,123 @@
-use crate::common::Execute;
-use clap::Subcommand;
+use crate::common::{is_dry_run, print_dry_run, settings_diff, Execute};
+use crate::output::{print_serialized, Table};
+use clap::{Args, Subcommand};
+use colored::Colorize;
 use std::error::Error;
-use tabled::{builder::Builder, settings::Style};
+use std::path::PathBuf;
 
 #[derive(Subcommand, Debug)]
 pub(crate) enum ThemeCommand {
     #[clap(about = "List themes")]
     List {},
+
+    #[clap(about = "Update the theme configuration")]
+    Update(ThemeUpdateArgs),
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct ThemeUpdateArgs {
+    #[clap(short, long, help = "Theme key, like default")]
+    theme: Option<String>,
+
+    #[clap(short, long, help = "Icon set, like mdi")]
+    iconset: Option<String>,
+
+    #[clap(long, help = "Enable dark mode", conflicts_with = "light_mode")]
+    dark_mode: bool,
+
+    #[clap(long, help = "Disable dark mode")]
+    light_mode: bool,
+
+    #[clap(long, help = "Table of contents position (left, right, hidden)")]
+    toc_position: Option<String>,
+
+    #[clap(long, help = "File with CSS to inject")]
+    inject_css_file: Option<PathBuf>,
+
+    #[clap(long, help = "File with HTML to inject into the head")]
+    inject_head_file: Option<PathBuf>,
+
+    #[clap(long, help = "File with HTML to inject at the end of the body")]
+    inject_body_file: Option<PathBuf>,
 }
 
 impl Execute for ThemeCommand {
     fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
         match self {
             ThemeCommand::List {} => theme_list(api),
+            ThemeCommand::Update(args) => theme_update(api, args),
         }
     }
 }
 
 pub fn theme_list(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
     let themes = api.theme_list()?;
-    let mut builder = Builder::new();
-    builder.push_record(["key", "title", "author"]);
+    if print_serialized(&themes)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["key", "title", "author"]);
     for theme in themes {
-        builder.push_record([
+        table.push_record([
             theme.key.unwrap_or("".to_string()).as_str(),
             theme.title.unwrap_or("".to_string()).as_str(),
             theme.author.unwrap_or("".to_string()).as_str(),
         ]);
     }
-    println!("{}", builder.build().with(Style::rounded()));
+    table.print()?;
+    Ok(())
+}
+
+fn read_file(path: &Option<PathBuf>) -> Result<Option<String>, Box<dyn Error>> {
+    Ok(match path {
+        Some(path) => Some(std::fs::read_to_string(path)?),
+        None => None,
+    })
+}
+
+// unset options keep their current value
+fn theme_update(
+    api: wikijs::Api,
+    args: &ThemeUpdateArgs,
+) -> Result<(), Box<dyn Error>> {
+    let old = api.theme_config_get()?;
+    let mut config = old.clone();
+    if let Some(theme) = &args.theme {
+        config.theme = theme.clone();
+    }
+    if let Some(iconset) = &args.iconset {
+        config.iconset = iconset.clone();
+    }
+    if args.dark_mode || args.light_mode {
+        config.dark_mode = args.dark_mode;
+    }
+    if args.toc_position.is_some() {
+        config.toc_position = args.toc_position.clone();
+    }
+    if let Some(css) = read_file(&args.inject_css_file)? {
+        config.inject_css = Some(css);
+    }
+    if let Some(head) = read_file(&args.inject_head_file)? {
+        config.inject_head = Some(head);
+    }
+    if let Some(body) = read_file(&args.inject_body_file)? {
+        config.inject_body = Some(body);
+    }
+    if is_dry_run() {
+        return print_dry_run(format!(
+            "update the theme config: {}",
+            settings_diff(&old, &config)
+        ));
+    }
+    api.theme_config_update(
+        config.theme,
+        config.iconset,
+        config.dark_mode,
+        config.toc_position,
+        config.inject_css,
+        config.inject_head,
+        config.inject_body,
+    )?;
+    info!("{}: Theme updated", "success".bold().green());
     Ok(())
 }
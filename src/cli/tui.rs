@@ -0,0 +1,606 @@
+use crate::common::{is_dry_run, open_url};
+use crate::page::page_edit;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, execute, terminal};
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::io::Write;
+use wikijs::page::{Page, PageListItem};
+
+// the terminal in raw mode on the alternate screen, restored when dropped
+struct Terminal {
+    // rows and columns, updated when the terminal is resized
+    size: Cell<(usize, usize)>,
+}
+
+impl Terminal {
+    fn new() -> Result<Self, Box<dyn Error>> {
+        let terminal = Self {
+            size: Cell::new((24, 80)),
+        };
+        terminal.resume()?;
+        Ok(terminal)
+    }
+
+    fn resume(&self) -> Result<(), Box<dyn Error>> {
+        terminal::enable_raw_mode()
+            .map_err(|_| "cannot set up the terminal, is stdin a terminal?")?;
+        execute!(
+            std::io::stdout(),
+            terminal::EnterAlternateScreen,
+            cursor::Hide
+        )?;
+        // it may have been resized while suspended
+        let (cols, rows) = terminal::size()?;
+        self.resize(cols, rows);
+        Ok(())
+    }
+
+    fn suspend(&self) {
+        let _ = execute!(
+            std::io::stdout(),
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+
+    fn resize(&self, cols: u16, rows: u16) {
+        self.size.set((rows.into(), cols.into()));
+    }
+
+    // rows and columns
+    fn size(&self) -> (usize, usize) {
+        self.size.get()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        self.suspend();
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Key {
+    Char(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    Backspace,
+    Esc,
+    Interrupt,
+    /// The terminal was resized and needs to be drawn again.
+    Resize,
+    Other,
+}
+
+fn read_key(terminal: &Terminal) -> Result<Key, Box<dyn Error>> {
+    loop {
+        let key = match event::read()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => key,
+            Event::Resize(cols, rows) => {
+                terminal.resize(cols, rows);
+                return Ok(Key::Resize);
+            }
+            _ => continue,
+        };
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        return Ok(match key.code {
+            KeyCode::Char('c' | 'd') if control => Key::Interrupt,
+            KeyCode::Char(_) if control => Key::Other,
+            KeyCode::Char(c) => Key::Char(c),
+            KeyCode::Up => Key::Up,
+            KeyCode::Down => Key::Down,
+            KeyCode::Left => Key::Left,
+            KeyCode::Right => Key::Right,
+            KeyCode::PageUp => Key::PageUp,
+            KeyCode::PageDown => Key::PageDown,
+            KeyCode::Home => Key::Home,
+            KeyCode::End => Key::End,
+            KeyCode::Enter => Key::Enter,
+            KeyCode::Backspace => Key::Backspace,
+            KeyCode::Esc => Key::Esc,
+            _ => Key::Other,
+        });
+    }
+}
+
+// cut or pad the text to exactly the width
+fn fit(text: &str, width: usize) -> String {
+    let mut fitted: String = text
+        .chars()
+        .map(|c| if c == '\t' { ' ' } else { c })
+        .filter(|c| !c.is_control())
+        .take(width)
+        .collect();
+    let len = fitted.chars().count();
+    fitted.extend(std::iter::repeat_n(' ', width - len));
+    fitted
+}
+
+struct Row {
+    depth: usize,
+    name: String,
+    // the path of the folder if the row has children
+    folder: Option<String>,
+    page: Option<usize>,
+}
+
+struct App {
+    api: wikijs::Api,
+    url: String,
+    locale: String,
+    editor: String,
+    pages: Vec<PageListItem>,
+    expanded: HashSet<String>,
+    rows: Vec<Row>,
+    selected: usize,
+    offset: usize,
+    query: String,
+    searching: bool,
+    previews: HashMap<i64, Result<Page, String>>,
+    preview_offset: usize,
+    message: String,
+}
+
+const HELP: &str = "j/k move  enter fold  / search  e edit  m move  \
+                    d delete  o open  r reload  q quit";
+
+impl App {
+    fn load(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut pages = self.api.page_list(
+            None,
+            None,
+            None,
+            None,
+            Some(self.locale.clone()),
+            None,
+            None,
+        )?;
+        pages.sort_by(|a, b| a.path.cmp(&b.path));
+        self.pages = pages;
+        self.previews.clear();
+        self.build_rows();
+        Ok(())
+    }
+
+    fn build_rows(&mut self) {
+        let selected = self.current_path();
+        self.rows = if self.query.is_empty() {
+            self.tree_rows()
+        } else {
+            let query = self.query.to_lowercase();
+            self.pages
+                .iter()
+                .enumerate()
+                .filter(|(_, page)| {
+                    page.path.to_lowercase().contains(&query)
+                        || page
+                            .title
+                            .as_ref()
+                            .is_some_and(|t| t.to_lowercase().contains(&query))
+                })
+                .map(|(index, page)| Row {
+                    depth: 0,
+                    name: page.path.clone(),
+                    folder: None,
+                    page: Some(index),
+                })
+                .collect()
+        };
+        // keep the selection on the same path if it is still shown
+        self.selected = selected
+            .and_then(|path| {
+                self.rows.iter().position(|row| {
+                    self.row_path(row).as_deref() == Some(path.as_str())
+                })
+            })
+            .unwrap_or(self.selected.min(self.rows.len().saturating_sub(1)));
+    }
+
+    // the pages and the folders above them, the rows below collapsed
+    // folders left out
+    fn tree_rows(&self) -> Vec<Row> {
+        // ordered by segments so children follow their folder
+        let mut nodes: BTreeMap<Vec<&str>, Option<usize>> = BTreeMap::new();
+        for (index, page) in self.pages.iter().enumerate() {
+            let segments: Vec<&str> = page.path.split('/').collect();
+            for depth in 1..segments.len() {
+                nodes.entry(segments[..depth].to_vec()).or_insert(None);
+            }
+            nodes.insert(segments, Some(index));
+        }
+        let nodes: Vec<_> = nodes.into_iter().collect();
+        let mut rows = Vec::new();
+        for (i, (segments, page)) in nodes.iter().enumerate() {
+            let visible = (1..segments.len()).all(|depth| {
+                self.expanded.contains(&segments[..depth].join("/"))
+            });
+            if !visible {
+                continue;
+            }
+            let has_children = nodes
+                .get(i + 1)
+                .is_some_and(|(next, _)| next.starts_with(segments));
+            rows.push(Row {
+                depth: segments.len() - 1,
+                name: segments.last().unwrap_or(&"").to_string(),
+                folder: has_children.then(|| segments.join("/")),
+                page: *page,
+            });
+        }
+        rows
+    }
+
+    fn row_path(&self, row: &Row) -> Option<String> {
+        row.folder
+            .clone()
+            .or_else(|| row.page.map(|index| self.pages[index].path.clone()))
+    }
+
+    fn current_path(&self) -> Option<String> {
+        self.rows
+            .get(self.selected)
+            .and_then(|row| self.row_path(row))
+    }
+
+    fn current_page(&self) -> Option<&PageListItem> {
+        let row = self.rows.get(self.selected)?;
+        row.page.map(|index| &self.pages[index])
+    }
+
+    fn select(&mut self, index: usize) {
+        let index = index.min(self.rows.len().saturating_sub(1));
+        if index != self.selected {
+            self.selected = index;
+            self.preview_offset = 0;
+        }
+    }
+
+    // lines of the preview with their style
+    fn preview(&mut self) -> Vec<(&'static str, String)> {
+        let Some(row) = self.rows.get(self.selected) else {
+            return vec![("", "No pages".to_string())];
+        };
+        let Some(index) = row.page else {
+            let folder = row.folder.clone().unwrap_or_default();
+            let count = self
+                .pages
+                .iter()
+                .filter(|page| page.path.starts_with(&format!("{}/", folder)))
+                .count();
+            return vec![
+                ("\x1b[1m", format!("{}/", folder)),
+                ("", String::new()),
+                ("", format!("{} pages", count)),
+            ];
+        };
+        let id = self.pages[index].id;
+        let preview = self.previews.entry(id).or_insert_with(|| {
+            self.api.page_get(id).map_err(|e| e.to_string())
+        });
+        match preview {
+            Ok(page) => {
+                let mut lines = vec![
+                    ("\x1b[1m", page.title.clone()),
+                    ("\x1b[2m", format!("{}/{}", page.locale, page.path)),
+                ];
+                if !page.description.is_empty() {
+                    lines.push(("", page.description.clone()));
+                }
+                lines.push(("", String::new()));
+                lines.extend(page.content.lines().map(|l| ("", l.to_string())));
+                lines
+            }
+            Err(e) => vec![("\x1b[31m", format!("error: {}", e))],
+        }
+    }
+
+    fn render(&mut self, terminal: &Terminal, status: Option<&str>) {
+        let (rows, cols) = terminal.size();
+        let height = rows.saturating_sub(2).max(1);
+        let left = (cols * 2 / 5).clamp(20, 60).min(cols.saturating_sub(2));
+        let right = cols.saturating_sub(left + 1);
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if self.selected >= self.offset + height {
+            self.offset = self.selected + 1 - height;
+        }
+        let preview = self.preview();
+        let mut screen = String::from("\x1b[H");
+        let title = format!(" wikijs  {}  {}", self.url, self.locale);
+        screen.push_str(&format!("\x1b[7m{}\x1b[0m", fit(&title, cols)));
+        for line in 0..height {
+            screen.push_str(&format!("\x1b[{};1H", line + 2));
+            let tree = match self.rows.get(self.offset + line) {
+                Some(row) => {
+                    let marker = match &row.folder {
+                        Some(folder) if self.expanded.contains(folder) => "▾ ",
+                        Some(_) => "▸ ",
+                        None => "  ",
+                    };
+                    let text = format!(
+                        "{}{}{}",
+                        "  ".repeat(row.depth),
+                        marker,
+                        row.name
+                    );
+                    let style = match (
+                        self.offset + line == self.selected,
+                        row.page.is_none(),
+                    ) {
+                        (true, _) => "\x1b[7m",
+                        (false, true) => "\x1b[1;34m",
+                        (false, false) => "",
+                    };
+                    format!("{}{}\x1b[0m", style, fit(&text, left))
+                }
+                None => fit("", left),
+            };
+            screen.push_str(&tree);
+            screen.push_str("\x1b[2m│\x1b[0m");
+            let (style, text) = preview
+                .get(self.preview_offset + line)
+                .map(|(style, text)| (*style, text.as_str()))
+                .unwrap_or(("", ""));
+            screen.push_str(&format!("{}{}\x1b[0m", style, fit(text, right)));
+        }
+        let status = match status {
+            Some(status) => status.to_string(),
+            None if self.searching => format!("/{}", self.query),
+            None if !self.message.is_empty() => self.message.clone(),
+            None => format!("\x1b[2m{}\x1b[0m", HELP),
+        };
+        screen.push_str(&format!("\x1b[{};1H{}\x1b[K", rows, status));
+        print!("{}", screen);
+        let _ = std::io::stdout().flush();
+    }
+
+    // ask for a line of text on the status line, None if cancelled
+    fn prompt(
+        &mut self,
+        terminal: &Terminal,
+        question: &str,
+        initial: &str,
+    ) -> Result<Option<String>, Box<dyn Error>> {
+        let mut answer = initial.to_string();
+        loop {
+            self.render(terminal, Some(&format!("{}{}", question, answer)));
+            match read_key(terminal)? {
+                Key::Enter => return Ok(Some(answer)),
+                Key::Esc | Key::Interrupt => return Ok(None),
+                Key::Backspace => {
+                    answer.pop();
+                }
+                Key::Char(c) => answer.push(c),
+                _ => {}
+            }
+        }
+    }
+
+    fn edit(&mut self, terminal: &Terminal) -> Result<(), Box<dyn Error>> {
+        let Some(page) = self.current_page() else {
+            return Ok(());
+        };
+        let id = page.id;
+        let path = page.path.clone();
+        terminal.suspend();
+        let result = page_edit(&self.api, &id.to_string(), "", &self.editor);
+        terminal.resume()?;
+        self.previews.remove(&id);
+        self.message = match result {
+            Ok(()) => format!("edited {}", path),
+            Err(e) => format!("error: {}", e),
+        };
+        Ok(())
+    }
+
+    fn move_page(&mut self, terminal: &Terminal) -> Result<(), Box<dyn Error>> {
+        let Some(page) = self.current_page() else {
+            return Ok(());
+        };
+        let (id, path) = (page.id, page.path.clone());
+        let Some(destination) = self.prompt(terminal, "Move to: ", &path)?
+        else {
+            return Ok(());
+        };
+        let destination = destination.trim_matches('/').to_string();
+        if destination.is_empty() || destination == path {
+            return Ok(());
+        }
+        if is_dry_run() {
+            self.message = format!(
+                "dry run: would move page {} ({}) to {}",
+                id, path, destination
+            );
+            return Ok(());
+        }
+        self.api
+            .page_move(id, destination.clone(), self.locale.clone())?;
+        self.message = format!("moved {} to {}", path, destination);
+        self.load()
+    }
+
+    fn delete(&mut self, terminal: &Terminal) -> Result<(), Box<dyn Error>> {
+        let Some(page) = self.current_page() else {
+            return Ok(());
+        };
+        let (id, path) = (page.id, page.path.clone());
+        let question = format!("Delete {}? [y/N] ", path);
+        let key = loop {
+            self.render(terminal, Some(&question));
+            match read_key(terminal)? {
+                Key::Resize => continue,
+                key => break key,
+            }
+        };
+        if key != Key::Char('y') {
+            return Ok(());
+        }
+        if is_dry_run() {
+            self.message =
+                format!("dry run: would delete page {} ({})", id, path);
+            return Ok(());
+        }
+        self.api.page_delete(id)?;
+        self.message = format!("deleted {}", path);
+        self.load()
+    }
+
+    fn toggle(&mut self, expand: Option<bool>) {
+        let Some(folder) =
+            self.rows.get(self.selected).and_then(|r| r.folder.clone())
+        else {
+            return;
+        };
+        let expand = expand.unwrap_or(!self.expanded.contains(&folder));
+        match expand {
+            true => self.expanded.insert(folder),
+            false => self.expanded.remove(&folder),
+        };
+        self.build_rows();
+    }
+
+    fn collapse_or_parent(&mut self) {
+        let Some(row) = self.rows.get(self.selected) else {
+            return;
+        };
+        if row
+            .folder
+            .as_ref()
+            .is_some_and(|f| self.expanded.contains(f))
+        {
+            return self.toggle(Some(false));
+        }
+        let depth = row.depth;
+        if let Some(parent) = self.rows[..self.selected]
+            .iter()
+            .rposition(|r| r.depth < depth)
+        {
+            self.select(parent);
+        }
+    }
+
+    // handle a key, false to quit
+    fn handle(
+        &mut self,
+        terminal: &Terminal,
+        key: Key,
+    ) -> Result<bool, Box<dyn Error>> {
+        let (rows, _) = terminal.size();
+        let height = rows.saturating_sub(2).max(1);
+        self.message.clear();
+        if self.searching {
+            match key {
+                Key::Char(c) => self.query.push(c),
+                Key::Backspace => {
+                    self.query.pop();
+                }
+                Key::Enter => self.searching = false,
+                Key::Esc => {
+                    self.searching = false;
+                    self.query.clear();
+                }
+                Key::Interrupt => return Ok(false),
+                _ => return self.navigate(key, height),
+            }
+            self.build_rows();
+            return Ok(true);
+        }
+        match key {
+            Key::Char('q') | Key::Interrupt => return Ok(false),
+            Key::Char('/') => self.searching = true,
+            Key::Esc => {
+                self.query.clear();
+                self.build_rows();
+            }
+            Key::Enter => self.toggle(None),
+            Key::Char('l') | Key::Right => self.toggle(Some(true)),
+            Key::Char('h') | Key::Left => self.collapse_or_parent(),
+            Key::Char('J') => self.preview_offset += 1,
+            Key::Char('K') => {
+                self.preview_offset = self.preview_offset.saturating_sub(1)
+            }
+            Key::Char('e') => self.edit(terminal)?,
+            Key::Char('m') => self.move_page(terminal)?,
+            Key::Char('d') => self.delete(terminal)?,
+            Key::Char('r') => self.load()?,
+            Key::Char('o') => {
+                if let Some(page) = self.current_page() {
+                    open_url(&self.api.page_url(&page.locale, &page.path))?;
+                }
+            }
+            key => return self.navigate(key, height),
+        }
+        Ok(true)
+    }
+
+    fn navigate(
+        &mut self,
+        key: Key,
+        height: usize,
+    ) -> Result<bool, Box<dyn Error>> {
+        let last = self.rows.len().saturating_sub(1);
+        match key {
+            Key::Char('j') | Key::Down => self.select(self.selected + 1),
+            Key::Char('k') | Key::Up => {
+                self.select(self.selected.saturating_sub(1))
+            }
+            Key::PageDown => self.select(self.selected + height),
+            Key::PageUp => self.select(self.selected.saturating_sub(height)),
+            Key::Char('g') | Key::Home => self.select(0),
+            Key::Char('G') | Key::End => self.select(last),
+            _ => {}
+        }
+        Ok(true)
+    }
+}
+
+/// Browse the pages of a locale in a tree with a preview of the selected
+/// page, and edit, move or delete them.
+pub(crate) fn run(
+    api: wikijs::Api,
+    url: &str,
+    locale: &str,
+    editor: &str,
+) -> Result<(), Box<dyn Error>> {
+    let mut app = App {
+        api,
+        url: url.to_string(),
+        locale: locale.to_string(),
+        editor: editor.to_string(),
+        pages: Vec::new(),
+        expanded: HashSet::new(),
+        rows: Vec::new(),
+        selected: 0,
+        offset: 0,
+        query: String::new(),
+        searching: false,
+        previews: HashMap::new(),
+        preview_offset: 0,
+        message: String::new(),
+    };
+    app.load()?;
+    let terminal = Terminal::new()?;
+    loop {
+        app.render(&terminal, None);
+        let key = match read_key(&terminal)? {
+            Key::Resize => continue,
+            key => key,
+        };
+        // errors of the actions are shown, the browser keeps running
+        match app.handle(&terminal, key) {
+            Ok(true) => {}
+            Ok(false) => return Ok(()),
+            Err(e) => app.message = format!("error: {}", e),
+        }
+    }
+}
@@ -1,8 +1,13 @@
-use crate::common::Execute;
+use crate::common::{
+    confirm, is_dry_run, parse_duration, print_dry_run, Execute, PagingArgs,
+};
+use crate::output::{print_serialized, Table};
 use clap::{ArgAction, Subcommand};
 use colored::Colorize;
 use std::error::Error;
-use tabled::{builder::Builder, settings::Style};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use wikijs::user::UserImportDefaults;
 
 #[derive(Subcommand, Debug)]
 pub(crate) enum UserCommand {
@@ -19,6 +24,9 @@ pub(crate) enum UserCommand {
 
         #[clap(short, long, help = "Order users by this")]
         order_by: Option<String>,
+
+        #[clap(flatten)]
+        paging: PagingArgs,
     },
 
     #[clap(about = "Create a user")]
@@ -146,6 +154,47 @@ pub(crate) enum UserCommand {
         #[clap(short, long, help = "Appearance")]
         appearance: Option<String>,
     },
+
+    #[clap(about = "Deactivate users who did not log in for a while")]
+    DeactivateInactive {
+        #[clap(
+            short,
+            long,
+            help = "Period without login, like 180d, 26w or 1y",
+            value_parser = parse_duration
+        )]
+        since: chrono::Duration,
+
+        #[clap(short = 'n', long, help = "Only list the inactive users")]
+        dry_run: bool,
+    },
+
+    #[clap(about = "Create users from a CSV file")]
+    Import {
+        #[clap(help = "CSV file with the columns email, name and optionally \
+                    password, provider and groups (IDs separated by ;)")]
+        file: PathBuf,
+
+        #[clap(short, long = "group", help = "Group ID for all users")]
+        groups: Vec<i64>,
+
+        #[clap(
+            short = 'P',
+            long = "provider",
+            help = "Provider key of rows without one",
+            default_value = "local"
+        )]
+        provider_key: String,
+
+        #[clap(short, long, help = "Users must change their password")]
+        must_change_password: bool,
+
+        #[clap(short, long, help = "Send a welcome email to every user")]
+        send_welcome_email: bool,
+
+        #[clap(short = 'n', long, help = "Only validate the file")]
+        dry_run: bool,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -197,9 +246,11 @@ impl Execute for UserCommand {
     fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
         match self {
             UserCommand::Get { id } => user_get(api, *id),
-            UserCommand::List { filter, order_by } => {
-                user_list(api, filter.to_owned(), order_by.to_owned())
-            }
+            UserCommand::List {
+                filter,
+                order_by,
+                paging,
+            } => user_list(api, filter.to_owned(), order_by.to_owned(), paging),
             UserCommand::Create {
                 email,
                 name,
@@ -227,6 +278,27 @@ impl Execute for UserCommand {
             UserCommand::Verify { id } => user_verify(api, *id),
             UserCommand::Search { query } => user_search(api, query.to_owned()),
             UserCommand::LastLogins {} => user_last_logins(api),
+            UserCommand::DeactivateInactive { since, dry_run } => {
+                user_deactivate_inactive(api, *since, *dry_run || is_dry_run())
+            }
+            UserCommand::Import {
+                file,
+                groups,
+                provider_key,
+                must_change_password,
+                send_welcome_email,
+                dry_run,
+            } => user_import(
+                api,
+                file,
+                &UserImportDefaults {
+                    groups: groups.to_owned(),
+                    provider_key: provider_key.to_owned(),
+                    must_change_password: *must_change_password,
+                    send_welcome_email: *send_welcome_email,
+                    dry_run: *dry_run || is_dry_run(),
+                },
+            ),
             UserCommand::Update {
                 id,
                 email,
@@ -294,38 +366,41 @@ impl Execute for PasswordCommand {
 
 fn user_get(api: wikijs::Api, id: i64) -> Result<(), Box<dyn Error>> {
     let user = api.user_get(id)?;
-    let mut builder = Builder::new();
-    builder.push_record(["key", "value"]);
-    builder.push_record(["id", user.id.to_string().as_str()]);
-    builder.push_record(["name", user.name.as_str()]);
-    builder.push_record(["email", user.email.as_str()]);
-    builder.push_record(["provider_key", user.provider_key.as_str()]);
-    builder.push_record([
+    if print_serialized(&user)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["key", "value"]);
+    table.push_record(["id", user.id.to_string().as_str()]);
+    table.push_record(["name", user.name.as_str()]);
+    table.push_record(["email", user.email.as_str()]);
+    table.push_record(["provider_key", user.provider_key.as_str()]);
+    table.push_record([
         "provider_name",
         user.provider_name.unwrap_or("".to_string()).as_str(),
     ]);
-    builder.push_record([
+    table.push_record([
         "provider_id",
         user.provider_id.unwrap_or("".to_string()).as_str(),
     ]);
     // providerIs2FACapable
-    builder.push_record(["is_system", user.is_system.to_string().as_str()]);
-    builder.push_record(["is_active", user.is_active.to_string().as_str()]);
-    builder.push_record(["is_verified", user.is_verified.to_string().as_str()]);
-    builder.push_record(["location", user.location.as_str()]);
-    builder.push_record(["job_title", user.job_title.as_str()]);
-    builder.push_record(["timezone", user.timezone.as_str()]);
-    builder.push_record(["date_format", user.date_format.as_str()]);
-    builder.push_record(["appearance", user.appearance.as_str()]);
-    builder.push_record(["created_at", user.created_at.to_string().as_str()]);
-    builder.push_record(["updated_at", user.updated_at.to_string().as_str()]);
-    builder.push_record([
+    table.push_record(["is_system", user.is_system.to_string().as_str()]);
+    table.push_record(["is_active", user.is_active.to_string().as_str()]);
+    table.push_record(["is_verified", user.is_verified.to_string().as_str()]);
+    table.push_record(["location", user.location.as_str()]);
+    table.push_record(["job_title", user.job_title.as_str()]);
+    table.push_record(["timezone", user.timezone.as_str()]);
+    table.push_record(["date_format", user.date_format.as_str()]);
+    table.push_record(["appearance", user.appearance.as_str()]);
+    table.push_record(["created_at", user.created_at.to_string().as_str()]);
+    table.push_record(["updated_at", user.updated_at.to_string().as_str()]);
+    table.push_record([
         "last_login_at",
         user.last_login_at.unwrap_or("".to_string()).as_str(),
     ]);
     // tfaIsActive
     // groups
-    println!("{}", builder.build().with(Style::rounded()));
+    table.print()?;
     Ok(())
 }
 
@@ -333,10 +408,14 @@ fn user_list(
     api: wikijs::Api,
     filter: Option<String>,
     order_by: Option<String>,
+    paging: &PagingArgs,
 ) -> Result<(), Box<dyn Error>> {
-    let users = api.user_list(filter, order_by)?;
-    let mut builder = Builder::new();
-    builder.push_record([
+    let users = paging.apply(api.user_list(filter, order_by)?)?;
+    if print_serialized(&users)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record([
         "id",
         "name",
         "email",
@@ -347,7 +426,7 @@ fn user_list(
         "last_login_at",
     ]);
     for user in users {
-        builder.push_record([
+        table.push_record([
             user.id.to_string().as_str(),
             user.name.as_str(),
             user.email.as_str(),
@@ -358,7 +437,7 @@ fn user_list(
             user.last_login_at.unwrap_or("".to_string()).as_str(),
         ]);
     }
-    println!("{}", builder.build().with(Style::rounded()));
+    table.print()?;
     Ok(())
 }
 
@@ -373,6 +452,12 @@ fn user_create(
     must_change_password: Option<bool>,
     send_welcome_email: Option<bool>,
 ) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        return print_dry_run(format!(
+            "create user {} <{}> with {}",
+            name, email, provider_key
+        ));
+    }
     api.user_create(
         email,
         name,
@@ -382,19 +467,38 @@ fn user_create(
         must_change_password,
         send_welcome_email,
     )?;
-    println!("{}: User created", "success".bold().green());
+    info!("{}: User created", "success".bold().green());
     Ok(())
 }
 
+// a user id with its name and email, to show what a dry run
+// or confirmation is about
+fn user_label(api: &wikijs::Api, id: i64) -> Result<String, Box<dyn Error>> {
+    let user = api.user_get(id)?;
+    Ok(format!("{} ({} <{}>)", id, user.name, user.email))
+}
+
 fn user_activate(api: wikijs::Api, id: i64) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        return print_dry_run(format!(
+            "activate user {}",
+            user_label(&api, id)?
+        ));
+    }
     api.user_activate(id)?;
-    println!("{}: User activated", "success".bold().green());
+    info!("{}: User activated", "success".bold().green());
     Ok(())
 }
 
 fn user_deactivate(api: wikijs::Api, id: i64) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        return print_dry_run(format!(
+            "deactivate user {}",
+            user_label(&api, id)?
+        ));
+    }
     api.user_deactivate(id)?;
-    println!("{}: User deactivated", "success".bold().green());
+    info!("{}: User deactivated", "success".bold().green());
     Ok(())
 }
 
@@ -403,8 +507,17 @@ fn user_delete(
     id: i64,
     replace_id: i64,
 ) -> Result<(), Box<dyn Error>> {
+    let action = format!(
+        "delete user {} and hand their content to user {}",
+        user_label(&api, id)?,
+        user_label(&api, replace_id)?
+    );
+    if is_dry_run() {
+        return print_dry_run(action);
+    }
+    confirm(action)?;
     api.user_delete(id, replace_id)?;
-    println!("{}: User deleted", "success".bold().green());
+    info!("{}: User deleted", "success".bold().green());
     Ok(())
 }
 
@@ -413,25 +526,38 @@ fn user_tfa(
     id: i64,
     enabled: bool,
 ) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        return print_dry_run(format!(
+            "{} TFA of user {}",
+            if enabled { "enable" } else { "disable" },
+            user_label(&api, id)?
+        ));
+    }
     if enabled {
         api.user_tfa_enable(id)?;
     } else {
         api.user_tfa_disable(id)?;
     }
-    println!("{}: User TFA updated", "success".bold().green());
+    info!("{}: User TFA updated", "success".bold().green());
     Ok(())
 }
 
 fn user_verify(api: wikijs::Api, id: i64) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        return print_dry_run(format!("verify user {}", user_label(&api, id)?));
+    }
     api.user_verify(id)?;
-    println!("{}: User verified", "success".bold().green());
+    info!("{}: User verified", "success".bold().green());
     Ok(())
 }
 
 fn user_search(api: wikijs::Api, query: String) -> Result<(), Box<dyn Error>> {
     let users = api.user_search(query)?;
-    let mut builder = Builder::new();
-    builder.push_record([
+    if print_serialized(&users)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record([
         "id",
         "name",
         "email",
@@ -442,7 +568,7 @@ fn user_search(api: wikijs::Api, query: String) -> Result<(), Box<dyn Error>> {
         "last_login_at",
     ]);
     for user in users {
-        builder.push_record([
+        table.push_record([
             user.id.to_string().as_str(),
             user.name.as_str(),
             user.email.as_str(),
@@ -453,56 +579,62 @@ fn user_search(api: wikijs::Api, query: String) -> Result<(), Box<dyn Error>> {
             user.last_login_at.unwrap_or("".to_string()).as_str(),
         ]);
     }
-    println!("{}", builder.build().with(Style::rounded()));
+    table.print()?;
     Ok(())
 }
 
 fn user_profile(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
     let user = api.user_profile_get()?;
-    let mut builder = Builder::new();
-    builder.push_record(["key", "value"]);
-    builder.push_record(["id", user.id.to_string().as_str()]);
-    builder.push_record(["name", user.name.as_str()]);
-    builder.push_record(["email", user.email.as_str()]);
-    builder.push_record([
+    if print_serialized(&user)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["key", "value"]);
+    table.push_record(["id", user.id.to_string().as_str()]);
+    table.push_record(["name", user.name.as_str()]);
+    table.push_record(["email", user.email.as_str()]);
+    table.push_record([
         "provider_key",
         user.provider_key.unwrap_or("".to_string()).as_str(),
     ]);
-    builder.push_record([
+    table.push_record([
         "provider_name",
         user.provider_name.unwrap_or("".to_string()).as_str(),
     ]);
-    builder.push_record(["is_system", user.is_system.to_string().as_str()]);
-    builder.push_record(["is_verified", user.is_verified.to_string().as_str()]);
-    builder.push_record(["location", user.location.as_str()]);
-    builder.push_record(["job_title", user.job_title.as_str()]);
-    builder.push_record(["timezone", user.timezone.as_str()]);
-    builder.push_record(["date_format", user.date_format.as_str()]);
-    builder.push_record(["appearance", user.appearance.as_str()]);
-    builder.push_record(["created_at", user.created_at.to_string().as_str()]);
-    builder.push_record(["updated_at", user.updated_at.to_string().as_str()]);
-    builder.push_record([
+    table.push_record(["is_system", user.is_system.to_string().as_str()]);
+    table.push_record(["is_verified", user.is_verified.to_string().as_str()]);
+    table.push_record(["location", user.location.as_str()]);
+    table.push_record(["job_title", user.job_title.as_str()]);
+    table.push_record(["timezone", user.timezone.as_str()]);
+    table.push_record(["date_format", user.date_format.as_str()]);
+    table.push_record(["appearance", user.appearance.as_str()]);
+    table.push_record(["created_at", user.created_at.to_string().as_str()]);
+    table.push_record(["updated_at", user.updated_at.to_string().as_str()]);
+    table.push_record([
         "last_login_at",
         user.last_login_at.unwrap_or("".to_string()).as_str(),
     ]);
     // groups
-    builder.push_record(["pages_total", user.pages_total.to_string().as_str()]);
-    println!("{}", builder.build().with(Style::rounded()));
+    table.push_record(["pages_total", user.pages_total.to_string().as_str()]);
+    table.print()?;
     Ok(())
 }
 
 fn user_last_logins(api: wikijs::Api) -> Result<(), Box<dyn Error>> {
     let logins = api.user_last_login_list()?;
-    let mut builder = Builder::new();
-    builder.push_record(["id", "name", "last_login_at"]);
+    if print_serialized(&logins)? {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.push_record(["id", "name", "last_login_at"]);
     for login in logins {
-        builder.push_record([
+        table.push_record([
             login.id.to_string().as_str(),
             login.name.to_string().as_str(),
             login.last_login_at.to_string().as_str(),
         ]);
     }
-    println!("{}", builder.build().with(Style::rounded()));
+    table.print()?;
     Ok(())
 }
 
@@ -521,6 +653,27 @@ fn user_update(
     date_format: Option<String>,
     appearance: Option<String>,
 ) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        let fields: Vec<&str> = [
+            ("email", email.is_some()),
+            ("name", name.is_some()),
+            ("password", new_password.is_some()),
+            ("groups", groups.is_some() || no_groups),
+            ("location", location.is_some()),
+            ("job_title", job_title.is_some()),
+            ("timezone", timezone.is_some()),
+            ("date_format", date_format.is_some()),
+            ("appearance", appearance.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(field, given)| given.then_some(field))
+        .collect();
+        return print_dry_run(format!(
+            "update {} of user {}",
+            fields.join(", "),
+            user_label(&api, id)?
+        ));
+    }
     api.user_update(
         id,
         email,
@@ -539,7 +692,7 @@ fn user_update(
         date_format,
         appearance,
     )?;
-    println!("{}: User updated", "success".bold().green());
+    info!("{}: User updated", "success".bold().green());
     Ok(())
 }
 
@@ -553,6 +706,13 @@ fn user_profile_update(
     date_format: String,
     appearance: String,
 ) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        let user = api.user_profile_get()?;
+        return print_dry_run(format!(
+            "update the profile of user {} ({} <{}>)",
+            user.id, user.name, user.email
+        ));
+    }
     api.user_profile_update(
         name,
         location,
@@ -561,7 +721,7 @@ fn user_profile_update(
         date_format,
         appearance,
     )?;
-    println!("{}: User profile updated", "success".bold().green());
+    info!("{}: User profile updated", "success".bold().green());
     Ok(())
 }
 
@@ -570,8 +730,15 @@ fn user_password_change(
     current: String,
     new: String,
 ) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        let user = api.user_profile_get()?;
+        return print_dry_run(format!(
+            "change the password of user {} ({} <{}>)",
+            user.id, user.name, user.email
+        ));
+    }
     api.user_password_change(current, new)?;
-    println!("{}: User password changed", "success".bold().green());
+    info!("{}: User password changed", "success".bold().green());
     Ok(())
 }
 
@@ -579,7 +746,123 @@ fn user_password_reset(
     api: wikijs::Api,
     id: i64,
 ) -> Result<(), Box<dyn Error>> {
+    if is_dry_run() {
+        return print_dry_run(format!(
+            "reset the password of user {}",
+            user_label(&api, id)?
+        ));
+    }
     api.user_password_reset(id)?;
-    println!("{}: User password reset", "success".bold().green());
+    info!("{}: User password reset", "success".bold().green());
+    Ok(())
+}
+
+fn user_import(
+    api: wikijs::Api,
+    file: &Path,
+    defaults: &UserImportDefaults,
+) -> Result<(), Box<dyn Error>> {
+    let outcomes = api.user_import_csv(File::open(file)?, defaults)?;
+    let result =
+        |outcome: &wikijs::user::UserImportOutcome| match &outcome.result {
+            Ok(()) if defaults.dry_run => "valid".green(),
+            Ok(()) => "created".green(),
+            Err(e) => e.to_string().red(),
+        };
+    let records: Vec<_> = outcomes
+        .iter()
+        .map(|outcome| {
+            serde_json::json!({
+                "line": outcome.line,
+                "email": outcome.email,
+                "result": result(outcome).clear().to_string(),
+            })
+        })
+        .collect();
+    let serialized = print_serialized(&records)?;
+    if !serialized {
+        let mut table = Table::new();
+        table.push_record(["line", "email", "result"]);
+        for outcome in &outcomes {
+            table.push_record([
+                outcome.line.to_string(),
+                outcome.email.clone(),
+                result(outcome).to_string(),
+            ]);
+        }
+        table.print()?;
+    }
+    let failed = outcomes.iter().filter(|o| o.result.is_err()).count();
+    if failed > 0 {
+        return Err(format!(
+            "{} of {} users could not be imported",
+            failed,
+            outcomes.len()
+        )
+        .into());
+    }
+    if serialized {
+        return Ok(());
+    }
+    match defaults.dry_run {
+        true => info!(
+            "{}: {} users valid",
+            "success".bold().green(),
+            outcomes.len()
+        ),
+        false => info!(
+            "{}: imported {} users",
+            "success".bold().green(),
+            outcomes.len()
+        ),
+    }
+    Ok(())
+}
+
+fn user_deactivate_inactive(
+    api: wikijs::Api,
+    since: chrono::Duration,
+    dry_run: bool,
+) -> Result<(), Box<dyn Error>> {
+    let users = api.user_inactive_list(chrono::Utc::now() - since)?;
+    // the serialized users are the whole output, so no summary is printed
+    let serialized = print_serialized(&users)?;
+    if !serialized {
+        let mut table = Table::new();
+        table.push_record([
+            "id",
+            "name",
+            "email",
+            "created_at",
+            "last_login_at",
+        ]);
+        for user in &users {
+            table.push_record([
+                user.id.to_string().as_str(),
+                user.name.as_str(),
+                user.email.as_str(),
+                user.created_at.as_str(),
+                user.last_login_at.as_deref().unwrap_or("never"),
+            ]);
+        }
+        table.print()?;
+    }
+    if dry_run {
+        if !serialized {
+            println!("{} inactive users", users.len());
+        }
+        return Ok(());
+    }
+    for user in &users {
+        api.user_deactivate(user.id)?;
+    }
+    if serialized {
+        return Ok(());
+    }
+    info!(
+        "{}: deactivated {} users",
+        "success".bold().green(),
+        users.len()
+    );
     Ok(())
 }
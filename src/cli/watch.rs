@@ -0,0 +1,122 @@
+use crate::common::Execute;
+use crate::page::PageFilterArgs;
+use clap::Args;
+use colored::Colorize;
+use std::error::Error;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use wikijs::watch::PageChangeEvent;
+
+#[derive(Args, Debug)]
+#[group(id = "target", required = true, multiple = true, args = ["exec", "webhook"])]
+pub(crate) struct WatchCommand {
+    #[clap(flatten)]
+    filter: PageFilterArgs,
+
+    #[clap(
+        short,
+        long,
+        help = "Seconds between two polls",
+        default_value = "30"
+    )]
+    interval: u64,
+
+    #[clap(
+        short,
+        long,
+        help = "Seconds a page must stay unchanged before it is reported",
+        default_value = "0"
+    )]
+    debounce: u64,
+
+    #[clap(short, long, help = "Also watch the comments of the pages")]
+    comments: bool,
+
+    #[clap(
+        short,
+        long,
+        help = "Shell command to run on every change, gets the JSON payload \
+                on stdin"
+    )]
+    exec: Option<String>,
+
+    #[clap(short, long, help = "URL to POST the JSON payload to")]
+    webhook: Option<String>,
+}
+
+impl Execute for WatchCommand {
+    fn execute(&self, api: wikijs::Api) -> Result<(), Box<dyn Error>> {
+        let watcher = api
+            .watch_changes(
+                Duration::from_secs(self.interval),
+                (&self.filter).into(),
+                None,
+            )?
+            .debounce(Duration::from_secs(self.debounce))
+            .comments(self.comments);
+        let client = reqwest::blocking::Client::new();
+        for event in watcher {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    eprintln!("{}: {}", "warning".bold().yellow(), e);
+                    continue;
+                }
+            };
+            let page = event.page();
+            println!("{} {}/{}", event.kind(), page.locale, page.path);
+            let payload = serde_json::to_string(&payload(&event))?;
+            if let Some(command) = &self.exec {
+                if let Err(e) = run_command(command, &event, &payload) {
+                    eprintln!("{}: {}", "warning".bold().yellow(), e);
+                }
+            }
+            if let Some(url) = &self.webhook {
+                let response = client
+                    .post(url)
+                    .header("Content-Type", "application/json")
+                    .body(payload.clone())
+                    .send()
+                    .and_then(|response| response.error_for_status());
+                if let Err(e) = response {
+                    eprintln!("{}: {}", "warning".bold().yellow(), e);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn payload(event: &PageChangeEvent) -> serde_json::Value {
+    serde_json::json!({
+        "event": event.kind(),
+        "page": event.page(),
+        "comment": event.comment(),
+    })
+}
+
+fn run_command(
+    command: &str,
+    event: &PageChangeEvent,
+    payload: &str,
+) -> Result<(), Box<dyn Error>> {
+    let page = event.page();
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("WIKI_JS_EVENT", event.kind())
+        .env("WIKI_JS_PAGE_ID", page.id.to_string())
+        .env("WIKI_JS_PAGE_LOCALE", &page.locale)
+        .env("WIKI_JS_PAGE_PATH", &page.path)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(payload.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(format!("command exited with {}", status).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,339 @@
+use crate::inode::Entry;
+use crate::stats::Stats;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use wikijs::asset::{AssetError, AssetFolderTree, AssetItem, AssetKind};
+use wikijs::Api;
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+
+// an asset open for reading or writing, held in memory until its last
+// handle is released
+struct Buffer {
+    data: Vec<u8>,
+    dirty: bool,
+    opens: usize,
+}
+
+/// Assets of the wiki with the folder tree and folder listings kept for a
+/// time to live, and the content of open assets, uploaded when they were
+/// changed and a handle is flushed.
+///
+/// Assets are known by folder and filename rather than id, so files that
+/// are created but not uploaded yet have an inode as well.
+pub(crate) struct AssetStore {
+    ttl: Duration,
+    folders: Option<(Instant, AssetFolderTree)>,
+    listings: HashMap<i64, (Instant, Vec<AssetItem>)>,
+    // the open assets by folder and filename
+    buffers: HashMap<(i64, String), Buffer>,
+    stats: Arc<Stats>,
+}
+
+impl AssetStore {
+    pub(crate) fn new(ttl: Duration, stats: Arc<Stats>) -> Self {
+        Self {
+            ttl,
+            folders: None,
+            listings: HashMap::new(),
+            buffers: HashMap::new(),
+            stats,
+        }
+    }
+
+    fn is_fresh(&self, fetched_at: Instant) -> bool {
+        fetched_at.elapsed() < self.ttl
+    }
+
+    /// The asset folder hierarchy.
+    pub(crate) fn folder_tree(
+        &mut self,
+        api: &Api,
+    ) -> Result<&AssetFolderTree, AssetError> {
+        match &self.folders {
+            Some((fetched_at, _)) if self.is_fresh(*fetched_at) => {
+                self.stats.cache_hit();
+            }
+            _ => {
+                self.stats.cache_miss();
+                self.folders = Some((Instant::now(), api.asset_folder_tree()?));
+            }
+        }
+        Ok(&self.folders.as_ref().unwrap().1)
+    }
+
+    fn list(
+        &mut self,
+        api: &Api,
+        folder: i64,
+    ) -> Result<Vec<AssetItem>, AssetError> {
+        if let Some((fetched_at, assets)) = self.listings.get(&folder) {
+            if self.is_fresh(*fetched_at) {
+                self.stats.cache_hit();
+                return Ok(assets.clone());
+            }
+        }
+        self.stats.cache_miss();
+        let assets = api.asset_list(folder, AssetKind::ALL)?;
+        self.listings
+            .insert(folder, (Instant::now(), assets.clone()));
+        Ok(assets)
+    }
+
+    /// The asset with the filename in the folder in the wiki, if it was
+    /// uploaded yet.
+    pub(crate) fn item(
+        &mut self,
+        api: &Api,
+        folder: i64,
+        name: &str,
+    ) -> Result<Option<AssetItem>, AssetError> {
+        Ok(self
+            .list(api, folder)?
+            .into_iter()
+            .find(|asset| asset.filename == name))
+    }
+
+    /// Size of the open asset, which may differ from the uploaded one.
+    pub(crate) fn buffered_size(&self, folder: i64, name: &str) -> Option<u64> {
+        self.buffers
+            .get(&(folder, name.to_string()))
+            .map(|buffer| buffer.data.len() as u64)
+    }
+
+    /// Subfolders and assets of the folder, including created assets that
+    /// are not uploaded yet.
+    pub(crate) fn entries(
+        &mut self,
+        api: &Api,
+        folder: i64,
+    ) -> Result<Vec<(Entry, fuser::FileType, String)>, AssetError> {
+        let mut entries: Vec<(Entry, fuser::FileType, String)> = self
+            .folder_tree(api)?
+            .children(folder)
+            .into_iter()
+            .map(|child| {
+                (
+                    Entry::AssetFolder(child.id),
+                    fuser::FileType::Directory,
+                    child.slug.clone(),
+                )
+            })
+            .collect();
+        let mut names: Vec<String> = self
+            .list(api, folder)?
+            .into_iter()
+            .map(|asset| asset.filename)
+            .collect();
+        for (f, name) in self.buffers.keys() {
+            if *f == folder && !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        for name in names {
+            let entry = Entry::Asset(folder, name.clone());
+            entries.push((entry, fuser::FileType::RegularFile, name));
+        }
+        Ok(entries)
+    }
+
+    /// The subfolder or asset with the name in the folder.
+    pub(crate) fn lookup(
+        &mut self,
+        api: &Api,
+        folder: i64,
+        name: &str,
+    ) -> Result<Option<Entry>, AssetError> {
+        let subfolder = self
+            .folder_tree(api)?
+            .children(folder)
+            .into_iter()
+            .find(|child| child.slug == name)
+            .map(|child| Entry::AssetFolder(child.id));
+        if subfolder.is_some() {
+            return Ok(subfolder);
+        }
+        if self.buffers.contains_key(&(folder, name.to_string()))
+            || self.item(api, folder, name)?.is_some()
+        {
+            return Ok(Some(Entry::Asset(folder, name.to_string())));
+        }
+        Ok(None)
+    }
+
+    /// Create an empty asset that is open once and uploaded on flush.
+    pub(crate) fn create(&mut self, folder: i64, name: &str) {
+        self.buffers.insert(
+            (folder, name.to_string()),
+            Buffer {
+                data: Vec::new(),
+                dirty: true,
+                opens: 1,
+            },
+        );
+    }
+
+    fn download(
+        &mut self,
+        api: &Api,
+        folder: i64,
+        name: &str,
+    ) -> Result<Vec<u8>, AssetError> {
+        let path = match self.folder_tree(api)?.path(folder) {
+            Some(path) if !path.is_empty() => format!("{}/{}", path, name),
+            Some(_) => name.to_string(),
+            None => return Err(AssetError::UnknownError),
+        };
+        debug!("asset store: downloading {}", path);
+        api.asset_download(path)
+    }
+
+    /// Open an asset, fetching its content unless it is truncated.
+    pub(crate) fn open(
+        &mut self,
+        api: &Api,
+        folder: i64,
+        name: &str,
+        truncate: bool,
+    ) -> Result<(), AssetError> {
+        let key = (folder, name.to_string());
+        if let Some(buffer) = self.buffers.get_mut(&key) {
+            buffer.opens += 1;
+            if truncate {
+                buffer.data.clear();
+                buffer.dirty = true;
+            }
+            return Ok(());
+        }
+        let data = match truncate {
+            true => Vec::new(),
+            false => self.download(api, folder, name)?,
+        };
+        self.buffers.insert(
+            key,
+            Buffer {
+                data,
+                dirty: truncate,
+                opens: 1,
+            },
+        );
+        Ok(())
+    }
+
+    /// Read from an open asset.
+    pub(crate) fn read(
+        &self,
+        folder: i64,
+        name: &str,
+        offset: usize,
+        size: usize,
+    ) -> Option<&[u8]> {
+        let data = &self.buffers.get(&(folder, name.to_string()))?.data;
+        let start = offset.min(data.len());
+        let end = (start + size).min(data.len());
+        Some(&data[start..end])
+    }
+
+    /// Write to an open asset, filling a gap after its end with zeros.
+    pub(crate) fn write(
+        &mut self,
+        folder: i64,
+        name: &str,
+        offset: usize,
+        data: &[u8],
+    ) -> Option<usize> {
+        let buffer = self.buffers.get_mut(&(folder, name.to_string()))?;
+        let end = offset + data.len();
+        if buffer.data.len() < end {
+            buffer.data.resize(end, 0);
+        }
+        buffer.data[offset..end].copy_from_slice(data);
+        buffer.dirty = true;
+        Some(data.len())
+    }
+
+    /// Change the size of an asset, uploading it right away if it is not
+    /// open.
+    pub(crate) fn truncate(
+        &mut self,
+        api: &Api,
+        folder: i64,
+        name: &str,
+        size: u64,
+    ) -> Result<(), AssetError> {
+        let key = (folder, name.to_string());
+        if let Some(buffer) = self.buffers.get_mut(&key) {
+            buffer.data.resize(size as usize, 0);
+            buffer.dirty = true;
+            return Ok(());
+        }
+        self.open(api, folder, name, size == 0)?;
+        if let Some(buffer) = self.buffers.get_mut(&key) {
+            buffer.data.resize(size as usize, 0);
+            buffer.dirty = true;
+        }
+        self.release(api, folder, name)
+    }
+
+    /// Upload an open asset if it was changed.
+    pub(crate) fn flush(
+        &mut self,
+        api: &Api,
+        folder: i64,
+        name: &str,
+    ) -> Result<(), AssetError> {
+        let Some(buffer) = self.buffers.get_mut(&(folder, name.to_string()))
+        else {
+            return Ok(());
+        };
+        if !buffer.dirty {
+            return Ok(());
+        }
+        debug!("asset store: uploading {} to folder {}", name, folder);
+        api.asset_upload(folder, name.to_string(), buffer.data.clone())?;
+        buffer.dirty = false;
+        self.listings.remove(&folder);
+        Ok(())
+    }
+
+    /// Close a handle of an asset, uploading it if it was changed and
+    /// dropping its content with the last handle.
+    pub(crate) fn release(
+        &mut self,
+        api: &Api,
+        folder: i64,
+        name: &str,
+    ) -> Result<(), AssetError> {
+        let result = self.flush(api, folder, name);
+        let key = (folder, name.to_string());
+        if let Some(buffer) = self.buffers.get_mut(&key) {
+            buffer.opens = buffer.opens.saturating_sub(1);
+            if buffer.opens == 0 && !buffer.dirty {
+                self.buffers.remove(&key);
+            }
+        }
+        result
+    }
+
+    /// Delete the asset with the filename in the folder, and whether there
+    /// was one.
+    pub(crate) fn delete(
+        &mut self,
+        api: &Api,
+        folder: i64,
+        name: &str,
+    ) -> Result<bool, AssetError> {
+        let buffered =
+            self.buffers.remove(&(folder, name.to_string())).is_some();
+        match self.item(api, folder, name)? {
+            Some(asset) => {
+                api.asset_delete(asset.id)?;
+                self.listings.remove(&folder);
+                Ok(true)
+            }
+            None => Ok(buffered),
+        }
+    }
+}
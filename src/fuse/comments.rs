@@ -0,0 +1,23 @@
+use wikijs::comment::{Comment, CommentTree};
+
+/// The comments of the page at `path` as a markdown transcript, replies
+/// quoted below the comment they answer, one level deeper each.
+pub(crate) fn render(path: &str, comments: Vec<Comment>) -> String {
+    let mut out = format!("# Comments on /{}\n", path);
+    for thread in CommentTree::build(comments) {
+        for (depth, comment) in thread.flatten() {
+            let quote = "> ".repeat(depth);
+            out += "\n";
+            out += &format!(
+                "{}**{}** on {} (#{}):\n",
+                quote, comment.author_name, comment.created_at, comment.id
+            );
+            out += &format!("{}\n", quote.trim_end());
+            for line in comment.text().lines() {
+                out += format!("{}{}", quote, line).trim_end();
+                out += "\n";
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use wikijs::page::PageMinimal;
+
+/// An open file or directory.
+pub(crate) enum Handle {
+    /// A page as of the open or the last flush, and its content as read
+    /// and written through the handle. Writes only change the content here,
+    /// it is written to the wiki on flush and release, so the chunks of a
+    /// write all land at the offsets they were meant for.
+    Page {
+        page: PageMinimal,
+        content: Vec<u8>,
+        // writes go to the end of the content, whatever their offset
+        append: bool,
+        // changed since the open or the last flush, which a truncation on
+        // open is as well
+        dirty: bool,
+    },
+    /// The comments of a page as of the open, and the text appended since
+    /// if it was opened for appending, which is posted as a new comment.
+    Comments {
+        page_id: i64,
+        transcript: String,
+        comment: Option<String>,
+    },
+    /// The entries of a directory as listed on opendir, so they stay
+    /// consistent across the readdir calls of a listing.
+    Directory(Vec<(u64, fuser::FileType, String)>),
+}
+
+/// The open handles by their number, starting at 1 as 0 is passed for
+/// files opened without one.
+pub(crate) struct HandleTable {
+    next: u64,
+    handles: HashMap<u64, Handle>,
+}
+
+impl HandleTable {
+    pub(crate) fn new() -> Self {
+        Self {
+            next: 1,
+            handles: HashMap::new(),
+        }
+    }
+
+    /// Keep the handle and return its number.
+    pub(crate) fn insert(&mut self, handle: Handle) -> u64 {
+        let fh = self.next;
+        self.next += 1;
+        self.handles.insert(fh, handle);
+        fh
+    }
+
+    pub(crate) fn get(&self, fh: u64) -> Option<&Handle> {
+        self.handles.get(&fh)
+    }
+
+    pub(crate) fn get_mut(&mut self, fh: u64) -> Option<&mut Handle> {
+        self.handles.get_mut(&fh)
+    }
+
+    pub(crate) fn remove(&mut self, fh: u64) -> Option<Handle> {
+        self.handles.remove(&fh)
+    }
+}
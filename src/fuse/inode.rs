@@ -0,0 +1,223 @@
+use fuser::FUSE_ROOT_ID;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+
+/// What an inode of the mount stands for.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Entry {
+    /// A directory of pages by its path in the mount, empty for the root.
+    Directory(String),
+    /// A page by its id.
+    Page(i64),
+    /// The virtual `/.wikijs` control directory.
+    ControlDirectory,
+    /// The virtual `/.wikijs/stats` file.
+    Stats,
+    /// The read-only `.comments` directory in the directory with the inode.
+    Comments(u64),
+    /// The `.comments/<page>.md` file of the page with the id.
+    PageComments(i64),
+    /// The read-only `.versions` directory in the directory with the inode.
+    Versions(u64),
+    /// The `.versions/<page>.md` directory of the page with the id.
+    PageVersions(i64),
+    /// A version of a page by page id and version id.
+    Version(i64, i64),
+    /// An asset folder by its id, 0 being `/_assets` itself.
+    AssetFolder(i64),
+    /// An asset by the id of its folder and its filename, which it is
+    /// known by before it is uploaded.
+    Asset(i64, String),
+}
+
+impl Entry {
+    /// Whether no pages or directories can be created in the entry, asset
+    /// folders take assets only.
+    pub(crate) fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            Entry::ControlDirectory
+                | Entry::Stats
+                | Entry::Versions(_)
+                | Entry::PageVersions(_)
+                | Entry::Version(..)
+                | Entry::Comments(_)
+                | Entry::AssetFolder(_)
+        )
+    }
+
+    // whether the inode is kept in the file of the table, the others are
+    // only looked up while the mount lives
+    fn is_persistent(&self) -> bool {
+        matches!(self, Entry::Directory(_) | Entry::Page(_))
+    }
+}
+
+/// Inodes of all entries of the mount, allocated on first use and never
+/// reused while the mount lives.
+///
+/// Directories are known by their path in the mount, so they keep their
+/// inode when the wiki rebuilds its page tree and when they are moved, and
+/// pages by their id, which never changes. With the table kept in a file
+/// both also keep their inodes across mounts.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct InodeTable {
+    next: u64,
+    directories: BTreeMap<String, u64>,
+    #[serde(default)]
+    pages: BTreeMap<i64, u64>,
+    #[serde(skip)]
+    inodes: HashMap<Entry, u64>,
+    #[serde(skip)]
+    entries: HashMap<u64, Entry>,
+    #[serde(skip)]
+    file: Option<PathBuf>,
+    #[serde(skip)]
+    changed: bool,
+}
+
+impl InodeTable {
+    /// A table with only the root, whose path is empty.
+    pub(crate) fn new() -> Self {
+        let root = Entry::Directory(String::new());
+        Self {
+            next: FUSE_ROOT_ID + 1,
+            directories: BTreeMap::from([(String::new(), FUSE_ROOT_ID)]),
+            pages: BTreeMap::new(),
+            inodes: HashMap::from([(root.clone(), FUSE_ROOT_ID)]),
+            entries: HashMap::from([(FUSE_ROOT_ID, root)]),
+            file: None,
+            changed: false,
+        }
+    }
+
+    /// The table kept in the file, a new one if it does not exist yet.
+    pub(crate) fn load(file: PathBuf) -> io::Result<Self> {
+        let mut table = match fs::read(&file) {
+            Ok(json) => serde_json::from_slice::<Self>(&json)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::new(),
+            Err(e) => return Err(e),
+        };
+        let directories = table
+            .directories
+            .iter()
+            .map(|(path, ino)| (Entry::Directory(path.clone()), *ino));
+        let pages =
+            table.pages.iter().map(|(id, ino)| (Entry::Page(*id), *ino));
+        table.inodes = directories.chain(pages).collect();
+        table.entries = table
+            .inodes
+            .iter()
+            .map(|(entry, ino)| (*ino, entry.clone()))
+            .collect();
+        debug!(
+            "inode table: loaded {} directories and {} pages from {}",
+            table.directories.len(),
+            table.pages.len(),
+            file.display()
+        );
+        table.file = Some(file);
+        Ok(table)
+    }
+
+    /// Write the table to its file if it changed.
+    pub(crate) fn save(&mut self) -> io::Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        if !self.changed {
+            return Ok(());
+        }
+        fs::write(file, serde_json::to_vec(self)?)?;
+        debug!("inode table: saved to {}", file.display());
+        self.changed = false;
+        Ok(())
+    }
+
+    /// Inode of the entry, allocating one if it has none.
+    pub(crate) fn ino(&mut self, entry: Entry) -> u64 {
+        if let Some(ino) = self.inodes.get(&entry) {
+            return *ino;
+        }
+        let ino = self.next;
+        self.next += 1;
+        match &entry {
+            Entry::Directory(path) => {
+                self.directories.insert(path.clone(), ino);
+            }
+            Entry::Page(id) => {
+                self.pages.insert(*id, ino);
+            }
+            _ => {}
+        }
+        self.changed |= entry.is_persistent();
+        self.inodes.insert(entry.clone(), ino);
+        self.entries.insert(ino, entry);
+        ino
+    }
+
+    /// Inode of the directory at the path, allocating one if it has none.
+    pub(crate) fn directory(&mut self, path: &str) -> u64 {
+        self.ino(Entry::Directory(path.to_string()))
+    }
+
+    /// Inode of the page, allocating one if it has none.
+    pub(crate) fn page(&mut self, id: i64) -> u64 {
+        self.ino(Entry::Page(id))
+    }
+
+    /// What the inode stands for, if it was allocated.
+    pub(crate) fn entry(&self, ino: u64) -> Option<Entry> {
+        self.entries.get(&ino).cloned()
+    }
+
+    /// Path of the directory inode.
+    pub(crate) fn path(&self, ino: u64) -> Option<&str> {
+        match self.entries.get(&ino)? {
+            Entry::Directory(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Move the directory and all below it to another path, keeping their
+    /// inodes.
+    pub(crate) fn rename(&mut self, from: &str, to: &str) {
+        let prefix = format!("{}/", from);
+        let moved: Vec<(String, u64)> = self
+            .directories
+            .iter()
+            .filter_map(|(path, ino)| {
+                let path = match path.strip_prefix(&prefix) {
+                    Some(rest) => format!("{}/{}", to, rest),
+                    None if path == from => to.to_string(),
+                    None => return None,
+                };
+                Some((path, *ino))
+            })
+            .collect();
+        self.directories.retain(|path, ino| {
+            let keep = path != from && !path.starts_with(&prefix);
+            if !keep {
+                self.inodes.remove(&Entry::Directory(path.clone()));
+                self.entries.remove(ino);
+            }
+            keep
+        });
+        for (path, ino) in moved {
+            let entry = Entry::Directory(path.clone());
+            // a directory that was at the new path before is gone
+            if let Some(replaced) = self.directories.insert(path, ino) {
+                self.entries.remove(&replaced);
+            }
+            self.inodes.insert(entry.clone(), ino);
+            self.entries.insert(ino, entry);
+        }
+        self.changed = true;
+    }
+}
@@ -1,30 +1,79 @@
-use fuser::MountOption::FSName;
+use fuser::consts::{FOPEN_DIRECT_IO, FUSE_ATOMIC_O_TRUNC};
+use fuser::MountOption::{AllowOther, AutoUnmount, FSName};
 use fuser::{
-    mount2, FileAttr, Filesystem, ReplyAttr, ReplyData, ReplyDirectory,
-    ReplyEntry, ReplyWrite, Request, TimeOrNow,
+    FileAttr, Filesystem, KernelConfig, ReplyAttr, ReplyCreate, ReplyData,
+    ReplyDirectory, ReplyEmpty, ReplyEntry, ReplyOpen, ReplyStatfs, ReplyWrite,
+    Request, Session, SessionUnmounter, TimeOrNow, FUSE_ROOT_ID,
+};
+use libc::{
+    c_int, EACCES, EBADF, EEXIST, EINVAL, EIO, EISDIR, ENOENT, ENOTEMPTY,
+    EPERM, O_APPEND, O_TRUNC,
+};
+use wikijs::asset::AssetItem;
+use wikijs::page::{
+    PageAttributes, PageEditor, PageError, PageMinimal, PageTreeItem,
+    PageTreeMode, PageVersion,
 };
-use libc::{EINVAL, EIO, EISDIR, ENOENT, O_TRUNC};
-use wikijs::page::{PageMinimal, PageTreeItem, PageTreeMode};
 use wikijs::{Api, Credentials};
 
 use chrono::DateTime;
 use clap::Parser;
+use std::collections::BTreeMap;
 use std::ffi::OsStr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::exit;
-use std::time::SystemTime;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[allow(unused_imports)]
 use colored::Colorize;
 #[allow(unused_imports)]
 use log::{debug, error, info, trace, warn};
 
+mod asset;
+mod comments;
+// shared with the CLI, which uses more of it
+#[allow(dead_code)]
+#[path = "../cli/credentials.rs"]
+mod credentials;
+mod handle;
+mod inode;
 mod page;
+mod stats;
+mod tree;
+mod usage;
+
+use credentials::{jwt_expiry, CredentialArgs};
+use handle::{Handle, HandleTable};
+use inode::Entry;
+use stats::{OpTimer, Stats};
+
+/// Block size reported by statfs.
+const BLOCK_SIZE: u64 = 4096;
+/// Free blocks reported by statfs, the wiki has no quota to tell the free
+/// space from, so tools checking it before writing are given 1 TiB.
+const FREE_BLOCKS: u64 = (1 << 40) / BLOCK_SIZE;
+/// Free inodes reported by statfs.
+const FREE_FILES: u64 = 1 << 20;
 
 #[allow(clippy::large_enum_variant)]
 enum Inode {
-    Page(PageMinimal),
-    Directory(Vec<PageTreeItem>),
+    // the size is only known while the content is cached, 0 otherwise
+    Page(u64, PageAttributes, u64),
+    Directory(u64, Vec<PageTreeItem>),
+    ControlDirectory(u64),
+    VirtualDirectory(u64),
+    Versions(u64),
+    PageVersions(u64),
+    Version(u64, PageVersion),
+    Comments(u64),
+    // the rendered comments, and whether they can be appended to
+    PageComments(u64, String, bool),
+    AssetFolder(u64),
+    // assets that are not uploaded yet have no item
+    Asset(u64, Option<AssetItem>, u64),
+    Stats(u64, String),
 }
 
 fn parse_systemtime(str: String) -> SystemTime {
@@ -41,12 +90,12 @@ fn parse_systemtime(str: String) -> SystemTime {
 impl Into<FileAttr> for Inode {
     fn into(self) -> FileAttr {
         match self {
-            Inode::Page(page) => {
+            Inode::Page(ino, page, size) => {
                 let update_time = parse_systemtime(page.updated_at);
                 let create_time = parse_systemtime(page.created_at);
                 FileAttr {
-                    ino: page.id as u64 | 0x80000000_00000000,
-                    size: page.content.len() as u64,
+                    ino,
+                    size,
                     blocks: 1,
                     atime: update_time,
                     mtime: update_time,
@@ -62,16 +111,41 @@ impl Into<FileAttr> for Inode {
                     flags: 0,
                 }
             }
-            Inode::Directory(page_tree) => {
-                let ino = if !page_tree.is_empty() {
-                    if let Some(id) = page_tree[0].parent {
-                        id as u64 + 1
-                    } else {
-                        0
-                    }
-                } else {
-                    0
-                };
+            Inode::Directory(ino, _) => FileAttr {
+                ino,
+                size: 0,
+                blocks: 0,
+                atime: SystemTime::now(),
+                mtime: SystemTime::now(),
+                ctime: SystemTime::now(),
+                crtime: SystemTime::now(),
+                kind: fuser::FileType::Directory,
+                perm: 0o755,
+                nlink: 1,
+                uid: 0,
+                gid: 0,
+                rdev: 0,
+                blksize: 0,
+                flags: 0,
+            },
+            Inode::ControlDirectory(ino) => FileAttr {
+                ino,
+                size: 0,
+                blocks: 0,
+                atime: SystemTime::now(),
+                mtime: SystemTime::now(),
+                ctime: SystemTime::now(),
+                crtime: SystemTime::now(),
+                kind: fuser::FileType::Directory,
+                perm: 0o555,
+                nlink: 1,
+                uid: 0,
+                gid: 0,
+                rdev: 0,
+                blksize: 0,
+                flags: 0,
+            },
+            Inode::VirtualDirectory(ino) | Inode::AssetFolder(ino) => {
                 FileAttr {
                     ino,
                     size: 0,
@@ -90,203 +164,1238 @@ impl Into<FileAttr> for Inode {
                     flags: 0,
                 }
             }
+            Inode::Versions(ino)
+            | Inode::PageVersions(ino)
+            | Inode::Comments(ino) => FileAttr {
+                ino,
+                size: 0,
+                blocks: 0,
+                atime: SystemTime::now(),
+                mtime: SystemTime::now(),
+                ctime: SystemTime::now(),
+                crtime: SystemTime::now(),
+                kind: fuser::FileType::Directory,
+                perm: 0o555,
+                nlink: 1,
+                uid: 0,
+                gid: 0,
+                rdev: 0,
+                blksize: 0,
+                flags: 0,
+            },
+            Inode::Version(ino, version) => {
+                let version_time = parse_systemtime(version.version_date);
+                FileAttr {
+                    ino,
+                    size: version.content.len() as u64,
+                    blocks: 1,
+                    atime: version_time,
+                    mtime: version_time,
+                    ctime: version_time,
+                    crtime: version_time,
+                    kind: fuser::FileType::RegularFile,
+                    perm: 0o444,
+                    nlink: 1,
+                    uid: 0,
+                    gid: 0,
+                    rdev: 0,
+                    blksize: 0,
+                    flags: 0,
+                }
+            }
+            Inode::Asset(ino, asset, size) => {
+                let (update_time, create_time) = match asset {
+                    Some(asset) => (
+                        parse_systemtime(asset.updated_at),
+                        parse_systemtime(asset.created_at),
+                    ),
+                    None => (SystemTime::now(), SystemTime::now()),
+                };
+                FileAttr {
+                    ino,
+                    size,
+                    blocks: 1,
+                    atime: update_time,
+                    mtime: update_time,
+                    ctime: update_time,
+                    crtime: create_time,
+                    kind: fuser::FileType::RegularFile,
+                    perm: 0o644,
+                    nlink: 1,
+                    uid: 0,
+                    gid: 0,
+                    rdev: 0,
+                    blksize: 0,
+                    flags: 0,
+                }
+            }
+            Inode::PageComments(ino, transcript, appendable) => FileAttr {
+                ino,
+                size: transcript.len() as u64,
+                blocks: 1,
+                atime: SystemTime::now(),
+                mtime: SystemTime::now(),
+                ctime: SystemTime::now(),
+                crtime: SystemTime::now(),
+                kind: fuser::FileType::RegularFile,
+                perm: if appendable { 0o644 } else { 0o444 },
+                nlink: 1,
+                uid: 0,
+                gid: 0,
+                rdev: 0,
+                blksize: 0,
+                flags: 0,
+            },
+            Inode::Stats(ino, content) => FileAttr {
+                ino,
+                size: content.len() as u64,
+                blocks: 1,
+                atime: SystemTime::now(),
+                mtime: SystemTime::now(),
+                ctime: SystemTime::now(),
+                crtime: SystemTime::now(),
+                kind: fuser::FileType::RegularFile,
+                perm: 0o444,
+                nlink: 1,
+                uid: 0,
+                gid: 0,
+                rdev: 0,
+                blksize: 0,
+                flags: 0,
+            },
         }
     }
 }
 
-enum InodeType {
-    Page(i64),
-    Directory(i64),
+/// Title of a page created as a file, like `Release Notes` for
+/// `release-notes`.
+fn title_from_filename(stem: &str) -> String {
+    stem.split(['-', '_', ' '])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
 }
 
-impl From<u64> for InodeType {
-    fn from(ino: u64) -> Self {
-        if ino & 0x80000000_00000000 == 0x80000000_00000000 {
-            InodeType::Page((ino & 0x7FFF_FFFF_FFFF_FFFF) as i64)
-        } else {
-            InodeType::Directory((ino - 1) as i64)
-        }
-    }
+/// Seconds before the JWT of a login expires to log in again.
+const RENEW_BEFORE: u64 = 60;
+
+// credentials of a username and password login, kept to log in again
+// before its JWT expires
+struct Login {
+    url: String,
+    credentials: CredentialArgs,
+    // seconds since the epoch to log in again at
+    renew_at: u64,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn renew_at(jwt: &str) -> u64 {
+    jwt_expiry(jwt).map_or(u64::MAX, |exp| exp.saturating_sub(RENEW_BEFORE))
+}
+
+// write the data over the content at the offset, extending it if needed
+fn splice(content: &mut Vec<u8>, offset: usize, data: &[u8]) {
+    let end = (offset + data.len()).min(content.len());
+    content.splice(offset..end, data.iter().copied());
 }
 
 struct Fs {
     api: Api,
-    locale: String,
+    login: Option<Login>,
+    // the mounted locales, with a page tree for each
+    locales: Vec<String>,
+    trees: Vec<tree::TreeCache>,
+    all_locales: bool,
+    // inodes of all entries of the mount
+    inodes: inode::InodeTable,
+    allow_delete: bool,
+    // whether appending to a comments file posts a comment
+    post_comments: bool,
     page_cache: page::PageCache,
+    assets: asset::AssetStore,
+    usage: usage::UsageCache,
+    handles: HandleTable,
+    stats: Arc<Stats>,
+    // paths of the directories made with mkdir by their inode
+    virtual_dirs: BTreeMap<u64, String>,
 }
 
 impl Fs {
-    pub fn new(api: Api, locale: String) -> Self {
+    /// With `all_locales` each locale is a directory in the root, otherwise
+    /// the single locale is mounted at the root.
+    pub fn new(
+        api: Api,
+        locales: Vec<String>,
+        all_locales: bool,
+        allow_delete: bool,
+        tree_ttl: Duration,
+    ) -> Self {
+        let stats = Arc::new(Stats::new());
+        let trees = locales
+            .iter()
+            .map(|_| tree::TreeCache::new(tree_ttl, Arc::clone(&stats)))
+            .collect();
         Self {
             api,
-            locale,
-            page_cache: page::PageCache::new(),
+            login: None,
+            locales,
+            trees,
+            all_locales,
+            inodes: inode::InodeTable::new(),
+            allow_delete,
+            post_comments: false,
+            page_cache: page::PageCache::new(Arc::clone(&stats)),
+            assets: asset::AssetStore::new(tree_ttl, Arc::clone(&stats)),
+            usage: usage::UsageCache::new(tree_ttl, Arc::clone(&stats)),
+            handles: HandleTable::new(),
+            stats,
+            virtual_dirs: BTreeMap::new(),
+        }
+    }
+
+    /// Show the title, description, tags and publishing state of pages as
+    /// front matter in their files, updating them when a file is written.
+    pub fn with_frontmatter(mut self) -> Self {
+        self.page_cache = self.page_cache.with_frontmatter();
+        self
+    }
+
+    /// Post the text appended to a `.comments/<page>.md` file as a new
+    /// comment on the page when the file is closed.
+    pub fn with_comment_posting(mut self) -> Self {
+        self.post_comments = true;
+        self
+    }
+
+    /// Take the inodes from the table, which may be kept in a file so
+    /// those of directories and pages stay the same across mounts.
+    pub fn with_inode_table(mut self, inodes: inode::InodeTable) -> Self {
+        self.inodes = inodes;
+        self
+    }
+
+    /// Log in again with the credentials of the login that returned `jwt`
+    /// shortly before it expires, so long-lived mounts keep working. A TFA
+    /// security code is never asked for, so TFA accounts need a secret.
+    pub fn with_login(
+        mut self,
+        url: String,
+        credentials: CredentialArgs,
+        jwt: &str,
+    ) -> Self {
+        self.login = Some(Login {
+            url,
+            credentials,
+            renew_at: renew_at(jwt),
+        });
+        self
+    }
+
+    /// Log in again if the JWT is about to expire, trying again a bit later
+    /// if that fails.
+    fn renew_login(&mut self) {
+        let Some(login) = &mut self.login else {
+            return;
+        };
+        if login.renew_at > now() {
+            return;
+        }
+        // never ask for a TFA security code here, nobody would see it
+        let result =
+            credentials::login_with(&login.url, &login.credentials, || {
+                Err("the account requires a TFA security code".into())
+            })
+            .and_then(|jwt| {
+                let api =
+                    Api::new(login.url.clone(), Credentials::Key(jwt.clone()))?;
+                Ok((api, jwt))
+            });
+        match result {
+            Ok((api, jwt)) => {
+                info!("renew_login: logged in again");
+                login.renew_at = renew_at(&jwt);
+                self.api = api;
+            }
+            Err(error) => {
+                self.stats.api_error();
+                warn!("renew_login: failed to log in again: {}", error);
+                login.renew_at = now() + RENEW_BEFORE / 2;
+            }
         }
     }
 
     fn get_inode(&mut self, ino: u64) -> Option<Inode> {
-        match InodeType::from(ino) {
-            InodeType::Page(id) => {
+        match self.inodes.entry(ino)? {
+            Entry::Page(id) => {
                 debug!("get_inode: page {}", id);
-                match self.page_cache.get(&self.api, id as u64) {
-                    Ok(page) => Some(Inode::Page(page)),
-                    Err(_) => None,
+                match self.page_cache.attributes(&self.api, id as u64) {
+                    Ok((page, size)) => Some(Inode::Page(ino, page, size)),
+                    Err(_) => {
+                        self.stats.api_error();
+                        None
+                    }
+                }
+            }
+            // the root only holds the locales when all are mounted
+            Entry::Directory(path) if path.is_empty() && self.all_locales => {
+                Some(Inode::Directory(ino, Vec::new()))
+            }
+            Entry::Directory(path) => {
+                debug!("get_inode: directory {}", path);
+                match self.folder(&path) {
+                    Some((locale, id)) => match self.children(locale, id) {
+                        Ok(page_tree) => Some(Inode::Directory(ino, page_tree)),
+                        Err(_) => {
+                            self.stats.api_error();
+                            None
+                        }
+                    },
+                    // made with mkdir and no page created in it yet
+                    None if self.virtual_dirs.contains_key(&ino) => {
+                        Some(Inode::VirtualDirectory(ino))
+                    }
+                    None => None,
                 }
             }
-            InodeType::Directory(id) => {
-                debug!("get_inode: directory {}", id);
-                match self.api.page_tree_get(
-                    id,
-                    PageTreeMode::ALL,
-                    true,
-                    self.locale.clone(),
-                ) {
-                    Ok(page_tree) => Some(Inode::Directory(page_tree)),
-                    Err(_) => None,
+            Entry::Comments(_) => Some(Inode::Comments(ino)),
+            Entry::PageComments(id) => {
+                debug!("get_inode: comments of page {}", id);
+                let transcript = self.comments(id)?;
+                Some(Inode::PageComments(ino, transcript, self.post_comments))
+            }
+            Entry::Versions(_) => Some(Inode::Versions(ino)),
+            Entry::PageVersions(_) => Some(Inode::PageVersions(ino)),
+            Entry::Version(id, version) => {
+                debug!("get_inode: version {} of page {}", version, id);
+                match self.page_cache.version(&self.api, id as u64, version) {
+                    // version ids are unique across pages
+                    Ok(found) if found.page_id == id => {
+                        Some(Inode::Version(ino, found))
+                    }
+                    Ok(_) => None,
+                    Err(_) => {
+                        self.stats.api_error();
+                        None
+                    }
+                }
+            }
+            Entry::AssetFolder(0) => Some(Inode::AssetFolder(ino)),
+            Entry::AssetFolder(id) => {
+                debug!("get_inode: asset folder {}", id);
+                match self.assets.folder_tree(&self.api) {
+                    Ok(folders) => {
+                        folders.get(id).map(|_| Inode::AssetFolder(ino))
+                    }
+                    Err(_) => {
+                        self.stats.api_error();
+                        None
+                    }
+                }
+            }
+            Entry::Asset(folder, name) => {
+                debug!("get_inode: asset {} in folder {}", name, folder);
+                match self.assets.item(&self.api, folder, &name) {
+                    Ok(asset) => {
+                        let size = self
+                            .assets
+                            .buffered_size(folder, &name)
+                            .or(asset.as_ref().map(|a| a.file_size as u64))?;
+                        Some(Inode::Asset(ino, asset, size))
+                    }
+                    Err(_) => {
+                        self.stats.api_error();
+                        None
+                    }
                 }
             }
+            Entry::ControlDirectory => Some(Inode::ControlDirectory(ino)),
+            Entry::Stats => Some(Inode::Stats(ino, self.stats.render())),
         }
     }
-}
 
-impl Filesystem for Fs {
-    /// Get attributes of an inode.
-    ///
-    /// # Arguments
-    /// * `req` - The request.
-    /// * `ino` - The inode number.
-    /// * `reply` - The reply.
-    ///
-    /// # Returns
-    /// Nothing.
-    fn getattr(&mut self, _req: &Request, ino: u64, reply: ReplyAttr) {
-        let start = SystemTime::now();
-        info!("getattr(ino={})", ino);
+    /// Page of the inode with its content, fetched only for reads and
+    /// writes.
+    fn page_content(&mut self, ino: u64) -> Option<PageMinimal> {
+        let Some(Entry::Page(id)) = self.inodes.entry(ino) else {
+            return None;
+        };
+        debug!("page_content: page {}", id);
+        match self.page_cache.get(&self.api, id as u64) {
+            Ok(page) => Some(page),
+            Err(_) => {
+                self.stats.api_error();
+                None
+            }
+        }
+    }
 
-        let attr = match self.get_inode(ino) {
-            Some(inode) => inode.into(),
-            None => {
-                warn!("getattr: inode {} not found", ino);
-                reply.error(ENOENT);
-                return;
+    /// Open a handle on the page with its content as of now, emptied for
+    /// `O_TRUNC` if opened for writing.
+    fn open_page(&mut self, ino: u64, flags: i32) -> Option<u64> {
+        let page = self.page_content(ino)?;
+        let truncated =
+            flags & O_TRUNC != 0 && flags & libc::O_ACCMODE != libc::O_RDONLY;
+        let content = match truncated {
+            true => Vec::new(),
+            false => page.content.clone().into_bytes(),
+        };
+        Some(self.handles.insert(Handle::Page {
+            page,
+            content,
+            append: flags & O_APPEND != 0,
+            dirty: truncated,
+        }))
+    }
+
+    /// Write the content of the page handle to the wiki if it changed since
+    /// the open or the last flush.
+    fn flush_page(&mut self, fh: u64) -> Result<(), i32> {
+        let Some(Handle::Page {
+            page,
+            content,
+            dirty,
+            ..
+        }) = self.handles.get_mut(fh)
+        else {
+            return Ok(());
+        };
+        if !*dirty {
+            return Ok(());
+        }
+        debug!("flush_page: writing page {}", page.id);
+        let text = String::from_utf8_lossy(content).into_owned();
+        match self
+            .page_cache
+            .update_content(&self.api, page.id as u64, text)
+        {
+            Ok(updated) => {
+                // the content stays as written, the front matter the wiki
+                // renders may differ from it
+                *page = updated;
+                *dirty = false;
+                Ok(())
+            }
+            Err(_) => {
+                self.stats.api_error();
+                Err(EIO)
+            }
+        }
+    }
+
+    /// The comments of the page rendered as a transcript.
+    fn comments(&mut self, id: i64) -> Option<String> {
+        let (page, _) = match self.page_cache.attributes(&self.api, id as u64) {
+            Ok(attributes) => attributes,
+            Err(_) => {
+                self.stats.api_error();
+                return None;
             }
         };
+        match self.api.comment_list(page.locale, page.path.clone()) {
+            Ok(comments) => Some(comments::render(&page.path, comments)),
+            Err(_) => {
+                self.stats.api_error();
+                None
+            }
+        }
+    }
 
-        let ttl = SystemTime::now().duration_since(start).unwrap();
-        reply.attr(&ttl, &attr);
+    /// Inode of the entry `name` in the page comments, below `.comments`.
+    fn comment_lookup(&mut self, parent: u64, name: &str) -> Option<u64> {
+        let entry = match self.inodes.entry(parent)? {
+            Entry::Directory(_) if name == ".comments" => {
+                match self.get_inode(parent)? {
+                    Inode::Directory(..) => Some(Entry::Comments(parent)),
+                    _ => None,
+                }
+            }
+            Entry::Comments(dir) => {
+                let stem = name.strip_suffix(".md")?;
+                match self.get_inode(dir)? {
+                    Inode::Directory(_, page_tree) => page_tree
+                        .iter()
+                        .filter(|pti| {
+                            pti.path.split('/').next_back() == Some(stem)
+                        })
+                        .find_map(|pti| pti.page_id)
+                        .map(Entry::PageComments),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }?;
+        Some(self.inodes.ino(entry))
     }
 
-    /// Set attributes of an inode.
-    ///
-    /// # Arguments
-    /// * `req` - The request.
-    /// * `ino` - The inode number.
-    /// * `mode` - The mode of the inode.
-    /// * `uid` - The user ID of the inode.
-    /// * `gid` - The group ID of the inode.
-    /// * `size` - The size of the inode.
-    /// * `atime` - The access time of the inode.
-    /// * `mtime` - The modification time of the inode.
-    /// * `fh` - The file handle.
-    /// * `crtime` - The creation time of the inode.
-    /// * `chgtime` - The change time of the inode.
-    /// * `bkuptime` - The backup time of the inode.
-    /// * `flags` - The flags of the inode.
-    /// * `reply` - The reply.
-    ///
-    /// # Returns
-    /// Nothing.
-    fn setattr(
+    /// Entries of a `.comments` directory: a file for each page of the
+    /// directory it is in.
+    fn comment_entries(
         &mut self,
-        _req: &Request,
         ino: u64,
-        mode: Option<u32>,
-        uid: Option<u32>,
-        gid: Option<u32>,
-        size: Option<u64>,
-        atime: Option<TimeOrNow>,
-        mtime: Option<TimeOrNow>,
-        _ctime: Option<SystemTime>,
-        fh: Option<u64>,
-        crtime: Option<SystemTime>,
-        chgtime: Option<SystemTime>,
-        bkuptime: Option<SystemTime>,
-        flags: Option<u32>,
-        reply: ReplyAttr,
-    ) {
-        let start = SystemTime::now();
-        info!(
-            "setattr(ino={}, mode={:?}, uid={:?}, gid={:?}, size={:?}, \
-              atime={:?}, mtime={:?}, fh={:?}, crtime={:?}, chgtime={:?}, \
-              bkuptime={:?}, flags={:?})",
-            ino,
-            mode,
-            uid,
-            gid,
-            size,
-            atime,
-            mtime,
-            fh,
-            crtime,
-            chgtime,
-            bkuptime,
-            flags
-        );
-
-        let inode = match self.get_inode(ino) {
-            Some(inode) => inode,
-            None => {
-                warn!("setattr: inode {} not found", ino);
-                reply.error(ENOENT);
-                return;
-            }
+    ) -> Option<Vec<(u64, fuser::FileType, String)>> {
+        let Some(Entry::Comments(dir)) = self.inodes.entry(ino) else {
+            return None;
         };
-
-        let page = match inode {
-            Inode::Page(page) => page,
-            _ => {
-                warn!("setattr: inode {} is not a page", ino);
-                reply.error(EINVAL);
-                return;
+        let mut entries = vec![(ino, fuser::FileType::Directory, ".".into())];
+        match self.get_inode(dir)? {
+            Inode::Directory(_, page_tree) => {
+                for pti in &page_tree {
+                    let (Some(basename), Some(id)) =
+                        (pti.path.split('/').next_back(), pti.page_id)
+                    else {
+                        continue;
+                    };
+                    entries.push((
+                        self.inodes.ino(Entry::PageComments(id)),
+                        fuser::FileType::RegularFile,
+                        format!("{}.md", basename),
+                    ));
+                }
             }
+            _ => return None,
+        }
+        Some(entries)
+    }
+
+    /// Post the text appended through the handle as a new comment, if any.
+    fn post_comment(&mut self, fh: u64) -> Result<(), i32> {
+        let Some(Handle::Comments {
+            page_id,
+            comment: Some(comment),
+            ..
+        }) = self.handles.get_mut(fh)
+        else {
+            return Ok(());
         };
+        let text = std::mem::take(comment).trim().to_string();
+        if text.is_empty() {
+            return Ok(());
+        }
+        debug!("post_comment: posting to page {}", page_id);
+        match self.api.comment_create(*page_id, None, text, None, None) {
+            Ok(()) => Ok(()),
+            Err(_) => {
+                self.stats.api_error();
+                Err(EIO)
+            }
+        }
+    }
 
-        if let Some(size) = size {
-            let mut content = page.content.clone();
-            if size < content.len() as u64 {
-                content.truncate(std::cmp::max(size as usize, 1));
+    /// Inode of the entry `name` in the page history, below `.versions`.
+    fn version_lookup(&mut self, parent: u64, name: &str) -> Option<u64> {
+        let entry = match self.inodes.entry(parent)? {
+            Entry::Directory(_) if name == ".versions" => {
+                match self.get_inode(parent)? {
+                    Inode::Directory(..) => Some(Entry::Versions(parent)),
+                    _ => None,
+                }
             }
-            match self.page_cache.update_content(
-                &self.api,
-                page.id as u64,
-                content,
-            ) {
-                Ok(_) => {
-                    debug!("setattr: updated inode {}", ino);
-                    let attr = match self.get_inode(ino) {
-                        Some(inode) => inode.into(),
-                        None => {
-                            warn!("setattr: inode {} not found", ino);
-                            reply.error(ENOENT);
-                            return;
-                        }
-                    };
-                    reply.attr(
-                        &SystemTime::now().duration_since(start).unwrap(),
-                        &attr,
-                    );
-                    return;
+            Entry::Versions(dir) => {
+                let stem = name.strip_suffix(".md")?;
+                match self.get_inode(dir)? {
+                    Inode::Directory(_, page_tree) => page_tree
+                        .iter()
+                        .filter(|pti| {
+                            pti.path.split('/').next_back() == Some(stem)
+                        })
+                        .find_map(|pti| pti.page_id)
+                        .map(Entry::PageVersions),
+                    _ => None,
                 }
-                Err(_) => {
-                    error!("setattr: failed to update inode {}", ino);
-                    reply.error(EIO);
-                    return;
+            }
+            Entry::PageVersions(id) => {
+                let version = name.strip_suffix(".md")?.parse().ok()?;
+                Some(Entry::Version(id, version))
+            }
+            _ => None,
+        }?;
+        Some(self.inodes.ino(entry))
+    }
+
+    /// Entries of a directory in the page history: the pages of the
+    /// directory in `.versions`, and their versions in `.versions/<page>.md`.
+    fn version_entries(
+        &mut self,
+        ino: u64,
+    ) -> Option<Vec<(u64, fuser::FileType, String)>> {
+        let mut entries = vec![(ino, fuser::FileType::Directory, ".".into())];
+        match self.inodes.entry(ino)? {
+            Entry::Versions(dir) => match self.get_inode(dir)? {
+                Inode::Directory(_, page_tree) => {
+                    for pti in &page_tree {
+                        let (Some(basename), Some(id)) =
+                            (pti.path.split('/').next_back(), pti.page_id)
+                        else {
+                            continue;
+                        };
+                        entries.push((
+                            self.inodes.ino(Entry::PageVersions(id)),
+                            fuser::FileType::Directory,
+                            format!("{}.md", basename),
+                        ));
+                    }
+                }
+                _ => return None,
+            },
+            Entry::PageVersions(id) => {
+                let history = match self.api.page_history_get(id, None, None) {
+                    Ok(history) => history,
+                    Err(_) => {
+                        self.stats.api_error();
+                        return None;
+                    }
+                };
+                for version in history.trail.into_iter().flatten().flatten() {
+                    entries.push((
+                        self.inodes.ino(Entry::Version(id, version.version_id)),
+                        fuser::FileType::RegularFile,
+                        format!("{}.md", version.version_id),
+                    ));
                 }
             }
+            _ => return None,
         }
-
-        let attr = Inode::Page(page).into();
-        reply.attr(&SystemTime::now().duration_since(start).unwrap(), &attr);
+        Some(entries)
+    }
+
+    /// Entries of an asset folder: its subfolders and assets.
+    fn asset_entries(
+        &mut self,
+        ino: u64,
+    ) -> Option<Vec<(u64, fuser::FileType, String)>> {
+        let Some(Entry::AssetFolder(folder)) = self.inodes.entry(ino) else {
+            return None;
+        };
+        let mut entries = vec![(ino, fuser::FileType::Directory, ".".into())];
+        match self.assets.entries(&self.api, folder) {
+            Ok(assets) => {
+                entries.extend(assets.into_iter().map(|(entry, kind, name)| {
+                    (self.inodes.ino(entry), kind, name)
+                }))
+            }
+            Err(_) => {
+                self.stats.api_error();
+                return None;
+            }
+        }
+        Some(entries)
+    }
+
+    /// Create the asset `name` in the asset folder `parent`, which is
+    /// uploaded once it is flushed, and get its inode and attributes.
+    fn create_asset(
+        &mut self,
+        parent: u64,
+        name: &OsStr,
+    ) -> Result<(u64, FileAttr), i32> {
+        let Some(Entry::AssetFolder(folder)) = self.inodes.entry(parent) else {
+            return Err(ENOENT);
+        };
+        let name = name
+            .to_str()
+            .filter(|name| !name.is_empty() && !name.contains('/'))
+            .ok_or(EINVAL)?;
+        debug!("create_asset: {} in folder {}", name, folder);
+        self.assets.create(folder, name);
+        let ino = self.inodes.ino(Entry::Asset(folder, name.to_string()));
+        let attr = self.get_inode(ino).map(Inode::into).ok_or(EIO)?;
+        Ok((ino, attr))
+    }
+
+    /// All entries of the directory, starting with itself as `.`.
+    fn dir_entries(
+        &mut self,
+        ino: u64,
+    ) -> Result<Vec<(u64, fuser::FileType, String)>, i32> {
+        // the virtual control directory
+        if self.inodes.entry(ino) == Some(Entry::ControlDirectory) {
+            return Ok(vec![
+                (ino, fuser::FileType::Directory, ".".into()),
+                (
+                    self.inodes.ino(Entry::Stats),
+                    fuser::FileType::RegularFile,
+                    "stats".into(),
+                ),
+            ]);
+        }
+
+        // the read-only page history and comments, the assets and the
+        // locales
+        if let Some(entries) = self
+            .version_entries(ino)
+            .or_else(|| self.comment_entries(ino))
+            .or_else(|| self.asset_entries(ino))
+            .or_else(|| self.locale_entries(ino))
+        {
+            return Ok(entries);
+        }
+
+        let page_tree = match self.get_inode(ino) {
+            Some(Inode::Directory(_, page_tree)) => page_tree,
+            Some(Inode::VirtualDirectory(_)) => Vec::new(),
+            _ => return Err(ENOENT),
+        };
+
+        // fetch the page attributes in one go before the getattr calls that
+        // follow
+        let page_ids: Vec<u64> = page_tree
+            .iter()
+            .filter_map(|pti| pti.page_id.map(|id| id as u64))
+            .collect();
+        self.page_cache.prefetch(&self.api, &page_ids);
+
+        let mut entries = vec![(ino, fuser::FileType::Directory, ".".into())];
+        for pti in &page_tree {
+            let basename = pti.path.split('/').next_back().unwrap();
+            if pti.is_folder {
+                let path = self.mount_path(&pti.locale, &pti.path);
+                entries.push((
+                    self.inodes.directory(&path),
+                    fuser::FileType::Directory,
+                    basename.to_string(),
+                ));
+            }
+            if let Some(pid) = pti.page_id {
+                entries.push((
+                    self.inodes.page(pid),
+                    fuser::FileType::RegularFile,
+                    format!("{}.md", basename),
+                ));
+            }
+        }
+
+        // the directories made with mkdir that hold no pages yet
+        if !self.virtual_dirs.is_empty() {
+            let path = self.dir_path(ino).unwrap_or_default();
+            for (child, name) in self.virtual_children(&path) {
+                let is_folder = |pti: &PageTreeItem| {
+                    pti.is_folder
+                        && pti.path.split('/').next_back() == Some(&name)
+                };
+                if !page_tree.iter().any(is_folder) {
+                    entries.push((child, fuser::FileType::Directory, name));
+                }
+            }
+        }
+
+        // the virtual control and asset directories in the root
+        if ino == FUSE_ROOT_ID {
+            entries.extend(self.root_entries());
+        }
+        Ok(entries)
+    }
+
+    /// Entries of the root when all locales are mounted: a directory for
+    /// each locale next to the virtual ones.
+    fn locale_entries(
+        &mut self,
+        ino: u64,
+    ) -> Option<Vec<(u64, fuser::FileType, String)>> {
+        if !self.all_locales || ino != FUSE_ROOT_ID {
+            return None;
+        }
+        let mut entries = vec![(ino, fuser::FileType::Directory, ".".into())];
+        for locale in self.locales.clone() {
+            let ino = self.inodes.directory(&locale);
+            entries.push((ino, fuser::FileType::Directory, locale));
+        }
+        entries.extend(self.root_entries());
+        Some(entries)
+    }
+
+    /// The virtual control and asset directories in the root.
+    fn root_entries(&mut self) -> [(u64, fuser::FileType, String); 2] {
+        [
+            (
+                self.inodes.ino(Entry::ControlDirectory),
+                fuser::FileType::Directory,
+                ".wikijs".into(),
+            ),
+            (
+                self.inodes.ino(Entry::AssetFolder(0)),
+                fuser::FileType::Directory,
+                "_assets".into(),
+            ),
+        ]
+    }
+
+    /// Children of the folder with the tree id in the page tree of the
+    /// locale.
+    fn children(
+        &mut self,
+        locale: usize,
+        id: i64,
+    ) -> Result<Vec<PageTreeItem>, PageError> {
+        self.trees[locale].children(&self.api, &self.locales[locale], id)
+    }
+
+    fn invalidate_trees(&mut self) {
+        for tree in self.trees.iter_mut() {
+            tree.invalidate();
+        }
+    }
+
+    /// Locale and path in the wiki of a path in the mount, which starts
+    /// with the locale when all locales are mounted.
+    fn wiki_path(&self, path: &str) -> (String, String) {
+        if !self.all_locales {
+            return (self.locales[0].clone(), path.to_string());
+        }
+        match path.split_once('/') {
+            Some((locale, path)) => (locale.to_string(), path.to_string()),
+            None => (path.to_string(), String::new()),
+        }
+    }
+
+    /// Path in the mount of a path in the wiki in the locale.
+    fn mount_path(&self, locale: &str, path: &str) -> String {
+        match (self.all_locales, path.is_empty()) {
+            (false, _) => path.to_string(),
+            (true, true) => locale.to_string(),
+            (true, false) => format!("{}/{}", locale, path),
+        }
+    }
+
+    /// Locale index and tree id of the folder at `path` in the mount, if
+    /// the wiki has one, with 0 for the root of a locale.
+    fn folder(&mut self, path: &str) -> Option<(usize, i64)> {
+        let (locale, path) = self.wiki_path(path);
+        let locale = self.locales.iter().position(|l| *l == locale)?;
+        let mut id = 0;
+        let mut prefix = String::new();
+        for segment in path.split('/').filter(|s| !s.is_empty()) {
+            prefix = match prefix.is_empty() {
+                true => segment.to_string(),
+                false => format!("{}/{}", prefix, segment),
+            };
+            let children = match self.children(locale, id) {
+                Ok(children) => children,
+                Err(_) => {
+                    self.stats.api_error();
+                    return None;
+                }
+            };
+            id = children
+                .into_iter()
+                .find(|pti| pti.is_folder && pti.path == prefix)?
+                .id;
+        }
+        Some((locale, id))
+    }
+
+    /// Path of the directory inode in the mount, empty for the root.
+    fn dir_path(&mut self, ino: u64) -> Option<String> {
+        self.inodes.path(ino).map(str::to_string)
+    }
+
+    /// Path in the mount of the entry `name` in the directory `parent`.
+    fn entry_path(&mut self, parent: u64, name: &str) -> Result<String, i32> {
+        if self.inodes.entry(parent).is_some_and(|e| e.is_read_only()) {
+            return Err(EACCES);
+        }
+        // the root only holds the locales when all are mounted
+        if self.all_locales && parent == FUSE_ROOT_ID {
+            return Err(EACCES);
+        }
+        let dir = self.dir_path(parent).ok_or(ENOENT)?;
+        Ok(match dir.is_empty() {
+            true => name.to_string(),
+            false => format!("{}/{}", dir, name),
+        })
+    }
+
+    fn virtual_dir_ino(&self, path: &str) -> Option<u64> {
+        self.virtual_dirs
+            .iter()
+            .find(|(_, dir)| *dir == path)
+            .map(|(ino, _)| *ino)
+    }
+
+    /// Directories made with mkdir directly below `path`, with their names.
+    fn virtual_children(&self, path: &str) -> Vec<(u64, String)> {
+        self.virtual_dirs
+            .iter()
+            .filter_map(|(ino, dir)| {
+                let name = match path.is_empty() {
+                    true => dir.as_str(),
+                    false => dir.strip_prefix(path)?.strip_prefix('/')?,
+                };
+                (!name.contains('/')).then(|| (*ino, name.to_string()))
+            })
+            .collect()
+    }
+
+    /// Move pages given as id, old and new path in the mount, moving the
+    /// already moved ones back if one fails. With all locales mounted pages
+    /// may move to another locale.
+    fn move_pages(
+        &mut self,
+        moves: &[(i64, String, String)],
+    ) -> Result<(), i32> {
+        self.invalidate_trees();
+        for (i, (id, from, to)) in moves.iter().enumerate() {
+            debug!("move_pages: page {} from {} to {}", id, from, to);
+            let (locale, path) = self.wiki_path(to);
+            let error = match self.api.page_move(*id, path, locale) {
+                Ok(()) => {
+                    self.page_cache.evict(*id as u64);
+                    continue;
+                }
+                Err(error) => error,
+            };
+            self.stats.api_error();
+            error!("move_pages: failed to move {} to {}: {}", from, to, error);
+            for (id, from, to) in moves[..i].iter().rev() {
+                let (locale, path) = self.wiki_path(from);
+                match self.api.page_move(*id, path, locale) {
+                    Ok(()) => {
+                        warn!("move_pages: moved {} back to {}", to, from)
+                    }
+                    Err(error) => error!(
+                        "move_pages: failed to move {} back to {}: {}",
+                        to, from, error
+                    ),
+                }
+                self.page_cache.evict(*id as u64);
+            }
+            return Err(match error {
+                PageError::PagePathCollision => EEXIST,
+                PageError::PageMoveForbidden => EACCES,
+                _ => EIO,
+            });
+        }
+        Ok(())
+    }
+
+    /// Create a markdown page for the new file `name` in the directory
+    /// `parent` in its locale, titled after the file name, and get its
+    /// attributes.
+    fn create_page(
+        &mut self,
+        parent: u64,
+        name: &OsStr,
+    ) -> Result<FileAttr, i32> {
+        let stem = name
+            .to_str()
+            .and_then(|name| name.strip_suffix(".md"))
+            .filter(|stem| !stem.is_empty() && !stem.contains('/'))
+            .ok_or(EINVAL)?;
+        let path = self.entry_path(parent, stem)?;
+        let (locale, path) = self.wiki_path(&path);
+        let title = title_from_filename(stem);
+        debug!("create_page: {} titled {}", path, title);
+        let result = self.api.page_create(
+            format!("# {}\n", title),
+            String::new(),
+            PageEditor::Markdown,
+            true,
+            false,
+            locale.clone(),
+            path.clone(),
+            None,
+            None,
+            None,
+            None,
+            Vec::new(),
+            title,
+        );
+        self.invalidate_trees();
+        match result {
+            Ok(()) => {}
+            Err(PageError::PageDuplicateCreate) => return Err(EEXIST),
+            Err(_) => {
+                self.stats.api_error();
+                return Err(EIO);
+            }
+        }
+        let page = self.api.page_get_by_path(path, locale).map_err(|_| {
+            self.stats.api_error();
+            EIO
+        })?;
+        let ino = self.inodes.page(page.id);
+        self.get_inode(ino).map(Inode::into).ok_or(EIO)
+    }
+}
+
+impl Filesystem for Fs {
+    /// Initialize the filesystem, asking the kernel to pass `O_TRUNC` on to
+    /// open instead of truncating files with setattr first.
+    ///
+    /// # Arguments
+    /// * `req` - The request.
+    /// * `config` - The kernel configuration.
+    ///
+    /// # Returns
+    /// Ok, as the filesystem also works without.
+    fn init(
+        &mut self,
+        _req: &Request<'_>,
+        config: &mut KernelConfig,
+    ) -> Result<(), c_int> {
+        info!("init()");
+        if config.add_capabilities(FUSE_ATOMIC_O_TRUNC).is_err() {
+            warn!("init: the kernel does not pass O_TRUNC on to open");
+        }
+        Ok(())
+    }
+
+    /// Clean up when the filesystem is unmounted, which saves the inode
+    /// table if it is kept in a file.
+    ///
+    /// # Returns
+    /// Nothing.
+    fn destroy(&mut self) {
+        info!("destroy()");
+        if let Err(error) = self.inodes.save() {
+            error!("destroy: failed to save the inode table: {}", error);
+        }
+    }
+
+    /// Get attributes of an inode.
+    ///
+    /// # Arguments
+    /// * `req` - The request.
+    /// * `ino` - The inode number.
+    /// * `reply` - The reply.
+    ///
+    /// # Returns
+    /// Nothing.
+    fn getattr(&mut self, _req: &Request, ino: u64, reply: ReplyAttr) {
+        let _timer = OpTimer::start(&self.stats, "getattr");
+        self.renew_login();
+        let start = SystemTime::now();
+        info!("getattr(ino={})", ino);
+
+        let attr = match self.get_inode(ino) {
+            Some(inode) => inode.into(),
+            None => {
+                warn!("getattr: inode {} not found", ino);
+                reply.error(ENOENT);
+                return;
+            }
+        };
+
+        let ttl = SystemTime::now().duration_since(start).unwrap();
+        reply.attr(&ttl, &attr);
+    }
+
+    /// Get filesystem statistics, with blocks and files counted from the
+    /// pages and assets of the wiki and its estimated size.
+    ///
+    /// # Arguments
+    /// * `req` - The request.
+    /// * `ino` - The inode number.
+    /// * `reply` - The reply.
+    ///
+    /// # Returns
+    /// Nothing.
+    fn statfs(&mut self, _req: &Request<'_>, ino: u64, reply: ReplyStatfs) {
+        let _timer = OpTimer::start(&self.stats, "statfs");
+        self.renew_login();
+        info!("statfs(ino={})", ino);
+
+        let locale = match self.all_locales {
+            true => None,
+            false => Some(self.locales[0].clone()),
+        };
+        let usage = match self.usage.get(&self.api, locale) {
+            Ok(usage) => usage,
+            Err(error) => {
+                self.stats.api_error();
+                error!("statfs: failed to count pages and assets: {}", error);
+                reply.error(EIO);
+                return;
+            }
+        };
+        let bytes = usage.total_bytes(self.page_cache.average_size());
+        debug!("statfs: estimated size of the wiki is {} bytes", bytes);
+        let used_blocks = bytes.div_ceil(BLOCK_SIZE);
+        reply.statfs(
+            used_blocks + FREE_BLOCKS,
+            FREE_BLOCKS,
+            FREE_BLOCKS,
+            usage.pages + usage.assets + FREE_FILES,
+            FREE_FILES,
+            BLOCK_SIZE as u32,
+            255,
+            BLOCK_SIZE as u32,
+        );
+    }
+
+    /// Set attributes of an inode.
+    ///
+    /// # Arguments
+    /// * `req` - The request.
+    /// * `ino` - The inode number.
+    /// * `mode` - The mode of the inode.
+    /// * `uid` - The user ID of the inode.
+    /// * `gid` - The group ID of the inode.
+    /// * `size` - The size of the inode.
+    /// * `atime` - The access time of the inode.
+    /// * `mtime` - The modification time of the inode.
+    /// * `fh` - The file handle.
+    /// * `crtime` - The creation time of the inode.
+    /// * `chgtime` - The change time of the inode.
+    /// * `bkuptime` - The backup time of the inode.
+    /// * `flags` - The flags of the inode.
+    /// * `reply` - The reply.
+    ///
+    /// # Returns
+    /// Nothing.
+    fn setattr(
+        &mut self,
+        _req: &Request,
+        ino: u64,
+        mode: Option<u32>,
+        uid: Option<u32>,
+        gid: Option<u32>,
+        size: Option<u64>,
+        atime: Option<TimeOrNow>,
+        mtime: Option<TimeOrNow>,
+        _ctime: Option<SystemTime>,
+        fh: Option<u64>,
+        crtime: Option<SystemTime>,
+        chgtime: Option<SystemTime>,
+        bkuptime: Option<SystemTime>,
+        flags: Option<u32>,
+        reply: ReplyAttr,
+    ) {
+        let _timer = OpTimer::start(&self.stats, "setattr");
+        self.renew_login();
+        let start = SystemTime::now();
+        info!(
+            "setattr(ino={}, mode={:?}, uid={:?}, gid={:?}, size={:?}, \
+              atime={:?}, mtime={:?}, fh={:?}, crtime={:?}, chgtime={:?}, \
+              bkuptime={:?}, flags={:?})",
+            ino,
+            mode,
+            uid,
+            gid,
+            size,
+            atime,
+            mtime,
+            fh,
+            crtime,
+            chgtime,
+            bkuptime,
+            flags
+        );
+
+        let entry = self.inodes.entry(ino);
+        if let Some(Entry::Asset(folder, name)) = &entry {
+            if let Some(size) = size {
+                if self
+                    .assets
+                    .truncate(&self.api, *folder, name, size)
+                    .is_err()
+                {
+                    self.stats.api_error();
+                    error!("setattr: failed to truncate asset {}", ino);
+                    reply.error(EIO);
+                    return;
+                }
+            }
+            match self.get_inode(ino) {
+                Some(inode) => reply.attr(
+                    &SystemTime::now().duration_since(start).unwrap(),
+                    &inode.into(),
+                ),
+                None => {
+                    warn!("setattr: inode {} not found", ino);
+                    reply.error(ENOENT);
+                }
+            }
+            return;
+        }
+
+        if !matches!(entry, Some(Entry::Page(_))) {
+            warn!("setattr: inode {} is not a page", ino);
+            reply.error(EINVAL);
+            return;
+        }
+
+        // an ftruncate only changes the content of the handle, which is
+        // written on flush
+        if let (
+            Some(size),
+            Some(Handle::Page {
+                page,
+                content,
+                dirty,
+                ..
+            }),
+        ) = (size, fh.and_then(|fh| self.handles.get_mut(fh)))
+        {
+            content.truncate(size as usize);
+            *dirty = true;
+            let size = content.len() as u64;
+            let attr =
+                Inode::Page(ino, page::page_attributes(page), size).into();
+            reply
+                .attr(&SystemTime::now().duration_since(start).unwrap(), &attr);
+            return;
+        }
+
+        let page = match self.page_content(ino) {
+            Some(page) => page,
+            None => {
+                warn!("setattr: inode {} not found", ino);
+                reply.error(ENOENT);
+                return;
+            }
+        };
+
+        if let Some(size) = size {
+            let mut content = page.content.clone();
+            if size < content.len() as u64 {
+                content.truncate(std::cmp::max(size as usize, 1));
+            }
+            match self.page_cache.update_content(
+                &self.api,
+                page.id as u64,
+                content,
+            ) {
+                Ok(_) => {
+                    debug!("setattr: updated inode {}", ino);
+                    let attr = match self.get_inode(ino) {
+                        Some(inode) => inode.into(),
+                        None => {
+                            warn!("setattr: inode {} not found", ino);
+                            reply.error(ENOENT);
+                            return;
+                        }
+                    };
+                    reply.attr(
+                        &SystemTime::now().duration_since(start).unwrap(),
+                        &attr,
+                    );
+                    return;
+                }
+                Err(_) => {
+                    self.stats.api_error();
+                    error!("setattr: failed to update inode {}", ino);
+                    reply.error(EIO);
+                    return;
+                }
+            }
+        }
+
+        let size = page.content.len() as u64;
+        let attr = Inode::Page(ino, page::page_attributes(&page), size).into();
+        reply.attr(&SystemTime::now().duration_since(start).unwrap(), &attr);
     }
 
     /// Read entries of a directory.
@@ -295,327 +1404,1197 @@ impl Filesystem for Fs {
     /// * `req` - The request.
     /// * `ino` - The inode number.
     /// * `fh` - The file handle.
-    /// * `offset` - The offset in the directory.
+    /// * `offset` - The offset in the directory.
+    /// * `reply` - The reply.
+    ///
+    /// # Returns
+    /// Nothing.
+    fn readdir(
+        &mut self,
+        _req: &Request,
+        ino: u64,
+        fh: u64,
+        offset: i64,
+        mut reply: ReplyDirectory,
+    ) {
+        let _timer = OpTimer::start(&self.stats, "readdir");
+        self.renew_login();
+        info!("readdir(ino={}, fh={}, offset={})", ino, fh, offset);
+
+        // a listing goes on with the entries of its opendir
+        let entries = match self.handles.get(fh) {
+            Some(Handle::Directory(entries)) => entries.clone(),
+            _ => match self.dir_entries(ino) {
+                Ok(entries) => entries,
+                Err(errno) => {
+                    warn!("readdir: inode {} is not a directory", ino);
+                    reply.error(errno);
+                    return;
+                }
+            },
+        };
+
+        for (i, (child, kind, name)) in
+            entries.into_iter().enumerate().skip(offset.max(0) as usize)
+        {
+            if reply.add(child, i as i64 + 1, kind, name) {
+                debug!("readdir: buffer full at offset {}", i);
+                break;
+            }
+        }
+        reply.ok();
+    }
+
+    /// Open a directory, which lists its entries once for all readdir calls
+    /// with the handle.
+    ///
+    /// # Arguments
+    /// * `req` - The request.
+    /// * `ino` - The inode number.
+    /// * `flags` - The flags of the directory.
+    /// * `reply` - The reply.
+    ///
+    /// # Returns
+    /// Nothing.
+    fn opendir(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        flags: i32,
+        reply: ReplyOpen,
+    ) {
+        let _timer = OpTimer::start(&self.stats, "opendir");
+        self.renew_login();
+        info!("opendir(ino={}, flags={:?})", ino, flags);
+        match self.dir_entries(ino) {
+            Ok(entries) => {
+                let fh = self.handles.insert(Handle::Directory(entries));
+                reply.opened(fh, 0);
+            }
+            Err(errno) => {
+                warn!("opendir: inode {} is not a directory", ino);
+                reply.error(errno);
+            }
+        }
+    }
+
+    /// Release a directory, which drops the entries of its handle.
+    ///
+    /// # Arguments
+    /// * `req` - The request.
+    /// * `ino` - The inode number.
+    /// * `fh` - The file handle.
+    /// * `flags` - The flags of the directory.
+    /// * `reply` - The reply.
+    ///
+    /// # Returns
+    /// Nothing.
+    fn releasedir(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        fh: u64,
+        flags: i32,
+        reply: ReplyEmpty,
+    ) {
+        let _timer = OpTimer::start(&self.stats, "releasedir");
+        info!("releasedir(ino={}, fh={}, flags={:?})", ino, fh, flags);
+        self.handles.remove(fh);
+        reply.ok();
+    }
+
+    // Lookup inode by name and parent inode.
+    //
+    // # Arguments
+    // * `req` - The request.
+    // * `parent` - The parent inode number.
+    // * `name` - The name of the inode.
+    // * `reply` - The reply.
+    //
+    // # Returns
+    // Nothing.
+    fn lookup(
+        &mut self,
+        _req: &Request,
+        parent: u64,
+        name: &OsStr,
+        reply: ReplyEntry,
+    ) {
+        let _timer = OpTimer::start(&self.stats, "lookup");
+        self.renew_login();
+        let start = SystemTime::now();
+        let mut name_str = name.to_str().unwrap();
+        info!("lookup(parent={}, name={:?})", parent, name_str);
+
+        let parent_entry = self.inodes.entry(parent);
+        let virtual_ino = match (&parent_entry, name_str) {
+            (_, ".wikijs") if parent == FUSE_ROOT_ID => {
+                Some(self.inodes.ino(Entry::ControlDirectory))
+            }
+            (_, "_assets") if parent == FUSE_ROOT_ID => {
+                Some(self.inodes.ino(Entry::AssetFolder(0)))
+            }
+            (_, _) if parent == FUSE_ROOT_ID && self.all_locales => {
+                match self.locales.iter().any(|locale| locale == name_str) {
+                    true => Some(self.inodes.directory(name_str)),
+                    false => None,
+                }
+            }
+            (Some(Entry::ControlDirectory), "stats") => {
+                Some(self.inodes.ino(Entry::Stats))
+            }
+            (Some(Entry::ControlDirectory), _) => {
+                reply.error(ENOENT);
+                return;
+            }
+            _ => None,
+        };
+        if let Some(ino) = virtual_ino {
+            if let Some(inode) = self.get_inode(ino) {
+                let ttl = SystemTime::now().duration_since(start).unwrap();
+                reply.entry(&ttl, &inode.into(), 0);
+                return;
+            }
+        }
+
+        // the assets
+        if let Some(Entry::AssetFolder(folder)) = parent_entry {
+            let inode = match self.assets.lookup(&self.api, folder, name_str) {
+                Ok(entry) => entry.and_then(|entry| {
+                    let ino = self.inodes.ino(entry);
+                    self.get_inode(ino)
+                }),
+                Err(_) => {
+                    self.stats.api_error();
+                    None
+                }
+            };
+            match inode {
+                Some(inode) => {
+                    let ttl = SystemTime::now().duration_since(start).unwrap();
+                    reply.entry(&ttl, &inode.into(), 0);
+                }
+                None => {
+                    warn!("lookup: asset {} not found", name_str);
+                    reply.error(ENOENT);
+                }
+            }
+            return;
+        }
+
+        // the page comments
+        if name_str == ".comments"
+            || matches!(parent_entry, Some(Entry::Comments(_)))
+        {
+            let inode = self
+                .comment_lookup(parent, name_str)
+                .and_then(|ino| self.get_inode(ino));
+            match inode {
+                Some(inode) => {
+                    let ttl = SystemTime::now().duration_since(start).unwrap();
+                    reply.entry(&ttl, &inode.into(), 0);
+                }
+                None => {
+                    warn!("lookup: {} not found in the comments", name_str);
+                    reply.error(ENOENT);
+                }
+            }
+            return;
+        }
+
+        // the read-only page history
+        if name_str == ".versions"
+            || matches!(
+                parent_entry,
+                Some(Entry::Versions(_) | Entry::PageVersions(_))
+            )
+        {
+            let inode = self
+                .version_lookup(parent, name_str)
+                .and_then(|ino| self.get_inode(ino));
+            match inode {
+                Some(inode) => {
+                    let ttl = SystemTime::now().duration_since(start).unwrap();
+                    reply.entry(&ttl, &inode.into(), 0);
+                }
+                None => {
+                    warn!("lookup: {} not found in the history", name_str);
+                    reply.error(ENOENT);
+                }
+            }
+            return;
+        }
+
+        let mut is_dir = true;
+        if name_str.ends_with(".md") {
+            name_str = &name_str[..name_str.len() - 3];
+            is_dir = false;
+        }
+
+        let page_tree = match self.get_inode(parent) {
+            Some(Inode::Directory(_, page_tree)) => page_tree,
+            Some(Inode::VirtualDirectory(_)) => Vec::new(),
+            _ => {
+                warn!("lookup: parent inode {} is not a directory", parent);
+                reply.error(ENOENT);
+                return;
+            }
+        };
+
+        for pti in page_tree {
+            if pti.path.split('/').last().unwrap() == name_str {
+                let ino = if is_dir {
+                    let path = self.mount_path(&pti.locale, &pti.path);
+                    self.inodes.directory(&path)
+                } else {
+                    self.inodes.page(pti.page_id.unwrap())
+                };
+                debug!("lookup: found inode {}", ino);
+                let attr = match self.get_inode(ino) {
+                    Some(inode) => inode.into(),
+                    None => {
+                        warn!("lookup: inode {} not found", ino);
+                        reply.error(ENOENT);
+                        return;
+                    }
+                };
+                let ttl = SystemTime::now().duration_since(start).unwrap();
+                reply.entry(&ttl, &attr, 0);
+                return;
+            }
+        }
+
+        if is_dir {
+            let ino = self
+                .entry_path(parent, name_str)
+                .ok()
+                .and_then(|path| self.virtual_dir_ino(&path));
+            if let Some(ino) = ino {
+                debug!("lookup: found virtual directory {}", ino);
+                let attr = Inode::VirtualDirectory(ino).into();
+                let ttl = SystemTime::now().duration_since(start).unwrap();
+                reply.entry(&ttl, &attr, 0);
+                return;
+            }
+        }
+
+        warn!("lookup: inode not found");
+        reply.error(ENOENT);
+    }
+
+    /// Read data from a file.
+    ///
+    /// # Arguments
+    /// * `req` - The request.
+    /// * `ino` - The inode number.
+    /// * `fh` - The file handle.
+    /// * `offset` - The offset in the file.
+    /// * `size` - The size of the data to read.
+    /// * `flags` - The flags.
+    /// * `lock_owner` - The lock owner.
+    /// * `reply` - The reply.
+    ///
+    /// # Returns
+    /// Nothing.
+    fn read(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        fh: u64,
+        offset: i64,
+        size: u32,
+        flags: i32,
+        lock_owner: Option<u64>,
+        reply: ReplyData,
+    ) {
+        let _timer = OpTimer::start(&self.stats, "read");
+        self.renew_login();
+        info!(
+            "read(ino={}, fh={}, offset={}, size={}, flags={:?}, \
+              lock_owner={:?})",
+            ino, fh, offset, size, flags, lock_owner
+        );
+
+        let entry = self.inodes.entry(ino);
+        if let Some(Entry::Directory(_)) = entry {
+            warn!("read: inode {} is a directory", ino);
+            reply.error(EISDIR);
+            return;
+        }
+
+        // virtual files are read as a whole
+        let content = match &entry {
+            Some(Entry::Version(..)) => match self.get_inode(ino) {
+                Some(Inode::Version(_, version)) => Some(version.content),
+                _ => {
+                    warn!("read: inode {} not found", ino);
+                    reply.error(ENOENT);
+                    return;
+                }
+            },
+            Some(Entry::PageComments(_)) => match self.handles.get(fh) {
+                Some(Handle::Comments { transcript, .. }) => {
+                    Some(transcript.clone())
+                }
+                _ => match self.get_inode(ino) {
+                    Some(Inode::PageComments(_, transcript, _)) => {
+                        Some(transcript)
+                    }
+                    _ => {
+                        warn!("read: inode {} not found", ino);
+                        reply.error(ENOENT);
+                        return;
+                    }
+                },
+            },
+            Some(Entry::Stats) => Some(self.stats.render()),
+            _ => None,
+        };
+        if let Some(content) = content {
+            let start = (offset.max(0) as usize).min(content.len());
+            let end = (start + size as usize).min(content.len());
+            reply.data(&content.as_bytes()[start..end]);
+            return;
+        }
+
+        // assets are held in memory while they are open
+        if let Some(Entry::Asset(folder, name)) = &entry {
+            let (offset, size) = (offset.max(0) as usize, size as usize);
+            match self.assets.read(*folder, name, offset, size) {
+                Some(data) => reply.data(data),
+                None => {
+                    warn!("read: asset {} is not open", ino);
+                    reply.error(EBADF);
+                }
+            }
+            return;
+        }
+
+        // the content of the handle, if the read comes with one
+        let content = match self.handles.get(fh) {
+            Some(Handle::Page { content, .. }) => Some(content.clone()),
+            _ => self.page_content(ino).map(|page| page.content.into_bytes()),
+        };
+        let content = match content {
+            Some(content) => content,
+            None => {
+                warn!("read: inode {} not found", ino);
+                reply.error(ENOENT);
+                return;
+            }
+        };
+
+        let content_size = content.len() as u64;
+
+        if offset < 0 || offset as u64 > content_size {
+            warn!(
+                "read: invalid offset {} for file of size {} with inode {}",
+                offset, size, ino
+            );
+            reply.error(ENOENT);
+            return;
+        }
+
+        let end = (offset as u64 + size as u64).min(content_size);
+        reply.data(&content[offset as usize..end as usize]);
+    }
+
+    /// Write data to a file.
+    ///
+    /// # Arguments
+    /// * `req` - The request.
+    /// * `ino` - The inode number.
+    /// * `fh` - The file handle.
+    /// * `offset` - The offset in the file.
+    /// * `data` - The data to write.
+    /// * `write_flags` - The write flags.
+    /// * `flags` - The flags.
+    /// * `lock_owner` - The lock owner.
+    /// * `reply` - The reply.
+    ///
+    /// # Returns
+    /// Nothing.
+    fn write(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        fh: u64,
+        offset: i64,
+        data: &[u8],
+        write_flags: u32,
+        flags: i32,
+        lock_owner: Option<u64>,
+        reply: ReplyWrite,
+    ) {
+        let _timer = OpTimer::start(&self.stats, "write");
+        self.renew_login();
+        info!(
+            "write(ino={}, fh={}, offset={}, data={:?}, write_flags={:?}, \
+              flags={:?}, lock_owner={:?})",
+            ino, fh, offset, data, write_flags, flags, lock_owner
+        );
+
+        let entry = self.inodes.entry(ino);
+        if let Some(Entry::Directory(_)) = entry {
+            warn!("write: inode {} is a directory", ino);
+            reply.error(EISDIR);
+            return;
+        }
+
+        if entry.as_ref().is_some_and(Entry::is_read_only) {
+            warn!("write: inode {} is read-only", ino);
+            reply.error(EACCES);
+            return;
+        }
+
+        // comments files only take appended text
+        if let Some(Entry::PageComments(_)) = entry {
+            match self.handles.get_mut(fh) {
+                Some(Handle::Comments {
+                    comment: Some(comment),
+                    ..
+                }) => {
+                    comment.push_str(&String::from_utf8_lossy(data));
+                    reply.written(data.len() as u32);
+                }
+                _ => {
+                    warn!("write: inode {} is not open for appending", ino);
+                    reply.error(EBADF);
+                }
+            }
+            return;
+        }
+
+        if let Some(Entry::Asset(folder, name)) = &entry {
+            match self
+                .assets
+                .write(*folder, name, offset.max(0) as usize, data)
+            {
+                Some(written) => reply.written(written as u32),
+                None => {
+                    warn!("write: asset {} is not open", ino);
+                    reply.error(EBADF);
+                }
+            }
+            return;
+        }
+
+        // writes through a handle only change its content
+        if let Some(Handle::Page {
+            content,
+            append,
+            dirty,
+            ..
+        }) = self.handles.get_mut(fh)
+        {
+            let size = content.len() as u64;
+            let offset = match append {
+                true => size as i64,
+                false => offset,
+            };
+            if offset < 0 || offset as u64 > size {
+                warn!(
+                    "write: invalid offset {} for file of size {} with inode {}",
+                    offset, size, ino
+                );
+                reply.error(EINVAL);
+                return;
+            }
+            splice(content, offset as usize, data);
+            *dirty = true;
+            debug!(
+                "write: inode {} from {} to {}",
+                ino,
+                offset,
+                offset as usize + data.len()
+            );
+            reply.written(data.len() as u32);
+            return;
+        }
+
+        let page = match self.page_content(ino) {
+            Some(page) => page,
+            None => {
+                warn!("write: inode {} not found", ino);
+                reply.error(ENOENT);
+                return;
+            }
+        };
+
+        let mut content = page.content.into_bytes();
+        let size = content.len() as u64;
+        if offset < 0 || offset as u64 > size {
+            warn!(
+                "write: invalid offset {} for file of size {} with inode {}",
+                offset, size, ino
+            );
+            reply.error(EINVAL);
+            return;
+        }
+        splice(&mut content, offset as usize, data);
+        debug!("write: inode {} from {}", ino, offset);
+
+        match self.page_cache.update_content(
+            &self.api,
+            page.id as u64,
+            String::from_utf8_lossy(&content).into_owned(),
+        ) {
+            Ok(_) => {
+                debug!("write: updated inode {}", ino);
+                reply.written(data.len() as u32);
+            }
+            Err(_) => {
+                self.stats.api_error();
+                error!("write: failed to update inode {}", ino);
+                reply.error(EIO);
+            }
+        }
+    }
+
+    /// Open a file.
+    ///
+    /// # Arguments
+    /// * `req` - The request.
+    /// * `ino` - The inode number.
+    /// * `flags` - The flags of the file.
+    /// * `reply` - The reply.
+    ///
+    /// # Returns
+    /// Nothing.
+    // fn open(
+    //     &mut self,
+    //     _req: &Request<'_>,
+    //     ino: u64,
+    //     flags: i32,
+    //     reply: ReplyOpen
+    // ) {
+    //     info!("open(ino={}, flags={:?})", ino, flags);
+    //     reply.opened(0, flags.try_into().unwrap());
+    // }
+
+    /// Open a file.
+    ///
+    /// The virtual stats file is opened with direct IO, since its size
+    /// changes between the getattr and read calls. So are pages, whose size
+    /// is only known once their content is fetched. Opening a page fetches
+    /// it into a handle, which reads are served from, and which `O_TRUNC`
+    /// empties and `O_APPEND` makes writes go to the end of. Assets are
+    /// fetched on open and held in memory until they are released.
+    ///
+    /// # Arguments
+    /// * `req` - The request.
+    /// * `ino` - The inode number.
+    /// * `flags` - The flags of the file.
+    /// * `reply` - The reply.
+    ///
+    /// # Returns
+    /// Nothing.
+    fn open(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        flags: i32,
+        reply: ReplyOpen,
+    ) {
+        let _timer = OpTimer::start(&self.stats, "open");
+        self.renew_login();
+        info!("open(ino={}, flags={:?})", ino, flags);
+        match self.inodes.entry(ino) {
+            Some(Entry::Page(_)) => match self.open_page(ino, flags) {
+                Some(fh) => reply.opened(fh, FOPEN_DIRECT_IO),
+                None => {
+                    warn!("open: inode {} not found", ino);
+                    reply.error(ENOENT);
+                }
+            },
+            Some(Entry::PageComments(_))
+                if flags & libc::O_ACCMODE != libc::O_RDONLY
+                    && (!self.post_comments || flags & O_APPEND == 0) =>
+            {
+                warn!("open: comments of inode {} can only be appended", ino);
+                reply.error(EACCES);
+            }
+            Some(Entry::PageComments(page_id)) => {
+                match self.comments(page_id) {
+                    Some(transcript) => {
+                        let appending =
+                            flags & libc::O_ACCMODE != libc::O_RDONLY;
+                        let fh = self.handles.insert(Handle::Comments {
+                            page_id,
+                            transcript,
+                            comment: appending.then(String::new),
+                        });
+                        reply.opened(fh, FOPEN_DIRECT_IO);
+                    }
+                    None => {
+                        warn!("open: inode {} not found", ino);
+                        reply.error(ENOENT);
+                    }
+                }
+            }
+            Some(Entry::Version(..))
+                if flags & libc::O_ACCMODE != libc::O_RDONLY =>
+            {
+                warn!("open: inode {} is read-only", ino);
+                reply.error(EACCES);
+            }
+            Some(Entry::Asset(folder, name)) => {
+                let truncate = flags & O_TRUNC != 0;
+                match self.assets.open(&self.api, folder, &name, truncate) {
+                    Ok(()) => reply.opened(0, FOPEN_DIRECT_IO),
+                    Err(_) => {
+                        self.stats.api_error();
+                        error!("open: failed to fetch asset {}", ino);
+                        reply.error(EIO);
+                    }
+                }
+            }
+            Some(Entry::Stats) => reply.opened(0, FOPEN_DIRECT_IO),
+            _ => reply.opened(0, 0),
+        }
+    }
+
+    /// Flush a file, which writes a page and uploads an asset if they were
+    /// changed and posts the text appended to a comments file.
+    ///
+    /// # Arguments
+    /// * `req` - The request.
+    /// * `ino` - The inode number.
+    /// * `fh` - The file handle.
+    /// * `lock_owner` - The lock owner.
     /// * `reply` - The reply.
     ///
     /// # Returns
     /// Nothing.
-    fn readdir(
+    fn flush(
         &mut self,
-        _req: &Request,
+        _req: &Request<'_>,
         ino: u64,
         fh: u64,
-        offset: i64,
-        mut reply: ReplyDirectory,
+        lock_owner: u64,
+        reply: ReplyEmpty,
     ) {
-        info!("readdir(ino={}, fh={}, offset={})", ino, fh, offset);
-        let mut next_offset = offset + 1;
-
-        // get page tree
-        let page_tree = match self.get_inode(ino) {
-            Some(Inode::Directory(page_tree)) => page_tree,
-            _ => {
-                warn!("readdir: inode {} is not a directory", ino);
-                reply.error(ENOENT);
+        let _timer = OpTimer::start(&self.stats, "flush");
+        self.renew_login();
+        info!("flush(ino={}, fh={}, lock_owner={})", ino, fh, lock_owner);
+        if let Some(Entry::Asset(folder, name)) = self.inodes.entry(ino) {
+            if self.assets.flush(&self.api, folder, &name).is_err() {
+                self.stats.api_error();
+                error!("flush: failed to upload asset {}", ino);
+                reply.error(EIO);
                 return;
             }
-        };
+        }
+        if let Err(errno) = self.post_comment(fh) {
+            error!("flush: failed to post comment on inode {}", ino);
+            reply.error(errno);
+            return;
+        }
+        if let Err(errno) = self.flush_page(fh) {
+            error!("flush: failed to write inode {}", ino);
+            reply.error(errno);
+            return;
+        }
+        reply.ok();
+    }
 
-        // add current directory entry
-        if offset == 0 {
-            if reply.add(ino, 1, fuser::FileType::Directory, ".") {
-                debug!("readdir: buffer full at offset 0");
-                reply.ok();
+    /// Release a file, which writes a page changed since the last flush,
+    /// drops its handle, and drops an asset from memory once its last
+    /// handle is released.
+    ///
+    /// # Arguments
+    /// * `req` - The request.
+    /// * `ino` - The inode number.
+    /// * `fh` - The file handle.
+    /// * `flags` - The flags of the file.
+    /// * `lock_owner` - The lock owner.
+    /// * `flush` - Whether to flush the file.
+    /// * `reply` - The reply.
+    ///
+    /// # Returns
+    /// Nothing.
+    fn release(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        fh: u64,
+        flags: i32,
+        lock_owner: Option<u64>,
+        flush: bool,
+        reply: ReplyEmpty,
+    ) {
+        let _timer = OpTimer::start(&self.stats, "release");
+        self.renew_login();
+        info!(
+            "release(ino={}, fh={}, flags={:?}, lock_owner={:?}, flush={})",
+            ino, fh, flags, lock_owner, flush
+        );
+        if let Some(Entry::Asset(folder, name)) = self.inodes.entry(ino) {
+            if self.assets.release(&self.api, folder, &name).is_err() {
+                self.stats.api_error();
+                error!("release: failed to upload asset {}", ino);
+                reply.error(EIO);
                 return;
             }
-            next_offset += 1;
         }
+        if let Err(errno) = self.post_comment(fh) {
+            error!("release: failed to post comment on inode {}", ino);
+            self.handles.remove(fh);
+            reply.error(errno);
+            return;
+        }
+        if let Err(errno) = self.flush_page(fh) {
+            error!("release: failed to write inode {}", ino);
+            self.handles.remove(fh);
+            reply.error(errno);
+            return;
+        }
+        self.handles.remove(fh);
+        reply.ok();
+    }
 
-        // add parent directory entry
-        // if offset <= 1 {
-        // TODO
-        // }
-
-        // add child entries
-        let mut i = 0;
-        for pti in page_tree {
-            if i + 2 <= offset as usize {
-                continue;
-            }
-            let basename = pti.path.split('/').last().unwrap();
-            if pti.is_folder {
-                if reply.add(
-                    pti.id as u64 + 1,
-                    next_offset,
-                    fuser::FileType::Directory,
-                    basename,
-                ) {
-                    debug!("readdir: buffer full at offset {}", next_offset);
-                    reply.ok();
-                    return;
+    /// Create a file node.
+    ///
+    /// # Arguments
+    /// * `req` - The request.
+    /// * `parent` - The parent inode number.
+    /// * `name` - The name of the file.
+    /// * `mode` - The mode of the file.
+    /// * `umask` - The umask of the file.
+    /// * `flags` - The flags of the file.
+    /// * `reply` - The reply.
+    ///
+    /// # Returns
+    /// Nothing.
+    fn mknod(
+        &mut self,
+        _req: &Request<'_>,
+        parent: u64,
+        name: &OsStr,
+        mode: u32,
+        umask: u32,
+        rdev: u32,
+        reply: ReplyEntry,
+    ) {
+        let _timer = OpTimer::start(&self.stats, "mknod");
+        self.renew_login();
+        let start = SystemTime::now();
+        info!(
+            "mknod(parent={}, name={:?}, mode={}, umask={}, rdev={})",
+            parent, name, mode, umask, rdev
+        );
+        if mode & libc::S_IFMT != libc::S_IFREG {
+            warn!("mknod: only regular files can be created");
+            reply.error(EINVAL);
+            return;
+        }
+        // assets are uploaded right away, empty
+        if let Some(Entry::AssetFolder(folder)) = self.inodes.entry(parent) {
+            let result =
+                self.create_asset(parent, name).and_then(|(ino, _)| {
+                    let name = name.to_string_lossy();
+                    self.assets.release(&self.api, folder, &name).map_err(
+                        |_| {
+                            self.stats.api_error();
+                            EIO
+                        },
+                    )?;
+                    self.get_inode(ino).map(Inode::into).ok_or(EIO)
+                });
+            match result {
+                Ok(attr) => {
+                    let ttl = SystemTime::now().duration_since(start).unwrap();
+                    reply.entry(&ttl, &attr, 0);
                 }
-                i += 1;
-                next_offset += 1;
-            }
-            if let Some(pid) = pti.page_id {
-                let filename = format!("{}.md", basename);
-                if reply.add(
-                    pid as u64 | 0x80000000_00000000,
-                    next_offset,
-                    fuser::FileType::RegularFile,
-                    filename,
-                ) {
-                    debug!("readdir: buffer full at offset {}", next_offset);
-                    reply.ok();
-                    return;
+                Err(errno) => {
+                    warn!("mknod: failed to create asset {:?}", name);
+                    reply.error(errno);
                 }
-                i += 1;
-                next_offset += 1;
+            }
+            return;
+        }
+        match self.create_page(parent, name) {
+            Ok(attr) => {
+                let ttl = SystemTime::now().duration_since(start).unwrap();
+                reply.entry(&ttl, &attr, 0);
+            }
+            Err(errno) => {
+                warn!("mknod: failed to create {:?}", name);
+                reply.error(errno);
             }
         }
-
-        reply.ok();
     }
 
-    // Lookup inode by name and parent inode.
-    //
-    // # Arguments
-    // * `req` - The request.
-    // * `parent` - The parent inode number.
-    // * `name` - The name of the inode.
-    // * `reply` - The reply.
-    //
-    // # Returns
-    // Nothing.
-    fn lookup(
+    /// Create a directory.
+    ///
+    /// Folders of the wiki only exist while they hold pages, so the
+    /// directory is kept by the mount until a page is created in it.
+    ///
+    /// # Arguments
+    /// * `req` - The request.
+    /// * `parent` - The parent inode number.
+    /// * `name` - The name of the directory.
+    /// * `mode` - The mode of the directory.
+    /// * `umask` - The umask of the directory.
+    /// * `reply` - The reply.
+    ///
+    /// # Returns
+    /// Nothing.
+    fn mkdir(
         &mut self,
-        _req: &Request,
+        _req: &Request<'_>,
         parent: u64,
         name: &OsStr,
+        mode: u32,
+        umask: u32,
         reply: ReplyEntry,
     ) {
+        let _timer = OpTimer::start(&self.stats, "mkdir");
+        self.renew_login();
         let start = SystemTime::now();
-        let mut name_str = name.to_str().unwrap();
-        info!("lookup(parent={}, name={:?})", parent, name_str);
-        let mut is_dir = true;
-        if name_str.ends_with(".md") {
-            name_str = &name_str[..name_str.len() - 3];
-            is_dir = false;
-        }
+        info!(
+            "mkdir(parent={}, name={:?}, mode={}, umask={})",
+            parent, name, mode, umask
+        );
 
-        let page_tree = match self.get_inode(parent) {
-            Some(Inode::Directory(page_tree)) => page_tree,
+        let name = match name.to_str() {
+            Some(name) if !name.ends_with(".md") => name,
             _ => {
-                warn!("lookup: parent inode {} is not a directory", parent);
-                reply.error(ENOENT);
+                warn!("mkdir: invalid name {:?}", name);
+                reply.error(EINVAL);
+                return;
+            }
+        };
+        let path = match self.entry_path(parent, name) {
+            Ok(path) => path,
+            Err(errno) => {
+                warn!("mkdir: no directory {} to create {} in", parent, name);
+                reply.error(errno);
                 return;
             }
         };
 
-        for pti in page_tree {
-            if pti.path.split('/').last().unwrap() == name_str {
-                let ino = if is_dir {
-                    pti.id as u64 + 1
-                } else {
-                    pti.page_id.unwrap() as u64 | 0x80000000_00000000
-                };
-                debug!("lookup: found inode {}", ino);
-                let attr = match self.get_inode(ino) {
-                    Some(inode) => inode.into(),
-                    None => {
-                        warn!("lookup: inode {} not found", ino);
-                        reply.error(ENOENT);
-                        return;
-                    }
-                };
-                let ttl = SystemTime::now().duration_since(start).unwrap();
-                reply.entry(&ttl, &attr, 0);
+        let (_, wiki_path) = self.wiki_path(&path);
+        let is_folder = match self.get_inode(parent) {
+            Some(Inode::Directory(_, page_tree)) => page_tree
+                .iter()
+                .any(|pti| pti.is_folder && pti.path == wiki_path),
+            _ => false,
+        };
+        if is_folder || self.virtual_dir_ino(&path).is_some() {
+            warn!("mkdir: directory {} exists", path);
+            reply.error(EEXIST);
+            return;
+        }
+
+        let ino = self.inodes.directory(&path);
+        debug!("mkdir: directory {} has inode {}", path, ino);
+        self.virtual_dirs.insert(ino, path);
+        let attr = Inode::VirtualDirectory(ino).into();
+        reply.entry(
+            &SystemTime::now().duration_since(start).unwrap(),
+            &attr,
+            0,
+        );
+    }
+
+    /// Remove a directory, only those made with mkdir that still hold no
+    /// pages can be removed.
+    ///
+    /// # Arguments
+    /// * `req` - The request.
+    /// * `parent` - The parent inode number.
+    /// * `name` - The name of the directory.
+    /// * `reply` - The reply.
+    ///
+    /// # Returns
+    /// Nothing.
+    fn rmdir(
+        &mut self,
+        _req: &Request<'_>,
+        parent: u64,
+        name: &OsStr,
+        reply: ReplyEmpty,
+    ) {
+        let _timer = OpTimer::start(&self.stats, "rmdir");
+        self.renew_login();
+        info!("rmdir(parent={}, name={:?})", parent, name);
+
+        let path = match name.to_str().map(|n| self.entry_path(parent, n)) {
+            Some(Ok(path)) => path,
+            Some(Err(errno)) => {
+                reply.error(errno);
+                return;
+            }
+            None => {
+                reply.error(ENOENT);
                 return;
             }
+        };
+
+        // folders of the wiki always hold pages
+        let (_, wiki_path) = self.wiki_path(&path);
+        let is_folder = match self.get_inode(parent) {
+            Some(Inode::Directory(_, page_tree)) => page_tree
+                .iter()
+                .any(|pti| pti.is_folder && pti.path == wiki_path),
+            _ => false,
+        };
+        if is_folder || !self.virtual_children(&path).is_empty() {
+            warn!("rmdir: directory {} is not empty", path);
+            reply.error(ENOTEMPTY);
+            return;
         }
 
-        warn!("lookup: inode not found");
-        reply.error(ENOENT);
+        match self.virtual_dir_ino(&path) {
+            Some(ino) => {
+                debug!("rmdir: removed directory {}", path);
+                self.virtual_dirs.remove(&ino);
+                reply.ok();
+            }
+            None => {
+                warn!("rmdir: directory {} not found", path);
+                reply.error(ENOENT);
+            }
+        }
     }
 
-    /// Read data from a file.
+    /// Rename or move a file or directory.
+    ///
+    /// Files move their page, directories move all pages below them one by
+    /// one. If one of those moves fails, the moved pages are moved back.
     ///
     /// # Arguments
     /// * `req` - The request.
-    /// * `ino` - The inode number.
-    /// * `fh` - The file handle.
-    /// * `offset` - The offset in the file.
-    /// * `size` - The size of the data to read.
-    /// * `flags` - The flags.
-    /// * `lock_owner` - The lock owner.
+    /// * `parent` - The parent inode number.
+    /// * `name` - The name of the file or directory.
+    /// * `newparent` - The new parent inode number.
+    /// * `newname` - The new name of the file or directory.
+    /// * `flags` - The flags of the rename.
     /// * `reply` - The reply.
     ///
     /// # Returns
     /// Nothing.
-    fn read(
+    fn rename(
         &mut self,
         _req: &Request<'_>,
-        ino: u64,
-        fh: u64,
-        offset: i64,
-        size: u32,
-        flags: i32,
-        lock_owner: Option<u64>,
-        reply: ReplyData,
+        parent: u64,
+        name: &OsStr,
+        newparent: u64,
+        newname: &OsStr,
+        flags: u32,
+        reply: ReplyEmpty,
     ) {
+        let _timer = OpTimer::start(&self.stats, "rename");
+        self.renew_login();
         info!(
-            "read(ino={}, fh={}, offset={}, size={}, flags={:?}, \
-              lock_owner={:?})",
-            ino, fh, offset, size, flags, lock_owner
+            "rename(parent={}, name={:?}, newparent={}, newname={:?}, \
+              flags={})",
+            parent, name, newparent, newname, flags
         );
 
-        if let InodeType::Directory(_) = InodeType::from(ino) {
-            warn!("read: inode {} is a directory", ino);
-            reply.error(EISDIR);
+        let (name, newname) = match (name.to_str(), newname.to_str()) {
+            (Some(name), Some(newname)) => (name, newname),
+            _ => {
+                reply.error(EINVAL);
+                return;
+            }
+        };
+        let is_file = name.ends_with(".md");
+        if is_file != newname.ends_with(".md") {
+            warn!("rename: {} and {} are not both pages", name, newname);
+            reply.error(EINVAL);
             return;
         }
-
-        let page = match self.get_inode(ino) {
-            Some(Inode::Page(page)) => page,
-            _ => {
-                warn!("read: inode {} not found", ino);
-                reply.error(ENOENT);
+        let strip =
+            |name: &str| name.strip_suffix(".md").unwrap_or(name).to_string();
+        let paths = self.entry_path(parent, &strip(name)).and_then(|from| {
+            Ok((from, self.entry_path(newparent, &strip(newname))?))
+        });
+        let (from, to) = match paths {
+            Ok(paths) => paths,
+            Err(errno) => {
+                reply.error(errno);
                 return;
             }
         };
 
-        let content_size = page.content.len() as u64;
+        let moves: Vec<(i64, String, String)> = if is_file {
+            let page_tree = match self.get_inode(parent) {
+                Some(Inode::Directory(_, page_tree)) => page_tree,
+                _ => Vec::new(),
+            };
+            let (_, wiki_from) = self.wiki_path(&from);
+            page_tree
+                .iter()
+                .filter(|pti| pti.path == wiki_from)
+                .filter_map(|pti| pti.page_id)
+                .map(|id| (id, from.clone(), to.clone()))
+                .collect()
+        } else {
+            let (locale, wiki_from) = self.wiki_path(&from);
+            let pages = match self.api.page_list(
+                None,
+                None,
+                None,
+                None,
+                Some(locale.clone()),
+                None,
+                None,
+            ) {
+                Ok(pages) => pages,
+                Err(_) => {
+                    self.stats.api_error();
+                    error!("rename: failed to list the pages below {}", from);
+                    reply.error(EIO);
+                    return;
+                }
+            };
+            let prefix = format!("{}/", wiki_from);
+            pages
+                .into_iter()
+                .filter_map(|page| {
+                    let rest = page.path.strip_prefix(&prefix)?;
+                    Some((
+                        page.id,
+                        self.mount_path(&locale, &page.path),
+                        format!("{}/{}", to, rest),
+                    ))
+                })
+                .collect()
+        };
 
-        if offset < 0 || offset as u64 > content_size {
-            warn!(
-                "read: invalid offset {} for file of size {} with inode {}",
-                offset, size, ino
-            );
+        let is_virtual = !is_file && self.virtual_dir_ino(&from).is_some();
+        if moves.is_empty() && !is_virtual {
+            warn!("rename: {} not found", from);
             reply.error(ENOENT);
             return;
         }
 
-        let end = (offset as u64 + size as u64).min(content_size);
-        let data = page.content[offset as usize..end as usize].to_string();
-        reply.data(data.as_bytes());
+        if let Err(errno) = self.move_pages(&moves) {
+            reply.error(errno);
+            return;
+        }
+
+        // directories keep their inodes, those made with mkdir move along
+        if !is_file {
+            self.inodes.rename(&from, &to);
+            let prefix = format!("{}/", from);
+            for dir in self.virtual_dirs.values_mut() {
+                if *dir == from {
+                    *dir = to.clone();
+                } else if let Some(rest) = dir.strip_prefix(&prefix) {
+                    *dir = format!("{}/{}", to, rest);
+                }
+            }
+        }
+        debug!("rename: moved {} to {}", from, to);
+        reply.ok();
     }
 
-    /// Write data to a file.
+    /// Remove a file, which deletes its page or asset.
     ///
     /// # Arguments
     /// * `req` - The request.
-    /// * `ino` - The inode number.
-    /// * `fh` - The file handle.
-    /// * `offset` - The offset in the file.
-    /// * `data` - The data to write.
-    /// * `write_flags` - The write flags.
-    /// * `flags` - The flags.
-    /// * `lock_owner` - The lock owner.
+    /// * `parent` - The parent inode number.
+    /// * `name` - The name of the file.
     /// * `reply` - The reply.
     ///
     /// # Returns
     /// Nothing.
-    fn write(
+    fn unlink(
         &mut self,
         _req: &Request<'_>,
-        ino: u64,
-        fh: u64,
-        offset: i64,
-        data: &[u8],
-        write_flags: u32,
-        flags: i32,
-        lock_owner: Option<u64>,
-        reply: ReplyWrite,
+        parent: u64,
+        name: &OsStr,
+        reply: ReplyEmpty,
     ) {
-        info!(
-            "write(ino={}, fh={}, offset={}, data={:?}, write_flags={:?}, \
-              flags={:?}, lock_owner={:?})",
-            ino, fh, offset, data, write_flags, flags, lock_owner
-        );
+        let _timer = OpTimer::start(&self.stats, "unlink");
+        self.renew_login();
+        info!("unlink(parent={}, name={:?})", parent, name);
 
-        if let InodeType::Directory(_) = InodeType::from(ino) {
-            warn!("write: inode {} is a directory", ino);
-            reply.error(EISDIR);
+        if !self.allow_delete {
+            warn!("unlink: deleting is disabled");
+            reply.error(EPERM);
             return;
         }
 
-        let mut page = match self.get_inode(ino) {
-            Some(Inode::Page(page)) => page,
-            _ => {
-                warn!("write: inode {} not found", ino);
+        if let Some(Entry::AssetFolder(folder)) = self.inodes.entry(parent) {
+            let name = name.to_str().unwrap_or_default();
+            match self.assets.delete(&self.api, folder, name) {
+                Ok(true) => {
+                    debug!("unlink: deleted asset {}", name);
+                    reply.ok();
+                }
+                Ok(false) => {
+                    warn!("unlink: asset {} not found", name);
+                    reply.error(ENOENT);
+                }
+                Err(_) => {
+                    self.stats.api_error();
+                    error!("unlink: failed to delete asset {}", name);
+                    reply.error(EIO);
+                }
+            }
+            return;
+        }
+
+        let stem = match name.to_str().and_then(|n| n.strip_suffix(".md")) {
+            Some(stem) => stem,
+            None => {
+                warn!("unlink: {:?} is not a page", name);
                 reply.error(ENOENT);
                 return;
             }
         };
 
-        let size = page.content.len() as u64;
-
-        if offset < 0 || offset as u64 > size {
-            warn!(
-                "write: invalid offset {} for file of size {} with inode {}",
-                offset, size, ino
-            );
-            reply.error(EINVAL);
-            return;
-        }
-
-        let end = offset as usize + data.len();
-        if end < page.content.len() && write_flags as i32 == O_TRUNC {
-            debug!(
-                "write: truncating inode {} from {} to {}",
-                ino,
-                page.content.len(),
-                end
-            );
-            page.content.truncate(end);
-        }
+        let page_tree = match self.get_inode(parent) {
+            Some(Inode::Directory(_, page_tree)) => page_tree,
+            Some(_) => {
+                warn!("unlink: parent inode {} is read-only", parent);
+                reply.error(EACCES);
+                return;
+            }
+            None => {
+                warn!("unlink: parent inode {} not found", parent);
+                reply.error(ENOENT);
+                return;
+            }
+        };
 
-        // TODO maybe page content should be mutable, or all fields actually
-        // merge contents
-        let mut content = page.content[..offset as usize].to_string()
-            + &String::from_utf8_lossy(data);
-        if end < page.content.len() {
-            content += &page.content[end..];
-        }
-        debug!("write: inode {} from {} to {}", ino, offset, end);
+        let page_id = page_tree
+            .iter()
+            .filter(|pti| pti.path.split('/').next_back() == Some(stem))
+            .find_map(|pti| pti.page_id);
+        let page_id = match page_id {
+            Some(page_id) => page_id,
+            None => {
+                warn!("unlink: page {} not found", stem);
+                reply.error(ENOENT);
+                return;
+            }
+        };
 
-        match self
-            .page_cache
-            .update_content(&self.api, page.id as u64, content)
-        {
-            Ok(_) => {
-                debug!("write: updated inode {}", ino);
-                reply.written(data.len() as u32);
+        match self.api.page_delete(page_id) {
+            Ok(()) => {
+                debug!("unlink: deleted page {}", page_id);
+                self.invalidate_trees();
+                self.page_cache.evict(page_id as u64);
+                reply.ok();
             }
             Err(_) => {
-                error!("write: failed to update inode {}", ino);
+                self.stats.api_error();
+                error!("unlink: failed to delete page {}", page_id);
                 reply.error(EIO);
             }
         }
     }
 
-    /// Open a file.
-    ///
-    /// # Arguments
-    /// * `req` - The request.
-    /// * `ino` - The inode number.
-    /// * `flags` - The flags of the file.
-    /// * `reply` - The reply.
+    /// Create and open a file.
     ///
-    /// # Returns
-    /// Nothing.
-    // fn open(
-    //     &mut self,
-    //     _req: &Request<'_>,
-    //     ino: u64,
-    //     flags: i32,
-    //     reply: ReplyOpen
-    // ) {
-    //     info!("open(ino={}, flags={:?})", ino, flags);
-    //     reply.opened(0, flags.try_into().unwrap());
-    // }
-
-    /// Create a file node.
+    /// The file becomes a markdown page below the path of the parent
+    /// directory in the locale of the mount, titled after the file name.
+    /// Below `/_assets` it becomes an asset, uploaded when it is flushed.
     ///
     /// # Arguments
     /// * `req` - The request.
@@ -628,22 +2607,47 @@ impl Filesystem for Fs {
     ///
     /// # Returns
     /// Nothing.
-    fn mknod(
+    fn create(
         &mut self,
         _req: &Request<'_>,
         parent: u64,
         name: &OsStr,
         mode: u32,
         umask: u32,
-        rdev: u32,
-        reply: ReplyEntry,
+        flags: i32,
+        reply: ReplyCreate,
     ) {
-        let _start = SystemTime::now();
+        let _timer = OpTimer::start(&self.stats, "create");
+        self.renew_login();
+        let start = SystemTime::now();
         info!(
-            "mknod(parent={}, name={:?}, mode={}, umask={}, rdev={})",
-            parent, name, mode, umask, rdev
+            "create(parent={}, name={:?}, mode={}, umask={}, flags={:?})",
+            parent, name, mode, umask, flags
         );
-        reply.error(EINVAL);
+        if let Some(Entry::AssetFolder(_)) = self.inodes.entry(parent) {
+            match self.create_asset(parent, name) {
+                Ok((_, attr)) => {
+                    let ttl = SystemTime::now().duration_since(start).unwrap();
+                    reply.created(&ttl, &attr, 0, 0, FOPEN_DIRECT_IO);
+                }
+                Err(errno) => {
+                    warn!("create: failed to create asset {:?}", name);
+                    reply.error(errno);
+                }
+            }
+            return;
+        }
+        match self.create_page(parent, name) {
+            Ok(attr) => {
+                let fh = self.open_page(attr.ino, flags).unwrap_or(0);
+                let ttl = SystemTime::now().duration_since(start).unwrap();
+                reply.created(&ttl, &attr, 0, fh, FOPEN_DIRECT_IO);
+            }
+            Err(errno) => {
+                warn!("create: failed to create {:?}", name);
+                reply.error(errno);
+            }
+        }
     }
 }
 
@@ -656,8 +2660,8 @@ struct Cli {
     #[clap(short, long, help = "Wiki.js base URL", env = "WIKI_JS_BASE_URL")]
     url: String,
 
-    #[clap(short, long, help = "Wiki.js API key", env = "WIKI_JS_API_KEY")]
-    key: String,
+    #[clap(flatten)]
+    credentials: CredentialArgs,
 
     #[clap(help = "Mountpoint", env = "WIKI_JS_FUSE_MOUNTPOINT")]
     mountpoint: PathBuf,
@@ -671,12 +2675,207 @@ struct Cli {
     )]
     locale: String,
 
+    #[clap(
+        long,
+        help = "Mount every installed locale as a directory in the root, \
+                instead of the one given with --locale",
+        env = "WIKI_JS_FUSE_ALL_LOCALES"
+    )]
+    all_locales: bool,
+
+    #[clap(
+        long,
+        help = "Refuse to delete pages and assets when their files are \
+                removed",
+        env = "WIKI_JS_FUSE_NO_DELETE"
+    )]
+    no_delete: bool,
+
+    #[clap(
+        long,
+        default_value = "10",
+        help = "Seconds to keep the page tree before fetching it again, 0 \
+                fetches every directory when it is read",
+        env = "WIKI_JS_FUSE_TREE_TTL"
+    )]
+    tree_ttl: u64,
+
+    #[clap(
+        long,
+        help = "Show the title, description, tags and publishing state of \
+                pages as YAML front matter in their files, and update them \
+                when a file is written",
+        env = "WIKI_JS_FUSE_FRONTMATTER"
+    )]
+    frontmatter: bool,
+
+    #[clap(
+        long,
+        help = "Post text appended to the .comments/<page>.md files as a \
+                new comment on the page",
+        env = "WIKI_JS_FUSE_POST_COMMENTS"
+    )]
+    post_comments: bool,
+
+    #[clap(
+        long,
+        help = "Keep the directory and page inodes in a file next to the \
+                mountpoint, so they stay the same across mounts",
+        env = "WIKI_JS_FUSE_PERSIST_INODES"
+    )]
+    persist_inodes: bool,
+
+    #[clap(
+        short,
+        long,
+        help = "Fork to the background once the wiki is reachable",
+        env = "WIKI_JS_FUSE_DAEMON"
+    )]
+    daemon: bool,
+
+    #[clap(
+        long,
+        help = "Unmount automatically when the process exits, even if it \
+                is killed",
+        env = "WIKI_JS_FUSE_AUTO_UNMOUNT"
+    )]
+    auto_unmount: bool,
+
+    #[clap(
+        long,
+        help = "Allow other users to access the mount, which needs \
+                user_allow_other in /etc/fuse.conf",
+        env = "WIKI_JS_FUSE_ALLOW_OTHER"
+    )]
+    allow_other: bool,
+
     #[command(flatten)]
     verbose: clap_verbosity_flag::Verbosity,
 }
 
+/// Block SIGINT and SIGTERM for the calling thread and all threads spawned
+/// by it afterwards, so they are only received by `unmount_on_signal`, and
+/// SIGUSR1 the same way for `stats::dump_on_sigusr1`. This has to happen
+/// before any other thread is spawned, including the one of the HTTP
+/// client. Returns the set of the termination signals.
+fn block_termination_signals() -> libc::sigset_t {
+    // SAFETY: the signal sets are initialized by sigemptyset before use and
+    // only passed to the signal functions by reference.
+    unsafe {
+        let mut set: libc::sigset_t = std::mem::zeroed();
+        libc::sigemptyset(&mut set);
+        libc::sigaddset(&mut set, libc::SIGINT);
+        libc::sigaddset(&mut set, libc::SIGTERM);
+        let mut blocked = set;
+        libc::sigaddset(&mut blocked, libc::SIGUSR1);
+        if libc::pthread_sigmask(
+            libc::SIG_BLOCK,
+            &blocked,
+            std::ptr::null_mut(),
+        ) != 0
+        {
+            warn!("block_termination_signals: failed to block signals");
+        }
+        set
+    }
+}
+
+/// Unmount once one of the blocked termination signals is received, which
+/// ends the session and lets the process exit cleanly.
+fn unmount_on_signal(set: libc::sigset_t, mut unmounter: SessionUnmounter) {
+    thread::spawn(move || {
+        let mut signal = 0;
+        // SAFETY: set is a valid, initialized signal set.
+        while unsafe { libc::sigwait(&set, &mut signal) } != 0 {}
+        info!("unmount_on_signal: received signal {}, unmounting", signal);
+        if let Err(error) = unmounter.unmount() {
+            error!("unmount_on_signal: failed to unmount: {}", error);
+        }
+    });
+}
+
+/// Fork to the background: the parent exits, the child continues in a new
+/// session with its standard streams redirected to `/dev/null`.
+fn daemonize() {
+    // SAFETY: no other thread is running at this point, so the child
+    // starts in a consistent state.
+    match unsafe { libc::fork() } {
+        -1 => {
+            error!("daemonize: failed to fork");
+            exit(1);
+        }
+        0 => {}
+        _ => exit(0),
+    }
+    // SAFETY: plain system calls on a valid path and the descriptors of
+    // the process.
+    unsafe {
+        libc::setsid();
+        let null = libc::open(c"/dev/null".as_ptr(), libc::O_RDWR);
+        if null >= 0 {
+            for fd in 0..3 {
+                libc::dup2(null, fd);
+            }
+            if null > 2 {
+                libc::close(null);
+            }
+        }
+    }
+}
+
+// the API key, or the JWT of a login with username and password, asking
+// for the TFA security code if needed, and whether the login can be renewed
+// without asking for it again
+fn authenticate(url: &str, credentials: &CredentialArgs) -> (String, bool) {
+    match (&credentials.key, &credentials.username) {
+        (Some(key), _) => (key.clone(), false),
+        (None, Some(_)) => {
+            let mut asked = false;
+            let jwt = credentials::login_with(url, credentials, || {
+                asked = true;
+                credentials::prompt_hidden("TFA security code: ")
+            })
+            .unwrap_or_else(|error| {
+                error!("Login failed: {}", error);
+                exit(1);
+            });
+            (jwt, !asked)
+        }
+        (None, None) => {
+            error!("No credentials, pass an API key or username and password");
+            exit(1);
+        }
+    }
+}
+
+// the file the inode table of the mountpoint is kept in, a hidden one next
+// to it named after it
+fn inode_file(mountpoint: &Path) -> PathBuf {
+    let mountpoint = mountpoint.canonicalize().unwrap_or_else(|error| {
+        error!("{}", error);
+        exit(1);
+    });
+    let name = mountpoint
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    mountpoint
+        .parent()
+        .unwrap_or(&mountpoint)
+        .join(format!(".{}.inodes", name))
+}
+
+fn connect(url: &str, key: &str) -> Api {
+    let credentials = Credentials::Key(key.to_string());
+    Api::new(url.to_string(), credentials).unwrap_or_else(|error| {
+        error!("{}", error);
+        exit(1);
+    })
+}
+
 fn main() {
     let cli = Cli::parse();
+    let signals = block_termination_signals();
     stderrlog::new()
         .module(module_path!())
         .verbosity(cli.verbose.log_level_filter())
@@ -698,16 +2897,109 @@ fn main() {
         exit(1);
     }
 
-    let credentials = Credentials::Key(cli.key);
-    let api = Api::new(cli.url, credentials).unwrap_or_else(|error| {
-        error!("{}", error);
-        exit(1);
-    });
-    let fs = Fs::new(api, cli.locale);
+    let inodes = match cli.persist_inodes {
+        true => {
+            let file = inode_file(&cli.mountpoint);
+            inode::InodeTable::load(file.clone()).unwrap_or_else(|error| {
+                error!(
+                    "Failed to load the inode table {}: {}",
+                    file.display(),
+                    error
+                );
+                exit(1);
+            })
+        }
+        false => inode::InodeTable::new(),
+    };
+
+    let credentials = credentials::with_password(&cli.credentials)
+        .unwrap_or_else(|error| {
+            error!("{}", error);
+            exit(1);
+        });
+    let (key, renewable) = authenticate(&cli.url, &credentials);
+    if credentials.key.is_none() && !renewable {
+        warn!(
+            "The account requires a TFA security code, so the login is not \
+             renewed and the mount stops working when it expires, pass \
+             --totp-secret to renew it"
+        );
+    }
+    let api = connect(&cli.url, &key);
+    let locales = match cli.all_locales {
+        true => api
+            .locale_list()
+            .unwrap_or_else(|error| {
+                error!("{}", error);
+                exit(1);
+            })
+            .into_iter()
+            .filter(|locale| locale.is_installed)
+            .map(|locale| locale.code)
+            .collect(),
+        false => vec![cli.locale],
+    };
+
+    // fail in the foreground if the wiki is not reachable
+    if let Some(locale) = locales.first() {
+        if let Err(error) =
+            api.page_tree_get(0, PageTreeMode::ALL, false, locale.clone())
+        {
+            error!("Health check failed: {}", error);
+            exit(1);
+        }
+    }
+
+    // the thread of the HTTP client does not survive the fork, so the
+    // child connects again
+    let api = match cli.daemon {
+        true => {
+            drop(api);
+            daemonize();
+            connect(&cli.url, &key)
+        }
+        false => api,
+    };
+
+    let fs = Fs::new(
+        api,
+        locales,
+        cli.all_locales,
+        !cli.no_delete,
+        Duration::from_secs(cli.tree_ttl),
+    )
+    .with_inode_table(inodes);
+    let fs = match cli.frontmatter {
+        true => fs.with_frontmatter(),
+        false => fs,
+    };
+    let fs = match cli.post_comments {
+        true => fs.with_comment_posting(),
+        false => fs,
+    };
+    let fs = match renewable {
+        true => fs.with_login(cli.url.clone(), credentials, &key),
+        false => fs,
+    };
+    let stats = Arc::clone(&fs.stats);
 
-    mount2(fs, &cli.mountpoint, &[FSName("wikijs-fuse".to_string())])
+    let mut options = vec![FSName("wikijs-fuse".to_string())];
+    if cli.auto_unmount {
+        options.push(AutoUnmount);
+    }
+    if cli.allow_other {
+        options.push(AllowOther);
+    }
+    let mut session = Session::new(fs, &cli.mountpoint, &options)
         .unwrap_or_else(|error| {
             error!("{}", error);
             exit(1);
         });
+    unmount_on_signal(signals, session.unmount_callable());
+    stats::dump_on_sigusr1(stats);
+
+    session.run().unwrap_or_else(|error| {
+        error!("{}", error);
+        exit(1);
+    });
 }
@@ -1,15 +1,82 @@
+use crate::stats::Stats;
 use std::collections::HashMap;
-use wikijs::{page::PageError, page::PageMinimal, Api};
+use std::sync::Arc;
+use wikijs::batch::{BatchRequest, BatchResponse};
+use wikijs::page::{
+    parse_frontmatter_markdown, Page, PageAttributes, PageError,
+    PageFrontMatter, PageMinimal, PageVersion,
+};
+use wikijs::Api;
 
 pub(crate) struct PageCache {
     pages: HashMap<u64, PageMinimal>,
+    // attributes fetched ahead of the getattr calls after a readdir, each
+    // served once
+    prefetched: HashMap<u64, PageAttributes>,
+    // versions never change, so they are kept by their id
+    versions: HashMap<i64, PageVersion>,
+    // whether page files hold the metadata as front matter
+    frontmatter: bool,
+    stats: Arc<Stats>,
+}
+
+/// Attributes of a page whose content is already at hand.
+pub(crate) fn page_attributes(page: &PageMinimal) -> PageAttributes {
+    PageAttributes {
+        id: page.id,
+        path: page.path.clone(),
+        created_at: page.created_at.clone(),
+        updated_at: page.updated_at.clone(),
+        locale: page.locale.clone(),
+    }
+}
+
+/// A page as a file, with its title, description, tags and publishing
+/// state as YAML front matter above the content.
+fn page_file(page: Page) -> PageMinimal {
+    let front_matter = PageFrontMatter {
+        title: Some(page.title),
+        description: Some(page.description),
+        tags: Some(page.tags.into_iter().flatten().map(|t| t.tag).collect()),
+        is_published: Some(page.is_published),
+        ..Default::default()
+    };
+    // the front matter only contains strings, booleans and a list
+    let yaml = serde_yaml::to_string(&front_matter).unwrap();
+    PageMinimal {
+        id: page.id,
+        path: page.path,
+        content: format!("---\n{}---\n{}", yaml, page.content),
+        created_at: page.created_at,
+        updated_at: page.updated_at,
+        editor: page.editor,
+        locale: page.locale,
+    }
 }
 
 #[allow(unused)]
 impl PageCache {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(stats: Arc<Stats>) -> Self {
         Self {
             pages: HashMap::new(),
+            prefetched: HashMap::new(),
+            versions: HashMap::new(),
+            frontmatter: false,
+            stats,
+        }
+    }
+
+    /// Hold the metadata of pages as front matter in the content, and
+    /// update it from there when the content is written.
+    pub(crate) fn with_frontmatter(mut self) -> Self {
+        self.frontmatter = true;
+        self
+    }
+
+    fn fetch(&self, api: &Api, id: u64) -> Result<PageMinimal, PageError> {
+        match self.frontmatter {
+            true => api.page_get(id as i64).map(page_file),
+            false => api.page_get_minimal(id as i64),
         }
     }
 
@@ -21,21 +88,88 @@ impl PageCache {
         if let Some(page) = self.pages.get(&id) {
             let updated_at = api.page_get_updated_at(id as i64)?;
             if updated_at != page.updated_at {
-                let page = api.page_get_minimal(id as i64)?;
+                self.stats.cache_miss();
+                let page = self.fetch(api, id)?;
                 self.pages.insert(id, page.clone());
                 Ok(page)
             } else {
+                self.stats.cache_hit();
                 Ok(page.clone())
             }
         } else {
-            let page = api.page_get_minimal(id as i64)?;
+            self.stats.cache_miss();
+            let page = self.fetch(api, id)?;
             self.pages.insert(id, page.clone());
             Ok(page)
         }
     }
 
+    /// Get the attributes of a page without its content, and the size of
+    /// the content if it is cached and up to date, 0 otherwise.
+    pub(crate) fn attributes(
+        &mut self,
+        api: &Api,
+        id: u64,
+    ) -> Result<(PageAttributes, u64), PageError> {
+        let attributes = match self.prefetched.remove(&id) {
+            Some(attributes) => {
+                self.stats.cache_hit();
+                attributes
+            }
+            None => api.page_get_attributes(id as i64)?,
+        };
+        let size = self
+            .pages
+            .get(&id)
+            .filter(|page| page.updated_at == attributes.updated_at)
+            .map(|page| page.content.len() as u64)
+            .unwrap_or(0);
+        Ok((attributes, size))
+    }
+
+    /// Average size of the cached page contents, if any are cached.
+    pub(crate) fn average_size(&self) -> Option<u64> {
+        let total: usize = self.pages.values().map(|p| p.content.len()).sum();
+        (total as u64).checked_div(self.pages.len() as u64)
+    }
+
+    /// Fetch the attributes of all pages among `ids` in a single batched
+    /// request.
+    pub(crate) fn prefetch(&mut self, api: &Api, ids: &[u64]) {
+        if ids.is_empty() {
+            return;
+        }
+        let request = ids.iter().fold(BatchRequest::new(), |r, id| {
+            r.page_get_attributes(*id as i64)
+        });
+        for (id, response) in ids.iter().zip(api.batch_send(request)) {
+            if let BatchResponse::PageGetAttributes(Ok(attributes)) = response {
+                self.stats.cache_miss();
+                self.prefetched.insert(*id, attributes);
+            }
+        }
+    }
+
+    /// Get a previous version of a page.
+    pub(crate) fn version(
+        &mut self,
+        api: &Api,
+        id: u64,
+        version: i64,
+    ) -> Result<PageVersion, PageError> {
+        if let Some(cached) = self.versions.get(&version) {
+            self.stats.cache_hit();
+            return Ok(cached.clone());
+        }
+        self.stats.cache_miss();
+        let fetched = api.page_version_get(id as i64, version)?;
+        self.versions.insert(version, fetched.clone());
+        Ok(fetched)
+    }
+
     pub(crate) fn evict(&mut self, id: u64) {
         self.pages.remove(&id);
+        self.prefetched.remove(&id);
     }
 
     pub(crate) fn refetch(
@@ -44,7 +178,7 @@ impl PageCache {
         id: u64,
     ) -> Result<PageMinimal, PageError> {
         self.pages.remove(&id);
-        let page = api.page_get_minimal(id as i64)?;
+        let page = self.fetch(api, id)?;
         self.pages.insert(id, page.clone());
         Ok(page)
     }
@@ -54,9 +188,30 @@ impl PageCache {
         api: &Api,
         id: u64,
         content: String,
-    ) -> Result<(), PageError> {
-        api.page_update_content(id as i64, content)?;
-        self.refetch(api, id)?;
-        Ok(())
+    ) -> Result<PageMinimal, PageError> {
+        if !self.frontmatter {
+            api.page_update_content(id as i64, content)?;
+            return self.refetch(api, id);
+        }
+        let (front_matter, content) = parse_frontmatter_markdown(&content)?;
+        api.page_update(
+            id as i64,
+            Some(content),
+            front_matter.description,
+            None,
+            None,
+            front_matter.is_published,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            front_matter
+                .tags
+                .map(|tags| tags.into_iter().map(Some).collect()),
+            front_matter.title,
+        )?;
+        self.refetch(api, id)
     }
 }
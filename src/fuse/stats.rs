@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+
+/// Runtime counters of a mount.
+pub(crate) struct Stats {
+    started: Instant,
+    ops: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    api_errors: AtomicU64,
+    latency_us: AtomicU64,
+    ops_by_name: Mutex<BTreeMap<&'static str, u64>>,
+}
+
+impl Stats {
+    pub(crate) fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            ops: AtomicU64::new(0),
+            cache_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
+            api_errors: AtomicU64::new(0),
+            latency_us: AtomicU64::new(0),
+            ops_by_name: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub(crate) fn cache_hit(&self) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn cache_miss(&self) {
+        self.cache_misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn api_error(&self) {
+        self.api_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_op(&self, name: &'static str, latency: Duration) {
+        self.ops.fetch_add(1, Ordering::Relaxed);
+        self.latency_us
+            .fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+        if let Ok(mut ops_by_name) = self.ops_by_name.lock() {
+            *ops_by_name.entry(name).or_insert(0) += 1;
+        }
+    }
+
+    /// Render the counters as `key: value` lines.
+    pub(crate) fn render(&self) -> String {
+        let ops = self.ops.load(Ordering::Relaxed);
+        let latency_us = self.latency_us.load(Ordering::Relaxed);
+        let mut out = String::new();
+        out += &format!("uptime_s: {}\n", self.started.elapsed().as_secs());
+        out += &format!("ops_served: {}\n", ops);
+        out += &format!(
+            "cache_hits: {}\n",
+            self.cache_hits.load(Ordering::Relaxed)
+        );
+        out += &format!(
+            "cache_misses: {}\n",
+            self.cache_misses.load(Ordering::Relaxed)
+        );
+        out += &format!(
+            "api_errors: {}\n",
+            self.api_errors.load(Ordering::Relaxed)
+        );
+        out += &format!(
+            "average_latency_us: {}\n",
+            latency_us.checked_div(ops).unwrap_or(0)
+        );
+        if let Ok(ops_by_name) = self.ops_by_name.lock() {
+            for (name, count) in ops_by_name.iter() {
+                out += &format!("ops.{}: {}\n", name, count);
+            }
+        }
+        out
+    }
+}
+
+/// Records one served operation and its latency when dropped.
+pub(crate) struct OpTimer {
+    stats: Arc<Stats>,
+    name: &'static str,
+    start: Instant,
+}
+
+impl OpTimer {
+    pub(crate) fn start(stats: &Arc<Stats>, name: &'static str) -> Self {
+        Self {
+            stats: Arc::clone(stats),
+            name,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for OpTimer {
+    fn drop(&mut self) {
+        self.stats.record_op(self.name, self.start.elapsed());
+    }
+}
+
+/// Log the counters at info level whenever the process receives SIGUSR1.
+///
+/// SIGUSR1 has to be blocked in all threads already, which
+/// `block_termination_signals` does before any thread is spawned, so only
+/// the thread waiting for it here receives it.
+pub(crate) fn dump_on_sigusr1(stats: Arc<Stats>) {
+    // SAFETY: the signal set is initialized by sigemptyset before use and
+    // only passed to the signal functions by reference.
+    let set = unsafe {
+        let mut set: libc::sigset_t = std::mem::zeroed();
+        libc::sigemptyset(&mut set);
+        libc::sigaddset(&mut set, libc::SIGUSR1);
+        set
+    };
+    thread::spawn(move || loop {
+        let mut signal = 0;
+        // SAFETY: set is a valid, initialized signal set.
+        if unsafe { libc::sigwait(&set, &mut signal) } == 0
+            && signal == libc::SIGUSR1
+        {
+            info!("stats:\n{}", stats.render().trim_end());
+        }
+    });
+}
@@ -0,0 +1,112 @@
+use crate::stats::Stats;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use wikijs::batch::{BatchRequest, BatchResponse};
+use wikijs::page::{PageError, PageTreeItem, PageTreeMode};
+use wikijs::Api;
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+
+// number of folders asked for in one batched request
+const BATCH_SIZE: usize = 50;
+
+/// The page tree of a locale, fetched as a whole and kept for a time to
+/// live, so listing and looking up entries does not ask the wiki for every
+/// directory.
+pub(crate) struct TreeCache {
+    ttl: Duration,
+    // children of each folder by its tree id, 0 for the root
+    children: HashMap<i64, Vec<PageTreeItem>>,
+    fetched_at: Option<Instant>,
+    stats: Arc<Stats>,
+}
+
+impl TreeCache {
+    /// A time to live of zero asks the wiki for each directory on every
+    /// call instead.
+    pub(crate) fn new(ttl: Duration, stats: Arc<Stats>) -> Self {
+        Self {
+            ttl,
+            children: HashMap::new(),
+            fetched_at: None,
+            stats,
+        }
+    }
+
+    /// Children of the folder with the tree id, or of the root for 0.
+    pub(crate) fn children(
+        &mut self,
+        api: &Api,
+        locale: &str,
+        id: i64,
+    ) -> Result<Vec<PageTreeItem>, PageError> {
+        if self.ttl.is_zero() {
+            self.stats.cache_miss();
+            return api.page_tree_get(
+                id,
+                PageTreeMode::ALL,
+                false,
+                locale.to_string(),
+            );
+        }
+        match self.fetched_at {
+            Some(fetched_at) if fetched_at.elapsed() < self.ttl => {
+                self.stats.cache_hit();
+            }
+            _ => {
+                self.stats.cache_miss();
+                self.fetch(api, locale)?;
+            }
+        }
+        match self.children.get(&id) {
+            Some(children) => Ok(children.clone()),
+            None if id == 0 => Ok(Vec::new()),
+            None => Err(PageError::PageNotFound),
+        }
+    }
+
+    /// Drop the tree, so the next call fetches it again.
+    pub(crate) fn invalidate(&mut self) {
+        debug!("tree cache: invalidated");
+        self.fetched_at = None;
+    }
+
+    // fetch the tree level by level, with one batched request for all
+    // folders of a level
+    fn fetch(&mut self, api: &Api, locale: &str) -> Result<(), PageError> {
+        let mut children = HashMap::new();
+        let mut level = vec![0];
+        while !level.is_empty() {
+            let mut next = Vec::new();
+            for ids in level.chunks(BATCH_SIZE) {
+                let request = ids.iter().fold(BatchRequest::new(), |r, id| {
+                    r.page_tree_get(
+                        *id,
+                        PageTreeMode::ALL,
+                        false,
+                        locale.to_string(),
+                    )
+                });
+                for (id, response) in ids.iter().zip(api.batch_send(request)) {
+                    if let BatchResponse::PageTreeGet(items) = response {
+                        let items = items?;
+                        next.extend(
+                            items
+                                .iter()
+                                .filter(|pti| pti.is_folder)
+                                .map(|pti| pti.id),
+                        );
+                        children.insert(*id, items);
+                    }
+                }
+            }
+            level = next;
+        }
+        debug!("tree cache: fetched {} folders", children.len());
+        self.children = children;
+        self.fetched_at = Some(Instant::now());
+        Ok(())
+    }
+}
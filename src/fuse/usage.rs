@@ -0,0 +1,79 @@
+use crate::stats::Stats;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use wikijs::asset::AssetKind;
+use wikijs::Api;
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+
+/// Size assumed for pages while none of their contents are cached.
+const PAGE_SIZE_ESTIMATE: u64 = 4096;
+
+/// Number and size of the pages and assets in the wiki.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Usage {
+    pub pages: u64,
+    pub assets: u64,
+    pub asset_bytes: u64,
+}
+
+impl Usage {
+    /// Estimated size of the wiki, the pages counted with the given average
+    /// size as their content is not listed.
+    pub(crate) fn total_bytes(&self, average_page_size: Option<u64>) -> u64 {
+        let page_size = average_page_size.unwrap_or(PAGE_SIZE_ESTIMATE);
+        self.pages * page_size + self.asset_bytes
+    }
+}
+
+/// The usage of the wiki kept for a time to live, as `df` and file managers
+/// ask for it often and listing all pages and assets is expensive.
+pub(crate) struct UsageCache {
+    ttl: Duration,
+    usage: Option<(Instant, Usage)>,
+    stats: Arc<Stats>,
+}
+
+impl UsageCache {
+    pub(crate) fn new(ttl: Duration, stats: Arc<Stats>) -> Self {
+        Self {
+            ttl,
+            usage: None,
+            stats,
+        }
+    }
+
+    /// Usage of the pages in the locale, all locales for `None`, and of all
+    /// assets.
+    pub(crate) fn get(
+        &mut self,
+        api: &Api,
+        locale: Option<String>,
+    ) -> Result<Usage, Box<dyn std::error::Error>> {
+        if let Some((fetched_at, usage)) = self.usage {
+            if fetched_at.elapsed() < self.ttl {
+                self.stats.cache_hit();
+                return Ok(usage);
+            }
+        }
+        self.stats.cache_miss();
+        let pages =
+            api.page_list(None, None, None, None, locale, None, None)?;
+        let assets = api.asset_list_recursive(0, AssetKind::ALL)?;
+        let usage = Usage {
+            pages: pages.len() as u64,
+            assets: assets.len() as u64,
+            asset_bytes: assets
+                .iter()
+                .map(|item| item.asset.file_size.max(0) as u64)
+                .sum(),
+        };
+        debug!(
+            "usage cache: {} pages and {} assets",
+            usage.pages, usage.assets
+        );
+        self.usage = Some((Instant::now(), usage));
+        Ok(usage)
+    }
+}
@@ -1,12 +1,11 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::common::{
-    classify_response_error, classify_response_status_error, Boolean,
-    KeyValuePair, KeyValuePairInput, KnownErrorCodes, ResponseStatus,
-    UnknownError,
+    classify_post_error, classify_response_error,
+    classify_response_status_error, post_graphql, Boolean, KeyValuePair,
+    KeyValuePairInput, KnownErrorCodes, ResponseStatus, UnknownError,
 };
 
 #[derive(Clone, Debug, Error, PartialEq)]
@@ -15,6 +14,8 @@ pub enum AnalyticsError {
     UnknownErrorCode { code: i64, message: String },
     #[error("Unknown response error: {message}")]
     UnknownErrorMessage { message: String },
+    #[error("Request failed: {message}")]
+    RequestFailed { message: String },
     #[error("Unknown response error.")]
     UnknownError,
 }
@@ -35,6 +36,9 @@ impl UnknownError for AnalyticsError {
     fn unknown_error_message(message: String) -> Self {
         AnalyticsError::UnknownErrorMessage { message }
     }
+    fn request_failed(message: String) -> Self {
+        AnalyticsError::RequestFailed { message }
+    }
     fn unknown_error() -> Self {
         AnalyticsError::UnknownError
     }
@@ -50,7 +54,7 @@ impl KnownErrorCodes for AnalyticsError {
     }
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct AnalyticsProvider {
     #[serde(rename = "isEnabled")]
     pub is_enabled: Boolean,
@@ -73,6 +77,160 @@ pub struct AnalyticsProviderInput {
     pub config: Option<Vec<Option<KeyValuePairInput>>>,
 }
 
+impl AnalyticsProvider {
+    /// The current config options of the provider as `(key, value)` pairs.
+    pub fn config_values(&self) -> Vec<(String, serde_json::Value)> {
+        self.config
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|pair| (pair.key.clone(), pair.config_value()))
+            .collect()
+    }
+
+    /// The provider as input for an update, keeping its current settings.
+    pub fn to_input(&self) -> AnalyticsProviderInput {
+        AnalyticsProviderInput {
+            is_enabled: self.is_enabled,
+            key: self.key.clone(),
+            config: Some(
+                self.config_values()
+                    .into_iter()
+                    .map(|(key, value)| {
+                        Some(KeyValuePairInput::config(&key, value))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl AnalyticsProviderInput {
+    /// Set a config option, replacing an existing one with the same key.
+    ///
+    /// # Arguments
+    /// * `key` - The key of the option.
+    /// * `value` - The new value.
+    pub fn set_config(&mut self, key: &str, value: serde_json::Value) {
+        let config = self.config.get_or_insert_with(Vec::new);
+        config.retain(|p| p.as_ref().is_none_or(|p| p.key != key));
+        config.push(Some(KeyValuePairInput::config(key, value)));
+    }
+}
+
+/// Typed configuration of an analytics provider.
+pub trait AnalyticsProviderConfig {
+    /// Key of the provider, like `google`.
+    fn key(&self) -> &'static str;
+
+    /// The config options as `(key, value)` pairs.
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)>;
+
+    /// An enabled provider with this config.
+    fn to_provider_input(&self) -> AnalyticsProviderInput {
+        AnalyticsProviderInput {
+            is_enabled: true,
+            key: self.key().to_string(),
+            config: Some(
+                self.options()
+                    .into_iter()
+                    .map(|(key, value)| {
+                        Some(KeyValuePairInput::config(key, value))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Config of the `google` provider for Google Analytics.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GoogleAnalyticsConfig {
+    /// The measurement or tracking ID, like `G-XXXXXXXXXX`.
+    pub property_tracking_id: String,
+}
+
+impl GoogleAnalyticsConfig {
+    pub fn new(property_tracking_id: impl Into<String>) -> Self {
+        Self {
+            property_tracking_id: property_tracking_id.into(),
+        }
+    }
+}
+
+impl AnalyticsProviderConfig for GoogleAnalyticsConfig {
+    fn key(&self) -> &'static str {
+        "google"
+    }
+
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)> {
+        vec![(
+            "propertyTrackingId",
+            self.property_tracking_id.clone().into(),
+        )]
+    }
+}
+
+/// Config of the `matomo` provider.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatomoConfig {
+    pub site_id: i64,
+    /// Base URL of the Matomo server.
+    pub server_host: String,
+}
+
+impl MatomoConfig {
+    pub fn new(site_id: i64, server_host: impl Into<String>) -> Self {
+        Self {
+            site_id,
+            server_host: server_host.into(),
+        }
+    }
+}
+
+impl AnalyticsProviderConfig for MatomoConfig {
+    fn key(&self) -> &'static str {
+        "matomo"
+    }
+
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)> {
+        vec![
+            ("siteId", self.site_id.into()),
+            ("serverHost", self.server_host.clone().into()),
+        ]
+    }
+}
+
+/// Config of the `umami` provider.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UmamiConfig {
+    pub website_id: String,
+    /// Base URL of the Umami server.
+    pub url: String,
+}
+
+impl UmamiConfig {
+    pub fn new(website_id: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            website_id: website_id.into(),
+            url: url.into(),
+        }
+    }
+}
+
+impl AnalyticsProviderConfig for UmamiConfig {
+    fn key(&self) -> &'static str {
+        "umami"
+    }
+
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)> {
+        vec![
+            ("websiteID", self.website_id.clone().into()),
+            ("url", self.url.clone().into()),
+        ]
+    }
+}
+
 pub mod analytics_provider_list {
     use super::*;
 
@@ -84,12 +242,12 @@ pub mod analytics_provider_list {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub analytics: Option<Analytics>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Analytics {
         pub providers: Option<Vec<Option<AnalyticsProvider>>>,
     }
@@ -119,9 +277,7 @@ pub fn analytics_provider_list(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(AnalyticsError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -149,18 +305,18 @@ pub mod analytics_provider_update {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub analytics: Option<Analytics>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Analytics {
         #[serde(rename = "updateProviders")]
         pub update_providers: Option<UpdateProviders>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct UpdateProviders {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -194,9 +350,7 @@ pub fn analytics_provider_update(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(AnalyticsError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
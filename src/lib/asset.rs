@@ -1,12 +1,13 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt::Display;
 use thiserror::Error;
 
 use crate::common::{
-    classify_response_error, classify_response_status_error, Date, Int,
-    KnownErrorCodes, ResponseStatus, UnknownError,
+    base_url, classify_post_error, classify_response_error,
+    classify_response_status_error, post_graphql, Date, Int, KnownErrorCodes,
+    ResponseStatus, UnknownError,
 };
 
 #[derive(Clone, Error, Debug, PartialEq)]
@@ -37,6 +38,8 @@ pub enum AssetError {
     UnknownErrorCode { code: i64, message: String },
     #[error("Unknown response error: {message}")]
     UnknownErrorMessage { message: String },
+    #[error("Request failed: {message}")]
+    RequestFailed { message: String },
     #[error("Unknown response error.")]
     UnknownError,
 }
@@ -68,6 +71,9 @@ impl UnknownError for AssetError {
     fn unknown_error_message(message: String) -> Self {
         AssetError::UnknownErrorMessage { message }
     }
+    fn request_failed(message: String) -> Self {
+        AssetError::RequestFailed { message }
+    }
     fn unknown_error() -> Self {
         AssetError::UnknownError
     }
@@ -83,7 +89,7 @@ impl KnownErrorCodes for AssetError {
     }
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct AssetItem {
     pub id: Int,
     pub filename: String,
@@ -101,13 +107,110 @@ pub struct AssetItem {
     pub author: Option<Int>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+/// An asset annotated with the path of the folder it is stored in.
+#[derive(Clone, Debug, Serialize)]
+pub struct AssetItemWithPath {
+    /// Slash separated folder slugs relative to the listed root folder,
+    /// empty for assets directly in it.
+    pub folder_path: String,
+    pub asset: AssetItem,
+}
+
+impl AssetItemWithPath {
+    /// The path of the asset itself, i.e. folder path and filename.
+    pub fn path(&self) -> String {
+        if self.folder_path.is_empty() {
+            self.asset.filename.clone()
+        } else {
+            format!("{}/{}", self.folder_path, self.asset.filename)
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct AssetFolder {
     pub id: Int,
     pub slug: String,
     pub name: Option<String>,
 }
 
+/// The complete asset folder hierarchy, resolving folder paths to ids and
+/// back.
+///
+/// Paths are the slash separated slugs of the folders, the root folder with
+/// id 0 has the empty path.
+#[derive(Clone, Debug, Default)]
+pub struct AssetFolderTree {
+    // folder id to parent folder id and folder
+    folders: HashMap<Int, (Int, AssetFolder)>,
+}
+
+impl AssetFolderTree {
+    /// Build a tree from `(parent_folder_id, folder)` pairs.
+    pub fn new(folders: Vec<(Int, AssetFolder)>) -> Self {
+        Self {
+            folders: folders
+                .into_iter()
+                .map(|(parent, folder)| (folder.id, (parent, folder)))
+                .collect(),
+        }
+    }
+
+    /// Get a folder by its id.
+    pub fn get(&self, id: Int) -> Option<&AssetFolder> {
+        self.folders.get(&id).map(|(_, folder)| folder)
+    }
+
+    /// Get the id of the parent of a folder.
+    pub fn parent(&self, id: Int) -> Option<Int> {
+        self.folders.get(&id).map(|(parent, _)| *parent)
+    }
+
+    /// List the direct subfolders of a folder.
+    pub fn children(&self, id: Int) -> Vec<&AssetFolder> {
+        let mut children: Vec<&AssetFolder> = self
+            .folders
+            .values()
+            .filter(|(parent, _)| *parent == id)
+            .map(|(_, folder)| folder)
+            .collect();
+        children.sort_by(|a, b| a.slug.cmp(&b.slug));
+        children
+    }
+
+    /// Resolve a folder path like `images/screenshots` to the folder id.
+    ///
+    /// Leading and trailing slashes are ignored.
+    pub fn resolve(&self, path: &str) -> Option<Int> {
+        path.split('/').filter(|slug| !slug.is_empty()).try_fold(
+            0,
+            |id, slug| {
+                self.children(id)
+                    .into_iter()
+                    .find(|folder| folder.slug == slug)
+                    .map(|folder| folder.id)
+            },
+        )
+    }
+
+    pub(crate) fn insert(&mut self, parent: Int, folder: AssetFolder) {
+        self.folders.insert(folder.id, (parent, folder));
+    }
+
+    /// Get the path of a folder by its id.
+    pub fn path(&self, id: Int) -> Option<String> {
+        let mut slugs = Vec::new();
+        let mut current = id;
+        while current != 0 {
+            let (parent, folder) = self.folders.get(&current)?;
+            slugs.push(folder.slug.as_str());
+            current = *parent;
+        }
+        slugs.reverse();
+        Some(slugs.join("/"))
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum AssetKind {
     IMAGE,
@@ -142,12 +245,12 @@ pub(crate) mod asset_list {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub assets: Option<Assets>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Assets {
         pub list: Option<Vec<Option<AssetItem>>>,
     }
@@ -178,9 +281,7 @@ pub fn asset_list(
     let response =
         post_graphql::<asset_list::AssetList, _>(client, url, variables);
     if response.is_err() {
-        return Err(AssetError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -212,12 +313,12 @@ pub mod asset_folder_list {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub assets: Option<Assets>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Assets {
         pub folders: Option<Vec<Option<AssetFolder>>>,
     }
@@ -247,9 +348,7 @@ pub fn asset_folder_list(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(AssetError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -265,6 +364,56 @@ pub fn asset_folder_list(
     Err(classify_response_error(response_body.errors))
 }
 
+/// List the assets of a folder and all its subfolders.
+///
+/// # Returns
+/// A Result containing either the flat list of assets, folder by folder in
+/// depth-first order, or an asset error.
+pub fn asset_list_recursive(
+    client: &Client,
+    url: &str,
+    root_folder_id: Int,
+    kind: AssetKind,
+) -> Result<Vec<AssetItemWithPath>, AssetError> {
+    let mut assets = Vec::new();
+    let mut folders = vec![(root_folder_id, String::new())];
+    while let Some((folder_id, folder_path)) = folders.pop() {
+        for asset in asset_list(client, url, folder_id, kind.clone())? {
+            assets.push(AssetItemWithPath {
+                folder_path: folder_path.clone(),
+                asset,
+            });
+        }
+        let subfolders = asset_folder_list(client, url, folder_id)?;
+        // reversed so the first subfolder is listed first
+        for subfolder in subfolders.into_iter().rev() {
+            let path = if folder_path.is_empty() {
+                subfolder.slug
+            } else {
+                format!("{}/{}", folder_path, subfolder.slug)
+            };
+            folders.push((subfolder.id, path));
+        }
+    }
+    Ok(assets)
+}
+
+/// Fetch the complete asset folder hierarchy.
+pub fn asset_folder_tree(
+    client: &Client,
+    url: &str,
+) -> Result<AssetFolderTree, AssetError> {
+    let mut folders = Vec::new();
+    let mut parents = vec![0];
+    while let Some(parent) = parents.pop() {
+        for folder in asset_folder_list(client, url, parent)? {
+            parents.push(folder.id);
+            folders.push((parent, folder));
+        }
+    }
+    Ok(AssetFolderTree::new(folders))
+}
+
 pub mod asset_folder_create {
 
     use super::*;
@@ -284,18 +433,18 @@ pub mod asset_folder_create {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub assets: Option<Assets>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Assets {
         #[serde(rename = "createFolder")]
         pub create_folder: Option<CreateFolder>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct CreateFolder {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -332,9 +481,7 @@ pub fn asset_folder_create(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(AssetError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -355,6 +502,40 @@ pub fn asset_folder_create(
     Err(classify_response_error(response_body.errors))
 }
 
+/// Create a folder path like `images/screenshots` including all missing
+/// parent folders, and add the new folders to the tree.
+///
+/// # Returns
+/// A Result containing either the id of the folder or an asset error.
+pub fn asset_folder_create_all(
+    client: &Client,
+    url: &str,
+    tree: &mut AssetFolderTree,
+    path: &str,
+) -> Result<Int, AssetError> {
+    let mut id = 0;
+    for slug in path.split('/').filter(|slug| !slug.is_empty()) {
+        if let Some(folder) =
+            tree.children(id).into_iter().find(|f| f.slug == slug)
+        {
+            id = folder.id;
+            continue;
+        }
+        asset_folder_create(client, url, id, slug.to_string(), None)?;
+        // the mutation does not return the folder, so look it up
+        let folder = asset_folder_list(client, url, id)?
+            .into_iter()
+            .find(|f| f.slug == slug)
+            .ok_or(AssetError::UnknownErrorMessage {
+                message: format!("created folder {} not found", slug),
+            })?;
+        let parent = id;
+        id = folder.id;
+        tree.insert(parent, folder);
+    }
+    Ok(id)
+}
+
 pub mod asset_rename {
     use super::*;
 
@@ -371,17 +552,17 @@ pub mod asset_rename {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub assets: Option<Assets>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Assets {
         #[serde(rename = "renameAsset")]
         pub rename_asset: Option<RenameAsset>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct RenameAsset {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -412,9 +593,7 @@ pub fn asset_rename(
     let response =
         post_graphql::<asset_rename::AssetRename, _>(client, url, variables);
     if response.is_err() {
-        return Err(AssetError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -435,6 +614,47 @@ pub fn asset_rename(
     Err(classify_response_error(response_body.errors))
 }
 
+/// Move an asset to another folder.
+///
+/// The Wiki.js 2.x schema has no mutation for moving assets, so the asset is
+/// downloaded, uploaded to the destination folder under the same filename
+/// and only then deleted from its old folder. It therefore gets a new id.
+///
+/// # Arguments
+/// * `id` - The id of the asset to move.
+/// * `folder_id` - The id of the destination folder.
+///
+/// # Returns
+/// A Result containing either the new id of the asset or an asset error.
+pub fn asset_move(
+    client: &Client,
+    url: &str,
+    id: Int,
+    folder_id: Int,
+) -> Result<Int, AssetError> {
+    let destination = asset_list(client, url, folder_id, AssetKind::ALL)?;
+    if destination.iter().any(|asset| asset.id == id) {
+        return Ok(id);
+    }
+    let source = asset_list_recursive(client, url, 0, AssetKind::ALL)?
+        .into_iter()
+        .find(|asset| asset.asset.id == id)
+        .ok_or(AssetError::AssetInvalid)?;
+    let filename = source.asset.filename.clone();
+    // an upload would silently replace the asset of the same name
+    if destination.iter().any(|asset| asset.filename == filename) {
+        return Err(AssetError::AssetRenameCollision);
+    }
+    let data = asset_download(client, base_url(url), source.path())?;
+    asset_upload(client, base_url(url), folder_id, filename.clone(), data)?;
+    let moved = asset_list(client, url, folder_id, AssetKind::ALL)?
+        .into_iter()
+        .find(|asset| asset.filename == filename)
+        .ok_or(AssetError::AssetGenericError)?;
+    asset_delete(client, url, id)?;
+    Ok(moved.id)
+}
+
 pub mod asset_delete {
     use super::*;
 
@@ -450,18 +670,18 @@ pub mod asset_delete {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub assets: Option<Assets>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Assets {
         #[serde(rename = "deleteAsset")]
         pub delete_asset: Option<DeleteAsset>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct DeleteAsset {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -491,9 +711,7 @@ pub fn asset_delete(
     let response =
         post_graphql::<asset_delete::AssetDelete, _>(client, url, variables);
     if response.is_err() {
-        return Err(AssetError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -525,18 +743,18 @@ pub mod asset_temp_upload_flush {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub assets: Option<Assets>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Assets {
         #[serde(rename = "flushTempUploads")]
         pub flush_temp_uploads: Option<FlushTempUploads>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct FlushTempUploads {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -567,9 +785,7 @@ pub fn asset_temp_upload_flush(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(AssetError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -599,7 +815,7 @@ pub fn asset_download(
 ) -> Result<Vec<u8>, AssetError> {
     let response = client.get(format!("{}/{}", url, path).as_str()).send();
     if response.is_err() {
-        return Err(AssetError::UnknownErrorMessage {
+        return Err(AssetError::RequestFailed {
             message: response.err().unwrap().to_string(),
         });
     }
@@ -634,7 +850,7 @@ pub fn asset_upload(
         .multipart(form)
         .send();
     if response.is_err() {
-        return Err(AssetError::UnknownErrorMessage {
+        return Err(AssetError::RequestFailed {
             message: response.err().unwrap().to_string(),
         });
     }
@@ -0,0 +1,109 @@
+use reqwest::blocking::Client;
+use std::collections::BTreeSet;
+use thiserror::Error;
+
+use crate::asset::{
+    asset_list_recursive, AssetError, AssetItemWithPath, AssetKind,
+};
+use crate::batch::page_get_many;
+use crate::common::{is_cancelled, CancellationToken};
+use crate::page::{page_link_list, page_list, PageError};
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum AuditError {
+    #[error(transparent)]
+    Asset(#[from] AssetError),
+    #[error(transparent)]
+    Page(#[from] PageError),
+    #[error("The audit was cancelled.")]
+    Cancelled,
+}
+
+/// An asset and the pages referencing it.
+#[derive(Clone, Debug)]
+pub struct AssetUsage {
+    pub asset: AssetItemWithPath,
+    /// Ids of the pages whose content or links contain the asset path.
+    pub pages: Vec<i64>,
+}
+
+/// Result of [`asset_orphan_report`](fn.asset_orphan_report.html).
+#[derive(Clone, Debug)]
+pub struct AssetOrphanReport {
+    /// Number of pages whose content was scanned.
+    pub pages_scanned: usize,
+    /// Every asset with the pages referencing it.
+    pub assets: Vec<AssetUsage>,
+}
+
+impl AssetOrphanReport {
+    /// The assets not referenced by any page.
+    pub fn orphans(&self) -> Vec<&AssetItemWithPath> {
+        self.assets
+            .iter()
+            .filter(|usage| usage.pages.is_empty())
+            .map(|usage| &usage.asset)
+            .collect()
+    }
+}
+
+/// Find the assets no page refers to.
+///
+/// An asset counts as referenced by a page if its absolute path, like
+/// `/images/logo.png`, occurs in the page content or its links.
+///
+/// # Returns
+/// A Result containing either the report or an audit error.
+pub fn asset_orphan_report(
+    client: &Client,
+    url: &str,
+    cancel: Option<&CancellationToken>,
+) -> Result<AssetOrphanReport, AuditError> {
+    let assets = asset_list_recursive(client, url, 0, AssetKind::ALL)?;
+    let pages =
+        page_list(client, url, None, None, None, None, None, None, None)?;
+
+    // page id and text to search for asset paths
+    let ids: Vec<i64> = pages.iter().map(|page| page.id).collect();
+    let mut texts: Vec<(i64, String)> =
+        page_get_many(client, url, &ids, cancel)?
+            .into_iter()
+            .map(|page| (page.id, page.content))
+            .collect();
+    if is_cancelled(cancel) {
+        return Err(AuditError::Cancelled);
+    }
+    let pages_scanned = texts.len();
+
+    let locales: BTreeSet<String> =
+        pages.iter().map(|page| page.locale.clone()).collect();
+    for locale in locales {
+        if is_cancelled(cancel) {
+            return Err(AuditError::Cancelled);
+        }
+        for item in page_link_list(client, url, locale)? {
+            let links: Vec<String> = item.links.into_iter().flatten().collect();
+            texts.push((item.id, links.join("\n")));
+        }
+    }
+
+    let assets = assets
+        .into_iter()
+        .map(|asset| {
+            let needle = format!("/{}", asset.path());
+            let pages: BTreeSet<i64> = texts
+                .iter()
+                .filter(|(_, text)| text.contains(&needle))
+                .map(|(id, _)| *id)
+                .collect();
+            AssetUsage {
+                asset,
+                pages: pages.into_iter().collect(),
+            }
+        })
+        .collect();
+    Ok(AssetOrphanReport {
+        pages_scanned,
+        assets,
+    })
+}
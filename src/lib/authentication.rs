@@ -1,14 +1,15 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
+use chrono::{DateTime, Utc};
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 
 use crate::common::{
-    classify_response_error, classify_response_status_error, Boolean, Date,
+    classify_post_error, classify_response_error,
+    classify_response_status_error, post_graphql, random_id, Boolean, Date,
     Int, KeyValuePair, KeyValuePairInput, ResponseStatus,
 };
 use crate::user::UserError;
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct AuthenticationLoginResponse {
     #[serde(rename = "responseResult")]
     pub response_result: Option<ResponseStatus>,
@@ -26,7 +27,7 @@ pub struct AuthenticationLoginResponse {
     pub tfa_qr_image: Option<String>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct ApiKey {
     pub id: Int,
     pub name: String,
@@ -41,7 +42,16 @@ pub struct ApiKey {
     pub is_revoked: Boolean,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+impl ApiKey {
+    /// Whether the key expires before the date. A key whose expiration
+    /// cannot be parsed counts as expiring, so it is not overlooked.
+    pub fn expires_before(&self, date: DateTime<Utc>) -> bool {
+        DateTime::parse_from_rfc3339(&self.expiration)
+            .map_or(true, |expiration| expiration < date)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct AuthenticationStrategy {
     pub key: String,
     pub props: Option<Vec<Option<KeyValuePair>>>,
@@ -59,7 +69,7 @@ pub struct AuthenticationStrategy {
     pub icon: Option<String>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct AuthenticationActiveStrategy {
     pub key: String,
     pub strategy: AuthenticationStrategy,
@@ -77,14 +87,14 @@ pub struct AuthenticationActiveStrategy {
     pub auto_enroll_groups: Vec<Option<Int>>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct AuthenticationCreateApiKeyResponse {
     #[serde(rename = "responseResult")]
     pub response_result: Option<ResponseStatus>,
     pub key: Option<String>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct AuthenticationRegisterResponse {
     #[serde(rename = "responseResult")]
     pub response_result: Option<ResponseStatus>,
@@ -110,6 +120,562 @@ pub struct AuthenticationStrategyInput {
     pub auto_enroll_groups: Vec<Option<Int>>,
 }
 
+impl AuthenticationActiveStrategy {
+    /// The strategy as input for an update, keeping its current settings.
+    pub fn to_input(&self) -> AuthenticationStrategyInput {
+        AuthenticationStrategyInput {
+            key: self.key.clone(),
+            strategy_key: self.strategy.key.clone(),
+            config: Some(
+                self.config
+                    .iter()
+                    .flatten()
+                    .flatten()
+                    .map(|pair| {
+                        Some(KeyValuePairInput::config(
+                            &pair.key,
+                            pair.config_value(),
+                        ))
+                    })
+                    .collect(),
+            ),
+            display_name: self.display_name.clone(),
+            order: self.order,
+            is_enabled: self.is_enabled,
+            self_registration: self.self_registration,
+            domain_whitelist: self.domain_whitelist.clone(),
+            auto_enroll_groups: self.auto_enroll_groups.clone(),
+        }
+    }
+}
+
+/// Typed configuration of an authentication strategy.
+pub trait AuthenticationStrategyConfig {
+    /// Key of the strategy, like `oidc`.
+    fn strategy_key(&self) -> &'static str;
+
+    /// The config options as `(key, value)` pairs.
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)>;
+
+    /// A new enabled strategy with this config and without self
+    /// registration.
+    ///
+    /// # Arguments
+    /// * `display_name` - Name shown on the login page.
+    fn to_strategy_input(
+        &self,
+        display_name: &str,
+    ) -> AuthenticationStrategyInput {
+        AuthenticationStrategyInput {
+            key: random_id(),
+            strategy_key: self.strategy_key().to_string(),
+            config: Some(
+                self.options()
+                    .into_iter()
+                    .map(|(key, value)| {
+                        Some(KeyValuePairInput::config(key, value))
+                    })
+                    .collect(),
+            ),
+            display_name: display_name.to_string(),
+            order: 0,
+            is_enabled: true,
+            self_registration: false,
+            domain_whitelist: Vec::new(),
+            auto_enroll_groups: Vec::new(),
+        }
+    }
+}
+
+/// Config of the generic OpenID Connect strategy `oidc`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OidcConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    pub authorization_url: String,
+    pub token_url: String,
+    pub user_info_url: String,
+    pub issuer: String,
+    pub skip_user_profile: bool,
+    pub email_claim: String,
+    pub display_name_claim: String,
+    /// Assign users to the groups named in the groups claim.
+    pub map_groups: bool,
+    pub groups_claim: String,
+    pub logout_url: Option<String>,
+}
+
+impl OidcConfig {
+    pub fn new(
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+        issuer: impl Into<String>,
+        authorization_url: impl Into<String>,
+        token_url: impl Into<String>,
+        user_info_url: impl Into<String>,
+    ) -> Self {
+        Self {
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            authorization_url: authorization_url.into(),
+            token_url: token_url.into(),
+            user_info_url: user_info_url.into(),
+            issuer: issuer.into(),
+            skip_user_profile: false,
+            email_claim: "email".to_string(),
+            display_name_claim: "displayName".to_string(),
+            map_groups: false,
+            groups_claim: "groups".to_string(),
+            logout_url: None,
+        }
+    }
+
+    pub fn skip_user_profile(mut self, skip: bool) -> Self {
+        self.skip_user_profile = skip;
+        self
+    }
+
+    pub fn email_claim(mut self, claim: impl Into<String>) -> Self {
+        self.email_claim = claim.into();
+        self
+    }
+
+    pub fn display_name_claim(mut self, claim: impl Into<String>) -> Self {
+        self.display_name_claim = claim.into();
+        self
+    }
+
+    /// Map the groups of the claim to Wiki.js groups of the same name.
+    pub fn map_groups(mut self, groups_claim: impl Into<String>) -> Self {
+        self.map_groups = true;
+        self.groups_claim = groups_claim.into();
+        self
+    }
+
+    pub fn logout_url(mut self, url: impl Into<String>) -> Self {
+        self.logout_url = Some(url.into());
+        self
+    }
+}
+
+impl AuthenticationStrategyConfig for OidcConfig {
+    fn strategy_key(&self) -> &'static str {
+        "oidc"
+    }
+
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)> {
+        vec![
+            ("clientId", self.client_id.clone().into()),
+            ("clientSecret", self.client_secret.clone().into()),
+            ("authorizationURL", self.authorization_url.clone().into()),
+            ("tokenURL", self.token_url.clone().into()),
+            ("userInfoURL", self.user_info_url.clone().into()),
+            ("skipUserProfile", self.skip_user_profile.into()),
+            ("issuer", self.issuer.clone().into()),
+            ("emailClaim", self.email_claim.clone().into()),
+            ("displayNameClaim", self.display_name_claim.clone().into()),
+            ("mapGroups", self.map_groups.into()),
+            ("groupsClaim", self.groups_claim.clone().into()),
+            (
+                "logoutURL",
+                self.logout_url.clone().unwrap_or_default().into(),
+            ),
+        ]
+    }
+}
+
+/// Config of the `ldap` strategy for LDAP and Active Directory.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LdapConfig {
+    /// Server URL, like `ldap://ldap.example.com:389`.
+    pub url: String,
+    pub bind_dn: String,
+    pub bind_credentials: String,
+    pub search_base: String,
+    /// Filter to find the user, `{{username}}` is replaced by the login.
+    pub search_filter: String,
+    pub tls_enabled: bool,
+    pub verify_tls_certificate: bool,
+    pub tls_cert_path: String,
+    pub mapping_uid: String,
+    pub mapping_email: String,
+    pub mapping_display_name: String,
+    pub mapping_picture: String,
+    pub map_groups: bool,
+    pub group_search_base: String,
+    pub group_search_filter: String,
+    pub group_name_field: String,
+}
+
+impl LdapConfig {
+    pub fn new(
+        url: impl Into<String>,
+        bind_dn: impl Into<String>,
+        bind_credentials: impl Into<String>,
+        search_base: impl Into<String>,
+    ) -> Self {
+        Self {
+            url: url.into(),
+            bind_dn: bind_dn.into(),
+            bind_credentials: bind_credentials.into(),
+            search_base: search_base.into(),
+            search_filter: "(uid={{username}})".to_string(),
+            tls_enabled: false,
+            verify_tls_certificate: true,
+            tls_cert_path: String::new(),
+            mapping_uid: "uid".to_string(),
+            mapping_email: "mail".to_string(),
+            mapping_display_name: "displayName".to_string(),
+            mapping_picture: "jpegPhoto".to_string(),
+            map_groups: false,
+            group_search_base: String::new(),
+            group_search_filter: "(member={{dn}})".to_string(),
+            group_name_field: "cn".to_string(),
+        }
+    }
+
+    pub fn search_filter(mut self, filter: impl Into<String>) -> Self {
+        self.search_filter = filter.into();
+        self
+    }
+
+    /// Use TLS, verifying the server against the certificate at the path,
+    /// or not at all if `verify` is false.
+    pub fn tls(mut self, cert_path: impl Into<String>, verify: bool) -> Self {
+        self.tls_enabled = true;
+        self.tls_cert_path = cert_path.into();
+        self.verify_tls_certificate = verify;
+        self
+    }
+
+    /// Which attributes hold the unique id, email, display name and
+    /// picture of a user.
+    pub fn mapping(
+        mut self,
+        uid: impl Into<String>,
+        email: impl Into<String>,
+        display_name: impl Into<String>,
+        picture: impl Into<String>,
+    ) -> Self {
+        self.mapping_uid = uid.into();
+        self.mapping_email = email.into();
+        self.mapping_display_name = display_name.into();
+        self.mapping_picture = picture.into();
+        self
+    }
+
+    /// Map the LDAP groups of a user to Wiki.js groups of the same name.
+    pub fn map_groups(
+        mut self,
+        search_base: impl Into<String>,
+        search_filter: impl Into<String>,
+        name_field: impl Into<String>,
+    ) -> Self {
+        self.map_groups = true;
+        self.group_search_base = search_base.into();
+        self.group_search_filter = search_filter.into();
+        self.group_name_field = name_field.into();
+        self
+    }
+}
+
+impl AuthenticationStrategyConfig for LdapConfig {
+    fn strategy_key(&self) -> &'static str {
+        "ldap"
+    }
+
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)> {
+        vec![
+            ("url", self.url.clone().into()),
+            ("bindDn", self.bind_dn.clone().into()),
+            ("bindCredentials", self.bind_credentials.clone().into()),
+            ("searchBase", self.search_base.clone().into()),
+            ("searchFilter", self.search_filter.clone().into()),
+            ("tlsEnabled", self.tls_enabled.into()),
+            ("verifyTLSCertificate", self.verify_tls_certificate.into()),
+            ("tlsCertPath", self.tls_cert_path.clone().into()),
+            ("mappingUID", self.mapping_uid.clone().into()),
+            ("mappingEmail", self.mapping_email.clone().into()),
+            (
+                "mappingDisplayName",
+                self.mapping_display_name.clone().into(),
+            ),
+            ("mappingPicture", self.mapping_picture.clone().into()),
+            ("mapGroups", self.map_groups.into()),
+            ("groupSearchBase", self.group_search_base.clone().into()),
+            ("groupSearchFilter", self.group_search_filter.clone().into()),
+            ("groupNameField", self.group_name_field.clone().into()),
+        ]
+    }
+}
+
+/// Config of the `saml` strategy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SamlConfig {
+    /// Login URL of the identity provider.
+    pub entry_point: String,
+    /// Issuer string sent to the identity provider.
+    pub issuer: String,
+    pub audience: String,
+    /// Public certificate of the identity provider, PEM without header.
+    pub cert: String,
+    pub private_key: String,
+    pub signature_algorithm: String,
+    pub identifier_format: String,
+    pub accepted_clock_skew_ms: i64,
+    pub force_authn: bool,
+    pub mapping_uid: String,
+    pub mapping_email: String,
+    pub mapping_display_name: String,
+    pub mapping_picture: String,
+}
+
+impl SamlConfig {
+    pub fn new(
+        entry_point: impl Into<String>,
+        issuer: impl Into<String>,
+        cert: impl Into<String>,
+    ) -> Self {
+        Self {
+            entry_point: entry_point.into(),
+            issuer: issuer.into(),
+            audience: String::new(),
+            cert: cert.into(),
+            private_key: String::new(),
+            signature_algorithm: "sha256".to_string(),
+            identifier_format:
+                "urn:oasis:names:tc:SAML:1.1:nameid-format:emailAddress"
+                    .to_string(),
+            accepted_clock_skew_ms: -1,
+            force_authn: false,
+            mapping_uid: "nameID".to_string(),
+            mapping_email: "mail".to_string(),
+            mapping_display_name: "displayName".to_string(),
+            mapping_picture: "picture".to_string(),
+        }
+    }
+
+    pub fn audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = audience.into();
+        self
+    }
+
+    /// Key to sign the requests with, PEM without header.
+    pub fn private_key(mut self, key: impl Into<String>) -> Self {
+        self.private_key = key.into();
+        self
+    }
+
+    /// `sha1`, `sha256` or `sha512`.
+    pub fn signature_algorithm(mut self, algorithm: impl Into<String>) -> Self {
+        self.signature_algorithm = algorithm.into();
+        self
+    }
+
+    pub fn identifier_format(mut self, format: impl Into<String>) -> Self {
+        self.identifier_format = format.into();
+        self
+    }
+
+    /// Tolerated clock difference to the identity provider, -1 to not
+    /// check the timestamps at all.
+    pub fn accepted_clock_skew_ms(mut self, skew: i64) -> Self {
+        self.accepted_clock_skew_ms = skew;
+        self
+    }
+
+    pub fn force_authn(mut self, force: bool) -> Self {
+        self.force_authn = force;
+        self
+    }
+
+    /// Which attributes hold the unique id, email, display name and
+    /// picture of a user.
+    pub fn mapping(
+        mut self,
+        uid: impl Into<String>,
+        email: impl Into<String>,
+        display_name: impl Into<String>,
+        picture: impl Into<String>,
+    ) -> Self {
+        self.mapping_uid = uid.into();
+        self.mapping_email = email.into();
+        self.mapping_display_name = display_name.into();
+        self.mapping_picture = picture.into();
+        self
+    }
+}
+
+impl AuthenticationStrategyConfig for SamlConfig {
+    fn strategy_key(&self) -> &'static str {
+        "saml"
+    }
+
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)> {
+        vec![
+            ("entryPoint", self.entry_point.clone().into()),
+            ("issuer", self.issuer.clone().into()),
+            ("audience", self.audience.clone().into()),
+            ("cert", self.cert.clone().into()),
+            ("privateKey", self.private_key.clone().into()),
+            (
+                "signatureAlgorithm",
+                self.signature_algorithm.clone().into(),
+            ),
+            ("identifierFormat", self.identifier_format.clone().into()),
+            ("acceptedClockSkewMs", self.accepted_clock_skew_ms.into()),
+            ("forceAuthn", self.force_authn.into()),
+            ("mappingUID", self.mapping_uid.clone().into()),
+            ("mappingEmail", self.mapping_email.clone().into()),
+            (
+                "mappingDisplayName",
+                self.mapping_display_name.clone().into(),
+            ),
+            ("mappingPicture", self.mapping_picture.clone().into()),
+        ]
+    }
+}
+
+/// Config of the `github` strategy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GitHubConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    pub use_enterprise: bool,
+    /// Domain of a GitHub Enterprise server, like `github.example.com`.
+    pub enterprise_domain: String,
+    pub enterprise_user_endpoint: String,
+}
+
+impl GitHubConfig {
+    pub fn new(
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        Self {
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            use_enterprise: false,
+            enterprise_domain: String::new(),
+            enterprise_user_endpoint: String::new(),
+        }
+    }
+
+    /// Authenticate against a GitHub Enterprise server.
+    pub fn enterprise(
+        mut self,
+        domain: impl Into<String>,
+        user_endpoint: impl Into<String>,
+    ) -> Self {
+        self.use_enterprise = true;
+        self.enterprise_domain = domain.into();
+        self.enterprise_user_endpoint = user_endpoint.into();
+        self
+    }
+}
+
+impl AuthenticationStrategyConfig for GitHubConfig {
+    fn strategy_key(&self) -> &'static str {
+        "github"
+    }
+
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)> {
+        vec![
+            ("clientId", self.client_id.clone().into()),
+            ("clientSecret", self.client_secret.clone().into()),
+            ("useEnterprise", self.use_enterprise.into()),
+            ("enterpriseDomain", self.enterprise_domain.clone().into()),
+            (
+                "enterpriseUserEndpoint",
+                self.enterprise_user_endpoint.clone().into(),
+            ),
+        ]
+    }
+}
+
+/// Config of the `keycloak` strategy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeycloakConfig {
+    /// Base URL of the Keycloak server, like `https://auth.example.com`.
+    pub host: String,
+    pub realm: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub authorization_url: String,
+    pub token_url: String,
+    pub user_info_url: String,
+    /// Also log out of Keycloak when logging out of the wiki.
+    pub logout_upstream: bool,
+    pub logout_url: String,
+}
+
+impl KeycloakConfig {
+    /// The endpoint URLs are derived from the host and realm, override
+    /// them with [`endpoints`](#method.endpoints) if they differ.
+    pub fn new(
+        host: impl Into<String>,
+        realm: impl Into<String>,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        let host: String = host.into();
+        let realm: String = realm.into();
+        let base = format!(
+            "{}/realms/{}/protocol/openid-connect",
+            host.trim_end_matches('/'),
+            realm
+        );
+        Self {
+            authorization_url: format!("{}/auth", base),
+            token_url: format!("{}/token", base),
+            user_info_url: format!("{}/userinfo", base),
+            logout_url: format!("{}/logout", base),
+            logout_upstream: false,
+            host,
+            realm,
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+        }
+    }
+
+    pub fn endpoints(
+        mut self,
+        authorization_url: impl Into<String>,
+        token_url: impl Into<String>,
+        user_info_url: impl Into<String>,
+    ) -> Self {
+        self.authorization_url = authorization_url.into();
+        self.token_url = token_url.into();
+        self.user_info_url = user_info_url.into();
+        self
+    }
+
+    pub fn logout_upstream(mut self, logout: bool) -> Self {
+        self.logout_upstream = logout;
+        self
+    }
+}
+
+impl AuthenticationStrategyConfig for KeycloakConfig {
+    fn strategy_key(&self) -> &'static str {
+        "keycloak"
+    }
+
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)> {
+        vec![
+            ("host", self.host.clone().into()),
+            ("realm", self.realm.clone().into()),
+            ("clientId", self.client_id.clone().into()),
+            ("clientSecret", self.client_secret.clone().into()),
+            ("authorizationURL", self.authorization_url.clone().into()),
+            ("tokenURL", self.token_url.clone().into()),
+            ("userInfoURL", self.user_info_url.clone().into()),
+            ("logoutUpstream", self.logout_upstream.into()),
+            ("logoutURL", self.logout_url.clone().into()),
+        ]
+    }
+}
+
 pub(crate) mod login_mod {
     use super::*;
 
@@ -127,12 +693,12 @@ pub(crate) mod login_mod {
 
     impl Variables {}
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct ResponseData {
         pub authentication: Option<Authentication>,
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct Authentication {
         pub login: Option<AuthenticationLoginResponse>,
     }
@@ -166,9 +732,7 @@ pub fn login(
     };
     let response = post_graphql::<login_mod::Login, _>(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -204,12 +768,12 @@ pub mod api_key_list {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub authentication: Option<Authentication>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Authentication {
         #[serde(rename = "apiKeys")]
         pub api_keys: Option<Vec<Option<ApiKey>>>,
@@ -238,9 +802,7 @@ pub fn api_key_list(
     let response =
         post_graphql::<api_key_list::ApiKeyList, _>(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -265,12 +827,12 @@ pub mod api_state_get {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub authentication: Option<Authentication>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Authentication {
         #[serde(rename = "apiState")]
         pub api_state: Boolean,
@@ -296,9 +858,7 @@ pub fn api_state_get(client: &Client, url: &str) -> Result<Boolean, UserError> {
     let response =
         post_graphql::<api_state_get::ApiStateGet, _>(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -320,12 +880,12 @@ pub mod authentication_strategy_list {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub authentication: Option<Authentication>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Authentication {
         pub strategies: Option<Vec<Option<AuthenticationStrategy>>>,
     }
@@ -355,9 +915,7 @@ pub fn authentication_strategy_list(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -386,12 +944,12 @@ pub mod authentication_active_strategy_list {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub authentication: Option<Authentication>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Authentication {
         #[serde(rename = "activeStrategies")]
         pub active_strategies:
@@ -425,9 +983,7 @@ pub fn authentication_active_strategy_list(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -459,12 +1015,12 @@ pub mod api_key_create {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub authentication: Option<Authentication>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Authentication {
         #[serde(rename = "createApiKey")]
         pub create_api_key: Option<AuthenticationCreateApiKeyResponse>,
@@ -502,9 +1058,7 @@ pub fn api_key_create(
     let response =
         post_graphql::<api_key_create::ApiKeyCreate, _>(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -550,12 +1104,12 @@ pub mod login_tfa {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub authentication: Option<Authentication>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Authentication {
         #[serde(rename = "loginTFA")]
         pub login_tfa: Option<AuthenticationLoginResponse>,
@@ -591,9 +1145,7 @@ pub fn login_tfa(
     let response =
         post_graphql::<login_tfa::LoginTfa, _>(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -624,12 +1176,12 @@ pub mod login_password_change {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub authentication: Option<Authentication>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Authentication {
         #[serde(rename = "loginChangePassword")]
         pub login_change_password: Option<AuthenticationLoginResponse>,
@@ -664,9 +1216,7 @@ pub fn login_password_change(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -696,17 +1246,17 @@ pub mod password_forgot {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub authentication: Option<Authentication>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Authentication {
         #[serde(rename = "forgotPassword")]
         pub forgot_password: Option<ForgotPassword>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ForgotPassword {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -737,9 +1287,7 @@ pub fn password_forgot(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
 
@@ -778,12 +1326,12 @@ pub mod register {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub authentication: Option<Authentication>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Authentication {
         pub register: Option<AuthenticationRegisterResponse>,
     }
@@ -818,9 +1366,7 @@ pub fn register(
     let response =
         post_graphql::<register::Register, _>(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
 
@@ -849,17 +1395,17 @@ pub mod api_key_revoke {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub authentication: Option<Authentication>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Authentication {
         #[serde(rename = "revokeApiKey")]
         pub revoke_api_key: Option<RevokeApiKey>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct RevokeApiKey {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -889,9 +1435,7 @@ pub fn api_key_revoke(
     let response =
         post_graphql::<api_key_revoke::ApiKeyRevoke, _>(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
 
@@ -928,24 +1472,24 @@ pub mod api_state_set {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub authentication: Option<Authentication>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Authentication {
         #[serde(rename = "setApiState")]
         pub set_api_state: Option<SetApiState>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct SetApiState {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ApiStateSetAuthenticationSetApiStateResponseResult {
         pub succeeded: Boolean,
         #[serde(rename = "errorCode")]
@@ -978,9 +1522,7 @@ pub fn api_state_set(
     let response =
         post_graphql::<api_state_set::ApiStateSet, _>(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
 
@@ -1022,16 +1564,16 @@ pub mod authentication_strategy_update {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub authentication: Option<Authentication>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Authentication {
         #[serde(rename = "updateStrategies")]
         pub update_strategies: Option<UpdateStrategies>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct UpdateStrategies {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -1065,9 +1607,7 @@ pub fn authentication_strategy_update(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
 
@@ -1101,18 +1641,18 @@ pub mod authentication_certificate_regenerate {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub authentication: Option<Authentication>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Authentication {
         #[serde(rename = "regenerateCertificates")]
         pub regenerate_certificates: Option<RegenerateCertificates>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct RegenerateCertificates {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -1140,9 +1680,7 @@ pub fn authentication_certificate_regenerate(
     let variables = authentication_certificate_regenerate::Variables {};
     let response = post_graphql::<authentication_certificate_regenerate::AuthenticationCertificateRegenerate, _>(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
 
@@ -1179,18 +1717,18 @@ pub mod guest_user_reset {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub authentication: Option<Authentication>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Authentication {
         #[serde(rename = "resetGuestUser")]
         pub reset_guest_user: Option<ResetGuestUser>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResetGuestUser {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -1217,9 +1755,7 @@ pub fn guest_user_reset(client: &Client, url: &str) -> Result<(), UserError> {
         client, url, variables,
     );
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
 
@@ -1241,3 +1777,180 @@ pub fn guest_user_reset(client: &Client, url: &str) -> Result<(), UserError> {
     }
     Err(classify_response_error(response_body.errors))
 }
+
+/// List the API keys that are revoked, expired or expire within the
+/// duration.
+pub fn api_key_expiring(
+    client: &Client,
+    url: &str,
+    within: std::time::Duration,
+) -> Result<Vec<ApiKey>, UserError> {
+    let within =
+        chrono::Duration::from_std(within).unwrap_or(chrono::Duration::MAX);
+    let limit = Utc::now()
+        .checked_add_signed(within)
+        .unwrap_or(DateTime::<Utc>::MAX_UTC);
+    Ok(api_key_list(client, url)?
+        .into_iter()
+        .filter(|key| key.is_revoked || key.expires_before(limit))
+        .collect())
+}
+
+/// Load the active strategies, edit them and write them back. Wiki.js
+/// replaces all active strategies on update, so strategies must not be
+/// dropped from the list unless they should be deleted.
+pub fn authentication_strategy_edit<F>(
+    client: &Client,
+    url: &str,
+    edit: F,
+) -> Result<(), UserError>
+where
+    F: FnOnce(&mut Vec<AuthenticationStrategyInput>) -> Result<(), UserError>,
+{
+    let mut strategies: Vec<AuthenticationStrategyInput> =
+        authentication_active_strategy_list(client, url, None)?
+            .iter()
+            .map(AuthenticationActiveStrategy::to_input)
+            .collect();
+    edit(&mut strategies)?;
+    authentication_strategy_update(client, url, strategies)
+}
+
+/// Add a strategy after the active ones.
+pub fn authentication_strategy_add(
+    client: &Client,
+    url: &str,
+    mut strategy: AuthenticationStrategyInput,
+) -> Result<(), UserError> {
+    authentication_strategy_edit(client, url, |strategies| {
+        strategy.order = strategies.len() as Int;
+        strategies.push(strategy);
+        Ok(())
+    })
+}
+
+/// Enable or disable an active strategy.
+pub fn authentication_strategy_set_enabled(
+    client: &Client,
+    url: &str,
+    key: &str,
+    enabled: bool,
+) -> Result<(), UserError> {
+    authentication_strategy_edit(client, url, |strategies| {
+        let strategy = strategies
+            .iter_mut()
+            .find(|strategy| strategy.key == key)
+            .ok_or(UserError::AuthenticationStrategyNotFound {
+                key: key.to_string(),
+            })?;
+        strategy.is_enabled = enabled;
+        Ok(())
+    })
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] =
+        [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+    for chunk in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+    let mut digest = [0u8; 20];
+    for (i, v) in h.iter().enumerate() {
+        digest[i * 4..i * 4 + 4].copy_from_slice(&v.to_be_bytes());
+    }
+    digest
+}
+
+fn hmac_sha1(key: &[u8], message: &[u8]) -> [u8; 20] {
+    let mut block = [0u8; 64];
+    if key.len() > 64 {
+        block[..20].copy_from_slice(&sha1(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner: Vec<u8> = block.iter().map(|b| b ^ 0x36).collect();
+    inner.extend_from_slice(message);
+    let mut outer: Vec<u8> = block.iter().map(|b| b ^ 0x5c).collect();
+    outer.extend_from_slice(&sha1(&inner));
+    sha1(&outer)
+}
+
+// RFC 4648 base32 as used for TFA secrets, ignoring case, spaces and
+// padding
+fn base32_decode(text: &str) -> Option<Vec<u8>> {
+    let mut bits: u64 = 0;
+    let mut count = 0;
+    let mut bytes = Vec::new();
+    for c in text.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let value = match c.to_ascii_uppercase() {
+            c @ 'A'..='Z' => c as u64 - 'A' as u64,
+            c @ '2'..='7' => c as u64 - '2' as u64 + 26,
+            _ => return None,
+        };
+        bits = (bits << 5) | value;
+        count += 5;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Compute the 6 digit TFA security code for a time, like an authenticator
+/// app does (TOTP with SHA-1 and 30 second steps).
+///
+/// # Arguments
+/// * `secret` - The base32 encoded secret shown when setting up TFA.
+/// * `time` - The time to compute the code for, usually now.
+///
+/// # Returns
+/// The code, or None if the secret is not valid base32.
+pub fn totp_code(secret: &str, time: std::time::SystemTime) -> Option<String> {
+    let key = base32_decode(secret).filter(|key| !key.is_empty())?;
+    let step = time.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs() / 30;
+    let hash = hmac_sha1(&key, &step.to_be_bytes());
+    let offset = (hash[19] & 0x0f) as usize;
+    let code = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]) % 1_000_000;
+    Some(format!("{:06}", code))
+}
@@ -0,0 +1,638 @@
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+use crate::asset::{
+    asset_download, asset_folder_create_all, asset_folder_tree,
+    asset_list_recursive, asset_upload, AssetError, AssetFolderTree, AssetKind,
+};
+use crate::batch::page_get_many;
+use crate::common::{
+    base_url, concurrency, is_cancelled, run_parallel, CancellationToken, Date,
+    Int,
+};
+use crate::page::{
+    page_create, page_get_by_path, page_list, page_update,
+    parse_frontmatter_markdown, Page, PageEditor, PageError, PageFrontMatter,
+};
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum BackupError {
+    #[error(transparent)]
+    Page(#[from] PageError),
+    #[error(transparent)]
+    Asset(#[from] AssetError),
+    #[error("Failed to access the backup archive: {0}")]
+    Io(String),
+    #[error("The backup archive is invalid: {0}")]
+    Invalid(String),
+    #[error("The backup was cancelled.")]
+    Cancelled,
+}
+
+impl From<std::io::Error> for BackupError {
+    fn from(e: std::io::Error) -> Self {
+        BackupError::Io(e.to_string())
+    }
+}
+
+/// Archive formats a backup can be written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackupFormat {
+    TarGz,
+    Zip,
+}
+
+impl BackupFormat {
+    /// Guess the format from a file name, `.zip` or else tar.gz.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("zip") => BackupFormat::Zip,
+            _ => BackupFormat::TarGz,
+        }
+    }
+}
+
+/// Settings of [`backup_create`](fn.backup_create.html).
+#[derive(Clone, Debug, Default)]
+pub struct BackupOptions {
+    /// Only include pages and assets updated at or after this date, for an
+    /// incremental backup on top of an earlier one.
+    pub since: Option<Date>,
+    /// Leave out the assets.
+    pub skip_assets: bool,
+}
+
+/// Path of a page inside the archive, like `pages/en/docs/intro.md`.
+pub fn page_file_path(page: &Page) -> String {
+    format!(
+        "pages/{}/{}.{}",
+        page.locale,
+        page.path.trim_matches('/'),
+        page.editor.file_extension()
+    )
+}
+
+/// Contents of the `manifest.yaml` at the root of every backup.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct BackupManifest {
+    pub version: u32,
+    /// The `since` of an incremental backup.
+    pub since: Option<Date>,
+    /// Latest update date of all included pages and assets, to be used as
+    /// `since` of the next incremental backup.
+    pub newest_updated_at: Option<Date>,
+    pub pages: usize,
+    pub assets: usize,
+}
+
+trait ArchiveWriter {
+    fn add(&mut self, path: &str, data: &[u8]) -> std::io::Result<()>;
+    fn finish(self: Box<Self>) -> std::io::Result<()>;
+}
+
+struct TarGzWriter(tar::Builder<GzEncoder<File>>);
+
+impl ArchiveWriter for TarGzWriter {
+    fn add(&mut self, path: &str, data: &[u8]) -> std::io::Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        );
+        header.set_cksum();
+        self.0.append_data(&mut header, path, data)
+    }
+
+    fn finish(self: Box<Self>) -> std::io::Result<()> {
+        self.0.into_inner()?.finish()?;
+        Ok(())
+    }
+}
+
+struct ZipWriter(zip::ZipWriter<File>);
+
+impl ArchiveWriter for ZipWriter {
+    fn add(&mut self, path: &str, data: &[u8]) -> std::io::Result<()> {
+        self.0
+            .start_file(path, zip::write::SimpleFileOptions::default())?;
+        self.0.write_all(data)
+    }
+
+    fn finish(self: Box<Self>) -> std::io::Result<()> {
+        self.0.finish()?;
+        Ok(())
+    }
+}
+
+fn is_since(date: &str, since: &Option<Date>) -> bool {
+    since.as_ref().is_none_or(|since| date >= since.as_str())
+}
+
+/// Write all pages and assets into an archive file.
+///
+/// Pages end up under `pages/<locale>/<path>.<ext>` with their metadata as
+/// YAML front matter, assets under `assets/<folder path>/<filename>` and a
+/// [`BackupManifest`](struct.BackupManifest.html) in `manifest.yaml`.
+///
+/// The archive is written to a hidden `.partial` file next to `path` first
+/// and only renamed to `path` once it is complete, so a failed or cancelled
+/// backup never replaces an existing archive.
+///
+/// # Returns
+/// A Result containing either the manifest written or a backup error.
+pub fn backup_create(
+    client: &Client,
+    url: &str,
+    path: &Path,
+    format: BackupFormat,
+    options: &BackupOptions,
+    cancel: Option<&CancellationToken>,
+) -> Result<BackupManifest, BackupError> {
+    let partial = partial_path(path);
+    let result =
+        File::create(&partial)
+            .map_err(BackupError::from)
+            .and_then(|file| {
+                write_backup(client, url, file, format, options, cancel)
+            });
+    match result {
+        Ok(manifest) => {
+            fs::rename(&partial, path)?;
+            Ok(manifest)
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&partial);
+            Err(e)
+        }
+    }
+}
+
+// next to the archive, so the rename stays on the same file system
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(".partial");
+    path.with_file_name(name)
+}
+
+fn write_backup(
+    client: &Client,
+    url: &str,
+    file: File,
+    format: BackupFormat,
+    options: &BackupOptions,
+    cancel: Option<&CancellationToken>,
+) -> Result<BackupManifest, BackupError> {
+    let mut archive: Box<dyn ArchiveWriter> = match format {
+        BackupFormat::TarGz => Box::new(TarGzWriter(tar::Builder::new(
+            GzEncoder::new(file, Compression::default()),
+        ))),
+        BackupFormat::Zip => Box::new(ZipWriter(zip::ZipWriter::new(file))),
+    };
+    let mut newest: Option<Date> = None;
+    let mut update_newest = |date: &Date| {
+        if newest.as_ref().is_none_or(|newest| date > newest) {
+            newest = Some(date.clone());
+        }
+    };
+
+    let ids: Vec<Int> =
+        page_list(client, url, None, None, None, None, None, None, None)?
+            .into_iter()
+            .filter(|page| is_since(&page.updated_at, &options.since))
+            .map(|page| page.id)
+            .collect();
+    let pages = page_get_many(client, url, &ids, cancel)?;
+    for page in &pages {
+        archive.add(
+            &page_file_path(page),
+            page.to_markdown_with_frontmatter().as_bytes(),
+        )?;
+        update_newest(&page.updated_at);
+    }
+
+    let mut assets = 0;
+    if !options.skip_assets {
+        let list: Vec<_> =
+            asset_list_recursive(client, url, 0, AssetKind::ALL)?
+                .into_iter()
+                .filter(|asset| {
+                    is_since(&asset.asset.updated_at, &options.since)
+                })
+                .collect();
+        // downloaded a few at a time so they need not all fit in memory
+        for chunk in list.chunks(concurrency()) {
+            let results = run_parallel(chunk, concurrency(), cancel, |asset| {
+                asset_download(client, base_url(url), asset.path())
+            });
+            for (asset, result) in chunk.iter().zip(results) {
+                let data = result.ok_or(BackupError::Cancelled)??;
+                archive.add(&format!("assets/{}", asset.path()), &data)?;
+                update_newest(&asset.asset.updated_at);
+                assets += 1;
+            }
+        }
+    }
+    if is_cancelled(cancel) {
+        return Err(BackupError::Cancelled);
+    }
+
+    let manifest = BackupManifest {
+        version: 1,
+        since: options.since.clone(),
+        newest_updated_at: newest,
+        pages: pages.len(),
+        assets,
+    };
+    // the manifest only contains strings and numbers
+    let yaml = serde_yaml::to_string(&manifest).unwrap();
+    archive.add("manifest.yaml", yaml.as_bytes())?;
+    archive.finish()?;
+    Ok(manifest)
+}
+
+/// Settings of [`backup_restore`](fn.backup_restore.html).
+#[derive(Clone, Debug, Default)]
+pub struct RestoreOptions {
+    /// Only find out what would be restored, without changing the wiki.
+    pub dry_run: bool,
+    /// Leave out the assets.
+    pub skip_assets: bool,
+}
+
+/// What a restore does with a file of the archive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RestoreAction {
+    /// The page does not exist in the wiki.
+    Create,
+    /// The page exists with other content or metadata.
+    Update,
+    /// The asset does not exist in the wiki or has other content.
+    Upload,
+    /// The page or asset is already as in the archive.
+    Unchanged,
+}
+
+impl std::fmt::Display for RestoreAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let action = match self {
+            RestoreAction::Create => "create",
+            RestoreAction::Update => "update",
+            RestoreAction::Upload => "upload",
+            RestoreAction::Unchanged => "unchanged",
+        };
+        write!(f, "{}", action)
+    }
+}
+
+/// A file of the archive and what the restore does with it.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RestoreItem {
+    pub file: String,
+    pub action: RestoreAction,
+}
+
+/// Result of restoring each [`RestoreItem`](struct.RestoreItem.html).
+pub type RestoreResult = Vec<(RestoreItem, Result<(), BackupError>)>;
+
+// all files of the archive by their path
+fn read_archive(path: &Path) -> Result<BTreeMap<String, Vec<u8>>, BackupError> {
+    let file = File::open(path)?;
+    let mut files = BTreeMap::new();
+    match BackupFormat::from_path(path) {
+        BackupFormat::TarGz => {
+            let mut archive = tar::Archive::new(GzDecoder::new(file));
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+                let name = entry.path()?.to_string_lossy().into_owned();
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data)?;
+                files.insert(name, data);
+            }
+        }
+        BackupFormat::Zip => {
+            let invalid =
+                |e: zip::result::ZipError| BackupError::Invalid(e.to_string());
+            let mut archive = zip::ZipArchive::new(file).map_err(invalid)?;
+            for index in 0..archive.len() {
+                let mut entry = archive.by_index(index).map_err(invalid)?;
+                if entry.is_dir() {
+                    continue;
+                }
+                let name = entry.name().to_string();
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data)?;
+                files.insert(name, data);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn data_hash(data: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    hasher.finish()
+}
+
+// a page file of the archive with locale and path from its front matter,
+// or else from where it is in the archive
+struct ArchivedPage {
+    locale: String,
+    path: String,
+    front_matter: PageFrontMatter,
+    content: String,
+}
+
+impl ArchivedPage {
+    fn parse(file: &str, data: &[u8]) -> Result<Self, BackupError> {
+        let invalid = |message: String| {
+            BackupError::Invalid(format!("{}: {}", file, message))
+        };
+        let text =
+            std::str::from_utf8(data).map_err(|e| invalid(e.to_string()))?;
+        let (front_matter, content) = parse_frontmatter_markdown(text)
+            .map_err(|e| invalid(e.to_string()))?;
+        // pages/<locale>/<path>.<ext>
+        let (locale, path) = file
+            .strip_prefix("pages/")
+            .and_then(|rest| rest.split_once('/'))
+            .map(|(locale, rest)| {
+                let path = rest.rsplit_once('.').map_or(rest, |(path, _)| path);
+                (locale.to_string(), path.to_string())
+            })
+            .ok_or_else(|| invalid("not a page file".to_string()))?;
+        Ok(Self {
+            locale: front_matter.locale.clone().unwrap_or(locale),
+            path: front_matter.path.clone().unwrap_or(path),
+            front_matter,
+            content,
+        })
+    }
+
+    // whether the page in the wiki already has the content and the
+    // metadata of the archived one
+    fn matches(&self, page: &Page) -> bool {
+        let front_matter = &self.front_matter;
+        let mut tags: Vec<&String> =
+            page.tags.iter().flatten().map(|t| &t.tag).collect();
+        tags.sort();
+        let archived_tags = front_matter.tags.as_ref().map(|archived| {
+            let mut archived: Vec<&String> = archived.iter().collect();
+            archived.sort();
+            archived
+        });
+        page.content == self.content
+            && front_matter.title.as_ref().is_none_or(|t| *t == page.title)
+            && front_matter
+                .description
+                .as_ref()
+                .is_none_or(|d| *d == page.description)
+            && front_matter
+                .editor
+                .as_ref()
+                .is_none_or(|e| *e == page.editor)
+            && front_matter
+                .is_published
+                .is_none_or(|p| p == page.is_published)
+            && front_matter.is_private.is_none_or(|p| p == page.is_private)
+            && front_matter
+                .publish_start_date
+                .as_ref()
+                .is_none_or(|d| *d == page.publish_start_date)
+            && front_matter
+                .publish_end_date
+                .as_ref()
+                .is_none_or(|d| *d == page.publish_end_date)
+            && archived_tags.is_none_or(|archived| archived == tags)
+    }
+
+    fn create(&self, client: &Client, url: &str) -> Result<(), PageError> {
+        let front_matter = self.front_matter.clone();
+        page_create(
+            client,
+            url,
+            self.content.clone(),
+            front_matter.description.unwrap_or_default(),
+            front_matter.editor.unwrap_or(PageEditor::Markdown),
+            front_matter.is_published.unwrap_or(true),
+            front_matter.is_private.unwrap_or(false),
+            self.locale.clone(),
+            self.path.clone(),
+            front_matter.publish_end_date.filter(|d| !d.is_empty()),
+            front_matter.publish_start_date.filter(|d| !d.is_empty()),
+            front_matter.script_css,
+            front_matter.script_js,
+            front_matter
+                .tags
+                .unwrap_or_default()
+                .into_iter()
+                .map(Some)
+                .collect(),
+            front_matter.title.unwrap_or_else(|| {
+                self.path.rsplit('/').next().unwrap_or_default().to_string()
+            }),
+        )
+    }
+
+    fn update(
+        &self,
+        client: &Client,
+        url: &str,
+        id: Int,
+    ) -> Result<(), PageError> {
+        let front_matter = self.front_matter.clone();
+        page_update(
+            client,
+            url,
+            id,
+            Some(self.content.clone()),
+            front_matter.description,
+            front_matter.editor,
+            front_matter.is_private,
+            front_matter.is_published,
+            None,
+            None,
+            front_matter.publish_end_date,
+            front_matter.publish_start_date,
+            front_matter.script_css,
+            front_matter.script_js,
+            front_matter
+                .tags
+                .map(|tags| tags.into_iter().map(Some).collect()),
+            front_matter.title,
+        )
+    }
+}
+
+fn restore_page(
+    client: &Client,
+    url: &str,
+    file: &str,
+    data: &[u8],
+    dry_run: bool,
+) -> (RestoreItem, Result<(), BackupError>) {
+    let item = |action| RestoreItem {
+        file: file.to_string(),
+        action,
+    };
+    let page = match ArchivedPage::parse(file, data) {
+        Ok(page) => page,
+        Err(e) => return (item(RestoreAction::Create), Err(e)),
+    };
+    let existing =
+        page_get_by_path(client, url, page.path.clone(), page.locale.clone());
+    let (action, result) = match existing {
+        Ok(existing) if page.matches(&existing) => {
+            (RestoreAction::Unchanged, Ok(()))
+        }
+        Ok(_) if dry_run => (RestoreAction::Update, Ok(())),
+        Ok(existing) => {
+            (RestoreAction::Update, page.update(client, url, existing.id))
+        }
+        Err(PageError::PageNotFound) if dry_run => {
+            (RestoreAction::Create, Ok(()))
+        }
+        Err(PageError::PageNotFound) => {
+            (RestoreAction::Create, page.create(client, url))
+        }
+        Err(e) => (RestoreAction::Create, Err(e)),
+    };
+    (item(action), result.map_err(BackupError::from))
+}
+
+fn restore_asset(
+    client: &Client,
+    url: &str,
+    folders: &mut Option<AssetFolderTree>,
+    path: &str,
+    data: &[u8],
+) -> Result<(), BackupError> {
+    let (folder_path, filename) = path.rsplit_once('/').unwrap_or(("", path));
+    let tree = match folders {
+        Some(tree) => tree,
+        None => folders.insert(asset_folder_tree(client, url)?),
+    };
+    let folder = asset_folder_create_all(client, url, tree, folder_path)?;
+    asset_upload(
+        client,
+        base_url(url),
+        folder,
+        filename.to_string(),
+        data.to_vec(),
+    )?;
+    Ok(())
+}
+
+/// Restore the pages and assets of a backup archive written by
+/// [`backup_create`](fn.backup_create.html), `.zip` or tar.gz by its name.
+///
+/// Assets come first so the pages can link them. Pages are matched by
+/// locale and path: missing ones are created, those with other content or
+/// metadata updated and the rest left alone, so restoring the same archive
+/// twice changes nothing the second time. Assets are uploaded unless one
+/// with the same content already exists at their path, which is checked by
+/// downloading those of the same size and comparing hashes. Nothing in the
+/// wiki that is not in the archive is deleted.
+///
+/// # Arguments
+/// * `cancel` - Stops before the next file, the result lists those done.
+///
+/// # Returns
+/// A Result containing either the manifest of the archive with the result
+/// of every file, or a backup error if the archive cannot be read.
+pub fn backup_restore(
+    client: &Client,
+    url: &str,
+    path: &Path,
+    options: &RestoreOptions,
+    cancel: Option<&CancellationToken>,
+) -> Result<(BackupManifest, RestoreResult), BackupError> {
+    let files = read_archive(path)?;
+    let manifest: BackupManifest = files
+        .get("manifest.yaml")
+        .ok_or_else(|| BackupError::Invalid("no manifest.yaml".to_string()))
+        .and_then(|yaml| {
+            serde_yaml::from_slice(yaml)
+                .map_err(|e| BackupError::Invalid(e.to_string()))
+        })?;
+    if manifest.version != 1 {
+        return Err(BackupError::Invalid(format!(
+            "unsupported version {}",
+            manifest.version
+        )));
+    }
+    let mut result = RestoreResult::new();
+
+    if !options.skip_assets {
+        let sizes: HashMap<String, Int> =
+            asset_list_recursive(client, url, 0, AssetKind::ALL)?
+                .into_iter()
+                .map(|asset| (asset.path(), asset.asset.file_size))
+                .collect();
+        let mut folders = None;
+        for (file, data) in &files {
+            let Some(path) = file.strip_prefix("assets/") else {
+                continue;
+            };
+            if is_cancelled(cancel) {
+                return Ok((manifest, result));
+            }
+            // only assets of the same size can be unchanged, so just
+            // download those to compare the hashes
+            let unchanged = match sizes.get(path) {
+                Some(size) if *size == data.len() as Int => {
+                    asset_download(client, base_url(url), path.to_string())
+                        .map(|existing| data_hash(&existing) == data_hash(data))
+                }
+                _ => Ok(false),
+            };
+            let (action, outcome) = match unchanged {
+                Ok(true) => (RestoreAction::Unchanged, Ok(())),
+                Ok(false) if options.dry_run => (RestoreAction::Upload, Ok(())),
+                Ok(false) => (
+                    RestoreAction::Upload,
+                    restore_asset(client, url, &mut folders, path, data),
+                ),
+                Err(e) => (RestoreAction::Upload, Err(e.into())),
+            };
+            result.push((
+                RestoreItem {
+                    file: file.clone(),
+                    action,
+                },
+                outcome,
+            ));
+        }
+    }
+
+    let pages: Vec<(&String, &Vec<u8>)> = files
+        .iter()
+        .filter(|(file, _)| file.starts_with("pages/"))
+        .collect();
+    let restored =
+        run_parallel(&pages, concurrency(), cancel, |(file, data)| {
+            restore_page(client, url, file, data, options.dry_run)
+        });
+    result.extend(restored.into_iter().flatten());
+    Ok((manifest, result))
+}
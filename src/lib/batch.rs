@@ -0,0 +1,358 @@
+use graphql_client::GraphQLQuery;
+use reqwest::blocking::Client;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+// number of queries sent per batched request by the helpers below
+const BATCH_SIZE: usize = 50;
+
+use crate::common::{
+    concurrency, post_graphql_batch, run_parallel, CancellationToken, Date,
+    Int, PostError,
+};
+use crate::page::{
+    page_get, page_get_attributes, page_get_attributes_response,
+    page_get_by_path, page_get_by_path_response, page_get_minimal,
+    page_get_minimal_response, page_get_response, page_get_updated_at,
+    page_get_updated_at_response, page_tree, page_tree_get_response, Page,
+    PageAttributes, PageError, PageMinimal, PageTreeItem, PageTreeMode,
+};
+
+// variants are named after the functions they batch
+#[derive(Clone, Debug)]
+#[allow(clippy::enum_variant_names)]
+enum BatchQuery {
+    PageGet(Int),
+    PageGetByPath(String, String),
+    PageGetMinimal(Int),
+    PageGetAttributes(Int),
+    PageGetUpdatedAt(Int),
+    PageTreeGet(Int, PageTreeMode, bool, String),
+}
+
+/// Result of a single query of a [`BatchRequest`](struct.BatchRequest.html).
+#[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum BatchResponse {
+    PageGet(Result<Page, PageError>),
+    PageGetByPath(Result<Page, PageError>),
+    PageGetMinimal(Result<PageMinimal, PageError>),
+    PageGetAttributes(Result<PageAttributes, PageError>),
+    PageGetUpdatedAt(Result<Date, PageError>),
+    PageTreeGet(Result<Vec<PageTreeItem>, PageError>),
+}
+
+/// Builder grouping several queries into one HTTP round trip.
+///
+/// ```no_run
+/// use wikijs::batch::{BatchRequest, BatchResponse};
+/// # use wikijs::{Api, Credentials};
+/// # let api = Api::new(
+/// #     "http://localhost:3000".to_string(),
+/// #     Credentials::Key("my-api-key".to_string()),
+/// # ).unwrap();
+///
+/// let request = (1..=50).fold(BatchRequest::new(), |r, id| r.page_get(id));
+/// for response in api.batch_send(request) {
+///     if let BatchResponse::PageGet(Ok(page)) = response {
+///         println!("{}", page.title);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct BatchRequest {
+    queries: Vec<BatchQuery>,
+}
+
+impl BatchRequest {
+    /// Create an empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a [`page_get`](../struct.Api.html#method.page_get) query.
+    pub fn page_get(mut self, id: Int) -> Self {
+        self.queries.push(BatchQuery::PageGet(id));
+        self
+    }
+
+    /// Add a [`page_get_by_path`](../struct.Api.html#method.page_get_by_path)
+    /// query.
+    pub fn page_get_by_path(mut self, path: String, locale: String) -> Self {
+        self.queries.push(BatchQuery::PageGetByPath(path, locale));
+        self
+    }
+
+    /// Add a [`page_get_minimal`](../struct.Api.html#method.page_get_minimal)
+    /// query.
+    pub fn page_get_minimal(mut self, id: Int) -> Self {
+        self.queries.push(BatchQuery::PageGetMinimal(id));
+        self
+    }
+
+    /// Add a
+    /// [`page_get_attributes`](../struct.Api.html#method.page_get_attributes)
+    /// query.
+    pub fn page_get_attributes(mut self, id: Int) -> Self {
+        self.queries.push(BatchQuery::PageGetAttributes(id));
+        self
+    }
+
+    /// Add a
+    /// [`page_get_updated_at`](../struct.Api.html#method.page_get_updated_at)
+    /// query.
+    pub fn page_get_updated_at(mut self, id: Int) -> Self {
+        self.queries.push(BatchQuery::PageGetUpdatedAt(id));
+        self
+    }
+
+    /// Add a [`page_tree_get`](../struct.Api.html#method.page_tree_get)
+    /// query.
+    pub fn page_tree_get(
+        mut self,
+        parent: Int,
+        mode: PageTreeMode,
+        include_ancestors: bool,
+        locale: String,
+    ) -> Self {
+        self.queries.push(BatchQuery::PageTreeGet(
+            parent,
+            mode,
+            include_ancestors,
+            locale,
+        ));
+        self
+    }
+
+    /// Number of queries in the batch.
+    pub fn len(&self) -> usize {
+        self.queries.len()
+    }
+
+    /// Whether the batch contains no queries.
+    pub fn is_empty(&self) -> bool {
+        self.queries.is_empty()
+    }
+}
+
+impl BatchQuery {
+    fn body(&self) -> Value {
+        let body = match self {
+            BatchQuery::PageGet(id) => serde_json::to_value(
+                page_get::PageGet::build_query(page_get::Variables { id: *id }),
+            ),
+            BatchQuery::PageGetByPath(path, locale) => serde_json::to_value(
+                page_get_by_path::PageGetByPath::build_query(
+                    page_get_by_path::Variables {
+                        path: path.clone(),
+                        locale: locale.clone(),
+                    },
+                ),
+            ),
+            BatchQuery::PageGetMinimal(id) => serde_json::to_value(
+                page_get_minimal::PageGetMinimal::build_query(
+                    page_get_minimal::Variables { id: *id },
+                ),
+            ),
+            BatchQuery::PageGetAttributes(id) => serde_json::to_value(
+                page_get_attributes::PageGetAttributes::build_query(
+                    page_get_attributes::Variables { id: *id },
+                ),
+            ),
+            BatchQuery::PageGetUpdatedAt(id) => serde_json::to_value(
+                page_get_updated_at::PageGetUpdatedAt::build_query(
+                    page_get_updated_at::Variables { id: *id },
+                ),
+            ),
+            BatchQuery::PageTreeGet(
+                parent,
+                mode,
+                include_ancestors,
+                locale,
+            ) => serde_json::to_value(page_tree::PageTree::build_query(
+                page_tree::Variables {
+                    parent: *parent,
+                    mode: mode.clone(),
+                    include_ancestors: *include_ancestors,
+                    locale: locale.clone(),
+                },
+            )),
+        };
+        // the query bodies only contain strings and integers
+        body.unwrap()
+    }
+
+    fn response(&self, value: Result<&Value, PostError>) -> BatchResponse {
+        use crate::common::{decode_response as decode, strict_mode};
+        match self {
+            BatchQuery::PageGet(_) => {
+                BatchResponse::PageGet(page_get_response(value.and_then(|v| {
+                    decode::<page_get::PageGet>(
+                        page_get::OPERATION_NAME,
+                        page_get::QUERY,
+                        v,
+                        strict_mode(),
+                    )
+                })))
+            }
+            BatchQuery::PageGetByPath(..) => BatchResponse::PageGetByPath(
+                page_get_by_path_response(value.and_then(|v| {
+                    decode::<page_get_by_path::PageGetByPath>(
+                        page_get_by_path::OPERATION_NAME,
+                        page_get_by_path::QUERY,
+                        v,
+                        strict_mode(),
+                    )
+                })),
+            ),
+            BatchQuery::PageGetMinimal(_) => BatchResponse::PageGetMinimal(
+                page_get_minimal_response(value.and_then(|v| {
+                    decode::<page_get_minimal::PageGetMinimal>(
+                        page_get_minimal::OPERATION_NAME,
+                        page_get_minimal::QUERY,
+                        v,
+                        strict_mode(),
+                    )
+                })),
+            ),
+            BatchQuery::PageGetAttributes(_) => {
+                BatchResponse::PageGetAttributes(page_get_attributes_response(
+                    value.and_then(|v| {
+                        decode::<page_get_attributes::PageGetAttributes>(
+                            page_get_attributes::OPERATION_NAME,
+                            page_get_attributes::QUERY,
+                            v,
+                            strict_mode(),
+                        )
+                    }),
+                ))
+            }
+            BatchQuery::PageGetUpdatedAt(_) => BatchResponse::PageGetUpdatedAt(
+                page_get_updated_at_response(value.and_then(|v| {
+                    decode::<page_get_updated_at::PageGetUpdatedAt>(
+                        page_get_updated_at::OPERATION_NAME,
+                        page_get_updated_at::QUERY,
+                        v,
+                        strict_mode(),
+                    )
+                })),
+            ),
+            BatchQuery::PageTreeGet(..) => BatchResponse::PageTreeGet(
+                page_tree_get_response(value.and_then(|v| {
+                    decode::<page_tree::PageTree>(
+                        page_tree::OPERATION_NAME,
+                        page_tree::QUERY,
+                        v,
+                        strict_mode(),
+                    )
+                })),
+            ),
+        }
+    }
+}
+
+/// Send all queries of a batch in a single request.
+///
+/// # Returns
+/// One response per query in the order they were added. If the request as a
+/// whole fails, every response carries that error.
+pub fn batch_send(
+    client: &Client,
+    url: &str,
+    request: BatchRequest,
+) -> Vec<BatchResponse> {
+    if request.is_empty() {
+        return Vec::new();
+    }
+    let bodies: Vec<Value> = request.queries.iter().map(|q| q.body()).collect();
+    match post_graphql_batch(client, url, &bodies) {
+        Ok(values) => request
+            .queries
+            .iter()
+            .zip(values.iter())
+            .map(|(query, value)| query.response(Ok(value)))
+            .collect(),
+        Err(e) => {
+            let message = e.to_string();
+            request
+                .queries
+                .iter()
+                .map(|query| {
+                    query.response(Err(PostError::BatchFailed(message.clone())))
+                })
+                .collect()
+        }
+    }
+}
+
+// send page_get queries in batches of BATCH_SIZE, None for the batches
+// skipped after a cancel
+fn page_get_batches(
+    client: &Client,
+    url: &str,
+    ids: &[Int],
+    cancel: Option<&CancellationToken>,
+) -> Vec<Option<Vec<BatchResponse>>> {
+    let chunks: Vec<&[Int]> = ids.chunks(BATCH_SIZE).collect();
+    run_parallel(&chunks, concurrency(), cancel, |chunk| {
+        let request = chunk
+            .iter()
+            .fold(BatchRequest::new(), |r, id| r.page_get(*id));
+        batch_send(client, url, request)
+    })
+}
+
+/// Fetch full pages in batches of 50, sending as many batches at once as
+/// the [`concurrency`](../common/fn.concurrency.html) allows.
+///
+/// # Returns
+/// The pages in the order of `ids`, or the first error. A cancelled token
+/// stops between batches and returns the pages fetched so far.
+pub(crate) fn page_get_many(
+    client: &Client,
+    url: &str,
+    ids: &[Int],
+    cancel: Option<&CancellationToken>,
+) -> Result<Vec<Page>, PageError> {
+    let responses = page_get_batches(client, url, ids, cancel);
+    let mut pages = Vec::with_capacity(ids.len());
+    // the batches after the first skipped one are not part of the result
+    for responses in responses.into_iter().map_while(|r| r) {
+        for response in responses {
+            if let BatchResponse::PageGet(result) = response {
+                pages.push(result?);
+            }
+        }
+    }
+    Ok(pages)
+}
+
+/// Fetch full pages like [`page_get_many`], but keep going past failed
+/// pages.
+///
+/// # Returns
+/// The result of every page by its id, duplicate ids are fetched once. The
+/// ids of batches skipped after a cancel are missing.
+pub fn page_get_map(
+    client: &Client,
+    url: &str,
+    ids: &[Int],
+    cancel: Option<&CancellationToken>,
+) -> HashMap<Int, Result<Page, PageError>> {
+    let mut unique = HashSet::with_capacity(ids.len());
+    let ids: Vec<Int> = ids
+        .iter()
+        .copied()
+        .filter(|id| unique.insert(*id))
+        .collect();
+    let responses = page_get_batches(client, url, &ids, cancel);
+    ids.chunks(BATCH_SIZE)
+        .zip(responses)
+        .filter_map(|(chunk, responses)| Some(chunk.iter().zip(responses?)))
+        .flatten()
+        .filter_map(|(id, response)| match response {
+            BatchResponse::PageGet(result) => Some((*id, result)),
+            _ => None,
+        })
+        .collect()
+}
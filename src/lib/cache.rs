@@ -0,0 +1,393 @@
+use reqwest::blocking::Client;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::asset::AssetError;
+use crate::common::stable_hash;
+use crate::page::{
+    page_get, page_get_updated_at, page_tree_get, Page, PageError,
+    PageTreeItem, PageTreeMode,
+};
+
+/// A cached response together with what is needed to validate it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+enum CacheEntry {
+    /// Valid as long as the `updatedAt` of the page did not change.
+    Page { page: Box<Page>, fetched_at: u64 },
+    /// The tree has no update date, so it is valid for the time to live
+    /// after it was fetched.
+    PageTree {
+        items: Vec<PageTreeItem>,
+        fetched_at: u64,
+    },
+    /// Revalidated with a conditional request. The data lives in its own
+    /// file on disk.
+    Asset {
+        etag: Option<String>,
+        last_modified: Option<String>,
+        #[serde(skip)]
+        data: Vec<u8>,
+    },
+}
+
+/// An entry as stored in the cache directory, with its key since the file
+/// name is only a hash of it.
+#[derive(Serialize, Deserialize)]
+struct DiskEntry<E = CacheEntry> {
+    key: String,
+    entry: E,
+}
+
+#[derive(Debug, Default)]
+struct Lru {
+    entries: HashMap<String, CacheEntry>,
+    // least recently used first
+    order: VecDeque<String>,
+}
+
+impl Lru {
+    fn touch(&mut self, key: &str) {
+        if let Some(index) = self.order.iter().position(|k| k == key) {
+            let key = self.order.remove(index).unwrap();
+            self.order.push_back(key);
+        }
+    }
+
+    fn get(&mut self, key: &str) -> Option<CacheEntry> {
+        let entry = self.entries.get(key).cloned();
+        if entry.is_some() {
+            self.touch(key);
+        }
+        entry
+    }
+
+    fn insert(&mut self, key: String, entry: CacheEntry, capacity: usize) {
+        if self.entries.insert(key.clone(), entry).is_some() {
+            self.touch(&key);
+        } else {
+            self.order.push_back(key);
+        }
+        while self.order.len() > capacity {
+            if let Some(old) = self.order.pop_front() {
+                self.entries.remove(&old);
+            }
+        }
+    }
+}
+
+/// A page read from the cache without asking the server.
+#[derive(Clone, Debug)]
+pub struct CachedPage {
+    pub page: Page,
+    /// When the page was fetched from the server, to judge how stale it is.
+    pub fetched_at: SystemTime,
+}
+
+/// Read cache for pages, page trees and assets.
+///
+/// Entries are kept in memory, least recently used ones being dropped
+/// beyond the capacity, and optionally in a directory so they survive the
+/// process. Before a cached entry is served it is validated against the
+/// server: pages by their update date, assets by a conditional request
+/// with their ETag or modification date, and page trees by a time to live.
+/// Failing to read or write the directory is not an error, the entry is
+/// simply fetched again.
+///
+/// Attach it to an [`Api`](../struct.Api.html#method.with_cache) to have
+/// `page_get`, `page_tree_get` and `asset_download` use it.
+#[derive(Debug)]
+pub struct Cache {
+    capacity: usize,
+    dir: Option<PathBuf>,
+    tree_ttl: Duration,
+    memory: Mutex<Lru>,
+}
+
+impl Cache {
+    /// Create a cache holding up to `capacity` entries in memory.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            dir: None,
+            tree_ttl: Duration::from_secs(60),
+            memory: Mutex::new(Lru::default()),
+        }
+    }
+
+    /// Also store the entries in this directory, it is created if needed.
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// How long a cached page tree is served without asking the server.
+    /// One minute by default.
+    pub fn tree_ttl(mut self, ttl: Duration) -> Self {
+        self.tree_ttl = ttl;
+        self
+    }
+
+    /// Drop all entries from memory and the directory.
+    pub fn clear(&self) -> std::io::Result<()> {
+        *self.memory.lock().unwrap() = Lru::default();
+        match &self.dir {
+            Some(dir) if dir.exists() => fs::remove_dir_all(dir),
+            _ => Ok(()),
+        }
+    }
+
+    /// Get a cached page without validating it against the server.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the page.
+    pub fn page(&self, id: i64) -> Option<CachedPage> {
+        match self.get(&page_key(id)) {
+            Some(CacheEntry::Page { page, fetched_at }) => Some(CachedPage {
+                page: *page,
+                fetched_at: UNIX_EPOCH + Duration::from_secs(fetched_at),
+            }),
+            _ => None,
+        }
+    }
+
+    /// List all cached pages, in memory or in the directory, ordered by
+    /// locale and path.
+    pub fn pages(&self) -> Vec<CachedPage> {
+        let mut keys: Vec<String> = self
+            .memory
+            .lock()
+            .unwrap()
+            .entries
+            .keys()
+            .cloned()
+            .collect();
+        if let Some(Ok(files)) = self.dir.as_ref().map(fs::read_dir) {
+            for file in files.flatten() {
+                let path = file.path();
+                if path.extension().is_some_and(|e| e == "json") {
+                    keys.extend(read_disk_entry(&path).map(|disk| disk.key));
+                }
+            }
+        }
+        keys.sort();
+        keys.dedup();
+        let mut pages: Vec<CachedPage> = keys
+            .iter()
+            .filter_map(|key| key.strip_prefix("page/")?.parse().ok())
+            .filter_map(|id| self.page(id))
+            .collect();
+        pages.sort_by(|a, b| {
+            (&a.page.locale, &a.page.path).cmp(&(&b.page.locale, &b.page.path))
+        });
+        pages
+    }
+
+    /// Find cached pages whose title, description or content contain the
+    /// query, ignoring case.
+    ///
+    /// # Arguments
+    /// * `query` - The text to search for.
+    pub fn search(&self, query: &str) -> Vec<CachedPage> {
+        let query = query.to_lowercase();
+        self.pages()
+            .into_iter()
+            .filter(|cached| {
+                [
+                    &cached.page.title,
+                    &cached.page.description,
+                    &cached.page.content,
+                ]
+                .iter()
+                .any(|text| text.to_lowercase().contains(&query))
+            })
+            .collect()
+    }
+
+    // hashed as keys like asset paths can exceed the file name limit, with
+    // a hash that stays the same across Rust releases
+    fn entry_path(dir: &Path, key: &str) -> PathBuf {
+        dir.join(stable_hash(key.as_bytes()))
+    }
+
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        if let Some(entry) = self.memory.lock().unwrap().get(key) {
+            return Some(entry);
+        }
+        let path = Self::entry_path(self.dir.as_ref()?, key);
+        let disk = read_disk_entry(&path.with_extension("json"))?;
+        // another key with the same hash
+        if disk.key != key {
+            return None;
+        }
+        let mut entry = disk.entry;
+        if let CacheEntry::Asset { data, .. } = &mut entry {
+            *data = fs::read(path.with_extension("bin")).ok()?;
+        }
+        self.memory.lock().unwrap().insert(
+            key.to_string(),
+            entry.clone(),
+            self.capacity,
+        );
+        Some(entry)
+    }
+
+    fn insert(&self, key: &str, entry: CacheEntry) {
+        if let Some(dir) = &self.dir {
+            let path = Self::entry_path(dir, key);
+            // best effort, a missing file is just a cache miss
+            let _ = fs::create_dir_all(dir).and_then(|_| {
+                if let CacheEntry::Asset { data, .. } = &entry {
+                    fs::write(path.with_extension("bin"), data)?;
+                }
+                let disk = DiskEntry {
+                    key: key.to_string(),
+                    entry: &entry,
+                };
+                fs::write(
+                    path.with_extension("json"),
+                    serde_json::to_vec(&disk)?,
+                )
+            });
+        }
+        self.memory.lock().unwrap().insert(
+            key.to_string(),
+            entry,
+            self.capacity,
+        );
+    }
+}
+
+fn read_disk_entry(path: &Path) -> Option<DiskEntry> {
+    serde_json::from_slice(&fs::read(path).ok()?).ok()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn page_key(id: i64) -> String {
+    format!("page/{}", id)
+}
+
+pub(crate) fn cached_page_get(
+    cache: &Cache,
+    client: &Client,
+    url: &str,
+    id: i64,
+) -> Result<Page, PageError> {
+    let key = page_key(id);
+    if let Some(CacheEntry::Page { page, .. }) = cache.get(&key) {
+        if page_get_updated_at(client, url, id)? == page.updated_at {
+            return Ok(*page);
+        }
+    }
+    let page = page_get(client, url, id)?;
+    cache.insert(
+        &key,
+        CacheEntry::Page {
+            page: Box::new(page.clone()),
+            fetched_at: now(),
+        },
+    );
+    Ok(page)
+}
+
+pub(crate) fn cached_page_tree_get(
+    cache: &Cache,
+    client: &Client,
+    url: &str,
+    parent: i64,
+    mode: PageTreeMode,
+    include_ancestors: bool,
+    locale: String,
+) -> Result<Vec<PageTreeItem>, PageError> {
+    let key = format!(
+        "tree/{}/{}/{:?}/{}",
+        locale, parent, mode, include_ancestors
+    );
+    if let Some(CacheEntry::PageTree { items, fetched_at }) = cache.get(&key) {
+        if now().saturating_sub(fetched_at) < cache.tree_ttl.as_secs() {
+            return Ok(items);
+        }
+    }
+    let items =
+        page_tree_get(client, url, parent, mode, include_ancestors, locale)?;
+    cache.insert(
+        &key,
+        CacheEntry::PageTree {
+            items: items.clone(),
+            fetched_at: now(),
+        },
+    );
+    Ok(items)
+}
+
+pub(crate) fn cached_asset_download(
+    cache: &Cache,
+    client: &Client,
+    url: &str,
+    path: String,
+) -> Result<Vec<u8>, AssetError> {
+    let key = format!("asset/{}", path);
+    let mut request = client.get(format!("{}/{}", url, path).as_str());
+    let cached = cache.get(&key);
+    if let Some(CacheEntry::Asset {
+        etag,
+        last_modified,
+        ..
+    }) = &cached
+    {
+        if let Some(etag) = etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+    let response = request.send().map_err(|e| AssetError::RequestFailed {
+        message: e.to_string(),
+    })?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        if let Some(CacheEntry::Asset { data, .. }) = cached {
+            return Ok(data);
+        }
+    }
+    if !response.status().is_success() {
+        return Err(AssetError::UnknownError);
+    }
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    let etag = header(ETAG);
+    let last_modified = header(LAST_MODIFIED);
+    let data = response
+        .bytes()
+        .map_err(|e| AssetError::RequestFailed {
+            message: e.to_string(),
+        })?
+        .to_vec();
+    if etag.is_some() || last_modified.is_some() {
+        cache.insert(
+            &key,
+            CacheEntry::Asset {
+                etag,
+                last_modified,
+                data: data.clone(),
+            },
+        );
+    }
+    Ok(data)
+}
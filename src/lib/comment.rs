@@ -1,11 +1,11 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::common::{
-    classify_response_error, classify_response_status_error, Boolean, Date,
-    Int, KeyValuePair, KeyValuePairInput, KnownErrorCodes, ResponseStatus,
+    classify_post_error, classify_response_error,
+    classify_response_status_error, post_graphql, Boolean, Date, Int,
+    KeyValuePair, KeyValuePairInput, KnownErrorCodes, ResponseStatus,
     UnknownError,
 };
 
@@ -27,6 +27,8 @@ pub enum CommentError {
     UnknownErrorCode { code: i64, message: String },
     #[error("Unknown response error: {message}")]
     UnknownErrorMessage { message: String },
+    #[error("Request failed: {message}")]
+    RequestFailed { message: String },
     #[error("Unknown response error.")]
     UnknownError,
 }
@@ -55,6 +57,9 @@ impl UnknownError for CommentError {
     fn unknown_error_message(message: String) -> Self {
         CommentError::UnknownErrorMessage { message }
     }
+    fn request_failed(message: String) -> Self {
+        CommentError::RequestFailed { message }
+    }
     fn unknown_error() -> Self {
         CommentError::UnknownError
     }
@@ -70,7 +75,7 @@ impl KnownErrorCodes for CommentError {
     }
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct Comment {
     pub id: Int,
     pub content: String,
@@ -89,7 +94,99 @@ pub struct Comment {
     pub updated_at: Date,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+// Wiki.js stores the parent of a reply but does not expose it over GraphQL,
+// so replies created through this crate carry it in an HTML comment that is
+// not visible in the rendered comment.
+const REPLY_MARKER_PREFIX: &str = "<!-- reply-to:";
+const REPLY_MARKER_SUFFIX: &str = " -->";
+
+impl Comment {
+    /// The id of the comment this one replies to, if it was created with
+    /// [`comment_reply`].
+    pub fn reply_to(&self) -> Option<Int> {
+        self.content
+            .strip_prefix(REPLY_MARKER_PREFIX)?
+            .split_once(REPLY_MARKER_SUFFIX)?
+            .0
+            .parse()
+            .ok()
+    }
+
+    /// The content without the reply marker.
+    pub fn text(&self) -> &str {
+        match self.reply_to() {
+            Some(_) => self
+                .content
+                .split_once(REPLY_MARKER_SUFFIX)
+                .map(|(_, text)| text.trim_start())
+                .unwrap_or(&self.content),
+            None => &self.content,
+        }
+    }
+}
+
+/// A comment with its replies, for rendering reply chains.
+#[derive(Clone, Debug, Serialize)]
+pub struct CommentTree {
+    pub comment: Comment,
+    pub replies: Vec<CommentTree>,
+}
+
+impl CommentTree {
+    /// Arrange comments into threads, keeping their order. Replies whose
+    /// parent is not among the comments become threads of their own.
+    ///
+    /// # Arguments
+    /// * `comments` - The comments of a page.
+    pub fn build(comments: Vec<Comment>) -> Vec<CommentTree> {
+        let ids: Vec<Int> = comments.iter().map(|c| c.id).collect();
+        let (roots, replies): (Vec<Comment>, Vec<Comment>) =
+            comments.into_iter().partition(|c| {
+                c.reply_to().is_none_or(|parent| !ids.contains(&parent))
+            });
+        let mut replies: Vec<Option<Comment>> =
+            replies.into_iter().map(Some).collect();
+        roots
+            .into_iter()
+            .map(|comment| Self::attach(comment, &mut replies))
+            .collect()
+    }
+
+    fn attach(comment: Comment, replies: &mut [Option<Comment>]) -> Self {
+        let children: Vec<Comment> = replies
+            .iter_mut()
+            .filter(|r| {
+                r.as_ref().is_some_and(|r| r.reply_to() == Some(comment.id))
+            })
+            .filter_map(Option::take)
+            .collect();
+        CommentTree {
+            comment,
+            replies: children
+                .into_iter()
+                .map(|child| Self::attach(child, replies))
+                .collect(),
+        }
+    }
+
+    /// Number of comments in the thread, including this one.
+    pub fn count(&self) -> usize {
+        1 + self.replies.iter().map(CommentTree::count).sum::<usize>()
+    }
+
+    /// The comments of the thread depth first, with their depth.
+    pub fn flatten(&self) -> Vec<(usize, &Comment)> {
+        let mut comments = vec![(0, &self.comment)];
+        for reply in &self.replies {
+            comments.extend(
+                reply.flatten().into_iter().map(|(depth, c)| (depth + 1, c)),
+            );
+        }
+        comments
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct CommentProvider {
     #[serde(rename = "isEnabled")]
     pub is_enabled: Boolean,
@@ -111,14 +208,14 @@ pub struct CommentProviderInput {
     pub config: Option<Vec<Option<KeyValuePairInput>>>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct CommentCreateResponse {
     #[serde(rename = "responseResult")]
     pub response_result: Option<ResponseStatus>,
     pub id: Option<Int>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct CommentUpdateResponse {
     #[serde(rename = "responseResult")]
     pub response_result: Option<ResponseStatus>,
@@ -141,12 +238,12 @@ pub mod comment_list {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub comments: Option<Comments>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Comments {
         pub list: Vec<Option<Comment>>,
     }
@@ -176,9 +273,7 @@ pub fn comment_list(
     let response =
         post_graphql::<comment_list::CommentList, _>(client, url, variables);
     if response.is_err() {
-        return Err(CommentError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -202,12 +297,12 @@ pub mod comment_provider_list {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub comments: Option<Comments>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Comments {
         pub providers: Option<Vec<Option<CommentProvider>>>,
     }
@@ -236,9 +331,7 @@ pub fn comment_provider_list(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(CommentError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -268,12 +361,12 @@ pub mod comment_get {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub comments: Option<Comments>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Comments {
         pub single: Option<Comment>,
     }
@@ -302,9 +395,7 @@ pub fn comment_get(
     let response =
         post_graphql::<comment_get::CommentGet, _>(client, url, variables);
     if response.is_err() {
-        return Err(CommentError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -334,18 +425,18 @@ pub mod comment_provider_update {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub comments: Option<Comments>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Comments {
         #[serde(rename = "updateProviders")]
         pub update_providers: Option<UpdateProviders>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct UpdateProviders {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -379,9 +470,7 @@ pub fn comment_provider_update(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(CommentError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -428,12 +517,12 @@ pub mod comment_create {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub comments: Option<Comments>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Comments {
         pub create: Option<CommentCreateResponse>,
     }
@@ -473,9 +562,7 @@ pub fn comment_create(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(CommentError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -515,12 +602,12 @@ pub mod comment_update {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub comments: Option<Comments>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Comments {
         pub update: Option<CommentUpdateResponse>,
     }
@@ -551,9 +638,7 @@ pub fn comment_update(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(CommentError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -591,17 +676,17 @@ pub mod comment_delete {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub comments: Option<Comments>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Comments {
         pub delete: Option<Delete>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Delete {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -632,9 +717,7 @@ pub fn comment_delete(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(CommentError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -656,3 +739,32 @@ pub fn comment_delete(
         response_body.errors,
     ))
 }
+
+/// Reply to a comment, marking the reply so [`CommentTree`] can thread it.
+pub fn comment_reply(
+    client: &Client,
+    url: &str,
+    page_id: Int,
+    reply_to: Int,
+    content: String,
+) -> Result<(), CommentError> {
+    let content = format!(
+        "{}{}{}\n{}",
+        REPLY_MARKER_PREFIX, reply_to, REPLY_MARKER_SUFFIX, content
+    );
+    comment_create(client, url, page_id, Some(reply_to), content, None, None)
+}
+
+/// Delete all comments of a page and return how many were deleted.
+pub fn comment_delete_all_for_page(
+    client: &Client,
+    url: &str,
+    locale: String,
+    path: String,
+) -> Result<usize, CommentError> {
+    let comments = comment_list(client, url, locale, path)?;
+    for comment in &comments {
+        comment_delete(client, url, comment.id)?;
+    }
+    Ok(comments.len())
+}
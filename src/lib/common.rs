@@ -1,10 +1,21 @@
+use graphql_client::{GraphQLQuery, Response};
+use reqwest::blocking::Client;
+use reqwest::IntoUrl;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cell::Cell;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
 
 pub type Boolean = bool;
 pub type Int = i64;
 pub type Date = String;
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct KeyValuePair {
     pub key: String,
     pub value: String,
@@ -16,7 +27,32 @@ pub struct KeyValuePairInput {
     pub value: String,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+impl KeyValuePair {
+    /// The value of a module config option.
+    ///
+    /// Wiki.js returns each value of a storage or search engine config
+    /// together with its schema as a JSON object, this extracts just the
+    /// value.
+    pub fn config_value(&self) -> Value {
+        serde_json::from_str::<Value>(&self.value)
+            .ok()
+            .and_then(|v| v.get("value").cloned())
+            .unwrap_or(Value::Null)
+    }
+}
+
+impl KeyValuePairInput {
+    /// A module config option, with the value wrapped as `{"v": value}`
+    /// like Wiki.js expects it.
+    pub fn config(key: &str, value: Value) -> Self {
+        Self {
+            key: key.to_string(),
+            value: serde_json::json!({ "v": value }).to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
 #[allow(unused)]
 pub struct ResponseStatus {
     pub succeeded: Boolean,
@@ -26,9 +62,372 @@ pub struct ResponseStatus {
     pub message: Option<String>,
 }
 
+/// Token to abort long-running helpers like bulk operations, exports or
+/// watchers from another thread.
+///
+/// Helpers taking an `Option<&CancellationToken>` check it between items and
+/// stop cleanly once [`cancel`](#method.cancel) was called. Clones share the
+/// same state.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a new token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation of every helper holding this token.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether cancellation was requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Random enough id in the uuid format the Wiki.js admin uses for
+/// navigation items and page rules.
+pub(crate) fn random_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed) as u128;
+    let hex = format!("{:032x}", nanos ^ (count << 96));
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// FNV-1a hash of the data in hex, which unlike the std hasher is stable
+/// across Rust releases, so it can name files and be stored.
+pub(crate) fn stable_hash(data: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in data {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Whether an optional cancellation token was cancelled.
+pub(crate) fn is_cancelled(token: Option<&CancellationToken>) -> bool {
+    token.is_some_and(|t| t.is_cancelled())
+}
+
+/// Apply `f` to every item on up to `concurrency` threads.
+///
+/// The results are returned in the order of `items`. Items that were not
+/// started because the token got cancelled are `None`.
+pub(crate) fn run_parallel<T, R, F>(
+    items: &[T],
+    concurrency: usize,
+    cancel: Option<&CancellationToken>,
+    f: F,
+) -> Vec<Option<R>>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let strict = strict_mode();
+    let inherited = CONCURRENCY.with(Cell::get);
+    let next = AtomicUsize::new(0);
+    let results =
+        Mutex::new((0..items.len()).map(|_| None).collect::<Vec<_>>());
+    thread::scope(|scope| {
+        for _ in 0..concurrency.clamp(1, items.len().max(1)) {
+            scope.spawn(|| {
+                let _scope = StrictScope::enter(strict);
+                let _concurrency = ConcurrencyScope::enter(inherited);
+                loop {
+                    if is_cancelled(cancel) {
+                        break;
+                    }
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    if index >= items.len() {
+                        break;
+                    }
+                    let result = f(&items[index]);
+                    results.lock().unwrap()[index] = Some(result);
+                }
+            });
+        }
+    });
+    results.into_inner().unwrap()
+}
+
+/// How many API calls the bulk operations make at once unless set
+/// otherwise.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+thread_local! {
+    static CONCURRENCY: Cell<usize> = const { Cell::new(DEFAULT_CONCURRENCY) };
+}
+
+/// Run `f` with the bulk operations on the current thread making up to
+/// `concurrency` API calls at once, like importing, exporting, syncing,
+/// backing up and re-tagging pages. 1 makes them sequential.
+///
+/// API calls through [`Api`](../struct.Api.html) use the concurrency set
+/// with [`Api::with_concurrency`](../struct.Api.html#method.with_concurrency),
+/// this is for the bulk helpers called outside of it.
+///
+/// # Arguments
+/// * `concurrency` - The number of calls at once, at least 1.
+/// * `f` - The function to run.
+///
+/// # Returns
+/// The return value of `f`.
+pub fn with_concurrency<R>(concurrency: usize, f: impl FnOnce() -> R) -> R {
+    let _scope = ConcurrencyScope::enter(concurrency);
+    f()
+}
+
+/// How many API calls the bulk operations on the current thread make at
+/// once.
+pub fn concurrency() -> usize {
+    CONCURRENCY.with(Cell::get)
+}
+
+/// Sets the concurrency on the current thread until it is dropped.
+struct ConcurrencyScope {
+    previous: usize,
+}
+
+impl ConcurrencyScope {
+    fn enter(concurrency: usize) -> Self {
+        Self {
+            previous: CONCURRENCY.with(|c| c.replace(concurrency.max(1))),
+        }
+    }
+}
+
+impl Drop for ConcurrencyScope {
+    fn drop(&mut self) {
+        CONCURRENCY.with(|c| c.set(self.previous));
+    }
+}
+
+thread_local! {
+    static STRICT_MODE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run `f` with strict mode enabled or disabled on the current thread.
+///
+/// By default the library is lenient with what the server returns: unknown
+/// values of open enums like [`PageEditor`](../page/enum.PageEditor.html)
+/// are kept as `Other` and a missing or null `responseResult` ends up as a
+/// generic unknown error. In strict mode these cases fail with a diagnostic
+/// naming the operation and the JSON path of the unexpected value, which
+/// helps when talking to forked or patched Wiki.js servers.
+///
+/// API calls through [`Api`](../struct.Api.html) use the mode set with
+/// [`Api::with_strict_mode`](../struct.Api.html#method.with_strict_mode),
+/// this is for deserializing responses outside of it.
+///
+/// # Arguments
+/// * `strict` - Whether strict mode should be enabled.
+/// * `f` - The function to run.
+///
+/// # Returns
+/// The return value of `f`.
+pub fn with_strict_mode<R>(strict: bool, f: impl FnOnce() -> R) -> R {
+    let _scope = StrictScope::enter(strict);
+    f()
+}
+
+/// Whether strict mode is enabled on the current thread.
+pub fn strict_mode() -> bool {
+    STRICT_MODE.with(Cell::get)
+}
+
+/// Sets strict mode on the current thread until it is dropped.
+struct StrictScope {
+    previous: bool,
+}
+
+impl StrictScope {
+    fn enter(strict: bool) -> Self {
+        Self {
+            previous: STRICT_MODE.with(|mode| mode.replace(strict)),
+        }
+    }
+}
+
+impl Drop for StrictScope {
+    fn drop(&mut self) {
+        STRICT_MODE.with(|mode| mode.set(self.previous));
+    }
+}
+
+/// The client of an [`Api`](../struct.Api.html) lent to a single API call,
+/// with the strict mode and concurrency of the `Api` in effect on the
+/// calling thread.
+pub(crate) struct ApiClient<'a> {
+    client: &'a Client,
+    _scope: StrictScope,
+    _concurrency: Option<ConcurrencyScope>,
+}
+
+impl<'a> ApiClient<'a> {
+    pub(crate) fn new(client: &'a Client, strict: bool) -> Self {
+        Self {
+            client,
+            _scope: StrictScope::enter(strict),
+            _concurrency: None,
+        }
+    }
+
+    pub(crate) fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self._concurrency = Some(ConcurrencyScope::enter(concurrency));
+        self
+    }
+}
+
+impl Deref for ApiClient<'_> {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        self.client
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum PostError {
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+    #[error("{operation}: unexpected response at {path}: {message}")]
+    UnexpectedResponse {
+        operation: &'static str,
+        path: String,
+        message: String,
+    },
+    #[error("batch request failed: {0}")]
+    BatchFailed(String),
+}
+
+/// The base URL of the Wiki.js instance serving the GraphQL endpoint `url`,
+/// for the endpoints outside of GraphQL like asset downloads and uploads.
+pub(crate) fn base_url(url: &str) -> &str {
+    url.strip_suffix("/graphql").unwrap_or(url)
+}
+
+/// Post a GraphQL query and decode its response.
+///
+/// This is a replacement for graphql_client's `post_graphql_blocking` that
+/// reports the JSON path of values that fail to decode and runs the
+/// additional checks of strict mode.
+pub(crate) fn post_graphql<Q: GraphQLQuery, U: IntoUrl>(
+    client: &Client,
+    url: U,
+    variables: Q::Variables,
+) -> Result<Response<Q::ResponseData>, PostError> {
+    let body = Q::build_query(variables);
+    let value: Value = client.post(url).json(&body).send()?.json()?;
+    decode_response::<Q>(body.operation_name, body.query, &value, strict_mode())
+}
+
+/// Post several GraphQL query bodies as one batched request.
+///
+/// # Returns
+/// The raw response of every body in the order they were given.
+pub(crate) fn post_graphql_batch<U: IntoUrl>(
+    client: &Client,
+    url: U,
+    bodies: &[Value],
+) -> Result<Vec<Value>, PostError> {
+    let value: Value = client.post(url).json(bodies).send()?.json()?;
+    match value {
+        Value::Array(responses) if responses.len() == bodies.len() => {
+            Ok(responses)
+        }
+        _ => Err(PostError::UnexpectedResponse {
+            operation: "batch",
+            path: "$".to_string(),
+            message: format!("expected an array of {} responses", bodies.len()),
+        }),
+    }
+}
+
+/// Decode the raw response to a query of type `Q`.
+pub(crate) fn decode_response<Q: GraphQLQuery>(
+    operation: &'static str,
+    query: &'static str,
+    value: &Value,
+    strict: bool,
+) -> Result<Response<Q::ResponseData>, PostError> {
+    let unexpected = |path: String, message: String| {
+        Err(PostError::UnexpectedResponse {
+            operation,
+            path,
+            message,
+        })
+    };
+    if strict {
+        if let Some(path) = find_null(value, "responseResult", "$") {
+            return unexpected(path, "responseResult is null".to_string());
+        }
+        let data = value.get("data").unwrap_or(&Value::Null);
+        if query.contains("responseResult")
+            && value.get("errors").is_none()
+            && !data.is_null()
+            && find_key(data, "responseResult").is_none()
+        {
+            return unexpected(
+                "$.data".to_string(),
+                "responseResult is missing".to_string(),
+            );
+        }
+    }
+    serde_path_to_error::deserialize(value).or_else(|e| {
+        unexpected(format!("$.{}", e.path()), e.into_inner().to_string())
+    })
+}
+
+fn find_key<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(map) => map
+            .get(key)
+            .or_else(|| map.values().find_map(|v| find_key(v, key))),
+        Value::Array(array) => array.iter().find_map(|v| find_key(v, key)),
+        _ => None,
+    }
+}
+
+fn find_null(value: &Value, key: &str, path: &str) -> Option<String> {
+    match value {
+        Value::Object(map) => map.iter().find_map(|(k, v)| {
+            let path = format!("{}.{}", path, k);
+            if k == key && v.is_null() {
+                Some(path)
+            } else {
+                find_null(v, key, &path)
+            }
+        }),
+        Value::Array(array) => array
+            .iter()
+            .enumerate()
+            .find_map(|(i, v)| find_null(v, key, &format!("{}[{}]", path, i))),
+        _ => None,
+    }
+}
+
 pub(crate) trait UnknownError {
     fn unknown_error_code(code: i64, message: String) -> Self;
     fn unknown_error_message(message: String) -> Self;
+    fn request_failed(message: String) -> Self;
     fn unknown_error() -> Self;
 }
 
@@ -38,6 +437,15 @@ pub(crate) trait KnownErrorCodes {
     fn is_known_error_code(code: i64) -> bool;
 }
 
+/// Turn the error of posting a query into the error of the operation,
+/// keeping failed requests apart from unexpected responses.
+pub(crate) fn classify_post_error<E: UnknownError>(error: PostError) -> E {
+    match error {
+        PostError::Request(e) => E::request_failed(e.to_string()),
+        e => E::unknown_error_message(e.to_string()),
+    }
+}
+
 pub(crate) fn classify_response_error<E: UnknownError + From<i64>>(
     response_errors: Option<Vec<graphql_client::Error>>,
 ) -> E {
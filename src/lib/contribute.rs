@@ -1,9 +1,11 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::common::{classify_response_error, Date, UnknownError};
+use crate::common::{
+    classify_post_error, classify_response_error, post_graphql, Date,
+    UnknownError,
+};
 
 #[derive(Clone, Error, Debug, PartialEq)]
 pub enum ContributeError {
@@ -11,6 +13,8 @@ pub enum ContributeError {
     UnknownErrorCode { code: i64, message: String },
     #[error("Unknown response error: {message}")]
     UnknownErrorMessage { message: String },
+    #[error("Request failed: {message}")]
+    RequestFailed { message: String },
     #[error("Unknown response error.")]
     UnknownError,
 }
@@ -31,12 +35,15 @@ impl UnknownError for ContributeError {
     fn unknown_error_message(message: String) -> Self {
         ContributeError::UnknownErrorMessage { message }
     }
+    fn request_failed(message: String) -> Self {
+        ContributeError::RequestFailed { message }
+    }
     fn unknown_error() -> Self {
         ContributeError::UnknownError
     }
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Contributor {
     pub id: String,
     pub source: String,
@@ -58,12 +65,12 @@ pub mod contributor_list {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub contribute: Option<Contribute>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Contribute {
         pub contributors: Option<Vec<Option<Contributor>>>,
     }
@@ -92,9 +99,7 @@ pub fn contributor_list(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(ContributeError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if response_body.data.is_some() {
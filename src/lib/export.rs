@@ -0,0 +1,518 @@
+use regex::{Captures, Regex};
+use reqwest::blocking::Client;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+
+use crate::asset::{
+    asset_download, asset_list_recursive, AssetError, AssetKind,
+};
+use crate::batch::page_get_many;
+use crate::common::{
+    concurrency, is_cancelled, run_parallel, CancellationToken,
+};
+use crate::page::{page_list, Page, PageEditor, PageError};
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum ExportError {
+    #[error(transparent)]
+    Page(#[from] PageError),
+    #[error(transparent)]
+    Asset(#[from] AssetError),
+    #[error("Failed to write the export: {0}")]
+    Io(String),
+    #[error("The export was cancelled.")]
+    Cancelled,
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(e: std::io::Error) -> Self {
+        ExportError::Io(e.to_string())
+    }
+}
+
+/// What an export wrote.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExportSummary {
+    pub pages: usize,
+    pub assets: usize,
+}
+
+/// Relative link from one file to another, both given relative to the
+/// export directory, like `../images/logo.png`.
+pub fn relative_link(from: &str, to: &str) -> String {
+    let from_dir: Vec<&str> = match from.rsplit_once('/') {
+        Some((dir, _)) => dir.split('/').collect(),
+        None => Vec::new(),
+    };
+    let to: Vec<&str> = to.split('/').collect();
+    let common = from_dir
+        .iter()
+        .zip(&to)
+        .take_while(|(a, b)| a == b)
+        .count()
+        .min(to.len() - 1);
+    let mut link = "../".repeat(from_dir.len() - common);
+    link += &to[common..].join("/");
+    link
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html_file(page: &Page) -> String {
+    format!("{}/{}.html", page.locale, page.path.trim_matches('/'))
+}
+
+/// What an absolute wiki link points to.
+enum LinkTarget {
+    Page { locale: String, path: String },
+    Asset(String),
+}
+
+/// Resolves absolute wiki links to the exported pages and assets.
+struct LinkResolver {
+    pages: HashSet<(String, String)>,
+    locales: HashSet<String>,
+    assets: HashSet<String>,
+}
+
+impl LinkResolver {
+    fn new(pages: &[Page], assets: HashSet<String>) -> Self {
+        Self {
+            pages: pages
+                .iter()
+                .map(|p| (p.locale.clone(), p.path.trim_matches('/').into()))
+                .collect(),
+            locales: pages.iter().map(|p| p.locale.clone()).collect(),
+            assets,
+        }
+    }
+
+    // the target of a link on a page of the given locale, links without a
+    // locale prefix point to the same locale
+    fn resolve(&self, locale: &str, target: &str) -> Option<LinkTarget> {
+        let target = target.trim_matches('/');
+        let page = |locale: &str, path: &str| {
+            let path = if path.is_empty() { "home" } else { path };
+            self.pages
+                .contains(&(locale.to_string(), path.to_string()))
+                .then(|| LinkTarget::Page {
+                    locale: locale.to_string(),
+                    path: path.to_string(),
+                })
+        };
+        let (first, rest) = target.split_once('/').unwrap_or((target, ""));
+        if self.locales.contains(first) {
+            if let Some(page) = page(first, rest) {
+                return Some(page);
+            }
+        }
+        if let Some(page) = page(locale, target) {
+            return Some(page);
+        }
+        self.assets
+            .contains(target)
+            .then(|| LinkTarget::Asset(target.to_string()))
+    }
+}
+
+fn render_page(
+    page: &Page,
+    resolver: &LinkResolver,
+    link_regex: &Regex,
+    used_assets: &mut BTreeSet<String>,
+) -> String {
+    let file = html_file(page);
+    let body = match &page.render {
+        Some(render) => render.clone(),
+        None => format!("<pre>{}</pre>", escape_html(&page.content)),
+    };
+    let body = link_regex.replace_all(&body, |caps: &Captures| {
+        let target = match resolver.resolve(&page.locale, &caps[2]) {
+            Some(LinkTarget::Page { locale, path }) => {
+                format!("{}/{}.html", locale, path)
+            }
+            Some(LinkTarget::Asset(asset)) => {
+                let target = format!("assets/{}", asset);
+                used_assets.insert(asset);
+                target
+            }
+            None => return caps[0].to_string(),
+        };
+        format!(
+            "{}=\"{}{}\"",
+            &caps[1],
+            relative_link(&file, &target),
+            &caps[3]
+        )
+    });
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n</head>\n<body>\n<nav><a href=\"{}\">Index</a></nav>\n\
+         <h1>{}</h1>\n{}\n</body>\n</html>\n",
+        escape_html(&page.locale),
+        escape_html(&page.title),
+        relative_link(&file, "index.html"),
+        escape_html(&page.title),
+        body
+    )
+}
+
+fn render_index(pages: &[Page]) -> String {
+    let mut list = String::new();
+    let mut locale = None;
+    for page in pages {
+        if locale != Some(&page.locale) {
+            if locale.is_some() {
+                list += "</ul>\n";
+            }
+            list += &format!("<h2>{}</h2>\n<ul>\n", escape_html(&page.locale));
+            locale = Some(&page.locale);
+        }
+        list += &format!(
+            "<li><a href=\"{}\">{}</a> <small>{}</small></li>\n",
+            html_file(page),
+            escape_html(&page.title),
+            escape_html(&page.path)
+        );
+    }
+    if locale.is_some() {
+        list += "</ul>\n";
+    }
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Index</title>\n</head>\n<body>\n<h1>Index</h1>\n{}</body>\n\
+         </html>\n",
+        list
+    )
+}
+
+// fetch all published pages sorted by locale and path, and a resolver for
+// links to them and to the assets
+fn fetch_published(
+    client: &Client,
+    url: &str,
+    cancel: Option<&CancellationToken>,
+) -> Result<(Vec<Page>, LinkResolver), ExportError> {
+    let graphql_url = format!("{}/graphql", url);
+    let ids: Vec<_> = page_list(
+        client,
+        &graphql_url,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )?
+    .into_iter()
+    .filter(|page| page.is_published)
+    .map(|page| page.id)
+    .collect();
+    let mut pages = page_get_many(client, &graphql_url, &ids, cancel)?;
+    pages.sort_by(|a, b| (&a.locale, &a.path).cmp(&(&b.locale, &b.path)));
+    let assets = asset_list_recursive(client, &graphql_url, 0, AssetKind::ALL)?
+        .iter()
+        .map(|asset| asset.path())
+        .collect();
+    let resolver = LinkResolver::new(&pages, assets);
+    Ok((pages, resolver))
+}
+
+fn write_file(path: &Path, data: &[u8]) -> Result<(), ExportError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, data)?;
+    Ok(())
+}
+
+fn download_assets(
+    client: &Client,
+    url: &str,
+    dir: &Path,
+    assets: &BTreeSet<String>,
+    cancel: Option<&CancellationToken>,
+) -> Result<(), ExportError> {
+    let assets: Vec<&String> = assets.iter().collect();
+    let results = run_parallel(&assets, concurrency(), cancel, |asset| {
+        let data = asset_download(client, url, asset.to_string())?;
+        write_file(&dir.join(asset), &data)
+    });
+    for result in results {
+        result.ok_or(ExportError::Cancelled)??;
+    }
+    Ok(())
+}
+
+/// Export all published pages as a static HTML site.
+///
+/// Every page is written with its rendered HTML to
+/// `<locale>/<path>.html`, the assets it references to
+/// `assets/<path>` and an `index.html` links all pages. Absolute links to
+/// pages and assets are rewritten to relative ones so the site can be
+/// browsed offline.
+///
+/// # Arguments
+/// * `url` - The base URL of the Wiki.js instance, not the GraphQL endpoint.
+///
+/// # Returns
+/// A Result containing either what was exported or an export error.
+pub fn export_html(
+    client: &Client,
+    url: &str,
+    dir: &Path,
+    cancel: Option<&CancellationToken>,
+) -> Result<ExportSummary, ExportError> {
+    let (pages, resolver) = fetch_published(client, url, cancel)?;
+    // the pattern is constant and valid
+    let link_regex =
+        Regex::new(r##"\b(href|src)="/([^"#?]*)([^"]*)""##).unwrap();
+
+    let mut used_assets = BTreeSet::new();
+    for page in &pages {
+        if is_cancelled(cancel) {
+            return Err(ExportError::Cancelled);
+        }
+        write_file(
+            &dir.join(html_file(page)),
+            render_page(page, &resolver, &link_regex, &mut used_assets)
+                .as_bytes(),
+        )?;
+    }
+    write_file(&dir.join("index.html"), render_index(&pages).as_bytes())?;
+    download_assets(client, url, &dir.join("assets"), &used_assets, cancel)?;
+    Ok(ExportSummary {
+        pages: pages.len(),
+        assets: used_assets.len(),
+    })
+}
+
+/// Folder layouts of [`export_vault`](fn.export_vault.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VaultFormat {
+    /// Hugo site with a `content/<locale>` dir per language, links as
+    /// `ref` shortcodes and assets in `static`.
+    Hugo,
+    /// Obsidian vault with a folder per locale, links as wikilinks and
+    /// assets in `assets`.
+    Obsidian,
+}
+
+impl FromStr for VaultFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hugo" => Ok(VaultFormat::Hugo),
+            "obsidian" => Ok(VaultFormat::Obsidian),
+            _ => Err(format!("unknown vault format: {}", s)),
+        }
+    }
+}
+
+impl Display for VaultFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VaultFormat::Hugo => write!(f, "hugo"),
+            VaultFormat::Obsidian => write!(f, "obsidian"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct HugoFrontMatter<'a> {
+    title: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    description: &'a str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<&'a str>,
+    date: &'a str,
+    lastmod: &'a str,
+    draft: bool,
+}
+
+// hugo uses _index.md for the page of a section, the home page included
+fn hugo_path(path: &str) -> &str {
+    if path == "home" {
+        "_index"
+    } else {
+        path
+    }
+}
+
+fn vault_file(format: VaultFormat, page: &Page) -> String {
+    let path = page.path.trim_matches('/');
+    let extension = page.editor.file_extension();
+    match format {
+        VaultFormat::Hugo => {
+            format!("content/{}/{}.{}", page.locale, hugo_path(path), extension)
+        }
+        VaultFormat::Obsidian => {
+            format!("{}/{}.{}", page.locale, path, extension)
+        }
+    }
+}
+
+// convert the absolute links of a markdown page to the link style of a
+// vault format, collecting the linked assets
+fn convert_links(
+    format: VaultFormat,
+    page: &Page,
+    resolver: &LinkResolver,
+    link_regex: &Regex,
+    used_assets: &mut BTreeSet<String>,
+) -> String {
+    link_regex
+        .replace_all(&page.content, |caps: &Captures| {
+            let bang = &caps[1];
+            let text = &caps[2];
+            let fragment = caps.get(4).map_or("", |m| m.as_str());
+            let title = caps.get(5).map_or("", |m| m.as_str());
+            match (format, resolver.resolve(&page.locale, &caps[3])) {
+                (_, None) => caps[0].to_string(),
+                (
+                    VaultFormat::Hugo,
+                    Some(LinkTarget::Page { locale, path }),
+                ) => {
+                    let path = format!("/{}.md{}", hugo_path(&path), fragment);
+                    let shortcode = if locale == page.locale {
+                        format!("{{{{< ref \"{}\" >}}}}", path)
+                    } else {
+                        format!(
+                            "{{{{< ref path=\"{}\" lang=\"{}\" >}}}}",
+                            path, locale
+                        )
+                    };
+                    format!("{}[{}]({}{})", bang, text, shortcode, title)
+                }
+                (VaultFormat::Hugo, Some(LinkTarget::Asset(asset))) => {
+                    used_assets.insert(asset);
+                    caps[0].to_string()
+                }
+                (
+                    VaultFormat::Obsidian,
+                    Some(LinkTarget::Page { locale, path }),
+                ) => {
+                    let link = format!("{}/{}{}", locale, path, fragment);
+                    if text.is_empty() {
+                        format!("[[{}]]", link)
+                    } else {
+                        format!("[[{}|{}]]", link, text)
+                    }
+                }
+                (VaultFormat::Obsidian, Some(LinkTarget::Asset(asset))) => {
+                    let link = format!("assets/{}", asset);
+                    used_assets.insert(asset);
+                    if !bang.is_empty() {
+                        format!("![[{}]]", link)
+                    } else if text.is_empty() {
+                        format!("[[{}]]", link)
+                    } else {
+                        format!("[[{}|{}]]", link, text)
+                    }
+                }
+            }
+        })
+        .into_owned()
+}
+
+fn render_vault_page(
+    format: VaultFormat,
+    page: &Page,
+    content: String,
+) -> String {
+    match format {
+        VaultFormat::Hugo => {
+            let front_matter = HugoFrontMatter {
+                title: &page.title,
+                description: &page.description,
+                tags: page
+                    .tags
+                    .iter()
+                    .flatten()
+                    .map(|t| t.tag.as_str())
+                    .collect(),
+                date: &page.created_at,
+                lastmod: &page.updated_at,
+                draft: !page.is_published,
+            };
+            // the front matter only contains strings, bools and lists
+            let yaml = serde_yaml::to_string(&front_matter).unwrap();
+            format!("---\n{}---\n{}", yaml, content)
+        }
+        VaultFormat::Obsidian => Page {
+            content,
+            ..page.clone()
+        }
+        .to_markdown_with_frontmatter(),
+    }
+}
+
+/// Export all published pages as markdown files with front matter, laid out
+/// as a Hugo content dir or an Obsidian vault.
+///
+/// Absolute links between markdown pages are converted to `ref` shortcodes
+/// for Hugo or wikilinks for Obsidian, and the linked assets are downloaded
+/// next to the pages.
+///
+/// # Arguments
+/// * `url` - The base URL of the Wiki.js instance, not the GraphQL endpoint.
+///
+/// # Returns
+/// A Result containing either what was exported or an export error.
+pub fn export_vault(
+    client: &Client,
+    url: &str,
+    dir: &Path,
+    format: VaultFormat,
+    cancel: Option<&CancellationToken>,
+) -> Result<ExportSummary, ExportError> {
+    let (pages, resolver) = fetch_published(client, url, cancel)?;
+    // the pattern is constant and valid
+    let link_regex = Regex::new(
+        r#"(!?)\[([^\]]*)\]\(/([^)\s#?]*)([#?][^)\s]*)?(\s+"[^"]*")?\)"#,
+    )
+    .unwrap();
+
+    let mut used_assets = BTreeSet::new();
+    for page in &pages {
+        if is_cancelled(cancel) {
+            return Err(ExportError::Cancelled);
+        }
+        let content = if page.editor == PageEditor::Markdown {
+            convert_links(
+                format,
+                page,
+                &resolver,
+                &link_regex,
+                &mut used_assets,
+            )
+        } else {
+            page.content.clone()
+        };
+        write_file(
+            &dir.join(vault_file(format, page)),
+            render_vault_page(format, page, content).as_bytes(),
+        )?;
+    }
+    let assets_dir = match format {
+        VaultFormat::Hugo => dir.join("static"),
+        VaultFormat::Obsidian => dir.join("assets"),
+    };
+    download_assets(client, url, &assets_dir, &used_assets, cancel)?;
+    Ok(ExportSummary {
+        pages: pages.len(),
+        assets: used_assets.len(),
+    })
+}
@@ -0,0 +1,169 @@
+use reqwest::blocking::Client;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::common::Int;
+use crate::page::{page_link_list, PageError, PageLinkItem};
+
+/// Output formats of [`PageGraph`](struct.PageGraph.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    Dot,
+    /// GraphML as understood by Gephi, yEd and others
+    GraphMl,
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dot" => Ok(GraphFormat::Dot),
+            "graphml" => Ok(GraphFormat::GraphMl),
+            _ => Err(format!("unknown graph format: {}", s)),
+        }
+    }
+}
+
+impl Display for GraphFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphFormat::Dot => Display::fmt("dot", f),
+            GraphFormat::GraphMl => Display::fmt("graphml", f),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageGraphNode {
+    pub id: Int,
+    pub path: String,
+    pub title: String,
+}
+
+/// Directed graph of the links between the pages of one locale.
+#[derive(Clone, Debug, Default)]
+pub struct PageGraph {
+    pub nodes: Vec<PageGraphNode>,
+    /// Links as pairs of source and target page id.
+    pub edges: Vec<(Int, Int)>,
+    /// Links to paths without a page, as source page id and target path.
+    pub dangling: Vec<(Int, String)>,
+}
+
+impl PageGraph {
+    /// Build the graph from the link list of a locale.
+    ///
+    /// Wiki.js reports link targets prefixed with their locale, links are
+    /// matched with and without that prefix.
+    pub fn from_links(items: Vec<PageLinkItem>, locale: &str) -> Self {
+        let ids: HashMap<String, Int> = items
+            .iter()
+            .map(|item| (item.path.clone(), item.id))
+            .collect();
+        let prefix = format!("{}/", locale);
+        let mut graph = PageGraph::default();
+        for item in items {
+            for link in item.links.into_iter().flatten() {
+                let path = link.strip_prefix(&prefix).unwrap_or(&link);
+                let path = path.trim_start_matches('/');
+                match ids.get(path) {
+                    Some(target) => graph.edges.push((item.id, *target)),
+                    None => graph.dangling.push((item.id, link.clone())),
+                }
+            }
+            graph.nodes.push(PageGraphNode {
+                id: item.id,
+                path: item.path,
+                title: item.title,
+            });
+        }
+        graph.edges.sort();
+        graph.edges.dedup();
+        graph
+    }
+
+    /// Serialize the graph in the given format.
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::GraphMl => self.to_graphml(),
+        }
+    }
+
+    /// Serialize the graph to Graphviz DOT.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph wiki {\n");
+        for node in &self.nodes {
+            out += &format!(
+                "  {} [label=\"{}\", tooltip=\"{}\"];\n",
+                node.id,
+                escape_dot(&node.path),
+                escape_dot(&node.title)
+            );
+        }
+        for (source, target) in &self.edges {
+            out += &format!("  {} -> {};\n", source, target);
+        }
+        out += "}\n";
+        out
+    }
+
+    /// Serialize the graph to GraphML.
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"path\" for=\"node\" attr.name=\"path\" ",
+            "attr.type=\"string\"/>\n",
+            "  <key id=\"title\" for=\"node\" attr.name=\"title\" ",
+            "attr.type=\"string\"/>\n",
+            "  <graph id=\"wiki\" edgedefault=\"directed\">\n",
+        ));
+        for node in &self.nodes {
+            out += &format!(
+                concat!(
+                    "    <node id=\"n{}\">\n",
+                    "      <data key=\"path\">{}</data>\n",
+                    "      <data key=\"title\">{}</data>\n",
+                    "    </node>\n",
+                ),
+                node.id,
+                escape_xml(&node.path),
+                escape_xml(&node.title)
+            );
+        }
+        for (source, target) in &self.edges {
+            out += &format!(
+                "    <edge source=\"n{}\" target=\"n{}\"/>\n",
+                source, target
+            );
+        }
+        out += "  </graph>\n</graphml>\n";
+        out
+    }
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Fetch the links of a locale and build the page graph.
+pub fn page_graph(
+    client: &Client,
+    url: &str,
+    locale: String,
+) -> Result<PageGraph, PageError> {
+    let items = page_link_list(client, url, locale.clone())?;
+    Ok(PageGraph::from_links(items, &locale))
+}
@@ -1,20 +1,26 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::str::FromStr;
 use thiserror::Error;
 
 use crate::common::{
-    classify_response_error, classify_response_status_error, Boolean, Date,
+    classify_post_error, classify_response_error,
+    classify_response_status_error, post_graphql, random_id, Boolean, Date,
     Int, KnownErrorCodes, ResponseStatus, UnknownError,
 };
-use crate::user::UserMinimal;
+use crate::user::{user_list, UserMinimal};
 
 #[derive(Clone, Error, Debug, PartialEq)]
 pub enum GroupError {
+    #[error("No user with the email address {email}.")]
+    UserNotFound { email: String },
     #[error("Unknown response error code: {code}: {message}")]
     UnknownErrorCode { code: i64, message: String },
     #[error("Unknown response error: {message}")]
     UnknownErrorMessage { message: String },
+    #[error("Request failed: {message}")]
+    RequestFailed { message: String },
     #[error("Unknown response error.")]
     UnknownError,
 }
@@ -35,6 +41,9 @@ impl UnknownError for GroupError {
     fn unknown_error_message(message: String) -> Self {
         GroupError::UnknownErrorMessage { message }
     }
+    fn request_failed(message: String) -> Self {
+        GroupError::RequestFailed { message }
+    }
     fn unknown_error() -> Self {
         GroupError::UnknownError
     }
@@ -50,14 +59,14 @@ impl KnownErrorCodes for GroupError {
     }
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct GroupResponse {
     #[serde(rename = "responseResult")]
     pub response_result: ResponseStatus,
     pub group: Option<Group>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct GroupMinimal {
     pub id: Int,
     pub name: String,
@@ -71,7 +80,7 @@ pub struct GroupMinimal {
     pub updated_at: Date,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Group {
     pub id: Int,
     pub name: String,
@@ -80,6 +89,7 @@ pub struct Group {
     #[serde(rename = "redirectOnLogin")]
     pub redirect_on_login: Option<String>,
     pub permissions: Vec<String>,
+    #[serde(rename = "pageRules")]
     pub page_rules: Option<Vec<Option<PageRule>>>,
     pub users: Option<Vec<UserMinimal>>,
     #[serde(rename = "createdAt")]
@@ -88,7 +98,7 @@ pub struct Group {
     pub updated_at: Date,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct PageRule {
     pub id: String,
     pub deny: Boolean,
@@ -108,7 +118,7 @@ pub struct PageRuleInput {
     pub locales: Vec<String>,
 }
 
-#[derive(Clone, Deserialize, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
 pub enum PageRuleMatch {
     START,
     EXACT,
@@ -117,6 +127,200 @@ pub enum PageRuleMatch {
     TAG,
 }
 
+impl FromStr for PageRuleMatch {
+    type Err = String;
+
+    fn from_str(r#match: &str) -> Result<Self, Self::Err> {
+        match r#match.to_lowercase().as_str() {
+            "start" => Ok(PageRuleMatch::START),
+            "exact" => Ok(PageRuleMatch::EXACT),
+            "end" => Ok(PageRuleMatch::END),
+            "regex" => Ok(PageRuleMatch::REGEX),
+            "tag" => Ok(PageRuleMatch::TAG),
+            _ => Err(format!("unknown page rule match {}", r#match)),
+        }
+    }
+}
+
+impl PageRule {
+    /// The rule as input for an update, keeping its id.
+    pub fn to_input(&self) -> PageRuleInput {
+        PageRuleInput {
+            id: self.id.clone(),
+            deny: self.deny,
+            r#match: self.r#match.clone(),
+            roles: self.roles.clone(),
+            path: self.path.clone(),
+            locales: self.locales.clone(),
+        }
+    }
+}
+
+/// A permission of a group, also used as the roles of a page rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Permission {
+    ReadPages,
+    ReadSource,
+    ReadHistory,
+    ReadAssets,
+    ReadComments,
+    WritePages,
+    ManagePages,
+    DeletePages,
+    WriteStyles,
+    WriteScripts,
+    WriteComments,
+    ManageComments,
+    WriteAssets,
+    ManageAssets,
+    WriteUsers,
+    ManageUsers,
+    WriteGroups,
+    ManageGroups,
+    ManageNavigation,
+    ManageTheme,
+    ManageApi,
+    ManageSystem,
+}
+
+impl Permission {
+    /// All permissions, in the order of the Wiki.js admin.
+    pub const ALL: [Permission; 22] = [
+        Permission::ReadPages,
+        Permission::ReadSource,
+        Permission::ReadHistory,
+        Permission::ReadAssets,
+        Permission::ReadComments,
+        Permission::WritePages,
+        Permission::ManagePages,
+        Permission::DeletePages,
+        Permission::WriteStyles,
+        Permission::WriteScripts,
+        Permission::WriteComments,
+        Permission::ManageComments,
+        Permission::WriteAssets,
+        Permission::ManageAssets,
+        Permission::WriteUsers,
+        Permission::ManageUsers,
+        Permission::WriteGroups,
+        Permission::ManageGroups,
+        Permission::ManageNavigation,
+        Permission::ManageTheme,
+        Permission::ManageApi,
+        Permission::ManageSystem,
+    ];
+
+    /// The permission string as used by the API, like `read:pages`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ReadPages => "read:pages",
+            Permission::ReadSource => "read:source",
+            Permission::ReadHistory => "read:history",
+            Permission::ReadAssets => "read:assets",
+            Permission::ReadComments => "read:comments",
+            Permission::WritePages => "write:pages",
+            Permission::ManagePages => "manage:pages",
+            Permission::DeletePages => "delete:pages",
+            Permission::WriteStyles => "write:styles",
+            Permission::WriteScripts => "write:scripts",
+            Permission::WriteComments => "write:comments",
+            Permission::ManageComments => "manage:comments",
+            Permission::WriteAssets => "write:assets",
+            Permission::ManageAssets => "manage:assets",
+            Permission::WriteUsers => "write:users",
+            Permission::ManageUsers => "manage:users",
+            Permission::WriteGroups => "write:groups",
+            Permission::ManageGroups => "manage:groups",
+            Permission::ManageNavigation => "manage:navigation",
+            Permission::ManageTheme => "manage:theme",
+            Permission::ManageApi => "manage:api",
+            Permission::ManageSystem => "manage:system",
+        }
+    }
+}
+
+impl FromStr for Permission {
+    type Err = String;
+
+    fn from_str(permission: &str) -> Result<Self, Self::Err> {
+        Permission::ALL
+            .into_iter()
+            .find(|p| p.as_str() == permission)
+            .ok_or(format!("unknown permission {}", permission))
+    }
+}
+
+impl Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self.as_str(), f)
+    }
+}
+
+/// Builder for a new page rule. Without further settings the rule allows
+/// reading pages below the path in all locales.
+#[derive(Clone, Debug)]
+pub struct PageRuleBuilder {
+    rule: PageRuleInput,
+}
+
+impl PageRuleBuilder {
+    /// Start a rule for pages starting with the path.
+    ///
+    /// # Arguments
+    /// * `path` - The path, without leading slash or locale.
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            rule: PageRuleInput {
+                id: random_id(),
+                deny: false,
+                r#match: PageRuleMatch::START,
+                roles: Vec::new(),
+                path: path.into(),
+                locales: Vec::new(),
+            },
+        }
+    }
+
+    /// How the path is matched against the page path.
+    pub fn r#match(mut self, r#match: PageRuleMatch) -> Self {
+        self.rule.r#match = r#match;
+        self
+    }
+
+    /// Deny the roles instead of allowing them.
+    pub fn deny(mut self, deny: bool) -> Self {
+        self.rule.deny = deny;
+        self
+    }
+
+    /// Add a role to the rule.
+    pub fn role(mut self, role: Permission) -> Self {
+        if !self.rule.roles.iter().any(|r| r == role.as_str()) {
+            self.rule.roles.push(role.as_str().to_string());
+        }
+        self
+    }
+
+    /// Add several roles to the rule.
+    pub fn roles(self, roles: impl IntoIterator<Item = Permission>) -> Self {
+        roles.into_iter().fold(self, PageRuleBuilder::role)
+    }
+
+    /// Restrict the rule to a locale, it applies to all locales otherwise.
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.rule.locales.push(locale.into());
+        self
+    }
+
+    pub fn build(self) -> PageRuleInput {
+        let mut rule = self.rule;
+        if rule.roles.is_empty() {
+            rule.roles.push(Permission::ReadPages.as_str().to_string());
+        }
+        rule
+    }
+}
+
 pub mod group_list {
     use super::*;
 
@@ -134,12 +338,12 @@ pub mod group_list {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub groups: Option<Groups>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Groups {
         pub list: Option<Vec<Option<GroupMinimal>>>,
     }
@@ -169,9 +373,7 @@ pub fn group_list(
     let response =
         post_graphql::<group_list::GroupList, _>(client, url, variables);
     if response.is_err() {
-        return Err(GroupError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -190,7 +392,7 @@ pub mod group_get {
     pub struct GroupGet;
 
     pub const OPERATION_NAME: &str = "GroupGet";
-    pub const QUERY : & str = "query GroupGet($id: Int!) {\n  groups {\n    single(id: $id) {\n      id\n      name\n      isSystem\n      redirectOnLogin\n      permissions\n      pageRules\n      users\n      createdAt\n      updatedAt\n    }\n  }\n}\n" ;
+    pub const QUERY : & str = "query GroupGet($id: Int!) {\n  groups {\n    single(id: $id) {\n      id\n      name\n      isSystem\n      redirectOnLogin\n      permissions\n      pageRules {\n        id\n        deny\n        match\n        roles\n        path\n        locales\n      }\n      users {\n        id\n        name\n        email\n        providerKey\n        isSystem\n        isActive\n        createdAt\n        lastLoginAt\n      }\n      createdAt\n      updatedAt\n    }\n  }\n}\n" ;
 
     #[derive(Serialize)]
     pub struct Variables {
@@ -199,12 +401,12 @@ pub mod group_get {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub groups: Option<Groups>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Groups {
         pub single: Option<Group>,
     }
@@ -233,9 +435,7 @@ pub fn group_get(
     let response =
         post_graphql::<group_get::GroupGet, _>(client, url, variables);
     if response.is_err() {
-        return Err(GroupError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -263,12 +463,12 @@ pub mod group_create {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub groups: Option<Groups>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Groups {
         pub create: Option<GroupResponse>,
     }
@@ -297,9 +497,7 @@ pub fn group_create(
     let response =
         post_graphql::<group_create::GroupCreate, _>(client, url, variables);
     if response.is_err() {
-        return Err(GroupError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -343,17 +541,17 @@ pub mod group_update {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub groups: Option<Groups>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Groups {
         pub update: Option<Update>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Update {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -393,9 +591,7 @@ pub fn group_update(
     let response =
         post_graphql::<group_update::GroupUpdate, _>(client, url, variables);
     if response.is_err() {
-        return Err(GroupError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -433,17 +629,17 @@ pub mod group_delete {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub groups: Option<Groups>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Groups {
         pub delete: Option<Delete>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Delete {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -473,9 +669,7 @@ pub fn group_delete(
     let response =
         post_graphql::<group_delete::GroupDelete, _>(client, url, variables);
     if response.is_err() {
-        return Err(GroupError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -516,18 +710,18 @@ pub mod group_user_assign {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub groups: Option<Groups>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Groups {
         #[serde(rename = "assignUser")]
         pub assign_user: Option<AssignUser>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct AssignUser {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -559,9 +753,7 @@ pub fn group_user_assign(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(GroupError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -602,18 +794,18 @@ pub mod group_user_unassign {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub groups: Option<Groups>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Groups {
         #[serde(rename = "unassignUser")]
         pub unassign_user: Option<UnassignUser>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct UnassignUser {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -645,9 +837,7 @@ pub fn group_user_unassign(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(GroupError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -669,3 +859,141 @@ pub fn group_user_unassign(
     }
     Err(classify_response_error::<GroupError>(response_body.errors))
 }
+
+/// Load a group, edit its permissions and page rules and write it back.
+pub fn group_rules_edit<F>(
+    client: &Client,
+    url: &str,
+    id: Int,
+    edit: F,
+) -> Result<(), GroupError>
+where
+    F: FnOnce(&mut Vec<String>, &mut Vec<PageRuleInput>),
+{
+    let group = group_get(client, url, id)?;
+    let mut permissions = group.permissions;
+    let mut page_rules: Vec<PageRuleInput> = group
+        .page_rules
+        .iter()
+        .flatten()
+        .flatten()
+        .map(PageRule::to_input)
+        .collect();
+    edit(&mut permissions, &mut page_rules);
+    group_update(
+        client,
+        url,
+        id,
+        group.name,
+        group.redirect_on_login.unwrap_or_default(),
+        permissions,
+        page_rules,
+    )
+}
+
+/// Grant a permission to a group, keeping its other permissions.
+pub fn group_permission_add(
+    client: &Client,
+    url: &str,
+    id: Int,
+    permission: Permission,
+) -> Result<(), GroupError> {
+    group_rules_edit(client, url, id, |permissions, _| {
+        if !permissions.iter().any(|p| p == permission.as_str()) {
+            permissions.push(permission.to_string());
+        }
+    })
+}
+
+/// Revoke a permission from a group, keeping its other permissions.
+pub fn group_permission_remove(
+    client: &Client,
+    url: &str,
+    id: Int,
+    permission: Permission,
+) -> Result<(), GroupError> {
+    group_rules_edit(client, url, id, |permissions, _| {
+        permissions.retain(|p| p != permission.as_str())
+    })
+}
+
+/// A change made to the members of a group.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MembershipChange {
+    Assigned { user_id: Int, email: String },
+    Unassigned { user_id: Int, email: String },
+}
+
+/// The changes turning the current members of a group into the desired
+/// ones. Emails are compared ignoring case.
+///
+/// # Arguments
+/// * `members` - The current members of the group.
+/// * `users` - All users, to look up the desired emails.
+/// * `desired_emails` - The emails of the users who should be members.
+pub fn membership_changes(
+    members: &[UserMinimal],
+    users: &[UserMinimal],
+    desired_emails: &[String],
+) -> Result<Vec<MembershipChange>, GroupError> {
+    let desired: Vec<String> =
+        desired_emails.iter().map(|e| e.to_lowercase()).collect();
+    let mut changes = Vec::new();
+    for email in &desired {
+        if members.iter().any(|m| &m.email.to_lowercase() == email) {
+            continue;
+        }
+        let user = users
+            .iter()
+            .find(|u| &u.email.to_lowercase() == email)
+            .ok_or(GroupError::UserNotFound {
+                email: email.clone(),
+            })?;
+        if !changes.iter().any(|c| {
+            matches!(c, MembershipChange::Assigned { user_id, .. }
+                if *user_id == user.id)
+        }) {
+            changes.push(MembershipChange::Assigned {
+                user_id: user.id,
+                email: user.email.clone(),
+            });
+        }
+    }
+    for member in members {
+        if !desired.contains(&member.email.to_lowercase()) {
+            changes.push(MembershipChange::Unassigned {
+                user_id: member.id,
+                email: member.email.clone(),
+            });
+        }
+    }
+    Ok(changes)
+}
+
+/// Assign and unassign users so the group has exactly the desired members.
+/// Nothing is changed if one of the emails does not belong to a user.
+pub fn group_membership_sync(
+    client: &Client,
+    url: &str,
+    group_id: Int,
+    desired_emails: &[String],
+) -> Result<Vec<MembershipChange>, GroupError> {
+    let members = group_get(client, url, group_id)?.users.unwrap_or_default();
+    let users = user_list(client, url, None, None).map_err(|e| {
+        GroupError::UnknownErrorMessage {
+            message: e.to_string(),
+        }
+    })?;
+    let changes = membership_changes(&members, &users, desired_emails)?;
+    for change in &changes {
+        match change {
+            MembershipChange::Assigned { user_id, .. } => {
+                group_user_assign(client, url, group_id, *user_id)?
+            }
+            MembershipChange::Unassigned { user_id, .. } => {
+                group_user_unassign(client, url, group_id, *user_id)?
+            }
+        }
+    }
+    Ok(changes)
+}
@@ -0,0 +1,350 @@
+use reqwest::blocking::Client;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+use thiserror::Error;
+
+use crate::common::{concurrency, run_parallel, CancellationToken};
+use crate::page::{
+    page_create, page_get_by_path, page_update, parse_frontmatter_markdown,
+    PageEditor, PageError, PageFrontMatter,
+};
+
+/// Structs and internal implementation to import a MediaWiki XML dump.
+pub mod mediawiki;
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum ImportError {
+    #[error(transparent)]
+    Page(#[from] PageError),
+    #[error("Failed to read the import directory: {0}")]
+    Io(String),
+    #[error("Invalid MediaWiki XML dump: {0}")]
+    InvalidDump(String),
+}
+
+impl From<std::io::Error> for ImportError {
+    fn from(e: std::io::Error) -> Self {
+        ImportError::Io(e.to_string())
+    }
+}
+
+/// What to do when a page already exists at the path of a file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ImportCollision {
+    /// Leave the existing page alone.
+    #[default]
+    Skip,
+    /// Replace the content and all metadata, metadata missing from the
+    /// front matter is reset like for a new page.
+    Overwrite,
+    /// Replace the content and the metadata set in the front matter, and
+    /// keep the rest.
+    Update,
+}
+
+impl FromStr for ImportCollision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "skip" => Ok(ImportCollision::Skip),
+            "overwrite" => Ok(ImportCollision::Overwrite),
+            "update" => Ok(ImportCollision::Update),
+            _ => Err(format!("unknown collision handling: {}", s)),
+        }
+    }
+}
+
+/// Settings of [`import_dir`](fn.import_dir.html).
+#[derive(Clone, Debug)]
+pub struct ImportOptions {
+    /// Locale of the pages whose front matter does not set one.
+    pub locale: String,
+    pub collision: ImportCollision,
+    /// Only look up which pages exist and report what would be done,
+    /// without creating or updating any.
+    pub dry_run: bool,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            locale: "en".to_string(),
+            collision: ImportCollision::default(),
+            dry_run: false,
+        }
+    }
+}
+
+/// What happened to an imported file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportAction {
+    Created,
+    Updated,
+    Skipped,
+}
+
+/// The outcome of importing one file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportOutcome {
+    /// The file relative to the imported directory.
+    pub file: String,
+    pub locale: String,
+    pub path: String,
+    pub result: Result<ImportAction, PageError>,
+}
+
+/// Wiki path of a markdown file relative to the imported directory.
+///
+/// The extension is dropped and `index.md` and `README.md` stand for their
+/// folder, so `docs/README.md` becomes `docs` and `index.md` at the top
+/// becomes the home page.
+pub fn import_path(file: &str) -> String {
+    let path = file.strip_suffix(".md").unwrap_or(file);
+    let path = match path.rsplit_once('/') {
+        Some((folder, "index" | "README")) => folder,
+        None if path == "index" || path == "README" => "home",
+        _ => path,
+    };
+    path.to_string()
+}
+
+fn scan_dir(
+    root: &Path,
+    dir: &Path,
+    files: &mut Vec<String>,
+) -> Result<(), ImportError> {
+    let mut entries = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            scan_dir(root, &path, files)?;
+        } else if path.extension().is_some_and(|e| e == "md") {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            files.push(
+                relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+            );
+        }
+    }
+    Ok(())
+}
+
+// create or update the page at a path according to the collision handling,
+// a dry run stops right before doing so
+#[allow(clippy::too_many_arguments)]
+fn import_file(
+    client: &Client,
+    url: &str,
+    path: &str,
+    locale: &str,
+    front_matter: PageFrontMatter,
+    content: String,
+    collision: ImportCollision,
+    dry_run: bool,
+) -> Result<ImportAction, PageError> {
+    let existing =
+        match page_get_by_path(client, url, path.to_string(), locale.into()) {
+            Ok(page) => Some(page),
+            Err(PageError::PageNotFound) => None,
+            Err(e) => return Err(e),
+        };
+    if dry_run {
+        return Ok(match (existing, collision) {
+            (None, _) => ImportAction::Created,
+            (Some(_), ImportCollision::Skip) => ImportAction::Skipped,
+            (Some(_), _) => ImportAction::Updated,
+        });
+    }
+    let tags = front_matter
+        .tags
+        .map(|tags| tags.into_iter().map(Some).collect::<Vec<_>>());
+    let Some(page) = existing else {
+        page_create(
+            client,
+            url,
+            content,
+            front_matter.description.unwrap_or_default(),
+            front_matter.editor.unwrap_or(PageEditor::Markdown),
+            front_matter.is_published.unwrap_or(true),
+            front_matter.is_private.unwrap_or(false),
+            locale.to_string(),
+            path.to_string(),
+            front_matter.publish_end_date,
+            front_matter.publish_start_date,
+            front_matter.script_css,
+            front_matter.script_js,
+            tags.unwrap_or_default(),
+            front_matter.title.unwrap_or_else(|| {
+                path.rsplit('/').next().unwrap_or(path).to_string()
+            }),
+        )?;
+        return Ok(ImportAction::Created);
+    };
+    match collision {
+        ImportCollision::Skip => Ok(ImportAction::Skipped),
+        ImportCollision::Overwrite => {
+            page_update(
+                client,
+                url,
+                page.id,
+                Some(content),
+                Some(front_matter.description.unwrap_or_default()),
+                Some(front_matter.editor.unwrap_or(PageEditor::Markdown)),
+                Some(front_matter.is_private.unwrap_or(false)),
+                Some(front_matter.is_published.unwrap_or(true)),
+                None,
+                None,
+                Some(front_matter.publish_end_date.unwrap_or_default()),
+                Some(front_matter.publish_start_date.unwrap_or_default()),
+                Some(front_matter.script_css.unwrap_or_default()),
+                Some(front_matter.script_js.unwrap_or_default()),
+                Some(tags.unwrap_or_default()),
+                Some(front_matter.title.unwrap_or_else(|| {
+                    path.rsplit('/').next().unwrap_or(path).to_string()
+                })),
+            )?;
+            Ok(ImportAction::Updated)
+        }
+        ImportCollision::Update => {
+            page_update(
+                client,
+                url,
+                page.id,
+                Some(content),
+                front_matter.description,
+                front_matter.editor,
+                front_matter.is_private,
+                front_matter.is_published,
+                None,
+                None,
+                front_matter.publish_end_date,
+                front_matter.publish_start_date,
+                front_matter.script_css,
+                front_matter.script_js,
+                tags,
+                front_matter.title,
+            )?;
+            Ok(ImportAction::Updated)
+        }
+    }
+}
+
+// import the items as many at once as the concurrency allows, reporting
+// every finished one to the progress callback
+fn import_parallel<T, O, F, P>(
+    items: &[T],
+    import: F,
+    progress: P,
+    cancel: Option<&CancellationToken>,
+) -> Vec<O>
+where
+    T: Sync,
+    O: Send,
+    F: Fn(&T) -> O + Sync,
+    P: FnMut(usize, usize, &O) + Send,
+{
+    let total = items.len();
+    let progress = Mutex::new((0, progress));
+    run_parallel(items, concurrency(), cancel, |item| {
+        let outcome = import(item);
+        let mut progress = progress.lock().unwrap();
+        progress.0 += 1;
+        let done = progress.0;
+        (progress.1)(done, total, &outcome);
+        outcome
+    })
+    .into_iter()
+    // the items skipped due to cancellation are left out
+    .flatten()
+    .collect()
+}
+
+/// Create pages from a directory tree of markdown files.
+///
+/// The wiki path of each file follows from its path relative to the
+/// directory, see [`import_path`](fn.import_path.html). Optional YAML front
+/// matter may set the title, tags, locale and the other page metadata, the
+/// title defaults to the last path segment.
+///
+/// The files are imported as many at once as the
+/// [`concurrency`](../common/fn.concurrency.html) allows.
+///
+/// # Arguments
+/// * `progress` - Called after each file with the number of finished
+///   files, the total number of files and the outcome.
+/// * `cancel` - Optional token to stop before the remaining files, which
+///   are left out of the result.
+///
+/// # Returns
+/// A Result containing either the outcome of every file or an import error
+/// if the directory could not be read.
+pub fn import_dir<F>(
+    client: &Client,
+    url: &str,
+    dir: &Path,
+    options: &ImportOptions,
+    progress: F,
+    cancel: Option<&CancellationToken>,
+) -> Result<Vec<ImportOutcome>, ImportError>
+where
+    F: FnMut(usize, usize, &ImportOutcome) + Send,
+{
+    let mut files = Vec::new();
+    scan_dir(dir, dir, &mut files)?;
+    let files = files
+        .into_iter()
+        .map(|file| {
+            let data = fs::read_to_string(dir.join(&file))?;
+            Ok((file, data))
+        })
+        .collect::<Result<Vec<_>, ImportError>>()?;
+    let outcomes = import_parallel(
+        &files,
+        |(file, data)| {
+            let file = file.clone();
+            let path = import_path(&file);
+            match parse_frontmatter_markdown(data) {
+                Ok((front_matter, content)) => {
+                    let locale = front_matter
+                        .locale
+                        .clone()
+                        .unwrap_or_else(|| options.locale.clone());
+                    ImportOutcome {
+                        result: import_file(
+                            client,
+                            url,
+                            &path,
+                            &locale,
+                            front_matter,
+                            content,
+                            options.collision,
+                            options.dry_run,
+                        ),
+                        file,
+                        locale,
+                        path,
+                    }
+                }
+                Err(e) => ImportOutcome {
+                    file,
+                    locale: options.locale.clone(),
+                    path,
+                    result: Err(e),
+                },
+            }
+        },
+        progress,
+        cancel,
+    );
+    Ok(outcomes)
+}
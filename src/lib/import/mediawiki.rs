@@ -0,0 +1,430 @@
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use regex::{Captures, Regex};
+use reqwest::blocking::Client;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use super::{
+    import_file, import_parallel, ImportAction, ImportCollision, ImportError,
+};
+use crate::common::{CancellationToken, Int};
+use crate::page::{PageError, PageFrontMatter};
+
+/// A page of a MediaWiki XML dump with the text of its latest revision.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MediaWikiPage {
+    pub title: String,
+    pub namespace: Int,
+    pub text: String,
+}
+
+/// The namespaces and pages of a MediaWiki XML dump.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MediaWikiDump {
+    /// Namespace keys to names, the main namespace 0 has an empty name.
+    pub namespaces: BTreeMap<Int, String>,
+    pub pages: Vec<MediaWikiPage>,
+}
+
+impl MediaWikiDump {
+    /// Wiki path of a page title like `Help:Getting started`.
+    ///
+    /// Pages of the main namespace go to the top level, the others below a
+    /// prefix named after their namespace, like `help/getting-started`.
+    pub fn path(&self, title: &str) -> String {
+        let (namespace, name) = self.split_title(title);
+        let name = slug(name);
+        match namespace {
+            Some(namespace) => format!("{}/{}", slug(namespace), name),
+            None => name,
+        }
+    }
+
+    // split the namespace name off a title, if it names a known namespace
+    fn split_title<'a>(&self, title: &'a str) -> (Option<&'a str>, &'a str) {
+        if let Some((namespace, name)) = title.split_once(':') {
+            if self.namespace_key(namespace).is_some_and(|key| key != 0) {
+                return (Some(namespace), name);
+            }
+        }
+        (None, title)
+    }
+
+    fn namespace_key(&self, name: &str) -> Option<Int> {
+        let name = name.trim().replace('_', " ");
+        self.namespaces
+            .iter()
+            .find(|(_, n)| n.eq_ignore_ascii_case(&name))
+            .map(|(key, _)| *key)
+    }
+}
+
+// lowercase and replace the characters wiki paths cannot contain
+fn slug(name: &str) -> String {
+    name.trim()
+        .split('/')
+        .map(|segment| {
+            segment
+                .trim()
+                .to_lowercase()
+                .chars()
+                .map(|c| match c {
+                    ' ' | '_' | '.' | '?' | '#' | '\\' => '-',
+                    c => c,
+                })
+                .collect::<String>()
+        })
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn xml_error(e: impl std::fmt::Display) -> ImportError {
+    ImportError::InvalidDump(e.to_string())
+}
+
+/// Parse a MediaWiki XML dump.
+///
+/// # Returns
+/// A Result containing either the dump or ImportError::InvalidDump.
+pub fn parse_dump(xml: &str) -> Result<MediaWikiDump, ImportError> {
+    let mut reader = Reader::from_str(xml);
+    let mut dump = MediaWikiDump::default();
+    // names of the open elements
+    let mut elements: Vec<String> = Vec::new();
+    let mut namespace_key = 0;
+    let mut text = String::new();
+    let mut page: Option<MediaWikiPage> = None;
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref())
+                    .to_string();
+                if name == "namespace" {
+                    namespace_key = e
+                        .try_get_attribute("key")
+                        .map_err(xml_error)?
+                        .and_then(|a| {
+                            String::from_utf8_lossy(&a.value).parse().ok()
+                        })
+                        .unwrap_or(0);
+                } else if name == "page" {
+                    page = Some(MediaWikiPage {
+                        title: String::new(),
+                        namespace: 0,
+                        text: String::new(),
+                    });
+                }
+                elements.push(name);
+                text.clear();
+            }
+            Event::Text(e) => text += &e.unescape().map_err(xml_error)?,
+            Event::CData(e) => {
+                text += &String::from_utf8_lossy(&e.into_inner());
+            }
+            Event::End(_) => {
+                let name = elements.pop().unwrap_or_default();
+                let parent = elements.last().map(String::as_str);
+                match (name.as_str(), parent, page.as_mut()) {
+                    ("namespace", Some("namespaces"), _) => {
+                        dump.namespaces.insert(namespace_key, text.clone());
+                    }
+                    ("title", Some("page"), Some(page)) => {
+                        page.title = text.clone();
+                    }
+                    ("ns", Some("page"), Some(page)) => {
+                        page.namespace = text.trim().parse().unwrap_or(0);
+                    }
+                    // revisions are in chronological order, keep the last
+                    ("text", Some("revision"), Some(page)) => {
+                        page.text = text.clone();
+                    }
+                    ("page", _, _) => dump.pages.extend(page.take()),
+                    _ => {}
+                }
+                text.clear();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    dump.namespaces.entry(0).or_default();
+    Ok(dump)
+}
+
+/// The result of converting wikitext to markdown.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MediaWikiConversion {
+    pub markdown: String,
+    /// The categories of the page, to be used as tags.
+    pub categories: Vec<String>,
+    /// Names of the constructs that were left as they are, like templates
+    /// and tables.
+    pub unconverted: BTreeSet<String>,
+}
+
+struct Converter {
+    heading: Regex,
+    internal_link: Regex,
+    external_link: Regex,
+    bold_italic: Regex,
+    bold: Regex,
+    italic: Regex,
+    nowiki: Regex,
+    magic_word: Regex,
+}
+
+impl Converter {
+    fn new() -> Self {
+        // the patterns are constant and valid
+        Self {
+            heading: Regex::new(r"^(={1,6})\s*(.*?)\s*={1,6}\s*$").unwrap(),
+            internal_link: Regex::new(r"\[\[([^\]|]+)(?:\|([^\]]*))?\]\]")
+                .unwrap(),
+            external_link: Regex::new(
+                r"\[((?:https?|ftp|mailto):[^\s\]]+)(?:\s+([^\]]*))?\]",
+            )
+            .unwrap(),
+            bold_italic: Regex::new(r"'''''(.+?)'''''").unwrap(),
+            bold: Regex::new(r"'''(.+?)'''").unwrap(),
+            italic: Regex::new(r"''(.+?)''").unwrap(),
+            nowiki: Regex::new(r"</?nowiki\s*/?>").unwrap(),
+            magic_word: Regex::new(r"__[A-Z]+__").unwrap(),
+        }
+    }
+
+    fn inline(
+        &self,
+        dump: &MediaWikiDump,
+        line: &str,
+        conversion: &mut MediaWikiConversion,
+    ) -> String {
+        if line.contains("{{") {
+            conversion.unconverted.insert("template".to_string());
+        }
+        if line.contains("<ref") {
+            conversion.unconverted.insert("reference".to_string());
+        }
+        let line = self.magic_word.replace_all(line, "");
+        let line = self.nowiki.replace_all(&line, "");
+        let line = self.internal_link.replace_all(&line, |caps: &Captures| {
+            let target = caps[1].trim();
+            let options = caps.get(2).map(|m| m.as_str());
+            // a leading colon links a category or file instead of using it
+            let linked = target.starts_with(':');
+            let target = target.trim_start_matches(':');
+            let (namespace, name) = match target.split_once(':') {
+                Some((namespace, name)) => {
+                    (dump.namespace_key(namespace), name)
+                }
+                None => (None, target),
+            };
+            match namespace {
+                Some(14) if !linked => {
+                    conversion.categories.push(name.trim().to_string());
+                    String::new()
+                }
+                Some(6) if !linked => {
+                    let caption = options
+                        .and_then(|o| o.rsplit('|').next())
+                        .unwrap_or_default();
+                    format!("![{}](/{})", caption, name.trim())
+                }
+                _ => {
+                    let (page, anchor) =
+                        target.split_once('#').unwrap_or((target, ""));
+                    let text = options.unwrap_or(target);
+                    let mut link = format!("/{}", dump.path(page));
+                    if !anchor.is_empty() {
+                        link += &format!("#{}", slug(anchor));
+                    }
+                    format!("[{}]({})", text, link)
+                }
+            }
+        });
+        let line =
+            self.external_link
+                .replace_all(&line, |caps: &Captures| match caps.get(2) {
+                    Some(text) => format!("[{}]({})", text.as_str(), &caps[1]),
+                    None => format!("<{}>", &caps[1]),
+                });
+        let line = self.bold_italic.replace_all(&line, "***$1***");
+        let line = self.bold.replace_all(&line, "**$1**");
+        self.italic.replace_all(&line, "*$1*").into_owned()
+    }
+}
+
+/// Convert MediaWiki wikitext to markdown, as far as possible.
+///
+/// Headings, emphasis, lists, links, images, categories, rules and
+/// preformatted text are converted. Templates, tables and references are
+/// kept as they are and named in the `unconverted` report.
+pub fn wikitext_to_markdown(
+    dump: &MediaWikiDump,
+    wikitext: &str,
+) -> MediaWikiConversion {
+    let converter = Converter::new();
+    let mut conversion = MediaWikiConversion::default();
+    let mut lines = Vec::new();
+    let mut in_code = false;
+    let mut in_table = false;
+    for line in wikitext.lines() {
+        let trimmed = line.trim();
+        if in_code {
+            if trimmed == "</pre>" {
+                lines.push("```".to_string());
+                in_code = false;
+            } else {
+                lines.push(line.to_string());
+            }
+            continue;
+        }
+        if trimmed == "<pre>" {
+            lines.push("```".to_string());
+            in_code = true;
+            continue;
+        }
+        if trimmed.starts_with("{|") {
+            conversion.unconverted.insert("table".to_string());
+            in_table = true;
+        }
+        if in_table {
+            in_table = !trimmed.starts_with("|}");
+            lines.push(line.to_string());
+            continue;
+        }
+        if let Some(caps) = converter.heading.captures(line) {
+            let text = converter.inline(dump, &caps[2], &mut conversion);
+            lines.push(format!("{} {}", "#".repeat(caps[1].len()), text));
+            continue;
+        }
+        if trimmed == "----" {
+            lines.push("---".to_string());
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix(' ') {
+            // a leading space marks a preformatted line
+            lines.push(format!("    {}", rest));
+            continue;
+        }
+        let markers: String = line
+            .chars()
+            .take_while(|c| matches!(c, '*' | '#' | ':' | ';'))
+            .collect();
+        let text = converter.inline(
+            dump,
+            line[markers.len()..].trim(),
+            &mut conversion,
+        );
+        let depth = markers.len().saturating_sub(1);
+        lines.push(match markers.chars().last() {
+            Some('*') => format!("{}- {}", "  ".repeat(depth), text),
+            Some('#') => format!("{}1. {}", "   ".repeat(depth), text),
+            Some(':') => format!("{} {}", ">".repeat(markers.len()), text),
+            Some(';') => format!("**{}**", text),
+            _ => text,
+        });
+    }
+    if in_code {
+        lines.push("```".to_string());
+    }
+    conversion.markdown = lines.join("\n").trim().to_string() + "\n";
+    conversion
+}
+
+/// Settings of [`import_mediawiki`](fn.import_mediawiki.html).
+#[derive(Clone, Debug)]
+pub struct MediaWikiOptions {
+    pub locale: String,
+    pub collision: ImportCollision,
+    /// Import the talk pages, all odd namespaces, as well.
+    pub include_talk: bool,
+    /// Only report what would be done, without creating or updating pages.
+    pub dry_run: bool,
+}
+
+impl Default for MediaWikiOptions {
+    fn default() -> Self {
+        Self {
+            locale: "en".to_string(),
+            collision: ImportCollision::default(),
+            include_talk: false,
+            dry_run: false,
+        }
+    }
+}
+
+/// The outcome of importing one page of a dump.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MediaWikiOutcome {
+    pub title: String,
+    pub path: String,
+    pub unconverted: BTreeSet<String>,
+    pub result: Result<ImportAction, PageError>,
+}
+
+/// Create pages from a MediaWiki XML dump.
+///
+/// The text of the latest revision of every page is converted with
+/// [`wikitext_to_markdown`](fn.wikitext_to_markdown.html) and its
+/// categories become tags.
+///
+/// # Arguments
+/// * `progress` - Called after each page with the number of finished
+///   pages, the total number of pages and the outcome.
+/// * `cancel` - Optional token to stop before the remaining pages, which
+///   are left out of the result.
+///
+/// # Returns
+/// A Result containing either the outcome of every page or an import error
+/// if the dump could not be read.
+pub fn import_mediawiki<F>(
+    client: &Client,
+    url: &str,
+    dump_file: &Path,
+    options: &MediaWikiOptions,
+    progress: F,
+    cancel: Option<&CancellationToken>,
+) -> Result<Vec<MediaWikiOutcome>, ImportError>
+where
+    F: FnMut(usize, usize, &MediaWikiOutcome) + Send,
+{
+    let dump = parse_dump(&fs::read_to_string(dump_file)?)?;
+    let pages: Vec<&MediaWikiPage> = dump
+        .pages
+        .iter()
+        .filter(|page| options.include_talk || page.namespace % 2 == 0)
+        .collect();
+    let outcomes = import_parallel(
+        &pages,
+        |page| {
+            let path = dump.path(&page.title);
+            let conversion = wikitext_to_markdown(&dump, &page.text);
+            let front_matter = PageFrontMatter {
+                title: Some(dump.split_title(&page.title).1.to_string()),
+                tags: Some(conversion.categories),
+                ..Default::default()
+            };
+            MediaWikiOutcome {
+                title: page.title.clone(),
+                result: import_file(
+                    client,
+                    url,
+                    &path,
+                    &options.locale,
+                    front_matter,
+                    conversion.markdown,
+                    options.collision,
+                    options.dry_run,
+                ),
+                path,
+                unconverted: conversion.unconverted,
+            }
+        },
+        progress,
+        cancel,
+    );
+    Ok(outcomes)
+}
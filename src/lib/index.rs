@@ -0,0 +1,380 @@
+use reqwest::blocking::Client;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tantivy::collector::{DocSetCollector, TopDocs};
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{AllQuery, QueryParser};
+use tantivy::schema::{Field, Schema, Value, INDEXED, STORED, STRING, TEXT};
+use tantivy::{
+    doc, Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument,
+    TantivyError, Term,
+};
+use thiserror::Error;
+
+use crate::batch::page_get_map;
+use crate::common::{CancellationToken, Date, Int};
+use crate::page::{page_list, Page, PageError};
+
+// a term in the title counts as much as this many in the content, one in
+// the description half as much
+const TITLE_BOOST: f32 = 4.0;
+const DESCRIPTION_BOOST: f32 = 2.0;
+// memory the writer may buffer documents in before it writes a segment,
+// the least tantivy accepts
+const WRITER_MEMORY: usize = 15_000_000;
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum IndexError {
+    #[error(transparent)]
+    Page(#[from] PageError),
+    #[error("Cannot read or write the search index: {0}")]
+    Io(String),
+    #[error("The search index is damaged: {0}")]
+    Corrupt(String),
+    #[error("No local search index is attached to the API.")]
+    NoIndex,
+}
+
+impl From<io::Error> for IndexError {
+    fn from(e: io::Error) -> Self {
+        IndexError::Io(e.to_string())
+    }
+}
+
+impl From<TantivyError> for IndexError {
+    fn from(e: TantivyError) -> Self {
+        match e {
+            TantivyError::IoError(e) => IndexError::Io(e.to_string()),
+            TantivyError::OpenDirectoryError(e) => {
+                IndexError::Io(e.to_string())
+            }
+            TantivyError::LockFailure(e, _) => IndexError::Io(e.to_string()),
+            e => IndexError::Corrupt(e.to_string()),
+        }
+    }
+}
+
+/// A page found by [`SearchIndex::search`], the best first.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct IndexHit {
+    pub id: Int,
+    pub path: String,
+    pub locale: String,
+    pub title: String,
+    pub description: String,
+    pub score: f64,
+}
+
+/// What [`SearchIndex::update`] changed, by page id.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IndexUpdate {
+    pub added: Vec<Int>,
+    pub updated: Vec<Int>,
+    pub removed: Vec<Int>,
+    /// Pages that could not be fetched. They are tried again on the next
+    /// update.
+    pub failed: Vec<(Int, PageError)>,
+}
+
+/// Fields of the indexed pages.
+struct Fields {
+    id: Field,
+    path: Field,
+    locale: Field,
+    title: Field,
+    description: Field,
+    content: Field,
+    updated_at: Field,
+}
+
+impl Fields {
+    fn schema() -> (Schema, Self) {
+        let mut builder = Schema::builder();
+        let fields = Self {
+            id: builder.add_i64_field("id", INDEXED | STORED),
+            path: builder.add_text_field("path", STORED),
+            locale: builder.add_text_field("locale", STORED),
+            title: builder.add_text_field("title", TEXT | STORED),
+            description: builder.add_text_field("description", TEXT | STORED),
+            content: builder.add_text_field("content", TEXT),
+            updated_at: builder.add_text_field("updatedAt", STRING | STORED),
+        };
+        (builder.build(), fields)
+    }
+
+    fn text(&self, document: &TantivyDocument, field: Field) -> String {
+        document
+            .get_first(field)
+            .and_then(|value| value.as_str())
+            .unwrap_or_default()
+            .to_string()
+    }
+}
+
+/// Full-text index of the page content, to search the wiki offline and
+/// rank the results by relevance, which the basic search engine of Wiki.js
+/// does not.
+///
+/// The index is kept with [tantivy](https://docs.rs/tantivy), which ranks
+/// the pages with BM25 over their title, description and content. Updates
+/// only fetch the pages whose `updatedAt` changed since they were indexed.
+/// Changes are staged until [`save`](#method.save) commits them, and only
+/// then seen by [`search`](#method.search), [`len`](#method.len) and the
+/// next update.
+pub struct SearchIndex {
+    directory: Option<PathBuf>,
+    index: Index,
+    reader: IndexReader,
+    // created on the first change, so searching does not lock the index
+    writer: Mutex<Option<IndexWriter>>,
+    fields: Fields,
+}
+
+impl fmt::Debug for SearchIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SearchIndex")
+            .field("directory", &self.directory)
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SearchIndex {
+    fn with_index(
+        index: Index,
+        directory: Option<PathBuf>,
+        fields: Fields,
+    ) -> Result<Self, IndexError> {
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        Ok(Self {
+            directory,
+            index,
+            reader,
+            writer: Mutex::new(None),
+            fields,
+        })
+    }
+
+    /// Create an empty index kept in memory only.
+    pub fn new() -> Self {
+        let (schema, fields) = Fields::schema();
+        Self::with_index(Index::create_in_ram(schema), None, fields)
+            .expect("an index in memory can always be read")
+    }
+
+    /// Open the index kept in the directory, creating an empty one if it
+    /// does not exist yet. [`save`](#method.save) writes the changes to it.
+    pub fn open(directory: impl Into<PathBuf>) -> Result<Self, IndexError> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory)?;
+        let (schema, fields) = Fields::schema();
+        let index = Index::open_or_create(
+            MmapDirectory::open(&directory).map_err(TantivyError::from)?,
+            schema,
+        )?;
+        Self::with_index(index, Some(directory), fields)
+    }
+
+    // run the function with the writer, creating it if needed
+    fn write<T>(
+        &self,
+        f: impl FnOnce(&mut IndexWriter) -> T,
+    ) -> Result<T, IndexError> {
+        let mut writer = self.writer.lock().unwrap();
+        let writer = match writer.as_mut() {
+            Some(writer) => writer,
+            None => writer
+                .insert(self.index.writer_with_num_threads(1, WRITER_MEMORY)?),
+        };
+        Ok(f(writer))
+    }
+
+    /// Commit the changes, writing them to the directory of the index if
+    /// it has one, and make them visible to searches.
+    pub fn save(&self) -> Result<(), IndexError> {
+        let mut writer = self.writer.lock().unwrap();
+        if let Some(writer) = writer.as_mut() {
+            writer.commit()?;
+            self.reader.reload()?;
+        }
+        Ok(())
+    }
+
+    /// Number of indexed pages.
+    pub fn len(&self) -> usize {
+        self.reader.searcher().num_docs() as usize
+    }
+
+    /// Whether no page is indexed.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Index the page, replacing the previous version of it.
+    pub fn add(&self, page: &Page) -> Result<(), IndexError> {
+        let fields = &self.fields;
+        let document = doc!(
+            fields.id => page.id,
+            fields.path => page.path.as_str(),
+            fields.locale => page.locale.as_str(),
+            fields.title => page.title.as_str(),
+            fields.description => page.description.as_str(),
+            fields.content => page.content.as_str(),
+            fields.updated_at => page.updated_at.as_str(),
+        );
+        self.write(|writer| {
+            writer.delete_term(Term::from_field_i64(fields.id, page.id));
+            writer.add_document(document).map(|_| ())
+        })??;
+        Ok(())
+    }
+
+    /// Drop the page from the index.
+    pub fn remove(&self, id: Int) -> Result<(), IndexError> {
+        self.write(|writer| {
+            writer.delete_term(Term::from_field_i64(self.fields.id, id));
+        })
+    }
+
+    // when each saved page was last updated
+    fn updated_at(&self) -> Result<HashMap<Int, Date>, IndexError> {
+        let searcher = self.reader.searcher();
+        let mut updated_at = HashMap::new();
+        for address in searcher.search(&AllQuery, &DocSetCollector)? {
+            let document: TantivyDocument = searcher.doc(address)?;
+            let Some(id) =
+                document.get_first(self.fields.id).and_then(|v| v.as_i64())
+            else {
+                continue;
+            };
+            updated_at.insert(
+                id,
+                self.fields.text(&document, self.fields.updated_at),
+            );
+        }
+        Ok(updated_at)
+    }
+
+    /// Bring the index in line with the wiki: index new pages, re-index
+    /// those updated since and drop deleted ones. The changes are staged
+    /// until the index is saved.
+    ///
+    /// # Arguments
+    /// * `cancel` - Stops fetching pages, those fetched so far are indexed.
+    pub fn update(
+        &self,
+        client: &Client,
+        url: &str,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<IndexUpdate, IndexError> {
+        let items =
+            page_list(client, url, None, None, None, None, None, None, None)?;
+        let mut update = IndexUpdate::default();
+        let mut indexed = self.updated_at()?;
+        let listed: HashSet<Int> = items.iter().map(|i| i.id).collect();
+        update.removed = indexed
+            .keys()
+            .copied()
+            .filter(|id| !listed.contains(id))
+            .collect();
+        update.removed.sort();
+        for id in &update.removed {
+            self.remove(*id)?;
+            indexed.remove(id);
+        }
+        let stale: Vec<Int> = items
+            .iter()
+            .filter(|item| {
+                indexed
+                    .get(&item.id)
+                    .is_none_or(|updated_at| *updated_at != item.updated_at)
+            })
+            .map(|item| item.id)
+            .collect();
+        let mut pages: Vec<(Int, Result<Page, PageError>)> =
+            page_get_map(client, url, &stale, cancel)
+                .into_iter()
+                .collect();
+        pages.sort_by_key(|(id, _)| *id);
+        for (id, result) in pages {
+            match result {
+                Ok(page) => {
+                    self.add(&page)?;
+                    match indexed.contains_key(&id) {
+                        true => update.updated.push(id),
+                        false => update.added.push(id),
+                    }
+                }
+                // deleted since it was listed
+                Err(PageError::PageNotFound) => {
+                    if indexed.remove(&id).is_some() {
+                        self.remove(id)?;
+                        update.removed.push(id);
+                    }
+                }
+                Err(e) => update.failed.push((id, e)),
+            }
+        }
+        Ok(update)
+    }
+
+    /// Find the pages matching any word of the query, ignoring case, the
+    /// most relevant first.
+    ///
+    /// # Arguments
+    /// * `query` - The words to search for.
+    /// * `limit` - The maximum number of pages to return.
+    pub fn search(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<IndexHit>, IndexError> {
+        let fields = &self.fields;
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let mut parser = QueryParser::for_index(
+            &self.index,
+            vec![fields.title, fields.description, fields.content],
+        );
+        parser.set_field_boost(fields.title, TITLE_BOOST);
+        parser.set_field_boost(fields.description, DESCRIPTION_BOOST);
+        // words the query syntax does not allow are left out
+        let (query, _) = parser.parse_query_lenient(query);
+        let searcher = self.reader.searcher();
+        let mut hits = Vec::new();
+        for (score, address) in
+            searcher.search(&query, &TopDocs::with_limit(limit))?
+        {
+            let document: TantivyDocument = searcher.doc(address)?;
+            let Some(id) =
+                document.get_first(fields.id).and_then(|v| v.as_i64())
+            else {
+                continue;
+            };
+            hits.push(IndexHit {
+                id,
+                path: fields.text(&document, fields.path),
+                locale: fields.text(&document, fields.locale),
+                title: fields.text(&document, fields.title),
+                description: fields.text(&document, fields.description),
+                score: score as f64,
+            });
+        }
+        Ok(hits)
+    }
+}
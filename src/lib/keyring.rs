@@ -0,0 +1,212 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use thiserror::Error;
+
+use crate::Credentials;
+
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum KeyringError {
+    #[error("No secret stored for {account}.")]
+    NotFound { account: String },
+    #[error("The system keyring is not available: {0}")]
+    Unavailable(String),
+    #[error("The system keyring failed: {0}")]
+    Failed(String),
+}
+
+/// A secret in the system keyring, identified by service and account.
+///
+/// The keyring is accessed through the tools of the platform, so nothing
+/// needs to be linked: `secret-tool` of libsecret on Linux and the BSDs and
+/// `security` on macOS. Other platforms are not supported.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyringCredentials {
+    pub service: String,
+    pub account: String,
+}
+
+fn run(
+    program: &str,
+    args: &[&str],
+    input: Option<&str>,
+) -> Result<String, KeyringError> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            KeyringError::Unavailable(format!("{}: {}", program, e))
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Some(input) = input {
+            stdin
+                .write_all(input.as_bytes())
+                .map_err(|e| KeyringError::Failed(e.to_string()))?;
+        }
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| KeyringError::Failed(e.to_string()))?;
+    if !output.status.success() {
+        return Err(KeyringError::Failed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end_matches('\n')
+        .to_string())
+}
+
+// quoted for the command parser of `security -i`
+fn security_quote(arg: &str) -> String {
+    format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl KeyringCredentials {
+    /// # Arguments
+    /// * `service` - Name of the application, like `wikijs`.
+    /// * `account` - Name of the secret, like the URL of the wiki.
+    pub fn new(service: impl Into<String>, account: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+            account: account.into(),
+        }
+    }
+
+    fn not_found(&self) -> KeyringError {
+        KeyringError::NotFound {
+            account: self.account.clone(),
+        }
+    }
+
+    /// Read the secret.
+    pub fn get(&self) -> Result<String, KeyringError> {
+        let secret = if cfg!(target_os = "macos") {
+            run(
+                "security",
+                &[
+                    "find-generic-password",
+                    "-s",
+                    &self.service,
+                    "-a",
+                    &self.account,
+                    "-w",
+                ],
+                None,
+            )
+            .map_err(|_| self.not_found())?
+        } else if cfg!(unix) {
+            // secret-tool exits with an error and no output if nothing
+            // matches
+            run(
+                "secret-tool",
+                &["lookup", "service", &self.service, "account", &self.account],
+                None,
+            )
+            .or_else(|e| match e {
+                KeyringError::Failed(message) if message.is_empty() => {
+                    Ok(String::new())
+                }
+                e => Err(e),
+            })?
+        } else {
+            return Err(KeyringError::Unavailable(
+                "not supported on this platform".to_string(),
+            ));
+        };
+        match secret.is_empty() {
+            true => Err(self.not_found()),
+            false => Ok(secret),
+        }
+    }
+
+    /// Store the secret, replacing an existing one.
+    pub fn set(&self, secret: &str) -> Result<(), KeyringError> {
+        if cfg!(target_os = "macos") {
+            // given as an argument the secret would be visible in the
+            // process list, so the command is read from stdin instead
+            if secret.contains(['\n', '\r']) {
+                return Err(KeyringError::Failed(
+                    "the secret must not contain line breaks".to_string(),
+                ));
+            }
+            let command = [
+                "add-generic-password",
+                "-U",
+                "-s",
+                &self.service,
+                "-a",
+                &self.account,
+                "-w",
+                secret,
+            ]
+            .map(security_quote)
+            .join(" ");
+            run("security", &["-i"], Some(&format!("{}\n", command)))?;
+            // the interactive mode does not fail with the command
+            if self.get().ok().as_deref() != Some(secret) {
+                return Err(KeyringError::Failed(
+                    "security did not store the secret".to_string(),
+                ));
+            }
+        } else if cfg!(unix) {
+            let label = format!("{} {}", self.service, self.account);
+            run(
+                "secret-tool",
+                &[
+                    "store",
+                    "--label",
+                    &label,
+                    "service",
+                    &self.service,
+                    "account",
+                    &self.account,
+                ],
+                Some(secret),
+            )?;
+        } else {
+            return Err(KeyringError::Unavailable(
+                "not supported on this platform".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Remove the secret.
+    pub fn delete(&self) -> Result<(), KeyringError> {
+        if cfg!(target_os = "macos") {
+            run(
+                "security",
+                &[
+                    "delete-generic-password",
+                    "-s",
+                    &self.service,
+                    "-a",
+                    &self.account,
+                ],
+                None,
+            )
+            .map_err(|_| self.not_found())?;
+        } else if cfg!(unix) {
+            self.get()?;
+            run(
+                "secret-tool",
+                &["clear", "service", &self.service, "account", &self.account],
+                None,
+            )?;
+        } else {
+            return Err(KeyringError::Unavailable(
+                "not supported on this platform".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The stored API key or JWT as credentials for
+    /// [`Api::new`](../struct.Api.html#method.new).
+    pub fn credentials(&self) -> Result<Credentials, KeyringError> {
+        self.get().map(Credentials::Key)
+    }
+}
@@ -36,6 +36,11 @@
 //! types derived from Wiki.js' error codes. Note we are ignoring a potential
 //! [`page::PageError`](page/enum.PageError.html) in the example above!
 //!
+//! # Strict mode
+//! By default unexpected server responses are handled leniently. Build the
+//! API with [`Api::with_strict_mode`](struct.Api.html#method.with_strict_mode)
+//! to turn them into errors that name the operation and JSON path instead.
+//!
 //! # Testing
 //! The integration tests require a clean Wiki.js instance running on localhost
 //! with predefined admin login credentials. See the testing section of the
@@ -51,9 +56,21 @@ pub mod analytics;
 /// Structs, enums, errors and internal API call implementations to interact
 /// with the assets and asset folders.
 pub mod asset;
+/// Structs, errors and internal implementations of reports combining
+/// several API calls, like finding orphaned assets.
+pub mod audit;
 /// Structs, enums, errors and internal API call implementations to call
 /// authentication functions, create API keys and so on.
 pub mod authentication;
+/// Structs, enums, errors and internal implementation to back up all pages
+/// and assets into an archive.
+pub mod backup;
+/// Structs and internal implementation to send several queries in one
+/// batched request.
+pub mod batch;
+/// Structs and internal implementation of the optional read cache for
+/// pages, page trees and assets.
+pub mod cache;
 /// Structs, enums, errors and internal API call implementations to interact
 /// with comments.
 pub mod comment;
@@ -62,9 +79,25 @@ pub mod common;
 /// Structs, enums, errors and internal API call implementations to list
 /// contributors.
 pub mod contribute;
+/// Structs, errors and internal implementation to export the wiki to other
+/// formats like a static HTML site.
+pub mod export;
+/// Structs and enums to build the page link graph and export it to DOT or
+/// GraphML.
+pub mod graph;
 /// Structs, enums, errors and internal API call implementations to interact
 /// with user groups.
 pub mod group;
+/// Structs, enums, errors and internal implementation to import pages from
+/// a directory of markdown files.
+pub mod import;
+/// Structs and errors of the local full-text search index of the page
+/// content.
+#[cfg(feature = "index")]
+pub mod index;
+/// Structs and errors to store API keys and tokens in the system keyring.
+#[cfg(feature = "keyring")]
+pub mod keyring;
 /// Structs, enums, errors and internal API call implementations to interact
 /// with localization settings.
 pub mod localization;
@@ -74,6 +107,9 @@ pub mod logging;
 /// Structs, enums, errors and internal API call implementations to interact
 /// with mail settings.
 pub mod mail;
+/// Structs, enums, errors and internal implementation to mirror the pages
+/// and assets of one wiki to another.
+pub mod mirror;
 /// Structs, enums, errors and internal API call implementations to interact
 /// with navigation settings and modify the navigation tree.
 pub mod navigation;
@@ -83,6 +119,12 @@ pub mod page;
 /// Structs, enums, errors and internal API call implementations to interact
 /// with rendering settings.
 pub mod rendering;
+/// Structs, enums and internal implementation to search and replace text
+/// across pages.
+pub mod replace;
+/// Structs, errors and internal implementation of the wiki statistics and
+/// activity report.
+pub mod report;
 /// Structs, enums, errors and internal API call implementations to interact
 /// with search engine settings.
 pub mod search;
@@ -92,15 +134,27 @@ pub mod site;
 /// Structs, enums, errors and internal API call implementations to interact
 /// with storage settings.
 pub mod storage;
+/// Structs, enums, errors and internal implementation to sync a local
+/// directory of page files with the wiki.
+pub mod sync;
 /// Structs, enums, errors and internal API call implementations to interact
 /// with system settings.
 pub mod system;
+/// Internal implementations of higher level tag management like renaming and
+/// merging tags.
+pub mod tag;
+/// Structs, errors and internal implementation to create pages from template
+/// pages with variable substitution.
+pub mod template;
 /// Structs, enums, errors and internal API call implementations to interact
 /// with theming settings.
 pub mod theming;
 /// Structs, enums, errors and internal API call implementations to interact
 /// with users.
 pub mod user;
+/// Structs, enums, errors and internal implementation to watch the wiki
+/// for page and comment changes by polling.
+pub mod watch;
 
 /// Credentials to authenticate against the Wiki.js API.
 #[derive(Debug)]
@@ -116,6 +170,11 @@ pub enum Credentials {
 pub struct Api {
     pub(crate) url: String,
     pub(crate) client: Client,
+    pub(crate) strict: bool,
+    pub(crate) concurrency: usize,
+    pub(crate) cache: Option<cache::Cache>,
+    #[cfg(feature = "index")]
+    pub(crate) index: Option<index::SearchIndex>,
 }
 
 /// The main implementation of the API struct.
@@ -146,7 +205,13 @@ impl Api {
                     password,
                     strategy,
                 )?;
-                auth_response.jwt.unwrap()
+                match auth_response.jwt {
+                    Some(jwt) => jwt,
+                    None if auth_response.must_provide_tfa == Some(true) => {
+                        return Err(user::UserError::AuthTFARequired)
+                    }
+                    None => return Err(user::UserError::AuthGenericError),
+                }
             }
         };
         Ok(Self {
@@ -163,9 +228,110 @@ impl Api {
                 )
                 .build()
                 .unwrap(),
+            strict: false,
+            concurrency: common::DEFAULT_CONCURRENCY,
+            cache: None,
+            #[cfg(feature = "index")]
+            index: None,
         })
     }
 
+    /// Enable or disable strict mode for the API calls of this struct.
+    ///
+    /// By default the library is lenient with what the server returns:
+    /// unknown values of open enums like
+    /// [`PageEditor`](page/enum.PageEditor.html) are kept as `Other` and a
+    /// missing or null `responseResult` ends up as a generic unknown error.
+    /// In strict mode these cases fail with a diagnostic naming the
+    /// operation and the JSON path of the unexpected value, which helps when
+    /// talking to forked or patched Wiki.js servers.
+    ///
+    /// # Arguments
+    /// * `strict` - Whether strict mode should be enabled.
+    ///
+    /// # Returns
+    /// The API struct with strict mode set.
+    pub fn with_strict_mode(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Whether strict mode is enabled for the API calls of this struct.
+    pub fn strict_mode(&self) -> bool {
+        self.strict
+    }
+
+    /// Set how many API calls the bulk operations of this struct make at
+    /// once, like importing, exporting, syncing, backing up and re-tagging
+    /// pages. 4 by default, 1 makes them sequential.
+    ///
+    /// # Arguments
+    /// * `concurrency` - The number of calls at once, at least 1.
+    ///
+    /// # Returns
+    /// The API struct with the concurrency set.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// How many API calls the bulk operations of this struct make at once.
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// The HTTP client for a single API call, with the strict mode and
+    /// concurrency of this struct in effect until it is dropped.
+    pub(crate) fn client(&self) -> common::ApiClient<'_> {
+        common::ApiClient::new(&self.client, self.strict)
+            .with_concurrency(self.concurrency)
+    }
+
+    /// Serve `page_get`, `page_tree_get` and `asset_download` from a read
+    /// cache, validating its entries against the server.
+    ///
+    /// # Arguments
+    /// * `cache` - The cache to use.
+    ///
+    /// # Returns
+    /// The API struct with the cache attached.
+    pub fn with_cache(mut self, cache: cache::Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Attach a local search index for `local_search` and
+    /// `local_index_update`.
+    ///
+    /// # Arguments
+    /// * `index` - The index to use.
+    ///
+    /// # Returns
+    /// The API struct with the index attached.
+    #[cfg(feature = "index")]
+    pub fn with_index(mut self, index: index::SearchIndex) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Get the URL a page is viewed at in the browser.
+    ///
+    /// # Arguments
+    /// * `locale` - The locale of the page.
+    /// * `path` - The path of the page.
+    pub fn page_url(&self, locale: &str, path: &str) -> String {
+        format!("{}/{}/{}", self.url, locale, path)
+    }
+
+    /// Get the URL a page is edited at in the browser.
+    ///
+    /// # Arguments
+    /// * `locale` - The locale of the page.
+    /// * `path` - The path of the page.
+    pub fn page_edit_url(&self, locale: &str, path: &str) -> String {
+        format!("{}/e/{}/{}", self.url, locale, path)
+    }
+
     // asset functions
 
     /// List all assets in a folder.
@@ -179,13 +345,36 @@ impl Api {
         kind: asset::AssetKind,
     ) -> Result<Vec<asset::AssetItem>, asset::AssetError> {
         asset::asset_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             folder_id,
             kind,
         )
     }
 
+    /// List the assets of a folder and all its subfolders.
+    ///
+    /// # Arguments
+    /// * `root_folder_id` - The id of the folder to start from, 0 for the
+    ///   root folder.
+    /// * `kind` - The kind of assets to list.
+    ///
+    /// # Returns
+    /// A Result containing either the assets with their folder paths
+    /// relative to the root folder or an asset error.
+    pub fn asset_list_recursive(
+        &self,
+        root_folder_id: i64,
+        kind: asset::AssetKind,
+    ) -> Result<Vec<asset::AssetItemWithPath>, asset::AssetError> {
+        asset::asset_list_recursive(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            root_folder_id,
+            kind,
+        )
+    }
+
     /// List all asset folders.
     ///
     /// # Arguments
@@ -196,12 +385,25 @@ impl Api {
         parent_id: i64,
     ) -> Result<Vec<asset::AssetFolder>, asset::AssetError> {
         asset::asset_folder_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             parent_id,
         )
     }
 
+    /// Fetch the complete asset folder hierarchy to resolve folder paths.
+    ///
+    /// # Returns
+    /// A Result containing either the folder tree or an asset error.
+    pub fn asset_folder_tree(
+        &self,
+    ) -> Result<asset::AssetFolderTree, asset::AssetError> {
+        asset::asset_folder_tree(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+        )
+    }
+
     /// Create a new asset folder.
     ///
     /// # Arguments
@@ -216,7 +418,7 @@ impl Api {
         name: Option<String>,
     ) -> Result<(), asset::AssetError> {
         asset::asset_folder_create(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             parent_folder_id,
             slug,
@@ -224,6 +426,28 @@ impl Api {
         )
     }
 
+    /// Create a folder path like `images/screenshots` including all missing
+    /// parent folders, and add the new folders to the tree.
+    ///
+    /// # Arguments
+    /// * `tree` - The folder tree, as returned by `asset_folder_tree`.
+    /// * `path` - The slash separated folder path.
+    ///
+    /// # Returns
+    /// A Result containing either the id of the folder or an asset error.
+    pub fn asset_folder_create_all(
+        &self,
+        tree: &mut asset::AssetFolderTree,
+        path: &str,
+    ) -> Result<i64, asset::AssetError> {
+        asset::asset_folder_create_all(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            tree,
+            path,
+        )
+    }
+
     /// Rename an asset.
     ///
     /// # Arguments
@@ -235,25 +459,54 @@ impl Api {
         filename: String,
     ) -> Result<(), asset::AssetError> {
         asset::asset_rename(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
             filename,
         )
     }
 
+    /// Move an asset to another folder.
+    ///
+    /// Wiki.js 2.x has no mutation for this, so the asset is re-uploaded to
+    /// the destination folder and deleted afterwards, which gives it a new
+    /// id.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the asset to move.
+    /// * `folder_id` - The id of the destination folder.
+    ///
+    /// # Returns
+    /// A Result containing either the new id of the asset or an asset error.
+    pub fn asset_move(
+        &self,
+        id: i64,
+        folder_id: i64,
+    ) -> Result<i64, asset::AssetError> {
+        asset::asset_move(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            id,
+            folder_id,
+        )
+    }
+
     /// Delete an asset.
     ///
     /// # Arguments
     /// * `id` - The id of the asset to delete.
     pub fn asset_delete(&self, id: i64) -> Result<(), asset::AssetError> {
-        asset::asset_delete(&self.client, &format!("{}/graphql", self.url), id)
+        asset::asset_delete(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            id,
+        )
     }
 
     /// Flush the temporary upload folder.
     pub fn asset_temp_upload_flush(&self) -> Result<(), asset::AssetError> {
         asset::asset_temp_upload_flush(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -269,7 +522,17 @@ impl Api {
         &self,
         path: String,
     ) -> Result<Vec<u8>, asset::AssetError> {
-        asset::asset_download(&self.client, self.url.as_str(), path)
+        match &self.cache {
+            Some(cache) => cache::cached_asset_download(
+                cache,
+                &self.client(),
+                self.url.as_str(),
+                path,
+            ),
+            None => {
+                asset::asset_download(&self.client(), self.url.as_str(), path)
+            }
+        }
     }
 
     /// Upload an asset.
@@ -284,7 +547,13 @@ impl Api {
         name: String,
         data: Vec<u8>,
     ) -> Result<(), asset::AssetError> {
-        asset::asset_upload(&self.client, self.url.as_str(), folder, name, data)
+        asset::asset_upload(
+            &self.client(),
+            self.url.as_str(),
+            folder,
+            name,
+            data,
+        )
     }
 
     // page functions
@@ -308,7 +577,13 @@ impl Api {
     /// println!("{:?}", api.page_get(1).unwrap());
     /// ```
     pub fn page_get(&self, id: i64) -> Result<page::Page, page::PageError> {
-        page::page_get(&self.client, &format!("{}/graphql", self.url), id)
+        let url = format!("{}/graphql", self.url);
+        match &self.cache {
+            Some(cache) => {
+                cache::cached_page_get(cache, &self.client(), &url, id)
+            }
+            None => page::page_get(&self.client(), &url, id),
+        }
     }
 
     /// Get datetime of last update of a page.
@@ -324,7 +599,7 @@ impl Api {
         id: i64,
     ) -> Result<String, page::PageError> {
         page::page_get_updated_at(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
         )
@@ -343,7 +618,43 @@ impl Api {
         id: i64,
     ) -> Result<page::PageMinimal, page::PageError> {
         page::page_get_minimal(
-            &self.client,
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            id,
+        )
+    }
+
+    /// Get a page's id, path, locale and dates without its content.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the page to get the attributes of.
+    ///
+    /// # Returns
+    /// A Result containing either the page attributes or a page error.
+    pub fn page_get_attributes(
+        &self,
+        id: i64,
+    ) -> Result<page::PageAttributes, page::PageError> {
+        page::page_get_attributes(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            id,
+        )
+    }
+
+    /// Get a page's server-rendered HTML and table of contents.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the page to get the rendered HTML of.
+    ///
+    /// # Returns
+    /// A Result containing either the rendered page or a page error.
+    pub fn page_get_rendered(
+        &self,
+        id: i64,
+    ) -> Result<page::PageRendered, page::PageError> {
+        page::page_get_rendered(
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
         )
@@ -360,7 +671,7 @@ impl Api {
         locale: String,
     ) -> Result<page::Page, page::PageError> {
         page::page_get_by_path(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             path,
             locale,
@@ -369,7 +680,7 @@ impl Api {
 
     /// List all page tags.
     pub fn page_tag_list(&self) -> Result<Vec<page::PageTag>, page::PageError> {
-        page::page_tag_list(&self.client, &format!("{}/graphql", self.url))
+        page::page_tag_list(&self.client(), &format!("{}/graphql", self.url))
     }
 
     /// List all pages.
@@ -394,7 +705,7 @@ impl Api {
         author_id: Option<i64>,
     ) -> Result<Vec<page::PageListItem>, page::PageError> {
         page::page_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             limit,
             order_by,
@@ -420,14 +731,26 @@ impl Api {
         include_ancestors: bool,
         locale: String,
     ) -> Result<Vec<page::PageTreeItem>, page::PageError> {
-        page::page_tree_get(
-            &self.client,
-            &format!("{}/graphql", self.url),
-            parent,
-            mode,
-            include_ancestors,
-            locale,
-        )
+        let url = format!("{}/graphql", self.url);
+        match &self.cache {
+            Some(cache) => cache::cached_page_tree_get(
+                cache,
+                &self.client(),
+                &url,
+                parent,
+                mode,
+                include_ancestors,
+                locale,
+            ),
+            None => page::page_tree_get(
+                &self.client(),
+                &url,
+                parent,
+                mode,
+                include_ancestors,
+                locale,
+            ),
+        }
     }
 
     /// Delete a page.
@@ -435,7 +758,31 @@ impl Api {
     /// # Arguments
     /// * `id` - The id of the page to delete.
     pub fn page_delete(&self, id: i64) -> Result<(), page::PageError> {
-        page::page_delete(&self.client, &format!("{}/graphql", self.url), id)
+        page::page_delete(&self.client(), &format!("{}/graphql", self.url), id)
+    }
+
+    /// Delete many pages in parallel.
+    ///
+    /// # Arguments
+    /// * `ids` - The ids of the pages to delete.
+    /// * `concurrency` - The maximum number of pages deleted at once.
+    /// * `cancel` - Optional token to stop before the remaining pages.
+    ///
+    /// # Returns
+    /// The id and result of every processed page in the order of `ids`.
+    pub fn page_bulk_delete(
+        &self,
+        ids: &[i64],
+        concurrency: usize,
+        cancel: Option<&common::CancellationToken>,
+    ) -> page::PageBulkResult {
+        page::page_bulk_delete(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            ids,
+            concurrency,
+            cancel,
+        )
     }
 
     /// Render a page.
@@ -443,7 +790,7 @@ impl Api {
     /// # Arguments
     /// * `id` - The id of the page to render.
     pub fn page_render(&self, id: i64) -> Result<(), page::PageError> {
-        page::page_render(&self.client, &format!("{}/graphql", self.url), id)
+        page::page_render(&self.client(), &format!("{}/graphql", self.url), id)
     }
 
     /// Create a new page.
@@ -467,7 +814,7 @@ impl Api {
         &self,
         content: String,
         description: String,
-        editor: String,
+        editor: page::PageEditor,
         is_published: bool,
         is_private: bool,
         locale: String,
@@ -480,7 +827,7 @@ impl Api {
         title: String,
     ) -> Result<(), page::PageError> {
         page::page_create(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             content,
             description,
@@ -498,6 +845,65 @@ impl Api {
         )
     }
 
+    /// Create a page from a template page, replacing its `{{var}}`
+    /// placeholders.
+    ///
+    /// # Arguments
+    /// * `template_path` - The path of the template below the template
+    ///   prefix, like `meeting-notes`.
+    /// * `vars` - The values of the placeholders.
+    /// * `dest_path` - The path of the new page.
+    /// * `options` - The template prefix and locale.
+    pub fn page_create_from_template(
+        &self,
+        template_path: &str,
+        vars: &std::collections::HashMap<String, String>,
+        dest_path: &str,
+        options: &template::TemplateOptions,
+    ) -> Result<(), template::TemplateError> {
+        template::page_create_from_template(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            template_path,
+            vars,
+            dest_path,
+            options,
+        )
+    }
+
+    /// Publish a page, optionally only within a time frame.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the page to publish.
+    /// * `start` - When the page becomes visible, or right away if None.
+    /// * `end` - When the page is hidden again, or never if None.
+    pub fn page_publish_at(
+        &self,
+        id: i64,
+        start: Option<chrono::DateTime<chrono::Utc>>,
+        end: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<(), page::PageError> {
+        page::page_publish_at(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            id,
+            start,
+            end,
+        )
+    }
+
+    /// Unpublish a page and clear its publishing time frame.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the page to unpublish.
+    pub fn page_unpublish(&self, id: i64) -> Result<(), page::PageError> {
+        page::page_unpublish(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            id,
+        )
+    }
+
     /// Update a page.
     ///
     /// # Arguments
@@ -521,7 +927,7 @@ impl Api {
         id: i64,
         content: Option<String>,
         description: Option<String>,
-        editor: Option<String>,
+        editor: Option<page::PageEditor>,
         is_private: Option<bool>,
         is_published: Option<bool>,
         locale: Option<String>,
@@ -534,7 +940,7 @@ impl Api {
         title: Option<String>,
     ) -> Result<(), page::PageError> {
         page::page_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
             content,
@@ -553,6 +959,28 @@ impl Api {
         )
     }
 
+    /// Update a page's content unless it was changed after it was fetched.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the page to update.
+    /// * `content` - The new content of the page.
+    /// * `checkout_date` - The `updated_at` of the page when it was fetched.
+    ///
+    /// # Returns
+    /// A Result that is `PageError::PageUpdateConflict` if somebody else
+    /// edited the page meanwhile.
+    pub fn page_update_content_checked(
+        &self,
+        id: i64,
+        content: String,
+        checkout_date: String,
+    ) -> Result<(), page::PageError> {
+        if self.page_conflict_check(id, checkout_date)? {
+            return Err(page::PageError::PageUpdateConflict);
+        }
+        self.page_update_content(id, content)
+    }
+
     /// Update a page's content.
     ///
     /// # Arguments
@@ -581,6 +1009,40 @@ impl Api {
         )
     }
 
+    /// Update many pages in parallel.
+    ///
+    /// Every page is fetched, passed to `mutator` and written back if the
+    /// mutator returns true.
+    ///
+    /// # Arguments
+    /// * `ids` - The ids of the pages to update.
+    /// * `mutator` - Function modifying a page in place, returning whether
+    ///   it changed anything.
+    /// * `concurrency` - The maximum number of pages processed at once.
+    /// * `cancel` - Optional token to stop before the remaining pages.
+    ///
+    /// # Returns
+    /// The id and result of every processed page in the order of `ids`.
+    pub fn page_bulk_update<F>(
+        &self,
+        ids: &[i64],
+        mutator: F,
+        concurrency: usize,
+        cancel: Option<&common::CancellationToken>,
+    ) -> page::PageBulkResult
+    where
+        F: Fn(&mut page::Page) -> bool + Sync,
+    {
+        page::page_bulk_update(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            ids,
+            mutator,
+            concurrency,
+            cancel,
+        )
+    }
+
     /// Get a page's history.
     ///
     /// # Arguments
@@ -594,7 +1056,7 @@ impl Api {
         offset_size: Option<i64>,
     ) -> Result<page::PageHistoryResult, page::PageError> {
         page::page_history_get(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
             offset_page,
@@ -613,13 +1075,59 @@ impl Api {
         version: i64,
     ) -> Result<page::PageVersion, page::PageError> {
         page::page_version_get(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
             version,
         )
     }
 
+    /// Compute the diff between two versions of a page.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the page.
+    /// * `from_version` - The version to diff from.
+    /// * `to_version` - The version to diff to.
+    ///
+    /// # Returns
+    /// A Result containing either the diff or a page error.
+    pub fn page_version_diff(
+        &self,
+        id: i64,
+        from_version: i64,
+        to_version: i64,
+    ) -> Result<page::PageDiff, page::PageError> {
+        page::page_version_diff(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            id,
+            from_version,
+            to_version,
+        )
+    }
+
+    /// Compute the diff between a version of a page and its current
+    /// content.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the page.
+    /// * `from_version` - The version to diff from.
+    ///
+    /// # Returns
+    /// A Result containing either the diff or a page error.
+    pub fn page_version_diff_current(
+        &self,
+        id: i64,
+        from_version: i64,
+    ) -> Result<page::PageDiff, page::PageError> {
+        page::page_version_diff_current(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            id,
+            from_version,
+        )
+    }
+
     /// Search for pages.
     ///
     /// # Arguments
@@ -633,7 +1141,7 @@ impl Api {
         locale: Option<String>,
     ) -> Result<page::PageSearchResponse, page::PageError> {
         page::page_search(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             query,
             path,
@@ -650,7 +1158,199 @@ impl Api {
         locale: String,
     ) -> Result<Vec<page::PageLinkItem>, page::PageError> {
         page::page_link_list(
-            &self.client,
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            locale,
+        )
+    }
+
+    /// Search and replace text in the content of many pages.
+    ///
+    /// Pages edited since they were fetched are skipped with a conflict
+    /// error. The check is best-effort, see
+    /// [`replace::page_search_replace`](replace/fn.page_search_replace.html).
+    ///
+    /// # Arguments
+    /// * `pattern` - The text or regular expression to search for.
+    /// * `replacement` - The replacement text.
+    /// * `filter` - Restricts the pages to search in.
+    /// * `dry_run` - Only compute the diffs without updating any page.
+    /// * `cancel` - Optional token to abort the remaining pages.
+    ///
+    /// # Returns
+    /// A Result containing either the replacement for every matching page or
+    /// a page error.
+    pub fn page_search_replace(
+        &self,
+        pattern: &replace::ReplacePattern,
+        replacement: &str,
+        filter: &replace::PageFilter,
+        dry_run: bool,
+        cancel: Option<&common::CancellationToken>,
+    ) -> Result<Vec<replace::PageReplacement>, page::PageError> {
+        replace::page_search_replace(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            pattern,
+            replacement,
+            filter,
+            dry_run,
+            cancel,
+        )
+    }
+
+    /// Move a page and rewrite the links of the pages referring to it.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the page to move.
+    /// * `new_path` - The new path of the page in its locale.
+    /// * `cancel` - Optional token to abort rewriting the links.
+    ///
+    /// # Returns
+    /// A Result containing either a replacement for every page whose links
+    /// were rewritten or a page error.
+    pub fn page_move_with_link_fixup(
+        &self,
+        id: i64,
+        new_path: String,
+        cancel: Option<&common::CancellationToken>,
+    ) -> Result<Vec<replace::PageReplacement>, page::PageError> {
+        replace::page_move_with_link_fixup(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            id,
+            new_path,
+            cancel,
+        )
+    }
+
+    /// Rename a tag, merging it if the new name is already taken.
+    ///
+    /// # Arguments
+    /// * `old` - The current name of the tag.
+    /// * `new` - The new name of the tag.
+    /// * `cancel` - Optional token to abort the remaining pages.
+    ///
+    /// # Returns
+    /// A Result containing either the id and result of every re-tagged page
+    /// or a page error.
+    pub fn page_tag_rename(
+        &self,
+        old: &str,
+        new: &str,
+        cancel: Option<&common::CancellationToken>,
+    ) -> Result<page::PageBulkResult, page::PageError> {
+        tag::page_tag_rename(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            old,
+            new,
+            cancel,
+        )
+    }
+
+    /// Merge a tag into another one and re-tag all its pages.
+    ///
+    /// # Arguments
+    /// * `from` - The tag to merge and delete.
+    /// * `into` - The tag to merge into.
+    /// * `cancel` - Optional token to abort the remaining pages.
+    ///
+    /// # Returns
+    /// A Result containing either the id and result of every re-tagged page
+    /// or a page error.
+    pub fn page_tag_merge(
+        &self,
+        from: &str,
+        into: &str,
+        cancel: Option<&common::CancellationToken>,
+    ) -> Result<page::PageBulkResult, page::PageError> {
+        tag::page_tag_merge(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            from,
+            into,
+            cancel,
+        )
+    }
+
+    /// List the pages matching a filter.
+    ///
+    /// # Arguments
+    /// * `filter` - Restricts the pages to list.
+    ///
+    /// # Returns
+    /// A Result containing either the matching pages or a page error.
+    pub fn page_filter_list(
+        &self,
+        filter: &replace::PageFilter,
+    ) -> Result<Vec<page::PageListItem>, page::PageError> {
+        filter.pages(&self.client(), &format!("{}/graphql", self.url))
+    }
+
+    /// Add tags to every page matching a filter.
+    ///
+    /// # Arguments
+    /// * `filter` - Restricts the pages to tag.
+    /// * `tags` - The tags to add.
+    /// * `cancel` - Optional token to abort the remaining pages.
+    ///
+    /// # Returns
+    /// A Result containing either the id and result of every matching page
+    /// or a page error.
+    pub fn page_tags_add(
+        &self,
+        filter: &replace::PageFilter,
+        tags: &[String],
+        cancel: Option<&common::CancellationToken>,
+    ) -> Result<page::PageBulkResult, page::PageError> {
+        tag::page_tags_add(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            filter,
+            tags,
+            cancel,
+        )
+    }
+
+    /// Remove tags from every page matching a filter.
+    ///
+    /// # Arguments
+    /// * `filter` - Restricts the pages to untag.
+    /// * `tags` - The tags to remove.
+    /// * `cancel` - Optional token to abort the remaining pages.
+    ///
+    /// # Returns
+    /// A Result containing either the id and result of every matching page
+    /// or a page error.
+    pub fn page_tags_remove(
+        &self,
+        filter: &replace::PageFilter,
+        tags: &[String],
+        cancel: Option<&common::CancellationToken>,
+    ) -> Result<page::PageBulkResult, page::PageError> {
+        tag::page_tags_remove(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            filter,
+            tags,
+            cancel,
+        )
+    }
+
+    /// Build the graph of links between the pages of a locale.
+    ///
+    /// # Arguments
+    /// * `locale` - The locale of the pages.
+    ///
+    /// # Returns
+    /// A Result containing either the page graph or a page error.
+    pub fn page_graph(
+        &self,
+        locale: String,
+    ) -> Result<graph::PageGraph, page::PageError> {
+        graph::page_graph(
+            &self.client(),
             &format!("{}/graphql", self.url),
             locale,
         )
@@ -667,7 +1367,7 @@ impl Api {
         checkout_date: String,
     ) -> Result<bool, page::PageError> {
         page::page_conflict_check(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
             checkout_date,
@@ -683,7 +1383,7 @@ impl Api {
         id: i64,
     ) -> Result<page::PageConflictLatest, page::PageError> {
         page::page_conflict_latest(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
         )
@@ -697,10 +1397,10 @@ impl Api {
     pub fn page_convert(
         &self,
         id: i64,
-        editor: String,
+        editor: page::PageEditor,
     ) -> Result<(), page::PageError> {
         page::page_convert(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
             editor,
@@ -720,7 +1420,7 @@ impl Api {
         destination_locale: String,
     ) -> Result<(), page::PageError> {
         page::page_move(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
             destination_path,
@@ -734,7 +1434,7 @@ impl Api {
     /// * `id` - The id of the page tag.
     pub fn page_tag_delete(&self, id: i64) -> Result<(), page::PageError> {
         page::page_tag_delete(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
         )
@@ -753,7 +1453,7 @@ impl Api {
         title: String,
     ) -> Result<(), page::PageError> {
         page::page_tag_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
             tag,
@@ -763,7 +1463,7 @@ impl Api {
 
     /// Flush the page cache.
     pub fn page_cache_flush(&self) -> Result<(), page::PageError> {
-        page::page_cache_flush(&self.client, &format!("{}/graphql", self.url))
+        page::page_cache_flush(&self.client(), &format!("{}/graphql", self.url))
     }
 
     /// Migrate pages from one locale to another.
@@ -777,7 +1477,7 @@ impl Api {
         target_locale: String,
     ) -> Result<(), page::PageError> {
         page::page_migrate_to_locale(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             source_locale,
             target_locale,
@@ -786,7 +1486,10 @@ impl Api {
 
     /// Rebuild the page tree.
     pub fn page_tree_rebuild(&self) -> Result<(), page::PageError> {
-        page::page_tree_rebuild(&self.client, &format!("{}/graphql", self.url))
+        page::page_tree_rebuild(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+        )
     }
 
     /// Restore a page version.
@@ -800,7 +1503,7 @@ impl Api {
         version_id: i64,
     ) -> Result<(), page::PageError> {
         page::page_restore(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             page_id,
             version_id,
@@ -816,9 +1519,337 @@ impl Api {
         older_than: String,
     ) -> Result<(), page::PageError> {
         page::page_history_purge(
-            &self.client,
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            older_than,
+        )
+    }
+
+    // audit functions
+
+    /// Find the assets that are not referenced by any page.
+    ///
+    /// # Arguments
+    /// * `cancel` - Optional token to abort the scan.
+    ///
+    /// # Returns
+    /// A Result containing either the report or an audit error.
+    pub fn asset_orphan_report(
+        &self,
+        cancel: Option<&common::CancellationToken>,
+    ) -> Result<audit::AssetOrphanReport, audit::AuditError> {
+        audit::asset_orphan_report(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            cancel,
+        )
+    }
+
+    // backup functions
+
+    /// Back up all pages and assets into an archive file.
+    ///
+    /// # Arguments
+    /// * `path` - The archive file to write, `.zip` for a zip archive and
+    ///   tar.gz otherwise.
+    /// * `options` - Settings for incremental backups.
+    /// * `cancel` - Optional token to abort the backup.
+    ///
+    /// # Returns
+    /// A Result containing either the manifest of the backup or a backup
+    /// error.
+    pub fn backup_create(
+        &self,
+        path: &std::path::Path,
+        options: &backup::BackupOptions,
+        cancel: Option<&common::CancellationToken>,
+    ) -> Result<backup::BackupManifest, backup::BackupError> {
+        backup::backup_create(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            path,
+            backup::BackupFormat::from_path(path),
+            options,
+            cancel,
+        )
+    }
+
+    /// Restore the pages and assets of a backup archive, creating and
+    /// updating only what differs from the archive.
+    ///
+    /// # Arguments
+    /// * `path` - The archive file to read, `.zip` for a zip archive and
+    ///   tar.gz otherwise.
+    /// * `options` - Settings for dry runs and leaving out assets.
+    /// * `cancel` - Optional token to abort the restore.
+    ///
+    /// # Returns
+    /// A Result containing either the manifest of the backup with the
+    /// result of every file restored, or a backup error.
+    pub fn backup_restore(
+        &self,
+        path: &std::path::Path,
+        options: &backup::RestoreOptions,
+        cancel: Option<&common::CancellationToken>,
+    ) -> Result<
+        (backup::BackupManifest, backup::RestoreResult),
+        backup::BackupError,
+    > {
+        backup::backup_restore(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            path,
+            options,
+            cancel,
+        )
+    }
+
+    // export functions
+
+    /// Export all published pages as a static HTML site.
+    ///
+    /// # Arguments
+    /// * `dir` - The directory to write the site to.
+    /// * `cancel` - Optional token to abort the export.
+    ///
+    /// # Returns
+    /// A Result containing either what was exported or an export error.
+    pub fn export_html(
+        &self,
+        dir: &std::path::Path,
+        cancel: Option<&common::CancellationToken>,
+    ) -> Result<export::ExportSummary, export::ExportError> {
+        export::export_html(&self.client(), self.url.as_str(), dir, cancel)
+    }
+
+    /// Export all published pages as a Hugo content dir or Obsidian vault.
+    ///
+    /// # Arguments
+    /// * `dir` - The directory to write the pages and assets to.
+    /// * `format` - The folder layout and link style.
+    /// * `cancel` - Optional token to abort the export.
+    ///
+    /// # Returns
+    /// A Result containing either what was exported or an export error.
+    pub fn export_vault(
+        &self,
+        dir: &std::path::Path,
+        format: export::VaultFormat,
+        cancel: Option<&common::CancellationToken>,
+    ) -> Result<export::ExportSummary, export::ExportError> {
+        export::export_vault(
+            &self.client(),
+            self.url.as_str(),
+            dir,
+            format,
+            cancel,
+        )
+    }
+
+    // import functions
+
+    /// Create pages from a directory tree of markdown files.
+    ///
+    /// # Arguments
+    /// * `dir` - The directory to import, file paths become page paths.
+    /// * `options` - The default locale and what to do with existing pages.
+    /// * `progress` - Called after each file with the number of finished
+    ///   files, the total number of files and the outcome.
+    /// * `cancel` - Optional token to abort the import.
+    ///
+    /// # Returns
+    /// A Result containing either the outcome of every file or an import
+    /// error.
+    pub fn import_dir<F>(
+        &self,
+        dir: &std::path::Path,
+        options: &import::ImportOptions,
+        progress: F,
+        cancel: Option<&common::CancellationToken>,
+    ) -> Result<Vec<import::ImportOutcome>, import::ImportError>
+    where
+        F: FnMut(usize, usize, &import::ImportOutcome) + Send,
+    {
+        import::import_dir(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            dir,
+            options,
+            progress,
+            cancel,
+        )
+    }
+
+    /// Create pages from a MediaWiki XML dump.
+    ///
+    /// # Arguments
+    /// * `dump` - The XML dump file.
+    /// * `options` - The locale, what to do with existing pages and whether
+    ///   to import talk pages.
+    /// * `progress` - Called after each page with the number of finished
+    ///   pages, the total number of pages and the outcome.
+    /// * `cancel` - Optional token to abort the import.
+    ///
+    /// # Returns
+    /// A Result containing either the outcome of every page or an import
+    /// error.
+    pub fn import_mediawiki<F>(
+        &self,
+        dump: &std::path::Path,
+        options: &import::mediawiki::MediaWikiOptions,
+        progress: F,
+        cancel: Option<&common::CancellationToken>,
+    ) -> Result<Vec<import::mediawiki::MediaWikiOutcome>, import::ImportError>
+    where
+        F: FnMut(usize, usize, &import::mediawiki::MediaWikiOutcome) + Send,
+    {
+        import::mediawiki::import_mediawiki(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            dump,
+            options,
+            progress,
+            cancel,
+        )
+    }
+
+    // mirror functions
+
+    /// Plan the changes that make another wiki a mirror of this one.
+    ///
+    /// # Arguments
+    /// * `dest` - The destination wiki.
+    /// * `options` - The pages to mirror and whether to delete extra pages
+    ///   and assets on the destination.
+    ///
+    /// # Returns
+    /// A Result containing either the plan or a mirror error.
+    pub fn mirror_plan(
+        &self,
+        dest: &Api,
+        options: &mirror::MirrorOptions,
+    ) -> Result<mirror::MirrorPlan, mirror::MirrorError> {
+        mirror::mirror_plan(self, dest, options)
+    }
+
+    /// Apply a mirror plan from this wiki to another.
+    ///
+    /// # Arguments
+    /// * `dest` - The destination wiki.
+    /// * `plan` - The plan from [`mirror_plan`](#method.mirror_plan).
+    /// * `cancel` - Optional token to stop before the remaining actions.
+    ///
+    /// # Returns
+    /// The result of every action of the plan that was applied.
+    pub fn mirror_apply(
+        &self,
+        dest: &Api,
+        plan: &mirror::MirrorPlan,
+        cancel: Option<&common::CancellationToken>,
+    ) -> mirror::MirrorResult {
+        mirror::mirror_apply(self, dest, plan, cancel)
+    }
+
+    /// Compare the pages and assets of this wiki with another.
+    ///
+    /// # Arguments
+    /// * `other` - The wiki to compare with, whose extra pages and assets
+    ///   count as added.
+    /// * `filter` - Only compare the pages matching this filter.
+    /// * `skip_assets` - Leave out the assets.
+    ///
+    /// # Returns
+    /// A Result containing either the differences or a mirror error.
+    pub fn wiki_diff(
+        &self,
+        other: &Api,
+        filter: &replace::PageFilter,
+        skip_assets: bool,
+    ) -> Result<mirror::WikiDiff, mirror::MirrorError> {
+        mirror::wiki_diff(self, other, filter, skip_assets)
+    }
+
+    // report functions
+
+    /// Summarize the pages, tags, authors and comments of the wiki.
+    ///
+    /// # Arguments
+    /// * `options` - The time frame and length of the ranked lists.
+    /// * `cancel` - Optional token to abort the report.
+    ///
+    /// # Returns
+    /// A Result containing either the report or a report error.
+    pub fn wiki_report(
+        &self,
+        options: &report::ReportOptions,
+        cancel: Option<&common::CancellationToken>,
+    ) -> Result<report::WikiReport, report::ReportError> {
+        report::wiki_report(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            options,
+            cancel,
+        )
+    }
+
+    // sync functions
+
+    /// Compare a local directory of page files with the wiki.
+    ///
+    /// # Arguments
+    /// * `dir` - The directory with one file per page at
+    ///   `<locale>/<path>.<ext>`.
+    ///
+    /// # Returns
+    /// A Result containing either the status of every page or a sync error.
+    pub fn sync_status(
+        &self,
+        dir: &std::path::Path,
+    ) -> Result<Vec<sync::SyncItem>, sync::SyncError> {
+        sync::sync_status(&self.client(), &format!("{}/graphql", self.url), dir)
+    }
+
+    /// Upload the local changes of a directory of page files to the wiki.
+    ///
+    /// # Arguments
+    /// * `dir` - The directory with one file per page at
+    ///   `<locale>/<path>.<ext>`.
+    /// * `cancel` - Optional token to stop before the remaining changes.
+    ///
+    /// # Returns
+    /// A Result containing either the result of each change or a sync error.
+    pub fn sync_push(
+        &self,
+        dir: &std::path::Path,
+        cancel: Option<&common::CancellationToken>,
+    ) -> Result<sync::SyncResult, sync::SyncError> {
+        sync::sync_push(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            dir,
+            cancel,
+        )
+    }
+
+    /// Download the changes of the wiki into a directory of page files.
+    ///
+    /// # Arguments
+    /// * `dir` - The directory with one file per page at
+    ///   `<locale>/<path>.<ext>`.
+    /// * `cancel` - Optional token to stop before the remaining changes.
+    ///
+    /// # Returns
+    /// A Result containing either the result of each change or a sync error.
+    pub fn sync_pull(
+        &self,
+        dir: &std::path::Path,
+        cancel: Option<&common::CancellationToken>,
+    ) -> Result<sync::SyncResult, sync::SyncError> {
+        sync::sync_pull(
+            &self.client(),
             &format!("{}/graphql", self.url),
-            older_than,
+            dir,
+            cancel,
         )
     }
 
@@ -839,7 +1870,7 @@ impl Api {
     ) -> Result<authentication::AuthenticationLoginResponse, user::UserError>
     {
         authentication::login(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             username,
             password,
@@ -847,12 +1878,28 @@ impl Api {
         )
     }
 
+    /// List the API keys that are revoked, already expired or expire
+    /// within the duration.
+    ///
+    /// # Arguments
+    /// * `within` - How far ahead to look for expiring keys.
+    pub fn api_key_expiring(
+        &self,
+        within: std::time::Duration,
+    ) -> Result<Vec<authentication::ApiKey>, user::UserError> {
+        authentication::api_key_expiring(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            within,
+        )
+    }
+
     /// List API keys.
     pub fn api_key_list(
         &self,
     ) -> Result<Vec<authentication::ApiKey>, user::UserError> {
         authentication::api_key_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -860,7 +1907,7 @@ impl Api {
     /// Get the current API state.
     pub fn api_state_get(&self) -> Result<bool, user::UserError> {
         authentication::api_state_get(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -871,7 +1918,7 @@ impl Api {
     ) -> Result<Vec<authentication::AuthenticationStrategy>, user::UserError>
     {
         authentication::authentication_strategy_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -888,7 +1935,7 @@ impl Api {
         user::UserError,
     > {
         authentication::authentication_active_strategy_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             enabled_only,
         )
@@ -909,7 +1956,7 @@ impl Api {
         group: Option<i64>,
     ) -> Result<String, user::UserError> {
         authentication::api_key_create(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             name,
             expiration,
@@ -932,7 +1979,7 @@ impl Api {
     ) -> Result<authentication::AuthenticationLoginResponse, user::UserError>
     {
         authentication::login_tfa(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             continuation_token,
             security_code,
@@ -952,7 +1999,7 @@ impl Api {
     ) -> Result<authentication::AuthenticationLoginResponse, user::UserError>
     {
         authentication::login_password_change(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             continuation_token,
             new_password,
@@ -968,7 +2015,7 @@ impl Api {
         email: String,
     ) -> Result<(), user::UserError> {
         authentication::password_forgot(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             email,
         )
@@ -988,7 +2035,7 @@ impl Api {
     ) -> Result<authentication::AuthenticationRegisterResponse, user::UserError>
     {
         authentication::register(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             email,
             password,
@@ -1002,7 +2049,7 @@ impl Api {
     /// * `id` - The id of the API key to revoke.
     pub fn api_key_revoke(&self, id: i64) -> Result<(), user::UserError> {
         authentication::api_key_revoke(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
         )
@@ -1014,7 +2061,7 @@ impl Api {
     /// * `enabled` - Whether the API should be enabled.
     pub fn api_state_set(&self, enabled: bool) -> Result<(), user::UserError> {
         authentication::api_state_set(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             enabled,
         )
@@ -1029,18 +2076,66 @@ impl Api {
         strategies: Vec<authentication::AuthenticationStrategyInput>,
     ) -> Result<(), user::UserError> {
         authentication::authentication_strategy_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             strategies,
         )
     }
 
+    /// Add an authentication strategy, keeping the active ones.
+    ///
+    /// # Arguments
+    /// * `strategy` - The strategy, like from
+    ///   [`AuthenticationStrategyConfig::to_strategy_input`](authentication/trait.AuthenticationStrategyConfig.html#method.to_strategy_input).
+    pub fn authentication_strategy_add(
+        &self,
+        strategy: authentication::AuthenticationStrategyInput,
+    ) -> Result<(), user::UserError> {
+        authentication::authentication_strategy_add(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            strategy,
+        )
+    }
+
+    /// Enable an active authentication strategy.
+    ///
+    /// # Arguments
+    /// * `key` - The key of the active strategy.
+    pub fn authentication_strategy_enable(
+        &self,
+        key: &str,
+    ) -> Result<(), user::UserError> {
+        authentication::authentication_strategy_set_enabled(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            key,
+            true,
+        )
+    }
+
+    /// Disable an active authentication strategy, keeping its config.
+    ///
+    /// # Arguments
+    /// * `key` - The key of the active strategy.
+    pub fn authentication_strategy_disable(
+        &self,
+        key: &str,
+    ) -> Result<(), user::UserError> {
+        authentication::authentication_strategy_set_enabled(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            key,
+            false,
+        )
+    }
+
     /// Regenerate the authentication certificates.
     pub fn authentication_certificate_regenerate(
         &self,
     ) -> Result<(), user::UserError> {
         authentication::authentication_certificate_regenerate(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -1048,7 +2143,7 @@ impl Api {
     /// Reset the guest user.
     pub fn guest_user_reset(&self) -> Result<(), user::UserError> {
         authentication::guest_user_reset(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -1060,7 +2155,7 @@ impl Api {
         &self,
     ) -> Result<Vec<contribute::Contributor>, contribute::ContributeError> {
         contribute::contributor_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -1073,7 +2168,7 @@ impl Api {
     ) -> Result<Vec<analytics::AnalyticsProvider>, analytics::AnalyticsError>
     {
         analytics::analytics_provider_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -1087,12 +2182,23 @@ impl Api {
         providers: Vec<analytics::AnalyticsProviderInput>,
     ) -> Result<(), analytics::AnalyticsError> {
         analytics::analytics_provider_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             providers,
         )
     }
 
+    /// Configure and enable an analytics provider from a typed config.
+    ///
+    /// # Arguments
+    /// * `config` - The config of the provider.
+    pub fn analytics_provider_configure(
+        &self,
+        config: &impl analytics::AnalyticsProviderConfig,
+    ) -> Result<(), analytics::AnalyticsError> {
+        self.analytics_provider_update(vec![config.to_provider_input()])
+    }
+
     // comment functions
 
     /// List all comments of a page
@@ -1106,7 +2212,7 @@ impl Api {
         path: String,
     ) -> Result<Vec<comment::Comment>, comment::CommentError> {
         comment::comment_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             locale,
             path,
@@ -1118,7 +2224,7 @@ impl Api {
         &self,
     ) -> Result<Vec<comment::CommentProvider>, comment::CommentError> {
         comment::comment_provider_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -1131,7 +2237,11 @@ impl Api {
         &self,
         id: i64,
     ) -> Result<comment::Comment, comment::CommentError> {
-        comment::comment_get(&self.client, &format!("{}/graphql", self.url), id)
+        comment::comment_get(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            id,
+        )
     }
 
     /// Update the comment providers.
@@ -1143,7 +2253,7 @@ impl Api {
         providers: Vec<comment::CommentProviderInput>,
     ) -> Result<(), comment::CommentError> {
         comment::comment_provider_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             providers,
         )
@@ -1168,7 +2278,7 @@ impl Api {
         guest_email: Option<String>,
     ) -> Result<(), comment::CommentError> {
         comment::comment_create(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             page_id,
             reply_to,
@@ -1189,7 +2299,7 @@ impl Api {
         content: String,
     ) -> Result<(), comment::CommentError> {
         comment::comment_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
             content,
@@ -1202,12 +2312,51 @@ impl Api {
     /// * `id` - The id of the comment to delete.
     pub fn comment_delete(&self, id: i64) -> Result<(), comment::CommentError> {
         comment::comment_delete(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
         )
     }
 
+    /// Reply to a comment.
+    ///
+    /// # Arguments
+    /// * `page_id` - The id of the page the comment belongs to.
+    /// * `reply_to` - The id of the comment to reply to.
+    /// * `content` - The content of the reply.
+    pub fn comment_reply(
+        &self,
+        page_id: i64,
+        reply_to: i64,
+        content: String,
+    ) -> Result<(), comment::CommentError> {
+        comment::comment_reply(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            page_id,
+            reply_to,
+            content,
+        )
+    }
+
+    /// Delete all comments of a page and return how many were deleted.
+    ///
+    /// # Arguments
+    /// * `locale` - The locale of the page.
+    /// * `path` - The path of the page.
+    pub fn comment_delete_all_for_page(
+        &self,
+        locale: String,
+        path: String,
+    ) -> Result<usize, comment::CommentError> {
+        comment::comment_delete_all_for_page(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            locale,
+            path,
+        )
+    }
+
     // user functions
 
     /// Get a user.
@@ -1215,7 +2364,7 @@ impl Api {
     /// # Arguments
     /// * `id` - The id of the user to get.
     pub fn user_get(&self, id: i64) -> Result<user::User, user::UserError> {
-        user::user_get(&self.client, &format!("{}/graphql", self.url), id)
+        user::user_get(&self.client(), &format!("{}/graphql", self.url), id)
     }
 
     /// List users.
@@ -1229,7 +2378,7 @@ impl Api {
         order_by: Option<String>,
     ) -> Result<Vec<user::UserMinimal>, user::UserError> {
         user::user_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             filter,
             order_by,
@@ -1241,7 +2390,11 @@ impl Api {
     /// # Arguments
     /// * `id` - The id of the user to activate.
     pub fn user_activate(&self, id: i64) -> Result<(), user::UserError> {
-        user::user_activate(&self.client, &format!("{}/graphql", self.url), id)
+        user::user_activate(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            id,
+        )
     }
 
     /// Deactivate a user.
@@ -1250,7 +2403,7 @@ impl Api {
     /// * `id` - The id of the user to deactivate.
     pub fn user_deactivate(&self, id: i64) -> Result<(), user::UserError> {
         user::user_deactivate(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
         )
@@ -1266,7 +2419,7 @@ impl Api {
         replace_id: i64,
     ) -> Result<(), user::UserError> {
         user::user_delete(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
             replace_id,
@@ -1279,7 +2432,7 @@ impl Api {
     /// * `id` - The id of the user.
     pub fn user_tfa_disable(&self, id: i64) -> Result<(), user::UserError> {
         user::user_tfa_disable(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
         )
@@ -1291,7 +2444,7 @@ impl Api {
     /// * `id` - The id of the user.
     pub fn user_tfa_enable(&self, id: i64) -> Result<(), user::UserError> {
         user::user_tfa_enable(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
         )
@@ -1302,7 +2455,7 @@ impl Api {
     /// # Arguments
     /// * `id` - The id of the user.
     pub fn user_verify(&self, id: i64) -> Result<(), user::UserError> {
-        user::user_verify(&self.client, &format!("{}/graphql", self.url), id)
+        user::user_verify(&self.client(), &format!("{}/graphql", self.url), id)
     }
 
     /// Search for users.
@@ -1313,14 +2466,18 @@ impl Api {
         &self,
         query: String,
     ) -> Result<Vec<user::UserMinimal>, user::UserError> {
-        user::user_search(&self.client, &format!("{}/graphql", self.url), query)
+        user::user_search(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            query,
+        )
     }
 
     /// Get the current user's profile.
     pub fn user_profile_get(
         &self,
     ) -> Result<user::UserProfile, user::UserError> {
-        user::user_profile_get(&self.client, &format!("{}/graphql", self.url))
+        user::user_profile_get(&self.client(), &format!("{}/graphql", self.url))
     }
 
     /// List the last logins.
@@ -1328,8 +2485,44 @@ impl Api {
         &self,
     ) -> Result<Vec<user::UserLastLogin>, user::UserError> {
         user::user_last_login_list(
-            &self.client,
+            &self.client(),
+            &format!("{}/graphql", self.url),
+        )
+    }
+
+    /// List the active users who did not log in since a date. Users who
+    /// never logged in count by their creation date.
+    ///
+    /// # Arguments
+    /// * `since` - The date of the last login to count as active.
+    pub fn user_inactive_list(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<user::UserMinimal>, user::UserError> {
+        user::user_inactive_list(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            since,
+        )
+    }
+
+    /// Create users from a CSV file with per row results.
+    ///
+    /// # Arguments
+    /// * `reader` - The CSV file, see
+    ///   [`parse_user_csv`](user/fn.parse_user_csv.html) for the format.
+    /// * `defaults` - Settings for rows that do not set them and whether to
+    ///   only validate the rows.
+    pub fn user_import_csv(
+        &self,
+        reader: impl std::io::Read,
+        defaults: &user::UserImportDefaults,
+    ) -> Result<Vec<user::UserImportOutcome>, user::UserError> {
+        user::user_import_csv(
+            &self.client(),
             &format!("{}/graphql", self.url),
+            reader,
+            defaults,
         )
     }
 
@@ -1355,7 +2548,7 @@ impl Api {
         send_welcome_email: Option<bool>,
     ) -> Result<(), user::UserError> {
         user::user_create(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             email,
             name,
@@ -1395,7 +2588,7 @@ impl Api {
         appearance: Option<String>,
     ) -> Result<(), user::UserError> {
         user::user_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
             email,
@@ -1430,7 +2623,7 @@ impl Api {
         appearance: String,
     ) -> Result<Option<String>, user::UserError> {
         user::user_profile_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             name,
             location,
@@ -1452,7 +2645,7 @@ impl Api {
         new: String,
     ) -> Result<Option<String>, user::UserError> {
         user::user_password_change(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             current,
             new,
@@ -1465,7 +2658,7 @@ impl Api {
     /// * `id` - The id of the user to reset the password for.
     pub fn user_password_reset(&self, id: i64) -> Result<(), user::UserError> {
         user::user_password_reset(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
         )
@@ -1484,7 +2677,7 @@ impl Api {
         order_by: Option<String>,
     ) -> Result<Vec<group::GroupMinimal>, group::GroupError> {
         group::group_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             filter,
             order_by,
@@ -1499,7 +2692,7 @@ impl Api {
         &self,
         id: i64,
     ) -> Result<group::Group, group::GroupError> {
-        group::group_get(&self.client, &format!("{}/graphql", self.url), id)
+        group::group_get(&self.client(), &format!("{}/graphql", self.url), id)
     }
 
     /// Create a new group.
@@ -1508,7 +2701,7 @@ impl Api {
     /// * `name` - The name of the group.
     pub fn group_create(&self, name: String) -> Result<(), group::GroupError> {
         group::group_create(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             name,
         )
@@ -1531,7 +2724,7 @@ impl Api {
         page_rules: Vec<group::PageRuleInput>,
     ) -> Result<(), group::GroupError> {
         group::group_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             id,
             name,
@@ -1546,7 +2739,91 @@ impl Api {
     /// # Arguments
     /// * `id` - The id of the group to delete.
     pub fn group_delete(&self, id: i64) -> Result<(), group::GroupError> {
-        group::group_delete(&self.client, &format!("{}/graphql", self.url), id)
+        group::group_delete(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            id,
+        )
+    }
+
+    /// Edit the permissions and page rules of a group and write them back.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the group.
+    /// * `edit` - Function changing the permissions and page rules.
+    pub fn group_rules_edit<F>(
+        &self,
+        id: i64,
+        edit: F,
+    ) -> Result<(), group::GroupError>
+    where
+        F: FnOnce(&mut Vec<String>, &mut Vec<group::PageRuleInput>),
+    {
+        group::group_rules_edit(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            id,
+            edit,
+        )
+    }
+
+    /// Grant a permission to a group.
+    ///
+    /// # Arguments
+    /// * `group_id` - The id of the group.
+    /// * `permission` - The permission to grant.
+    pub fn group_permission_add(
+        &self,
+        group_id: i64,
+        permission: group::Permission,
+    ) -> Result<(), group::GroupError> {
+        group::group_permission_add(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            group_id,
+            permission,
+        )
+    }
+
+    /// Revoke a permission from a group.
+    ///
+    /// # Arguments
+    /// * `group_id` - The id of the group.
+    /// * `permission` - The permission to revoke.
+    pub fn group_permission_remove(
+        &self,
+        group_id: i64,
+        permission: group::Permission,
+    ) -> Result<(), group::GroupError> {
+        group::group_permission_remove(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            group_id,
+            permission,
+        )
+    }
+
+    /// Make the users with the desired emails the only members of a group,
+    /// assigning and unassigning only where needed.
+    ///
+    /// # Arguments
+    /// * `group_id` - The id of the group.
+    /// * `desired_user_emails` - The emails of the users who should be
+    ///   members.
+    ///
+    /// # Returns
+    /// A Result containing either the applied changes or a group error.
+    pub fn group_membership_sync(
+        &self,
+        group_id: i64,
+        desired_user_emails: &[String],
+    ) -> Result<Vec<group::MembershipChange>, group::GroupError> {
+        group::group_membership_sync(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            group_id,
+            desired_user_emails,
+        )
     }
 
     /// Assign a user to a group.
@@ -1560,7 +2837,7 @@ impl Api {
         user_id: i64,
     ) -> Result<(), group::GroupError> {
         group::group_user_assign(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             group_id,
             user_id,
@@ -1578,7 +2855,7 @@ impl Api {
         user_id: i64,
     ) -> Result<(), group::GroupError> {
         group::group_user_unassign(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             group_id,
             user_id,
@@ -1592,7 +2869,7 @@ impl Api {
         &self,
     ) -> Result<Vec<localization::Locale>, localization::LocaleError> {
         localization::locale_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -1602,7 +2879,7 @@ impl Api {
         &self,
     ) -> Result<localization::LocaleConfig, localization::LocaleError> {
         localization::locale_config_get(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -1618,7 +2895,7 @@ impl Api {
         namespace: String,
     ) -> Result<Vec<localization::Translation>, localization::LocaleError> {
         localization::translation_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             locale,
             namespace,
@@ -1634,7 +2911,7 @@ impl Api {
         locale: String,
     ) -> Result<(), localization::LocaleError> {
         localization::locale_download(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             locale,
         )
@@ -1655,7 +2932,7 @@ impl Api {
         namespaces: Vec<String>,
     ) -> Result<(), localization::LocaleError> {
         localization::locale_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             locale,
             auto_update,
@@ -1677,7 +2954,7 @@ impl Api {
         order_by: Option<String>,
     ) -> Result<Vec<logging::Logger>, logging::LoggingError> {
         logging::logger_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             filter,
             order_by,
@@ -1693,7 +2970,7 @@ impl Api {
         loggers: Vec<logging::LoggerInput>,
     ) -> Result<(), logging::LoggingError> {
         logging::logger_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             loggers,
         )
@@ -1703,7 +2980,7 @@ impl Api {
 
     /// Get the mail configuration.
     pub fn mail_config_get(&self) -> Result<mail::MailConfig, mail::MailError> {
-        mail::mail_config_get(&self.client, &format!("{}/graphql", self.url))
+        mail::mail_config_get(&self.client(), &format!("{}/graphql", self.url))
     }
 
     /// Send a test mail.
@@ -1715,7 +2992,7 @@ impl Api {
         recipient_email: String,
     ) -> Result<(), mail::MailError> {
         mail::mail_send_test(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             recipient_email,
         )
@@ -1755,7 +3032,7 @@ impl Api {
         dkim_private_key: String,
     ) -> Result<(), mail::MailError> {
         mail::mail_config_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             sender_name,
             sender_email,
@@ -1780,7 +3057,7 @@ impl Api {
         &self,
     ) -> Result<navigation::NavigationConfig, navigation::NavigationError> {
         navigation::navigation_config_get(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -1791,7 +3068,7 @@ impl Api {
     ) -> Result<Vec<navigation::NavigationTree>, navigation::NavigationError>
     {
         navigation::navigation_tree_get(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -1805,7 +3082,7 @@ impl Api {
         mode: navigation::NavigationMode,
     ) -> Result<(), navigation::NavigationError> {
         navigation::navigation_config_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             mode,
         )
@@ -1820,26 +3097,55 @@ impl Api {
         tree: Vec<navigation::NavigationTreeInput>,
     ) -> Result<(), navigation::NavigationError> {
         navigation::navigation_tree_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             tree,
         )
     }
 
+    /// Edit the navigation tree of a locale and write it back.
+    ///
+    /// # Arguments
+    /// * `locale` - The locale of the tree, it is created if missing.
+    /// * `edit` - Function changing the tree, like inserting an item.
+    pub fn navigation_tree_edit<F>(
+        &self,
+        locale: &str,
+        edit: F,
+    ) -> Result<(), navigation::NavigationError>
+    where
+        F: FnOnce(
+            &mut navigation::NavigationTreeInput,
+        ) -> Result<(), navigation::NavigationError>,
+    {
+        navigation::navigation_tree_edit(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            locale,
+            edit,
+        )
+    }
+
     // system functions
 
     /// List all system flags.
     pub fn system_flag_list(
         &self,
     ) -> Result<Vec<system::SystemFlag>, system::SystemError> {
-        system::system_flag_list(&self.client, &format!("{}/graphql", self.url))
+        system::system_flag_list(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+        )
     }
 
     /// Get the system info.
     pub fn system_info_get(
         &self,
     ) -> Result<system::SystemInfo, system::SystemError> {
-        system::system_info_get(&self.client, &format!("{}/graphql", self.url))
+        system::system_info_get(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+        )
     }
 
     /// List all system extensions.
@@ -1847,7 +3153,7 @@ impl Api {
         &self,
     ) -> Result<Vec<system::SystemExtension>, system::SystemError> {
         system::system_extension_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -1857,7 +3163,7 @@ impl Api {
         &self,
     ) -> Result<system::SystemExportStatus, system::SystemError> {
         system::system_export_status_get(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -1871,7 +3177,7 @@ impl Api {
         flags: Vec<system::SystemFlagInput>,
     ) -> Result<(), system::SystemError> {
         system::system_flags_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             flags,
         )
@@ -1880,7 +3186,7 @@ impl Api {
     /// Reset the telemetry client id.
     pub fn telemetry_client_id_reset(&self) -> Result<(), system::SystemError> {
         system::telemetry_client_id_reset(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -1894,7 +3200,7 @@ impl Api {
         enabled: bool,
     ) -> Result<(), system::SystemError> {
         system::telemetry_set(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             enabled,
         )
@@ -1903,7 +3209,7 @@ impl Api {
     /// Perform a system upgrade.
     pub fn system_upgrade_perform(&self) -> Result<(), system::SystemError> {
         system::system_upgrade_perform(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -1919,7 +3225,7 @@ impl Api {
         group_code: system::SystemImportUsersGroupMode,
     ) -> Result<(), system::SystemError> {
         system::system_user_import_from_v1(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             mongo_db_conn_string,
             group_code,
@@ -1935,7 +3241,7 @@ impl Api {
         enabled: bool,
     ) -> Result<(), system::SystemError> {
         system::https_redirection_set(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             enabled,
         )
@@ -1944,7 +3250,7 @@ impl Api {
     /// Renew the https certificate.
     pub fn https_certificate_renew(&self) -> Result<(), system::SystemError> {
         system::https_certificate_renew(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -1955,7 +3261,7 @@ impl Api {
     pub fn theme_list(
         &self,
     ) -> Result<Vec<theming::Theme>, theming::ThemeError> {
-        theming::theme_list(&self.client, &format!("{}/graphql", self.url))
+        theming::theme_list(&self.client(), &format!("{}/graphql", self.url))
     }
 
     /// Get the theme configuration.
@@ -1963,7 +3269,7 @@ impl Api {
         &self,
     ) -> Result<theming::ThemingConfig, theming::ThemeError> {
         theming::theme_config_get(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -1990,7 +3296,7 @@ impl Api {
         inject_body: Option<String>,
     ) -> Result<(), theming::ThemeError> {
         theming::theme_config_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             theme,
             iconset,
@@ -2015,7 +3321,7 @@ impl Api {
         order_by: Option<String>,
     ) -> Result<Vec<rendering::Renderer>, rendering::RenderingError> {
         rendering::renderer_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             filter,
             order_by,
@@ -2031,12 +3337,23 @@ impl Api {
         renderers: Vec<rendering::RendererInput>,
     ) -> Result<(), rendering::RenderingError> {
         rendering::renderer_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             renderers,
         )
     }
 
+    /// Configure and enable a renderer from a typed config.
+    ///
+    /// # Arguments
+    /// * `config` - The config of the renderer.
+    pub fn renderer_configure(
+        &self,
+        config: &impl rendering::RendererConfig,
+    ) -> Result<(), rendering::RenderingError> {
+        self.renderer_update(vec![config.to_renderer_input()])
+    }
+
     // search functions
 
     /// List search engines.
@@ -2050,7 +3367,7 @@ impl Api {
         order_by: Option<String>,
     ) -> Result<Vec<search::SearchEngine>, search::SearchError> {
         search::search_engine_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             filter,
             order_by,
@@ -2062,7 +3379,7 @@ impl Api {
         &self,
     ) -> Result<(), search::SearchError> {
         search::search_engine_index_rebuild(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -2076,17 +3393,39 @@ impl Api {
         engines: Vec<search::SearchEngineInput>,
     ) -> Result<(), search::SearchError> {
         search::search_engine_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             engines,
         )
     }
 
+    /// Configure a search engine from a typed config and make it the only
+    /// enabled one.
+    ///
+    /// # Arguments
+    /// * `config` - The config of the search engine.
+    pub fn search_engine_configure(
+        &self,
+        config: &impl search::SearchEngineConfig,
+    ) -> Result<(), search::SearchError> {
+        let mut engines: Vec<search::SearchEngineInput> = self
+            .search_engine_list(None, None)?
+            .iter()
+            .filter(|engine| engine.key != config.key())
+            .map(|engine| search::SearchEngineInput {
+                is_enabled: false,
+                ..engine.to_input()
+            })
+            .collect();
+        engines.push(config.to_engine_input());
+        self.search_engine_update(engines)
+    }
+
     // site functions
 
     /// Get the site configuration.
     pub fn site_config_get(&self) -> Result<site::SiteConfig, site::SiteError> {
-        site::site_config_get(&self.client, &format!("{}/graphql", self.url))
+        site::site_config_get(&self.client(), &format!("{}/graphql", self.url))
     }
 
     /// Update the site configuration.
@@ -2098,12 +3437,31 @@ impl Api {
         config: site::SiteConfig,
     ) -> Result<(), site::SiteError> {
         site::site_config_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             config,
         )
     }
 
+    /// Update only some fields of the site configuration.
+    ///
+    /// # Arguments
+    /// * `changes` - The fields to change.
+    ///
+    /// # Returns
+    /// A Result containing either the new site configuration or a site
+    /// error.
+    pub fn site_config_update_partial(
+        &self,
+        changes: &site::SiteConfigUpdateBuilder,
+    ) -> Result<site::SiteConfig, site::SiteError> {
+        site::site_config_update_partial(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            changes,
+        )
+    }
+
     // storage functions
 
     /// Execute a storage action.
@@ -2117,7 +3475,7 @@ impl Api {
         handler: String,
     ) -> Result<(), storage::StorageError> {
         storage::storage_action_execute(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             target_key,
             handler,
@@ -2129,7 +3487,7 @@ impl Api {
         &self,
     ) -> Result<Vec<storage::StorageStatus>, storage::StorageError> {
         storage::storage_status_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -2139,7 +3497,7 @@ impl Api {
         &self,
     ) -> Result<Vec<storage::StorageTarget>, storage::StorageError> {
         storage::storage_target_list(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
         )
     }
@@ -2153,9 +3511,206 @@ impl Api {
         targets: Vec<storage::StorageTargetInput>,
     ) -> Result<(), storage::StorageError> {
         storage::storage_target_update(
-            &self.client,
+            &self.client(),
             &format!("{}/graphql", self.url),
             targets,
         )
     }
+
+    /// Configure and enable a storage target from a typed config.
+    ///
+    /// # Arguments
+    /// * `config` - The config of the storage module.
+    /// * `mode` - The sync mode, `sync`, `push` or `pull`.
+    pub fn storage_target_configure(
+        &self,
+        config: &impl storage::StorageConfig,
+        mode: &str,
+    ) -> Result<(), storage::StorageError> {
+        self.storage_target_update(vec![config.to_target_input(mode)])
+    }
+
+    /// Force a sync of the git storage target with its remote.
+    pub fn storage_git_sync(&self) -> Result<(), storage::StorageError> {
+        self.storage_action_execute("git".to_string(), "sync".to_string())
+    }
+
+    /// Write all content to the git storage target, committing untracked
+    /// pages and assets.
+    pub fn storage_git_export_all(&self) -> Result<(), storage::StorageError> {
+        self.storage_action_execute(
+            "git".to_string(),
+            "syncUntracked".to_string(),
+        )
+    }
+
+    /// Import all content of the git storage target into the wiki.
+    pub fn storage_git_import_all(&self) -> Result<(), storage::StorageError> {
+        self.storage_action_execute("git".to_string(), "importAll".to_string())
+    }
+
+    /// Delete the local clone of the git storage target.
+    pub fn storage_git_purge(&self) -> Result<(), storage::StorageError> {
+        self.storage_action_execute("git".to_string(), "purge".to_string())
+    }
+
+    /// Export all content to a storage target that supports it, like `s3`,
+    /// `azure` or `disk`.
+    ///
+    /// # Arguments
+    /// * `target_key` - The key of the storage target.
+    pub fn storage_export_all(
+        &self,
+        target_key: &str,
+    ) -> Result<(), storage::StorageError> {
+        let handler = match target_key {
+            "disk" => "dump",
+            _ => "exportAll",
+        };
+        self.storage_action_execute(target_key.to_string(), handler.to_string())
+    }
+
+    // watch functions
+
+    /// Watch the wiki for page changes by polling the page list.
+    ///
+    /// # Arguments
+    /// * `interval` - The time between two polls.
+    /// * `filter` - Restricts which pages are watched.
+    /// * `cancel` - Optional token ending the iterator.
+    ///
+    /// # Returns
+    /// A Result containing either an iterator blocking until the next
+    /// change or a watch error if the pages could not be listed. See
+    /// [`watch::ChangeWatcher`](watch/struct.ChangeWatcher.html) for
+    /// debouncing and comment events.
+    pub fn watch_changes(
+        &self,
+        interval: std::time::Duration,
+        filter: replace::PageFilter,
+        cancel: Option<&common::CancellationToken>,
+    ) -> Result<watch::ChangeWatcher, watch::WatchError> {
+        watch::ChangeWatcher::new(
+            self.client.clone(),
+            self.strict,
+            format!("{}/graphql", self.url),
+            interval,
+            filter,
+            cancel,
+        )
+    }
+
+    // batch functions
+
+    /// Send several queries in one batched request.
+    ///
+    /// # Arguments
+    /// * `request` - The batch of queries to send.
+    ///
+    /// # Returns
+    /// One response per query in the order they were added.
+    pub fn batch_send(
+        &self,
+        request: batch::BatchRequest,
+    ) -> Vec<batch::BatchResponse> {
+        batch::batch_send(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            request,
+        )
+    }
+
+    /// Fetch many pages at once, in batched requests of 50 pages sent
+    /// concurrently as set by
+    /// [`with_concurrency`](#method.with_concurrency).
+    ///
+    /// # Arguments
+    /// * `ids` - The ids of the pages to fetch.
+    ///
+    /// # Returns
+    /// The page or page error of every id, so a page that fails does not
+    /// fail the others.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wikijs::{Api, Credentials};
+    ///
+    /// let api = Api::new(
+    ///     "http://localhost:3000".to_string(),
+    ///     Credentials::Key("my-api-key".to_string()),
+    /// ).unwrap();
+    /// for (id, page) in api.page_get_many(&[1, 2, 3]) {
+    ///     println!("{}: {:?}", id, page.map(|p| p.title));
+    /// }
+    /// ```
+    pub fn page_get_many(
+        &self,
+        ids: &[i64],
+    ) -> std::collections::HashMap<i64, Result<page::Page, page::PageError>>
+    {
+        batch::page_get_map(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            ids,
+            None,
+        )
+    }
+
+    // index functions
+
+    /// Bring the attached search index in line with the wiki, fetching
+    /// only the pages updated since they were indexed, and save it.
+    ///
+    /// # Returns
+    /// A Result containing either the changes to the index or an index
+    /// error.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wikijs::index::SearchIndex;
+    /// use wikijs::{Api, Credentials};
+    ///
+    /// let api = Api::new(
+    ///     "http://localhost:3000".to_string(),
+    ///     Credentials::Key("my-api-key".to_string()),
+    /// )
+    /// .unwrap()
+    /// .with_index(SearchIndex::open("index").unwrap());
+    /// api.local_index_update().unwrap();
+    /// for hit in api.local_search("install docker", 10).unwrap() {
+    ///     println!("{:.2} {}", hit.score, hit.title);
+    /// }
+    /// ```
+    #[cfg(feature = "index")]
+    pub fn local_index_update(
+        &self,
+    ) -> Result<index::IndexUpdate, index::IndexError> {
+        let index = self.index.as_ref().ok_or(index::IndexError::NoIndex)?;
+        let update = index.update(
+            &self.client(),
+            &format!("{}/graphql", self.url),
+            None,
+        )?;
+        index.save()?;
+        Ok(update)
+    }
+
+    /// Search the attached search index, without contacting the server.
+    ///
+    /// # Arguments
+    /// * `query` - The words to search for.
+    /// * `limit` - The maximum number of pages to return.
+    ///
+    /// # Returns
+    /// A Result containing either the matching pages, the most relevant
+    /// first, or an index error.
+    #[cfg(feature = "index")]
+    pub fn local_search(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<index::IndexHit>, index::IndexError> {
+        let index = self.index.as_ref().ok_or(index::IndexError::NoIndex)?;
+        index.search(query, limit)
+    }
 }
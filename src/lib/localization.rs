@@ -1,11 +1,11 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::common::{
-    classify_response_error, classify_response_status_error, Boolean, Date,
-    Int, KnownErrorCodes, ResponseStatus, UnknownError,
+    classify_post_error, classify_response_error,
+    classify_response_status_error, post_graphql, Boolean, Date, Int,
+    KnownErrorCodes, ResponseStatus, UnknownError,
 };
 
 #[derive(Clone, Debug, Error, PartialEq)]
@@ -18,6 +18,8 @@ pub enum LocaleError {
     UnknownErrorCode { code: i64, message: String },
     #[error("Unknown response error: {message}")]
     UnknownErrorMessage { message: String },
+    #[error("Request failed: {message}")]
+    RequestFailed { message: String },
     #[error("Unknown response error.")]
     UnknownError,
 }
@@ -42,6 +44,9 @@ impl UnknownError for LocaleError {
     fn unknown_error_message(message: String) -> Self {
         LocaleError::UnknownErrorMessage { message }
     }
+    fn request_failed(message: String) -> Self {
+        LocaleError::RequestFailed { message }
+    }
     fn unknown_error() -> Self {
         LocaleError::UnknownError
     }
@@ -57,7 +62,7 @@ impl KnownErrorCodes for LocaleError {
     }
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Locale {
     pub availability: Int,
     pub code: String,
@@ -76,7 +81,7 @@ pub struct Locale {
     pub updated_at: Date,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct LocaleConfig {
     pub locale: String,
     #[serde(rename = "autoUpdate")]
@@ -85,7 +90,7 @@ pub struct LocaleConfig {
     pub namespaces: Vec<Option<String>>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Translation {
     pub key: String,
     pub value: String,
@@ -102,12 +107,12 @@ pub mod locale_list {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub localization: Option<Localization>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Localization {
         pub locales: Option<Vec<Option<Locale>>>,
     }
@@ -135,9 +140,7 @@ pub fn locale_list(
     let response =
         post_graphql::<locale_list::LocaleList, _>(client, url, variables);
     if response.is_err() {
-        return Err(LocaleError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -162,12 +165,12 @@ pub mod locale_config_get {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub localization: Option<Localization>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Localization {
         pub config: Option<LocaleConfig>,
     }
@@ -196,9 +199,7 @@ pub fn locale_config_get(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(LocaleError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -227,11 +228,11 @@ pub mod translation_list {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub localization: Option<Localization>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Localization {
         pub translations: Option<Vec<Option<Translation>>>,
     }
@@ -262,9 +263,7 @@ pub fn translation_list(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(LocaleError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -292,18 +291,18 @@ pub mod locale_download {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub localization: Option<Localization>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Localization {
         #[serde(rename = "downloadLocale")]
         pub download_locale: Option<DownloadLocale>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct DownloadLocale {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -334,9 +333,7 @@ pub fn locale_download(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(LocaleError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -376,18 +373,18 @@ pub mod locale_update {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub localization: Option<Localization>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Localization {
         #[serde(rename = "updateLocale")]
         pub update_locale: Option<UpdateLocale>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct UpdateLocale {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -425,9 +422,7 @@ pub fn locale_update(
     let response =
         post_graphql::<locale_update::LocaleUpdate, _>(client, url, variables);
     if response.is_err() {
-        return Err(LocaleError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
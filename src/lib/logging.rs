@@ -1,12 +1,11 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::common::{
-    classify_response_error, classify_response_status_error, Boolean,
-    KeyValuePair, KeyValuePairInput, KnownErrorCodes, ResponseStatus,
-    UnknownError,
+    classify_post_error, classify_response_error,
+    classify_response_status_error, post_graphql, Boolean, KeyValuePair,
+    KeyValuePairInput, KnownErrorCodes, ResponseStatus, UnknownError,
 };
 
 #[derive(Clone, Debug, Error, PartialEq)]
@@ -15,6 +14,8 @@ pub enum LoggingError {
     UnknownErrorCode { code: i64, message: String },
     #[error("Unknown response error: {message}")]
     UnknownErrorMessage { message: String },
+    #[error("Request failed: {message}")]
+    RequestFailed { message: String },
     #[error("Unknown response error.")]
     UnknownError,
 }
@@ -35,6 +36,9 @@ impl UnknownError for LoggingError {
     fn unknown_error_message(message: String) -> Self {
         LoggingError::UnknownErrorMessage { message }
     }
+    fn request_failed(message: String) -> Self {
+        LoggingError::RequestFailed { message }
+    }
     fn unknown_error() -> Self {
         LoggingError::UnknownError
     }
@@ -50,7 +54,7 @@ impl KnownErrorCodes for LoggingError {
     }
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Logger {
     #[serde(rename = "isEnabled")]
     pub is_enabled: Boolean,
@@ -89,12 +93,12 @@ pub mod logger_list {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub logging: Option<Logging>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Logging {
         pub loggers: Option<Vec<Option<Logger>>>,
     }
@@ -124,9 +128,7 @@ pub fn logger_list(
     let response =
         post_graphql::<logger_list::LoggerList, _>(client, url, variables);
     if let Err(e) = response {
-        return Err(LoggingError::UnknownErrorMessage {
-            message: e.to_string(),
-        });
+        return Err(classify_post_error(e));
     }
     let response = response.unwrap();
     if let Some(data) = response.data {
@@ -154,18 +156,18 @@ pub mod logger_update {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub logging: Option<Logging>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Logging {
         #[serde(rename = "updateLoggers")]
         pub update_loggers: Option<UpdateLoggers>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct UpdateLoggers {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -197,9 +199,7 @@ pub fn logger_update(
     let response =
         post_graphql::<logger_update::LoggerUpdate, _>(client, url, variables);
     if let Err(e) = response {
-        return Err(LoggingError::UnknownErrorMessage {
-            message: e.to_string(),
-        });
+        return Err(classify_post_error(e));
     }
     let response = response.unwrap();
     if let Some(data) = response.data {
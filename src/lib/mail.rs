@@ -1,10 +1,10 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::common::{
-    classify_response_error, classify_response_status_error, Boolean, Int,
+    classify_post_error, classify_response_error,
+    classify_response_status_error, post_graphql, Boolean, Int,
     KnownErrorCodes, ResponseStatus, UnknownError,
 };
 
@@ -22,6 +22,8 @@ pub enum MailError {
     UnknownErrorCode { code: i64, message: String },
     #[error("Unknown response error: {message}")]
     UnknownErrorMessage { message: String },
+    #[error("Request failed: {message}")]
+    RequestFailed { message: String },
     #[error("Unknown response error.")]
     UnknownError,
 }
@@ -48,6 +50,9 @@ impl UnknownError for MailError {
     fn unknown_error_message(message: String) -> Self {
         MailError::UnknownErrorMessage { message }
     }
+    fn request_failed(message: String) -> Self {
+        MailError::RequestFailed { message }
+    }
     fn unknown_error() -> Self {
         MailError::UnknownError
     }
@@ -63,7 +68,7 @@ impl KnownErrorCodes for MailError {
     }
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct MailConfig {
     #[serde(rename = "senderName")]
     pub sender_name: Option<String>,
@@ -98,12 +103,12 @@ pub mod mail_config_get {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub mail: Option<Mail>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Mail {
         pub config: Option<MailConfig>,
     }
@@ -132,9 +137,7 @@ pub fn mail_config_get(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(MailError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -163,18 +166,18 @@ pub mod mail_send_test {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub mail: Option<Mail>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Mail {
         #[serde(rename = "sendTest")]
         pub send_test: Option<SendTest>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct SendTest {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -204,9 +207,7 @@ pub fn mail_send_test(
     let response =
         post_graphql::<mail_send_test::MailSendTest, _>(client, url, variables);
     if response.is_err() {
-        return Err(MailError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -263,17 +264,17 @@ pub mod mail_config_update {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub mail: Option<Mail>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Mail {
         #[serde(rename = "updateConfig")]
         pub update_config: Option<UpdateConfig>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct UpdateConfig {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -331,9 +332,7 @@ pub fn mail_config_update(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(MailError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
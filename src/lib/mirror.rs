@@ -0,0 +1,498 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use thiserror::Error;
+
+use crate::asset::{
+    asset_delete, asset_download, asset_folder_create_all, asset_folder_tree,
+    asset_list_recursive, asset_upload, AssetError, AssetItemWithPath,
+    AssetKind,
+};
+use crate::batch::page_get_many;
+use crate::common::{
+    base_url, concurrency, is_cancelled, run_parallel, CancellationToken, Int,
+};
+use crate::page::{
+    page_create, page_delete, page_get, page_update, Page, PageError,
+};
+use crate::replace::PageFilter;
+use crate::Api;
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum MirrorError {
+    #[error(transparent)]
+    Page(#[from] PageError),
+    #[error(transparent)]
+    Asset(#[from] AssetError),
+}
+
+/// Settings of [`mirror_plan`](fn.mirror_plan.html).
+#[derive(Clone, Debug, Default)]
+pub struct MirrorOptions {
+    /// Only mirror the pages matching this filter. Pages outside of it are
+    /// neither copied nor deleted on the destination. Assets have no locale
+    /// or tags, so only its path prefix applies to them, and no assets are
+    /// deleted when it filters by locale or tags.
+    pub filter: PageFilter,
+    /// Delete pages and assets on the destination that do not exist on the
+    /// source.
+    pub delete: bool,
+    /// Leave out the assets.
+    pub skip_assets: bool,
+}
+
+/// A single change to bring the destination in line with the source.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MirrorAction {
+    AssetUpload {
+        folder_path: String,
+        filename: String,
+    },
+    PageCreate {
+        locale: String,
+        path: String,
+        source_id: Int,
+    },
+    PageUpdate {
+        locale: String,
+        path: String,
+        source_id: Int,
+        dest_id: Int,
+    },
+    PageDelete {
+        locale: String,
+        path: String,
+        dest_id: Int,
+    },
+    AssetDelete {
+        path: String,
+        dest_id: Int,
+    },
+}
+
+impl Display for MirrorAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MirrorAction::AssetUpload {
+                folder_path,
+                filename,
+            } if folder_path.is_empty() => {
+                write!(f, "upload asset {}", filename)
+            }
+            MirrorAction::AssetUpload {
+                folder_path,
+                filename,
+            } => write!(f, "upload asset {}/{}", folder_path, filename),
+            MirrorAction::PageCreate { locale, path, .. } => {
+                write!(f, "create page {}/{}", locale, path)
+            }
+            MirrorAction::PageUpdate { locale, path, .. } => {
+                write!(f, "update page {}/{}", locale, path)
+            }
+            MirrorAction::PageDelete { locale, path, .. } => {
+                write!(f, "delete page {}/{}", locale, path)
+            }
+            MirrorAction::AssetDelete { path, .. } => {
+                write!(f, "delete asset {}", path)
+            }
+        }
+    }
+}
+
+/// The changes a mirror run would make, in the order they are applied:
+/// assets first so new pages can link them, deletions last.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MirrorPlan {
+    pub actions: Vec<MirrorAction>,
+}
+
+impl MirrorPlan {
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+/// Result of applying one [`MirrorAction`](enum.MirrorAction.html).
+pub type MirrorResult = Vec<(MirrorAction, Result<(), MirrorError>)>;
+
+fn tag_names(page: &Page) -> Vec<String> {
+    let mut tags: Vec<String> =
+        page.tags.iter().flatten().map(|t| t.tag.clone()).collect();
+    tags.sort();
+    tags
+}
+
+fn page_differs(source: &Page, dest: &Page) -> bool {
+    source.content != dest.content
+        || source.title != dest.title
+        || source.description != dest.description
+        || source.editor != dest.editor
+        || source.is_published != dest.is_published
+        || source.is_private != dest.is_private
+        || source.publish_start_date != dest.publish_start_date
+        || source.publish_end_date != dest.publish_end_date
+        || source.script_css != dest.script_css
+        || source.script_js != dest.script_js
+        || tag_names(source) != tag_names(dest)
+}
+
+fn pages_by_key(
+    api: &Api,
+    filter: &PageFilter,
+) -> Result<BTreeMap<(String, String), Page>, PageError> {
+    let url = format!("{}/graphql", api.url);
+    let ids = filter.page_ids(&api.client(), &url)?;
+    Ok(page_get_many(&api.client(), &url, &ids, None)?
+        .into_iter()
+        .map(|page| ((page.locale.clone(), page.path.clone()), page))
+        .collect())
+}
+
+fn assets_by_path(
+    api: &Api,
+) -> Result<BTreeMap<String, AssetItemWithPath>, AssetError> {
+    Ok(asset_list_recursive(
+        &api.client(),
+        &format!("{}/graphql", api.url),
+        0,
+        AssetKind::ALL,
+    )?
+    .into_iter()
+    .map(|asset| (asset.path(), asset))
+    .collect())
+}
+
+/// Compare two wikis and plan the changes that make the destination a
+/// mirror of the source.
+///
+/// Pages are matched by locale and path and updated when their content or
+/// metadata differ. Assets are matched by path and uploaded again when
+/// their size differs or the source copy is newer.
+///
+/// # Returns
+/// A Result containing either the plan or a mirror error.
+pub fn mirror_plan(
+    source: &Api,
+    dest: &Api,
+    options: &MirrorOptions,
+) -> Result<MirrorPlan, MirrorError> {
+    let mut actions = Vec::new();
+
+    let mut asset_deletions = Vec::new();
+    if !options.skip_assets {
+        let filter = &options.filter;
+        let in_filter = |path: &String, _: &mut AssetItemWithPath| {
+            filter
+                .path_prefix
+                .as_ref()
+                .is_none_or(|prefix| path.starts_with(prefix.as_str()))
+        };
+        let mut source_assets = assets_by_path(source)?;
+        let mut dest_assets = assets_by_path(dest)?;
+        source_assets.retain(in_filter);
+        dest_assets.retain(in_filter);
+        for (path, asset) in &source_assets {
+            let outdated = dest_assets.get(path).is_none_or(|d| {
+                d.asset.file_size != asset.asset.file_size
+                    || d.asset.updated_at < asset.asset.updated_at
+            });
+            if outdated {
+                actions.push(MirrorAction::AssetUpload {
+                    folder_path: asset.folder_path.clone(),
+                    filename: asset.asset.filename.clone(),
+                });
+            }
+        }
+        if options.delete && filter.locale.is_none() && filter.tags.is_none() {
+            for (path, asset) in &dest_assets {
+                if !source_assets.contains_key(path) {
+                    asset_deletions.push(MirrorAction::AssetDelete {
+                        path: path.clone(),
+                        dest_id: asset.asset.id,
+                    });
+                }
+            }
+        }
+    }
+
+    let source_pages = pages_by_key(source, &options.filter)?;
+    let dest_pages = pages_by_key(dest, &options.filter)?;
+    for ((locale, path), page) in &source_pages {
+        match dest_pages.get(&(locale.clone(), path.clone())) {
+            None => actions.push(MirrorAction::PageCreate {
+                locale: locale.clone(),
+                path: path.clone(),
+                source_id: page.id,
+            }),
+            Some(dest_page) if page_differs(page, dest_page) => {
+                actions.push(MirrorAction::PageUpdate {
+                    locale: locale.clone(),
+                    path: path.clone(),
+                    source_id: page.id,
+                    dest_id: dest_page.id,
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    if options.delete {
+        for ((locale, path), page) in &dest_pages {
+            if !source_pages.contains_key(&(locale.clone(), path.clone())) {
+                actions.push(MirrorAction::PageDelete {
+                    locale: locale.clone(),
+                    path: path.clone(),
+                    dest_id: page.id,
+                });
+            }
+        }
+    }
+    actions.extend(asset_deletions);
+    Ok(MirrorPlan { actions })
+}
+
+/// Differences between two wikis, pages as `<locale>/<path>` and assets as
+/// their path. Added means only on the right, removed only on the left.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WikiDiff {
+    pub pages_added: Vec<String>,
+    pub pages_removed: Vec<String>,
+    /// Pages whose content or metadata differ.
+    pub pages_modified: Vec<String>,
+    pub assets_added: Vec<String>,
+    pub assets_removed: Vec<String>,
+    /// Assets whose file size differs.
+    pub assets_modified: Vec<String>,
+}
+
+impl WikiDiff {
+    pub fn is_empty(&self) -> bool {
+        self.pages_added.is_empty()
+            && self.pages_removed.is_empty()
+            && self.pages_modified.is_empty()
+            && self.assets_added.is_empty()
+            && self.assets_removed.is_empty()
+            && self.assets_modified.is_empty()
+    }
+}
+
+// split the keys of two maps into only right, only left and modified
+fn diff_maps<'a, K: Ord, V>(
+    left: &'a BTreeMap<K, V>,
+    right: &'a BTreeMap<K, V>,
+    differs: impl Fn(&V, &V) -> bool,
+) -> (Vec<&'a K>, Vec<&'a K>, Vec<&'a K>) {
+    let added = right.keys().filter(|k| !left.contains_key(k)).collect();
+    let mut removed = Vec::new();
+    let mut modified = Vec::new();
+    for (key, value) in left {
+        match right.get(key) {
+            None => removed.push(key),
+            Some(other) if differs(value, other) => modified.push(key),
+            Some(_) => {}
+        }
+    }
+    (added, removed, modified)
+}
+
+/// Compare the pages and assets of two wikis, for example staging and
+/// production before and after a migration.
+///
+/// # Arguments
+/// * `filter` - Only compare the pages matching this filter.
+/// * `skip_assets` - Leave out the assets.
+///
+/// # Returns
+/// A Result containing either the differences or a mirror error.
+pub fn wiki_diff(
+    left: &Api,
+    right: &Api,
+    filter: &PageFilter,
+    skip_assets: bool,
+) -> Result<WikiDiff, MirrorError> {
+    let left_pages = pages_by_key(left, filter)?;
+    let right_pages = pages_by_key(right, filter)?;
+    let page_name =
+        |(locale, path): &(String, String)| format!("{}/{}", locale, path);
+    let (added, removed, modified) =
+        diff_maps(&left_pages, &right_pages, page_differs);
+    let mut diff = WikiDiff {
+        pages_added: added.into_iter().map(page_name).collect(),
+        pages_removed: removed.into_iter().map(page_name).collect(),
+        pages_modified: modified.into_iter().map(page_name).collect(),
+        ..Default::default()
+    };
+    if !skip_assets {
+        let left_assets = assets_by_path(left)?;
+        let right_assets = assets_by_path(right)?;
+        let (added, removed, modified) =
+            diff_maps(&left_assets, &right_assets, |l, r| {
+                l.asset.file_size != r.asset.file_size
+            });
+        diff.assets_added = added.into_iter().cloned().collect();
+        diff.assets_removed = removed.into_iter().cloned().collect();
+        diff.assets_modified = modified.into_iter().cloned().collect();
+    }
+    Ok(diff)
+}
+
+// the folders of all uploads, created up front as creating them from
+// concurrent uploads would race
+fn create_asset_folders(
+    dest: &Api,
+    plan: &MirrorPlan,
+) -> Result<BTreeMap<String, Int>, AssetError> {
+    let paths: BTreeSet<&String> = plan
+        .actions
+        .iter()
+        .filter_map(|action| match action {
+            MirrorAction::AssetUpload { folder_path, .. } => Some(folder_path),
+            _ => None,
+        })
+        .collect();
+    if paths.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    let url = format!("{}/graphql", dest.url);
+    let mut tree = asset_folder_tree(&dest.client(), &url)?;
+    paths
+        .into_iter()
+        .map(|path| {
+            let id =
+                asset_folder_create_all(&dest.client(), &url, &mut tree, path)?;
+            Ok((path.clone(), id))
+        })
+        .collect()
+}
+
+fn apply_action(
+    source: &Api,
+    dest: &Api,
+    folders: &Result<BTreeMap<String, Int>, AssetError>,
+    action: &MirrorAction,
+) -> Result<(), MirrorError> {
+    let source_url = format!("{}/graphql", source.url);
+    let dest_url = format!("{}/graphql", dest.url);
+    match action {
+        MirrorAction::AssetUpload {
+            folder_path,
+            filename,
+        } => {
+            let folder = folders.as_ref().map_err(Clone::clone)?[folder_path];
+            let path = if folder_path.is_empty() {
+                filename.clone()
+            } else {
+                format!("{}/{}", folder_path, filename)
+            };
+            let data =
+                asset_download(&source.client(), base_url(&source_url), path)?;
+            asset_upload(
+                &dest.client(),
+                base_url(&dest_url),
+                folder,
+                filename.clone(),
+                data,
+            )?;
+        }
+        MirrorAction::PageCreate { source_id, .. } => {
+            let page = page_get(&source.client(), &source_url, *source_id)?;
+            page_create(
+                &dest.client(),
+                &dest_url,
+                page.content,
+                page.description,
+                page.editor,
+                page.is_published,
+                page.is_private,
+                page.locale,
+                page.path,
+                Some(page.publish_end_date),
+                Some(page.publish_start_date),
+                page.script_css,
+                page.script_js,
+                page.tags.into_iter().map(|t| t.map(|t| t.tag)).collect(),
+                page.title,
+            )?;
+        }
+        MirrorAction::PageUpdate {
+            source_id, dest_id, ..
+        } => {
+            let page = page_get(&source.client(), &source_url, *source_id)?;
+            page_update(
+                &dest.client(),
+                &dest_url,
+                *dest_id,
+                Some(page.content),
+                Some(page.description),
+                Some(page.editor),
+                Some(page.is_private),
+                Some(page.is_published),
+                None,
+                None,
+                Some(page.publish_end_date),
+                Some(page.publish_start_date),
+                page.script_css,
+                page.script_js,
+                Some(page.tags.into_iter().map(|t| t.map(|t| t.tag)).collect()),
+                Some(page.title),
+            )?;
+        }
+        MirrorAction::PageDelete { dest_id, .. } => {
+            page_delete(&dest.client(), &dest_url, *dest_id)?;
+        }
+        MirrorAction::AssetDelete { dest_id, .. } => {
+            asset_delete(&dest.client(), &dest_url, *dest_id)?;
+        }
+    }
+    Ok(())
+}
+
+// the order of the plan is kept between these groups of actions, within
+// a group they are applied concurrently
+fn action_group(action: &MirrorAction) -> u8 {
+    match action {
+        MirrorAction::AssetUpload { .. } => 0,
+        MirrorAction::PageCreate { .. } | MirrorAction::PageUpdate { .. } => 1,
+        MirrorAction::PageDelete { .. } | MirrorAction::AssetDelete { .. } => 2,
+    }
+}
+
+/// Apply a plan from [`mirror_plan`](fn.mirror_plan.html).
+///
+/// Asset uploads, page changes and deletions are applied one group after
+/// the other, the actions of a group concurrently. A failed action does not
+/// stop the remaining ones.
+///
+/// # Arguments
+/// * `cancel` - Stops before the next action, the result only lists those
+///   done.
+///
+/// # Returns
+/// The result of every action of the plan that was applied.
+pub fn mirror_apply(
+    source: &Api,
+    dest: &Api,
+    plan: &MirrorPlan,
+    cancel: Option<&CancellationToken>,
+) -> MirrorResult {
+    let mut result = MirrorResult::new();
+    if is_cancelled(cancel) {
+        return result;
+    }
+    let folders = create_asset_folders(dest, plan);
+    for group in 0..3 {
+        let actions: Vec<&MirrorAction> = plan
+            .actions
+            .iter()
+            .filter(|action| action_group(action) == group)
+            .collect();
+        let results = run_parallel(&actions, concurrency(), cancel, |action| {
+            apply_action(source, dest, &folders, action)
+        });
+        result.extend(
+            actions
+                .into_iter()
+                .zip(results)
+                .filter_map(|(action, r)| Some((action.clone(), r?))),
+        );
+    }
+    result
+}
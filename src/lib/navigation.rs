@@ -1,19 +1,26 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use thiserror::Error;
 
 use crate::common::{
-    classify_response_error, classify_response_status_error, Int,
+    classify_post_error, classify_response_error,
+    classify_response_status_error, post_graphql, random_id, Int,
     KnownErrorCodes, ResponseStatus, UnknownError,
 };
 
 #[derive(Clone, Error, Debug, PartialEq)]
 pub enum NavigationError {
+    #[error("Navigation item not found: {id}")]
+    NavigationItemNotFound { id: String },
+    #[error("Navigation item position {position} is out of range.")]
+    NavigationItemPositionOutOfRange { position: usize },
     #[error("Unknown response error code: {code}: {message}")]
     UnknownErrorCode { code: i64, message: String },
     #[error("Unknown response error: {message}")]
     UnknownErrorMessage { message: String },
+    #[error("Request failed: {message}")]
+    RequestFailed { message: String },
     #[error("Unknown response error.")]
     UnknownError,
 }
@@ -34,6 +41,9 @@ impl UnknownError for NavigationError {
     fn unknown_error_message(message: String) -> Self {
         NavigationError::UnknownErrorMessage { message }
     }
+    fn request_failed(message: String) -> Self {
+        NavigationError::RequestFailed { message }
+    }
     fn unknown_error() -> Self {
         NavigationError::UnknownError
     }
@@ -57,18 +67,32 @@ pub enum NavigationMode {
     STATIC,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+impl FromStr for NavigationMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode.to_lowercase().as_str() {
+            "none" => Ok(NavigationMode::NONE),
+            "tree" => Ok(NavigationMode::TREE),
+            "mixed" => Ok(NavigationMode::MIXED),
+            "static" => Ok(NavigationMode::STATIC),
+            _ => Err(format!("unknown navigation mode {}", mode)),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct NavigationConfig {
     pub mode: NavigationMode,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct NavigationTree {
     pub locale: String,
     pub items: Vec<Option<NavigationTreeItem>>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct NavigationTreeItem {
     pub id: String,
     pub kind: String,
@@ -104,6 +128,167 @@ pub struct NavigationItemInput {
     pub visibility_groups: Option<Vec<Option<Int>>>,
 }
 
+impl NavigationTreeItem {
+    /// The item as input for an update.
+    pub fn to_input(&self) -> NavigationItemInput {
+        NavigationItemInput {
+            id: self.id.clone(),
+            kind: self.kind.clone(),
+            label: self.label.clone(),
+            icon: self.icon.clone(),
+            target_type: self.target_type.clone(),
+            target: self.target.clone(),
+            visibility_mode: self.visibility_mode.clone(),
+            visibility_groups: self.visibility_groups.clone(),
+        }
+    }
+}
+
+impl NavigationTree {
+    /// The tree as input for an update.
+    pub fn to_input(&self) -> NavigationTreeInput {
+        NavigationTreeInput {
+            locale: self.locale.clone(),
+            items: self
+                .items
+                .iter()
+                .map(|item| item.as_ref().map(|item| item.to_input()))
+                .collect(),
+        }
+    }
+}
+
+impl NavigationItemInput {
+    fn new(kind: &str) -> Self {
+        Self {
+            id: random_id(),
+            kind: kind.to_string(),
+            label: None,
+            icon: None,
+            target_type: None,
+            target: None,
+            visibility_mode: Some("all".to_string()),
+            visibility_groups: Some(Vec::new()),
+        }
+    }
+
+    /// A link to a page or URL, visible to everyone.
+    ///
+    /// # Arguments
+    /// * `label` - The text of the link.
+    /// * `target_type` - `page`, `external`, `externalblank`, `home` or
+    ///   `search`.
+    /// * `target` - The page path or URL.
+    pub fn link(label: &str, target_type: &str, target: &str) -> Self {
+        Self {
+            label: Some(label.to_string()),
+            icon: Some("mdi-arrow-right-circle".to_string()),
+            target_type: Some(target_type.to_string()),
+            target: Some(target.to_string()),
+            ..Self::new("link")
+        }
+    }
+
+    /// A header grouping the following links.
+    pub fn header(label: &str) -> Self {
+        Self {
+            label: Some(label.to_string()),
+            ..Self::new("header")
+        }
+    }
+
+    /// A horizontal divider.
+    pub fn divider() -> Self {
+        Self::new("divider")
+    }
+
+    /// Use this icon, like `mdi-home`.
+    pub fn icon(mut self, icon: &str) -> Self {
+        self.icon = Some(icon.to_string());
+        self
+    }
+}
+
+impl NavigationTreeInput {
+    fn position(&self, id: &str) -> Result<usize, NavigationError> {
+        self.items
+            .iter()
+            .position(|item| item.as_ref().is_some_and(|item| item.id == id))
+            .ok_or(NavigationError::NavigationItemNotFound {
+                id: id.to_string(),
+            })
+    }
+
+    /// Insert an item at a position, or at the end if it is `None`.
+    pub fn insert(
+        &mut self,
+        position: Option<usize>,
+        item: NavigationItemInput,
+    ) -> Result<(), NavigationError> {
+        let position = position.unwrap_or(self.items.len());
+        if position > self.items.len() {
+            return Err(NavigationError::NavigationItemPositionOutOfRange {
+                position,
+            });
+        }
+        self.items.insert(position, Some(item));
+        Ok(())
+    }
+
+    /// Remove the item with this id.
+    pub fn remove(
+        &mut self,
+        id: &str,
+    ) -> Result<NavigationItemInput, NavigationError> {
+        let position = self.position(id)?;
+        Ok(self.items.remove(position).unwrap())
+    }
+
+    /// Move the item with this id to a new position.
+    pub fn move_item(
+        &mut self,
+        id: &str,
+        position: usize,
+    ) -> Result<(), NavigationError> {
+        if position >= self.items.len() {
+            return Err(NavigationError::NavigationItemPositionOutOfRange {
+                position,
+            });
+        }
+        let item = self.remove(id)?;
+        self.insert(Some(position), item)
+    }
+}
+
+/// Load the navigation trees, edit the one of a locale and write them
+/// back. A tree is created for the locale if there is none.
+pub fn navigation_tree_edit<F>(
+    client: &Client,
+    url: &str,
+    locale: &str,
+    edit: F,
+) -> Result<(), NavigationError>
+where
+    F: FnOnce(&mut NavigationTreeInput) -> Result<(), NavigationError>,
+{
+    let mut trees: Vec<NavigationTreeInput> = navigation_tree_get(client, url)?
+        .iter()
+        .map(NavigationTree::to_input)
+        .collect();
+    let index = match trees.iter().position(|tree| tree.locale == locale) {
+        Some(index) => index,
+        None => {
+            trees.push(NavigationTreeInput {
+                locale: locale.to_string(),
+                items: Vec::new(),
+            });
+            trees.len() - 1
+        }
+    };
+    edit(&mut trees[index])?;
+    navigation_tree_update(client, url, trees)
+}
+
 pub mod navigation_config_get {
     use super::*;
 
@@ -115,12 +300,12 @@ pub mod navigation_config_get {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub navigation: Option<Navigation>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Navigation {
         pub config: NavigationConfig,
     }
@@ -150,9 +335,7 @@ pub fn navigation_config_get(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(NavigationError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -176,12 +359,12 @@ pub mod navigation_tree_get {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub navigation: Option<Navigation>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Navigation {
         pub tree: Vec<Option<NavigationTree>>,
     }
@@ -211,9 +394,7 @@ pub fn navigation_tree_get(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(NavigationError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -241,17 +422,17 @@ pub mod navigation_config_update {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub navigation: Option<Navigation>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Navigation {
         #[serde(rename = "updateConfig")]
         pub update_config: Option<UpdateConfig>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct UpdateConfig {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -284,9 +465,7 @@ pub fn navigation_config_update(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(NavigationError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -324,17 +503,17 @@ pub mod navigation_tree_update {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub navigation: Option<Navigation>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Navigation {
         #[serde(rename = "updateTree")]
         pub update_tree: Option<UpdateTree>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct UpdateTree {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -369,9 +548,7 @@ pub fn navigation_tree_update(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(NavigationError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -1,11 +1,19 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
+use chrono::{DateTime, SecondsFormat, Utc};
+use graphql_client::Response;
 use reqwest::blocking::Client;
-use serde::{Deserialize, Serialize};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
+use similar::{Algorithm, ChangeTag, DiffOp, TextDiff};
+use std::convert::Infallible;
+use std::fmt::Display;
+use std::str::FromStr;
 use thiserror::Error;
 
 use crate::common::{
-    classify_response_error, classify_response_status_error, Boolean, Date,
-    Int, KnownErrorCodes, ResponseStatus, UnknownError,
+    classify_post_error, classify_response_error,
+    classify_response_status_error, post_graphql, run_parallel, strict_mode,
+    Boolean, CancellationToken, Date, Int, KnownErrorCodes, PostError,
+    ResponseStatus, UnknownError,
 };
 
 #[derive(Clone, Error, Debug, PartialEq)]
@@ -18,8 +26,8 @@ pub enum PageError {
     PageNotFound,
     #[error("Page content cannot be empty.")]
     PageEmptyContent,
-    #[error("Page path cannot contains illegal characters.")]
-    PageIllegalPath,
+    #[error("Page path is illegal: {details}")]
+    PageIllegalPath { details: String },
     #[error("Destination page path already exists.")]
     PagePathCollision,
     #[error("You are not authorized to move this page.")]
@@ -36,10 +44,20 @@ pub enum PageError {
     PageHistoryForbidden,
     #[error("You are not authorized to view this page.")]
     PageViewForbidden,
+    #[error("This tag does not exist.")]
+    PageTagNotFound,
+    #[error("This page was modified after it was fetched.")]
+    PageUpdateConflict,
+    #[error("Invalid page front matter: {message}")]
+    PageInvalidFrontMatter { message: String },
+    #[error("The publish end date must be after the start date.")]
+    PageInvalidPublishRange,
     #[error("Unknown response error code: {code}: {message}")]
     UnknownErrorCode { code: i64, message: String },
     #[error("Unknown response error: {message}")]
     UnknownErrorMessage { message: String },
+    #[error("Request failed: {message}")]
+    RequestFailed { message: String },
     #[error("Unknown response error.")]
     UnknownError,
 }
@@ -51,7 +69,10 @@ impl From<i64> for PageError {
             6002 => PageError::PageDuplicateCreate,
             6003 => PageError::PageNotFound,
             6004 => PageError::PageEmptyContent,
-            6005 => PageError::PageIllegalPath,
+            6005 => PageError::PageIllegalPath {
+                details: "Page path cannot contains illegal characters."
+                    .to_string(),
+            },
             6006 => PageError::PagePathCollision,
             6007 => PageError::PageMoveForbidden,
             6008 => PageError::PageCreateForbidden,
@@ -75,6 +96,9 @@ impl UnknownError for PageError {
     fn unknown_error_message(message: String) -> Self {
         PageError::UnknownErrorMessage { message }
     }
+    fn request_failed(message: String) -> Self {
+        PageError::RequestFailed { message }
+    }
     fn unknown_error() -> Self {
         PageError::UnknownError
     }
@@ -93,7 +117,161 @@ impl KnownErrorCodes for PageError {
     }
 }
 
-#[derive(Clone, Deserialize, Debug)]
+const PAGE_PATH_ILLEGAL_CHARACTERS: [char; 5] = ['.', ' ', '\\', '?', '#'];
+
+const PAGE_PATH_RESERVED_SEGMENTS: [&str; 11] = [
+    "login", "logout", "rss", "register", "verify", "favicons", "fonts",
+    "graphql", "img", "js", "svg",
+];
+
+/// Check a page path against the rules the Wiki.js server enforces.
+///
+/// A single leading slash is allowed, since the server strips it.
+///
+/// # Arguments
+/// * `path` - The page path to check.
+///
+/// # Returns
+/// A Result containing either nothing or a `PageError::PageIllegalPath`
+/// describing the first offending character or segment.
+pub fn validate_path(path: &str) -> Result<(), PageError> {
+    let illegal = |details: String| Err(PageError::PageIllegalPath { details });
+    let path = path.strip_prefix('/').unwrap_or(path);
+    if path.is_empty() {
+        return illegal("path is empty".to_string());
+    }
+    if let Some(c) = path
+        .chars()
+        .find(|c| PAGE_PATH_ILLEGAL_CHARACTERS.contains(c) || c.is_control())
+    {
+        return illegal(format!("path {:?} contains {:?}", path, c));
+    }
+    if path.split('/').any(|segment| segment.is_empty()) {
+        return illegal(format!("path {:?} contains an empty segment", path));
+    }
+    let first = path.split('/').next().unwrap();
+    if first.chars().count() <= 1 {
+        return illegal(format!(
+            "first segment {:?} must be longer than one character",
+            first
+        ));
+    }
+    if is_locale_segment(first) {
+        return illegal(format!(
+            "first segment {:?} looks like a locale code",
+            first
+        ));
+    }
+    if PAGE_PATH_RESERVED_SEGMENTS.contains(&first.to_lowercase().as_str()) {
+        return illegal(format!("first segment {:?} is reserved", first));
+    }
+    Ok(())
+}
+
+// mirrors the server's /^[A-Z]{2}(-[A-Z]{2})?$/i
+fn is_locale_segment(segment: &str) -> bool {
+    let bytes = segment.as_bytes();
+    let alpha = |b: &[u8]| b.iter().all(u8::is_ascii_alphabetic);
+    match bytes.len() {
+        2 => alpha(bytes),
+        5 => alpha(&bytes[..2]) && bytes[2] == b'-' && alpha(&bytes[3..]),
+        _ => false,
+    }
+}
+
+/// The id and outcome of every page processed by a bulk operation.
+pub type PageBulkResult = Vec<(Int, Result<(), PageError>)>;
+
+#[derive(Clone, Serialize, Debug, PartialEq, Eq, Hash)]
+#[serde(into = "String")]
+pub enum PageEditor {
+    Markdown,
+    Ckeditor,
+    Code,
+    Asciidoc,
+    Other(String),
+}
+
+impl<'de> Deserialize<'de> for PageEditor {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let editor = PageEditor::from(String::deserialize(deserializer)?);
+        if let PageEditor::Other(other) = &editor {
+            if strict_mode() {
+                return Err(D::Error::custom(format!(
+                    "unknown editor {:?}",
+                    other
+                )));
+            }
+        }
+        Ok(editor)
+    }
+}
+
+impl From<String> for PageEditor {
+    fn from(editor: String) -> Self {
+        match editor.as_str() {
+            "markdown" => PageEditor::Markdown,
+            "ckeditor" => PageEditor::Ckeditor,
+            "code" => PageEditor::Code,
+            "asciidoc" => PageEditor::Asciidoc,
+            _ => PageEditor::Other(editor),
+        }
+    }
+}
+
+impl From<&str> for PageEditor {
+    fn from(editor: &str) -> Self {
+        PageEditor::from(editor.to_string())
+    }
+}
+
+impl From<PageEditor> for String {
+    fn from(editor: PageEditor) -> Self {
+        editor.as_str().to_string()
+    }
+}
+
+impl FromStr for PageEditor {
+    type Err = Infallible;
+
+    fn from_str(editor: &str) -> Result<Self, Self::Err> {
+        Ok(PageEditor::from(editor))
+    }
+}
+
+impl Display for PageEditor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self.as_str(), f)
+    }
+}
+
+impl PageEditor {
+    /// The key of the editor as used by the API.
+    pub fn as_str(&self) -> &str {
+        match self {
+            PageEditor::Markdown => "markdown",
+            PageEditor::Ckeditor => "ckeditor",
+            PageEditor::Code => "code",
+            PageEditor::Asciidoc => "asciidoc",
+            PageEditor::Other(editor) => editor.as_str(),
+        }
+    }
+
+    /// The file extension for contents of this editor, without dot.
+    pub fn file_extension(&self) -> &str {
+        match self {
+            PageEditor::Markdown => "md",
+            PageEditor::Ckeditor | PageEditor::Code => "html",
+            PageEditor::Asciidoc => "adoc",
+            PageEditor::Other(_) => "txt",
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct Page {
     pub id: Int,
     pub path: String,
@@ -120,7 +298,7 @@ pub struct Page {
     pub created_at: Date,
     #[serde(rename = "updatedAt")]
     pub updated_at: Date,
-    pub editor: String,
+    pub editor: PageEditor,
     pub locale: String,
     #[serde(rename = "scriptCss")]
     pub script_css: Option<String>,
@@ -140,7 +318,129 @@ pub struct Page {
     pub creator_email: String,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+/// Page metadata stored as YAML front matter above the content of a page
+/// file.
+///
+/// Every field is optional, so hand written files only need to set what
+/// they want to change. The ID, dates and names are informational and
+/// ignored when writing a page back.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PageFrontMatter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Int>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub editor: Option<PageEditor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_published: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_private: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publish_start_date: Option<Date>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publish_end_date: Option<Date>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script_css: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script_js: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<Date>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<Date>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creator_name: Option<String>,
+}
+
+impl From<&Page> for PageFrontMatter {
+    fn from(page: &Page) -> Self {
+        Self {
+            id: Some(page.id),
+            locale: Some(page.locale.clone()),
+            path: Some(page.path.clone()),
+            title: Some(page.title.clone()),
+            description: Some(page.description.clone()),
+            editor: Some(page.editor.clone()),
+            tags: Some(
+                page.tags.iter().flatten().map(|t| t.tag.clone()).collect(),
+            ),
+            is_published: Some(page.is_published),
+            is_private: Some(page.is_private),
+            publish_start_date: Some(page.publish_start_date.clone()),
+            publish_end_date: Some(page.publish_end_date.clone()),
+            script_css: page.script_css.clone(),
+            script_js: page.script_js.clone(),
+            created_at: Some(page.created_at.clone()),
+            updated_at: Some(page.updated_at.clone()),
+            author_name: Some(page.author_name.clone()),
+            creator_name: Some(page.creator_name.clone()),
+        }
+    }
+}
+
+impl Page {
+    /// Render the page as YAML front matter followed by its content.
+    pub fn to_markdown_with_frontmatter(&self) -> String {
+        // the front matter only contains strings, numbers and lists of them
+        let yaml = serde_yaml::to_string(&PageFrontMatter::from(self)).unwrap();
+        format!("---\n{}---\n{}", yaml, self.content)
+    }
+}
+
+/// Split a page file into its front matter and content.
+///
+/// A file without front matter yields empty metadata and the whole file
+/// as content.
+///
+/// # Returns
+/// A Result containing either the front matter and the content or a
+/// PageError::PageInvalidFrontMatter.
+pub fn parse_frontmatter_markdown(
+    file: &str,
+) -> Result<(PageFrontMatter, String), PageError> {
+    let rest = match file.strip_prefix("---\n") {
+        Some(rest) => rest,
+        None => return Ok((PageFrontMatter::default(), file.to_string())),
+    };
+    let (yaml, content) = if let Some(content) = rest.strip_prefix("---\n") {
+        ("", content)
+    } else if let Some(end) = rest.find("\n---\n") {
+        (&rest[..end + 1], &rest[end + 5..])
+    } else {
+        return Ok((PageFrontMatter::default(), file.to_string()));
+    };
+    let front_matter = serde_yaml::from_str::<Option<PageFrontMatter>>(yaml)
+        .map_err(|e| PageError::PageInvalidFrontMatter {
+            message: e.to_string(),
+        })?
+        .unwrap_or_default();
+    Ok((front_matter, content.to_string()))
+}
+
+/// Page information without the content, for file attributes.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PageAttributes {
+    pub id: Int,
+    pub path: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: Date,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: Date,
+    pub locale: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
 #[allow(unused)]
 pub struct PageMinimal {
     pub id: Int,
@@ -150,11 +450,20 @@ pub struct PageMinimal {
     pub created_at: Date,
     #[serde(rename = "updatedAt")]
     pub updated_at: Date,
-    pub editor: String,
+    pub editor: PageEditor,
     pub locale: String,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PageRendered {
+    pub id: Int,
+    pub path: String,
+    pub locale: String,
+    pub render: Option<String>,
+    pub toc: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct PageListItem {
     pub id: Int,
     pub path: String,
@@ -176,7 +485,7 @@ pub struct PageListItem {
     pub tags: Option<Vec<Option<String>>>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct PageTreeItem {
     pub id: Int,
     pub path: String,
@@ -194,7 +503,7 @@ pub struct PageTreeItem {
     pub locale: String,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct PageTag {
     pub id: Int,
     pub tag: String,
@@ -227,13 +536,13 @@ pub enum PageOrderByDirection {
     DESC,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct PageHistoryResult {
     pub trail: Option<Vec<Option<PageHistory>>>,
     pub total: Int,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct PageHistory {
     #[serde(rename = "versionId")]
     pub version_id: Int,
@@ -251,7 +560,7 @@ pub struct PageHistory {
     pub value_after: Option<String>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct PageVersion {
     pub action: String,
     #[serde(rename = "authorId")]
@@ -266,7 +575,7 @@ pub struct PageVersion {
     #[serde(rename = "versionDate")]
     pub version_date: Date,
     pub description: String,
-    pub editor: String,
+    pub editor: PageEditor,
     #[serde(rename = "isPrivate")]
     pub is_private: Boolean,
     #[serde(rename = "isPublished")]
@@ -285,7 +594,7 @@ pub struct PageVersion {
     pub version_id: Int,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct PageSearchResponse {
     pub results: Vec<Option<PageSearchResult>>,
     pub suggestions: Vec<Option<String>>,
@@ -293,7 +602,7 @@ pub struct PageSearchResponse {
     pub total_hits: Int,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct PageSearchResult {
     pub id: String,
     pub title: String,
@@ -302,7 +611,7 @@ pub struct PageSearchResult {
     pub locale: String,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct PageLinkItem {
     pub id: Int,
     pub path: String,
@@ -310,7 +619,7 @@ pub struct PageLinkItem {
     pub links: Vec<Option<String>>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct PageConflictLatest {
     pub id: Int,
     #[serde(rename = "authorId")]
@@ -331,6 +640,239 @@ pub struct PageConflictLatest {
     pub updated_at: Date,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum PageDiffLine {
+    Context(String),
+    Added(String),
+    Removed(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageDiffHunk {
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+    pub lines: Vec<PageDiffLine>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageDiff {
+    pub old_label: String,
+    pub new_label: String,
+    pub hunks: Vec<PageDiffHunk>,
+}
+
+impl PageDiff {
+    /// Compute a line based diff between two contents with three lines of
+    /// context around each change.
+    ///
+    /// # Arguments
+    /// * `old_label` - The label of the old content, like `path@1`.
+    /// * `new_label` - The label of the new content.
+    /// * `old` - The old content.
+    /// * `new` - The new content.
+    pub fn new(
+        old_label: String,
+        new_label: String,
+        old: &str,
+        new: &str,
+    ) -> Self {
+        let diff = TextDiff::from_lines(old, new);
+        let mut hunks = Vec::new();
+        for group in diff.grouped_ops(3) {
+            let (first, last) = (&group[0], &group[group.len() - 1]);
+            let old_range = first.old_range().start..last.old_range().end;
+            let new_range = first.new_range().start..last.new_range().end;
+            let mut lines = Vec::new();
+            for op in &group {
+                for change in diff.iter_changes(op) {
+                    let line = change.value().trim_end_matches('\n');
+                    lines.push(match change.tag() {
+                        ChangeTag::Equal => {
+                            PageDiffLine::Context(line.to_string())
+                        }
+                        ChangeTag::Insert => {
+                            PageDiffLine::Added(line.to_string())
+                        }
+                        ChangeTag::Delete => {
+                            PageDiffLine::Removed(line.to_string())
+                        }
+                    });
+                }
+            }
+            hunks.push(PageDiffHunk {
+                old_start: old_range.start + 1,
+                old_len: old_range.len(),
+                new_start: new_range.start + 1,
+                new_len: new_range.len(),
+                lines,
+            });
+        }
+        Self {
+            old_label,
+            new_label,
+            hunks,
+        }
+    }
+
+    /// Whether the two contents are identical.
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+
+    /// Number of added lines.
+    pub fn insertions(&self) -> usize {
+        self.hunks
+            .iter()
+            .flat_map(|h| h.lines.iter())
+            .filter(|l| matches!(l, PageDiffLine::Added(_)))
+            .count()
+    }
+
+    /// Number of removed lines.
+    pub fn deletions(&self) -> usize {
+        self.hunks
+            .iter()
+            .flat_map(|h| h.lines.iter())
+            .filter(|l| matches!(l, PageDiffLine::Removed(_)))
+            .count()
+    }
+
+    /// Render the diff in unified diff format.
+    pub fn to_unified(&self) -> String {
+        let mut out = String::new();
+        if self.is_empty() {
+            return out;
+        }
+        out += &format!("--- {}\n+++ {}\n", self.old_label, self.new_label);
+        for hunk in &self.hunks {
+            out += &format!(
+                "@@ -{},{} +{},{} @@\n",
+                hunk.old_start, hunk.old_len, hunk.new_start, hunk.new_len
+            );
+            for line in &hunk.lines {
+                match line {
+                    PageDiffLine::Context(l) => out += &format!(" {}\n", l),
+                    PageDiffLine::Added(l) => out += &format!("+{}\n", l),
+                    PageDiffLine::Removed(l) => out += &format!("-{}\n", l),
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Two edits of the same content merged line by line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageMerge {
+    /// The merged content, with conflicting lines between git style
+    /// conflict markers.
+    pub content: String,
+    /// Number of regions both edits changed differently.
+    pub conflicts: usize,
+}
+
+// for every line of the base its index in the other content if unchanged
+fn unchanged_lines(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let mut unchanged = vec![None; base.len()];
+    for op in similar::capture_diff_slices(Algorithm::Myers, base, other) {
+        if let DiffOp::Equal {
+            old_index,
+            new_index,
+            len,
+        } = op
+        {
+            for offset in 0..len {
+                unchanged[old_index + offset] = Some(new_index + offset);
+            }
+        }
+    }
+    unchanged
+}
+
+impl PageMerge {
+    /// Three-way merge of two contents both edited from a common base, like
+    /// diff3. Regions changed on only one side or identically on both are
+    /// taken over, the others become conflicts.
+    ///
+    /// # Arguments
+    /// * `ours_label` - The label of our side in the conflict markers.
+    /// * `theirs_label` - The label of their side in the conflict markers.
+    /// * `base` - The content both edits started from.
+    /// * `ours` - Our edit of the content.
+    /// * `theirs` - Their edit of the content.
+    pub fn new(
+        ours_label: &str,
+        theirs_label: &str,
+        base: &str,
+        ours: &str,
+        theirs: &str,
+    ) -> Self {
+        let base: Vec<&str> = base.split_inclusive('\n').collect();
+        let ours: Vec<&str> = ours.split_inclusive('\n').collect();
+        let theirs: Vec<&str> = theirs.split_inclusive('\n').collect();
+        let ours_unchanged = unchanged_lines(&base, &ours);
+        let theirs_unchanged = unchanged_lines(&base, &theirs);
+        let mut content = String::new();
+        let mut conflicts = 0;
+        let push = |content: &mut String, lines: &[&str]| {
+            for line in lines {
+                content.push_str(line);
+            }
+            if !content.is_empty() && !content.ends_with('\n') {
+                content.push('\n');
+            }
+        };
+        let (mut b, mut o, mut t) = (0, 0, 0);
+        loop {
+            while b < base.len()
+                && ours_unchanged[b] == Some(o)
+                && theirs_unchanged[b] == Some(t)
+            {
+                content.push_str(base[b]);
+                (b, o, t) = (b + 1, o + 1, t + 1);
+            }
+            // the next line unchanged on both sides ends the changed region
+            let next = (b..base.len()).find_map(|k| {
+                match (ours_unchanged[k], theirs_unchanged[k]) {
+                    (Some(ok), Some(tk)) if ok >= o && tk >= t => {
+                        Some((k, ok, tk))
+                    }
+                    _ => None,
+                }
+            });
+            let (bk, ok, tk) =
+                next.unwrap_or((base.len(), ours.len(), theirs.len()));
+            if (bk, ok, tk) == (b, o, t) {
+                break;
+            }
+            let (base_part, ours_part, theirs_part) =
+                (&base[b..bk], &ours[o..ok], &theirs[t..tk]);
+            if ours_part == base_part {
+                theirs_part.iter().for_each(|l| content.push_str(l));
+            } else if theirs_part == base_part || ours_part == theirs_part {
+                ours_part.iter().for_each(|l| content.push_str(l));
+            } else {
+                conflicts += 1;
+                push(&mut content, &[]);
+                content.push_str(&format!("<<<<<<< {}\n", ours_label));
+                push(&mut content, ours_part);
+                content.push_str("=======\n");
+                push(&mut content, theirs_part);
+                content.push_str(&format!(">>>>>>> {}\n", theirs_label));
+            }
+            (b, o, t) = (bk, ok, tk);
+        }
+        Self { content, conflicts }
+    }
+
+    /// Whether conflict markers are left in the content.
+    pub fn has_markers(content: &str) -> bool {
+        content.lines().any(|line| line.starts_with("<<<<<<< "))
+    }
+}
+
 pub(crate) mod page_get {
     use super::*;
 
@@ -346,12 +888,12 @@ pub(crate) mod page_get {
 
     impl Variables {}
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct Pages {
         pub single: Option<Page>,
     }
@@ -378,10 +920,14 @@ pub fn page_get(
 ) -> Result<Page, PageError> {
     let variables = page_get::Variables { id };
     let response = post_graphql::<page_get::PageGet, _>(client, url, variables);
+    page_get_response(response)
+}
+
+pub(crate) fn page_get_response(
+    response: Result<Response<page_get::ResponseData>, PostError>,
+) -> Result<Page, PageError> {
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if response_body.data.is_some() {
@@ -420,12 +966,12 @@ pub(crate) mod page_list {
         pub author_id: Option<Int>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         pub list: Vec<PageListItem>,
     }
@@ -469,9 +1015,7 @@ pub fn page_list(
     let response =
         post_graphql::<page_list::PageList, _>(client, url, variables);
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if response_body.data.is_some() {
@@ -503,12 +1047,12 @@ pub(crate) mod page_tree {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         pub tree: Option<Vec<Option<PageTreeItem>>>,
     }
@@ -545,10 +1089,14 @@ pub fn page_tree_get(
     };
     let response =
         post_graphql::<page_tree::PageTree, _>(client, url, variables);
+    page_tree_get_response(response)
+}
+
+pub(crate) fn page_tree_get_response(
+    response: Result<Response<page_tree::ResponseData>, PostError>,
+) -> Result<Vec<PageTreeItem>, PageError> {
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if response_body.data.is_some() {
@@ -572,12 +1120,12 @@ pub(crate) mod page_tag_list {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         pub tags: Vec<Option<PageTag>>,
     }
@@ -605,9 +1153,7 @@ pub fn page_tag_list(
     let response =
         post_graphql::<page_tag_list::PageTagList, _>(client, url, variables);
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -633,17 +1179,17 @@ pub(crate) mod page_delete {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         pub delete: Option<Delete>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Delete {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -673,9 +1219,7 @@ pub fn page_delete(
     let response =
         post_graphql::<page_delete::PageDelete, _>(client, url, variables);
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -711,17 +1255,17 @@ pub(crate) mod page_render {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         pub render: Option<Render>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Render {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -751,9 +1295,7 @@ pub fn page_render(
     let response =
         post_graphql::<page_render::PageRender, _>(client, url, variables);
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -786,7 +1328,7 @@ pub(crate) mod page_create {
     pub struct Variables {
         pub content: String,
         pub description: String,
-        pub editor: String,
+        pub editor: PageEditor,
         #[serde(rename = "isPublished")]
         pub is_published: Boolean,
         #[serde(rename = "isPrivate")]
@@ -807,17 +1349,17 @@ pub(crate) mod page_create {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<PageCreatePages>,
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct PageCreatePages {
         pub create: Option<Create>,
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct Create {
         #[serde(rename = "responseResult")]
         pub response_result: ResponseStatus,
@@ -846,7 +1388,7 @@ pub fn page_create(
     url: &str,
     content: String,
     description: String,
-    editor: String,
+    editor: PageEditor,
     is_published: bool,
     is_private: bool,
     locale: String,
@@ -858,6 +1400,7 @@ pub fn page_create(
     tags: Vec<Option<String>>,
     title: String,
 ) -> Result<(), PageError> {
+    validate_path(&path)?;
     let variables = page_create::Variables {
         content,
         description,
@@ -876,9 +1419,7 @@ pub fn page_create(
     let response =
         post_graphql::<page_create::PageCreate, _>(client, url, variables);
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -915,12 +1456,12 @@ pub(crate) mod page_get_by_path {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         #[serde(rename = "singleByPath")]
         pub single_by_path: Option<Page>,
@@ -951,10 +1492,14 @@ pub fn page_get_by_path(
     let response = post_graphql::<page_get_by_path::PageGetByPath, _>(
         client, url, variables,
     );
+    page_get_by_path_response(response)
+}
+
+pub(crate) fn page_get_by_path_response(
+    response: Result<Response<page_get_by_path::ResponseData>, PostError>,
+) -> Result<Page, PageError> {
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -980,7 +1525,7 @@ pub(crate) mod page_update {
         pub id: Int,
         pub content: Option<String>,
         pub description: Option<String>,
-        pub editor: Option<String>,
+        pub editor: Option<PageEditor>,
         #[serde(rename = "isPrivate")]
         pub is_private: Option<Boolean>,
         #[serde(rename = "isPublished")]
@@ -1001,17 +1546,17 @@ pub(crate) mod page_update {
 
     impl Variables {}
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct Pages {
         pub update: Option<Update>,
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct Update {
         #[serde(rename = "responseResult")]
         pub response_result: ResponseStatus,
@@ -1041,7 +1586,7 @@ pub fn page_update(
     id: i64,
     content: Option<String>,
     description: Option<String>,
-    editor: Option<String>,
+    editor: Option<PageEditor>,
     is_private: Option<bool>,
     is_published: Option<bool>,
     locale: Option<String>,
@@ -1076,9 +1621,7 @@ pub fn page_update(
     let response =
         post_graphql::<page_update::PageUpdate, _>(client, url, variables);
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -1200,12 +1743,12 @@ pub(crate) mod page_history_get {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         pub history: Option<PageHistoryResult>,
     }
@@ -1241,9 +1784,7 @@ pub fn page_history_get(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -1275,11 +1816,11 @@ pub(crate) mod page_version_get {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         pub version: Option<PageVersion>,
     }
@@ -1313,9 +1854,7 @@ pub fn page_version_get(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -1345,12 +1884,12 @@ pub(crate) mod page_search {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         pub search: PageSearchResponse,
     }
@@ -1385,9 +1924,7 @@ pub fn page_search(
     let response =
         post_graphql::<page_search::PageSearch, _>(client, url, variables);
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -1413,12 +1950,12 @@ pub(crate) mod page_link_get {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         pub links: Option<Vec<Option<PageLinkItem>>>,
     }
@@ -1448,9 +1985,7 @@ pub fn page_link_list(
     let response =
         post_graphql::<page_link_get::PageLinkGet, _>(client, url, variables);
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -1480,12 +2015,12 @@ pub(crate) mod page_conflict_check {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         #[serde(rename = "checkConflicts")]
         pub check_conflicts: Boolean,
@@ -1517,9 +2052,7 @@ pub fn page_conflict_check(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -1545,12 +2078,12 @@ pub(crate) mod page_conflict_latest {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         #[serde(rename = "conflictLatest")]
         pub conflict_latest: PageConflictLatest,
@@ -1582,9 +2115,7 @@ pub fn page_conflict_latest(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -1606,21 +2137,21 @@ pub(crate) mod page_convert {
     #[derive(Serialize)]
     pub struct Variables {
         pub id: Int,
-        pub editor: String,
+        pub editor: PageEditor,
     }
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         pub convert: Option<Convert>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Convert {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -1645,15 +2176,13 @@ pub fn page_convert(
     client: &Client,
     url: &str,
     id: i64,
-    editor: String,
+    editor: PageEditor,
 ) -> Result<(), PageError> {
     let variables = page_convert::Variables { id, editor };
     let response =
         post_graphql::<page_convert::PageConvert, _>(client, url, variables);
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
 
@@ -1694,17 +2223,17 @@ pub(crate) mod page_move {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         #[serde(rename = "move")]
         pub move_: Option<Move>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Move {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -1732,6 +2261,7 @@ pub fn page_move(
     destination_path: String,
     destination_locale: String,
 ) -> Result<(), PageError> {
+    validate_path(&destination_path)?;
     let variables = page_move::Variables {
         id,
         destination_path,
@@ -1740,9 +2270,7 @@ pub fn page_move(
     let response =
         post_graphql::<page_move::PageMove, _>(client, url, variables);
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
 
     let response_body = response.unwrap();
@@ -1779,17 +2307,17 @@ pub(crate) mod page_tag_delete {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         #[serde(rename = "deleteTag")]
         pub delete_tag: Option<DeleteTag>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct DeleteTag {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -1820,9 +2348,7 @@ pub fn page_tag_delete(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
 
     let response_body = response.unwrap();
@@ -1861,18 +2387,18 @@ pub(crate) mod page_tag_update {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         #[serde(rename = "updateTag")]
         pub update_tag: Option<UpdateTag>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct UpdateTag {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -1905,9 +2431,7 @@ pub fn page_tag_update(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
 
     let response_body = response.unwrap();
@@ -1940,18 +2464,18 @@ pub(crate) mod page_cache_flush {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         #[serde(rename = "flushCache")]
         pub flush_cache: Option<FlushCache>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct FlushCache {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -1978,9 +2502,7 @@ pub fn page_cache_flush(client: &Client, url: &str) -> Result<(), PageError> {
         client, url, variables,
     );
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
 
     let response_body = response.unwrap();
@@ -2020,17 +2542,17 @@ pub mod page_migrate_to_locale {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         #[serde(rename = "migrateToLocale")]
         pub migrate_to_locale: Option<MigrateToLocale>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct MigrateToLocale {
         #[serde(rename = "responseResult")]
         pub response_result: ResponseStatus,
@@ -2065,9 +2587,7 @@ pub fn page_migrate_to_locale(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
 
     let response_body = response.unwrap();
@@ -2098,18 +2618,18 @@ pub mod page_tree_rebuild {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         #[serde(rename = "rebuildTree")]
         pub rebuild_tree: Option<RebuildTree>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct RebuildTree {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -2136,9 +2656,7 @@ pub fn page_tree_rebuild(client: &Client, url: &str) -> Result<(), PageError> {
         client, url, variables,
     );
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
 
     let response_body = response.unwrap();
@@ -2178,17 +2696,17 @@ pub mod page_restore {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         pub restore: Option<Restore>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Restore {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -2222,9 +2740,7 @@ pub fn page_restore(
     let response =
         post_graphql::<page_restore::PageRestore, _>(client, url, variables);
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
 
     let response_body = response.unwrap();
@@ -2262,17 +2778,17 @@ pub mod page_history_purge {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         #[serde(rename = "purgeHistory")]
         pub purge_history: Option<PurgeHistory>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct PurgeHistory {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -2303,9 +2819,7 @@ pub fn page_history_purge(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
 
     let response_body = response.unwrap();
@@ -2342,17 +2856,17 @@ pub(crate) mod page_get_updated_at {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         pub single: Option<Single>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Single {
         #[serde(rename = "updatedAt")]
         pub updated_at: Date,
@@ -2382,10 +2896,14 @@ pub fn page_get_updated_at(
     let response = post_graphql::<page_get_updated_at::PageGetUpdatedAt, _>(
         client, url, variables,
     );
+    page_get_updated_at_response(response)
+}
+
+pub(crate) fn page_get_updated_at_response(
+    response: Result<Response<page_get_updated_at::ResponseData>, PostError>,
+) -> Result<Date, PageError> {
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
 
     let response_body = response.unwrap();
@@ -2400,6 +2918,77 @@ pub fn page_get_updated_at(
     Err(classify_response_error(response_body.errors))
 }
 
+pub(crate) mod page_get_attributes {
+    use super::*;
+
+    pub struct PageGetAttributes;
+
+    pub const OPERATION_NAME: &str = "PageGetAttributes";
+    pub const QUERY : & str = "query PageGetAttributes($id: Int!) {\n  pages {\n    single (id: $id) {\n      id\n      path\n      createdAt\n      updatedAt\n      locale\n    }\n  }\n}\n" ;
+
+    #[derive(Serialize)]
+    pub struct Variables {
+        pub id: Int,
+    }
+
+    impl Variables {}
+
+    #[derive(Serialize, Deserialize)]
+    pub struct ResponseData {
+        pub pages: Option<Pages>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Pages {
+        pub(crate) single: Option<PageAttributes>,
+    }
+
+    impl graphql_client::GraphQLQuery for PageGetAttributes {
+        type Variables = Variables;
+        type ResponseData = ResponseData;
+        fn build_query(
+            variables: Self::Variables,
+        ) -> ::graphql_client::QueryBody<Self::Variables> {
+            ::graphql_client::QueryBody {
+                variables,
+                query: QUERY,
+                operation_name: OPERATION_NAME,
+            }
+        }
+    }
+}
+
+pub fn page_get_attributes(
+    client: &Client,
+    url: &str,
+    id: i64,
+) -> Result<PageAttributes, PageError> {
+    let variables = page_get_attributes::Variables { id };
+    let response = post_graphql::<page_get_attributes::PageGetAttributes, _>(
+        client, url, variables,
+    );
+    page_get_attributes_response(response)
+}
+
+pub(crate) fn page_get_attributes_response(
+    response: Result<Response<page_get_attributes::ResponseData>, PostError>,
+) -> Result<PageAttributes, PageError> {
+    if response.is_err() {
+        return Err(classify_post_error(response.err().unwrap()));
+    }
+
+    let response_body = response.unwrap();
+
+    if let Some(data) = response_body.data {
+        if let Some(pages) = data.pages {
+            if let Some(single) = pages.single {
+                return Ok(single);
+            }
+        }
+    }
+    Err(classify_response_error(response_body.errors))
+}
+
 pub(crate) mod page_get_minimal {
     use super::*;
 
@@ -2415,12 +3004,12 @@ pub(crate) mod page_get_minimal {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub pages: Option<Pages>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Pages {
         pub(crate) single: Option<PageMinimal>,
     }
@@ -2449,10 +3038,79 @@ pub fn page_get_minimal(
     let response = post_graphql::<page_get_minimal::PageGetMinimal, _>(
         client, url, variables,
     );
+    page_get_minimal_response(response)
+}
+
+pub(crate) fn page_get_minimal_response(
+    response: Result<Response<page_get_minimal::ResponseData>, PostError>,
+) -> Result<PageMinimal, PageError> {
+    if response.is_err() {
+        return Err(classify_post_error(response.err().unwrap()));
+    }
+
+    let response_body = response.unwrap();
+
+    if let Some(data) = response_body.data {
+        if let Some(pages) = data.pages {
+            if let Some(single) = pages.single {
+                return Ok(single);
+            }
+        }
+    }
+    Err(classify_response_error(response_body.errors))
+}
+
+pub(crate) mod page_get_rendered {
+    use super::*;
+
+    pub struct PageGetRendered;
+
+    pub const OPERATION_NAME: &str = "PageGetRendered";
+    pub const QUERY : & str = "query PageGetRendered($id: Int!) {\n  pages {\n    single (id: $id) {\n      id\n      path\n      locale\n      render\n      toc\n    }\n  }\n}\n" ;
+
+    #[derive(Serialize)]
+    pub struct Variables {
+        pub id: Int,
+    }
+
+    impl Variables {}
+
+    #[derive(Serialize, Deserialize)]
+    pub struct ResponseData {
+        pub pages: Option<Pages>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Pages {
+        pub single: Option<PageRendered>,
+    }
+
+    impl graphql_client::GraphQLQuery for PageGetRendered {
+        type Variables = Variables;
+        type ResponseData = ResponseData;
+        fn build_query(
+            variables: Self::Variables,
+        ) -> ::graphql_client::QueryBody<Self::Variables> {
+            ::graphql_client::QueryBody {
+                variables,
+                query: QUERY,
+                operation_name: OPERATION_NAME,
+            }
+        }
+    }
+}
+
+pub fn page_get_rendered(
+    client: &Client,
+    url: &str,
+    id: i64,
+) -> Result<PageRendered, PageError> {
+    let variables = page_get_rendered::Variables { id };
+    let response = post_graphql::<page_get_rendered::PageGetRendered, _>(
+        client, url, variables,
+    );
     if response.is_err() {
-        return Err(PageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
 
     let response_body = response.unwrap();
@@ -2466,3 +3124,200 @@ pub fn page_get_minimal(
     }
     Err(classify_response_error(response_body.errors))
 }
+
+pub fn page_version_diff(
+    client: &Client,
+    url: &str,
+    id: i64,
+    from_version: i64,
+    to_version: i64,
+) -> Result<PageDiff, PageError> {
+    let from = page_version_get(client, url, id, from_version)?;
+    let to = page_version_get(client, url, id, to_version)?;
+    Ok(PageDiff::new(
+        format!("{}@{}", from.path, from.version_id),
+        format!("{}@{}", to.path, to.version_id),
+        &from.content,
+        &to.content,
+    ))
+}
+
+pub fn page_version_diff_current(
+    client: &Client,
+    url: &str,
+    id: i64,
+    from_version: i64,
+) -> Result<PageDiff, PageError> {
+    let from = page_version_get(client, url, id, from_version)?;
+    let page = page_get(client, url, id)?;
+    Ok(PageDiff::new(
+        format!("{}@{}", from.path, from.version_id),
+        format!("{}@current", page.path),
+        &from.content,
+        &page.content,
+    ))
+}
+
+/// Fetch every page, apply `mutator` and write back the pages it changed.
+///
+/// # Arguments
+/// * `ids` - The ids of the pages to update.
+/// * `mutator` - Function modifying a page in place, returning whether it
+///   changed anything. Unchanged pages are not written back.
+/// * `concurrency` - The maximum number of pages processed at once.
+/// * `cancel` - Optional token to stop before the remaining pages.
+///
+/// # Returns
+/// The id and result of every processed page in the order of `ids`. Pages
+/// skipped due to cancellation are left out.
+pub fn page_bulk_update<F>(
+    client: &Client,
+    url: &str,
+    ids: &[Int],
+    mutator: F,
+    concurrency: usize,
+    cancel: Option<&CancellationToken>,
+) -> PageBulkResult
+where
+    F: Fn(&mut Page) -> bool + Sync,
+{
+    let results = run_parallel(ids, concurrency, cancel, |id| {
+        let mut page = page_get(client, url, *id)?;
+        if !mutator(&mut page) {
+            return Ok(());
+        }
+        let tags = page.tags.iter().map(|tag| Some(tag.clone()?.tag)).collect();
+        page_update_from(client, url, page, tags)
+    });
+    ids.iter()
+        .zip(results)
+        .filter_map(|(id, result)| result.map(|result| (*id, result)))
+        .collect()
+}
+
+// write all editable fields of a fetched page back with the given tags
+pub(crate) fn page_update_from(
+    client: &Client,
+    url: &str,
+    page: Page,
+    tags: Vec<Option<String>>,
+) -> Result<(), PageError> {
+    page_update(
+        client,
+        url,
+        page.id,
+        Some(page.content),
+        Some(page.description),
+        Some(page.editor),
+        Some(page.is_private),
+        Some(page.is_published),
+        Some(page.locale),
+        Some(page.path),
+        Some(page.publish_end_date),
+        Some(page.publish_start_date),
+        page.script_css,
+        page.script_js,
+        Some(tags),
+        Some(page.title),
+    )
+}
+
+/// Delete several pages at once.
+///
+/// # Arguments
+/// * `ids` - The ids of the pages to delete.
+/// * `concurrency` - The maximum number of pages deleted at once.
+/// * `cancel` - Optional token to stop before the remaining pages.
+///
+/// # Returns
+/// The id and result of every processed page in the order of `ids`. Pages
+/// skipped due to cancellation are left out.
+pub fn page_bulk_delete(
+    client: &Client,
+    url: &str,
+    ids: &[Int],
+    concurrency: usize,
+    cancel: Option<&CancellationToken>,
+) -> PageBulkResult {
+    let results = run_parallel(ids, concurrency, cancel, |id| {
+        page_delete(client, url, *id)
+    });
+    ids.iter()
+        .zip(results)
+        .filter_map(|(id, result)| result.map(|result| (*id, result)))
+        .collect()
+}
+
+fn publish_date(date: Option<DateTime<Utc>>) -> Date {
+    // an empty date means no restriction
+    date.map(|d| d.to_rfc3339_opts(SecondsFormat::Millis, true))
+        .unwrap_or_default()
+}
+
+/// Publish a page, optionally only within a time frame.
+///
+/// # Arguments
+/// * `id` - The id of the page to publish.
+/// * `start` - When the page becomes visible, or right away if None.
+/// * `end` - When the page is hidden again, or never if None.
+///
+/// # Returns
+/// A Result containing either nothing or a page error, like
+/// PageError::PageInvalidPublishRange if `end` is not after `start`.
+pub fn page_publish_at(
+    client: &Client,
+    url: &str,
+    id: Int,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> Result<(), PageError> {
+    if let (Some(start), Some(end)) = (start, end) {
+        if end <= start {
+            return Err(PageError::PageInvalidPublishRange);
+        }
+    }
+    page_update(
+        client,
+        url,
+        id,
+        None,
+        None,
+        None,
+        None,
+        Some(true),
+        None,
+        None,
+        Some(publish_date(end)),
+        Some(publish_date(start)),
+        None,
+        None,
+        None,
+        None,
+    )
+}
+
+/// Unpublish a page and clear its publishing time frame.
+pub fn page_unpublish(
+    client: &Client,
+    url: &str,
+    id: Int,
+) -> Result<(), PageError> {
+    page_update(
+        client,
+        url,
+        id,
+        None,
+        None,
+        None,
+        None,
+        Some(false),
+        None,
+        None,
+        Some(Date::new()),
+        Some(Date::new()),
+        None,
+        None,
+        None,
+        None,
+    )
+}
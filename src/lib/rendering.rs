@@ -1,12 +1,11 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::common::{
-    classify_response_error, classify_response_status_error, Boolean,
-    KeyValuePair, KeyValuePairInput, KnownErrorCodes, ResponseStatus,
-    UnknownError,
+    classify_post_error, classify_response_error,
+    classify_response_status_error, post_graphql, Boolean, KeyValuePair,
+    KeyValuePairInput, KnownErrorCodes, ResponseStatus, UnknownError,
 };
 
 #[derive(Clone, Debug, Error, PartialEq)]
@@ -15,6 +14,8 @@ pub enum RenderingError {
     UnknownErrorCode { code: i64, message: String },
     #[error("Unknown response error: {message}")]
     UnknownErrorMessage { message: String },
+    #[error("Request failed: {message}")]
+    RequestFailed { message: String },
     #[error("Unknown response error.")]
     UnknownError,
 }
@@ -35,6 +36,9 @@ impl UnknownError for RenderingError {
     fn unknown_error_message(message: String) -> Self {
         RenderingError::UnknownErrorMessage { message }
     }
+    fn request_failed(message: String) -> Self {
+        RenderingError::RequestFailed { message }
+    }
     fn unknown_error() -> Self {
         RenderingError::UnknownError
     }
@@ -50,7 +54,7 @@ impl KnownErrorCodes for RenderingError {
     }
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Renderer {
     #[serde(rename = "isEnabled")]
     pub is_enabled: Boolean,
@@ -73,6 +77,191 @@ pub struct RendererInput {
     pub config: Option<Vec<Option<KeyValuePairInput>>>,
 }
 
+impl Renderer {
+    /// The current config options of the renderer as `(key, value)` pairs.
+    pub fn config_values(&self) -> Vec<(String, serde_json::Value)> {
+        self.config
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|pair| (pair.key.clone(), pair.config_value()))
+            .collect()
+    }
+
+    /// The renderer as input for an update, keeping its current settings.
+    pub fn to_input(&self) -> RendererInput {
+        RendererInput {
+            is_enabled: self.is_enabled,
+            key: self.key.clone(),
+            config: Some(
+                self.config_values()
+                    .into_iter()
+                    .map(|(key, value)| {
+                        Some(KeyValuePairInput::config(&key, value))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl RendererInput {
+    /// Set a config option, replacing an existing one with the same key.
+    ///
+    /// # Arguments
+    /// * `key` - The key of the option.
+    /// * `value` - The new value.
+    pub fn set_config(&mut self, key: &str, value: serde_json::Value) {
+        let config = self.config.get_or_insert_with(Vec::new);
+        config.retain(|p| p.as_ref().is_none_or(|p| p.key != key));
+        config.push(Some(KeyValuePairInput::config(key, value)));
+    }
+}
+
+/// Typed configuration of a renderer.
+pub trait RendererConfig {
+    /// Key of the renderer, like `markdownKroki`.
+    fn key(&self) -> &'static str;
+
+    /// The config options as `(key, value)` pairs.
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)>;
+
+    /// An enabled renderer with this config.
+    fn to_renderer_input(&self) -> RendererInput {
+        RendererInput {
+            is_enabled: true,
+            key: self.key().to_string(),
+            config: Some(
+                self.options()
+                    .into_iter()
+                    .map(|(key, value)| {
+                        Some(KeyValuePairInput::config(key, value))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Config of the `markdownKroki` renderer drawing diagrams with a Kroki
+/// server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KrokiConfig {
+    pub server: String,
+    pub open_marker: String,
+    pub close_marker: String,
+}
+
+impl KrokiConfig {
+    /// The defaults of Wiki.js, using the public kroki.io server.
+    pub fn new() -> Self {
+        Self {
+            server: "https://kroki.io".to_string(),
+            open_marker: "```kroki".to_string(),
+            close_marker: "```".to_string(),
+        }
+    }
+
+    pub fn server(mut self, server: impl Into<String>) -> Self {
+        self.server = server.into();
+        self
+    }
+
+    pub fn markers(
+        mut self,
+        open_marker: impl Into<String>,
+        close_marker: impl Into<String>,
+    ) -> Self {
+        self.open_marker = open_marker.into();
+        self.close_marker = close_marker.into();
+        self
+    }
+}
+
+impl Default for KrokiConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RendererConfig for KrokiConfig {
+    fn key(&self) -> &'static str {
+        "markdownKroki"
+    }
+
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)> {
+        vec![
+            ("server", self.server.clone().into()),
+            ("openMarker", self.open_marker.clone().into()),
+            ("closeMarker", self.close_marker.clone().into()),
+        ]
+    }
+}
+
+/// Config of the `markdownPlantuml` renderer drawing diagrams with a
+/// PlantUML server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlantumlConfig {
+    pub server: String,
+    pub open_marker: String,
+    pub close_marker: String,
+    /// `svg` or `png`.
+    pub image_format: String,
+}
+
+impl PlantumlConfig {
+    /// The defaults of Wiki.js, using the public Requarks server.
+    pub fn new() -> Self {
+        Self {
+            server: "https://plantuml.requarks.io".to_string(),
+            open_marker: "```plantuml".to_string(),
+            close_marker: "```".to_string(),
+            image_format: "svg".to_string(),
+        }
+    }
+
+    pub fn server(mut self, server: impl Into<String>) -> Self {
+        self.server = server.into();
+        self
+    }
+
+    pub fn markers(
+        mut self,
+        open_marker: impl Into<String>,
+        close_marker: impl Into<String>,
+    ) -> Self {
+        self.open_marker = open_marker.into();
+        self.close_marker = close_marker.into();
+        self
+    }
+
+    pub fn image_format(mut self, image_format: impl Into<String>) -> Self {
+        self.image_format = image_format.into();
+        self
+    }
+}
+
+impl Default for PlantumlConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RendererConfig for PlantumlConfig {
+    fn key(&self) -> &'static str {
+        "markdownPlantuml"
+    }
+
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)> {
+        vec![
+            ("server", self.server.clone().into()),
+            ("openMarker", self.open_marker.clone().into()),
+            ("closeMarker", self.close_marker.clone().into()),
+            ("imageFormat", self.image_format.clone().into()),
+        ]
+    }
+}
+
 pub mod renderer_list {
     use super::*;
 
@@ -90,12 +279,12 @@ pub mod renderer_list {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub rendering: Option<Rendering>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Rendering {
         pub renderers: Option<Vec<Option<Renderer>>>,
     }
@@ -125,9 +314,7 @@ pub fn renderer_list(
     let response =
         post_graphql::<renderer_list::RendererList, _>(client, url, variables);
     if response.is_err() {
-        return Err(RenderingError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -156,16 +343,16 @@ pub mod renderer_update {
     }
 
     impl Variables {}
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub rendering: Option<Rendering>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Rendering {
         #[serde(rename = "updateRenderers")]
         pub update_renderers: Option<UpdateRenderers>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct UpdateRenderers {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -198,9 +385,7 @@ pub fn renderer_update(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(RenderingError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
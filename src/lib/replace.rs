@@ -0,0 +1,258 @@
+use regex::Regex;
+use reqwest::blocking::Client;
+
+use crate::batch::page_get_many;
+use crate::common::{is_cancelled, CancellationToken, Int};
+use crate::page::{
+    page_conflict_check, page_get_minimal, page_link_list, page_list,
+    page_move, page_update, Page, PageDiff, PageError, PageListItem,
+};
+
+/// What to search for in page contents.
+#[derive(Clone, Debug)]
+pub enum ReplacePattern {
+    /// Literal text.
+    Text(String),
+    /// Regular expression, the replacement may refer to capture groups
+    /// like `$1`.
+    Regex(Regex),
+}
+
+impl ReplacePattern {
+    fn count(&self, content: &str) -> usize {
+        match self {
+            ReplacePattern::Text(text) if text.is_empty() => 0,
+            ReplacePattern::Text(text) => {
+                content.matches(text.as_str()).count()
+            }
+            ReplacePattern::Regex(regex) => regex.find_iter(content).count(),
+        }
+    }
+
+    fn replace(&self, content: &str, replacement: &str) -> String {
+        match self {
+            ReplacePattern::Text(text) => content.replace(text, replacement),
+            ReplacePattern::Regex(regex) => {
+                regex.replace_all(content, replacement).into_owned()
+            }
+        }
+    }
+}
+
+/// Restricts which pages a search and replace touches.
+#[derive(Clone, Debug, Default)]
+pub struct PageFilter {
+    /// Only pages of this locale.
+    pub locale: Option<String>,
+    /// Only pages whose path starts with this prefix.
+    pub path_prefix: Option<String>,
+    /// Only pages having all of these tags.
+    pub tags: Option<Vec<String>>,
+}
+
+impl PageFilter {
+    // list all matching pages
+    pub(crate) fn pages(
+        &self,
+        client: &Client,
+        url: &str,
+    ) -> Result<Vec<PageListItem>, PageError> {
+        Ok(page_list(
+            client,
+            url,
+            None,
+            None,
+            None,
+            self.tags.clone(),
+            self.locale.clone(),
+            None,
+            None,
+        )?
+        .into_iter()
+        .filter(|page| {
+            self.path_prefix
+                .as_ref()
+                .is_none_or(|prefix| page.path.starts_with(prefix.as_str()))
+        })
+        .collect())
+    }
+
+    // list the ids of all matching pages
+    pub(crate) fn page_ids(
+        &self,
+        client: &Client,
+        url: &str,
+    ) -> Result<Vec<Int>, PageError> {
+        Ok(self
+            .pages(client, url)?
+            .into_iter()
+            .map(|page| page.id)
+            .collect())
+    }
+}
+
+/// The outcome of a search and replace on one page.
+#[derive(Clone, Debug)]
+pub struct PageReplacement {
+    pub id: Int,
+    pub path: String,
+    pub locale: String,
+    /// Number of matches replaced.
+    pub count: usize,
+    /// The change of the page content.
+    pub diff: PageDiff,
+    /// Result of writing the page back, None for a dry run.
+    pub result: Option<Result<(), PageError>>,
+}
+
+/// Search and replace in the content of all matching pages.
+///
+/// In apply mode every page is checked for changes since it was fetched
+/// right before the update, and left untouched with
+/// `PageError::PageUpdateConflict` if somebody else edited it meanwhile.
+/// This check is best-effort, an edit made between the check and the update
+/// is still overwritten since Wiki.js cannot update a page conditionally.
+///
+/// # Arguments
+/// * `cancel` - Optional token to stop before the remaining pages, which
+///   are left out of the result.
+///
+/// # Returns
+/// A Result containing either a replacement for every page with at least one
+/// match or a page error if listing or fetching the pages failed.
+pub fn page_search_replace(
+    client: &Client,
+    url: &str,
+    pattern: &ReplacePattern,
+    replacement: &str,
+    filter: &PageFilter,
+    dry_run: bool,
+    cancel: Option<&CancellationToken>,
+) -> Result<Vec<PageReplacement>, PageError> {
+    let ids = filter.page_ids(client, url)?;
+    Ok(page_get_many(client, url, &ids, cancel)?
+        .iter()
+        .take_while(|_| !is_cancelled(cancel))
+        .filter_map(|page| {
+            replace_in_page(client, url, page, pattern, replacement, dry_run)
+        })
+        .collect())
+}
+
+/// Move a page and rewrite the markdown links pointing to it.
+///
+/// Links to the old path on the other pages of the locale, with or without
+/// locale prefix, anchor or title, are pointed to the new path. The
+/// referring pages are found via the page link list and updated with the
+/// same conflict check as [`page_search_replace`](fn.page_search_replace.html).
+///
+/// # Arguments
+/// * `cancel` - Optional token to stop rewriting the links of the remaining
+///   pages after the move.
+///
+/// # Returns
+/// A Result containing either a replacement for every page whose links were
+/// rewritten or a page error if the move itself failed.
+pub fn page_move_with_link_fixup(
+    client: &Client,
+    url: &str,
+    id: Int,
+    new_path: String,
+    cancel: Option<&CancellationToken>,
+) -> Result<Vec<PageReplacement>, PageError> {
+    let page = page_get_minimal(client, url, id)?;
+    let links = page_link_list(client, url, page.locale.clone())?;
+    page_move(client, url, id, new_path.clone(), page.locale.clone())?;
+
+    let target = format!("{}/{}", page.locale, page.path);
+    let referring: Vec<Int> = links
+        .into_iter()
+        .filter(|item| item.links.iter().flatten().any(|link| *link == target))
+        .map(|item| item.id)
+        .collect();
+    // locale and path are escaped, so the pattern is always valid
+    let pattern = ReplacePattern::Regex(
+        Regex::new(&format!(
+            r#"\]\((/(?:{}/)?){}((?:[#?][^)\s]*)?(?:\s+"[^"]*")?)\)"#,
+            regex::escape(&page.locale),
+            regex::escape(page.path.trim_start_matches('/')),
+        ))
+        .unwrap(),
+    );
+    let replacement =
+        format!("](${{1}}{}${{2}})", new_path.trim_start_matches('/'));
+    Ok(page_get_many(client, url, &referring, cancel)?
+        .iter()
+        .take_while(|_| !is_cancelled(cancel))
+        .filter_map(|page| {
+            replace_in_page(client, url, page, &pattern, &replacement, false)
+        })
+        .collect())
+}
+
+fn replace_in_page(
+    client: &Client,
+    url: &str,
+    page: &Page,
+    pattern: &ReplacePattern,
+    replacement: &str,
+    dry_run: bool,
+) -> Option<PageReplacement> {
+    let count = pattern.count(&page.content);
+    if count == 0 {
+        return None;
+    }
+    let content = pattern.replace(&page.content, replacement);
+    if content == page.content {
+        return None;
+    }
+    let diff = PageDiff::new(
+        page.path.clone(),
+        page.path.clone(),
+        &page.content,
+        &content,
+    );
+    let result = if dry_run {
+        None
+    } else {
+        Some(update_if_unchanged(client, url, page, content))
+    };
+    Some(PageReplacement {
+        id: page.id,
+        path: page.path.clone(),
+        locale: page.locale.clone(),
+        count,
+        diff,
+        result,
+    })
+}
+
+// update the content unless the page changed after it was fetched
+fn update_if_unchanged(
+    client: &Client,
+    url: &str,
+    page: &Page,
+    content: String,
+) -> Result<(), PageError> {
+    if page_conflict_check(client, url, page.id, page.updated_at.clone())? {
+        return Err(PageError::PageUpdateConflict);
+    }
+    page_update(
+        client,
+        url,
+        page.id,
+        Some(content),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+}
@@ -0,0 +1,186 @@
+use reqwest::blocking::Client;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+
+use crate::comment::{comment_list, CommentError};
+use crate::common::{concurrency, run_parallel, CancellationToken, Date, Int};
+use crate::page::{page_history_get, page_list, PageError, PageListItem};
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum ReportError {
+    #[error(transparent)]
+    Page(#[from] PageError),
+    #[error(transparent)]
+    Comment(#[from] CommentError),
+    #[error("The report was cancelled.")]
+    Cancelled,
+}
+
+/// Settings of [`wiki_report`](fn.wiki_report.html).
+#[derive(Clone, Debug)]
+pub struct ReportOptions {
+    /// Only count updates, edits and comments at or after this date, like
+    /// `2024-01-01T00:00:00Z`.
+    pub since: Option<Date>,
+    /// Maximum number of entries of the ranked lists.
+    pub limit: usize,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            since: None,
+            limit: 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ReportPage {
+    pub id: Int,
+    pub path: String,
+    pub locale: String,
+    pub title: Option<String>,
+    pub updated_at: Date,
+}
+
+impl From<&PageListItem> for ReportPage {
+    fn from(page: &PageListItem) -> Self {
+        Self {
+            id: page.id,
+            path: page.path.clone(),
+            locale: page.locale.clone(),
+            title: page.title.clone(),
+            updated_at: page.updated_at.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ReportAuthor {
+    pub id: Int,
+    pub name: String,
+    /// Number of page history entries by this author.
+    pub edits: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ReportCommentCount {
+    pub page: ReportPage,
+    pub comments: usize,
+}
+
+/// Summary of the content and activity of a wiki.
+#[derive(Clone, Debug, Serialize)]
+pub struct WikiReport {
+    pub since: Option<Date>,
+    pub total_pages: usize,
+    pub pages_per_locale: BTreeMap<String, usize>,
+    pub pages_per_tag: BTreeMap<String, usize>,
+    /// Most recently updated pages, newest first.
+    pub recently_updated: Vec<ReportPage>,
+    /// Authors with the most history entries, most active first.
+    pub most_active_authors: Vec<ReportAuthor>,
+    pub total_comments: usize,
+    /// Pages with the most comments, most commented first.
+    pub most_commented: Vec<ReportCommentCount>,
+}
+
+fn is_since(date: &str, since: &Option<Date>) -> bool {
+    // ISO 8601 dates in the same format order lexicographically
+    since.as_ref().is_none_or(|since| date >= since.as_str())
+}
+
+/// Build a report from the page list, the page histories and comments.
+///
+/// # Arguments
+/// * `cancel` - Optional token to stop fetching the histories and comments
+///   of the remaining pages.
+///
+/// # Returns
+/// A Result containing either the report or the first error of the
+/// underlying API calls.
+pub fn wiki_report(
+    client: &Client,
+    url: &str,
+    options: &ReportOptions,
+    cancel: Option<&CancellationToken>,
+) -> Result<WikiReport, ReportError> {
+    let pages =
+        page_list(client, url, None, None, None, None, None, None, None)?;
+
+    let mut pages_per_locale = BTreeMap::new();
+    let mut pages_per_tag = BTreeMap::new();
+    for page in &pages {
+        *pages_per_locale.entry(page.locale.clone()).or_insert(0) += 1;
+        for tag in page.tags.iter().flatten().flatten() {
+            *pages_per_tag.entry(tag.clone()).or_insert(0) += 1;
+        }
+    }
+
+    let mut recently_updated: Vec<ReportPage> = pages
+        .iter()
+        .filter(|page| is_since(&page.updated_at, &options.since))
+        .map(ReportPage::from)
+        .collect();
+    recently_updated.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    recently_updated.truncate(options.limit);
+
+    let activity = run_parallel(&pages, concurrency(), cancel, |page| {
+        let history = page_history_get(client, url, page.id, None, None)?;
+        let comments =
+            comment_list(client, url, page.locale.clone(), page.path.clone())?;
+        Ok::<_, ReportError>((history, comments))
+    });
+
+    let mut authors: HashMap<Int, ReportAuthor> = HashMap::new();
+    let mut most_commented = Vec::new();
+    let mut total_comments = 0;
+    for (page, result) in pages.iter().zip(activity) {
+        let (history, comments) = result.ok_or(ReportError::Cancelled)??;
+        for entry in history.trail.into_iter().flatten().flatten() {
+            if !is_since(&entry.version_date, &options.since) {
+                continue;
+            }
+            authors
+                .entry(entry.author_id)
+                .or_insert_with(|| ReportAuthor {
+                    id: entry.author_id,
+                    name: entry.author_name.clone(),
+                    edits: 0,
+                })
+                .edits += 1;
+        }
+        let count = comments
+            .iter()
+            .filter(|comment| is_since(&comment.created_at, &options.since))
+            .count();
+        total_comments += count;
+        if count > 0 {
+            most_commented.push(ReportCommentCount {
+                page: page.into(),
+                comments: count,
+            });
+        }
+    }
+
+    let mut most_active_authors: Vec<ReportAuthor> =
+        authors.into_values().collect();
+    most_active_authors
+        .sort_by(|a, b| b.edits.cmp(&a.edits).then(a.name.cmp(&b.name)));
+    most_active_authors.truncate(options.limit);
+    most_commented.sort_by_key(|count| std::cmp::Reverse(count.comments));
+    most_commented.truncate(options.limit);
+
+    Ok(WikiReport {
+        since: options.since.clone(),
+        total_pages: pages.len(),
+        pages_per_locale,
+        pages_per_tag,
+        recently_updated,
+        most_active_authors,
+        total_comments,
+        most_commented,
+    })
+}
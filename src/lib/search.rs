@@ -1,12 +1,11 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::common::{
-    classify_response_error, classify_response_status_error, Boolean,
-    KeyValuePair, KeyValuePairInput, KnownErrorCodes, ResponseStatus,
-    UnknownError,
+    classify_post_error, classify_response_error,
+    classify_response_status_error, post_graphql, Boolean, KeyValuePair,
+    KeyValuePairInput, KnownErrorCodes, ResponseStatus, UnknownError,
 };
 
 #[derive(Clone, Debug, Error, PartialEq)]
@@ -19,6 +18,8 @@ pub enum SearchError {
     UnknownErrorCode { code: i64, message: String },
     #[error("Unknown response error: {message}")]
     UnknownErrorMessage { message: String },
+    #[error("Request failed: {message}")]
+    RequestFailed { message: String },
     #[error("Unknown response error.")]
     UnknownError,
 }
@@ -43,6 +44,9 @@ impl UnknownError for SearchError {
     fn unknown_error_message(message: String) -> Self {
         SearchError::UnknownErrorMessage { message }
     }
+    fn request_failed(message: String) -> Self {
+        SearchError::RequestFailed { message }
+    }
     fn unknown_error() -> Self {
         SearchError::UnknownError
     }
@@ -58,7 +62,7 @@ impl KnownErrorCodes for SearchError {
     }
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SearchEngine {
     #[serde(rename = "isEnabled")]
     pub is_enabled: Boolean,
@@ -80,6 +84,226 @@ pub struct SearchEngineInput {
     pub config: Option<Vec<Option<KeyValuePairInput>>>,
 }
 
+impl SearchEngine {
+    /// The current config options of the engine as `(key, value)` pairs.
+    pub fn config_values(&self) -> Vec<(String, serde_json::Value)> {
+        self.config
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|pair| (pair.key.clone(), pair.config_value()))
+            .collect()
+    }
+
+    /// The engine as input for an update, keeping its current settings.
+    pub fn to_input(&self) -> SearchEngineInput {
+        SearchEngineInput {
+            is_enabled: self.is_enabled,
+            key: self.key.clone(),
+            config: Some(
+                self.config_values()
+                    .into_iter()
+                    .map(|(key, value)| {
+                        Some(KeyValuePairInput::config(&key, value))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl SearchEngineInput {
+    /// Set a config option, replacing an existing one with the same key.
+    ///
+    /// # Arguments
+    /// * `key` - The key of the option.
+    /// * `value` - The new value.
+    pub fn set_config(&mut self, key: &str, value: serde_json::Value) {
+        let config = self.config.get_or_insert_with(Vec::new);
+        config.retain(|p| p.as_ref().is_none_or(|p| p.key != key));
+        config.push(Some(KeyValuePairInput::config(key, value)));
+    }
+}
+
+/// Typed configuration of a search engine.
+pub trait SearchEngineConfig {
+    /// Key of the search engine, like `elasticsearch`.
+    fn key(&self) -> &'static str;
+
+    /// The config options as `(key, value)` pairs.
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)>;
+
+    /// The config as key value pairs for a `SearchEngineInput`.
+    fn to_key_value_pairs(&self) -> Vec<KeyValuePairInput> {
+        self.options()
+            .into_iter()
+            .map(|(key, value)| KeyValuePairInput::config(key, value))
+            .collect()
+    }
+
+    /// An enabled search engine with this config.
+    fn to_engine_input(&self) -> SearchEngineInput {
+        SearchEngineInput {
+            is_enabled: true,
+            key: self.key().to_string(),
+            config: Some(
+                self.to_key_value_pairs().into_iter().map(Some).collect(),
+            ),
+        }
+    }
+}
+
+/// Config of the `elasticsearch` search engine.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElasticsearchConfig {
+    /// `7.x` or `6.x`.
+    pub api_version: String,
+    /// Comma separated list of host URLs.
+    pub hosts: String,
+    pub verify_tls_certificate: bool,
+    pub tls_cert_path: String,
+    pub index_name: String,
+    pub analyzer: String,
+    pub sniff_on_start: bool,
+    /// Seconds between sniffs, 0 to disable.
+    pub sniff_interval: i64,
+}
+
+impl ElasticsearchConfig {
+    pub fn new(hosts: impl Into<String>) -> Self {
+        Self {
+            api_version: "7.x".to_string(),
+            hosts: hosts.into(),
+            verify_tls_certificate: true,
+            tls_cert_path: String::new(),
+            index_name: "wiki".to_string(),
+            analyzer: "simple".to_string(),
+            sniff_on_start: false,
+            sniff_interval: 0,
+        }
+    }
+
+    pub fn api_version(mut self, api_version: impl Into<String>) -> Self {
+        self.api_version = api_version.into();
+        self
+    }
+
+    pub fn verify_tls_certificate(mut self, verify: bool) -> Self {
+        self.verify_tls_certificate = verify;
+        self
+    }
+
+    pub fn tls_cert_path(mut self, path: impl Into<String>) -> Self {
+        self.tls_cert_path = path.into();
+        self
+    }
+
+    pub fn index_name(mut self, index_name: impl Into<String>) -> Self {
+        self.index_name = index_name.into();
+        self
+    }
+
+    pub fn analyzer(mut self, analyzer: impl Into<String>) -> Self {
+        self.analyzer = analyzer.into();
+        self
+    }
+
+    pub fn sniff(mut self, on_start: bool, interval: i64) -> Self {
+        self.sniff_on_start = on_start;
+        self.sniff_interval = interval;
+        self
+    }
+}
+
+impl SearchEngineConfig for ElasticsearchConfig {
+    fn key(&self) -> &'static str {
+        "elasticsearch"
+    }
+
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)> {
+        vec![
+            ("apiVersion", self.api_version.clone().into()),
+            ("hosts", self.hosts.clone().into()),
+            ("verifyTLSCertificate", self.verify_tls_certificate.into()),
+            ("tlsCertPath", self.tls_cert_path.clone().into()),
+            ("indexName", self.index_name.clone().into()),
+            ("analyzer", self.analyzer.clone().into()),
+            ("sniffOnStart", self.sniff_on_start.into()),
+            ("sniffInterval", self.sniff_interval.into()),
+        ]
+    }
+}
+
+/// Config of the `algolia` search engine.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlgoliaConfig {
+    pub app_id: String,
+    /// An admin API key, as the index is written to.
+    pub api_key: String,
+    pub index_name: String,
+}
+
+impl AlgoliaConfig {
+    pub fn new(app_id: impl Into<String>, api_key: impl Into<String>) -> Self {
+        Self {
+            app_id: app_id.into(),
+            api_key: api_key.into(),
+            index_name: "wiki".to_string(),
+        }
+    }
+
+    pub fn index_name(mut self, index_name: impl Into<String>) -> Self {
+        self.index_name = index_name.into();
+        self
+    }
+}
+
+impl SearchEngineConfig for AlgoliaConfig {
+    fn key(&self) -> &'static str {
+        "algolia"
+    }
+
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)> {
+        vec![
+            ("appId", self.app_id.clone().into()),
+            ("apiKey", self.api_key.clone().into()),
+            ("indexName", self.index_name.clone().into()),
+        ]
+    }
+}
+
+/// Config of the `postgres` search engine, only usable with a PostgreSQL
+/// database.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PostgresSearchConfig {
+    /// The text search dictionary, like `english` or `german`.
+    pub dict_language: String,
+}
+
+impl PostgresSearchConfig {
+    pub fn new(dict_language: impl Into<String>) -> Self {
+        Self {
+            dict_language: dict_language.into(),
+        }
+    }
+}
+
+impl Default for PostgresSearchConfig {
+    fn default() -> Self {
+        Self::new("english")
+    }
+}
+
+impl SearchEngineConfig for PostgresSearchConfig {
+    fn key(&self) -> &'static str {
+        "postgres"
+    }
+
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)> {
+        vec![("dictLanguage", self.dict_language.clone().into())]
+    }
+}
+
 pub mod search_engine_list {
     use super::*;
 
@@ -97,12 +321,12 @@ pub mod search_engine_list {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub search: Option<SearchEngineListSearch>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct SearchEngineListSearch {
         #[serde(rename = "searchEngines")]
         pub search_engines: Option<Vec<Option<SearchEngine>>>,
@@ -134,9 +358,7 @@ pub fn search_engine_list(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(SearchError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -160,18 +382,18 @@ pub mod search_engine_index_rebuild {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub search: Option<Search>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Search {
         #[serde(rename = "rebuildIndex")]
         pub rebuild_index: Option<RebuildIndex>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct RebuildIndex {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -202,9 +424,7 @@ pub fn search_engine_index_rebuild(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(SearchError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -240,18 +460,18 @@ pub mod search_engine_update {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub search: Option<Search>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Search {
         #[serde(rename = "updateSearchEngines")]
         pub update_search_engines: Option<UpdateSearchEngines>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct UpdateSearchEngines {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -284,9 +504,7 @@ pub fn search_engine_update(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(SearchError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
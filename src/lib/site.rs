@@ -1,19 +1,26 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use thiserror::Error;
 
 use crate::common::{
-    classify_response_error, classify_response_status_error, Boolean, Int,
+    classify_post_error, classify_response_error,
+    classify_response_status_error, post_graphql, Boolean, Int,
     KnownErrorCodes, ResponseStatus, UnknownError,
 };
 
 #[derive(Clone, Debug, Error, PartialEq)]
 pub enum SiteError {
+    #[error("Unknown site config field: {field}")]
+    UnknownField { field: String },
+    #[error("Invalid value for site config field {field}: {value}")]
+    InvalidValue { field: String, value: String },
     #[error("Unknown response error code: {code}: {message}")]
     UnknownErrorCode { code: i64, message: String },
     #[error("Unknown response error: {message}")]
     UnknownErrorMessage { message: String },
+    #[error("Request failed: {message}")]
+    RequestFailed { message: String },
     #[error("Unknown response error.")]
     UnknownError,
 }
@@ -34,6 +41,9 @@ impl UnknownError for SiteError {
     fn unknown_error_message(message: String) -> Self {
         SiteError::UnknownErrorMessage { message }
     }
+    fn request_failed(message: String) -> Self {
+        SiteError::RequestFailed { message }
+    }
     fn unknown_error() -> Self {
         SiteError::UnknownError
     }
@@ -130,6 +140,130 @@ pub struct SiteConfig {
     pub upload_force_download: Option<Boolean>,
 }
 
+/// Changes to the site config, applied on top of the current config so only
+/// the changed fields need to be given.
+///
+/// Fields can be set with the typed methods or by name with
+/// [`set`](#method.set), which accepts both the API name like `featurePageComments`
+/// and the field name like `feature_page_comments`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SiteConfigUpdateBuilder {
+    changes: Map<String, Value>,
+}
+
+// the API names and GraphQL types of all fields, from the update mutation
+fn site_config_fields() -> impl Iterator<Item = (&'static str, &'static str)> {
+    site_config_update::QUERY
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix('$')?.split_once(": "))
+}
+
+fn normalize_field(field: &str) -> String {
+    field.replace('_', "").to_lowercase()
+}
+
+impl SiteConfigUpdateBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn change(mut self, field: &str, value: Value) -> Self {
+        self.changes.insert(field.to_string(), value);
+        self
+    }
+
+    pub fn title(self, title: impl Into<String>) -> Self {
+        self.change("title", Value::String(title.into()))
+    }
+
+    pub fn description(self, description: impl Into<String>) -> Self {
+        self.change("description", Value::String(description.into()))
+    }
+
+    pub fn host(self, host: impl Into<String>) -> Self {
+        self.change("host", Value::String(host.into()))
+    }
+
+    pub fn company(self, company: impl Into<String>) -> Self {
+        self.change("company", Value::String(company.into()))
+    }
+
+    pub fn logo_url(self, logo_url: impl Into<String>) -> Self {
+        self.change("logoUrl", Value::String(logo_url.into()))
+    }
+
+    pub fn feature_page_comments(self, enabled: bool) -> Self {
+        self.change("featurePageComments", Value::Bool(enabled))
+    }
+
+    pub fn feature_page_ratings(self, enabled: bool) -> Self {
+        self.change("featurePageRatings", Value::Bool(enabled))
+    }
+
+    pub fn feature_personal_wikis(self, enabled: bool) -> Self {
+        self.change("featurePersonalWikis", Value::Bool(enabled))
+    }
+
+    /// Set a field by name, parsing the value according to its type.
+    /// Lists like `robots` are given comma separated.
+    ///
+    /// # Arguments
+    /// * `field` - The API or field name.
+    /// * `value` - The new value.
+    pub fn set(self, field: &str, value: &str) -> Result<Self, SiteError> {
+        let (name, kind) = site_config_fields()
+            .find(|(name, _)| normalize_field(name) == normalize_field(field))
+            .ok_or(SiteError::UnknownField {
+                field: field.to_string(),
+            })?;
+        let invalid = || SiteError::InvalidValue {
+            field: name.to_string(),
+            value: value.to_string(),
+        };
+        let value = match kind {
+            "Boolean" => Value::Bool(value.parse().map_err(|_| invalid())?),
+            "Int" => Value::from(value.parse::<Int>().map_err(|_| invalid())?),
+            "[String]" => Value::from(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .collect::<Vec<&str>>(),
+            ),
+            _ => Value::String(value.to_string()),
+        };
+        Ok(self.change(name, value))
+    }
+
+    /// Whether no field was changed.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Apply the changes to a config.
+    pub fn apply(&self, config: SiteConfig) -> Result<SiteConfig, SiteError> {
+        let error = |e: serde_json::Error| SiteError::UnknownErrorMessage {
+            message: e.to_string(),
+        };
+        let mut value = serde_json::to_value(config).map_err(error)?;
+        if let Value::Object(fields) = &mut value {
+            fields.extend(self.changes.clone());
+        }
+        serde_json::from_value(value).map_err(error)
+    }
+}
+
+/// Fetch the site config, apply the changes and push it.
+pub fn site_config_update_partial(
+    client: &Client,
+    url: &str,
+    changes: &SiteConfigUpdateBuilder,
+) -> Result<SiteConfig, SiteError> {
+    let config = changes.apply(site_config_get(client, url)?)?;
+    site_config_update(client, url, config.clone())?;
+    Ok(config)
+}
+
 pub mod site_config_get {
     use super::*;
 
@@ -141,12 +275,12 @@ pub mod site_config_get {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub site: Option<Site>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Site {
         pub config: Option<SiteConfig>,
     }
@@ -175,9 +309,7 @@ pub fn site_config_get(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(SiteError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -198,16 +330,16 @@ pub mod site_config_update {
     pub const OPERATION_NAME: &str = "SiteConfigUpdate";
     pub const QUERY : & str = "mutation SiteConfigUpdate(\n  $host: String\n  $title: String\n  $description: String\n  $robots: [String]\n  $analyticsService: String\n  $analyticsId: String\n  $company: String\n  $contentLicense: String\n  $footerOverride: String\n  $logoUrl: String\n  $pageExtensions: String\n  $authAutoLogin: Boolean\n  $authEnforce2FA: Boolean\n  $authHideLocal: Boolean\n  $authLoginBgUrl: String\n  $authJwtAudience: String\n  $authJwtExpiration: String\n  $authJwtRenewablePeriod: String\n  $editFab: Boolean\n  $editMenuBar: Boolean\n  $editMenuBtn: Boolean\n  $editMenuExternalBtn: Boolean\n  $editMenuExternalName: String\n  $editMenuExternalIcon: String\n  $editMenuExternalUrl: String\n  $featurePageRatings: Boolean\n  $featurePageComments: Boolean\n  $featurePersonalWikis: Boolean\n  $securityOpenRedirect: Boolean\n  $securityIframe: Boolean\n  $securityReferrerPolicy: Boolean\n  $securityTrustProxy: Boolean\n  $securitySRI: Boolean\n  $securityHSTS: Boolean\n  $securityHSTSDuration: Int\n  $securityCSP: Boolean\n  $securityCSPDirectives: String\n  $uploadMaxFileSize: Int\n  $uploadMaxFiles: Int\n  $uploadScanSVG: Boolean\n  $uploadForceDownload: Boolean\n) {\n  site {\n    updateConfig(\n      host: $host\n      title: $title\n      description: $description\n      robots: $robots\n      analyticsService: $analyticsService\n      analyticsId: $analyticsId\n      company: $company\n      contentLicense: $contentLicense\n      footerOverride: $footerOverride\n      logoUrl: $logoUrl\n      pageExtensions: $pageExtensions\n      authAutoLogin: $authAutoLogin\n      authEnforce2FA: $authEnforce2FA\n      authHideLocal: $authHideLocal\n      authLoginBgUrl: $authLoginBgUrl\n      authJwtAudience: $authJwtAudience\n      authJwtExpiration: $authJwtExpiration\n      authJwtRenewablePeriod: $authJwtRenewablePeriod\n      editFab: $editFab\n      editMenuBar: $editMenuBar\n      editMenuBtn: $editMenuBtn\n      editMenuExternalBtn: $editMenuExternalBtn\n      editMenuExternalName: $editMenuExternalName\n      editMenuExternalIcon: $editMenuExternalIcon\n      editMenuExternalUrl: $editMenuExternalUrl\n      featurePageRatings: $featurePageRatings\n      featurePageComments: $featurePageComments\n      featurePersonalWikis: $featurePersonalWikis\n      securityOpenRedirect: $securityOpenRedirect\n      securityIframe: $securityIframe\n      securityReferrerPolicy: $securityReferrerPolicy\n      securityTrustProxy: $securityTrustProxy\n      securitySRI: $securitySRI\n      securityHSTS: $securityHSTS\n      securityHSTSDuration: $securityHSTSDuration\n      securityCSP: $securityCSP\n      securityCSPDirectives: $securityCSPDirectives\n      uploadMaxFileSize: $uploadMaxFileSize\n      uploadMaxFiles: $uploadMaxFiles\n      uploadScanSVG: $uploadScanSVG\n      uploadForceDownload: $uploadForceDownload\n    ) {\n      responseResult {\n        succeeded\n        errorCode\n        slug\n        message\n      }\n    }\n  }\n}\n" ;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub site: Option<Site>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Site {
         #[serde(rename = "updateConfig")]
         pub update_config: Option<UpdateConfig>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct UpdateConfig {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -238,9 +370,7 @@ pub fn site_config_update(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(SiteError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -1,12 +1,11 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::common::{
-    classify_response_error, classify_response_status_error, Boolean,
-    KeyValuePair, KeyValuePairInput, KnownErrorCodes, ResponseStatus,
-    UnknownError,
+    classify_post_error, classify_response_error,
+    classify_response_status_error, post_graphql, Boolean, KeyValuePair,
+    KeyValuePairInput, KnownErrorCodes, ResponseStatus, UnknownError,
 };
 
 #[derive(Clone, Debug, Error, PartialEq)]
@@ -15,6 +14,8 @@ pub enum StorageError {
     UnknownErrorCode { code: i64, message: String },
     #[error("Unknown response error: {message}")]
     UnknownErrorMessage { message: String },
+    #[error("Request failed: {message}")]
+    RequestFailed { message: String },
     #[error("Unknown response error.")]
     UnknownError,
 }
@@ -35,6 +36,9 @@ impl UnknownError for StorageError {
     fn unknown_error_message(message: String) -> Self {
         StorageError::UnknownErrorMessage { message }
     }
+    fn request_failed(message: String) -> Self {
+        StorageError::RequestFailed { message }
+    }
     fn unknown_error() -> Self {
         StorageError::UnknownError
     }
@@ -50,7 +54,7 @@ impl KnownErrorCodes for StorageError {
     }
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct StorageStatus {
     pub key: String,
     pub title: String,
@@ -60,7 +64,7 @@ pub struct StorageStatus {
     pub last_attempt: String,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct StorageTarget {
     #[serde(rename = "isAvailable")]
     pub is_available: Boolean,
@@ -95,13 +99,398 @@ pub struct StorageTargetInput {
     pub config: Option<Vec<Option<KeyValuePairInput>>>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct StorageTargetAction {
     pub handler: String,
     pub label: String,
     pub hint: String,
 }
 
+impl StorageTarget {
+    /// The current config options of the target as `(key, value)` pairs.
+    pub fn config_values(&self) -> Vec<(String, serde_json::Value)> {
+        self.config
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|pair| (pair.key.clone(), pair.config_value()))
+            .collect()
+    }
+
+    /// The target as input for an update, keeping its current settings.
+    ///
+    /// Wiki.js replaces the whole config of an updated target, so changes
+    /// should be made to this rather than to a fresh input.
+    pub fn to_input(&self) -> StorageTargetInput {
+        StorageTargetInput {
+            is_enabled: self.is_enabled,
+            key: self.key.clone(),
+            mode: self.mode.clone().unwrap_or("push".to_string()),
+            sync_interval: self.sync_interval.clone(),
+            config: Some(
+                self.config_values()
+                    .into_iter()
+                    .map(|(key, value)| {
+                        Some(KeyValuePairInput::config(&key, value))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl StorageTargetInput {
+    /// Set a config option, replacing an existing one with the same key.
+    ///
+    /// # Arguments
+    /// * `key` - The key of the option.
+    /// * `value` - The new value.
+    pub fn set_config(&mut self, key: &str, value: serde_json::Value) {
+        let pair = KeyValuePairInput::config(key, value);
+        let config = self.config.get_or_insert_with(Vec::new);
+        config.retain(|p| p.as_ref().is_none_or(|p| p.key != key));
+        config.push(Some(pair));
+    }
+}
+
+/// Typed configuration of a storage module.
+pub trait StorageConfig {
+    /// Key of the storage module, like `git`.
+    fn key(&self) -> &'static str;
+
+    /// The config options as `(key, value)` pairs.
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)>;
+
+    /// The config as key value pairs for a `StorageTargetInput`.
+    fn to_key_value_pairs(&self) -> Vec<KeyValuePairInput> {
+        self.options()
+            .into_iter()
+            .map(|(key, value)| KeyValuePairInput::config(key, value))
+            .collect()
+    }
+
+    /// An enabled storage target with this config.
+    ///
+    /// # Arguments
+    /// * `mode` - The sync mode, `sync`, `push` or `pull`.
+    fn to_target_input(&self, mode: &str) -> StorageTargetInput {
+        StorageTargetInput {
+            is_enabled: true,
+            key: self.key().to_string(),
+            mode: mode.to_string(),
+            sync_interval: None,
+            config: Some(
+                self.to_key_value_pairs().into_iter().map(Some).collect(),
+            ),
+        }
+    }
+}
+
+/// Config of the `git` storage module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GitStorageConfig {
+    pub repo_url: String,
+    pub branch: String,
+    /// `ssh` or `basic`.
+    pub auth_type: String,
+    /// `path` or `contents`.
+    pub ssh_private_key_mode: String,
+    pub ssh_private_key_path: String,
+    pub ssh_private_key_content: String,
+    pub basic_username: String,
+    pub basic_password: String,
+    pub verify_ssl: bool,
+    pub default_name: String,
+    pub default_email: String,
+    pub local_repo_path: String,
+    pub git_binary_path: String,
+}
+
+impl GitStorageConfig {
+    /// Create a config for a repository, using SSH and the `master`
+    /// branch like Wiki.js does by default.
+    pub fn new(repo_url: impl Into<String>) -> Self {
+        Self {
+            repo_url: repo_url.into(),
+            branch: "master".to_string(),
+            auth_type: "ssh".to_string(),
+            ssh_private_key_mode: "path".to_string(),
+            ssh_private_key_path: String::new(),
+            ssh_private_key_content: String::new(),
+            basic_username: String::new(),
+            basic_password: String::new(),
+            verify_ssl: true,
+            default_name: "John Smith".to_string(),
+            default_email: "name@company.com".to_string(),
+            local_repo_path: "./data/repo".to_string(),
+            git_binary_path: String::new(),
+        }
+    }
+
+    pub fn branch(mut self, branch: impl Into<String>) -> Self {
+        self.branch = branch.into();
+        self
+    }
+
+    /// Authenticate with the SSH key at this path on the server.
+    pub fn ssh_key_path(mut self, path: impl Into<String>) -> Self {
+        self.auth_type = "ssh".to_string();
+        self.ssh_private_key_mode = "path".to_string();
+        self.ssh_private_key_path = path.into();
+        self
+    }
+
+    /// Authenticate with this SSH private key.
+    pub fn ssh_key_content(mut self, content: impl Into<String>) -> Self {
+        self.auth_type = "ssh".to_string();
+        self.ssh_private_key_mode = "contents".to_string();
+        self.ssh_private_key_content = content.into();
+        self
+    }
+
+    /// Authenticate with a username and password or token over HTTPS.
+    pub fn basic_auth(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.auth_type = "basic".to_string();
+        self.basic_username = username.into();
+        self.basic_password = password.into();
+        self
+    }
+
+    pub fn verify_ssl(mut self, verify_ssl: bool) -> Self {
+        self.verify_ssl = verify_ssl;
+        self
+    }
+
+    /// Author of commits whose user has no name or email.
+    pub fn default_author(
+        mut self,
+        name: impl Into<String>,
+        email: impl Into<String>,
+    ) -> Self {
+        self.default_name = name.into();
+        self.default_email = email.into();
+        self
+    }
+
+    pub fn local_repo_path(mut self, path: impl Into<String>) -> Self {
+        self.local_repo_path = path.into();
+        self
+    }
+
+    pub fn git_binary_path(mut self, path: impl Into<String>) -> Self {
+        self.git_binary_path = path.into();
+        self
+    }
+}
+
+impl StorageConfig for GitStorageConfig {
+    fn key(&self) -> &'static str {
+        "git"
+    }
+
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)> {
+        vec![
+            ("authType", self.auth_type.clone().into()),
+            ("repoUrl", self.repo_url.clone().into()),
+            ("branch", self.branch.clone().into()),
+            (
+                "sshPrivateKeyMode",
+                self.ssh_private_key_mode.clone().into(),
+            ),
+            (
+                "sshPrivateKeyPath",
+                self.ssh_private_key_path.clone().into(),
+            ),
+            (
+                "sshPrivateKeyContent",
+                self.ssh_private_key_content.clone().into(),
+            ),
+            ("verifySSL", self.verify_ssl.into()),
+            ("basicUsername", self.basic_username.clone().into()),
+            ("basicPassword", self.basic_password.clone().into()),
+            ("defaultEmail", self.default_email.clone().into()),
+            ("defaultName", self.default_name.clone().into()),
+            ("localRepoPath", self.local_repo_path.clone().into()),
+            ("gitBinaryPath", self.git_binary_path.clone().into()),
+        ]
+    }
+}
+
+/// Config of the `s3` storage module for Amazon S3, or `s3generic` for
+/// other S3 compatible services once an endpoint is set.
+#[derive(Clone, Debug, PartialEq)]
+pub struct S3StorageConfig {
+    pub region: String,
+    pub bucket: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Only for S3 compatible services.
+    pub endpoint: Option<String>,
+    pub ssl_enabled: bool,
+    pub force_path_style: bool,
+    pub bucket_endpoint: bool,
+}
+
+impl S3StorageConfig {
+    pub fn new(
+        bucket: impl Into<String>,
+        access_key_id: impl Into<String>,
+        secret_access_key: impl Into<String>,
+    ) -> Self {
+        Self {
+            region: "us-east-1".to_string(),
+            bucket: bucket.into(),
+            access_key_id: access_key_id.into(),
+            secret_access_key: secret_access_key.into(),
+            endpoint: None,
+            ssl_enabled: true,
+            force_path_style: false,
+            bucket_endpoint: false,
+        }
+    }
+
+    pub fn region(mut self, region: impl Into<String>) -> Self {
+        self.region = region.into();
+        self
+    }
+
+    /// Use an S3 compatible service like MinIO at this endpoint.
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    pub fn ssl_enabled(mut self, ssl_enabled: bool) -> Self {
+        self.ssl_enabled = ssl_enabled;
+        self
+    }
+
+    pub fn force_path_style(mut self, force_path_style: bool) -> Self {
+        self.force_path_style = force_path_style;
+        self
+    }
+
+    pub fn bucket_endpoint(mut self, bucket_endpoint: bool) -> Self {
+        self.bucket_endpoint = bucket_endpoint;
+        self
+    }
+}
+
+impl StorageConfig for S3StorageConfig {
+    fn key(&self) -> &'static str {
+        match self.endpoint {
+            Some(_) => "s3generic",
+            None => "s3",
+        }
+    }
+
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)> {
+        let mut options = vec![
+            ("bucket", self.bucket.clone().into()),
+            ("accessKeyId", self.access_key_id.clone().into()),
+            ("secretAccessKey", self.secret_access_key.clone().into()),
+            ("sslEnabled", self.ssl_enabled.into()),
+            ("s3ForcePathStyle", self.force_path_style.into()),
+            ("s3BucketEndpoint", self.bucket_endpoint.into()),
+        ];
+        match &self.endpoint {
+            Some(endpoint) => {
+                options.push(("endpoint", endpoint.clone().into()))
+            }
+            None => options.push(("region", self.region.clone().into())),
+        }
+        options
+    }
+}
+
+/// Config of the `azure` storage module for Azure Blob Storage.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AzureStorageConfig {
+    pub account_name: String,
+    pub account_key: String,
+    pub container_name: String,
+    /// `Hot` or `Cool`.
+    pub storage_tier: String,
+}
+
+impl AzureStorageConfig {
+    pub fn new(
+        account_name: impl Into<String>,
+        account_key: impl Into<String>,
+    ) -> Self {
+        Self {
+            account_name: account_name.into(),
+            account_key: account_key.into(),
+            container_name: "wiki".to_string(),
+            storage_tier: "Cool".to_string(),
+        }
+    }
+
+    pub fn container_name(mut self, container_name: impl Into<String>) -> Self {
+        self.container_name = container_name.into();
+        self
+    }
+
+    pub fn storage_tier(mut self, storage_tier: impl Into<String>) -> Self {
+        self.storage_tier = storage_tier.into();
+        self
+    }
+}
+
+impl StorageConfig for AzureStorageConfig {
+    fn key(&self) -> &'static str {
+        "azure"
+    }
+
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)> {
+        vec![
+            ("accountName", self.account_name.clone().into()),
+            ("accountKey", self.account_key.clone().into()),
+            ("containerName", self.container_name.clone().into()),
+            ("storageTier", self.storage_tier.clone().into()),
+        ]
+    }
+}
+
+/// Config of the `disk` storage module writing to a local directory of the
+/// server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocalStorageConfig {
+    pub path: String,
+    pub create_daily_backups: bool,
+}
+
+impl LocalStorageConfig {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            create_daily_backups: false,
+        }
+    }
+
+    pub fn create_daily_backups(mut self, create_daily_backups: bool) -> Self {
+        self.create_daily_backups = create_daily_backups;
+        self
+    }
+}
+
+impl StorageConfig for LocalStorageConfig {
+    fn key(&self) -> &'static str {
+        "disk"
+    }
+
+    fn options(&self) -> Vec<(&'static str, serde_json::Value)> {
+        vec![
+            ("path", self.path.clone().into()),
+            ("createDailyBackups", self.create_daily_backups.into()),
+        ]
+    }
+}
+
 pub mod storage_action_execute {
     use super::*;
 
@@ -119,17 +508,17 @@ pub mod storage_action_execute {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub storage: Option<Storage>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Storage {
         #[serde(rename = "executeAction")]
         pub execute_action: Option<ExecuteAction>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ExecuteAction {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -165,9 +554,7 @@ pub fn storage_action_execute(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(StorageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -201,12 +588,12 @@ pub mod storage_status_get {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub storage: Option<Storage>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Storage {
         pub status: Option<Vec<Option<StorageStatus>>>,
     }
@@ -236,9 +623,7 @@ pub fn storage_status_list(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(StorageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -267,12 +652,12 @@ pub mod storage_target_list {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub storage: Option<Storage>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Storage {
         pub targets: Option<Vec<Option<StorageTarget>>>,
     }
@@ -301,9 +686,7 @@ pub fn storage_target_list(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(StorageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -336,18 +719,18 @@ pub mod storage_target_update {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub storage: Option<Storage>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Storage {
         #[serde(rename = "updateTargets")]
         pub update_targets: Option<UpdateTargets>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct UpdateTargets {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -380,9 +763,7 @@ pub fn storage_target_update(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(StorageError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -0,0 +1,568 @@
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use thiserror::Error;
+
+use crate::common::{
+    concurrency, run_parallel, stable_hash, CancellationToken, Date, Int,
+};
+use crate::page::{
+    page_create, page_delete, page_get, page_get_by_path, page_get_updated_at,
+    page_list, page_update, parse_frontmatter_markdown, PageEditor, PageError,
+    PageFrontMatter,
+};
+
+/// Name of the file in the synced directory that remembers the state of
+/// the last sync.
+pub const SYNC_STATE_FILE: &str = ".wikijs-sync.yaml";
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum SyncError {
+    #[error(transparent)]
+    Page(#[from] PageError),
+    #[error("Failed to access the sync directory: {0}")]
+    Io(String),
+    #[error("Invalid front matter in {file}: {message}")]
+    InvalidFrontMatter { file: String, message: String },
+    #[error("{0} was changed both locally and in the wiki.")]
+    Conflict(String),
+}
+
+impl From<std::io::Error> for SyncError {
+    fn from(e: std::io::Error) -> Self {
+        SyncError::Io(e.to_string())
+    }
+}
+
+/// How a page differs between the local directory and the wiki.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncStatus {
+    Unchanged,
+    LocalModified,
+    RemoteModified,
+    LocalNew,
+    RemoteNew,
+    LocalDeleted,
+    RemoteDeleted,
+    /// Changed on both sides since the last sync, or present on both sides
+    /// without ever having been synced.
+    Conflict,
+}
+
+impl std::fmt::Display for SyncStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            SyncStatus::Unchanged => "unchanged",
+            SyncStatus::LocalModified => "local modified",
+            SyncStatus::RemoteModified => "remote modified",
+            SyncStatus::LocalNew => "local new",
+            SyncStatus::RemoteNew => "remote new",
+            SyncStatus::LocalDeleted => "local deleted",
+            SyncStatus::RemoteDeleted => "remote deleted",
+            SyncStatus::Conflict => "conflict",
+        };
+        write!(f, "{}", status)
+    }
+}
+
+/// A page as seen by a sync.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SyncItem {
+    pub locale: String,
+    pub path: String,
+    pub id: Option<Int>,
+    /// The local file relative to the synced directory, if there is one.
+    pub file: Option<String>,
+    pub status: SyncStatus,
+}
+
+/// Result of applying one [`SyncItem`](struct.SyncItem.html).
+pub type SyncResult = Vec<(SyncItem, Result<(), SyncError>)>;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+struct SyncState {
+    entries: BTreeMap<String, SyncEntry>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+struct SyncEntry {
+    id: Int,
+    file: String,
+    hash: String,
+    updated_at: Date,
+}
+
+impl SyncState {
+    fn load(dir: &Path) -> Result<Self, SyncError> {
+        let path = dir.join(SYNC_STATE_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        serde_yaml::from_str(&fs::read_to_string(&path)?).map_err(|e| {
+            SyncError::InvalidFrontMatter {
+                file: SYNC_STATE_FILE.to_string(),
+                message: e.to_string(),
+            }
+        })
+    }
+
+    fn save(&self, dir: &Path) -> Result<(), SyncError> {
+        // the state only contains strings and numbers
+        let yaml = serde_yaml::to_string(self).unwrap();
+        fs::write(dir.join(SYNC_STATE_FILE), yaml)?;
+        Ok(())
+    }
+}
+
+fn editor_from_extension(extension: &str) -> Option<PageEditor> {
+    match extension {
+        "md" => Some(PageEditor::Markdown),
+        "html" => Some(PageEditor::Ckeditor),
+        "adoc" => Some(PageEditor::Asciidoc),
+        "txt" => Some(PageEditor::Code),
+        _ => None,
+    }
+}
+
+/// Key of a page in the sync state, like `en/docs/intro`.
+fn page_key(locale: &str, path: &str) -> String {
+    format!("{}/{}", locale, path.trim_matches('/'))
+}
+
+/// File of a page relative to the synced directory, like
+/// `en/docs/intro.md`.
+fn page_file(locale: &str, path: &str, editor: &PageEditor) -> String {
+    format!("{}.{}", page_key(locale, path), editor.file_extension())
+}
+
+struct LocalFile {
+    file: String,
+    hash: String,
+}
+
+fn scan_dir(
+    root: &Path,
+    dir: &Path,
+    files: &mut BTreeMap<String, LocalFile>,
+) -> Result<(), SyncError> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            scan_dir(root, &path, files)?;
+            continue;
+        }
+        let extension = path.extension().and_then(|e| e.to_str());
+        if extension.and_then(editor_from_extension).is_none() {
+            continue;
+        }
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        let file = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        // files directly in the root have no locale
+        let Some((key, _)) = file.rsplit_once('.') else {
+            continue;
+        };
+        if !key.contains('/') {
+            continue;
+        }
+        files.insert(
+            key.to_string(),
+            LocalFile {
+                hash: stable_hash(&fs::read(&path)?),
+                file,
+            },
+        );
+    }
+    Ok(())
+}
+
+fn status_of(
+    entry: Option<&SyncEntry>,
+    local: Option<&LocalFile>,
+    remote: Option<&Date>,
+) -> SyncStatus {
+    let Some(entry) = entry else {
+        return match (local, remote) {
+            (Some(_), None) => SyncStatus::LocalNew,
+            (None, Some(_)) => SyncStatus::RemoteNew,
+            _ => SyncStatus::Conflict,
+        };
+    };
+    let local_changed = local.is_none_or(|l| l.hash != entry.hash);
+    let remote_changed = remote.is_none_or(|r| *r != entry.updated_at);
+    match (local, remote, local_changed, remote_changed) {
+        (None, None, _, _) => SyncStatus::Unchanged,
+        (None, Some(_), _, true) => SyncStatus::Conflict,
+        (None, Some(_), _, false) => SyncStatus::LocalDeleted,
+        (Some(_), None, true, _) => SyncStatus::Conflict,
+        (Some(_), None, false, _) => SyncStatus::RemoteDeleted,
+        (_, _, true, true) => SyncStatus::Conflict,
+        (_, _, true, false) => SyncStatus::LocalModified,
+        (_, _, false, true) => SyncStatus::RemoteModified,
+        (_, _, false, false) => SyncStatus::Unchanged,
+    }
+}
+
+fn compare(
+    client: &Client,
+    url: &str,
+    dir: &Path,
+    state: &SyncState,
+) -> Result<Vec<SyncItem>, SyncError> {
+    let mut local = BTreeMap::new();
+    if dir.exists() {
+        scan_dir(dir, dir, &mut local)?;
+    }
+    let remote: BTreeMap<String, (Int, String, String, Date)> =
+        page_list(client, url, None, None, None, None, None, None, None)?
+            .into_iter()
+            .map(|p| {
+                (
+                    page_key(&p.locale, &p.path),
+                    (p.id, p.locale, p.path, p.updated_at),
+                )
+            })
+            .collect();
+
+    let keys: BTreeSet<&String> = state
+        .entries
+        .keys()
+        .chain(local.keys())
+        .chain(remote.keys())
+        .collect();
+    let mut items = Vec::new();
+    for key in keys {
+        let entry = state.entries.get(key);
+        let local = local.get(key);
+        let remote = remote.get(key);
+        let status = status_of(entry, local, remote.map(|r| &r.3));
+        if status == SyncStatus::Unchanged && local.is_none() {
+            continue;
+        }
+        let (locale, path) = match remote {
+            Some((_, locale, path, _)) => (locale.clone(), path.clone()),
+            None => {
+                // every key contains a slash, see scan_dir and page_key
+                let (locale, path) = key.split_once('/').unwrap();
+                (locale.to_string(), path.to_string())
+            }
+        };
+        items.push(SyncItem {
+            locale,
+            path,
+            id: remote.map(|r| r.0).or(entry.map(|e| e.id)),
+            file: local
+                .map(|l| l.file.clone())
+                .or(entry.map(|e| e.file.clone())),
+            status,
+        });
+    }
+    Ok(items)
+}
+
+/// Compare a local directory with the wiki.
+///
+/// The directory holds one file per page at `<locale>/<path>.<ext>`, where
+/// the extension follows the editor, with the page metadata as optional
+/// YAML front matter. Changes are detected against the state of the last
+/// push or pull, stored in [`SYNC_STATE_FILE`](constant.SYNC_STATE_FILE.html),
+/// using content hashes for local files and `updatedAt` for pages.
+///
+/// # Returns
+/// A Result containing either all pages and files with their status or a
+/// sync error.
+pub fn sync_status(
+    client: &Client,
+    url: &str,
+    dir: &Path,
+) -> Result<Vec<SyncItem>, SyncError> {
+    compare(client, url, dir, &SyncState::load(dir)?)
+}
+
+fn read_local(
+    dir: &Path,
+    file: &str,
+) -> Result<(PageFrontMatter, String, String), SyncError> {
+    let data = fs::read_to_string(dir.join(file))?;
+    let (front_matter, content) =
+        parse_frontmatter_markdown(&data).map_err(|e| {
+            SyncError::InvalidFrontMatter {
+                file: file.to_string(),
+                message: e.to_string(),
+            }
+        })?;
+    Ok((front_matter, content, stable_hash(data.as_bytes())))
+}
+
+fn push_item(
+    client: &Client,
+    url: &str,
+    dir: &Path,
+    state: &Mutex<SyncState>,
+    item: &SyncItem,
+) -> Result<(), SyncError> {
+    let key = page_key(&item.locale, &item.path);
+    // every item apart from remote only ones has a file
+    let file = item.file.clone().unwrap_or_default();
+    match item.status {
+        SyncStatus::LocalNew => {
+            let (front_matter, content, hash) = read_local(dir, &file)?;
+            let editor = front_matter.editor.unwrap_or_else(|| {
+                file.rsplit_once('.')
+                    .and_then(|(_, e)| editor_from_extension(e))
+                    .unwrap_or(PageEditor::Markdown)
+            });
+            let title = front_matter.title.unwrap_or_else(|| {
+                item.path.rsplit('/').next().unwrap_or_default().to_string()
+            });
+            page_create(
+                client,
+                url,
+                content,
+                front_matter.description.unwrap_or_default(),
+                editor,
+                front_matter.is_published.unwrap_or(true),
+                front_matter.is_private.unwrap_or(false),
+                item.locale.clone(),
+                item.path.clone(),
+                front_matter.publish_end_date,
+                front_matter.publish_start_date,
+                front_matter.script_css,
+                front_matter.script_js,
+                front_matter
+                    .tags
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(Some)
+                    .collect(),
+                title,
+            )?;
+            let page = page_get_by_path(
+                client,
+                url,
+                item.path.clone(),
+                item.locale.clone(),
+            )?;
+            state.lock().unwrap().entries.insert(
+                key,
+                SyncEntry {
+                    id: page.id,
+                    file,
+                    hash,
+                    updated_at: page.updated_at,
+                },
+            );
+        }
+        SyncStatus::LocalModified => {
+            let (front_matter, content, hash) = read_local(dir, &file)?;
+            // modified items always have a state entry
+            let id = item.id.unwrap_or_default();
+            page_update(
+                client,
+                url,
+                id,
+                Some(content),
+                front_matter.description,
+                front_matter.editor,
+                front_matter.is_private,
+                front_matter.is_published,
+                None,
+                None,
+                front_matter.publish_end_date,
+                front_matter.publish_start_date,
+                front_matter.script_css,
+                front_matter.script_js,
+                front_matter
+                    .tags
+                    .map(|tags| tags.into_iter().map(Some).collect()),
+                front_matter.title,
+            )?;
+            state.lock().unwrap().entries.insert(
+                key,
+                SyncEntry {
+                    id,
+                    file,
+                    hash,
+                    updated_at: page_get_updated_at(client, url, id)?,
+                },
+            );
+        }
+        SyncStatus::LocalDeleted => {
+            page_delete(client, url, item.id.unwrap_or_default())?;
+            state.lock().unwrap().entries.remove(&key);
+        }
+        SyncStatus::Conflict => return Err(SyncError::Conflict(key)),
+        _ => {}
+    }
+    Ok(())
+}
+
+fn pull_item(
+    client: &Client,
+    url: &str,
+    dir: &Path,
+    state: &Mutex<SyncState>,
+    item: &SyncItem,
+) -> Result<(), SyncError> {
+    let key = page_key(&item.locale, &item.path);
+    match item.status {
+        SyncStatus::RemoteNew | SyncStatus::RemoteModified => {
+            let page = page_get(client, url, item.id.unwrap_or_default())?;
+            let file = page_file(&page.locale, &page.path, &page.editor);
+            let data = page.to_markdown_with_frontmatter();
+            let path: PathBuf = dir.join(&file);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, &data)?;
+            // a changed editor also changes the file extension
+            if let Some(old) = &item.file {
+                if *old != file {
+                    fs::remove_file(dir.join(old))?;
+                }
+            }
+            state.lock().unwrap().entries.insert(
+                key,
+                SyncEntry {
+                    id: page.id,
+                    file,
+                    hash: stable_hash(data.as_bytes()),
+                    updated_at: page.updated_at,
+                },
+            );
+        }
+        SyncStatus::RemoteDeleted => {
+            if let Some(file) = &item.file {
+                fs::remove_file(dir.join(file))?;
+            }
+            state.lock().unwrap().entries.remove(&key);
+        }
+        SyncStatus::Conflict => return Err(SyncError::Conflict(key)),
+        _ => {}
+    }
+    Ok(())
+}
+
+fn apply<F>(
+    client: &Client,
+    url: &str,
+    dir: &Path,
+    statuses: &[SyncStatus],
+    apply_item: F,
+    cancel: Option<&CancellationToken>,
+) -> Result<SyncResult, SyncError>
+where
+    F: Fn(
+            &Client,
+            &str,
+            &Path,
+            &Mutex<SyncState>,
+            &SyncItem,
+        ) -> Result<(), SyncError>
+        + Sync,
+{
+    let mut state = SyncState::load(dir)?;
+    let items = compare(client, url, dir, &state)?;
+    // forget pages that are gone on both sides
+    let keys: BTreeSet<String> = items
+        .iter()
+        .map(|item| page_key(&item.locale, &item.path))
+        .collect();
+    state.entries.retain(|key, _| keys.contains(key));
+    let items: Vec<SyncItem> = items
+        .into_iter()
+        .filter(|item| {
+            item.status == SyncStatus::Conflict
+                || statuses.contains(&item.status)
+        })
+        .collect();
+    let state = Mutex::new(state);
+    let results = run_parallel(&items, concurrency(), cancel, |item| {
+        apply_item(client, url, dir, &state, item)
+    });
+    fs::create_dir_all(dir)?;
+    state.into_inner().unwrap().save(dir)?;
+    // items skipped after a cancellation are left for the next run
+    Ok(items
+        .into_iter()
+        .zip(results)
+        .filter_map(|(item, result)| Some((item, result?)))
+        .collect())
+}
+
+/// Upload local changes to the wiki.
+///
+/// New and modified files are created or updated in the wiki and deleted
+/// files delete their page. Conflicts are left alone and reported as
+/// [`SyncError::Conflict`](enum.SyncError.html#variant.Conflict).
+///
+/// # Arguments
+/// * `cancel` - Stops before the next change, the result and the sync state
+///   only contain those done.
+///
+/// # Returns
+/// A Result containing either the result of each change or a sync error
+/// if the directory or wiki could not be compared.
+pub fn sync_push(
+    client: &Client,
+    url: &str,
+    dir: &Path,
+    cancel: Option<&CancellationToken>,
+) -> Result<SyncResult, SyncError> {
+    apply(
+        client,
+        url,
+        dir,
+        &[
+            SyncStatus::LocalNew,
+            SyncStatus::LocalModified,
+            SyncStatus::LocalDeleted,
+        ],
+        push_item,
+        cancel,
+    )
+}
+
+/// Download changes from the wiki into the local directory.
+///
+/// New and modified pages are written as files with front matter and
+/// deleted pages remove their file. Conflicts are left alone and reported
+/// as [`SyncError::Conflict`](enum.SyncError.html#variant.Conflict).
+///
+/// # Arguments
+/// * `cancel` - Stops before the next change, the result and the sync state
+///   only contain those done.
+///
+/// # Returns
+/// A Result containing either the result of each change or a sync error
+/// if the directory or wiki could not be compared.
+pub fn sync_pull(
+    client: &Client,
+    url: &str,
+    dir: &Path,
+    cancel: Option<&CancellationToken>,
+) -> Result<SyncResult, SyncError> {
+    apply(
+        client,
+        url,
+        dir,
+        &[
+            SyncStatus::RemoteNew,
+            SyncStatus::RemoteModified,
+            SyncStatus::RemoteDeleted,
+        ],
+        pull_item,
+        cancel,
+    )
+}
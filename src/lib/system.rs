@@ -1,11 +1,11 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::common::{
-    classify_response_error, classify_response_status_error, Boolean, Date,
-    Int, KnownErrorCodes, ResponseStatus, UnknownError,
+    classify_post_error, classify_response_error,
+    classify_response_status_error, post_graphql, Boolean, Date, Int,
+    KnownErrorCodes, ResponseStatus, UnknownError,
 };
 
 #[derive(Clone, Debug, Error, PartialEq)]
@@ -22,6 +22,8 @@ pub enum SystemError {
     UnknownErrorCode { code: i64, message: String },
     #[error("Unknown response error: {message}")]
     UnknownErrorMessage { message: String },
+    #[error("Request failed: {message}")]
+    RequestFailed { message: String },
     #[error("Unknown response error.")]
     UnknownError,
 }
@@ -48,6 +50,9 @@ impl UnknownError for SystemError {
     fn unknown_error_message(message: String) -> Self {
         SystemError::UnknownErrorMessage { message }
     }
+    fn request_failed(message: String) -> Self {
+        SystemError::RequestFailed { message }
+    }
     fn unknown_error() -> Self {
         SystemError::UnknownError
     }
@@ -63,13 +68,13 @@ impl KnownErrorCodes for SystemError {
     }
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct SystemFlag {
     pub key: String,
     pub value: Boolean,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct SystemInfo {
     #[serde(rename = "configFile")]
     pub config_file: Option<String>,
@@ -128,7 +133,7 @@ pub struct SystemInfo {
     pub working_directory: Option<String>,
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SystemExtension {
     pub key: String,
     pub title: String,
@@ -152,7 +157,7 @@ pub enum SystemImportUsersGroupMode {
     NONE,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct SystemExportStatus {
     pub status: Option<String>,
     pub progress: Option<Int>,
@@ -172,12 +177,12 @@ pub mod system_flag_list {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct ResponseData {
         pub system: Option<System>,
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct System {
         pub flags: Option<Vec<Option<SystemFlag>>>,
     }
@@ -206,9 +211,7 @@ pub fn system_flag_list(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(SystemError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -232,12 +235,12 @@ pub mod system_info_get {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub system: Option<System>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct System {
         pub info: Option<SystemInfo>,
     }
@@ -266,9 +269,7 @@ pub fn system_info_get(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(SystemError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -292,12 +293,12 @@ pub mod system_extension_list {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub system: Option<System>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct System {
         pub extensions: Option<Vec<Option<SystemExtension>>>,
     }
@@ -326,9 +327,7 @@ pub fn system_extension_list(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(SystemError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -355,12 +354,12 @@ pub mod system_export_status_get {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub system: Option<System>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct System {
         #[serde(rename = "exportStatus")]
         pub export_status: Option<SystemExportStatus>,
@@ -391,9 +390,7 @@ pub fn system_export_status_get(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(SystemError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -421,18 +418,18 @@ pub mod system_flags_update {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub system: Option<System>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct System {
         #[serde(rename = "updateFlags")]
         pub update_flags: Option<UpdateFlags>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct UpdateFlags {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -465,9 +462,7 @@ pub fn system_flags_update(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(SystemError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -500,18 +495,18 @@ pub mod telemetry_client_id_reset {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub system: Option<System>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct System {
         #[serde(rename = "resetTelemetryClientId")]
         pub reset_telemetry_client_id: Option<ResetTelemetryClientId>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResetTelemetryClientId {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -542,9 +537,7 @@ pub fn telemetry_client_id_reset(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(SystemError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -584,18 +577,18 @@ pub mod telemetry_set {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub system: Option<System>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct System {
         #[serde(rename = "setTelemetry")]
         pub set_telemetry: Option<SetTelemetry>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct SetTelemetry {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -625,9 +618,7 @@ pub fn telemetry_set(
     let response =
         post_graphql::<telemetry_set::TelemetrySet, _>(client, url, variables);
     if response.is_err() {
-        return Err(SystemError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -659,18 +650,18 @@ pub mod system_upgrade_perform {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub system: Option<System>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct System {
         #[serde(rename = "performUpgrade")]
         pub perform_upgrade: Option<PerformUpgrade>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct PerformUpgrade {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -701,9 +692,7 @@ pub fn system_upgrade_perform(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(SystemError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -743,18 +732,18 @@ pub mod system_user_import_from_v1 {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub system: Option<System>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct System {
         #[serde(rename = "importUsersFromV1")]
         pub import_users_from_v1: Option<ImportUsersFromV1>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ImportUsersFromV1 {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -790,9 +779,7 @@ pub fn system_user_import_from_v1(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(SystemError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -831,18 +818,18 @@ pub mod system_https_redirection_set {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub system: Option<System>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct System {
         #[serde(rename = "setHTTPSRedirection")]
         pub set_https_redirection: Option<SetHttpsRedirection>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct SetHttpsRedirection {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -874,9 +861,7 @@ pub fn https_redirection_set(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(SystemError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
 
@@ -912,18 +897,18 @@ pub mod system_https_certificate_renew {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub system: Option<System>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct System {
         #[serde(rename = "renewHTTPSCertificate")]
         pub renew_https_certificate: Option<RenewHttpsCertificate>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct RenewHttpsCertificate {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -954,9 +939,7 @@ pub fn https_certificate_renew(
         _,
     >(client, url, variables);
     if response.is_err() {
-        return Err(SystemError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
 
     let response_body = response.unwrap();
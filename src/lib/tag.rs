@@ -0,0 +1,199 @@
+use reqwest::blocking::Client;
+
+use crate::common::{concurrency, run_parallel, CancellationToken, Int};
+use crate::page::{
+    page_get, page_list, page_tag_delete, page_tag_list, page_tag_update,
+    page_update_from, PageBulkResult, PageError, PageTag,
+};
+use crate::replace::PageFilter;
+
+fn find_tag(tags: &[PageTag], tag: &str) -> Option<PageTag> {
+    tags.iter().find(|t| t.tag == tag).cloned()
+}
+
+fn pages_with_tag(
+    client: &Client,
+    url: &str,
+    tag: &str,
+) -> Result<Vec<Int>, PageError> {
+    Ok(page_list(
+        client,
+        url,
+        None,
+        None,
+        None,
+        Some(vec![tag.to_string()]),
+        None,
+        None,
+        None,
+    )?
+    .into_iter()
+    .map(|page| page.id)
+    .collect())
+}
+
+/// Apply `f` to the tags of every page and write back the changed ones.
+fn retag<F>(
+    client: &Client,
+    url: &str,
+    ids: &[Int],
+    f: F,
+    cancel: Option<&CancellationToken>,
+) -> PageBulkResult
+where
+    F: Fn(&mut Vec<String>) + Sync,
+{
+    let results = run_parallel(ids, concurrency(), cancel, |id| {
+        let page = page_get(client, url, *id)?;
+        let old: Vec<String> = page
+            .tags
+            .iter()
+            .flatten()
+            .map(|tag| tag.tag.clone())
+            .collect();
+        let mut new = old.clone();
+        f(&mut new);
+        let mut seen = Vec::new();
+        new.retain(|tag| {
+            let first = !seen.contains(tag);
+            seen.push(tag.clone());
+            first
+        });
+        if new == old {
+            return Ok(());
+        }
+        page_update_from(client, url, page, new.into_iter().map(Some).collect())
+    });
+    ids.iter()
+        .zip(results)
+        .filter_map(|(id, result)| result.map(|result| (*id, result)))
+        .collect()
+}
+
+/// Rename a tag.
+///
+/// Pages reference tags by id, so renaming to an unused name only updates
+/// the tag itself. If a tag named `new` already exists, this is a
+/// [`page_tag_merge`](fn.page_tag_merge.html) instead.
+///
+/// # Arguments
+/// * `cancel` - Optional token to stop a merge before the remaining pages.
+///
+/// # Returns
+/// A Result containing either the id and result of every re-tagged page or
+/// a page error if the tags could not be listed or updated.
+pub fn page_tag_rename(
+    client: &Client,
+    url: &str,
+    old: &str,
+    new: &str,
+    cancel: Option<&CancellationToken>,
+) -> Result<PageBulkResult, PageError> {
+    let tags = page_tag_list(client, url)?;
+    let tag = find_tag(&tags, old).ok_or(PageError::PageTagNotFound)?;
+    if find_tag(&tags, new).is_some() {
+        return page_tag_merge(client, url, old, new, cancel);
+    }
+    let title = match tag.title {
+        Some(title) if title != old => title,
+        _ => new.to_string(),
+    };
+    page_tag_update(client, url, tag.id, new.to_string(), title)?;
+    Ok(Vec::new())
+}
+
+/// Merge a tag into another one.
+///
+/// Every page tagged with `from` is re-tagged with `into`, then `from` is
+/// deleted unless one of the pages failed to update or was skipped due to
+/// cancellation.
+///
+/// # Arguments
+/// * `cancel` - Optional token to stop before the remaining pages.
+///
+/// # Returns
+/// A Result containing either the id and result of every re-tagged page or
+/// a page error if the tags could not be listed or deleted.
+pub fn page_tag_merge(
+    client: &Client,
+    url: &str,
+    from: &str,
+    into: &str,
+    cancel: Option<&CancellationToken>,
+) -> Result<PageBulkResult, PageError> {
+    let tag = find_tag(&page_tag_list(client, url)?, from)
+        .ok_or(PageError::PageTagNotFound)?;
+    let ids = pages_with_tag(client, url, from)?;
+    let results = retag(
+        client,
+        url,
+        &ids,
+        |tags| {
+            for tag in tags.iter_mut() {
+                if tag == from {
+                    *tag = into.to_string();
+                }
+            }
+        },
+        cancel,
+    );
+    if results.len() == ids.len()
+        && results.iter().all(|(_, result)| result.is_ok())
+        && find_tag(&page_tag_list(client, url)?, from).is_some()
+    {
+        page_tag_delete(client, url, tag.id)?;
+    }
+    Ok(results)
+}
+
+/// Add tags to every page matching a filter.
+///
+/// # Arguments
+/// * `cancel` - Optional token to stop before the remaining pages, which
+///   are left out of the result.
+///
+/// # Returns
+/// A Result containing either the id and result of every matching page or
+/// a page error if the pages could not be listed.
+pub fn page_tags_add(
+    client: &Client,
+    url: &str,
+    filter: &PageFilter,
+    tags: &[String],
+    cancel: Option<&CancellationToken>,
+) -> Result<PageBulkResult, PageError> {
+    let ids = filter.page_ids(client, url)?;
+    Ok(retag(
+        client,
+        url,
+        &ids,
+        |page_tags| page_tags.extend(tags.iter().cloned()),
+        cancel,
+    ))
+}
+
+/// Remove tags from every page matching a filter.
+///
+/// # Arguments
+/// * `cancel` - Optional token to stop before the remaining pages, which
+///   are left out of the result.
+///
+/// # Returns
+/// A Result containing either the id and result of every matching page or
+/// a page error if the pages could not be listed.
+pub fn page_tags_remove(
+    client: &Client,
+    url: &str,
+    filter: &PageFilter,
+    tags: &[String],
+    cancel: Option<&CancellationToken>,
+) -> Result<PageBulkResult, PageError> {
+    let ids = filter.page_ids(client, url)?;
+    Ok(retag(
+        client,
+        url,
+        &ids,
+        |page_tags| page_tags.retain(|tag| !tags.contains(tag)),
+        cancel,
+    ))
+}
@@ -0,0 +1,120 @@
+use regex::{Captures, Regex};
+use reqwest::blocking::Client;
+use std::collections::HashMap;
+use thiserror::Error;
+
+use crate::page::{page_create, page_get_by_path, PageError};
+
+/// Path prefix below which templates are stored by default.
+pub const DEFAULT_TEMPLATE_PREFIX: &str = "templates";
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum TemplateError {
+    #[error(transparent)]
+    Page(#[from] PageError),
+    #[error("The template uses the variable {0}, but no value was given.")]
+    MissingVariable(String),
+}
+
+/// Where templates are looked up.
+#[derive(Clone, Debug)]
+pub struct TemplateOptions {
+    /// Path prefix of the template pages, like `templates` for
+    /// `templates/meeting-notes`.
+    pub prefix: String,
+    /// Locale of the template and the created page.
+    pub locale: String,
+}
+
+impl Default for TemplateOptions {
+    fn default() -> Self {
+        Self {
+            prefix: DEFAULT_TEMPLATE_PREFIX.to_string(),
+            locale: "en".to_string(),
+        }
+    }
+}
+
+/// Replace the `{{var}}` placeholders of a template.
+///
+/// Whitespace inside the braces is ignored, so `{{ date }}` works as well.
+///
+/// # Returns
+/// A Result containing either the rendered text or
+/// TemplateError::MissingVariable for the first placeholder without a
+/// value.
+pub fn template_render(
+    template: &str,
+    vars: &HashMap<String, String>,
+) -> Result<String, TemplateError> {
+    // the pattern is constant and valid
+    let placeholder = Regex::new(r"\{\{\s*([A-Za-z0-9_.-]+)\s*\}\}").unwrap();
+    let mut missing = None;
+    let rendered = placeholder.replace_all(template, |caps: &Captures| {
+        match vars.get(&caps[1]) {
+            Some(value) => value.clone(),
+            None => {
+                missing.get_or_insert_with(|| caps[1].to_string());
+                caps[0].to_string()
+            }
+        }
+    });
+    match missing {
+        Some(name) => Err(TemplateError::MissingVariable(name)),
+        None => Ok(rendered.into_owned()),
+    }
+}
+
+/// Create a page from a template page.
+///
+/// The content, title and description of the template at
+/// `<prefix>/<template_path>` are rendered with
+/// [`template_render`](fn.template_render.html), its editor and tags are
+/// copied. The `path` variable is set to the destination path unless given.
+///
+/// # Returns
+/// A Result containing either nothing or a template error.
+pub fn page_create_from_template(
+    client: &Client,
+    url: &str,
+    template_path: &str,
+    vars: &HashMap<String, String>,
+    dest_path: &str,
+    options: &TemplateOptions,
+) -> Result<(), TemplateError> {
+    let template = page_get_by_path(
+        client,
+        url,
+        format!(
+            "{}/{}",
+            options.prefix.trim_matches('/'),
+            template_path.trim_matches('/')
+        ),
+        options.locale.clone(),
+    )?;
+    let mut vars = vars.clone();
+    vars.entry("path".to_string())
+        .or_insert_with(|| dest_path.to_string());
+    page_create(
+        client,
+        url,
+        template_render(&template.content, &vars)?,
+        template_render(&template.description, &vars)?,
+        template.editor,
+        true,
+        false,
+        options.locale.clone(),
+        dest_path.to_string(),
+        None,
+        None,
+        template.script_css,
+        template.script_js,
+        template
+            .tags
+            .into_iter()
+            .map(|t| t.map(|t| t.tag))
+            .collect(),
+        template_render(&template.title, &vars)?,
+    )?;
+    Ok(())
+}
@@ -1,11 +1,11 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::common::{
-    classify_response_error, classify_response_status_error, Boolean,
-    KnownErrorCodes, ResponseStatus, UnknownError,
+    classify_post_error, classify_response_error,
+    classify_response_status_error, post_graphql, Boolean, KnownErrorCodes,
+    ResponseStatus, UnknownError,
 };
 
 #[derive(Clone, Error, Debug, PartialEq)]
@@ -14,6 +14,8 @@ pub enum ThemeError {
     UnknownErrorCode { code: i64, message: String },
     #[error("Unknown response error: {message}")]
     UnknownErrorMessage { message: String },
+    #[error("Request failed: {message}")]
+    RequestFailed { message: String },
     #[error("Unknown response error.")]
     UnknownError,
 }
@@ -34,6 +36,9 @@ impl UnknownError for ThemeError {
     fn unknown_error_message(message: String) -> Self {
         ThemeError::UnknownErrorMessage { message }
     }
+    fn request_failed(message: String) -> Self {
+        ThemeError::RequestFailed { message }
+    }
     fn unknown_error() -> Self {
         ThemeError::UnknownError
     }
@@ -49,14 +54,14 @@ impl KnownErrorCodes for ThemeError {
     }
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Theme {
     pub key: Option<String>,
     pub title: Option<String>,
     pub author: Option<String>,
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ThemingConfig {
     pub theme: String,
     pub iconset: String,
@@ -83,12 +88,12 @@ pub mod theme_list {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub theming: Option<Theming>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Theming {
         pub themes: Option<Vec<Option<Theme>>>,
     }
@@ -116,9 +121,7 @@ pub fn theme_list(
     let response =
         post_graphql::<theme_list::ThemeList, _>(client, url, variables);
     if response.is_err() {
-        return Err(ThemeError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -142,12 +145,12 @@ pub mod theme_config_get {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub theming: Option<Theming>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Theming {
         pub config: Option<ThemingConfig>,
     }
@@ -176,9 +179,7 @@ pub fn theme_config_get(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(ThemeError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -217,17 +218,17 @@ pub mod theme_config_update {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub theming: Option<Theming>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Theming {
         #[serde(rename = "setConfig")]
         pub set_config: Option<SetConfig>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct SetConfig {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -273,9 +274,7 @@ pub fn theme_config_update(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(ThemeError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
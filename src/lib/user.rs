@@ -1,11 +1,12 @@
-use graphql_client::reqwest::post_graphql_blocking as post_graphql;
+use chrono::{DateTime, Utc};
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::common::{
-    classify_response_error, classify_response_status_error, Boolean, Date,
-    Int, KnownErrorCodes, ResponseStatus, UnknownError,
+    classify_post_error, classify_response_error,
+    classify_response_status_error, post_graphql, Boolean, Date, Int,
+    KnownErrorCodes, ResponseStatus, UnknownError,
 };
 use crate::group::Group;
 
@@ -53,10 +54,18 @@ pub enum UserError {
     AuthRequired,
     #[error("Password is incorrect.")]
     AuthPasswordInvalid,
+    #[error("A TFA security code is required to log in.")]
+    AuthTFARequired,
+    #[error("No active authentication strategy with the key {key}.")]
+    AuthenticationStrategyNotFound { key: String },
+    #[error("Invalid user import line {line}: {message}")]
+    InvalidImportRow { line: usize, message: String },
     #[error("Unknown response error code: {code}: {message}")]
     UnknownErrorCode { code: i64, message: String },
     #[error("Unknown response error: {message}")]
     UnknownErrorMessage { message: String },
+    #[error("Request failed: {message}")]
+    RequestFailed { message: String },
     #[error("Unknown response error.")]
     UnknownError,
 }
@@ -99,6 +108,9 @@ impl UnknownError for UserError {
     fn unknown_error_message(message: String) -> Self {
         UserError::UnknownErrorMessage { message }
     }
+    fn request_failed(message: String) -> Self {
+        UserError::RequestFailed { message }
+    }
     fn unknown_error() -> Self {
         UserError::UnknownError
     }
@@ -117,14 +129,14 @@ impl KnownErrorCodes for UserError {
     }
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct UserResponse {
     #[serde(rename = "responseResult")]
     pub response_result: ResponseStatus,
     pub user: Option<User>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct UserLastLogin {
     pub id: Int,
     pub name: String,
@@ -132,7 +144,7 @@ pub struct UserLastLogin {
     pub last_login_at: Date,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct UserMinimal {
     pub id: Int,
     pub name: String,
@@ -149,7 +161,7 @@ pub struct UserMinimal {
     pub last_login_at: Option<Date>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct User {
     pub id: Int,
     pub name: String,
@@ -186,7 +198,7 @@ pub struct User {
     pub groups: Vec<Option<Group>>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct UserProfile {
     pub id: Int,
     pub name: String,
@@ -217,7 +229,7 @@ pub struct UserProfile {
     pub pages_total: Int,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct UserTokenResponse {
     #[serde(rename = "responseResult")]
     pub response_result: ResponseStatus,
@@ -239,12 +251,12 @@ pub mod user_get {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub users: Option<Users>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Users {
         pub single: Option<User>,
     }
@@ -272,9 +284,7 @@ pub fn user_get(
     let variables = user_get::Variables { id };
     let response = post_graphql::<user_get::UserGet, _>(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -304,12 +314,12 @@ pub mod user_list {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub users: Option<Users>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Users {
         pub list: Option<Vec<Option<UserMinimal>>>,
     }
@@ -339,9 +349,7 @@ pub fn user_list(
     let response =
         post_graphql::<user_list::UserList, _>(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -369,17 +377,17 @@ pub mod user_activate {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub users: Option<Users>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Users {
         pub activate: Option<Activate>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Activate {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -409,9 +417,7 @@ pub fn user_activate(
     let response =
         post_graphql::<user_activate::UserActivate, _>(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -449,17 +455,17 @@ pub mod user_deactivate {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub users: Option<Users>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Users {
         pub deactivate: Option<Deactivate>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Deactivate {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -490,9 +496,7 @@ pub fn user_deactivate(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -533,17 +537,17 @@ pub mod user_delete {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub users: Option<Users>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Users {
         pub delete: Option<Delete>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Delete {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -574,9 +578,7 @@ pub fn user_delete(
     let response =
         post_graphql::<user_delete::UserDelete, _>(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -614,17 +616,17 @@ pub mod user_tfa_disable {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub users: Option<Users>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Users {
         #[serde(rename = "disableTFA")]
         pub disable_tfa: Option<DisableTfa>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct DisableTfa {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -655,9 +657,7 @@ pub fn user_tfa_disable(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -695,18 +695,18 @@ pub mod user_tfa_enable {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub users: Option<Users>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Users {
         #[serde(rename = "enableTFA")]
         pub enable_tfa: Option<EnableTfa>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct EnableTfa {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -737,9 +737,7 @@ pub fn user_tfa_enable(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -777,15 +775,15 @@ pub mod user_verify {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub users: Option<Users>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Users {
         pub verify: Option<Verify>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Verify {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -815,9 +813,7 @@ pub fn user_verify(
     let response =
         post_graphql::<user_verify::UserVerify, _>(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -855,12 +851,12 @@ pub mod user_search {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub users: Option<Users>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Users {
         pub search: Option<Vec<Option<UserMinimal>>>,
     }
@@ -889,9 +885,7 @@ pub fn user_search(
     let response =
         post_graphql::<user_search::UserSearch, _>(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -915,11 +909,11 @@ pub mod user_profile_get {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub users: Option<Users>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Users {
         pub profile: Option<UserProfile>,
     }
@@ -948,9 +942,7 @@ pub fn user_profile_get(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -974,12 +966,12 @@ pub mod user_last_login_list {
     #[derive(Serialize)]
     pub struct Variables;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub users: Option<Users>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Users {
         #[serde(rename = "lastLogins")]
         pub last_logins: Option<Vec<Option<UserLastLogin>>>,
@@ -1009,9 +1001,7 @@ pub fn user_last_login_list(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -1049,12 +1039,12 @@ pub mod user_create {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub users: Option<Users>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Users {
         pub create: Option<UserResponse>,
     }
@@ -1098,9 +1088,7 @@ pub fn user_create(
     let response =
         post_graphql::<user_create::UserCreate, _>(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
     if let Some(data) = response_body.data {
@@ -1147,17 +1135,17 @@ pub mod user_update {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub users: Option<Users>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Users {
         pub update: Option<Update>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Update {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -1208,9 +1196,7 @@ pub fn user_update(
     let response =
         post_graphql::<user_update::UserUpdate, _>(client, url, variables);
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
 
@@ -1256,12 +1242,12 @@ pub mod user_profile_update {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub users: Option<Users>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Users {
         #[serde(rename = "updateProfile")]
         pub update_profile: Option<UserTokenResponse>,
@@ -1305,9 +1291,7 @@ pub fn user_profile_update(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
 
@@ -1343,12 +1327,12 @@ pub mod user_password_change {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub users: Option<Users>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Users {
         #[serde(rename = "changePassword")]
         pub change_password: Option<UserTokenResponse>,
@@ -1380,9 +1364,7 @@ pub fn user_password_change(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
     let response_body = response.unwrap();
 
@@ -1417,17 +1399,17 @@ pub mod user_password_reset {
 
     impl Variables {}
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResponseData {
         pub users: Option<Users>,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct Users {
         #[serde(rename = "resetPassword")]
         pub reset_password: Option<ResetPassword>,
     }
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct ResetPassword {
         #[serde(rename = "responseResult")]
         pub response_result: Option<ResponseStatus>,
@@ -1458,9 +1440,7 @@ pub fn user_password_reset(
         client, url, variables,
     );
     if response.is_err() {
-        return Err(UserError::UnknownErrorMessage {
-            message: response.err().unwrap().to_string(),
-        });
+        return Err(classify_post_error(response.err().unwrap()));
     }
 
     let response_body = response.unwrap();
@@ -1484,3 +1464,254 @@ pub fn user_password_reset(
     }
     Err(classify_response_error::<UserError>(response_body.errors))
 }
+
+/// Settings for the users of a CSV import whose row does not set them.
+#[derive(Clone, Debug)]
+pub struct UserImportDefaults {
+    /// Groups every imported user is assigned to, in addition to the ones
+    /// of the row.
+    pub groups: Vec<Int>,
+    pub provider_key: String,
+    pub must_change_password: bool,
+    pub send_welcome_email: bool,
+    /// Only parse and validate the rows, do not create any user.
+    pub dry_run: bool,
+}
+
+impl Default for UserImportDefaults {
+    fn default() -> Self {
+        Self {
+            groups: Vec::new(),
+            provider_key: "local".to_string(),
+            must_change_password: false,
+            send_welcome_email: false,
+            dry_run: false,
+        }
+    }
+}
+
+/// A user to create, read from a row of the CSV file.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct UserImportRow {
+    /// Line of the row in the file, for error reports.
+    pub line: usize,
+    pub email: String,
+    pub name: String,
+    pub password: Option<String>,
+    pub provider_key: String,
+    pub groups: Vec<Int>,
+}
+
+/// The outcome of importing one row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserImportOutcome {
+    pub line: usize,
+    pub email: String,
+    pub result: Result<(), UserError>,
+}
+
+// split CSV text into records with the line they start on, supporting
+// quoted fields with escaped quotes and line breaks
+fn parse_csv(text: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|f| !f.is_empty()) {
+                    records.push((start, std::mem::take(&mut record)));
+                }
+                record.clear();
+                line += 1;
+                start = line;
+            }
+            _ => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    record.push(field);
+    if record.iter().any(|f| !f.is_empty()) {
+        records.push((start, record));
+    }
+    records
+}
+
+/// Parse the rows of a user CSV file.
+///
+/// The first row is a header naming the columns, `email` and `name` are
+/// required, `password`, `provider` and `groups` (group ids separated by
+/// `;`) are optional. Invalid rows are returned as errors so the other
+/// rows can still be imported.
+///
+/// # Arguments
+/// * `text` - The content of the CSV file.
+/// * `defaults` - Settings for rows that do not set them.
+pub fn parse_user_csv(
+    text: &str,
+    defaults: &UserImportDefaults,
+) -> Result<Vec<Result<UserImportRow, UserError>>, UserError> {
+    let mut records = parse_csv(text).into_iter();
+    let (line, header) = records.next().ok_or(UserError::InvalidImportRow {
+        line: 1,
+        message: "missing header".to_string(),
+    })?;
+    let header: Vec<String> =
+        header.iter().map(|h| h.trim().to_lowercase()).collect();
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let (Some(email_column), Some(name_column)) =
+        (column("email"), column("name"))
+    else {
+        return Err(UserError::InvalidImportRow {
+            line,
+            message: "header needs email and name columns".to_string(),
+        });
+    };
+    let password_column = column("password");
+    let provider_column = column("provider");
+    let groups_column = column("groups");
+
+    let mut emails: Vec<String> = Vec::new();
+    Ok(records
+        .map(|(line, record)| {
+            let error =
+                |message: String| UserError::InvalidImportRow { line, message };
+            let value = |column: Option<usize>| {
+                column
+                    .and_then(|c| record.get(c))
+                    .map(|v| v.trim().to_string())
+                    .filter(|v| !v.is_empty())
+            };
+            let email = value(Some(email_column))
+                .filter(|email| email.contains('@'))
+                .ok_or_else(|| error("missing or invalid email".to_string()))?;
+            if emails.contains(&email.to_lowercase()) {
+                return Err(error(format!("duplicate email {}", email)));
+            }
+            emails.push(email.to_lowercase());
+            let name = value(Some(name_column))
+                .ok_or_else(|| error("missing name".to_string()))?;
+            let provider_key = value(provider_column)
+                .unwrap_or_else(|| defaults.provider_key.clone());
+            let password = value(password_column);
+            if provider_key == "local" && password.is_none() {
+                return Err(error(
+                    "password is required for the local provider".to_string(),
+                ));
+            }
+            let mut groups = defaults.groups.clone();
+            for group in value(groups_column).iter().flat_map(|g| g.split(';'))
+            {
+                let group: Int = group.trim().parse().map_err(|_| {
+                    error(format!("invalid group id {}", group))
+                })?;
+                if !groups.contains(&group) {
+                    groups.push(group);
+                }
+            }
+            Ok(UserImportRow {
+                line,
+                email,
+                name,
+                password,
+                provider_key,
+                groups,
+            })
+        })
+        .collect())
+}
+
+/// Create users from a CSV file, see [`parse_user_csv`] for the format.
+///
+/// # Arguments
+/// * `reader` - The CSV file.
+/// * `defaults` - Settings for rows that do not set them.
+pub fn user_import_csv(
+    client: &Client,
+    url: &str,
+    mut reader: impl std::io::Read,
+    defaults: &UserImportDefaults,
+) -> Result<Vec<UserImportOutcome>, UserError> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text).map_err(|e| {
+        UserError::UnknownErrorMessage {
+            message: e.to_string(),
+        }
+    })?;
+    Ok(parse_user_csv(&text, defaults)?
+        .into_iter()
+        .map(|row| match row {
+            Ok(row) => UserImportOutcome {
+                line: row.line,
+                email: row.email.clone(),
+                result: match defaults.dry_run {
+                    true => Ok(()),
+                    false => user_create(
+                        client,
+                        url,
+                        row.email,
+                        row.name,
+                        row.password,
+                        row.provider_key,
+                        row.groups.into_iter().map(Some).collect(),
+                        Some(defaults.must_change_password),
+                        Some(defaults.send_welcome_email),
+                    ),
+                },
+            },
+            Err(e) => UserImportOutcome {
+                line: match e {
+                    UserError::InvalidImportRow { line, .. } => line,
+                    _ => 0,
+                },
+                email: String::new(),
+                result: Err(e),
+            },
+        })
+        .collect())
+}
+
+/// Whether a user counts as inactive: an active, non-system account that
+/// did not log in since the date, or never logged in and was created
+/// before it.
+pub fn user_is_inactive(user: &UserMinimal, since: DateTime<Utc>) -> bool {
+    let before = |date: &str| {
+        DateTime::parse_from_rfc3339(date).is_ok_and(|date| date < since)
+    };
+    user.is_active
+        && !user.is_system
+        && match &user.last_login_at {
+            Some(last_login_at) => before(last_login_at),
+            None => before(&user.created_at),
+        }
+}
+
+/// List the users who did not log in since a date, see
+/// [`user_is_inactive`].
+pub fn user_inactive_list(
+    client: &Client,
+    url: &str,
+    since: DateTime<Utc>,
+) -> Result<Vec<UserMinimal>, UserError> {
+    Ok(user_list(client, url, None, None)?
+        .into_iter()
+        .filter(|user| user_is_inactive(user, since))
+        .collect())
+}
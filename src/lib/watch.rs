@@ -0,0 +1,424 @@
+use reqwest::blocking::Client;
+use std::collections::{HashMap, VecDeque};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+use crate::comment::{comment_list, Comment, CommentError};
+use crate::common::{is_cancelled, ApiClient, CancellationToken, Int};
+use crate::page::{
+    page_list, PageError, PageListItem, PageOrderBy, PageOrderByDirection,
+};
+use crate::replace::PageFilter;
+
+// how long a cancelled watcher may keep sleeping
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum WatchError {
+    #[error(transparent)]
+    Page(#[from] PageError),
+    #[error(transparent)]
+    Comment(#[from] CommentError),
+}
+
+/// A change of a watched page or one of its comments.
+#[derive(Clone)]
+pub enum PageChangeEvent {
+    PageCreated(PageListItem),
+    PageUpdated(PageListItem),
+    /// Carries the page as it was last seen.
+    PageDeleted(PageListItem),
+    CommentCreated {
+        page: PageListItem,
+        comment: Comment,
+    },
+    CommentUpdated {
+        page: PageListItem,
+        comment: Comment,
+    },
+    /// Carries the comment as it was last seen.
+    CommentDeleted {
+        page: PageListItem,
+        comment: Comment,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum EventKey {
+    Page(Int),
+    Comment(Int),
+}
+
+impl PageChangeEvent {
+    /// The name of the event, e.g. `page_updated`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PageChangeEvent::PageCreated(_) => "page_created",
+            PageChangeEvent::PageUpdated(_) => "page_updated",
+            PageChangeEvent::PageDeleted(_) => "page_deleted",
+            PageChangeEvent::CommentCreated { .. } => "comment_created",
+            PageChangeEvent::CommentUpdated { .. } => "comment_updated",
+            PageChangeEvent::CommentDeleted { .. } => "comment_deleted",
+        }
+    }
+
+    /// The page that changed or whose comment changed.
+    pub fn page(&self) -> &PageListItem {
+        match self {
+            PageChangeEvent::PageCreated(page)
+            | PageChangeEvent::PageUpdated(page)
+            | PageChangeEvent::PageDeleted(page)
+            | PageChangeEvent::CommentCreated { page, .. }
+            | PageChangeEvent::CommentUpdated { page, .. }
+            | PageChangeEvent::CommentDeleted { page, .. } => page,
+        }
+    }
+
+    /// The comment that changed, if any.
+    pub fn comment(&self) -> Option<&Comment> {
+        match self {
+            PageChangeEvent::CommentCreated { comment, .. }
+            | PageChangeEvent::CommentUpdated { comment, .. }
+            | PageChangeEvent::CommentDeleted { comment, .. } => Some(comment),
+            _ => None,
+        }
+    }
+
+    fn key(&self) -> EventKey {
+        match self {
+            PageChangeEvent::PageCreated(page)
+            | PageChangeEvent::PageUpdated(page)
+            | PageChangeEvent::PageDeleted(page) => EventKey::Page(page.id),
+            PageChangeEvent::CommentCreated { comment, .. }
+            | PageChangeEvent::CommentUpdated { comment, .. }
+            | PageChangeEvent::CommentDeleted { comment, .. } => {
+                EventKey::Comment(comment.id)
+            }
+        }
+    }
+
+    fn is_created(&self) -> bool {
+        matches!(
+            self,
+            PageChangeEvent::PageCreated(_)
+                | PageChangeEvent::CommentCreated { .. }
+        )
+    }
+
+    fn is_deleted(&self) -> bool {
+        matches!(
+            self,
+            PageChangeEvent::PageDeleted(_)
+                | PageChangeEvent::CommentDeleted { .. }
+        )
+    }
+
+    /// Combine this event with a newer one of the same page or comment.
+    ///
+    /// A creation followed by a deletion cancels out, a creation followed
+    /// by an update stays a creation and a deletion followed by a creation
+    /// becomes an update. Otherwise the newer event wins.
+    ///
+    /// # Returns
+    /// The combined event or None if they cancel out.
+    pub fn merge(self, newer: PageChangeEvent) -> Option<PageChangeEvent> {
+        match (self.is_created(), newer.is_deleted()) {
+            (true, true) => None,
+            (true, false) => Some(match newer {
+                PageChangeEvent::PageUpdated(page) => {
+                    PageChangeEvent::PageCreated(page)
+                }
+                PageChangeEvent::CommentUpdated { page, comment } => {
+                    PageChangeEvent::CommentCreated { page, comment }
+                }
+                newer => newer,
+            }),
+            (false, _) => Some(match (self.is_deleted(), newer) {
+                // the item disappeared and came back in between
+                (true, PageChangeEvent::PageCreated(page)) => {
+                    PageChangeEvent::PageUpdated(page)
+                }
+                (true, PageChangeEvent::CommentCreated { page, comment }) => {
+                    PageChangeEvent::CommentUpdated { page, comment }
+                }
+                (_, newer) => newer,
+            }),
+        }
+    }
+}
+
+/// Holds back events until their page or comment has not changed for a
+/// while, merging the events of a burst into one, as done by
+/// [`ChangeWatcher::debounce`](struct.ChangeWatcher.html#method.debounce).
+#[derive(Clone)]
+pub struct EventDebouncer {
+    debounce: Duration,
+    pending: Vec<(PageChangeEvent, Instant)>,
+}
+
+impl EventDebouncer {
+    /// Create a debouncer holding back events for `debounce`.
+    pub fn new(debounce: Duration) -> Self {
+        Self {
+            debounce,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Add an event seen at `now`, merged with the pending event of the
+    /// same item if there is one.
+    pub fn push(&mut self, event: PageChangeEvent, now: Instant) {
+        let key = event.key();
+        match self.pending.iter().position(|(e, _)| e.key() == key) {
+            Some(index) => {
+                let (older, _) = self.pending.remove(index);
+                if let Some(event) = older.merge(event) {
+                    self.pending.push((event, now));
+                }
+            }
+            None => self.pending.push((event, now)),
+        }
+    }
+
+    /// Take the events whose item has not changed for the debounce time at
+    /// `now`, in the order they were last pushed.
+    pub fn release(&mut self, now: Instant) -> Vec<PageChangeEvent> {
+        let debounce = self.debounce;
+        let (settled, pending): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.pending)
+                .into_iter()
+                .partition(|(_, seen)| now.duration_since(*seen) >= debounce);
+        self.pending = pending;
+        settled.into_iter().map(|(event, _)| event).collect()
+    }
+}
+
+/// Iterator over the changes of the wiki, returned by
+/// [`Api::watch_changes`](../struct.Api.html#method.watch_changes).
+///
+/// Only changes made after the watcher was created are reported, and for
+/// comments only those after the first call to `next`. Each call to `next`
+/// blocks until there is an event. A failed poll yields the error and the
+/// next call simply polls again. Once the token passed to `watch_changes`
+/// is cancelled, `next` returns None, also while it waits for a poll.
+pub struct ChangeWatcher {
+    client: Client,
+    strict: bool,
+    url: String,
+    interval: Duration,
+    filter: PageFilter,
+    cancel: Option<CancellationToken>,
+    comments: bool,
+    pages: HashMap<Int, PageListItem>,
+    // None until the comments were first listed
+    page_comments: Option<HashMap<Int, HashMap<Int, Comment>>>,
+    debouncer: EventDebouncer,
+    ready: VecDeque<PageChangeEvent>,
+    last_poll: Option<Instant>,
+}
+
+impl ChangeWatcher {
+    pub(crate) fn new(
+        client: Client,
+        strict: bool,
+        url: String,
+        interval: Duration,
+        filter: PageFilter,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Self, WatchError> {
+        let mut watcher = Self {
+            client,
+            strict,
+            url,
+            interval,
+            filter,
+            cancel: cancel.cloned(),
+            comments: false,
+            pages: HashMap::new(),
+            page_comments: None,
+            debouncer: EventDebouncer::new(Duration::ZERO),
+            ready: VecDeque::new(),
+            last_poll: None,
+        };
+        watcher.pages = watcher
+            .list_pages()?
+            .into_iter()
+            .map(|page| (page.id, page))
+            .collect();
+        watcher.last_poll = Some(Instant::now());
+        Ok(watcher)
+    }
+
+    fn client(&self) -> ApiClient<'_> {
+        ApiClient::new(&self.client, self.strict)
+    }
+
+    // oldest change first, so events come in the order they happened
+    fn list_pages(&self) -> Result<Vec<PageListItem>, PageError> {
+        Ok(page_list(
+            &self.client(),
+            &self.url,
+            None,
+            Some(PageOrderBy::UPDATED),
+            Some(PageOrderByDirection::ASC),
+            self.filter.tags.clone(),
+            self.filter.locale.clone(),
+            None,
+            None,
+        )?
+        .into_iter()
+        .filter(|page| {
+            self.filter
+                .path_prefix
+                .as_ref()
+                .is_none_or(|prefix| page.path.starts_with(prefix.as_str()))
+        })
+        .collect())
+    }
+
+    /// Hold back the events of an item until it has not changed for this
+    /// long, so a burst of edits yields a single event. Zero by default.
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debouncer = EventDebouncer::new(debounce);
+        self
+    }
+
+    /// Also report the comments of the watched pages. This lists the
+    /// comments of every page on each poll, so it is off by default.
+    pub fn comments(mut self, comments: bool) -> Self {
+        self.comments = comments;
+        self
+    }
+
+    fn poll_comments(
+        &mut self,
+        pages: &HashMap<Int, PageListItem>,
+        now: Instant,
+    ) -> Result<(), CommentError> {
+        let mut all = HashMap::new();
+        for page in pages.values() {
+            let comments = comment_list(
+                &self.client(),
+                &self.url,
+                page.locale.clone(),
+                page.path.clone(),
+            )?;
+            all.insert(
+                page.id,
+                comments.into_iter().map(|c| (c.id, c)).collect(),
+            );
+        }
+        let Some(old) = self.page_comments.replace(all) else {
+            return Ok(());
+        };
+        let current = self.page_comments.as_ref().unwrap();
+        let mut events = Vec::new();
+        for (page_id, comments) in current {
+            let page = &pages[page_id];
+            let before = old.get(page_id);
+            for comment in comments.values() {
+                match before.and_then(|b| b.get(&comment.id)) {
+                    None => events.push(PageChangeEvent::CommentCreated {
+                        page: page.clone(),
+                        comment: comment.clone(),
+                    }),
+                    Some(b) if b.updated_at != comment.updated_at => events
+                        .push(PageChangeEvent::CommentUpdated {
+                            page: page.clone(),
+                            comment: comment.clone(),
+                        }),
+                    Some(_) => {}
+                }
+            }
+        }
+        for (page_id, comments) in &old {
+            // comments of deleted pages go with the page
+            let Some(page) = pages.get(page_id) else {
+                continue;
+            };
+            let now_comments = current.get(page_id);
+            for comment in comments.values() {
+                if now_comments.is_none_or(|c| !c.contains_key(&comment.id)) {
+                    events.push(PageChangeEvent::CommentDeleted {
+                        page: page.clone(),
+                        comment: comment.clone(),
+                    });
+                }
+            }
+        }
+        for event in events {
+            self.debouncer.push(event, now);
+        }
+        Ok(())
+    }
+
+    fn poll(&mut self) -> Result<(), WatchError> {
+        let now = Instant::now();
+        let list = self.list_pages()?;
+        let mut events = Vec::new();
+        for page in &list {
+            match self.pages.get(&page.id) {
+                None => events.push(PageChangeEvent::PageCreated(page.clone())),
+                Some(old) if old.updated_at != page.updated_at => {
+                    events.push(PageChangeEvent::PageUpdated(page.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+        for page in self.pages.values() {
+            if !list.iter().any(|p| p.id == page.id) {
+                events.push(PageChangeEvent::PageDeleted(page.clone()));
+            }
+        }
+        for event in events {
+            self.debouncer.push(event, now);
+        }
+        self.pages = list.into_iter().map(|page| (page.id, page)).collect();
+        if self.comments {
+            let pages = self.pages.clone();
+            self.poll_comments(&pages, now)?;
+        }
+        Ok(())
+    }
+
+    // sleep until the next poll is due, None if cancelled meanwhile
+    fn wait(&self) -> Option<()> {
+        let cancel = self.cancel.as_ref();
+        if let Some(last) = self.last_poll {
+            loop {
+                let left = self.interval.saturating_sub(last.elapsed());
+                if left.is_zero() {
+                    break;
+                }
+                if is_cancelled(cancel) {
+                    return None;
+                }
+                sleep(left.min(CANCEL_CHECK_INTERVAL));
+            }
+        }
+        (!is_cancelled(cancel)).then_some(())
+    }
+}
+
+impl Iterator for ChangeWatcher {
+    type Item = Result<PageChangeEvent, WatchError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if is_cancelled(self.cancel.as_ref()) {
+                return None;
+            }
+            if let Some(event) = self.ready.pop_front() {
+                return Some(Ok(event));
+            }
+            self.wait()?;
+            self.last_poll = Some(Instant::now());
+            let result = self.poll();
+            self.ready.extend(self.debouncer.release(Instant::now()));
+            if let Err(e) = result {
+                return Some(Err(e));
+            }
+        }
+    }
+}
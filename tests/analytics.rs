@@ -0,0 +1,32 @@
+use wikijs::analytics::{
+    AnalyticsProviderConfig, GoogleAnalyticsConfig, MatomoConfig, UmamiConfig,
+};
+
+#[test]
+fn analytics_provider_configs() {
+    let input = GoogleAnalyticsConfig::new("G-12345").to_provider_input();
+    assert_eq!(input.key, "google");
+    assert!(input.is_enabled);
+    let config: Vec<(String, String)> = input
+        .config
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|p| (p.key, p.value))
+        .collect();
+    assert_eq!(
+        config,
+        vec![(
+            "propertyTrackingId".to_string(),
+            r#"{"v":"G-12345"}"#.to_string()
+        )]
+    );
+    assert_eq!(
+        MatomoConfig::new(3, "https://matomo.example.com").options()[0],
+        ("siteId", 3.into())
+    );
+    assert_eq!(
+        UmamiConfig::new("id", "https://umami.example.com").key(),
+        "umami"
+    );
+}
@@ -11,3 +11,75 @@ fn asset_list_empty() {
     assert!(result.is_ok());
     assert_eq!(result.unwrap().len(), 0);
 }
+
+#[test]
+#[serial]
+fn asset_list_recursive_empty() {
+    let result = API.asset_list_recursive(0, wikijs::asset::AssetKind::ALL);
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap().len(), 0);
+}
+
+#[test]
+fn asset_folder_tree_resolve() {
+    use wikijs::asset::{AssetFolder, AssetFolderTree};
+    let folder = |id: i64, slug: &str| AssetFolder {
+        id,
+        slug: slug.to_string(),
+        name: None,
+    };
+    let tree = AssetFolderTree::new(vec![
+        (0, folder(1, "images")),
+        (1, folder(2, "screenshots")),
+        (0, folder(3, "docs")),
+    ]);
+    assert_eq!(tree.resolve(""), Some(0));
+    assert_eq!(tree.resolve("images/screenshots"), Some(2));
+    assert_eq!(tree.resolve("/docs/"), Some(3));
+    assert_eq!(tree.resolve("images/missing"), None);
+    assert_eq!(tree.path(2), Some("images/screenshots".to_string()));
+    assert_eq!(tree.path(0), Some(String::new()));
+    assert_eq!(tree.path(42), None);
+}
+
+#[test]
+#[serial]
+fn asset_orphan_report_empty() {
+    let result = API.asset_orphan_report(None);
+    assert!(result.is_ok());
+    assert!(result.unwrap().orphans().is_empty());
+}
+
+#[test]
+#[serial]
+fn asset_move_to_folder() {
+    use wikijs::asset::AssetKind;
+    let data = b"asset move test".to_vec();
+    API.asset_folder_create(0, "asset-move-test".to_string(), None)
+        .unwrap();
+    let folder = API
+        .asset_folder_tree()
+        .unwrap()
+        .resolve("asset-move-test")
+        .unwrap();
+    API.asset_upload(0, "asset-move-test.txt".to_string(), data.clone())
+        .unwrap();
+    let id = API
+        .asset_list(0, AssetKind::ALL)
+        .unwrap()
+        .into_iter()
+        .find(|asset| asset.filename == "asset-move-test.txt")
+        .unwrap()
+        .id;
+
+    let moved = API.asset_move(id, folder).unwrap();
+    let root = API.asset_list(0, AssetKind::ALL).unwrap();
+    let moved_list = API.asset_list(folder, AssetKind::ALL).unwrap();
+    let downloaded =
+        API.asset_download("asset-move-test/asset-move-test.txt".to_string());
+    API.asset_delete(moved).unwrap();
+
+    assert!(root.iter().all(|asset| asset.id != id));
+    assert!(moved_list.iter().any(|asset| asset.id == moved));
+    assert_eq!(downloaded.unwrap(), data);
+}
@@ -0,0 +1,89 @@
+use std::time::{Duration, UNIX_EPOCH};
+use wikijs::authentication::{
+    totp_code, ApiKey, AuthenticationStrategyConfig, GitHubConfig,
+    KeycloakConfig, LdapConfig, OidcConfig, SamlConfig,
+};
+
+#[test]
+fn api_key_expires_before() {
+    let key = ApiKey {
+        id: 1,
+        name: "ci".to_string(),
+        key_short: "abc".to_string(),
+        expiration: "2024-06-01T00:00:00.000Z".to_string(),
+        created_at: "2023-06-01T00:00:00.000Z".to_string(),
+        updated_at: "2023-06-01T00:00:00.000Z".to_string(),
+        is_revoked: false,
+    };
+    assert!(key.expires_before("2024-07-01T00:00:00Z".parse().unwrap()));
+    assert!(!key.expires_before("2024-05-01T00:00:00Z".parse().unwrap()));
+    let broken = ApiKey {
+        expiration: "never".to_string(),
+        ..key
+    };
+    assert!(broken.expires_before("2024-05-01T00:00:00Z".parse().unwrap()));
+}
+
+#[test]
+fn authentication_strategy_configs() {
+    let input = KeycloakConfig::new(
+        "https://auth.example.com/",
+        "wiki",
+        "wikijs",
+        "secret",
+    )
+    .logout_upstream(true)
+    .to_strategy_input("Keycloak");
+    assert_eq!(input.strategy_key, "keycloak");
+    assert_eq!(input.display_name, "Keycloak");
+    assert!(input.is_enabled);
+    let config: Vec<(String, String)> = input
+        .config
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|p| (p.key, p.value))
+        .collect();
+    assert!(config.contains(&(
+        "tokenURL".to_string(),
+        r#"{"v":"https://auth.example.com/realms/wiki/protocol/openid-connect/token"}"#
+            .to_string()
+    )));
+    assert!(config.contains(&(
+        "logoutUpstream".to_string(),
+        r#"{"v":true}"#.to_string()
+    )));
+
+    let ldap = LdapConfig::new(
+        "ldap://ldap.example.com",
+        "cn=admin",
+        "pw",
+        "ou=users",
+    )
+    .map_groups("ou=groups", "(member={{dn}})", "cn");
+    assert!(ldap.options().contains(&("mapGroups", true.into())));
+    assert_eq!(
+        GitHubConfig::new("id", "secret").options()[2],
+        ("useEnterprise", false.into())
+    );
+    assert_eq!(
+        SamlConfig::new("https://idp", "wiki", "cert").strategy_key(),
+        "saml"
+    );
+    let oidc = OidcConfig::new("id", "secret", "iss", "auth", "token", "info");
+    assert_ne!(
+        oidc.to_strategy_input("A").key,
+        oidc.to_strategy_input("B").key
+    );
+}
+
+#[test]
+fn totp_code_rfc6238() {
+    // the SHA-1 test vectors of RFC 6238, truncated to 6 digits
+    let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+    let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+    assert_eq!(totp_code(secret, at(59)).as_deref(), Some("287082"));
+    assert_eq!(totp_code(secret, at(1111111109)).as_deref(), Some("081804"));
+    assert_eq!(totp_code(secret, at(2000000000)).as_deref(), Some("279037"));
+    assert_eq!(totp_code("not base32!", at(59)), None);
+}
@@ -0,0 +1,122 @@
+use std::path::Path;
+use wikijs::backup::{
+    backup_create, backup_restore, page_file_path, BackupError, BackupFormat,
+    BackupOptions, RestoreOptions,
+};
+use wikijs::page::{
+    parse_frontmatter_markdown, Page, PageEditor, PageFrontMatter,
+};
+
+fn page() -> Page {
+    Page {
+        id: 7,
+        path: "docs/intro".to_string(),
+        hash: "".to_string(),
+        title: "Intro: \"quoted\"".to_string(),
+        description: "".to_string(),
+        is_private: false,
+        is_published: true,
+        private_ns: None,
+        publish_start_date: "".to_string(),
+        publish_end_date: "".to_string(),
+        tags: vec![],
+        content: "# Intro\n".to_string(),
+        toc: None,
+        render: None,
+        content_type: "markdown".to_string(),
+        created_at: "2024-01-01T00:00:00.000Z".to_string(),
+        updated_at: "2024-01-02T00:00:00.000Z".to_string(),
+        editor: PageEditor::Markdown,
+        locale: "en".to_string(),
+        script_css: None,
+        script_js: None,
+        author_id: 1,
+        author_name: "Admin".to_string(),
+        author_email: "admin@example.com".to_string(),
+        creator_id: 1,
+        creator_name: "Admin".to_string(),
+        creator_email: "admin@example.com".to_string(),
+    }
+}
+
+#[test]
+fn backup_page_file() {
+    let page = page();
+    assert_eq!(page_file_path(&page), "pages/en/docs/intro.md");
+    let (front_matter, content) =
+        parse_frontmatter_markdown(&page.to_markdown_with_frontmatter())
+            .unwrap();
+    assert_eq!(content, "# Intro\n");
+    assert_eq!(front_matter, PageFrontMatter::from(&page));
+}
+
+#[test]
+fn backup_format_from_path() {
+    assert_eq!(
+        BackupFormat::from_path(Path::new("wiki.ZIP")),
+        BackupFormat::Zip
+    );
+    assert_eq!(
+        BackupFormat::from_path(Path::new("wiki.tar.gz")),
+        BackupFormat::TarGz
+    );
+}
+
+#[test]
+fn backup_restore_invalid() {
+    let client = reqwest::blocking::Client::new();
+    let options = RestoreOptions {
+        dry_run: true,
+        skip_assets: true,
+    };
+    let dir = std::env::temp_dir()
+        .join(format!("wikijs-backup-restore-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let result = backup_restore(
+        &client,
+        "http://localhost/graphql",
+        &dir.join("missing.tar.gz"),
+        &options,
+        None,
+    );
+    assert!(matches!(result, Err(BackupError::Io(_))));
+
+    let path = dir.join("invalid.zip");
+    std::fs::write(&path, "not a zip archive").unwrap();
+    let result = backup_restore(
+        &client,
+        "http://localhost/graphql",
+        &path,
+        &options,
+        None,
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(matches!(result, Err(BackupError::Invalid(_))));
+}
+
+#[test]
+fn backup_create_failed_keeps_archive() {
+    let client = reqwest::blocking::Client::new();
+    let dir = std::env::temp_dir()
+        .join(format!("wikijs-backup-create-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("backup.tar.gz");
+    std::fs::write(&path, "previous backup").unwrap();
+
+    // nothing listens on port 1, so listing the pages fails
+    let result = backup_create(
+        &client,
+        "http://localhost:1/graphql",
+        &path,
+        BackupFormat::TarGz,
+        &BackupOptions::default(),
+        None,
+    );
+    let content = std::fs::read_to_string(&path).unwrap();
+    let partial = dir.join(".backup.tar.gz.partial").exists();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(result.is_err());
+    assert_eq!(content, "previous backup");
+    assert!(!partial);
+}
@@ -0,0 +1,86 @@
+use serial_test::serial;
+use wikijs::cache::Cache;
+use wikijs::page::{Page, PageEditor, PageError};
+use wikijs::{Api, Credentials};
+
+fn cached_api() -> Api {
+    Api::new(
+        "http://localhost".to_string(),
+        Credentials::UsernamePassword(
+            "admin@admin.com".to_string(),
+            "password".to_string(),
+            "local".to_string(),
+        ),
+    )
+    .unwrap_or_else(|e| panic!("Error creating API: {}", e))
+    .with_cache(Cache::new(16))
+}
+
+#[test]
+#[serial]
+fn cache_page_get_nonexistent() {
+    let api = cached_api();
+    for _ in 0..2 {
+        let result = api.page_get(1000000);
+        assert_eq!(result.unwrap_err(), PageError::PageNotFound);
+    }
+}
+
+#[test]
+fn cache_offline_empty() {
+    let cache = Cache::new(16).dir(std::env::temp_dir().join("wikijs-empty"));
+    assert!(cache.page(1).is_none());
+    assert!(cache.pages().is_empty());
+    assert!(cache.search("qwerty").is_empty());
+}
+
+// the file names of the entries must not change with the Rust release, or
+// a cache directory would be unreadable after an upgrade
+#[test]
+fn cache_offline_stable_file_names() {
+    let dir = std::env::temp_dir().join("wikijs-stable-names");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let page = Page {
+        id: 1,
+        path: "home".to_string(),
+        hash: String::new(),
+        title: "Home".to_string(),
+        description: String::new(),
+        is_private: false,
+        is_published: true,
+        private_ns: None,
+        publish_start_date: String::new(),
+        publish_end_date: String::new(),
+        tags: Vec::new(),
+        content: "# Home".to_string(),
+        toc: None,
+        render: None,
+        content_type: "markdown".to_string(),
+        created_at: String::new(),
+        updated_at: String::new(),
+        editor: PageEditor::Markdown,
+        locale: "en".to_string(),
+        script_css: None,
+        script_js: None,
+        author_id: 1,
+        author_name: String::new(),
+        author_email: String::new(),
+        creator_id: 1,
+        creator_name: String::new(),
+        creator_email: String::new(),
+    };
+    let entry = serde_json::json!({
+        "key": "page/1",
+        "entry": {"Page": {"page": page, "fetched_at": 0}},
+    });
+    // FNV-1a of the key
+    std::fs::write(
+        dir.join("dc07b07998a29e9e.json"),
+        serde_json::to_vec(&entry).unwrap(),
+    )
+    .unwrap();
+    let cache = Cache::new(16).dir(&dir);
+    assert_eq!(cache.page(1).unwrap().page.title, "Home");
+    assert_eq!(cache.pages().len(), 1);
+}
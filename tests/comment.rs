@@ -0,0 +1,38 @@
+use wikijs::comment::{Comment, CommentTree};
+
+fn comment(id: i64, content: &str) -> Comment {
+    Comment {
+        id,
+        content: content.to_string(),
+        render: String::new(),
+        author_id: 1,
+        author_name: "Administrator".to_string(),
+        author_email: String::new(),
+        author_ip: String::new(),
+        created_at: format!("2024-05-0{}T08:00:00Z", id),
+        updated_at: format!("2024-05-0{}T08:00:00Z", id),
+    }
+}
+
+#[test]
+fn comment_tree_build() {
+    let comments = vec![
+        comment(1, "first"),
+        comment(2, "<!-- reply-to:1 -->\nreply"),
+        comment(3, "second"),
+        comment(4, "<!-- reply-to:2 -->\nnested"),
+        comment(5, "<!-- reply-to:9 -->\norphan"),
+    ];
+    assert_eq!(comments[1].reply_to(), Some(1));
+    assert_eq!(comments[1].text(), "reply");
+    let threads = CommentTree::build(comments);
+    assert_eq!(threads.len(), 3);
+    assert_eq!(threads[0].count(), 3);
+    let flat: Vec<(usize, i64)> = threads[0]
+        .flatten()
+        .into_iter()
+        .map(|(depth, c)| (depth, c.id))
+        .collect();
+    assert_eq!(flat, vec![(0, 1), (1, 2), (2, 4)]);
+    assert_eq!(threads[2].comment.id, 5);
+}
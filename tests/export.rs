@@ -0,0 +1,25 @@
+use wikijs::export::relative_link;
+
+#[test]
+fn export_relative_link() {
+    assert_eq!(relative_link("index.html", "en/home.html"), "en/home.html");
+    assert_eq!(relative_link("en/home.html", "index.html"), "../index.html");
+    assert_eq!(relative_link("en/a.html", "en/b.html"), "b.html");
+    assert_eq!(
+        relative_link("en/docs/intro.html", "assets/img/logo.png"),
+        "../../assets/img/logo.png"
+    );
+    assert_eq!(
+        relative_link("en/docs/intro.html", "en/docs/setup/linux.html"),
+        "setup/linux.html"
+    );
+}
+
+#[test]
+fn export_vault_format_from_str() {
+    use wikijs::export::VaultFormat;
+    assert_eq!("hugo".parse::<VaultFormat>(), Ok(VaultFormat::Hugo));
+    assert_eq!("Obsidian".parse::<VaultFormat>(), Ok(VaultFormat::Obsidian));
+    assert!("jekyll".parse::<VaultFormat>().is_err());
+    assert_eq!(VaultFormat::Hugo.to_string(), "hugo");
+}
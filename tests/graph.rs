@@ -0,0 +1,32 @@
+use wikijs::graph::{GraphFormat, PageGraph};
+use wikijs::page::PageLinkItem;
+
+fn link_item(id: i64, path: &str, links: &[&str]) -> PageLinkItem {
+    PageLinkItem {
+        id,
+        path: path.to_string(),
+        title: format!("\"{}\"", path),
+        links: links.iter().map(|link| Some(link.to_string())).collect(),
+    }
+}
+
+#[test]
+fn page_graph_from_links() {
+    let graph = PageGraph::from_links(
+        vec![
+            link_item(1, "home", &["en/docs", "en/missing"]),
+            link_item(2, "docs", &["en/home", "en/home"]),
+        ],
+        "en",
+    );
+    assert_eq!(graph.nodes.len(), 2);
+    assert_eq!(graph.edges, vec![(1, 2), (2, 1)]);
+    assert_eq!(graph.dangling, vec![(1, "en/missing".to_string())]);
+    let dot = graph.render(GraphFormat::Dot);
+    assert!(dot.starts_with("digraph wiki {\n"));
+    assert!(dot.contains("  1 [label=\"home\", tooltip=\"\\\"home\\\"\"];\n"));
+    assert!(dot.contains("  1 -> 2;\n"));
+    let graphml = graph.render(GraphFormat::GraphMl);
+    assert!(graphml.contains("<data key=\"title\">&quot;docs&quot;</data>"));
+    assert!(graphml.contains("<edge source=\"n2\" target=\"n1\"/>"));
+}
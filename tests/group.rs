@@ -0,0 +1,81 @@
+use wikijs::group::{
+    membership_changes, GroupError, MembershipChange, PageRuleBuilder,
+    PageRuleMatch, Permission,
+};
+use wikijs::user::UserMinimal;
+
+#[test]
+fn permission_strings() {
+    for permission in Permission::ALL {
+        assert_eq!(permission.as_str().parse(), Ok(permission));
+    }
+    assert_eq!(Permission::ManageSystem.to_string(), "manage:system");
+    assert!("read:everything".parse::<Permission>().is_err());
+}
+
+#[test]
+fn page_rule_builder() {
+    let rule = PageRuleBuilder::new("docs").build();
+    assert_eq!(rule.r#match, PageRuleMatch::START);
+    assert!(!rule.deny);
+    assert_eq!(rule.roles, vec!["read:pages"]);
+    assert!(rule.locales.is_empty());
+
+    let rule = PageRuleBuilder::new("internal")
+        .r#match(PageRuleMatch::EXACT)
+        .deny(true)
+        .roles([Permission::ReadPages, Permission::WritePages])
+        .role(Permission::ReadPages)
+        .locale("de")
+        .build();
+    assert!(rule.deny);
+    assert_eq!(rule.roles, vec!["read:pages", "write:pages"]);
+    assert_eq!(rule.locales, vec!["de"]);
+    assert_ne!(rule.id, PageRuleBuilder::new("internal").build().id);
+}
+
+fn user(id: i64, email: &str) -> UserMinimal {
+    UserMinimal {
+        id,
+        name: email.to_string(),
+        email: email.to_string(),
+        provider_key: "local".to_string(),
+        is_system: false,
+        is_active: true,
+        created_at: "2024-01-01T00:00:00Z".to_string(),
+        last_login_at: None,
+    }
+}
+
+#[test]
+fn group_membership_changes() {
+    let users = vec![
+        user(1, "alice@example.com"),
+        user(2, "bob@example.com"),
+        user(3, "carol@example.com"),
+    ];
+    let members = vec![users[0].clone(), users[1].clone()];
+    let desired = vec![
+        "Alice@example.com".to_string(),
+        "carol@example.com".to_string(),
+    ];
+    assert_eq!(
+        membership_changes(&members, &users, &desired),
+        Ok(vec![
+            MembershipChange::Assigned {
+                user_id: 3,
+                email: "carol@example.com".to_string()
+            },
+            MembershipChange::Unassigned {
+                user_id: 2,
+                email: "bob@example.com".to_string()
+            },
+        ])
+    );
+    assert_eq!(
+        membership_changes(&members, &users, &["dave@example.com".into()]),
+        Err(GroupError::UserNotFound {
+            email: "dave@example.com".to_string()
+        })
+    );
+}
@@ -0,0 +1,131 @@
+use wikijs::import::{import_path, ImportCollision};
+use wikijs::{Api, Credentials};
+
+#[test]
+fn import_path_from_file() {
+    assert_eq!(import_path("docs/intro.md"), "docs/intro");
+    assert_eq!(import_path("docs/README.md"), "docs");
+    assert_eq!(import_path("docs/setup/index.md"), "docs/setup");
+    assert_eq!(import_path("index.md"), "home");
+    assert_eq!(import_path("README.md"), "home");
+}
+
+#[test]
+fn import_collision_from_str() {
+    assert_eq!("skip".parse(), Ok(ImportCollision::Skip));
+    assert_eq!("Overwrite".parse(), Ok(ImportCollision::Overwrite));
+    assert_eq!("update".parse(), Ok(ImportCollision::Update));
+    assert!("merge".parse::<ImportCollision>().is_err());
+}
+
+const DUMP: &str = r#"<mediawiki xmlns="http://www.mediawiki.org/xml/export-0.10/">
+  <siteinfo>
+    <namespaces>
+      <namespace key="0" case="first-letter" />
+      <namespace key="1" case="first-letter">Talk</namespace>
+      <namespace key="6" case="first-letter">File</namespace>
+      <namespace key="12" case="first-letter">Help</namespace>
+      <namespace key="14" case="first-letter">Category</namespace>
+    </namespaces>
+  </siteinfo>
+  <page>
+    <title>Help:Getting started</title>
+    <ns>12</ns>
+    <revision><text>old</text></revision>
+    <revision>
+      <text xml:space="preserve">== Intro ==
+'''Bold''' and ''italic'' with [[Main Page|home]] &amp; [https://example.com site].
+* one
+** two
+# first
+[[File:Logo.png|thumb|The logo]]
+{{Note|text}}
+[[Category:Guides]]</text>
+    </revision>
+  </page>
+</mediawiki>
+"#;
+
+#[test]
+fn import_mediawiki_convert() {
+    use wikijs::import::mediawiki::{parse_dump, wikitext_to_markdown};
+    let dump = parse_dump(DUMP).unwrap();
+    assert_eq!(dump.namespaces.get(&12).map(String::as_str), Some("Help"));
+    assert_eq!(dump.pages.len(), 1);
+    let page = &dump.pages[0];
+    assert_eq!(page.namespace, 12);
+    assert_eq!(dump.path(&page.title), "help/getting-started");
+
+    let conversion = wikitext_to_markdown(&dump, &page.text);
+    assert_eq!(
+        conversion.markdown,
+        "## Intro\n\
+         **Bold** and *italic* with [home](/main-page) & \
+         [site](https://example.com).\n\
+         - one\n  \
+         - two\n\
+         1. first\n\
+         ![The logo](/Logo.png)\n\
+         {{Note|text}}\n"
+    );
+    assert_eq!(conversion.categories, vec!["Guides"]);
+    assert!(conversion.unconverted.contains("template"));
+}
+
+#[test]
+fn import_dir_parallel_keeps_order() {
+    use wikijs::common::with_concurrency;
+    use wikijs::import::{import_dir, ImportOptions};
+
+    let dir = std::env::temp_dir().join("wikijs-import-parallel");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    // invalid front matter fails before the wiki is asked
+    for i in 0..6 {
+        std::fs::write(dir.join(format!("{}.md", i)), "---\ntitle: [\n---\n")
+            .unwrap();
+    }
+    let mut done = Vec::new();
+    let outcomes = with_concurrency(3, || {
+        import_dir(
+            &reqwest::blocking::Client::new(),
+            "http://localhost/graphql",
+            &dir,
+            &ImportOptions {
+                locale: "en".to_string(),
+                collision: ImportCollision::Skip,
+                dry_run: false,
+            },
+            |finished, total, _| done.push((finished, total)),
+            None,
+        )
+    })
+    .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let files: Vec<&str> = outcomes.iter().map(|o| o.file.as_str()).collect();
+    assert_eq!(files, ["0.md", "1.md", "2.md", "3.md", "4.md", "5.md"]);
+    assert!(outcomes.iter().all(|o| o.result.is_err()));
+    assert_eq!(done, (1..=6).map(|i| (i, 6)).collect::<Vec<_>>());
+}
+
+#[test]
+fn concurrency_per_api() {
+    use wikijs::common::{concurrency, with_concurrency, DEFAULT_CONCURRENCY};
+
+    let api = |key: &str| {
+        Api::new(
+            "http://localhost".to_string(),
+            Credentials::Key(key.to_string()),
+        )
+        .unwrap()
+    };
+    let sequential = api("a").with_concurrency(0);
+    let parallel = api("b").with_concurrency(8);
+    assert_eq!(sequential.concurrency(), 1);
+    assert_eq!(parallel.concurrency(), 8);
+    assert_eq!(api("c").concurrency(), DEFAULT_CONCURRENCY);
+
+    assert_eq!(with_concurrency(2, concurrency), 2);
+    assert_eq!(concurrency(), DEFAULT_CONCURRENCY);
+}
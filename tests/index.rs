@@ -0,0 +1,79 @@
+#![cfg(feature = "index")]
+
+use serial_test::serial;
+use wikijs::index::{IndexError, SearchIndex};
+use wikijs::page::PageEditor;
+use wikijs::{Api, Credentials};
+
+fn indexed_api(index: SearchIndex) -> Api {
+    Api::new(
+        "http://localhost".to_string(),
+        Credentials::UsernamePassword(
+            "admin@admin.com".to_string(),
+            "password".to_string(),
+            "local".to_string(),
+        ),
+    )
+    .unwrap_or_else(|e| panic!("Error creating API: {}", e))
+    .with_index(index)
+}
+
+#[test]
+fn index_offline_empty() {
+    let index =
+        SearchIndex::open(std::env::temp_dir().join("wikijs-empty-index"))
+            .unwrap();
+    assert!(index.is_empty());
+    assert!(index.search("qwerty", 10).unwrap().is_empty());
+}
+
+#[test]
+#[serial]
+fn index_missing() {
+    let api = indexed_api(SearchIndex::new());
+    assert!(api.local_search("qwerty", 10).unwrap().is_empty());
+    let api = Api::new(
+        "http://localhost".to_string(),
+        Credentials::Key("qwerty".to_string()),
+    )
+    .unwrap();
+    assert_eq!(
+        api.local_search("qwerty", 10).unwrap_err(),
+        IndexError::NoIndex
+    );
+}
+
+#[test]
+#[serial]
+fn index_update_search() {
+    let api = indexed_api(SearchIndex::new());
+    api.page_create(
+        "how to install the flux capacitor".to_string(),
+        "".to_string(),
+        PageEditor::Markdown,
+        true,
+        false,
+        "en".to_string(),
+        "index-test".to_string(),
+        None,
+        None,
+        None,
+        None,
+        Vec::new(),
+        "Flux capacitor".to_string(),
+    )
+    .unwrap();
+    let update = api.local_index_update().unwrap();
+    assert_eq!(update.added.len(), 1);
+    let hits = api.local_search("capacitor", 10).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].path, "index-test");
+
+    let update = api.local_index_update().unwrap();
+    assert!(update.added.is_empty() && update.updated.is_empty());
+
+    api.page_delete(hits[0].id).unwrap();
+    let update = api.local_index_update().unwrap();
+    assert_eq!(update.removed, vec![hits[0].id]);
+    assert!(api.local_search("capacitor", 10).unwrap().is_empty());
+}
@@ -0,0 +1,38 @@
+mod common;
+use common::API;
+
+use serial_test::serial;
+use wikijs::mirror::{MirrorAction, MirrorOptions};
+
+#[test]
+#[serial]
+fn mirror_plan_same_wiki() {
+    let options = MirrorOptions {
+        delete: true,
+        ..Default::default()
+    };
+    let plan = API.mirror_plan(&API, &options).unwrap();
+    assert!(plan.is_empty(), "{:?}", plan);
+}
+
+#[test]
+fn mirror_action_display() {
+    let action = MirrorAction::AssetUpload {
+        folder_path: "".to_string(),
+        filename: "logo.png".to_string(),
+    };
+    assert_eq!(action.to_string(), "upload asset logo.png");
+    let action = MirrorAction::PageDelete {
+        locale: "en".to_string(),
+        path: "docs/old".to_string(),
+        dest_id: 3,
+    };
+    assert_eq!(action.to_string(), "delete page en/docs/old");
+}
+
+#[test]
+#[serial]
+fn wiki_diff_same_wiki() {
+    let diff = API.wiki_diff(&API, &Default::default(), false).unwrap();
+    assert!(diff.is_empty(), "{:?}", diff);
+}
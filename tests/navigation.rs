@@ -0,0 +1,39 @@
+use wikijs::navigation::{
+    NavigationError, NavigationItemInput, NavigationTreeInput,
+};
+
+#[test]
+fn navigation_tree_input_edit() {
+    let mut tree = NavigationTreeInput {
+        locale: "en".to_string(),
+        items: Vec::new(),
+    };
+    let header = NavigationItemInput::header("Docs");
+    let link = NavigationItemInput::link("Intro", "page", "/docs/intro");
+    let divider = NavigationItemInput::divider();
+    let (header_id, link_id) = (header.id.clone(), link.id.clone());
+    assert_ne!(header_id, link_id);
+
+    tree.insert(None, link).unwrap();
+    tree.insert(Some(0), header).unwrap();
+    tree.insert(None, divider).unwrap();
+    tree.move_item(&link_id, 2).unwrap();
+    let kinds: Vec<&str> = tree
+        .items
+        .iter()
+        .flatten()
+        .map(|item| item.kind.as_str())
+        .collect();
+    assert_eq!(kinds, vec!["header", "divider", "link"]);
+
+    assert_eq!(tree.remove(&header_id).unwrap().kind, "header");
+    assert_eq!(
+        tree.remove(&header_id).unwrap_err(),
+        NavigationError::NavigationItemNotFound { id: header_id }
+    );
+    assert_eq!(
+        tree.insert(Some(5), NavigationItemInput::divider())
+            .unwrap_err(),
+        NavigationError::NavigationItemPositionOutOfRange { position: 5 }
+    );
+}
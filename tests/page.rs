@@ -2,7 +2,7 @@ mod common;
 use common::API;
 
 use serial_test::serial;
-use wikijs::page::{PageError, PageTreeMode};
+use wikijs::page::{PageDiff, PageEditor, PageError, PageMerge, PageTreeMode};
 
 #[test]
 #[serial]
@@ -12,6 +12,14 @@ fn page_get_nonexistent() {
     assert_eq!(result.unwrap_err() == PageError::PageNotFound, true);
 }
 
+#[test]
+#[serial]
+fn page_get_attributes_nonexistent() {
+    let result = API.page_get_attributes(1000000);
+    assert!(result.is_err());
+    assert_eq!(result.unwrap_err(), PageError::PageNotFound);
+}
+
 #[test]
 #[serial]
 fn page_get_by_path_nonexistent() {
@@ -67,7 +75,7 @@ fn page_create_get_delete() {
     let result = API.page_create(
         "...".to_string(),
         "".to_string(),
-        "markdown".to_string(),
+        PageEditor::Markdown,
         true,
         false,
         "en".to_string(),
@@ -95,7 +103,7 @@ fn page_update() {
     let result = API.page_create(
         "...".to_string(),
         "".to_string(),
-        "markdown".to_string(),
+        PageEditor::Markdown,
         true,
         false,
         "en".to_string(),
@@ -136,3 +144,348 @@ fn page_update() {
     let result5 = API.page_delete(id);
     assert!(result5.is_ok());
 }
+
+#[test]
+#[serial]
+fn page_get_rendered_nonexistent() {
+    let result = API.page_get_rendered(1000000);
+    assert!(result.is_err());
+    assert_eq!(result.unwrap_err(), PageError::PageNotFound);
+}
+
+#[test]
+fn page_diff_unified() {
+    let diff = PageDiff::new(
+        "test@1".to_string(),
+        "test@2".to_string(),
+        "a\nb\nc\n",
+        "a\nB\nc\nd\n",
+    );
+    assert_eq!(diff.insertions(), 2);
+    assert_eq!(diff.deletions(), 1);
+    assert_eq!(
+        diff.to_unified(),
+        "--- test@1\n+++ test@2\n@@ -1,3 +1,4 @@\n a\n-b\n+B\n c\n+d\n"
+    );
+    assert!(
+        PageDiff::new(String::new(), String::new(), "a\n", "a\n").is_empty()
+    );
+}
+
+#[test]
+fn page_merge() {
+    let merge = PageMerge::new(
+        "local",
+        "remote",
+        "a\nb\nc\nd\n",
+        "A\nb\nc\nd\n",
+        "a\nb\nc\nD\ne",
+    );
+    assert_eq!(merge.conflicts, 0);
+    assert_eq!(merge.content, "A\nb\nc\nD\ne");
+    let merge = PageMerge::new(
+        "local",
+        "remote",
+        "a\nb\nc\n",
+        "a\nB\nc\n",
+        "a\nX\nc\n",
+    );
+    assert_eq!(merge.conflicts, 1);
+    assert_eq!(
+        merge.content,
+        "a\n<<<<<<< local\nB\n=======\nX\n>>>>>>> remote\nc\n"
+    );
+    assert!(PageMerge::has_markers(&merge.content));
+}
+
+#[test]
+fn page_validate_path() {
+    use wikijs::page::validate_path;
+    assert!(validate_path("test").is_ok());
+    assert!(validate_path("/docs/getting-started").is_ok());
+    for path in [
+        "",
+        "/",
+        "a",
+        "en",
+        "en-us/docs",
+        "login",
+        "docs//x",
+        "docs/a.md",
+        "my page",
+        "docs?x",
+        "docs#x",
+        "docs\\x",
+    ] {
+        assert!(
+            matches!(
+                validate_path(path),
+                Err(PageError::PageIllegalPath { .. })
+            ),
+            "{:?} should be illegal",
+            path
+        );
+    }
+}
+
+#[test]
+fn page_parse_frontmatter_markdown() {
+    use wikijs::page::parse_frontmatter_markdown;
+    let (front_matter, content) = parse_frontmatter_markdown(
+        "---\ntitle: Intro\ntags: [a, b]\n---\n# Intro\n",
+    )
+    .unwrap();
+    assert_eq!(front_matter.title.as_deref(), Some("Intro"));
+    assert_eq!(
+        front_matter.tags,
+        Some(vec!["a".to_string(), "b".to_string()])
+    );
+    assert_eq!(front_matter.is_published, None);
+    assert_eq!(content, "# Intro\n");
+
+    let (front_matter, content) =
+        parse_frontmatter_markdown("---\n---\ntext").unwrap();
+    assert_eq!(front_matter, Default::default());
+    assert_eq!(content, "text");
+
+    let (_, content) = parse_frontmatter_markdown("# Intro\n").unwrap();
+    assert_eq!(content, "# Intro\n");
+
+    assert!(matches!(
+        parse_frontmatter_markdown("---\ntitle: [\n---\n"),
+        Err(PageError::PageInvalidFrontMatter { .. })
+    ));
+}
+
+#[test]
+fn page_editor_strict_mode() {
+    use wikijs::common::with_strict_mode;
+    let editor: PageEditor = serde_json::from_str("\"wysiwyg\"").unwrap();
+    assert_eq!(editor, PageEditor::Other("wysiwyg".to_string()));
+    let (result, known) = with_strict_mode(true, || {
+        (
+            serde_json::from_str::<PageEditor>("\"wysiwyg\""),
+            serde_json::from_str::<PageEditor>("\"markdown\""),
+        )
+    });
+    assert!(result.is_err());
+    assert_eq!(known.unwrap(), PageEditor::Markdown);
+}
+
+#[test]
+#[serial]
+fn page_bulk_delete_nonexistent() {
+    let results = API.page_bulk_delete(&[1000000, 1000001, 1000002], 2, None);
+    assert_eq!(results.len(), 3);
+    for (id, (result_id, result)) in
+        [1000000, 1000001, 1000002].iter().zip(results)
+    {
+        assert_eq!(*id, result_id);
+        assert_eq!(result.unwrap_err(), PageError::PageNotFound);
+    }
+}
+
+#[test]
+#[serial]
+fn page_batch_get_nonexistent() {
+    use wikijs::batch::{BatchRequest, BatchResponse};
+    assert!(API.batch_send(BatchRequest::new()).is_empty());
+    let request = BatchRequest::new()
+        .page_get(1000000)
+        .page_get_minimal(1000001);
+    let responses = API.batch_send(request);
+    assert_eq!(responses.len(), 2);
+    assert!(matches!(
+        responses[0],
+        BatchResponse::PageGet(Err(PageError::PageNotFound))
+    ));
+    assert!(matches!(
+        responses[1],
+        BatchResponse::PageGetMinimal(Err(PageError::PageNotFound))
+    ));
+}
+
+#[test]
+#[serial]
+fn page_batch_tree_get() {
+    use wikijs::batch::{BatchRequest, BatchResponse};
+    let tree = API
+        .page_tree_get(0, PageTreeMode::ALL, false, "en".to_string())
+        .unwrap();
+    let request = BatchRequest::new().page_tree_get(
+        0,
+        PageTreeMode::ALL,
+        false,
+        "en".to_string(),
+    );
+    let responses = API.batch_send(request);
+    assert_eq!(responses.len(), 1);
+    match &responses[0] {
+        BatchResponse::PageTreeGet(Ok(items)) => {
+            assert_eq!(items.len(), tree.len());
+        }
+        response => panic!("unexpected response {:?}", response),
+    }
+}
+
+#[test]
+#[serial]
+fn page_get_many_nonexistent() {
+    assert!(API.page_get_many(&[]).is_empty());
+    let pages = API.page_get_many(&[1000000, 1000001, 1000000]);
+    assert_eq!(pages.len(), 2);
+    assert!(matches!(pages[&1000000], Err(PageError::PageNotFound)));
+    assert!(matches!(pages[&1000001], Err(PageError::PageNotFound)));
+}
+
+#[test]
+#[serial]
+fn page_search_replace_dry_run() {
+    use wikijs::replace::{PageFilter, ReplacePattern};
+    let result = API.page_create(
+        "foo bar foo".to_string(),
+        "".to_string(),
+        PageEditor::Markdown,
+        true,
+        false,
+        "en".to_string(),
+        "replace-test".to_string(),
+        None,
+        None,
+        None,
+        None,
+        vec![],
+        "Replace Test".to_string(),
+    );
+    assert!(result.is_ok());
+    let pattern = ReplacePattern::Text("foo".to_string());
+    let filter = PageFilter {
+        path_prefix: Some("replace-test".to_string()),
+        ..Default::default()
+    };
+    let result = API.page_search_replace(&pattern, "baz", &filter, true, None);
+    let page = API
+        .page_get_by_path("replace-test".to_string(), "en".to_string())
+        .unwrap();
+    assert!(API.page_delete(page.id).is_ok());
+    let replacements = result.unwrap();
+    assert_eq!(replacements.len(), 1);
+    assert_eq!(replacements[0].count, 2);
+    assert!(replacements[0].result.is_none());
+    assert_eq!(page.content, "foo bar foo");
+}
+
+#[test]
+#[serial]
+fn page_move_with_link_fixup() {
+    let create = |path: &str, content: &str| {
+        API.page_create(
+            content.to_string(),
+            "".to_string(),
+            PageEditor::Markdown,
+            true,
+            false,
+            "en".to_string(),
+            path.to_string(),
+            None,
+            None,
+            None,
+            None,
+            vec![],
+            path.to_string(),
+        )
+    };
+    assert!(create("fixup-target", "target").is_ok());
+    assert!(create(
+        "fixup-source",
+        "[a](/fixup-target) [b](/en/fixup-target#x)"
+    )
+    .is_ok());
+    let get = |path: &str| {
+        API.page_get_by_path(path.to_string(), "en".to_string())
+            .unwrap()
+    };
+    let target = get("fixup-target");
+    let source = get("fixup-source");
+    let result = API.page_move_with_link_fixup(
+        target.id,
+        "fixup-moved".to_string(),
+        None,
+    );
+    let content = API.page_get(source.id).unwrap().content;
+    assert!(API.page_delete(source.id).is_ok());
+    assert!(API.page_delete(target.id).is_ok());
+    let replacements = result.unwrap();
+    assert_eq!(replacements.len(), 1);
+    assert_eq!(replacements[0].id, source.id);
+    assert_eq!(content, "[a](/fixup-moved) [b](/en/fixup-moved#x)");
+}
+
+#[test]
+#[serial]
+fn page_tag_rename_nonexistent() {
+    let result = API.page_tag_rename("qwerty", "asdfgh", None);
+    assert_eq!(result.unwrap_err(), PageError::PageTagNotFound);
+}
+
+#[test]
+#[serial]
+fn page_tags_add_remove() {
+    use wikijs::replace::PageFilter;
+    let result = API.page_create(
+        "tags".to_string(),
+        "".to_string(),
+        PageEditor::Markdown,
+        true,
+        false,
+        "en".to_string(),
+        "tags-test".to_string(),
+        None,
+        None,
+        None,
+        None,
+        vec![Some("keep".to_string())],
+        "Tags Test".to_string(),
+    );
+    assert!(result.is_ok());
+    let filter = PageFilter {
+        path_prefix: Some("tags-test".to_string()),
+        ..Default::default()
+    };
+    let tags = |id| {
+        let mut tags: Vec<String> = API
+            .page_get(id)
+            .unwrap()
+            .tags
+            .into_iter()
+            .flatten()
+            .map(|tag| tag.tag)
+            .collect();
+        tags.sort();
+        tags
+    };
+    let added = API.page_tags_add(&filter, &["deprecated".to_string()], None);
+    let id = API
+        .page_get_by_path("tags-test".to_string(), "en".to_string())
+        .unwrap()
+        .id;
+    let after_add = tags(id);
+    let removed = API.page_tags_remove(&filter, &["keep".to_string()], None);
+    let after_remove = tags(id);
+    assert!(API.page_delete(id).is_ok());
+    let added = added.unwrap();
+    assert_eq!(added.len(), 1);
+    assert_eq!(added[0], (id, Ok(())));
+    assert_eq!(after_add, vec!["deprecated", "keep"]);
+    assert!(removed.unwrap().iter().all(|(_, result)| result.is_ok()));
+    assert_eq!(after_remove, vec!["deprecated"]);
+}
+
+#[test]
+#[serial]
+fn page_publish_at_invalid_range() {
+    let start = "2024-06-01T08:00:00Z".parse().unwrap();
+    let end = "2024-05-01T08:00:00Z".parse().unwrap();
+    let result = API.page_publish_at(1, Some(start), Some(end));
+    assert_eq!(result.unwrap_err(), PageError::PageInvalidPublishRange);
+}
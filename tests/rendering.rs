@@ -0,0 +1,22 @@
+use wikijs::rendering::{KrokiConfig, PlantumlConfig, RendererConfig};
+
+#[test]
+fn renderer_configs() {
+    let input = KrokiConfig::new()
+        .server("https://kroki.example.com")
+        .to_renderer_input();
+    assert_eq!(input.key, "markdownKroki");
+    assert!(input.is_enabled);
+    let server = input
+        .config
+        .iter()
+        .flatten()
+        .flatten()
+        .find(|p| p.key == "server")
+        .unwrap();
+    assert_eq!(server.value, r#"{"v":"https://kroki.example.com"}"#);
+
+    let config = PlantumlConfig::default().image_format("png");
+    assert_eq!(config.key(), "markdownPlantuml");
+    assert!(config.options().contains(&("imageFormat", "png".into())));
+}
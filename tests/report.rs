@@ -0,0 +1,21 @@
+mod common;
+use common::API;
+
+use serial_test::serial;
+use wikijs::report::ReportOptions;
+
+#[test]
+#[serial]
+fn wiki_report_limit() {
+    let options = ReportOptions {
+        since: None,
+        limit: 1,
+    };
+    let report = API.wiki_report(&options, None).unwrap();
+    assert_eq!(
+        report.pages_per_locale.values().sum::<usize>(),
+        report.total_pages
+    );
+    assert!(report.recently_updated.len() <= 1);
+    assert!(report.most_active_authors.len() <= 1);
+}
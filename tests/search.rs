@@ -0,0 +1,26 @@
+use wikijs::search::{
+    AlgoliaConfig, ElasticsearchConfig, PostgresSearchConfig,
+    SearchEngineConfig,
+};
+
+#[test]
+fn search_engine_configs() {
+    let config = ElasticsearchConfig::new("http://elastic:9200")
+        .index_name("docs")
+        .sniff(true, 30);
+    assert_eq!(config.key(), "elasticsearch");
+    let pairs = config.to_key_value_pairs();
+    let value =
+        |key: &str| pairs.iter().find(|p| p.key == key).unwrap().value.clone();
+    assert_eq!(value("indexName"), r#"{"v":"docs"}"#);
+    assert_eq!(value("sniffInterval"), r#"{"v":30}"#);
+
+    let input = AlgoliaConfig::new("app", "key").to_engine_input();
+    assert_eq!(input.key, "algolia");
+    assert!(input.is_enabled);
+
+    assert_eq!(
+        PostgresSearchConfig::default().options(),
+        vec![("dictLanguage", "english".into())]
+    );
+}
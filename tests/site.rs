@@ -0,0 +1,30 @@
+use wikijs::site::{SiteConfigUpdateBuilder, SiteError};
+
+#[test]
+fn site_config_update_builder_set() {
+    let by_name = SiteConfigUpdateBuilder::new()
+        .set("feature_page_comments", "true")
+        .unwrap()
+        .set("title", "Docs")
+        .unwrap();
+    let typed = SiteConfigUpdateBuilder::new()
+        .feature_page_comments(true)
+        .title("Docs");
+    assert_eq!(by_name, typed);
+    assert!(SiteConfigUpdateBuilder::new()
+        .set("robots", "index, follow")
+        .is_ok());
+    assert_eq!(
+        SiteConfigUpdateBuilder::new().set("uploadMaxFiles", "many"),
+        Err(SiteError::InvalidValue {
+            field: "uploadMaxFiles".to_string(),
+            value: "many".to_string(),
+        })
+    );
+    assert_eq!(
+        SiteConfigUpdateBuilder::new().set("qwerty", "1"),
+        Err(SiteError::UnknownField {
+            field: "qwerty".to_string(),
+        })
+    );
+}
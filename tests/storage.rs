@@ -0,0 +1,71 @@
+use wikijs::common::KeyValuePair;
+use wikijs::storage::{
+    GitStorageConfig, S3StorageConfig, StorageConfig, StorageTarget,
+};
+
+#[test]
+fn storage_git_config_key_value_pairs() {
+    let config = GitStorageConfig::new("https://example.com/wiki.git")
+        .branch("main")
+        .basic_auth("bot", "secret");
+    assert_eq!(config.key(), "git");
+    let pairs = config.to_key_value_pairs();
+    let value =
+        |key: &str| pairs.iter().find(|p| p.key == key).unwrap().value.clone();
+    assert_eq!(value("branch"), r#"{"v":"main"}"#);
+    assert_eq!(value("authType"), r#"{"v":"basic"}"#);
+    assert_eq!(value("verifySSL"), r#"{"v":true}"#);
+}
+
+#[test]
+fn storage_s3_config_generic_endpoint() {
+    let config = S3StorageConfig::new("wiki", "id", "secret");
+    assert_eq!(config.key(), "s3");
+    let config = config.endpoint("https://minio.example.com");
+    assert_eq!(config.key(), "s3generic");
+    let target = config.to_target_input("push");
+    assert_eq!(target.key, "s3generic");
+    assert_eq!(target.mode, "push");
+}
+
+#[test]
+fn storage_target_to_input_keeps_config() {
+    let target = StorageTarget {
+        is_available: true,
+        is_enabled: true,
+        key: "disk".to_string(),
+        title: "Local File System".to_string(),
+        description: None,
+        logo: None,
+        website: None,
+        supported_modes: None,
+        mode: Some("push".to_string()),
+        has_schedule: true,
+        sync_interval: Some("P1D".to_string()),
+        sync_interval_default: None,
+        config: Some(vec![Some(KeyValuePair {
+            key: "path".to_string(),
+            value: r#"{"type":"String","value":"/backup"}"#.to_string(),
+        })]),
+        actions: None,
+    };
+    let mut input = target.to_input();
+    let config = |input: &wikijs::storage::StorageTargetInput| {
+        input
+            .config
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|p| (p.key.clone(), p.value.clone()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        config(&input),
+        vec![("path".to_string(), r#"{"v":"/backup"}"#.to_string())]
+    );
+    input.set_config("path", "/srv".into());
+    assert_eq!(
+        config(&input),
+        vec![("path".to_string(), r#"{"v":"/srv"}"#.to_string())]
+    );
+}
@@ -0,0 +1,19 @@
+use std::collections::HashMap;
+use wikijs::template::{template_render, TemplateError};
+
+#[test]
+fn template_render_vars() {
+    let vars = HashMap::from([
+        ("date".to_string(), "2024-05-01".to_string()),
+        ("team".to_string(), "Infra".to_string()),
+    ]);
+    assert_eq!(
+        template_render("# {{team}} meeting {{ date }}\n", &vars).unwrap(),
+        "# Infra meeting 2024-05-01\n"
+    );
+    assert_eq!(template_render("no vars", &vars).unwrap(), "no vars");
+    assert_eq!(
+        template_render("{{date}} {{author}}", &vars),
+        Err(TemplateError::MissingVariable("author".to_string()))
+    );
+}
@@ -0,0 +1,68 @@
+use wikijs::user::{
+    parse_user_csv, user_is_inactive, UserError, UserImportDefaults,
+    UserMinimal,
+};
+
+#[test]
+fn user_csv_parse() {
+    let csv = "Email,Name,Password,Provider,Groups\n\
+               alice@example.com,\"Doe, Alice\",secret,,3;4\n\
+               bob@example.com,Bob,,ldap,\n\
+               \n\
+               carol@example.com,Carol,,,\n\
+               ALICE@example.com,Alice again,secret,,\n\
+               dave,Dave,secret,,x\n";
+    let defaults = UserImportDefaults {
+        groups: vec![3],
+        ..Default::default()
+    };
+    let rows = parse_user_csv(csv, &defaults).unwrap();
+    assert_eq!(rows.len(), 5);
+
+    let alice = rows[0].as_ref().unwrap();
+    assert_eq!(alice.line, 2);
+    assert_eq!(alice.name, "Doe, Alice");
+    assert_eq!(alice.password.as_deref(), Some("secret"));
+    assert_eq!(alice.provider_key, "local");
+    assert_eq!(alice.groups, vec![3, 4]);
+
+    let bob = rows[1].as_ref().unwrap();
+    assert_eq!(bob.provider_key, "ldap");
+    assert_eq!(bob.password, None);
+
+    let line = |row: &Result<_, UserError>| match row {
+        Err(UserError::InvalidImportRow { line, .. }) => *line,
+        _ => 0,
+    };
+    assert_eq!(line(&rows[2]), 5);
+    assert_eq!(line(&rows[3]), 6);
+    assert_eq!(line(&rows[4]), 7);
+}
+
+#[test]
+fn user_csv_missing_columns() {
+    assert!(parse_user_csv("mail,name\n", &Default::default()).is_err());
+    assert!(parse_user_csv("", &Default::default()).is_err());
+}
+
+#[test]
+fn user_inactive() {
+    let since = "2024-06-01T00:00:00Z".parse().unwrap();
+    let mut user = UserMinimal {
+        id: 2,
+        name: "Alice".to_string(),
+        email: "alice@example.com".to_string(),
+        provider_key: "local".to_string(),
+        is_system: false,
+        is_active: true,
+        created_at: "2024-01-01T00:00:00Z".to_string(),
+        last_login_at: None,
+    };
+    assert!(user_is_inactive(&user, since));
+    user.last_login_at = Some("2024-07-01T12:00:00.000Z".to_string());
+    assert!(!user_is_inactive(&user, since));
+    user.last_login_at = Some("2024-05-01T12:00:00.000Z".to_string());
+    assert!(user_is_inactive(&user, since));
+    user.is_active = false;
+    assert!(!user_is_inactive(&user, since));
+}
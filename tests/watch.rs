@@ -0,0 +1,101 @@
+use std::time::{Duration, Instant};
+use wikijs::comment::Comment;
+use wikijs::page::PageListItem;
+use wikijs::watch::{EventDebouncer, PageChangeEvent};
+
+fn page(id: i64) -> PageListItem {
+    PageListItem {
+        id,
+        path: format!("page-{}", id),
+        locale: "en".to_string(),
+        title: None,
+        description: None,
+        content_type: "markdown".to_string(),
+        is_published: true,
+        is_private: false,
+        private_ns: None,
+        created_at: "2024-01-01T00:00:00Z".to_string(),
+        updated_at: "2024-01-01T00:00:00Z".to_string(),
+        tags: None,
+    }
+}
+
+fn comment(id: i64) -> Comment {
+    Comment {
+        id,
+        content: "comment".to_string(),
+        render: "<p>comment</p>".to_string(),
+        author_id: 1,
+        author_name: "Administrator".to_string(),
+        author_email: "admin@example.com".to_string(),
+        author_ip: "127.0.0.1".to_string(),
+        created_at: "2024-01-01T00:00:00Z".to_string(),
+        updated_at: "2024-01-01T00:00:00Z".to_string(),
+    }
+}
+
+fn merged_kind(
+    older: PageChangeEvent,
+    newer: PageChangeEvent,
+) -> Option<&'static str> {
+    older.merge(newer).map(|event| event.kind())
+}
+
+#[test]
+fn page_change_event_merge() {
+    use PageChangeEvent::*;
+    assert_eq!(
+        merged_kind(PageCreated(page(1)), PageDeleted(page(1))),
+        None
+    );
+    assert_eq!(
+        merged_kind(PageCreated(page(1)), PageUpdated(page(1))),
+        Some("page_created")
+    );
+    assert_eq!(
+        merged_kind(PageDeleted(page(1)), PageCreated(page(1))),
+        Some("page_updated")
+    );
+    assert_eq!(
+        merged_kind(PageUpdated(page(1)), PageDeleted(page(1))),
+        Some("page_deleted")
+    );
+    let created = CommentCreated {
+        page: page(1),
+        comment: comment(2),
+    };
+    let updated = CommentUpdated {
+        page: page(1),
+        comment: comment(2),
+    };
+    assert_eq!(merged_kind(created, updated), Some("comment_created"));
+}
+
+#[test]
+fn event_debouncer_release() {
+    let start = Instant::now();
+    let second = Duration::from_secs(1);
+    let mut debouncer = EventDebouncer::new(2 * second);
+    debouncer.push(PageChangeEvent::PageCreated(page(1)), start);
+    debouncer.push(PageChangeEvent::PageUpdated(page(2)), start);
+    debouncer.push(PageChangeEvent::PageUpdated(page(1)), start + second);
+    debouncer.push(PageChangeEvent::PageDeleted(page(2)), start + second);
+
+    assert!(debouncer.release(start + second).is_empty());
+    let released = debouncer.release(start + 3 * second);
+    let released: Vec<_> = released
+        .iter()
+        .map(|event| (event.kind(), event.page().id))
+        .collect();
+    assert_eq!(released, vec![("page_created", 1), ("page_deleted", 2)]);
+    assert!(debouncer.release(start + 10 * second).is_empty());
+}
+
+#[test]
+fn event_debouncer_cancel_out() {
+    let now = Instant::now();
+    let mut debouncer = EventDebouncer::new(Duration::ZERO);
+    debouncer.push(PageChangeEvent::PageCreated(page(1)), now);
+    debouncer.push(PageChangeEvent::PageDeleted(page(1)), now);
+    assert!(debouncer.release(now).is_empty());
+}